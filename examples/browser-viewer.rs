@@ -0,0 +1,51 @@
+//! Shows the content of a table uploaded by a user in the browser
+//!
+//! Built for the browser with:
+//!
+//! ```text
+//! cargo build --example browser-viewer --target wasm32-unknown-unknown --release
+//! wasm-bindgen --target web --out-dir pkg \
+//!     target/wasm32-unknown-unknown/release/examples/browser-viewer.wasm
+//! ```
+//!
+//! and used from JavaScript like this:
+//!
+//! ```text
+//! import init, { describe_table } from "./pkg/browser-viewer.js";
+//! await init();
+//! input.onchange = async () => {
+//!     const bytes = new Uint8Array(await input.files[0].arrayBuffer());
+//!     output.textContent = describe_table(bytes);
+//! };
+//! ```
+//!
+//! On other targets, it shows the content of the file given as first argument.
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::prelude::*;
+
+fn describe(bytes: &[u8]) -> Result<String, dbase::Error> {
+    let mut reader = dbase::Reader::from_bytes(bytes)?;
+    let fields = reader.fields().to_vec();
+    let records = reader.read()?;
+    Ok(format!(
+        "{} records\n{}",
+        records.len(),
+        dbase::RecordTable::new(&fields, &records)
+    ))
+}
+
+/// Returns the records of the table as a text table
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn describe_table(bytes: &[u8]) -> Result<String, JsValue> {
+    describe(bytes).map_err(|error| JsValue::from_str(&error.to_string()))
+}
+
+fn main() {
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        let dbf_path = std::env::args().nth(1).expect("Path to file as first arg");
+        let bytes = std::fs::read(dbf_path).unwrap();
+        println!("{}", describe(&bytes).unwrap());
+    }
+}
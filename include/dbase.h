@@ -0,0 +1,57 @@
+/*
+ * C API of the dbase crate, built with:
+ *
+ *     cargo rustc --release --features ffi --crate-type cdylib
+ *
+ * Functions that fail return NULL or a negative value,
+ * the message of the error is then returned by dbase_last_error.
+ *
+ * Strings are UTF-8 and NUL terminated, the strings returned are owned
+ * by the reader: they stay valid until the next call with the same reader.
+ */
+#ifndef DBASE_H
+#define DBASE_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef struct DbaseReader DbaseReader;
+
+/* Message of the last error of the current thread, or NULL */
+const char *dbase_last_error(void);
+
+/* Opens the file, returns NULL on error. Free with dbase_reader_free */
+DbaseReader *dbase_reader_open(const char *path);
+void dbase_reader_free(DbaseReader *reader);
+
+/* Schema */
+uint32_t dbase_reader_num_records(const DbaseReader *reader);
+size_t dbase_reader_num_fields(const DbaseReader *reader);
+const char *dbase_reader_field_name(const DbaseReader *reader, size_t index);
+/* Type as written in the file: 'C', 'N', 'F', 'L', 'D', 'M', 'I', 'T', 'B', 'Y', 0 on error */
+char dbase_reader_field_type(const DbaseReader *reader, size_t index);
+int dbase_reader_field_length(const DbaseReader *reader, size_t index);
+int dbase_reader_field_decimals(const DbaseReader *reader, size_t index);
+
+/* Reads the next record: 1 if read, 0 at the end, -1 on error */
+int dbase_reader_next_record(DbaseReader *reader);
+
+/* Accessors of the current record */
+int dbase_record_is_deleted(const DbaseReader *reader);
+int dbase_field_is_null(const DbaseReader *reader, size_t index);
+/* NULL if the value is empty */
+const char *dbase_field_as_string(DbaseReader *reader, size_t index);
+/* 0 on success, 1 if the value is empty, -1 on error */
+int dbase_field_as_double(const DbaseReader *reader, size_t index, double *value);
+int dbase_field_as_bool(const DbaseReader *reader, size_t index, int *value);
+int dbase_field_as_date(const DbaseReader *reader, size_t index, int *year, int *month, int *day);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* DBASE_H */
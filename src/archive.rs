@@ -0,0 +1,159 @@
+//! Module with the definition of fn's to read dBase files stored in zip archives
+//!
+//! Shapefiles in particular are usually distributed as zip archives.
+use std::io::{Cursor, Read, Seek};
+use std::path::Path;
+
+use zip::ZipArchive;
+
+use crate::error::{Error, ErrorKind};
+use crate::reading::{MissingMemoPolicy, Reader, ReadingOptions};
+use crate::record::field::{FieldType, MemoFileType};
+
+fn zip_error(error: zip::result::ZipError) -> Error {
+    Error::io_error(std::io::Error::from(error), 0)
+}
+
+/// Finds the entry of the archive with the given path, the comparison
+/// ignores the case as archives are often created on case insensitive systems
+fn find_entry<R: Read + Seek>(archive: &ZipArchive<R>, entry_path: &str) -> Option<String> {
+    archive
+        .file_names()
+        .find(|name| name.eq_ignore_ascii_case(entry_path))
+        .map(str::to_owned)
+}
+
+fn read_entry<R: Read + Seek>(
+    archive: &mut ZipArchive<R>,
+    entry_path: &str,
+) -> Result<Vec<u8>, Error> {
+    let mut entry = archive.by_name(entry_path).map_err(zip_error)?;
+    let mut content = Vec::<u8>::with_capacity(entry.size() as usize);
+    entry
+        .read_to_end(&mut content)
+        .map_err(|error| Error::io_error(error, 0))?;
+    Ok(content)
+}
+
+impl Reader<Cursor<Vec<u8>>> {
+    /// Creates a reader for the .dbf file at `entry_path` in the zip archive,
+    /// the memo file (if needed) is expected to be next to it in the archive.
+    ///
+    /// The files are decompressed in memory.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # fn main() -> Result<(), dbase::Error> {
+    /// let archive = std::fs::File::open("countries.zip").unwrap();
+    /// let mut reader = dbase::Reader::from_zip(archive, "countries/countries.dbf")?;
+    /// let records = reader.read()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_zip<R: Read + Seek>(archive: R, entry_path: &str) -> Result<Self, Error> {
+        Self::from_zip_with_options(archive, entry_path, ReadingOptions::default())
+    }
+
+    /// Same as [from_zip](#method.from_zip) but the content is read using the given options
+    pub fn from_zip_with_options<R: Read + Seek>(
+        archive: R,
+        entry_path: &str,
+        options: ReadingOptions,
+    ) -> Result<Self, Error> {
+        let mut archive = ZipArchive::new(archive).map_err(zip_error)?;
+        let dbf_path = find_entry(&archive, entry_path).unwrap_or_else(|| entry_path.to_owned());
+        let content = read_entry(&mut archive, &dbf_path)?;
+        let mut reader = Reader::with_options(Cursor::new(content), options)?;
+
+        let at_least_one_field_is_memo = reader
+            .fields_info
+            .iter()
+            .any(|f_info| f_info.field_type == FieldType::Memo);
+        if !at_least_one_field_is_memo {
+            return Ok(reader);
+        }
+        if let Some(memo_type) = reader.options.memo_file_type(&reader.header) {
+            let extension = match memo_type {
+                MemoFileType::DbaseMemo | MemoFileType::DbaseMemo4 => "dbt",
+                MemoFileType::FoxBaseMemo => "fpt",
+            };
+            let memo_path = Path::new(&dbf_path).with_extension(extension);
+            let memo_path = memo_path.to_string_lossy();
+            let memo_path = match find_entry(&archive, &memo_path) {
+                Some(memo_path) => memo_path,
+                None if reader.options.missing_memo_policy != MissingMemoPolicy::Fail => {
+                    return Ok(reader)
+                }
+                None => {
+                    return Err(Error {
+                        record_num: 0,
+                        field: None,
+                        kind: ErrorKind::MissingMemoFile,
+                    })
+                }
+            };
+            let memo_content = read_entry(&mut archive, &memo_path)?;
+            let memo_reader = reader
+                .options
+                .open_memo(memo_type, Cursor::new(memo_content))
+                .map_err(|error| Error::io_error(error, 0))?;
+            reader.memo_reader = Some(memo_reader);
+        }
+        Ok(reader)
+    }
+}
+
+/// Returns the paths of the .dbf files in the zip archive
+pub fn dbf_files_in_zip<R: Read + Seek>(archive: R) -> Result<Vec<String>, Error> {
+    let archive = ZipArchive::new(archive).map_err(zip_error)?;
+    Ok(archive
+        .file_names()
+        .filter(|name| {
+            Path::new(name)
+                .extension()
+                .is_some_and(|extension| extension.eq_ignore_ascii_case("dbf"))
+        })
+        .map(str::to_owned)
+        .collect())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::io::Write;
+
+    use zip::write::{SimpleFileOptions, ZipWriter};
+
+    fn archive_with_line_dbf() -> Cursor<Vec<u8>> {
+        let mut writer = ZipWriter::new(Cursor::new(Vec::<u8>::new()));
+        writer
+            .start_file("data/README.txt", SimpleFileOptions::default())
+            .unwrap();
+        writer.write_all(b"A line").unwrap();
+        writer
+            .start_file("data/LINE.DBF", SimpleFileOptions::default())
+            .unwrap();
+        writer
+            .write_all(&std::fs::read("tests/data/line.dbf").unwrap())
+            .unwrap();
+        let mut archive = writer.finish().unwrap();
+        archive.set_position(0);
+        archive
+    }
+
+    #[test]
+    fn read_from_zip() {
+        assert_eq!(
+            dbf_files_in_zip(archive_with_line_dbf()).unwrap(),
+            vec!["data/LINE.DBF".to_owned()]
+        );
+
+        let mut reader = Reader::from_zip(archive_with_line_dbf(), "data/line.dbf").unwrap();
+        let records = reader.read().unwrap();
+        assert_eq!(records, crate::read("tests/data/line.dbf").unwrap());
+
+        assert!(Reader::from_zip(archive_with_line_dbf(), "data/nope.dbf").is_err());
+    }
+}
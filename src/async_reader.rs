@@ -0,0 +1,437 @@
+//! Module with the definition of the reader of dBase files
+//! from [tokio](https://tokio.rs) asynchronous sources
+//!
+//! The header and the records are read asynchronously, each record is read
+//! in its entirety before its fields are decoded,
+//! so the values are the same as the ones of the [Reader](struct.Reader.html).
+use std::io::Cursor;
+use std::path::Path;
+
+use futures::stream::Stream;
+use tokio::fs::File;
+use tokio::io::{AsyncRead, AsyncReadExt, BufReader};
+
+use crate::error::{Error, ErrorKind};
+use crate::header::Header;
+use crate::reading::{
+    check_encryption, check_field_names, read_table_info, ErrorPolicy, FieldIterator,
+    ReadableRecord, ReadingOptions, Record,
+};
+use crate::record::field::{FieldType, MemoFileType, MemoReader};
+use crate::record::{FieldInfo, FieldNameIssue};
+
+/// Reads dBase files from an asynchronous source
+///
+/// The API mirrors the one of the [Reader](struct.Reader.html), with the difference
+/// that the records can only be read in order, as the source is not required to seek.
+///
+/// The memo file, when there is one, is read entirely in memory when
+/// it is given to the reader, as its content is accessed in no particular order.
+///
+/// # Example
+///
+/// ```
+/// # extern crate dbase;
+/// # extern crate tokio;
+/// # fn main() -> Result<(), dbase::Error> {
+/// # tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap().block_on(async {
+/// let mut reader = dbase::AsyncReader::from_path("tests/data/line.dbf").await?;
+/// while let Some(record) = reader.read_next_record().await {
+///     let record = record?;
+///     println!("{:?}", record);
+/// }
+/// # Ok(())
+/// # })
+/// # }
+/// ```
+pub struct AsyncReader<T: AsyncRead + Unpin> {
+    /// Where the data is read from
+    source: T,
+    memo_reader: Option<MemoReader<Cursor<Vec<u8>>>>,
+    header: Header,
+    fields_info: Vec<FieldInfo>,
+    backlink: Option<String>,
+    options: ReadingOptions,
+    field_name_issues: Vec<FieldNameIssue>,
+    /// Index of the record the source is positioned at
+    next_record: u32,
+    /// Bytes of the record being decoded
+    record_bytes: Cursor<Vec<u8>>,
+}
+
+impl<T: AsyncRead + Unpin> AsyncReader<T> {
+    /// Creates a new reader from the source.
+    ///
+    /// Reads the header and fields information as soon as its created.
+    pub async fn new(source: T) -> Result<Self, Error> {
+        Self::with_options(source, ReadingOptions::default()).await
+    }
+
+    /// Creates a new reader from the source, that reads the content
+    /// using the given options.
+    pub async fn with_options(mut source: T, options: ReadingOptions) -> Result<Self, Error> {
+        let mut table_info = vec![0u8; Header::SIZE];
+        source
+            .read_exact(&mut table_info)
+            .await
+            .map_err(|error| Error::io_error(error, 0))?;
+        let header = Header::read_from(&mut table_info.as_slice())
+            .map_err(|error| Error::io_error(error, 0))?;
+
+        // Everything up to the first record is read, the data that may follow
+        // the fields information is then skipped
+        table_info.resize(
+            (header.offset_to_first_record as usize).max(Header::SIZE),
+            0u8,
+        );
+        source
+            .read_exact(&mut table_info[Header::SIZE..])
+            .await
+            .map_err(|error| Error::io_error(error, 0))?;
+        let (header, mut fields_info, backlink) =
+            read_table_info(&mut table_info.as_slice(), options.flavor, &*options.encoding)?;
+        check_encryption(&header)?;
+        let field_name_issues = check_field_names(&mut fields_info, &options)?;
+
+        // Like the Reader, which reads the fields one after the other,
+        // the records are made of the fields regardless of the size in the header
+        let record_size = fields_info
+            .iter()
+            .map(|field_info| usize::from(field_info.field_length))
+            .sum();
+
+        Ok(Self {
+            source,
+            memo_reader: None,
+            record_bytes: Cursor::new(vec![0u8; record_size]),
+            header,
+            fields_info,
+            backlink,
+            options,
+            field_name_issues,
+            next_record: 0,
+        })
+    }
+
+    /// Reads the memo file (.dbt / .fpt) of the table from the given source,
+    /// it is read to its end.
+    ///
+    /// Does nothing if the version of the file does not support memo fields.
+    pub async fn with_memo<M: AsyncRead + Unpin>(
+        mut self,
+        mut memo_source: M,
+    ) -> Result<Self, Error> {
+        if let Some(memo_type) = self.options.memo_file_type(&self.header) {
+            let mut memo = Vec::<u8>::new();
+            memo_source
+                .read_to_end(&mut memo)
+                .await
+                .map_err(|error| Error {
+                    record_num: 0,
+                    field: None,
+                    kind: ErrorKind::ErrorOpeningMemoFile(error),
+                })?;
+            let memo_reader = self
+                .options
+                .open_memo(memo_type, Cursor::new(memo))
+                .map_err(|error| Error::io_error(error, 0))?;
+            self.memo_reader = Some(memo_reader);
+        }
+        Ok(self)
+    }
+
+    /// Returns the header of the file
+    pub fn header(&self) -> &Header {
+        &self.header
+    }
+
+    /// Returns the path of the database container (.dbc) the table belongs to,
+    /// relative to the table.
+    ///
+    /// Only Visual FoxPro tables can belong to a database.
+    pub fn backlink(&self) -> Option<&str> {
+        self.backlink.as_deref()
+    }
+
+    /// Returns the problems with the names of the fields, see
+    /// [Reader::field_name_issues](struct.Reader.html#method.field_name_issues)
+    pub fn field_name_issues(&self) -> &[FieldNameIssue] {
+        &self.field_name_issues
+    }
+
+    /// Returns the information about fields present in the records
+    pub fn fields(&self) -> &[FieldInfo] {
+        &self.fields_info
+    }
+
+    /// Returns the index (0-based) of the record that will be read next
+    pub fn next_record_index(&self) -> usize {
+        self.next_record as usize
+    }
+
+    /// Reads the bytes of the next record
+    async fn read_record_bytes(&mut self) -> Result<(), Error> {
+        let record_num = self.next_record as usize;
+        self.next_record += 1;
+        self.record_bytes.set_position(0);
+        self.source
+            .read_exact(self.record_bytes.get_mut())
+            .await
+            .map_err(|error| Error::io_error(error, record_num))?;
+        Ok(())
+    }
+
+    /// Reads the next record, following the error policy of the reader,
+    /// returns `None` once all the records were read.
+    pub async fn read_next_record_as<R: ReadableRecord>(&mut self) -> Option<Result<R, Error>> {
+        while self.next_record < self.header.num_records {
+            let record_num = self.next_record as usize;
+            if let Err(error) = self.read_record_bytes().await {
+                return Some(Err(error));
+            }
+            let mut source = Cursor::new(&self.record_bytes.get_ref()[..]);
+            let mut iter = FieldIterator {
+                source: &mut source,
+                fields_info: self.fields_info.iter().peekable(),
+                memo_reader: &mut self.memo_reader,
+                options: &self.options,
+            };
+            // The fields that are not read do not need to be skipped,
+            // the next record is read in the buffer from its start
+            match R::read_using(&mut iter) {
+                Err(_) if self.options.error_policy == ErrorPolicy::SkipRecord => {}
+                record => return Some(record.map_err(|error| Error::new(error, record_num))),
+            }
+        }
+        None
+    }
+
+    /// Reads the next [Record](struct.Record.html),
+    /// returns `None` once all the records were read.
+    pub async fn read_next_record(&mut self) -> Option<Result<Record, Error>> {
+        self.read_next_record_as::<Record>().await
+    }
+
+    /// Skips the next record, without decoding it
+    pub async fn skip_next_record(&mut self) -> Result<(), Error> {
+        if self.next_record < self.header.num_records {
+            self.read_record_bytes().await?;
+        }
+        Ok(())
+    }
+
+    /// Turns the reader into a [Stream](https://docs.rs/futures/0.3/futures/stream/trait.Stream.html)
+    /// of the remaining records, read using the given type
+    pub fn into_stream_as<R: ReadableRecord>(self) -> impl Stream<Item = Result<R, Error>> {
+        futures::stream::unfold(self, |mut reader| async move {
+            reader
+                .read_next_record_as::<R>()
+                .await
+                .map(|record| (record, reader))
+        })
+    }
+
+    /// Turns the reader into a [Stream](https://docs.rs/futures/0.3/futures/stream/trait.Stream.html)
+    /// of the remaining [Records](struct.Record.html)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # extern crate dbase;
+    /// # extern crate futures;
+    /// # extern crate tokio;
+    /// use futures::stream::{StreamExt, TryStreamExt};
+    /// # fn main() -> Result<(), dbase::Error> {
+    /// # tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap().block_on(async {
+    /// let reader = dbase::AsyncReader::from_path("tests/data/stations.dbf").await?;
+    /// let chunks = reader
+    ///     .into_stream()
+    ///     .try_chunks(4)
+    ///     .map_ok(|records| records.len())
+    ///     .try_collect::<Vec<usize>>()
+    ///     .await
+    ///     .map_err(|error| error.1)?;
+    /// assert_eq!(chunks, vec![4, 2]);
+    /// # Ok(())
+    /// # })
+    /// # }
+    /// ```
+    pub fn into_stream(self) -> impl Stream<Item = Result<Record, Error>> {
+        self.into_stream_as::<Record>()
+    }
+
+    /// Reads all the remaining records inside a `Vec`
+    pub async fn read_as<R: ReadableRecord>(&mut self) -> Result<Vec<R>, Error> {
+        let mut records = Vec::<R>::with_capacity(
+            (self.header.num_records - self.next_record.min(self.header.num_records)) as usize,
+        );
+        while let Some(record) = self.read_next_record_as::<R>().await {
+            records.push(record?);
+        }
+        Ok(records)
+    }
+
+    /// Reads all the remaining [Records](struct.Record.html) inside a `Vec`
+    pub async fn read(&mut self) -> Result<Vec<Record>, Error> {
+        self.read_as::<Record>().await
+    }
+}
+
+impl AsyncReader<BufReader<File>> {
+    /// Creates a new reader from a path, the memo file (if any) is read
+    /// from the path next to the file with the extension of the dBase version (.dbt / .fpt)
+    pub async fn from_path<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        Self::from_path_with_options(path, ReadingOptions::default()).await
+    }
+
+    /// Creates a new reader from a path, that reads the content
+    /// using the given options.
+    pub async fn from_path_with_options<P: AsRef<Path>>(
+        path: P,
+        options: ReadingOptions,
+    ) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let file = File::open(path)
+            .await
+            .map_err(|error| Error::io_error(error, 0))?;
+        let buffer_size = options.buffer_size;
+        let reader =
+            Self::with_options(BufReader::with_capacity(buffer_size, file), options).await?;
+        let at_least_one_field_is_memo = reader
+            .fields_info
+            .iter()
+            .any(|f_info| f_info.field_type == FieldType::Memo);
+        let memo_type = reader.options.memo_file_type(&reader.header);
+        match memo_type {
+            Some(memo_type) if at_least_one_field_is_memo => {
+                let memo_path = match memo_type {
+                    MemoFileType::DbaseMemo | MemoFileType::DbaseMemo4 => {
+                        path.with_extension("dbt")
+                    }
+                    MemoFileType::FoxBaseMemo => path.with_extension("fpt"),
+                };
+                let memo_file = match File::open(memo_path).await {
+                    Ok(memo_file) => memo_file,
+                    Err(error) if reader.options.tolerates_missing_memo(&error) => {
+                        return Ok(reader)
+                    }
+                    Err(error) => {
+                        return Err(Error {
+                            record_num: 0,
+                            field: None,
+                            kind: ErrorKind::ErrorOpeningMemoFile(error),
+                        })
+                    }
+                };
+                reader
+                    .with_memo(BufReader::with_capacity(buffer_size, memo_file))
+                    .await
+            }
+            _ => Ok(reader),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    extern crate tokio;
+
+    use super::*;
+
+    use crate::{FieldValue, Reader};
+
+    fn block_on<F: std::future::Future>(future: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap()
+            .block_on(future)
+    }
+
+    #[test]
+    fn read_same_records_as_reader() {
+        let bytes = std::fs::read("tests/data/stations.dbf").unwrap();
+        let expected = Reader::new(Cursor::new(bytes.clone()))
+            .unwrap()
+            .read()
+            .unwrap();
+
+        let records = block_on(async {
+            let mut reader = AsyncReader::new(bytes.as_slice()).await.unwrap();
+            assert_eq!(reader.fields().len(), 5);
+            reader.skip_next_record().await.unwrap();
+            reader.read().await.unwrap()
+        });
+        assert_eq!(records, expected[1..]);
+    }
+
+    #[test]
+    fn read_memo_fields() {
+        // dBase III table with a memo field, the value is in the block 1 of the memo
+        let mut table = vec![0x83, 120, 1, 1, 1, 0, 0, 0, 65, 0, 11, 0];
+        table.resize(Header::SIZE, 0);
+        table.extend_from_slice(b"NOTES\0\0\0\0\0\0M");
+        table.extend_from_slice(&[0, 0, 0, 0, 10, 0]);
+        table.resize(Header::SIZE + FieldInfo::SIZE, 0);
+        table.push(0x0D);
+        table.extend_from_slice(b"          1");
+        let mut memo = vec![2, 0, 0, 0];
+        memo.resize(512, 0);
+        memo.extend_from_slice(b"Ferris the crab\x1A\x1A");
+        memo.resize(1024, 0);
+
+        let records = block_on(async {
+            AsyncReader::new(table.as_slice())
+                .await
+                .unwrap()
+                .with_memo(memo.as_slice())
+                .await
+                .unwrap()
+                .read()
+                .await
+                .unwrap()
+        });
+        assert_eq!(
+            records[0].get("NOTES"),
+            Some(&FieldValue::Memo("Ferris the crab".to_owned()))
+        );
+    }
+
+    #[test]
+    fn stream_of_records() {
+        use futures::stream::StreamExt;
+
+        let bytes = std::fs::read("tests/data/stations.dbf").unwrap();
+        let expected = Reader::new(Cursor::new(bytes.clone()))
+            .unwrap()
+            .read()
+            .unwrap();
+        let records = block_on(async {
+            AsyncReader::new(bytes.as_slice())
+                .await
+                .unwrap()
+                .into_stream()
+                .skip(2)
+                .map(Result::unwrap)
+                .collect::<Vec<Record>>()
+                .await
+        });
+        assert_eq!(records, expected[2..]);
+    }
+
+    #[test]
+    fn truncated_source() {
+        let bytes = std::fs::read("tests/data/stations.dbf").unwrap();
+        let truncated = &bytes[..bytes.len() - 10];
+        let result = block_on(async {
+            let mut reader = AsyncReader::new(truncated).await.unwrap();
+            reader.read().await
+        });
+        assert!(matches!(
+            result,
+            Err(Error {
+                kind: ErrorKind::IoError(_),
+                ..
+            })
+        ));
+    }
+}
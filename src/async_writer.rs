@@ -0,0 +1,320 @@
+//! Module with the definition of the writer of dBase files
+//! to [tokio](https://tokio.rs) asynchronous destinations
+//!
+//! The records are written one at a time, the header is written first with
+//! no records and patched with the number of records written when the writer is finalized.
+use std::io::{Cursor, SeekFrom};
+use std::path::Path;
+use std::sync::Arc;
+
+use tokio::fs::File;
+use tokio::io::{AsyncSeek, AsyncSeekExt, AsyncWrite, AsyncWriteExt, BufWriter};
+
+use crate::encoding::Encoding;
+use crate::encryption::{encrypt_record, RecordCipher};
+use crate::error::Error;
+use crate::header::Header;
+use crate::record::FieldInfo;
+use crate::writing::{
+    check_format_limits, update_header, write_table_info, FieldDefaults, FieldWriter,
+    OverflowPolicy, TableWriterBuilder, WritableRecord, FILE_TERMINATOR,
+};
+
+/// Writes dBase records to an asynchronous destination
+///
+/// It is created with [build_with_async_dest](struct.TableWriterBuilder.html#method.build_with_async_dest),
+/// the destination must be able to seek to update the header once
+/// all the records are written by [finalize](#method.finalize).
+///
+/// # Example
+///
+/// ```
+/// # extern crate dbase;
+/// # extern crate tokio;
+/// use dbase::{FieldName, FieldValue, Record, TableWriterBuilder};
+/// use std::convert::TryFrom;
+/// use std::io::Cursor;
+///
+/// # fn main() -> Result<(), dbase::Error> {
+/// # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+/// let mut writer = TableWriterBuilder::new()
+///     .add_character_field(FieldName::try_from("NAME").unwrap(), 20)
+///     .build_with_async_dest(Cursor::new(Vec::<u8>::new()));
+///
+/// let mut record = Record::default();
+/// record.insert("NAME".to_owned(), FieldValue::Character(Some("Ferris".to_owned())));
+/// writer.write_record(&record).await?;
+/// let cursor = writer.finalize().await?;
+///
+/// let records = dbase::Reader::new(Cursor::new(cursor.into_inner()))?.read()?;
+/// assert_eq!(records, vec![record]);
+/// # Ok(())
+/// # })
+/// # }
+/// ```
+pub struct AsyncTableWriter<W: AsyncWrite + AsyncSeek + Unpin> {
+    dst: W,
+    fields_info: Vec<FieldInfo>,
+    header: Header,
+    backlink: Option<String>,
+    defaults: FieldDefaults,
+    max_file_size: u64,
+    overflow_policy: OverflowPolicy,
+    encoding: Arc<dyn Encoding>,
+    cipher: Option<Arc<dyn RecordCipher>>,
+    /// Position of the start of the file in the destination,
+    /// once the content before the records is written
+    start: Option<u64>,
+    num_records: usize,
+    /// Bytes of the record being written
+    record_buffer: Vec<u8>,
+}
+
+impl<W: AsyncWrite + AsyncSeek + Unpin> AsyncTableWriter<W> {
+    /// Writes the header, fields information and backlink, with no records
+    async fn start(&mut self) -> Result<(), Error> {
+        if self.start.is_some() {
+            return Ok(());
+        }
+        check_format_limits(&self.header, &self.fields_info, 0, self.max_file_size)?;
+        let start = self
+            .dst
+            .stream_position()
+            .await
+            .map_err(|error| Error::io_error(error, 0))?;
+        update_header(&mut self.header, &self.fields_info, 0);
+        let mut table_info = Vec::<u8>::new();
+        write_table_info(
+            &mut table_info,
+            &self.header,
+            &self.fields_info,
+            self.backlink.as_deref(),
+            &*self.encoding,
+        )
+        .map_err(|error| Error::io_error(error, 0))?;
+        self.dst
+            .write_all(&table_info)
+            .await
+            .map_err(|error| Error::io_error(error, 0))?;
+        self.start = Some(start);
+        Ok(())
+    }
+
+    /// Writes a record after the ones already written
+    ///
+    /// When the record cannot be written, nothing is written and
+    /// the following records can still be written.
+    pub async fn write_record<R: WritableRecord>(&mut self, record: &R) -> Result<(), Error> {
+        self.start().await?;
+        check_format_limits(
+            &self.header,
+            &self.fields_info,
+            self.num_records + 1,
+            self.max_file_size,
+        )?;
+        let mut field_writer = FieldWriter {
+            dst: &mut self.record_buffer,
+            fields_info: self.fields_info.iter().peekable(),
+            buffer: Cursor::new(vec![0u8; 255]),
+            defaults: Some(&self.defaults),
+            overflow_policy: self.overflow_policy,
+            encoding: &*self.encoding,
+        };
+        field_writer.write_record(record, self.num_records)?;
+        encrypt_record(
+            self.cipher.as_deref(),
+            self.num_records,
+            &mut self.record_buffer,
+        );
+        self.dst
+            .write_all(&self.record_buffer)
+            .await
+            .map_err(|error| Error::io_error(error, self.num_records))?;
+        self.num_records += 1;
+        Ok(())
+    }
+
+    /// Writes the records after the ones already written
+    pub async fn write_records<R: WritableRecord>(&mut self, records: &[R]) -> Result<(), Error> {
+        for record in records {
+            self.write_record(record).await?;
+        }
+        Ok(())
+    }
+
+    /// Returns the number of records written so far
+    pub fn num_records(&self) -> usize {
+        self.num_records
+    }
+
+    /// Ends the file and updates its header with the number of records written,
+    /// then returns the destination positioned at the end of the file.
+    pub async fn finalize(mut self) -> Result<W, Error> {
+        self.start().await?;
+        self.dst
+            .write_u8(FILE_TERMINATOR)
+            .await
+            .map_err(|error| Error::io_error(error, self.num_records))?;
+        let end = self
+            .dst
+            .stream_position()
+            .await
+            .map_err(|error| Error::io_error(error, self.num_records))?;
+
+        update_header(&mut self.header, &self.fields_info, self.num_records);
+        let mut header = Vec::<u8>::with_capacity(Header::SIZE);
+        self.header
+            .write_to(&mut header)
+            .map_err(|error| Error::io_error(error, self.num_records))?;
+        self.dst
+            .seek(SeekFrom::Start(self.start.unwrap_or(0)))
+            .await
+            .map_err(|error| Error::io_error(error, self.num_records))?;
+        self.dst
+            .write_all(&header)
+            .await
+            .map_err(|error| Error::io_error(error, self.num_records))?;
+        self.dst
+            .seek(SeekFrom::Start(end))
+            .await
+            .map_err(|error| Error::io_error(error, self.num_records))?;
+        self.dst
+            .flush()
+            .await
+            .map_err(|error| Error::io_error(error, self.num_records))?;
+        Ok(self.dst)
+    }
+}
+
+impl TableWriterBuilder {
+    /// Builds a writer that writes the records to the asynchronous destination
+    pub fn build_with_async_dest<W: AsyncWrite + AsyncSeek + Unpin>(
+        self,
+        dst: W,
+    ) -> AsyncTableWriter<W> {
+        AsyncTableWriter {
+            dst,
+            fields_info: self.v,
+            header: self.hdr,
+            backlink: self.backlink,
+            defaults: self.defaults,
+            max_file_size: self.max_file_size,
+            overflow_policy: self.overflow_policy,
+            encoding: self.encoding,
+            cipher: self.cipher,
+            start: None,
+            num_records: 0,
+            record_buffer: vec![],
+        }
+    }
+
+    /// Helper function to create a file at the given path
+    /// and make the asynchronous writer write to it.
+    ///
+    /// This function wraps the `File` in a `BufWriter` to increase performance.
+    pub async fn build_with_async_file_dest<P: AsRef<Path>>(
+        self,
+        path: P,
+    ) -> std::io::Result<AsyncTableWriter<BufWriter<File>>> {
+        let dst = BufWriter::new(File::create(path).await?);
+        Ok(self.build_with_async_dest(dst))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    extern crate tokio;
+
+    use super::*;
+
+    use std::convert::TryFrom;
+
+    use crate::{ErrorKind, FieldName, FieldValue, Reader, Record};
+
+    fn block_on<F: std::future::Future>(future: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap()
+            .block_on(future)
+    }
+
+    fn builder() -> TableWriterBuilder {
+        TableWriterBuilder::new()
+            .add_character_field(FieldName::try_from("NAME").unwrap(), 20)
+            .add_numeric_field(FieldName::try_from("PRICE").unwrap(), 8, 2)
+    }
+
+    fn records() -> Vec<Record> {
+        [(Some("Ferris"), Some(1.5)), (None, None)]
+            .iter()
+            .map(|(name, price)| {
+                let mut record = Record::default();
+                record.insert(
+                    "NAME".to_owned(),
+                    FieldValue::Character(name.map(str::to_owned)),
+                );
+                record.insert("PRICE".to_owned(), FieldValue::Numeric(*price));
+                record
+            })
+            .collect()
+    }
+
+    #[test]
+    fn same_content_as_table_writer() {
+        let expected = builder()
+            .build_with_dest(Cursor::new(Vec::<u8>::new()))
+            .write(&records())
+            .unwrap()
+            .into_inner();
+
+        let content = block_on(async {
+            let mut writer = builder().build_with_async_dest(Cursor::new(Vec::<u8>::new()));
+            writer.write_records(&records()).await.unwrap();
+            assert_eq!(writer.num_records(), 2);
+            writer.finalize().await.unwrap().into_inner()
+        });
+        assert_eq!(content, expected);
+        assert_eq!(
+            Reader::new(Cursor::new(content)).unwrap().read().unwrap(),
+            records()
+        );
+    }
+
+    #[test]
+    fn empty_table() {
+        let expected = builder()
+            .build_with_dest(Cursor::new(Vec::<u8>::new()))
+            .write::<Record>(&[])
+            .unwrap()
+            .into_inner();
+        let content = block_on(async {
+            builder()
+                .build_with_async_dest(Cursor::new(Vec::<u8>::new()))
+                .finalize()
+                .await
+                .unwrap()
+                .into_inner()
+        });
+        assert_eq!(content, expected);
+    }
+
+    #[test]
+    fn record_that_cannot_be_written() {
+        let mut record = Record::default();
+        record.insert(
+            "NAME".to_owned(),
+            FieldValue::Character(Some("Ferris".to_owned())),
+        );
+        let content = block_on(async {
+            let mut writer = builder().build_with_async_dest(Cursor::new(Vec::<u8>::new()));
+            let error = writer.write_record(&record).await.unwrap_err();
+            assert!(matches!(error.kind(), ErrorKind::Message(_)));
+            writer.write_records(&records()).await.unwrap();
+            writer.finalize().await.unwrap().into_inner()
+        });
+        assert_eq!(
+            Reader::new(Cursor::new(content)).unwrap().read().unwrap(),
+            records()
+        );
+    }
+}
@@ -0,0 +1,265 @@
+//! Module with the definition of fn's to export dBase files as [Avro](https://avro.apache.org)
+//! object container files
+//!
+//! The fields are mapped to Avro types as follows:
+//!
+//! | dBase type                       | Avro type                                      |
+//! |----------------------------------|------------------------------------------------|
+//! | Character, Memo                  | string                                         |
+//! | Numeric, Float, Double, Currency | double                                         |
+//! | Integer                          | int                                            |
+//! | Logical                          | boolean                                        |
+//! | Date                             | int, with the `date` logical type              |
+//! | DateTime                         | long, with the `timestamp-millis` logical type |
+//!
+//! The types of the fields that may be empty (Character, Numeric, Float, Logical and Date)
+//! are unions of `null` and the type.
+//!
+//! Avro names may only contain ASCII letters, digits and underscores, other characters
+//! of the field names are replaced by an underscore.
+use std::io::{Read, Seek, Write};
+
+use apache_avro::types::Value;
+use apache_avro::Schema;
+
+use crate::error::{Error, ErrorKind};
+use crate::reading::{Reader, RecordValues};
+use crate::record::field::{FieldType, FieldValue};
+use crate::record::FieldInfo;
+
+fn avro_error(error: apache_avro::Error, record_num: usize) -> Error {
+    Error {
+        record_num,
+        field: None,
+        kind: ErrorKind::Message(error.to_string()),
+    }
+}
+
+/// Returns the name with the characters that are not valid in Avro names replaced
+fn avro_name(name: &str) -> String {
+    let mut avro_name = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect::<String>();
+    if !avro_name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+        avro_name.insert(0, '_');
+    }
+    avro_name
+}
+
+fn avro_type(field_info: &FieldInfo) -> &'static str {
+    match field_info.field_type {
+        FieldType::Character => "[\"null\", \"string\"]",
+        FieldType::Memo => "\"string\"",
+        FieldType::Numeric | FieldType::Float => "[\"null\", \"double\"]",
+        FieldType::Double | FieldType::Currency => "\"double\"",
+        FieldType::Integer | FieldType::Autoincrement => "\"int\"",
+        FieldType::Long => "[\"null\", \"int\"]",
+        FieldType::DBase7Double => "[\"null\", \"double\"]",
+        FieldType::Logical => "[\"null\", \"boolean\"]",
+        FieldType::Date => "[\"null\", {\"type\": \"int\", \"logicalType\": \"date\"}]",
+        FieldType::DateTime => "{\"type\": \"long\", \"logicalType\": \"timestamp-millis\"}",
+        FieldType::Timestamp => {
+            "[\"null\", {\"type\": \"long\", \"logicalType\": \"timestamp-millis\"}]"
+        }
+    }
+}
+
+/// Returns the Avro schema of the records of a table with the given fields,
+/// `record_name` is the name of the Avro record.
+///
+/// Fails if the names, once made valid, are not unique.
+pub fn avro_schema(fields_info: &[FieldInfo], record_name: &str) -> Result<Schema, Error> {
+    let fields = fields_info
+        .iter()
+        .filter(|field_info| !field_info.is_deletion_flag())
+        .map(|field_info| {
+            format!(
+                "{{\"name\": \"{}\", \"type\": {}}}",
+                avro_name(field_info.name()),
+                avro_type(field_info)
+            )
+        })
+        .collect::<Vec<String>>();
+    let schema = format!(
+        "{{\"type\": \"record\", \"name\": \"{}\", \"fields\": [{}]}}",
+        avro_name(record_name),
+        fields.join(", ")
+    );
+    Schema::parse_str(&schema).map_err(|error| avro_error(error, 0))
+}
+
+fn nullable(value: Option<Value>) -> Value {
+    match value {
+        Some(value) => Value::Union(1, Box::new(value)),
+        None => Value::Union(0, Box::new(Value::Null)),
+    }
+}
+
+fn avro_value(value: FieldValue) -> Value {
+    match value {
+        FieldValue::Character(value) => nullable(value.map(Value::String)),
+        FieldValue::Memo(value) => Value::String(value),
+        FieldValue::Numeric(value) => nullable(value.map(Value::Double)),
+        FieldValue::NumericInteger(value) => nullable(value.map(|value| Value::Double(value as f64))),
+        FieldValue::Float(value) => nullable(value.map(|value| Value::Double(f64::from(value)))),
+        FieldValue::Double(value) | FieldValue::Currency(value) => Value::Double(value),
+        FieldValue::Integer(value) | FieldValue::Autoincrement(value) => Value::Int(value),
+        FieldValue::Long(value) => nullable(value.map(Value::Int)),
+        FieldValue::DBase7Double(value) => nullable(value.map(Value::Double)),
+        FieldValue::Logical(value) => nullable(value.map(Value::Boolean)),
+        FieldValue::Date(value) => {
+            nullable(value.map(|date| Value::Date(date.days_since_unix_epoch())))
+        }
+        FieldValue::DateTime(value) => {
+            Value::TimestampMillis(value.milliseconds_since_unix_epoch())
+        }
+        FieldValue::Timestamp(value) => nullable(
+            value.map(|value| Value::TimestampMillis(value.milliseconds_since_unix_epoch())),
+        ),
+    }
+}
+
+impl<T: Read + Seek> Reader<T> {
+    /// Returns the Avro schema of the records of the table,
+    /// see [avro_schema](fn.avro_schema.html)
+    pub fn avro_schema(&self, record_name: &str) -> Result<Schema, Error> {
+        avro_schema(&self.fields_info, record_name)
+    }
+
+    /// Writes the remaining records as an Avro object container file,
+    /// the records marked as deleted are not written.
+    ///
+    /// `record_name` is the name of the record in the Avro schema.
+    /// Returns the number of records written.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), dbase::Error> {
+    /// let mut reader = dbase::Reader::from_path("tests/data/line.dbf")?;
+    /// let mut avro = Vec::<u8>::new();
+    /// let num_records = reader.to_avro(&mut avro, "line")?;
+    /// assert_eq!(num_records, 1);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn to_avro<W: Write>(&mut self, dst: W, record_name: &str) -> Result<usize, Error> {
+        let schema = self.avro_schema(record_name)?;
+        let names = self
+            .fields_info
+            .iter()
+            .filter(|field_info| !field_info.is_deletion_flag())
+            .map(|field_info| avro_name(field_info.name()))
+            .collect::<Vec<String>>();
+        let mut writer =
+            apache_avro::Writer::new(&schema, dst).map_err(|error| avro_error(error, 0))?;
+
+        let mut num_records = 0;
+        while let Some(record) = self.read_next_record_as::<RecordValues>() {
+            let record = record?;
+            if record.is_deleted {
+                continue;
+            }
+            let fields = names
+                .iter()
+                .cloned()
+                .zip(record.values.into_iter().map(avro_value))
+                .collect::<Vec<(String, Value)>>();
+            writer
+                .append_value(Value::Record(fields))
+                .map_err(|error| avro_error(error, num_records))?;
+            num_records += 1;
+        }
+        writer
+            .flush()
+            .map_err(|error| avro_error(error, num_records))?;
+        Ok(num_records)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::convert::TryFrom;
+    use std::io::Cursor;
+
+    use crate::record::field::Date;
+    use crate::{FieldName, Record, TableWriterBuilder};
+
+    #[test]
+    fn valid_avro_names() {
+        assert_eq!(avro_name("NAME"), "NAME");
+        assert_eq!(avro_name("marker-col"), "marker_col");
+        assert_eq!(avro_name("2ND"), "_2ND");
+    }
+
+    #[test]
+    fn export_to_avro() {
+        let records = [
+            (Some("Ferris"), Some(1.5), 3, Some(Date::new(2, 1, 1970))),
+            (None, None, -1, None),
+        ]
+        .iter()
+        .map(|(name, price, count, since)| {
+            let mut record = Record::default();
+            record.insert(
+                "NAME".to_owned(),
+                FieldValue::Character(name.map(str::to_owned)),
+            );
+            record.insert("PRICE".to_owned(), FieldValue::Numeric(*price));
+            record.insert("COUNT".to_owned(), FieldValue::Integer(*count));
+            record.insert("SINCE".to_owned(), FieldValue::Date(*since));
+            record
+        })
+        .collect::<Vec<Record>>();
+        let mut cursor = TableWriterBuilder::new()
+            .add_character_field(FieldName::try_from("NAME").unwrap(), 20)
+            .add_numeric_field(FieldName::try_from("PRICE").unwrap(), 8, 2)
+            .add_integer_field(FieldName::try_from("COUNT").unwrap())
+            .add_date_field(FieldName::try_from("SINCE").unwrap())
+            .build_with_dest(Cursor::new(Vec::<u8>::new()))
+            .write(&records)
+            .unwrap();
+        cursor.set_position(0);
+
+        let mut avro = Vec::<u8>::new();
+        let num_records = Reader::new(cursor)
+            .unwrap()
+            .to_avro(&mut avro, "item")
+            .unwrap();
+        assert_eq!(num_records, 2);
+
+        let values = apache_avro::Reader::new(avro.as_slice())
+            .unwrap()
+            .collect::<Result<Vec<Value>, _>>()
+            .unwrap();
+        assert_eq!(
+            values,
+            vec![
+                Value::Record(vec![
+                    (
+                        "NAME".to_owned(),
+                        Value::Union(1, Box::new(Value::String("Ferris".to_owned())))
+                    ),
+                    (
+                        "PRICE".to_owned(),
+                        Value::Union(1, Box::new(Value::Double(1.5)))
+                    ),
+                    ("COUNT".to_owned(), Value::Int(3)),
+                    (
+                        "SINCE".to_owned(),
+                        Value::Union(1, Box::new(Value::Date(1)))
+                    ),
+                ]),
+                Value::Record(vec![
+                    ("NAME".to_owned(), Value::Union(0, Box::new(Value::Null))),
+                    ("PRICE".to_owned(), Value::Union(0, Box::new(Value::Null))),
+                    ("COUNT".to_owned(), Value::Int(-1)),
+                    ("SINCE".to_owned(), Value::Union(0, Box::new(Value::Null))),
+                ]),
+            ]
+        );
+    }
+}
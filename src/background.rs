@@ -0,0 +1,121 @@
+//! Module with the definition of the reader that reads records in a thread
+//!
+//! The records are read and decoded ahead of the consumer, which receives
+//! them over a bounded channel, so that reading overlaps with what is done
+//! with the records.
+use std::io::{Read, Seek};
+use std::sync::mpsc::{sync_channel, Receiver};
+use std::thread::JoinHandle;
+
+use crate::error::Error;
+use crate::reading::{ReadableRecord, Reader};
+
+/// Number of records read ahead of the consumer by default
+pub const DEFAULT_CHANNEL_CAPACITY: usize = 1024;
+
+/// Iterator over the records read by a thread
+///
+/// It is created with [Reader::into_background](struct.Reader.html#method.into_background).
+/// The items are the same as the ones of
+/// [iter_records_as](struct.Reader.html#method.iter_records_as), in the same order.
+///
+/// Dropping the iterator stops the thread once it is done with the record
+/// it was reading.
+#[derive(Debug)]
+pub struct BackgroundReader<R> {
+    receiver: Receiver<Result<R, Error>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl<R> BackgroundReader<R> {
+    /// Waits for the thread to have read all the records
+    fn join(&mut self) {
+        if let Some(thread) = self.thread.take() {
+            // The thread only panics if reading a record panics
+            if let Err(panic) = thread.join() {
+                std::panic::resume_unwind(panic);
+            }
+        }
+    }
+}
+
+impl<R> Iterator for BackgroundReader<R> {
+    type Item = Result<R, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.receiver.recv() {
+            Ok(record) => Some(record),
+            Err(_) => {
+                self.join();
+                None
+            }
+        }
+    }
+}
+
+impl<T: Read + Seek + Send + 'static> Reader<T> {
+    /// Moves the reader to a thread that reads the records as R ahead of the consumer,
+    /// at most `capacity` records are waiting to be consumed
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), dbase::Error> {
+    /// let reader = dbase::Reader::from_path("tests/data/stations.dbf")?;
+    /// let mut num_records = 0;
+    /// for record in reader.into_background::<dbase::Record>(dbase::DEFAULT_CHANNEL_CAPACITY) {
+    ///     let record = record?;
+    ///     num_records += 1;
+    /// }
+    /// assert_eq!(num_records, 6);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn into_background<R: ReadableRecord + Send + 'static>(
+        mut self,
+        capacity: usize,
+    ) -> BackgroundReader<R> {
+        let (sender, receiver) = sync_channel(capacity);
+        let thread = std::thread::spawn(move || {
+            for record in self.iter_records_as::<R>() {
+                if sender.send(record).is_err() {
+                    // The consumer is gone
+                    break;
+                }
+            }
+        });
+        BackgroundReader {
+            receiver,
+            thread: Some(thread),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{FieldValue, Reader, Record};
+
+    #[test]
+    fn records_are_received_in_order() {
+        let mut reader = Reader::from_path("tests/data/stations.dbf").unwrap();
+        let expected = reader.read().unwrap();
+        let reader = Reader::from_path("tests/data/stations.dbf").unwrap();
+        let records = reader
+            .into_background::<Record>(1)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(records, expected);
+    }
+
+    #[test]
+    fn consumer_stops_early() {
+        let reader = Reader::from_path("tests/data/stations.dbf").unwrap();
+        let mut records = reader.into_background::<Record>(1);
+        let first = records.next().unwrap().unwrap();
+        assert_eq!(
+            first.get("name"),
+            Some(&FieldValue::Character(Some("Van Dorn Street".to_owned())))
+        );
+        drop(records);
+    }
+}
@@ -0,0 +1,285 @@
+//! Module with the definition of the fn copying a table with its memo and index files
+//!
+//! Unlike a plain copy of the files, the backup is made consistent with the
+//! applications that may still be writing to the table: the files are copied
+//! to temporary files, the copy is started again if the table changed meanwhile,
+//! and the temporary files are only renamed once the copy is complete and synced.
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::dataset::{INDEX_EXTENSIONS, MEMO_EXTENSIONS};
+use crate::error::{Error, ErrorKind};
+use crate::header::Header;
+
+/// Number of times the files are copied before giving up,
+/// when the table keeps changing during the copy
+pub const BACKUP_ATTEMPTS: usize = 3;
+
+/// Backup made by [backup](fn.backup.html)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Backup {
+    /// The files written, the .dbf file first then its memo and index files
+    pub files: Vec<PathBuf>,
+    /// The number of records of the copied table, as given by its header
+    pub num_records: usize,
+    /// The number of times the files were copied,
+    /// more than one if the table changed during a copy
+    pub num_attempts: usize,
+}
+
+/// What is compared before and after a copy to know if the table changed meanwhile
+#[derive(Debug, PartialEq)]
+struct TableState {
+    num_records: u32,
+    size_of_record: u16,
+    offset_to_first_record: u16,
+    /// The length and modification time of each file
+    files: Vec<(u64, Option<SystemTime>)>,
+}
+
+impl TableState {
+    fn of(paths: &[PathBuf]) -> std::io::Result<Self> {
+        let header = Header::read_from(&mut BufReader::new(File::open(&paths[0])?))?;
+        let files = paths
+            .iter()
+            .map(|path| {
+                let metadata = std::fs::metadata(path)?;
+                Ok((metadata.len(), metadata.modified().ok()))
+            })
+            .collect::<std::io::Result<Vec<_>>>()?;
+        Ok(Self {
+            num_records: header.num_records,
+            size_of_record: header.size_of_record,
+            offset_to_first_record: header.offset_to_first_record,
+            files,
+        })
+    }
+
+    /// Returns the minimum length of a .dbf file with the records the header counts
+    fn expected_table_len(&self) -> u64 {
+        u64::from(self.offset_to_first_record)
+            + u64::from(self.num_records) * u64::from(self.size_of_record)
+    }
+}
+
+/// Returns the .dbf file then the memo and index files that have the same name,
+/// whatever the case
+fn table_files(path: &Path) -> std::io::Result<Vec<PathBuf>> {
+    let stem = path.file_stem().unwrap_or_default();
+    let directory = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let mut sidecars = vec![];
+    for entry in std::fs::read_dir(directory)? {
+        let other = entry?.path();
+        let extension = other
+            .extension()
+            .map(|extension| extension.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        let is_sidecar = MEMO_EXTENSIONS.contains(&extension.as_str())
+            || INDEX_EXTENSIONS.contains(&extension.as_str());
+        let has_same_stem = other
+            .file_stem()
+            .is_some_and(|other_stem| other_stem.eq_ignore_ascii_case(stem));
+        if is_sidecar && has_same_stem && other.is_file() {
+            sidecars.push(other);
+        }
+    }
+    sidecars.sort();
+    let mut files = vec![path.to_owned()];
+    files.extend(sidecars);
+    Ok(files)
+}
+
+fn temporary_path(path: &Path) -> PathBuf {
+    PathBuf::from(format!("{}.tmp", path.display()))
+}
+
+fn remove_all(paths: &[PathBuf]) {
+    for path in paths {
+        let _ = std::fs::remove_file(path);
+    }
+}
+
+/// Copies the file and syncs the copy to the disk
+fn copy_synced(src: &Path, dst: &Path) -> std::io::Result<()> {
+    let mut source = File::open(src)?;
+    let mut copy = File::create(dst)?;
+    std::io::copy(&mut source, &mut copy)?;
+    copy.sync_all()
+}
+
+/// Syncs the directory, so that the renaming of the files is on the disk
+#[cfg(unix)]
+fn sync_directory(path: &Path) -> std::io::Result<()> {
+    let directory = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    File::open(directory)?.sync_all()
+}
+
+#[cfg(not(unix))]
+fn sync_directory(_path: &Path) -> std::io::Result<()> {
+    Ok(())
+}
+
+/// Copies the table at `src`, with its memo and index files, to `dst`
+///
+/// The memo and index files are the ones next to `src` that have the same name
+/// (whatever the case) with one of the extensions .dbt, .fpt, .ndx, .mdx, .cdx, .idx or .ntx.
+/// Their copies are named after `dst`, with the same extensions,
+/// eg: `backup("clients.dbf", "saved/clients.dbf")` also writes `saved/clients.fpt`.
+///
+/// The files are first copied to temporary files next to `dst` (named after the files
+/// with a `.tmp` suffix), which are synced to the disk. If the header or the length
+/// of a file changed during the copy, the copy is started again, at most
+/// [BACKUP_ATTEMPTS](constant.BACKUP_ATTEMPTS.html) times. The copy of the .dbf file must
+/// be long enough for the number of records of its header. Only then are the temporary
+/// files renamed, the .dbf file last, replacing the previous backup if there is one.
+///
+/// # Example
+///
+/// ```
+/// # fn main() -> Result<(), dbase::Error> {
+/// let backup_path = std::env::temp_dir().join("dbase_backup_doc.dbf");
+/// let backup = dbase::backup("tests/data/stations.dbf", &backup_path)?;
+/// assert_eq!(backup.num_records, 6);
+/// assert_eq!(backup.files, vec![backup_path]);
+/// # Ok(())
+/// # }
+/// ```
+pub fn backup<P: AsRef<Path>, Q: AsRef<Path>>(src: P, dst: Q) -> Result<Backup, Error> {
+    let src = src.as_ref();
+    let dst = dst.as_ref();
+    let io_error = |error: std::io::Error| Error::io_error(error, 0);
+    let message = |message: String| Error {
+        record_num: 0,
+        field: None,
+        kind: ErrorKind::Message(message),
+    };
+    if src == dst {
+        return Err(message(
+            "the backup cannot be written over the table".to_owned(),
+        ));
+    }
+
+    let sources = table_files(src).map_err(io_error)?;
+    let destinations = sources
+        .iter()
+        .map(|source| match source.extension() {
+            Some(extension) if source != src => dst.with_extension(extension),
+            _ => dst.to_owned(),
+        })
+        .collect::<Vec<_>>();
+    let temporaries = destinations
+        .iter()
+        .map(|destination| temporary_path(destination))
+        .collect::<Vec<_>>();
+
+    for num_attempts in 1..=BACKUP_ATTEMPTS {
+        let before = TableState::of(&sources).map_err(io_error)?;
+        let copied = sources
+            .iter()
+            .zip(&temporaries)
+            .try_for_each(|(source, temporary)| copy_synced(source, temporary));
+        if let Err(error) = copied {
+            remove_all(&temporaries);
+            return Err(io_error(error));
+        }
+        let after = TableState::of(&sources).map_err(io_error)?;
+        if before != after {
+            remove_all(&temporaries);
+            continue;
+        }
+        let copy_len = std::fs::metadata(&temporaries[0]).map_err(io_error)?.len();
+        if copy_len < before.expected_table_len() {
+            remove_all(&temporaries);
+            return Err(message(format!(
+                "the table has {} bytes, but its header counts {} records, which need {} bytes",
+                copy_len,
+                before.num_records,
+                before.expected_table_len()
+            )));
+        }
+
+        // The sidecars first, the table appears once complete
+        for (temporary, destination) in temporaries.iter().zip(&destinations).rev() {
+            std::fs::rename(temporary, destination).map_err(io_error)?;
+        }
+        sync_directory(dst).map_err(io_error)?;
+        return Ok(Backup {
+            files: destinations,
+            num_records: before.num_records as usize,
+            num_attempts,
+        });
+    }
+    Err(message(format!(
+        "the table changed during each of the {} copies",
+        BACKUP_ATTEMPTS
+    )))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn directory(name: &str) -> PathBuf {
+        let directory = std::env::temp_dir().join(name);
+        let _ = std::fs::remove_dir_all(&directory);
+        std::fs::create_dir_all(&directory).unwrap();
+        directory
+    }
+
+    #[test]
+    fn sidecars_are_copied() {
+        let source_directory = directory("dbase_backup_sidecars_src");
+        let table = source_directory.join("stations.dbf");
+        std::fs::copy("tests/data/stations.dbf", &table).unwrap();
+        std::fs::write(source_directory.join("STATIONS.ndx"), b"index").unwrap();
+        std::fs::write(source_directory.join("stations.dbt"), b"memo").unwrap();
+        std::fs::write(source_directory.join("stations.txt"), b"notes").unwrap();
+
+        let backup_directory = directory("dbase_backup_sidecars_dst");
+        let dst = backup_directory.join("saved.dbf");
+        let backup = backup(&table, &dst).unwrap();
+        assert_eq!(backup.num_records, 6);
+        assert_eq!(backup.num_attempts, 1);
+        assert_eq!(
+            backup.files,
+            vec![
+                dst.clone(),
+                backup_directory.join("saved.ndx"),
+                backup_directory.join("saved.dbt")
+            ]
+        );
+        assert_eq!(std::fs::read(&dst).unwrap(), std::fs::read(&table).unwrap());
+        assert_eq!(
+            std::fs::read(backup_directory.join("saved.dbt")).unwrap(),
+            b"memo"
+        );
+        let mut names = std::fs::read_dir(&backup_directory)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect::<Vec<_>>();
+        names.sort();
+        assert_eq!(names, vec!["saved.dbf", "saved.dbt", "saved.ndx"]);
+    }
+
+    #[test]
+    fn truncated_tables_are_not_backed_up() {
+        let source_directory = directory("dbase_backup_truncated");
+        let table = source_directory.join("stations.dbf");
+        let mut content = std::fs::read("tests/data/stations.dbf").unwrap();
+        content.truncate(content.len() - 100);
+        std::fs::write(&table, content).unwrap();
+
+        let dst = source_directory.join("saved.dbf");
+        assert!(backup(&table, &dst).is_err());
+        assert!(!dst.exists());
+        assert!(!temporary_path(&dst).exists());
+    }
+}
@@ -0,0 +1,527 @@
+//! Module with the definition of fn's and struct's to read dBase files
+//! as [Arrow](https://arrow.apache.org) record batches
+//!
+//! The fields are mapped to Arrow types as follows:
+//!
+//! | dBase type         | Arrow type                                  |
+//! |--------------------|---------------------------------------------|
+//! | Character, Memo    | Utf8                                        |
+//! | Numeric            | Decimal128(field length, decimal places)    |
+//! | Float, Double      | Float64                                     |
+//! | Currency           | Decimal128(19, 4)                           |
+//! | Integer            | Int32                                       |
+//! | Logical            | Boolean                                     |
+//! | Date               | Date32                                      |
+//! | DateTime           | Timestamp(Millisecond, None)                |
+//!
+//! Numeric fields that cannot be represented as a Decimal128 are mapped to Float64.
+use std::convert::TryFrom;
+use std::io::{Read, Seek};
+use std::sync::Arc;
+
+use arrow_array::builder::{
+    BooleanBuilder, Date32Builder, Decimal128Builder, Float64Builder, Int32Builder, StringBuilder,
+    TimestampMillisecondBuilder,
+};
+use arrow_array::{ArrayRef, RecordBatch, RecordBatchOptions, RecordBatchReader};
+use arrow_schema::{ArrowError, DataType, Field, Schema, SchemaRef, TimeUnit};
+
+use crate::error::{ErrorKind, FieldIOError};
+use crate::reading::{FieldIterator, Reader};
+use crate::record::field::{Date, DateTime, FieldType, FieldValue};
+use crate::record::FieldInfo;
+
+/// Number of records in a batch, when not specified
+pub const DEFAULT_BATCH_SIZE: usize = 8192;
+
+/// Max precision of a Decimal128
+const MAX_DECIMAL_PRECISION: u8 = 38;
+/// Currency values are stored as a 64 bit integer scaled by 10 000
+const CURRENCY_PRECISION: u8 = 19;
+const CURRENCY_SCALE: i8 = 4;
+
+/// Returns the Arrow type the values of the field are mapped to
+pub fn arrow_data_type(field_info: &FieldInfo) -> DataType {
+    match field_info.field_type {
+        FieldType::Character | FieldType::Memo => DataType::Utf8,
+        FieldType::Numeric => {
+            let scale = field_info.num_decimal_places;
+            match u8::try_from(field_info.field_length) {
+                Ok(precision)
+                    if precision > 0
+                        && precision <= MAX_DECIMAL_PRECISION
+                        && scale <= precision =>
+                {
+                    DataType::Decimal128(precision, scale as i8)
+                }
+                _ => DataType::Float64,
+            }
+        }
+        FieldType::Float | FieldType::Double | FieldType::DBase7Double => DataType::Float64,
+        FieldType::Currency => DataType::Decimal128(CURRENCY_PRECISION, CURRENCY_SCALE),
+        FieldType::Integer | FieldType::Long | FieldType::Autoincrement => DataType::Int32,
+        FieldType::Logical => DataType::Boolean,
+        FieldType::Date => DataType::Date32,
+        FieldType::DateTime | FieldType::Timestamp => {
+            DataType::Timestamp(TimeUnit::Millisecond, None)
+        }
+    }
+}
+
+/// Returns the Arrow schema of a table with the given fields,
+/// all the fields of the schema are nullable.
+pub fn arrow_schema(fields_info: &[FieldInfo]) -> Schema {
+    Schema::new(
+        fields_info
+            .iter()
+            .filter(|field_info| !field_info.is_deletion_flag())
+            .map(|field_info| Field::new(field_info.name(), arrow_data_type(field_info), true))
+            .collect::<Vec<Field>>(),
+    )
+}
+
+fn to_decimal(value: f64, scale: i8) -> i128 {
+    (value * 10f64.powi(i32::from(scale))).round() as i128
+}
+
+enum ColumnBuilder {
+    Utf8(StringBuilder),
+    Float64(Float64Builder),
+    Decimal128(Decimal128Builder, i8),
+    Int32(Int32Builder),
+    Boolean(BooleanBuilder),
+    Date32(Date32Builder),
+    Timestamp(TimestampMillisecondBuilder),
+}
+
+impl ColumnBuilder {
+    fn new(data_type: &DataType, capacity: usize) -> Result<Self, ArrowError> {
+        let builder = match *data_type {
+            DataType::Utf8 => ColumnBuilder::Utf8(StringBuilder::with_capacity(capacity, 0)),
+            DataType::Float64 => ColumnBuilder::Float64(Float64Builder::with_capacity(capacity)),
+            DataType::Decimal128(precision, scale) => ColumnBuilder::Decimal128(
+                Decimal128Builder::with_capacity(capacity)
+                    .with_precision_and_scale(precision, scale)?,
+                scale,
+            ),
+            DataType::Int32 => ColumnBuilder::Int32(Int32Builder::with_capacity(capacity)),
+            DataType::Boolean => ColumnBuilder::Boolean(BooleanBuilder::with_capacity(capacity)),
+            DataType::Date32 => ColumnBuilder::Date32(Date32Builder::with_capacity(capacity)),
+            DataType::Timestamp(TimeUnit::Millisecond, None) => {
+                ColumnBuilder::Timestamp(TimestampMillisecondBuilder::with_capacity(capacity))
+            }
+            ref data_type => {
+                return Err(ArrowError::SchemaError(format!(
+                    "The Arrow type {} cannot be read from a dBase file",
+                    data_type
+                )))
+            }
+        };
+        Ok(builder)
+    }
+
+    fn append(&mut self, value: FieldValue) -> Result<(), ArrowError> {
+        match (self, value) {
+            (ColumnBuilder::Utf8(builder), FieldValue::Character(value)) => {
+                builder.append_option(value)
+            }
+            (ColumnBuilder::Utf8(builder), FieldValue::Memo(value)) => builder.append_value(value),
+            (ColumnBuilder::Float64(builder), FieldValue::Numeric(value)) => {
+                builder.append_option(value)
+            }
+            (ColumnBuilder::Float64(builder), FieldValue::NumericInteger(value)) => {
+                builder.append_option(value.map(|value| value as f64))
+            }
+            (ColumnBuilder::Float64(builder), FieldValue::Float(value)) => {
+                builder.append_option(value.map(f64::from))
+            }
+            (ColumnBuilder::Float64(builder), FieldValue::Double(value)) => {
+                builder.append_value(value)
+            }
+            (ColumnBuilder::Float64(builder), FieldValue::DBase7Double(value)) => {
+                builder.append_option(value)
+            }
+            (ColumnBuilder::Decimal128(builder, scale), FieldValue::Numeric(value)) => {
+                builder.append_option(value.map(|value| to_decimal(value, *scale)))
+            }
+            (ColumnBuilder::Decimal128(builder, scale), FieldValue::NumericInteger(value)) => {
+                builder.append_option(
+                    value.map(|value| i128::from(value) * 10i128.pow(*scale as u32)),
+                )
+            }
+            (ColumnBuilder::Decimal128(builder, scale), FieldValue::Currency(value)) => {
+                builder.append_value(to_decimal(value, *scale))
+            }
+            (ColumnBuilder::Int32(builder), FieldValue::Integer(value))
+            | (ColumnBuilder::Int32(builder), FieldValue::Autoincrement(value)) => {
+                builder.append_value(value)
+            }
+            (ColumnBuilder::Int32(builder), FieldValue::Long(value)) => {
+                builder.append_option(value)
+            }
+            (ColumnBuilder::Boolean(builder), FieldValue::Logical(value)) => {
+                builder.append_option(value)
+            }
+            (ColumnBuilder::Date32(builder), FieldValue::Date(value)) => {
+                builder.append_option(value.map(Date::days_since_unix_epoch))
+            }
+            (ColumnBuilder::Timestamp(builder), FieldValue::DateTime(value)) => {
+                builder.append_value(value.milliseconds_since_unix_epoch())
+            }
+            (ColumnBuilder::Timestamp(builder), FieldValue::Timestamp(value)) => {
+                builder.append_option(value.map(DateTime::milliseconds_since_unix_epoch))
+            }
+            (_, value) => {
+                return Err(ArrowError::InvalidArgumentError(format!(
+                    "The value {:?} does not match the type of the column",
+                    value
+                )))
+            }
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> ArrayRef {
+        match self {
+            ColumnBuilder::Utf8(mut builder) => Arc::new(builder.finish()),
+            ColumnBuilder::Float64(mut builder) => Arc::new(builder.finish()),
+            ColumnBuilder::Decimal128(mut builder, _) => Arc::new(builder.finish()),
+            ColumnBuilder::Int32(mut builder) => Arc::new(builder.finish()),
+            ColumnBuilder::Boolean(mut builder) => Arc::new(builder.finish()),
+            ColumnBuilder::Date32(mut builder) => Arc::new(builder.finish()),
+            ColumnBuilder::Timestamp(mut builder) => Arc::new(builder.finish()),
+        }
+    }
+}
+
+/// Reads the values of the fields that are part of the projection,
+/// the other fields are skipped
+fn read_projected_values<T: Read + Seek>(
+    field_iterator: &mut FieldIterator<T>,
+    columns: &[Option<usize>],
+) -> Result<Vec<FieldValue>, FieldIOError> {
+    field_iterator.read_deletion_flag()?;
+    let mut values = Vec::<FieldValue>::with_capacity(columns.len());
+    for column in columns {
+        if column.is_some() {
+            values.push(field_iterator.read_next_field()?.value);
+        } else {
+            field_iterator
+                .skip_next_field()
+                .map_err(|error| FieldIOError::new(ErrorKind::IoError(error), None))?;
+        }
+    }
+    Ok(values)
+}
+
+/// Iterator over the records of a table, grouped in Arrow record batches
+///
+/// Created by [Reader::into_record_batches](struct.Reader.html#method.into_record_batches),
+/// it implements Arrow's `RecordBatchReader`.
+pub struct RecordBatches<T: Read + Seek> {
+    reader: Reader<T>,
+    schema: SchemaRef,
+    batch_size: usize,
+    /// For each field of the table, the index of its column in the batches,
+    /// if the field is part of the projection
+    columns: Vec<Option<usize>>,
+}
+
+impl<T: Read + Seek> RecordBatches<T> {
+    /// Only reads the columns at the given indices (in the schema of the table),
+    /// the columns of the batches are in the order of the indices.
+    ///
+    /// The values of the other fields are skipped without being decoded.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # extern crate arrow_array;
+    /// # extern crate dbase;
+    /// use arrow_array::RecordBatchReader;
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let reader = dbase::Reader::from_path("tests/data/stations.dbf")?;
+    /// let batches = reader
+    ///     .into_record_batches(dbase::DEFAULT_BATCH_SIZE)
+    ///     .with_projection(&[1])?;
+    /// assert_eq!(batches.schema().field(0).name(), "marker-col");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_projection(mut self, projection: &[usize]) -> Result<Self, ArrowError> {
+        self.schema = Arc::new(self.reader.arrow_schema().project(projection)?);
+        self.columns = vec![None; self.columns.len()];
+        for (column_index, field_index) in projection.iter().enumerate() {
+            if self.columns[*field_index].replace(column_index).is_some() {
+                return Err(ArrowError::SchemaError(format!(
+                    "The column {} appears more than once in the projection",
+                    field_index
+                )));
+            }
+        }
+        Ok(self)
+    }
+
+    fn next_batch(&mut self) -> Result<Option<RecordBatch>, ArrowError> {
+        self.reader
+            .check_memory_budget(self.batch_size)
+            .map_err(|error| ArrowError::ExternalError(Box::new(error)))?;
+        let mut builders = self
+            .schema
+            .fields()
+            .iter()
+            .map(|field| ColumnBuilder::new(field.data_type(), self.batch_size))
+            .collect::<Result<Vec<ColumnBuilder>, ArrowError>>()?;
+
+        let columns = &self.columns;
+        let mut num_rows = 0;
+        while num_rows < self.batch_size {
+            let values = match self.reader.read_next_record_with(|field_iterator| {
+                read_projected_values(field_iterator, columns)
+            }) {
+                Some(values) => {
+                    values.map_err(|error| ArrowError::ExternalError(Box::new(error)))?
+                }
+                None => break,
+            };
+            for (column_index, value) in columns.iter().flatten().zip(values) {
+                builders[*column_index].append(value)?;
+            }
+            num_rows += 1;
+        }
+        if num_rows == 0 {
+            return Ok(None);
+        }
+
+        let columns = builders
+            .into_iter()
+            .map(ColumnBuilder::finish)
+            .collect::<Vec<ArrayRef>>();
+        let options = RecordBatchOptions::new().with_row_count(Some(num_rows));
+        RecordBatch::try_new_with_options(self.schema.clone(), columns, &options).map(Some)
+    }
+}
+
+impl<T: Read + Seek> Iterator for RecordBatches<T> {
+    type Item = Result<RecordBatch, ArrowError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_batch().transpose()
+    }
+}
+
+impl<T: Read + Seek> RecordBatchReader for RecordBatches<T> {
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+}
+
+impl<T: Read + Seek> Reader<T> {
+    /// Returns the Arrow schema of the table
+    pub fn arrow_schema(&self) -> Schema {
+        arrow_schema(&self.fields_info)
+    }
+
+    /// Turns the reader into an iterator of Arrow record batches
+    /// of (at most) `batch_size` records.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let reader = dbase::Reader::from_path("tests/data/line.dbf")?;
+    /// for batch in reader.into_record_batches(dbase::DEFAULT_BATCH_SIZE) {
+    ///     let batch = batch?;
+    ///     println!("{} records", batch.num_rows());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn into_record_batches(self, batch_size: usize) -> RecordBatches<T> {
+        let schema = Arc::new(self.arrow_schema());
+        let columns = (0..schema.fields().len()).map(Some).collect();
+        RecordBatches {
+            reader: self,
+            schema,
+            batch_size: batch_size.max(1),
+            columns,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::convert::TryFrom;
+    use std::io::Cursor;
+
+    use arrow_array::{Array, BooleanArray, Date32Array, Decimal128Array, Int32Array, StringArray};
+
+    use crate::{FieldName, Record, TableWriterBuilder};
+
+    fn record(name: Option<&str>, price: Option<f64>, count: i32, paid: Option<bool>) -> Record {
+        let mut record = Record::default();
+        record.insert(
+            "NAME".to_owned(),
+            FieldValue::Character(name.map(str::to_owned)),
+        );
+        record.insert("PRICE".to_owned(), FieldValue::Numeric(price));
+        record.insert("COUNT".to_owned(), FieldValue::Integer(count));
+        record.insert("PAID".to_owned(), FieldValue::Logical(paid));
+        record.insert(
+            "ON".to_owned(),
+            FieldValue::Date(count.ne(&2).then(|| Date::new(2, 1, 1970))),
+        );
+        record
+    }
+
+    #[test]
+    fn read_record_batches() {
+        let records = vec![
+            record(Some("apple"), Some(1.5), 1, Some(true)),
+            record(None, Some(2.5), 2, None),
+            record(Some("pear"), Some(-10.5), 3, Some(false)),
+        ];
+        let mut cursor = TableWriterBuilder::new()
+            .add_character_field(FieldName::try_from("NAME").unwrap(), 10)
+            .add_numeric_field(FieldName::try_from("PRICE").unwrap(), 8, 2)
+            .add_integer_field(FieldName::try_from("COUNT").unwrap())
+            .add_logical_field(FieldName::try_from("PAID").unwrap())
+            .add_date_field(FieldName::try_from("ON").unwrap())
+            .build_with_dest(Cursor::new(Vec::<u8>::new()))
+            .write(&records)
+            .unwrap();
+        cursor.set_position(0);
+
+        let reader = Reader::new(cursor).unwrap();
+        let schema = reader.arrow_schema();
+        assert_eq!(
+            schema
+                .fields()
+                .iter()
+                .map(|field| field.data_type().clone())
+                .collect::<Vec<DataType>>(),
+            vec![
+                DataType::Utf8,
+                DataType::Decimal128(8, 2),
+                DataType::Int32,
+                DataType::Boolean,
+                DataType::Date32
+            ]
+        );
+
+        let batches = reader
+            .into_record_batches(2)
+            .collect::<Result<Vec<RecordBatch>, ArrowError>>()
+            .unwrap();
+        assert_eq!(
+            batches
+                .iter()
+                .map(RecordBatch::num_rows)
+                .collect::<Vec<usize>>(),
+            vec![2, 1]
+        );
+
+        let batch = &batches[0];
+        let names = batch
+            .column(0)
+            .as_any()
+            .downcast_ref::<StringArray>()
+            .unwrap();
+        assert_eq!(names.value(0), "apple");
+        assert!(names.is_null(1));
+        let prices = batch
+            .column(1)
+            .as_any()
+            .downcast_ref::<Decimal128Array>()
+            .unwrap();
+        assert_eq!(prices.value(0), 150);
+        assert_eq!(prices.value(1), 250);
+        let counts = batch
+            .column(2)
+            .as_any()
+            .downcast_ref::<Int32Array>()
+            .unwrap();
+        assert_eq!(counts.values(), &[1, 2]);
+        let paid = batch
+            .column(3)
+            .as_any()
+            .downcast_ref::<BooleanArray>()
+            .unwrap();
+        assert!(paid.value(0));
+        assert!(paid.is_null(1));
+        let dates = batch
+            .column(4)
+            .as_any()
+            .downcast_ref::<Date32Array>()
+            .unwrap();
+        assert_eq!(dates.value(0), 1);
+        assert!(dates.is_null(1));
+
+        let prices = batches[1]
+            .column(1)
+            .as_any()
+            .downcast_ref::<Decimal128Array>()
+            .unwrap();
+        assert_eq!(prices.value(0), -1050);
+    }
+
+    #[test]
+    fn read_projected_record_batches() {
+        let records = vec![
+            record(Some("apple"), Some(1.5), 1, Some(true)),
+            record(None, Some(2.5), 2, None),
+        ];
+        let mut cursor = TableWriterBuilder::new()
+            .add_character_field(FieldName::try_from("NAME").unwrap(), 10)
+            .add_numeric_field(FieldName::try_from("PRICE").unwrap(), 8, 2)
+            .add_integer_field(FieldName::try_from("COUNT").unwrap())
+            .add_logical_field(FieldName::try_from("PAID").unwrap())
+            .add_date_field(FieldName::try_from("ON").unwrap())
+            .build_with_dest(Cursor::new(Vec::<u8>::new()))
+            .write(&records)
+            .unwrap();
+        cursor.set_position(0);
+
+        let batches = Reader::new(cursor.clone())
+            .unwrap()
+            .into_record_batches(10)
+            .with_projection(&[2, 0])
+            .unwrap()
+            .collect::<Result<Vec<RecordBatch>, ArrowError>>()
+            .unwrap();
+        assert_eq!(batches.len(), 1);
+        let batch = &batches[0];
+        assert_eq!(batch.num_columns(), 2);
+        assert_eq!(batch.schema().field(0).name(), "COUNT");
+        let counts = batch
+            .column(0)
+            .as_any()
+            .downcast_ref::<Int32Array>()
+            .unwrap();
+        assert_eq!(counts.values(), &[1, 2]);
+        let names = batch
+            .column(1)
+            .as_any()
+            .downcast_ref::<StringArray>()
+            .unwrap();
+        assert_eq!(names.value(0), "apple");
+        assert!(names.is_null(1));
+
+        let batches = Reader::new(cursor.clone())
+            .unwrap()
+            .into_record_batches(10)
+            .with_projection(&[])
+            .unwrap()
+            .collect::<Result<Vec<RecordBatch>, ArrowError>>()
+            .unwrap();
+        assert_eq!(batches[0].num_rows(), 2);
+
+        let reader = Reader::new(cursor).unwrap();
+        assert!(reader
+            .into_record_batches(10)
+            .with_projection(&[1, 1])
+            .is_err());
+    }
+}
@@ -0,0 +1,630 @@
+//! Module with the definition of the writer of
+//! [Arrow](https://arrow.apache.org) record batches to dBase files
+//!
+//! The fields of the table are given by a [TableWriterBuilder], each field is written
+//! with the values of the column with the same name, converted as follows:
+//!
+//! | dBase type                   | Arrow types                                         |
+//! |------------------------------|-----------------------------------------------------|
+//! | Character                    | Utf8, LargeUtf8                                     |
+//! | Numeric, Float, Double       | integers, Float32, Float64, Decimal128              |
+//! | Currency                     | integers, Float32, Float64, Decimal128              |
+//! | Integer                      | integers that fit in an Int32                       |
+//! | Logical                      | Boolean                                             |
+//! | Date                         | Date32, Date64                                      |
+//! | DateTime                     | Timestamp (any unit, the time zone is ignored)      |
+//!
+//! Null values are written as blanks, they cannot be written
+//! to Integer, Double, Currency and DateTime fields.
+use std::fs::File;
+use std::io::{BufWriter, Cursor, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use arrow_array::cast::AsArray;
+use arrow_array::types::{
+    Date32Type, Date64Type, Decimal128Type, Float32Type, Float64Type, Int16Type, Int32Type,
+    Int64Type, Int8Type, TimestampMicrosecondType, TimestampMillisecondType,
+    TimestampNanosecondType, TimestampSecondType, UInt16Type, UInt32Type, UInt64Type, UInt8Type,
+};
+use arrow_array::{Array, ArrayRef, RecordBatch};
+use arrow_schema::{ArrowError, DataType, TimeUnit};
+use byteorder::WriteBytesExt;
+
+use crate::encoding::Encoding;
+use crate::encryption::{encrypt_record, RecordCipher};
+use crate::error::{Error, ErrorKind, FieldIOError};
+use crate::header::Header;
+use crate::record::field::{Date, DateTime, FieldType, FieldValue};
+use crate::record::FieldInfo;
+use crate::split::SplitTable;
+use crate::writing::{
+    check_format_limits, check_no_memo_fields, continuation_path, table_info_size,
+    update_header, write_table_info, FieldWriter, OverflowPolicy, TableWriterBuilder,
+    WritableRecord, FILE_TERMINATOR, MAX_TABLE_SIZE,
+};
+
+const MILLISECONDS_PER_DAY: i64 = 24 * 60 * 60 * 1000;
+
+/// Returns the value of the cell as a number, if its type is a numeric one
+fn number(array: &dyn Array, row: usize) -> Option<f64> {
+    let value = match array.data_type() {
+        DataType::Int8 => f64::from(array.as_primitive::<Int8Type>().value(row)),
+        DataType::Int16 => f64::from(array.as_primitive::<Int16Type>().value(row)),
+        DataType::Int32 => f64::from(array.as_primitive::<Int32Type>().value(row)),
+        DataType::Int64 => array.as_primitive::<Int64Type>().value(row) as f64,
+        DataType::UInt8 => f64::from(array.as_primitive::<UInt8Type>().value(row)),
+        DataType::UInt16 => f64::from(array.as_primitive::<UInt16Type>().value(row)),
+        DataType::UInt32 => f64::from(array.as_primitive::<UInt32Type>().value(row)),
+        DataType::UInt64 => array.as_primitive::<UInt64Type>().value(row) as f64,
+        DataType::Float32 => f64::from(array.as_primitive::<Float32Type>().value(row)),
+        DataType::Float64 => array.as_primitive::<Float64Type>().value(row),
+        DataType::Decimal128(_, scale) => {
+            let value = array.as_primitive::<Decimal128Type>().value(row);
+            value as f64 / 10f64.powi(i32::from(*scale))
+        }
+        _ => return None,
+    };
+    Some(value)
+}
+
+/// Returns the value of the cell as an integer, if its type is an integer one
+/// and the value fits in a 32 bit integer
+fn integer(array: &dyn Array, row: usize) -> Option<i32> {
+    use std::convert::TryFrom;
+    match array.data_type() {
+        DataType::Int8 => Some(i32::from(array.as_primitive::<Int8Type>().value(row))),
+        DataType::Int16 => Some(i32::from(array.as_primitive::<Int16Type>().value(row))),
+        DataType::Int32 => Some(array.as_primitive::<Int32Type>().value(row)),
+        DataType::Int64 => i32::try_from(array.as_primitive::<Int64Type>().value(row)).ok(),
+        DataType::UInt8 => Some(i32::from(array.as_primitive::<UInt8Type>().value(row))),
+        DataType::UInt16 => Some(i32::from(array.as_primitive::<UInt16Type>().value(row))),
+        DataType::UInt32 => i32::try_from(array.as_primitive::<UInt32Type>().value(row)).ok(),
+        DataType::UInt64 => i32::try_from(array.as_primitive::<UInt64Type>().value(row)).ok(),
+        _ => None,
+    }
+}
+
+/// Returns the value of the cell as a number of milliseconds since the Unix epoch,
+/// if its type is a timestamp
+fn milliseconds(array: &dyn Array, row: usize) -> Option<i64> {
+    let milliseconds = match array.data_type() {
+        DataType::Timestamp(TimeUnit::Second, _) => {
+            array.as_primitive::<TimestampSecondType>().value(row) * 1000
+        }
+        DataType::Timestamp(TimeUnit::Millisecond, _) => {
+            array.as_primitive::<TimestampMillisecondType>().value(row)
+        }
+        DataType::Timestamp(TimeUnit::Microsecond, _) => array
+            .as_primitive::<TimestampMicrosecondType>()
+            .value(row)
+            .div_euclid(1_000),
+        DataType::Timestamp(TimeUnit::Nanosecond, _) => array
+            .as_primitive::<TimestampNanosecondType>()
+            .value(row)
+            .div_euclid(1_000_000),
+        _ => return None,
+    };
+    Some(milliseconds)
+}
+
+/// Converts the cell of the column to the value of the field
+fn to_field_value(
+    array: &dyn Array,
+    row: usize,
+    field_info: &FieldInfo,
+) -> Result<FieldValue, ErrorKind> {
+    let incompatible = || {
+        ErrorKind::Message(format!(
+            "the values of type {} cannot be written to the field '{}' of type {:?}",
+            array.data_type(),
+            field_info.name(),
+            field_info.field_type
+        ))
+    };
+
+    if array.is_null(row) {
+        return match field_info.field_type {
+            FieldType::Character => Ok(FieldValue::Character(None)),
+            FieldType::Numeric => Ok(FieldValue::Numeric(None)),
+            FieldType::Float => Ok(FieldValue::Float(None)),
+            FieldType::Logical => Ok(FieldValue::Logical(None)),
+            FieldType::Date => Ok(FieldValue::Date(None)),
+            FieldType::Long => Ok(FieldValue::Long(None)),
+            FieldType::DBase7Double => Ok(FieldValue::DBase7Double(None)),
+            FieldType::Timestamp => Ok(FieldValue::Timestamp(None)),
+            _ => Err(ErrorKind::Message(format!(
+                "null cannot be written to the field '{}' of type {:?}",
+                field_info.name(),
+                field_info.field_type
+            ))),
+        };
+    }
+
+    let value = match field_info.field_type {
+        FieldType::Character => {
+            let text = match array.data_type() {
+                DataType::Utf8 => array.as_string::<i32>().value(row),
+                DataType::LargeUtf8 => array.as_string::<i64>().value(row),
+                _ => return Err(incompatible()),
+            };
+            FieldValue::Character(Some(text.to_owned()))
+        }
+        FieldType::Numeric => {
+            FieldValue::Numeric(Some(number(array, row).ok_or_else(incompatible)?))
+        }
+        FieldType::Float => {
+            FieldValue::Float(Some(number(array, row).ok_or_else(incompatible)? as f32))
+        }
+        FieldType::Double => FieldValue::Double(number(array, row).ok_or_else(incompatible)?),
+        FieldType::Currency => FieldValue::Currency(number(array, row).ok_or_else(incompatible)?),
+        FieldType::Integer => FieldValue::Integer(integer(array, row).ok_or_else(incompatible)?),
+        FieldType::Long => FieldValue::Long(Some(integer(array, row).ok_or_else(incompatible)?)),
+        FieldType::Autoincrement => {
+            FieldValue::Autoincrement(integer(array, row).ok_or_else(incompatible)?)
+        }
+        FieldType::DBase7Double => {
+            FieldValue::DBase7Double(Some(number(array, row).ok_or_else(incompatible)?))
+        }
+        FieldType::Logical => match array.data_type() {
+            DataType::Boolean => FieldValue::Logical(Some(array.as_boolean().value(row))),
+            _ => return Err(incompatible()),
+        },
+        FieldType::Date => {
+            let days = match array.data_type() {
+                DataType::Date32 => array.as_primitive::<Date32Type>().value(row),
+                DataType::Date64 => {
+                    let milliseconds = array.as_primitive::<Date64Type>().value(row);
+                    milliseconds.div_euclid(MILLISECONDS_PER_DAY) as i32
+                }
+                _ => return Err(incompatible()),
+            };
+            FieldValue::Date(Some(Date::from_days_since_unix_epoch(days)))
+        }
+        FieldType::DateTime => FieldValue::DateTime(DateTime::from_milliseconds_since_unix_epoch(
+            milliseconds(array, row).ok_or_else(incompatible)?,
+        )),
+        FieldType::Timestamp => FieldValue::Timestamp(Some(
+            DateTime::from_milliseconds_since_unix_epoch(
+                milliseconds(array, row).ok_or_else(incompatible)?,
+            ),
+        )),
+        FieldType::Memo => return Err(incompatible()),
+    };
+    Ok(value)
+}
+
+/// A row of a record batch, written as a record
+struct BatchRow<'a> {
+    /// The columns of the fields of the table, in the order of the fields
+    columns: &'a [ArrayRef],
+    index: usize,
+}
+
+impl<'a> WritableRecord for BatchRow<'a> {
+    fn write_using<'b, W: Write>(
+        &self,
+        field_writer: &mut FieldWriter<'b, W>,
+    ) -> Result<(), FieldIOError> {
+        for column in self.columns {
+            let field_info = match field_writer.fields_info.peek().copied() {
+                Some(field_info) => field_info,
+                None => break,
+            };
+            let value = to_field_value(column.as_ref(), self.index, field_info)
+                .map_err(|kind| FieldIOError::new(kind, Some(field_info.to_owned())))?;
+            field_writer.write_next_field_value(&value)?;
+        }
+        Ok(())
+    }
+}
+
+/// The table being written
+struct TableFile {
+    dst: BufWriter<File>,
+    num_records: usize,
+    size: u64,
+}
+
+/// Writes Arrow record batches to dBase files, starting a new file
+/// each time the current one would exceed the size limit of the format (2 GB)
+///
+/// It is created with [build_record_batch_writer](struct.TableWriterBuilder.html#method.build_record_batch_writer),
+/// the first table is written at the path given to the builder, the next ones are
+/// named after it followed by their number (`table.dbf`, `table_2.dbf`, `table_3.dbf`, ...).
+/// As memo files cannot be written, the fields cannot be memo fields.
+///
+/// # Example
+///
+/// ```
+/// # extern crate arrow_array;
+/// # extern crate dbase;
+/// use std::convert::TryFrom;
+/// use std::sync::Arc;
+///
+/// use arrow_array::{ArrayRef, Int32Array, RecordBatch, StringArray};
+/// use dbase::{FieldName, TableWriterBuilder};
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let names: ArrayRef = Arc::new(StringArray::from(vec![Some("Ferris"), None]));
+/// let legs: ArrayRef = Arc::new(Int32Array::from(vec![10, 8]));
+/// let batch = RecordBatch::try_from_iter(vec![("NAME", names), ("LEGS", legs)])?;
+///
+/// let path = std::env::temp_dir().join("dbase_record_batch_writer_doc.dbf");
+/// let mut writer = TableWriterBuilder::new()
+///     .add_character_field(FieldName::try_from("NAME").unwrap(), 20)
+///     .add_numeric_field(FieldName::try_from("LEGS").unwrap(), 3, 0)
+///     .build_record_batch_writer(&path)?;
+/// writer.write(&batch)?;
+/// let tables = writer.finish()?;
+/// assert_eq!(tables[0].num_records, 2);
+///
+/// let records = dbase::read(&path)?;
+/// assert_eq!(records.len(), 2);
+/// # std::fs::remove_file(&path)?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct RecordBatchWriter {
+    fields_info: Vec<FieldInfo>,
+    header: Header,
+    backlink: Option<String>,
+    path: PathBuf,
+    max_file_size: u64,
+    /// Encoding of the names of the fields
+    encoding: Arc<dyn Encoding>,
+    cipher: Option<Arc<dyn RecordCipher>>,
+    current: Option<TableFile>,
+    tables: Vec<SplitTable>,
+    /// Bytes of the record being written
+    record_buffer: Vec<u8>,
+}
+
+impl RecordBatchWriter {
+    /// Sets the maximum size of the files, the 2 GB the format allows by default
+    ///
+    /// Files have at least one record, whatever their size.
+    pub fn max_file_size(mut self, size: u64) -> Self {
+        self.max_file_size = size.min(MAX_TABLE_SIZE);
+        self
+    }
+
+    /// Creates the next table, and writes everything that comes before the records
+    fn start_table(&mut self) -> Result<(), Error> {
+        let io_error = |error: std::io::Error| Error::io_error(error, 0);
+        let path = continuation_path(&self.path, self.tables.len());
+        let mut dst = BufWriter::new(File::create(&path).map_err(io_error)?);
+        update_header(&mut self.header, &self.fields_info, 0);
+        write_table_info(
+            &mut dst,
+            &self.header,
+            &self.fields_info,
+            self.backlink.as_deref(),
+            &*self.encoding,
+        )
+        .map_err(io_error)?;
+        self.tables.push(SplitTable {
+            path,
+            num_records: 0,
+            value: None,
+        });
+        self.current = Some(TableFile {
+            dst,
+            num_records: 0,
+            size: table_info_size(&self.header, self.fields_info.len()) as u64,
+        });
+        Ok(())
+    }
+
+    /// Ends the current table, and updates its header with the number of records written
+    fn finish_table(&mut self) -> Result<(), Error> {
+        let mut table = match self.current.take() {
+            Some(table) => table,
+            None => return Ok(()),
+        };
+        let num_records = table.num_records;
+        let io_error = |error: std::io::Error| Error::io_error(error, num_records);
+        table.dst.write_u8(FILE_TERMINATOR).map_err(io_error)?;
+        update_header(&mut self.header, &self.fields_info, num_records);
+        let mut header = Vec::<u8>::with_capacity(Header::SIZE);
+        self.header.write_to(&mut header).map_err(io_error)?;
+        table.dst.seek(SeekFrom::Start(0)).map_err(io_error)?;
+        table.dst.write_all(&header).map_err(io_error)?;
+        table.dst.flush().map_err(io_error)?;
+        if let Some(split_table) = self.tables.last_mut() {
+            split_table.num_records = num_records;
+        }
+        Ok(())
+    }
+
+    /// Writes the rows of the batch after the ones already written
+    ///
+    /// The batch must have a column for each field, the other columns are ignored.
+    pub fn write(&mut self, batch: &RecordBatch) -> Result<(), Error> {
+        let columns = self
+            .fields_info
+            .iter()
+            .map(|field_info| {
+                batch
+                    .column_by_name(field_info.name())
+                    .cloned()
+                    .ok_or_else(|| Error {
+                        record_num: 0,
+                        field: Some(field_info.clone()),
+                        kind: ErrorKind::Message(format!(
+                            "the record batch has no column named '{}'",
+                            field_info.name()
+                        )),
+                    })
+            })
+            .collect::<Result<Vec<ArrayRef>, Error>>()?;
+        // The deletion flag and the terminator of the file
+        let record_size = self.fields_info.iter().fold(1u64, |size, field_info| {
+            size + u64::from(field_info.length())
+        });
+
+        for index in 0..batch.num_rows() {
+            let is_full = self.current.as_ref().is_none_or(|table| {
+                table.num_records > 0
+                    && (table.size + record_size + 1 > self.max_file_size
+                        || table.num_records == u32::MAX as usize)
+            });
+            if is_full {
+                self.finish_table()?;
+                self.start_table()?;
+            }
+            let table = self.current.as_mut().expect("a table is started");
+            let mut field_writer = FieldWriter {
+                dst: &mut self.record_buffer,
+                fields_info: self.fields_info.iter().peekable(),
+                buffer: Cursor::new(vec![0u8; 255]),
+                defaults: None,
+                overflow_policy: OverflowPolicy::default(),
+                encoding: &*self.encoding,
+            };
+            field_writer.write_record(
+                &BatchRow {
+                    columns: &columns,
+                    index,
+                },
+                table.num_records,
+            )?;
+            encrypt_record(
+                self.cipher.as_deref(),
+                table.num_records,
+                &mut self.record_buffer,
+            );
+            table
+                .dst
+                .write_all(&self.record_buffer)
+                .map_err(|error| Error::io_error(error, table.num_records))?;
+            table.num_records += 1;
+            table.size += record_size;
+        }
+        Ok(())
+    }
+
+    /// Ends the tables, and returns them
+    ///
+    /// A table with no records is written if no rows were written.
+    pub fn finish(mut self) -> Result<Vec<SplitTable>, Error> {
+        if self.tables.is_empty() {
+            self.start_table()?;
+        }
+        self.finish_table()?;
+        Ok(self.tables)
+    }
+}
+
+impl TableWriterBuilder {
+    /// Builds a writer of Arrow record batches to the file at the given path,
+    /// and to continuation files next to it when the records do not fit in one file
+    ///
+    /// Fails if a field is a memo field, as memo files cannot be written.
+    pub fn build_record_batch_writer<P: AsRef<Path>>(
+        self,
+        path: P,
+    ) -> Result<RecordBatchWriter, Error> {
+        check_no_memo_fields(&self.v)?;
+        check_format_limits(&self.hdr, &self.v, 0, MAX_TABLE_SIZE)?;
+        Ok(RecordBatchWriter {
+            fields_info: self.v,
+            header: self.hdr,
+            backlink: self.backlink,
+            path: path.as_ref().to_owned(),
+            max_file_size: self.max_file_size,
+            encoding: self.encoding,
+            cipher: self.cipher,
+            current: None,
+            tables: vec![],
+            record_buffer: vec![],
+        })
+    }
+}
+
+/// Writes the record batches to the file at the given path, with the fields of the builder
+///
+/// The files are written by a [RecordBatchWriter], the tables written are returned.
+pub fn write_record_batches<P, I>(
+    batches: I,
+    builder: TableWriterBuilder,
+    path: P,
+) -> Result<Vec<SplitTable>, Error>
+where
+    P: AsRef<Path>,
+    I: IntoIterator<Item = Result<RecordBatch, ArrowError>>,
+{
+    let mut writer = builder.build_record_batch_writer(path)?;
+    for batch in batches {
+        let batch = batch.map_err(|error| Error {
+            record_num: 0,
+            field: None,
+            kind: ErrorKind::Message(error.to_string()),
+        })?;
+        writer.write(&batch)?;
+    }
+    writer.finish()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::convert::TryFrom;
+    use std::io::Cursor;
+    use std::sync::Arc;
+
+    use arrow_array::{
+        BooleanArray, Date32Array, Decimal128Array, Float64Array, Int64Array, StringArray,
+        TimestampSecondArray,
+    };
+
+    use crate::record::field::Time;
+    use crate::{FieldName, Reader};
+
+    fn batch() -> RecordBatch {
+        let columns: Vec<(&str, ArrayRef)> = vec![
+            (
+                "NAME",
+                Arc::new(StringArray::from(vec![Some("Ferris"), None, Some("Corro")])),
+            ),
+            ("LEGS", Arc::new(Int64Array::from(vec![10, 8, 5]))),
+            (
+                "PRICE",
+                Arc::new(
+                    Decimal128Array::from(vec![Some(1250), None, Some(-5)])
+                        .with_precision_and_scale(10, 2)
+                        .unwrap(),
+                ),
+            ),
+            ("WEIGHT", Arc::new(Float64Array::from(vec![0.5, 1.25, 2.0]))),
+            (
+                "SWIMS",
+                Arc::new(BooleanArray::from(vec![Some(true), None, Some(false)])),
+            ),
+            (
+                "BORN",
+                Arc::new(Date32Array::from(vec![Some(1), None, Some(-1)])),
+            ),
+            (
+                "UPDATED",
+                Arc::new(TimestampSecondArray::from(vec![0, 86_400, 90])),
+            ),
+        ];
+        RecordBatch::try_from_iter(columns).unwrap()
+    }
+
+    fn builder() -> TableWriterBuilder {
+        TableWriterBuilder::new()
+            .add_character_field(FieldName::try_from("NAME").unwrap(), 10)
+            .add_integer_field(FieldName::try_from("LEGS").unwrap())
+            .add_numeric_field(FieldName::try_from("PRICE").unwrap(), 10, 2)
+            .add_double_field(FieldName::try_from("WEIGHT").unwrap())
+            .add_logical_field(FieldName::try_from("SWIMS").unwrap())
+            .add_date_field(FieldName::try_from("BORN").unwrap())
+            .add_datetime_field(FieldName::try_from("UPDATED").unwrap())
+    }
+
+    #[test]
+    fn write_record_batches_to_a_table() {
+        let path = std::env::temp_dir().join("dbase_write_record_batches.dbf");
+        let tables =
+            write_record_batches(vec![Ok(batch()), Ok(batch())], builder(), &path).unwrap();
+        let table = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(
+            tables,
+            vec![SplitTable {
+                path: path.clone(),
+                num_records: 6,
+                value: None,
+            }]
+        );
+
+        let records = Reader::new(Cursor::new(table)).unwrap().read().unwrap();
+        assert_eq!(records.len(), 6);
+        let first = &records[0];
+        assert_eq!(
+            first.get("NAME"),
+            Some(&FieldValue::Character(Some("Ferris".to_owned())))
+        );
+        assert_eq!(first.get("LEGS"), Some(&FieldValue::Integer(10)));
+        assert_eq!(first.get("PRICE"), Some(&FieldValue::Numeric(Some(12.5))));
+        assert_eq!(first.get("WEIGHT"), Some(&FieldValue::Double(0.5)));
+        assert_eq!(first.get("SWIMS"), Some(&FieldValue::Logical(Some(true))));
+        assert_eq!(
+            first.get("BORN"),
+            Some(&FieldValue::Date(Some(Date::new(2, 1, 1970))))
+        );
+        let second = &records[1];
+        assert_eq!(second.get("NAME"), Some(&FieldValue::Character(None)));
+        assert_eq!(second.get("PRICE"), Some(&FieldValue::Numeric(None)));
+        assert_eq!(second.get("SWIMS"), Some(&FieldValue::Logical(None)));
+        assert_eq!(second.get("BORN"), Some(&FieldValue::Date(None)));
+        assert_eq!(
+            second.get("UPDATED"),
+            Some(&FieldValue::DateTime(DateTime::new(
+                Date::new(2, 1, 1970),
+                Time::new(0, 0, 0)
+            )))
+        );
+        assert_eq!(
+            records[2].get("BORN"),
+            Some(&FieldValue::Date(Some(Date::new(31, 12, 1969))))
+        );
+    }
+
+    #[test]
+    fn tables_are_split_at_the_size_limit() {
+        let path = std::env::temp_dir().join("dbase_split_record_batches.dbf");
+        let builder =
+            TableWriterBuilder::new().add_character_field(FieldName::try_from("NAME").unwrap(), 10);
+        // The header, two records and the terminator
+        let max_file_size = table_info_size(&builder.hdr, 1) as u64 + 2 * 11 + 1;
+        let mut writer = builder
+            .build_record_batch_writer(&path)
+            .unwrap()
+            .max_file_size(max_file_size);
+        writer.write(&batch()).unwrap();
+        writer.write(&batch()).unwrap();
+        let tables = writer.finish().unwrap();
+
+        let second_path = std::env::temp_dir().join("dbase_split_record_batches_2.dbf");
+        let third_path = std::env::temp_dir().join("dbase_split_record_batches_3.dbf");
+        assert_eq!(
+            tables
+                .iter()
+                .map(|table| (table.path.clone(), table.num_records))
+                .collect::<Vec<(PathBuf, usize)>>(),
+            vec![
+                (path.clone(), 2),
+                (second_path.clone(), 2),
+                (third_path.clone(), 2)
+            ]
+        );
+        for table in &tables {
+            assert_eq!(std::fs::metadata(&table.path).unwrap().len(), max_file_size);
+            let records = crate::read(&table.path).unwrap();
+            std::fs::remove_file(&table.path).unwrap();
+            assert_eq!(records.len(), 2);
+        }
+    }
+
+    #[test]
+    fn incompatible_columns() {
+        let path = std::env::temp_dir().join("dbase_incompatible_record_batches.dbf");
+        let mut writer = TableWriterBuilder::new()
+            .add_logical_field(FieldName::try_from("NAME").unwrap())
+            .build_record_batch_writer(&path)
+            .unwrap();
+        assert!(writer.write(&batch()).is_err());
+
+        let mut writer = TableWriterBuilder::new()
+            .add_integer_field(FieldName::try_from("MISSING").unwrap())
+            .build_record_batch_writer(&path)
+            .unwrap();
+        assert!(writer.write(&batch()).is_err());
+        writer.finish().unwrap();
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,285 @@
+//! Command line tool to inspect, export, check and rewrite dBase tables
+//!
+//! Built with the `cli` feature: `cargo install dbase --features cli`.
+use std::fs::File;
+use std::io::{BufWriter, Read, Seek, Write};
+use std::process::ExitCode;
+
+use dbase::{CsvOptions, FieldType, Reader, Version};
+
+const USAGE: &str = "\
+Usage: dbase-cli <command> <table> [arguments]
+
+Commands:
+    info <table>                          print the header and the fields of the table
+    csv <table> [output] [--delimiter C]  export the records as CSV
+    json <table> [output]                 export the records as JSON Lines
+    validate <table>                      check the table, exits with 1 if it has problems
+    pack <table> <output>                 write the records not marked as deleted to output
+    convert <table> <version> <output>    convert the table to a version among
+                                          dbase3, dbase4, foxpro and visual-foxpro
+
+The table is read from the standard input when it is '-', in which case
+its memo file cannot be read. The exports are written to the standard output
+when no output is given.";
+
+const COMMANDS: [&str; 6] = ["info", "csv", "json", "validate", "pack", "convert"];
+
+/// Error of the tool, with the exit code it ends with
+#[derive(Debug)]
+struct Failure {
+    message: String,
+    code: u8,
+}
+
+impl Failure {
+    fn usage(message: &str) -> Self {
+        Self {
+            message: format!("{}\n\n{}", message, USAGE),
+            code: 2,
+        }
+    }
+}
+
+impl From<dbase::Error> for Failure {
+    fn from(error: dbase::Error) -> Self {
+        Self {
+            message: error.to_string(),
+            code: 2,
+        }
+    }
+}
+
+impl From<std::io::Error> for Failure {
+    fn from(error: std::io::Error) -> Self {
+        Self {
+            message: error.to_string(),
+            code: 2,
+        }
+    }
+}
+
+/// Returns the version named on the command line
+fn parse_version(name: &str) -> Option<Version> {
+    match name.to_ascii_lowercase().as_str() {
+        "dbase3" => Some(Version::DBase3 {
+            supports_memo: false,
+        }),
+        "dbase4" => Some(Version::DBase4 {
+            supports_memo: false,
+        }),
+        "foxpro" => Some(Version::FoxPro2 {
+            supports_memo: false,
+        }),
+        "visual-foxpro" => Some(Version::VisualFoxPro),
+        _ => None,
+    }
+}
+
+/// Opens the output file, or the standard output if there is none
+fn output(path: Option<&String>) -> Result<Box<dyn Write>, Failure> {
+    match path {
+        Some(path) => Ok(Box::new(BufWriter::new(File::create(path)?))),
+        None => Ok(Box::new(BufWriter::new(std::io::stdout().lock()))),
+    }
+}
+
+fn info<T: Read + Seek>(reader: &Reader<T>, out: &mut dyn Write) -> Result<(), Failure> {
+    let header = reader.header();
+    writeln!(out, "Version:        {:?}", header.file_type)?;
+    writeln!(out, "Last update:    {}", header.last_update)?;
+    writeln!(out, "Records:        {}", header.num_records)?;
+    writeln!(out, "Record size:    {}", header.size_of_record)?;
+    writeln!(out, "Code page mark: 0x{:02X}", header.code_page_mark)?;
+    writeln!(out)?;
+    writeln!(
+        out,
+        "{:<32} {:<10} {:>6} {:>8}",
+        "Name", "Type", "Length", "Decimals"
+    )?;
+    for field_info in reader
+        .fields()
+        .iter()
+        .filter(|field_info| !field_info.is_deletion_flag())
+    {
+        writeln!(
+            out,
+            "{:<32} {:<10} {:>6} {:>8}",
+            field_info.name(),
+            format!("{:?}", field_info.field_type()),
+            field_info.length(),
+            field_info.num_decimal_places()
+        )?;
+    }
+    Ok(())
+}
+
+/// Writes the records of the reader that are not marked as deleted to the output table
+fn pack<T: Read + Seek>(mut reader: Reader<T>, path: &str) -> Result<usize, Failure> {
+    if reader
+        .fields()
+        .iter()
+        .any(|field_info| field_info.field_type() == FieldType::Memo)
+    {
+        return Err(Failure {
+            message: "tables with memo fields cannot be packed, as memo files cannot be written"
+                .to_owned(),
+            code: 2,
+        });
+    }
+    let mut records = vec![];
+    for result in reader.iter_records_with_metadata() {
+        let (metadata, record) = result?;
+        if !metadata.is_deleted {
+            records.push(record);
+        }
+    }
+    dbase::TableWriterBuilder::from_reader(reader)
+        .build_with_file_dest(path)?
+        .write(&records)?;
+    Ok(records.len())
+}
+
+/// Runs the command on the table read by the reader, returns the exit code
+fn run<T: Read + Seek>(
+    mut reader: Reader<T>,
+    command: &str,
+    table: &str,
+    args: &[String],
+) -> Result<u8, Failure> {
+    match command {
+        "info" => info(&reader, &mut *output(None)?)?,
+        "csv" => {
+            let mut options = CsvOptions::new();
+            let mut path = None;
+            let mut args = args.iter();
+            while let Some(arg) = args.next() {
+                if arg == "--delimiter" {
+                    let delimiter = args
+                        .next()
+                        .filter(|delimiter| delimiter.len() == 1)
+                        .ok_or_else(|| Failure::usage("--delimiter expects one character"))?;
+                    options = options.delimiter(delimiter.as_bytes()[0]);
+                } else if path.is_none() {
+                    path = Some(arg);
+                } else {
+                    return Err(Failure::usage("too many arguments"));
+                }
+            }
+            let mut out = output(path)?;
+            reader.to_csv(&mut out, &options)?;
+            out.flush()?;
+        }
+        "json" => {
+            let mut out = output(args.first())?;
+            reader.to_jsonl(&mut out)?;
+            out.flush()?;
+        }
+        "validate" => {
+            if table == "-" {
+                return Err(Failure::usage("validate reads the table from a file"));
+            }
+            drop(reader);
+            let report = dbase::validate(table)?;
+            for issue in &report.issues {
+                println!("{}", issue);
+            }
+            println!(
+                "{} records checked, {} problems found",
+                report.num_records_checked,
+                report.issues.len()
+            );
+            return Ok(if report.is_valid() { 0 } else { 1 });
+        }
+        "pack" => {
+            let path = args
+                .first()
+                .ok_or_else(|| Failure::usage("pack expects an output table"))?;
+            let num_records = pack(reader, path)?;
+            println!("{} records written", num_records);
+        }
+        "convert" => {
+            let (version, path) = match args {
+                [version, path] => (version, path),
+                _ => {
+                    return Err(Failure::usage(
+                        "convert expects a version and an output table",
+                    ))
+                }
+            };
+            let version = parse_version(version)
+                .ok_or_else(|| Failure::usage(&format!("unknown version '{}'", version)))?;
+            let (mut out, report) =
+                dbase::convert(reader, version, BufWriter::new(File::create(path)?))?;
+            out.flush()?;
+            for conversion in &report.conversions {
+                println!(
+                    "field '{}' converted from {:?} to {:?}, {} values altered",
+                    conversion.field_name,
+                    conversion.from,
+                    conversion.to,
+                    conversion.num_values_altered
+                );
+            }
+            println!("{} records written", report.num_records);
+        }
+        _ => unreachable!("the commands are checked before the table is opened"),
+    }
+    Ok(0)
+}
+
+fn main() -> ExitCode {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let result = match args.as_slice() {
+        [flag] if flag == "-h" || flag == "--help" => {
+            println!("{}", USAGE);
+            Ok(0)
+        }
+        [command, ..] if !COMMANDS.contains(&command.as_str()) => {
+            Err(Failure::usage(&format!("unknown command '{}'", command)))
+        }
+        [command, table, rest @ ..] if table == "-" => Reader::from_stream(std::io::stdin().lock())
+            .map_err(Failure::from)
+            .and_then(|reader| run(reader, command, table, rest)),
+        [command, table, rest @ ..] => Reader::from_path(table)
+            .map_err(Failure::from)
+            .and_then(|reader| run(reader, command, table, rest)),
+        _ => Err(Failure::usage("missing command or table")),
+    };
+    match result {
+        Ok(code) => ExitCode::from(code),
+        Err(failure) => {
+            eprintln!("dbase-cli: {}", failure.message);
+            ExitCode::from(failure.code)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn versions_are_parsed() {
+        assert!(matches!(
+            parse_version("dBase3"),
+            Some(Version::DBase3 { .. })
+        ));
+        assert!(matches!(
+            parse_version("visual-foxpro"),
+            Some(Version::VisualFoxPro)
+        ));
+        assert!(parse_version("dbase9").is_none());
+    }
+
+    #[test]
+    fn info_lists_the_fields() {
+        let reader = Reader::from_path("tests/data/stations.dbf").unwrap();
+        let mut out = Vec::<u8>::new();
+        info(&reader, &mut out).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        assert!(text.contains("Records:        6"));
+        assert!(text.contains("marker-col"));
+        assert!(!text.contains("DeletionFlag"));
+    }
+}
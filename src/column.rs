@@ -0,0 +1,182 @@
+//! Module with the definition of the fn reading the values of a single field
+//!
+//! For each record, only the deletion flag and the bytes of the field are read,
+//! the source is moved over the other fields, so that summing a column of a wide table
+//! does not decode, nor allocate, the values of all its fields.
+use std::convert::TryFrom;
+use std::io::{Cursor, Read, Seek, SeekFrom};
+
+use crate::error::{Error, ErrorKind};
+use crate::reading::{ErrorPolicy, Reader};
+use crate::record::field::{FieldType, FieldValue};
+use crate::record::{field_names_match, FieldConversionError};
+
+impl<T: Read + Seek> Reader<T> {
+    /// Reads the values of the field with the given name of the remaining records,
+    /// the records marked as deleted are skipped
+    ///
+    /// The values are converted to `V`, empty values are `None`s: `f64`s are read from
+    /// Numeric, Currency and Double fields, `String`s from Character fields, `bool`s
+    /// from Logical fields and so on. Values that cannot be converted are an error,
+    /// or skipped with their record if the reader follows
+    /// [ErrorPolicy::SkipRecord](enum.ErrorPolicy.html#variant.SkipRecord).
+    ///
+    /// Field names are compared ignoring the ASCII case.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), dbase::Error> {
+    /// let mut reader = dbase::Reader::from_path("tests/data/stations.dbf")?;
+    /// let names = reader.read_column::<String>("name")?;
+    /// assert_eq!(names.len(), 6);
+    /// assert_eq!(names[0].as_deref(), Some("Van Dorn Street"));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn read_column<V>(&mut self, field_name: &str) -> Result<Vec<Option<V>>, Error>
+    where
+        V: TryFrom<FieldValue, Error = FieldConversionError>,
+    {
+        let position = self
+            .fields_info
+            .iter()
+            .position(|field_info| {
+                !field_info.is_deletion_flag() && field_names_match(field_info.name(), field_name)
+            })
+            .ok_or_else(|| Error {
+                record_num: 0,
+                field: None,
+                kind: ErrorKind::Message(format!("the table has no field named '{}'", field_name)),
+            })?;
+        let field_info = self.fields_info[position].clone();
+        // The deletion flag is the first field
+        let offset = self.fields_info[1..position]
+            .iter()
+            .map(|field_info| i64::from(field_info.field_length))
+            .sum::<i64>();
+        let length = usize::from(field_info.field_length);
+        let remaining = self.record_buffer.len() as i64 - 1 - offset - length as i64;
+
+        let num_records = self.end_record().saturating_sub(self.next_record) as usize;
+        let text_size = match field_info.field_type {
+            FieldType::Character => length,
+            _ => 0,
+        };
+        self.check_estimate(
+            num_records.saturating_mul(std::mem::size_of::<Option<V>>() + text_size),
+        )?;
+
+        let is_encrypted = self.cipher().is_some();
+        let mut values = Vec::with_capacity(num_records);
+        let mut bytes = vec![0u8; length];
+        while self.next_record < self.end_record() {
+            let record_num = self.next_record as usize;
+            let mut deletion_flag = [0u8; 1];
+            if is_encrypted {
+                // The records are encrypted as a whole
+                self.source
+                    .read_exact(&mut self.record_buffer)
+                    .map_err(|error| Error::io_error(error, record_num))?;
+                self.decrypt_record_buffer(record_num as u32);
+                deletion_flag[0] = self.record_buffer[0];
+                let start = 1 + offset as usize;
+                bytes.copy_from_slice(&self.record_buffer[start..start + length]);
+            } else {
+                self.source
+                    .read_exact(&mut deletion_flag)
+                    .and_then(|_| match offset {
+                        0 => Ok(0),
+                        _ => self.source.seek(SeekFrom::Current(offset)),
+                    })
+                    .and_then(|_| self.source.read_exact(&mut bytes))
+                    .and_then(|_| match remaining {
+                        0 => Ok(0),
+                        _ => self.source.seek(SeekFrom::Current(remaining)),
+                    })
+                    .map_err(|error| Error::io_error(error, record_num))?;
+            }
+            self.next_record += 1;
+            if deletion_flag[0] == b'*' {
+                continue;
+            }
+
+            let value = FieldValue::read_from(
+                &mut Cursor::new(&bytes),
+                &mut self.memo_reader,
+                &field_info,
+                &self.options,
+            )
+            .and_then(|value| match V::try_from(value) {
+                Ok(value) => Ok(Some(value)),
+                Err(FieldConversionError::NoneValue) => Ok(None),
+                Err(error) => Err(ErrorKind::BadConversion(error)),
+            });
+            match value {
+                Ok(value) => values.push(value),
+                Err(kind) => {
+                    let is_io_error = matches!(kind, ErrorKind::IoError(_));
+                    if is_io_error || self.options.error_policy == ErrorPolicy::Fail {
+                        return Err(Error {
+                            record_num,
+                            field: Some(field_info),
+                            kind,
+                        });
+                    }
+                }
+            }
+        }
+        Ok(values)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::{FieldName, Record, TableWriterBuilder};
+
+    fn table() -> Vec<u8> {
+        let records = [(Some(12.5), "a"), (None, "b"), (Some(2.0), "c")]
+            .iter()
+            .map(|(amount, code)| {
+                Record::builder()
+                    .set("CODE", *code)
+                    .set("AMOUNT", *amount)
+                    .set("NOTE", "wide")
+                    .build()
+            })
+            .collect::<Vec<_>>();
+        let mut table = TableWriterBuilder::new()
+            .add_character_field(FieldName::try_from("CODE").unwrap(), 2)
+            .add_numeric_field(FieldName::try_from("AMOUNT").unwrap(), 8, 2)
+            .add_character_field(FieldName::try_from("NOTE").unwrap(), 200)
+            .build_with_dest(Cursor::new(Vec::<u8>::new()))
+            .write(&records)
+            .unwrap()
+            .into_inner();
+        // The last record is deleted
+        let offset = table.len() - (1 + 2 + 8 + 200) - 1;
+        table[offset] = b'*';
+        table
+    }
+
+    #[test]
+    fn values_of_one_field() {
+        let table = table();
+        let mut reader = Reader::new(Cursor::new(&table)).unwrap();
+        let amounts = reader.read_column::<f64>("amount").unwrap();
+        assert_eq!(amounts, vec![Some(12.5), None]);
+
+        let mut reader = Reader::new(Cursor::new(&table)).unwrap();
+        reader.set_record_range(1..2).unwrap();
+        assert_eq!(
+            reader.read_column::<String>("NOTE").unwrap(),
+            vec![Some("wide".to_owned())]
+        );
+
+        let mut reader = Reader::new(Cursor::new(&table)).unwrap();
+        assert!(reader.read_column::<bool>("CODE").is_err());
+        assert!(reader.read_column::<f64>("PRICE").is_err());
+    }
+}
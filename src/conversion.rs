@@ -0,0 +1,424 @@
+//! Module with the definition of fn's and struct's to convert tables
+//! from one version of dBase to another
+use std::io::{Read, Seek, Write};
+
+use crate::header::{TableFlags, Version};
+use crate::reading::{Reader, RecordValues};
+use crate::record::field::{FieldType, FieldValue};
+use crate::record::FieldInfo;
+use crate::sql::text_value;
+use crate::writing::{FieldWriter, TableWriterBuilder, WritableRecord};
+use crate::{Error, FieldIOError};
+
+/// Length of the Numeric fields Integer fields are converted to,
+/// enough for `-2147483648`
+const INTEGER_LENGTH: u8 = 11;
+/// Length of the Numeric fields Double and Currency fields are converted to
+const DECIMAL_LENGTH: u8 = 20;
+const CURRENCY_DECIMALS: u8 = 4;
+/// Length of the Character fields DateTime fields are converted to
+const DATETIME_LENGTH: u8 = 19;
+/// Max length of a Character field
+const MAX_CHARACTER_LENGTH: u8 = 254;
+
+/// The conversion of a field whose type does not exist in the target version
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldConversion {
+    /// The name of the field
+    pub field_name: String,
+    /// The type of the field in the source table
+    pub from: FieldType,
+    /// The type of the field in the converted table
+    pub to: FieldType,
+    /// The number of records whose value could not be kept as is:
+    /// rounded, truncated, or (if too large for the field) left empty
+    pub num_values_altered: usize,
+}
+
+impl FieldConversion {
+    /// Returns whether some values were altered by the conversion
+    pub fn is_lossy(&self) -> bool {
+        self.num_values_altered > 0
+    }
+}
+
+/// Report of a conversion made by [convert](fn.convert.html)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ConversionReport {
+    /// The number of records written, the records marked as deleted are not converted
+    pub num_records: usize,
+    /// The fields whose type was changed
+    pub conversions: Vec<FieldConversion>,
+}
+
+impl ConversionReport {
+    /// Returns the conversions that altered some values
+    pub fn lossy_conversions(&self) -> impl Iterator<Item = &FieldConversion> {
+        self.conversions
+            .iter()
+            .filter(|conversion| conversion.is_lossy())
+    }
+
+    /// Returns whether all the values were converted as is
+    pub fn is_lossless(&self) -> bool {
+        self.lossy_conversions().next().is_none()
+    }
+}
+
+/// Returns whether the fields of the type can be written as is in tables of the version
+fn supports(version: Version, field_type: FieldType) -> bool {
+    // Memo files are not written along with the table
+    field_type != FieldType::Memo && version.supports_field_type(field_type)
+}
+
+/// Returns the field the given field is converted to
+fn converted_field(field_info: &FieldInfo) -> FieldInfo {
+    let (field_type, length, num_decimal_places) = match field_info.field_type {
+        FieldType::Float => (
+            FieldType::Numeric,
+            field_info.field_length,
+            field_info.num_decimal_places,
+        ),
+        FieldType::Integer | FieldType::Long | FieldType::Autoincrement => {
+            (FieldType::Numeric, u16::from(INTEGER_LENGTH), 0)
+        }
+        FieldType::Double | FieldType::DBase7Double => (
+            FieldType::Numeric,
+            u16::from(DECIMAL_LENGTH),
+            field_info.num_decimal_places.min(DECIMAL_LENGTH - 2),
+        ),
+        FieldType::Currency => (
+            FieldType::Numeric,
+            u16::from(DECIMAL_LENGTH),
+            CURRENCY_DECIMALS,
+        ),
+        FieldType::DateTime | FieldType::Timestamp => {
+            (FieldType::Character, u16::from(DATETIME_LENGTH), 0)
+        }
+        FieldType::Memo => (FieldType::Character, u16::from(MAX_CHARACTER_LENGTH), 0),
+        field_type => (
+            field_type,
+            field_info.field_length,
+            field_info.num_decimal_places,
+        ),
+    };
+    FieldInfo {
+        field_type,
+        field_length: length,
+        num_decimal_places,
+        ..field_info.clone()
+    }
+}
+
+/// Converts the number to be written in the Numeric field,
+/// returns whether it was altered
+fn convert_number(value: f64, field_info: &FieldInfo) -> (FieldValue, bool) {
+    let decimals = usize::from(field_info.num_decimal_places);
+    let text = format!("{:.*}", decimals, value);
+    if !value.is_finite() || text.len() > usize::from(field_info.field_length) {
+        return (FieldValue::Numeric(None), true);
+    }
+    let rounded = text.parse::<f64>().unwrap_or(value);
+    (FieldValue::Numeric(Some(rounded)), rounded != value)
+}
+
+/// Converts the value to be written in the converted field,
+/// returns whether it was altered
+fn convert_value(value: FieldValue, field_info: &FieldInfo) -> (FieldValue, bool) {
+    match value {
+        FieldValue::Float(value) => (FieldValue::Numeric(value.map(f64::from)), false),
+        FieldValue::Integer(value) | FieldValue::Autoincrement(value) => {
+            (FieldValue::Numeric(Some(f64::from(value))), false)
+        }
+        FieldValue::Long(value) => (FieldValue::Numeric(value.map(f64::from)), false),
+        FieldValue::Double(value) | FieldValue::Currency(value) => {
+            convert_number(value, field_info)
+        }
+        FieldValue::DBase7Double(Some(value)) => convert_number(value, field_info),
+        FieldValue::DBase7Double(None) => (FieldValue::Numeric(None), false),
+        FieldValue::DateTime(_) | FieldValue::Timestamp(_) => {
+            (FieldValue::Character(text_value(&value)), false)
+        }
+        FieldValue::Memo(mut text) => {
+            let max_length = usize::from(field_info.field_length);
+            let is_truncated = text.len() > max_length;
+            if is_truncated {
+                let end = (0..=max_length)
+                    .rev()
+                    .find(|end| text.is_char_boundary(*end))
+                    .unwrap_or(0);
+                text.truncate(end);
+            }
+            (FieldValue::Character(Some(text)), is_truncated)
+        }
+        value => (value, false),
+    }
+}
+
+struct ConvertedRecord(Vec<FieldValue>);
+
+impl WritableRecord for ConvertedRecord {
+    fn write_using<'a, W: Write>(
+        &self,
+        field_writer: &mut FieldWriter<'a, W>,
+    ) -> Result<(), FieldIOError> {
+        for value in &self.0 {
+            field_writer.write_next_field_value(value)?;
+        }
+        Ok(())
+    }
+}
+
+/// Rewrites the remaining records of the table as a table of the `target_version`.
+///
+/// Fields whose type does not exist in the target version are converted:
+///
+/// | Field type | Converted to                                      |
+/// |------------|---------------------------------------------------|
+/// | Float      | Numeric, with the same length and decimals        |
+/// | Integer    | Numeric(11, 0)                                    |
+/// | Double     | Numeric(20, decimals of the field)                |
+/// | Currency   | Numeric(20, 4)                                    |
+/// | DateTime   | Character(19), formatted as `YYYY-MM-DD HH:MM:SS` |
+///
+/// The Long and Autoincrement fields of dBase 7 are converted like Integer fields,
+/// its Double fields like Double fields and its Timestamp fields like DateTime fields.
+///
+/// Memo fields are always converted to Character(254) fields, as memo files
+/// cannot be written yet.
+///
+/// Returns the destination and the report of the conversions,
+/// the records marked as deleted are not written.
+///
+/// # Example
+///
+/// ```
+/// # fn main() -> Result<(), dbase::Error> {
+/// use std::io::Cursor;
+///
+/// let reader = dbase::Reader::from_path("tests/data/stations.dbf")?;
+/// let (_, report) = dbase::convert(
+///     reader,
+///     dbase::Version::DBase3 { supports_memo: false },
+///     Cursor::new(Vec::<u8>::new()),
+/// )?;
+/// assert!(report.is_lossless());
+/// # Ok(())
+/// # }
+/// ```
+pub fn convert<T: Read + Seek, W: Write>(
+    mut reader: Reader<T>,
+    target_version: Version,
+    dst: W,
+) -> Result<(W, ConversionReport), Error> {
+    let mut report = ConversionReport::default();
+    let mut fields_info = Vec::<FieldInfo>::with_capacity(reader.fields_info.len());
+    for field_info in &reader.fields_info {
+        if field_info.is_deletion_flag() {
+            continue;
+        }
+        if supports(target_version, field_info.field_type) {
+            fields_info.push(field_info.clone());
+        } else {
+            let converted = converted_field(field_info);
+            report.conversions.push(FieldConversion {
+                field_name: field_info.name.clone(),
+                from: field_info.field_type,
+                to: converted.field_type,
+                num_values_altered: 0,
+            });
+            fields_info.push(converted);
+        }
+    }
+
+    let mut records = Vec::<ConvertedRecord>::new();
+    while let Some(record) = reader.read_next_record_as::<RecordValues>() {
+        let record = record?;
+        if record.is_deleted {
+            continue;
+        }
+        let mut conversions = report.conversions.iter_mut();
+        let values = record
+            .values
+            .into_iter()
+            .zip(&reader.fields_info[1..])
+            .zip(&fields_info)
+            .map(|((value, source_field), field_info)| {
+                if supports(target_version, source_field.field_type) {
+                    return value;
+                }
+                let (value, is_altered) = convert_value(value, field_info);
+                if let Some(conversion) = conversions.next() {
+                    conversion.num_values_altered += usize::from(is_altered);
+                }
+                value
+            })
+            .collect();
+        records.push(ConvertedRecord(values));
+    }
+    report.num_records = records.len();
+
+    let cipher = reader.cipher().cloned();
+    let mut header = reader.header;
+    header.file_type = target_version;
+    // Neither memo files nor indexes are written along with the table
+    header.table_flags = TableFlags::default();
+    header.update_date();
+    let backlink = if target_version.has_backlink() {
+        reader.backlink
+    } else {
+        None
+    };
+    let dst = TableWriterBuilder {
+        v: fields_info,
+        hdr: header,
+        backlink,
+        defaults: Default::default(),
+        max_file_size: crate::writing::MAX_TABLE_SIZE,
+        overflow_policy: Default::default(),
+        encoding: reader.options.encoding,
+        cipher,
+    }
+    .build_with_dest(dst)
+    .write(&records)?;
+    Ok((dst, report))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::convert::TryFrom;
+    use std::io::Cursor;
+
+    use crate::record::field::{Date, DateTime, Time};
+    use crate::{FieldName, Record, TableWriterBuilder};
+
+    fn vfp_table() -> Cursor<Vec<u8>> {
+        let records = [(1, 0.5, 12.3456), (-42, 1.0 / 3.0, -1.5)]
+            .iter()
+            .map(|(count, ratio, price)| {
+                let mut record = Record::default();
+                record.insert("COUNT".to_owned(), FieldValue::Integer(*count));
+                record.insert("RATIO".to_owned(), FieldValue::Double(*ratio));
+                record.insert("PRICE".to_owned(), FieldValue::Currency(*price));
+                record.insert(
+                    "SEEN".to_owned(),
+                    FieldValue::DateTime(DateTime::new(Date::new(1, 2, 2020), Time::new(13, 4, 5))),
+                );
+                record.insert("SCORE".to_owned(), FieldValue::Float(Some(2.5)));
+                record
+            })
+            .collect::<Vec<Record>>();
+        let mut field = FieldInfo::new(
+            FieldName::try_from("RATIO").unwrap(),
+            FieldType::Double,
+            FieldType::Double.size().unwrap(),
+        );
+        field.num_decimal_places = 2;
+        let mut builder = TableWriterBuilder::new()
+            .add_integer_field(FieldName::try_from("COUNT").unwrap())
+            .add_currency_field(FieldName::try_from("PRICE").unwrap())
+            .add_datetime_field(FieldName::try_from("SEEN").unwrap())
+            .add_float_field(FieldName::try_from("SCORE").unwrap(), 6, 2);
+        builder.v.insert(1, field);
+        builder.hdr.file_type = Version::VisualFoxPro;
+        let mut cursor = builder
+            .build_with_dest(Cursor::new(Vec::<u8>::new()))
+            .write(&records)
+            .unwrap();
+        cursor.set_position(0);
+        cursor
+    }
+
+    #[test]
+    fn convert_down_to_dbase3() {
+        let reader = Reader::new(vfp_table()).unwrap();
+        let (mut cursor, report) = convert(
+            reader,
+            Version::DBase3 {
+                supports_memo: false,
+            },
+            Cursor::new(Vec::<u8>::new()),
+        )
+        .unwrap();
+        assert_eq!(report.num_records, 2);
+        assert_eq!(
+            report
+                .conversions
+                .iter()
+                .map(|conversion| (
+                    conversion.from,
+                    conversion.to,
+                    conversion.num_values_altered
+                ))
+                .collect::<Vec<_>>(),
+            vec![
+                (FieldType::Integer, FieldType::Numeric, 0),
+                (FieldType::Double, FieldType::Numeric, 1),
+                (FieldType::Currency, FieldType::Numeric, 0),
+                (FieldType::DateTime, FieldType::Character, 0),
+                (FieldType::Float, FieldType::Numeric, 0),
+            ]
+        );
+        assert!(!report.is_lossless());
+        assert_eq!(report.lossy_conversions().count(), 1);
+
+        cursor.set_position(0);
+        let mut reader = Reader::new(cursor).unwrap();
+        assert_eq!(u8::from(reader.header().file_type), 0x03);
+        let records = reader.read().unwrap();
+        assert_eq!(
+            records[1].get("COUNT"),
+            Some(&FieldValue::Numeric(Some(-42.0)))
+        );
+        assert_eq!(
+            records[1].get("RATIO"),
+            Some(&FieldValue::Numeric(Some(0.33)))
+        );
+        assert_eq!(
+            records[0].get("PRICE"),
+            Some(&FieldValue::Numeric(Some(12.3456)))
+        );
+        assert_eq!(
+            records[0].get("SEEN"),
+            Some(&FieldValue::Character(Some(
+                "2020-02-01 13:04:05".to_owned()
+            )))
+        );
+        assert_eq!(
+            records[0].get("SCORE"),
+            Some(&FieldValue::Numeric(Some(2.5)))
+        );
+    }
+
+    #[test]
+    fn convert_up_to_visual_fox_pro() {
+        let reader = Reader::from_path("tests/data/line.dbf").unwrap();
+        let records = Reader::from_path("tests/data/line.dbf")
+            .unwrap()
+            .read()
+            .unwrap();
+        let (mut cursor, report) =
+            convert(reader, Version::VisualFoxPro, Cursor::new(Vec::<u8>::new())).unwrap();
+        assert!(report.conversions.is_empty());
+
+        cursor.set_position(0);
+        let mut reader = Reader::new(cursor).unwrap();
+        assert!(reader.header().file_type.is_visual_fox_pro());
+        assert_eq!(reader.read().unwrap(), records);
+    }
+
+    #[test]
+    fn memo_values_are_truncated() {
+        let field_info = converted_field(&FieldInfo::new(
+            FieldName::try_from("NOTES").unwrap(),
+            FieldType::Memo,
+            10,
+        ));
+        assert_eq!(field_info.field_length, u16::from(MAX_CHARACTER_LENGTH));
+        let (value, is_altered) = convert_value(FieldValue::Memo("é".repeat(200)), &field_info);
+        assert!(is_altered);
+        assert_eq!(value, FieldValue::Character(Some("é".repeat(127))));
+    }
+}
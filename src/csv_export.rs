@@ -0,0 +1,272 @@
+//! Module with the definition of fn's and struct's to export dBase files as CSV
+use std::io::{Read, Seek, Write};
+
+use crate::error::Error;
+use crate::reading::{Reader, RecordValues};
+use crate::record::field::FieldValue;
+use crate::record::FieldInfo;
+
+/// Options of the CSV export
+///
+/// # Example
+///
+/// ```
+/// let options = dbase::CsvOptions::new()
+///     .delimiter(b';')
+///     .decimal_separator(',')
+///     .date_format("%d/%m/%Y")
+///     .null_value("NULL");
+/// ```
+#[derive(Debug, Clone)]
+pub struct CsvOptions {
+    delimiter: u8,
+    has_headers: bool,
+    date_format: String,
+    datetime_format: String,
+    decimal_separator: char,
+    null_value: String,
+    include_deleted: bool,
+}
+
+impl CsvOptions {
+    /// Creates the default options
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The byte that separates the values, a comma by default
+    pub fn delimiter(mut self, delimiter: u8) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+
+    /// Whether the first line contains the names of the fields, true by default
+    pub fn has_headers(mut self, has_headers: bool) -> Self {
+        self.has_headers = has_headers;
+        self
+    }
+
+    /// The format of Date values, using chrono's syntax, "%Y-%m-%d" by default
+    pub fn date_format<S: Into<String>>(mut self, format: S) -> Self {
+        self.date_format = format.into();
+        self
+    }
+
+    /// The format of DateTime values, using chrono's syntax, "%Y-%m-%dT%H:%M:%S" by default
+    pub fn datetime_format<S: Into<String>>(mut self, format: S) -> Self {
+        self.datetime_format = format.into();
+        self
+    }
+
+    /// The character separating the integer and fractional part of numbers, a dot by default
+    pub fn decimal_separator(mut self, separator: char) -> Self {
+        self.decimal_separator = separator;
+        self
+    }
+
+    /// The text written for empty values, an empty string by default
+    pub fn null_value<S: Into<String>>(mut self, value: S) -> Self {
+        self.null_value = value.into();
+        self
+    }
+
+    /// Whether the records marked as deleted are exported, false by default
+    pub fn include_deleted(mut self, include: bool) -> Self {
+        self.include_deleted = include;
+        self
+    }
+
+    fn number(&self, text: String) -> String {
+        if self.decimal_separator == '.' {
+            text
+        } else {
+            text.replace('.', &self.decimal_separator.to_string())
+        }
+    }
+
+    fn format(&self, value: FieldValue, field_info: &FieldInfo) -> String {
+        let decimals = usize::from(field_info.num_decimal_places);
+        match value {
+            FieldValue::Character(Some(text)) | FieldValue::Memo(text) => text,
+            FieldValue::Numeric(Some(value)) => self.number(format!("{:.*}", decimals, value)),
+            FieldValue::Float(Some(value)) => self.number(value.to_string()),
+            FieldValue::Double(value) | FieldValue::DBase7Double(Some(value)) => {
+                self.number(value.to_string())
+            }
+            FieldValue::Currency(value) => self.number(format!("{:.4}", value)),
+            FieldValue::Integer(value)
+            | FieldValue::Long(Some(value))
+            | FieldValue::Autoincrement(value) => value.to_string(),
+            FieldValue::NumericInteger(Some(value)) => value.to_string(),
+            FieldValue::Logical(Some(value)) => value.to_string(),
+            FieldValue::Date(Some(date)) => chrono::NaiveDate::from(date)
+                .format(&self.date_format)
+                .to_string(),
+            FieldValue::DateTime(date_time) | FieldValue::Timestamp(Some(date_time)) => {
+                let time = date_time.time();
+                chrono::NaiveDate::from(date_time.date())
+                    .and_hms_opt(time.hours(), time.minutes(), time.seconds())
+                    .map(|date_time| date_time.format(&self.datetime_format).to_string())
+                    .unwrap_or_else(|| self.null_value.clone())
+            }
+            FieldValue::Character(None)
+            | FieldValue::Numeric(None)
+            | FieldValue::Float(None)
+            | FieldValue::Logical(None)
+            | FieldValue::Date(None)
+            | FieldValue::NumericInteger(None)
+            | FieldValue::Long(None)
+            | FieldValue::DBase7Double(None)
+            | FieldValue::Timestamp(None) => self.null_value.clone(),
+        }
+    }
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        Self {
+            delimiter: b',',
+            has_headers: true,
+            date_format: "%Y-%m-%d".to_owned(),
+            datetime_format: "%Y-%m-%dT%H:%M:%S".to_owned(),
+            decimal_separator: '.',
+            null_value: String::new(),
+            include_deleted: false,
+        }
+    }
+}
+
+fn csv_error(error: csv::Error, record_num: usize) -> Error {
+    Error::io_error(std::io::Error::from(error), record_num)
+}
+
+impl<T: Read + Seek> Reader<T> {
+    /// Writes the remaining records as CSV, the columns are in the order of the fields.
+    ///
+    /// Returns the number of records written.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), dbase::Error> {
+    /// let mut reader = dbase::Reader::from_path("tests/data/line.dbf")?;
+    /// let mut csv = Vec::<u8>::new();
+    /// reader.to_csv(&mut csv, &dbase::CsvOptions::default())?;
+    /// assert!(csv.starts_with(b"name\n"));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn to_csv<W: Write>(&mut self, dst: W, options: &CsvOptions) -> Result<usize, Error> {
+        let mut writer = csv::WriterBuilder::new()
+            .delimiter(options.delimiter)
+            .from_writer(dst);
+        let fields_info = self
+            .fields_info
+            .iter()
+            .filter(|field_info| !field_info.is_deletion_flag())
+            .cloned()
+            .collect::<Vec<FieldInfo>>();
+        if options.has_headers {
+            writer
+                .write_record(fields_info.iter().map(FieldInfo::name))
+                .map_err(|error| csv_error(error, 0))?;
+        }
+
+        let mut num_records = 0;
+        while let Some(record) = self.read_next_record_as::<RecordValues>() {
+            let record = record?;
+            if record.is_deleted && !options.include_deleted {
+                continue;
+            }
+            let row = record
+                .values
+                .into_iter()
+                .zip(&fields_info)
+                .map(|(value, field_info)| options.format(value, field_info));
+            writer
+                .write_record(row)
+                .map_err(|error| csv_error(error, num_records))?;
+            num_records += 1;
+        }
+        writer
+            .flush()
+            .map_err(|error| Error::io_error(error, num_records))?;
+        Ok(num_records)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::convert::TryFrom;
+    use std::io::Cursor;
+
+    use crate::record::field::Date;
+    use crate::{FieldName, Record, TableWriterBuilder};
+
+    fn table() -> Cursor<Vec<u8>> {
+        let records = [
+            ("Ferris", Some(1.5), Some(Date::new(15, 5, 2015))),
+            ("Corro, the unsafe", None, None),
+            ("Deleted", Some(3.0), None),
+        ]
+        .iter()
+        .map(|(name, price, date)| {
+            let mut record = Record::default();
+            record.insert(
+                "NAME".to_owned(),
+                FieldValue::Character(Some(name.to_string())),
+            );
+            record.insert("PRICE".to_owned(), FieldValue::Numeric(*price));
+            record.insert("SINCE".to_owned(), FieldValue::Date(*date));
+            record
+        })
+        .collect::<Vec<Record>>();
+
+        let mut content = TableWriterBuilder::new()
+            .add_character_field(FieldName::try_from("NAME").unwrap(), 20)
+            .add_numeric_field(FieldName::try_from("PRICE").unwrap(), 8, 2)
+            .add_date_field(FieldName::try_from("SINCE").unwrap())
+            .build_with_dest(Cursor::new(Vec::<u8>::new()))
+            .write(&records)
+            .unwrap()
+            .into_inner();
+        // Mark the last record as deleted
+        let first_record = usize::from(u16::from_le_bytes([content[8], content[9]]));
+        let record_size = usize::from(u16::from_le_bytes([content[10], content[11]]));
+        content[first_record + 2 * record_size] = b'*';
+        Cursor::new(content)
+    }
+
+    #[test]
+    fn export_to_csv() {
+        let mut csv = Vec::<u8>::new();
+        let num_records = Reader::new(table())
+            .unwrap()
+            .to_csv(&mut csv, &CsvOptions::default())
+            .unwrap();
+        assert_eq!(num_records, 2);
+        assert_eq!(
+            String::from_utf8(csv).unwrap(),
+            "NAME,PRICE,SINCE\nFerris,1.50,2015-05-15\n\"Corro, the unsafe\",,\n"
+        );
+
+        let options = CsvOptions::new()
+            .delimiter(b';')
+            .has_headers(false)
+            .date_format("%d/%m/%Y")
+            .decimal_separator(',')
+            .null_value("NULL")
+            .include_deleted(true);
+        let mut csv = Vec::<u8>::new();
+        Reader::new(table())
+            .unwrap()
+            .to_csv(&mut csv, &options)
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(csv).unwrap(),
+            "Ferris;1,50;15/05/2015\nCorro, the unsafe;NULL;NULL\nDeleted;3,00;NULL\n"
+        );
+    }
+}
@@ -0,0 +1,458 @@
+//! Module with the definition of fn's and struct's to read Visual FoxPro
+//! database containers (.dbc)
+//!
+//! A database container is itself a table (with a .dct memo file), each of its
+//! records describes an object of the database: the database itself, its tables,
+//! the fields of the tables, indexes, views, etc.
+//!
+//! The container is what gives tables and fields their long names, as well as
+//! properties like field captions or validation rules.
+use std::fs::File;
+use std::io::{BufReader, Read, Seek};
+use std::path::{Path, PathBuf};
+
+use byteorder::{LittleEndian, ReadBytesExt};
+
+use crate::error::{Error, ErrorKind, FieldIOError};
+use crate::reading::{FieldIterator, ReadableRecord, Reader, ReadingOptions};
+use crate::record::field::FieldValue;
+
+/// Extension of the memo file of database containers
+const DBC_MEMO_EXTENSION: &str = "dct";
+
+/// Size of the header of a property: its size (u32), its type (u16) and its id (u8)
+const PROPERTY_HEADER_SIZE: usize = 7;
+
+// Ids of the properties we know of
+const PROPERTY_PATH: u8 = 0x01;
+const PROPERTY_COMMENT: u8 = 0x07;
+const PROPERTY_RULE_EXPRESSION: u8 = 0x09;
+const PROPERTY_RULE_TEXT: u8 = 0x0A;
+const PROPERTY_DEFAULT_VALUE: u8 = 0x0B;
+const PROPERTY_CAPTION: u8 = 0x3A;
+
+/// One record of the container
+struct DatabaseObject {
+    is_deleted: bool,
+    object_id: i32,
+    parent_id: i32,
+    object_type: String,
+    object_name: String,
+    properties: Vec<(u8, String)>,
+}
+
+impl DatabaseObject {
+    fn property(&self, id: u8) -> Option<String> {
+        self.properties
+            .iter()
+            .find(|(property_id, _)| *property_id == id)
+            .map(|(_, value)| value.clone())
+    }
+}
+
+/// Reads the properties stored in the 'PROPERTY' memo of an object
+///
+/// Each property is stored as its total size, its type, its id and then its value,
+/// values are NUL terminated strings.
+fn parse_properties(mut bytes: &[u8]) -> Vec<(u8, String)> {
+    let mut properties = Vec::<(u8, String)>::new();
+    while bytes.len() >= PROPERTY_HEADER_SIZE {
+        let size = (&bytes[..4]).read_u32::<LittleEndian>().unwrap_or(0) as usize;
+        if size < PROPERTY_HEADER_SIZE {
+            break;
+        }
+        // Trailing NULs of memos are not kept, so the last property may be shorter
+        let size = size.min(bytes.len());
+        let id = bytes[6];
+        let value = &bytes[PROPERTY_HEADER_SIZE..size];
+        let value = match value.iter().position(|b| *b == 0) {
+            Some(pos) => &value[..pos],
+            None => value,
+        };
+        properties.push((id, String::from_utf8_lossy(value).into_owned()));
+        bytes = &bytes[size..];
+    }
+    properties
+}
+
+impl ReadableRecord for DatabaseObject {
+    fn read_using<T>(field_iterator: &mut FieldIterator<T>) -> Result<Self, FieldIOError>
+    where
+        T: Read + Seek,
+    {
+        let mut object = DatabaseObject {
+            is_deleted: field_iterator.read_deletion_flag()?,
+            object_id: 0,
+            parent_id: 0,
+            object_type: String::new(),
+            object_name: String::new(),
+            properties: vec![],
+        };
+        while let Some(field_info) = field_iterator.fields_info.peek().copied() {
+            match field_info.name().to_uppercase().as_str() {
+                "PROPERTY" => {
+                    object.properties =
+                        parse_properties(&field_iterator.read_next_field_memo_bytes()?)
+                }
+                "OBJECTID" | "PARENTID" | "OBJECTTYPE" | "OBJECTNAME" => {
+                    let field = field_iterator.read_next_field()?;
+                    match (field.name.to_uppercase().as_str(), field.value) {
+                        ("OBJECTID", FieldValue::Integer(id)) => object.object_id = id,
+                        ("PARENTID", FieldValue::Integer(id)) => object.parent_id = id,
+                        ("OBJECTTYPE", FieldValue::Character(s)) => {
+                            object.object_type = s.unwrap_or_default()
+                        }
+                        ("OBJECTNAME", FieldValue::Character(s)) => {
+                            object.object_name = s.unwrap_or_default()
+                        }
+                        _ => {
+                            return Err(FieldIOError::new(
+                                ErrorKind::IncompatibleType,
+                                Some(field_info.clone()),
+                            ))
+                        }
+                    }
+                }
+                _ => field_iterator
+                    .skip_next_field()
+                    .map_err(|error| FieldIOError::new(error.into(), None))?,
+            }
+        }
+        Ok(object)
+    }
+}
+
+/// A field of a table of the database, as described by the database container
+#[derive(Debug, Clone)]
+pub struct DatabaseField {
+    name: String,
+    caption: Option<String>,
+    comment: Option<String>,
+    rule_expression: Option<String>,
+    rule_text: Option<String>,
+    default_value: Option<String>,
+}
+
+impl DatabaseField {
+    /// Returns the long name of the field
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the caption of the field, used by applications as the title of the column
+    pub fn caption(&self) -> Option<&str> {
+        self.caption.as_deref()
+    }
+
+    /// Returns the comment of the field
+    pub fn comment(&self) -> Option<&str> {
+        self.comment.as_deref()
+    }
+
+    /// Returns the validation rule (an expression) of the field
+    pub fn rule_expression(&self) -> Option<&str> {
+        self.rule_expression.as_deref()
+    }
+
+    /// Returns the message shown when the validation rule is not respected
+    pub fn rule_text(&self) -> Option<&str> {
+        self.rule_text.as_deref()
+    }
+
+    /// Returns the default value (an expression) of the field
+    pub fn default_value(&self) -> Option<&str> {
+        self.default_value.as_deref()
+    }
+}
+
+/// A table that belongs to the database
+#[derive(Debug, Clone)]
+pub struct DatabaseTable {
+    name: String,
+    path: PathBuf,
+    comment: Option<String>,
+    fields: Vec<DatabaseField>,
+}
+
+impl DatabaseTable {
+    /// Returns the long name of the table
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the path of the .dbf file of the table
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the comment of the table
+    pub fn comment(&self) -> Option<&str> {
+        self.comment.as_deref()
+    }
+
+    /// Returns the fields of the table, in the same order as in the .dbf file
+    ///
+    /// The .dbf file only stores the first 10 characters of the field names,
+    /// these are the full ones.
+    pub fn fields(&self) -> &[DatabaseField] {
+        &self.fields
+    }
+}
+
+/// A Visual FoxPro database, read from its container (.dbc)
+///
+/// # Example
+///
+/// ```no_run
+/// # fn main() -> Result<(), dbase::Error> {
+/// let database = dbase::Database::from_path("data/sales.dbc")?;
+/// for table in database.tables() {
+///     println!("{} -> {}", table.name(), table.path().display());
+/// }
+/// let mut reader = database.open_table("customer_orders")?;
+/// let records = reader.read()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Database {
+    tables: Vec<DatabaseTable>,
+}
+
+impl Database {
+    /// Reads the database container at the given path, its memo file
+    /// (.dct) is expected to be next to it
+    ///
+    /// Paths of the tables are relative to the directory of the container
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let directory = path.parent().unwrap_or_else(|| Path::new(""));
+        let mut reader = Reader::from_path_with_memo_path(
+            path,
+            Some(path.with_extension(DBC_MEMO_EXTENSION)),
+            ReadingOptions::default(),
+        )?;
+        Self::read_from(&mut reader, directory)
+    }
+
+    fn read_from<T: Read + Seek>(reader: &mut Reader<T>, directory: &Path) -> Result<Self, Error> {
+        let objects = reader
+            .iter_records_as::<DatabaseObject>()
+            .filter(|object| object.as_ref().map_or(true, |object| !object.is_deleted))
+            .collect::<Result<Vec<DatabaseObject>, Error>>()?;
+
+        let tables = objects
+            .iter()
+            .filter(|object| object.object_type.eq_ignore_ascii_case("table"))
+            .map(|table| {
+                let fields = objects
+                    .iter()
+                    .filter(|object| {
+                        object.parent_id == table.object_id
+                            && object.object_type.eq_ignore_ascii_case("field")
+                    })
+                    .map(|field| DatabaseField {
+                        name: field.object_name.clone(),
+                        caption: field.property(PROPERTY_CAPTION),
+                        comment: field.property(PROPERTY_COMMENT),
+                        rule_expression: field.property(PROPERTY_RULE_EXPRESSION),
+                        rule_text: field.property(PROPERTY_RULE_TEXT),
+                        default_value: field.property(PROPERTY_DEFAULT_VALUE),
+                    })
+                    .collect();
+                let path = table
+                    .property(PROPERTY_PATH)
+                    .map(|path| path.replace('\\', "/"))
+                    .map_or_else(
+                        || directory.join(&table.object_name).with_extension("dbf"),
+                        |path| directory.join(path),
+                    );
+                DatabaseTable {
+                    name: table.object_name.clone(),
+                    path,
+                    comment: table.property(PROPERTY_COMMENT),
+                    fields,
+                }
+            })
+            .collect();
+        Ok(Self { tables })
+    }
+
+    /// Returns the tables of the database
+    pub fn tables(&self) -> &[DatabaseTable] {
+        &self.tables
+    }
+
+    /// Returns the table with the given long name (case-insensitive)
+    pub fn table(&self, name: &str) -> Option<&DatabaseTable> {
+        self.tables
+            .iter()
+            .find(|table| table.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Opens the table with the given long name (case-insensitive)
+    pub fn open_table(&self, name: &str) -> Result<Reader<BufReader<File>>, Error> {
+        let table = self.table(name).ok_or_else(|| Error {
+            record_num: 0,
+            field: None,
+            kind: ErrorKind::TableNotFound(name.to_owned()),
+        })?;
+        Reader::from_path(&table.path)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::convert::TryFrom;
+    use std::io::Write;
+
+    use byteorder::{BigEndian, WriteBytesExt};
+
+    use crate::{FieldName, Record, TableWriterBuilder};
+
+    const MEMO_BLOCK_SIZE: usize = 64;
+
+    fn property(id: u8, value: &str) -> Vec<u8> {
+        let mut bytes = vec![];
+        let size = PROPERTY_HEADER_SIZE + value.len() + 1;
+        bytes.write_u32::<LittleEndian>(size as u32).unwrap();
+        bytes.write_u16::<LittleEndian>(1).unwrap();
+        bytes.push(id);
+        bytes.extend_from_slice(value.as_bytes());
+        bytes.push(0);
+        bytes
+    }
+
+    /// object id, parent id, deleted, type, name, properties
+    type ContainerObject<'a> = (i32, i32, bool, &'a str, &'a str, Vec<u8>);
+
+    /// Writes a Visual FoxPro container (and its memo) with the given objects
+    fn write_container(path: &Path, objects: &[ContainerObject]) {
+        let fields: [(&str, u8, u8); 5] = [
+            ("OBJECTID", b'I', 4),
+            ("PARENTID", b'I', 4),
+            ("OBJECTTYPE", b'C', 10),
+            ("OBJECTNAME", b'C', 128),
+            ("PROPERTY", b'M', 4),
+        ];
+        let record_size = 1 + fields.iter().map(|f| usize::from(f.2)).sum::<usize>();
+        let offset = 32 + 32 * fields.len() + 1 + 263;
+
+        let mut dbc = vec![0x30u8, 120, 1, 1];
+        dbc.write_u32::<LittleEndian>(objects.len() as u32).unwrap();
+        dbc.write_u16::<LittleEndian>(offset as u16).unwrap();
+        dbc.write_u16::<LittleEndian>(record_size as u16).unwrap();
+        dbc.resize(32, 0);
+        for (name, field_type, length) in &fields {
+            let mut descriptor = vec![0u8; 32];
+            descriptor[..name.len()].copy_from_slice(name.as_bytes());
+            descriptor[11] = *field_type;
+            descriptor[16] = *length;
+            dbc.extend_from_slice(&descriptor);
+        }
+        dbc.push(0x0D);
+        dbc.resize(offset, 0);
+
+        let mut dct = vec![];
+        dct.write_u32::<BigEndian>(0).unwrap();
+        dct.write_u16::<BigEndian>(0).unwrap();
+        dct.write_u16::<BigEndian>(MEMO_BLOCK_SIZE as u16).unwrap();
+        dct.resize(512, 0);
+
+        for (object_id, parent_id, is_deleted, object_type, name, properties) in objects {
+            let memo_block = dct.len() / MEMO_BLOCK_SIZE;
+            dct.write_u32::<BigEndian>(1).unwrap();
+            dct.write_u32::<BigEndian>(properties.len() as u32).unwrap();
+            dct.extend_from_slice(properties);
+            dct.resize(dct.len().div_ceil(MEMO_BLOCK_SIZE) * MEMO_BLOCK_SIZE, 0);
+
+            dbc.push(if *is_deleted { b'*' } else { b' ' });
+            dbc.write_i32::<LittleEndian>(*object_id).unwrap();
+            dbc.write_i32::<LittleEndian>(*parent_id).unwrap();
+            dbc.extend_from_slice(format!("{:<10}", object_type).as_bytes());
+            dbc.extend_from_slice(format!("{:<128}", name).as_bytes());
+            dbc.write_u32::<LittleEndian>(memo_block as u32).unwrap();
+        }
+        dbc.push(0x1A);
+
+        File::create(path).unwrap().write_all(&dbc).unwrap();
+        File::create(path.with_extension(DBC_MEMO_EXTENSION))
+            .unwrap()
+            .write_all(&dct)
+            .unwrap();
+    }
+
+    #[test]
+    fn read_database_container() {
+        let directory = std::env::temp_dir().join("dbase_read_database_container");
+        std::fs::create_dir_all(&directory).unwrap();
+        let dbc_path = directory.join("sales.dbc");
+
+        let mut properties = property(PROPERTY_PATH, "orders.dbf");
+        properties.extend(property(PROPERTY_COMMENT, "All the orders"));
+        let mut field_properties = property(PROPERTY_CAPTION, "Name of the customer");
+        field_properties.extend(property(PROPERTY_RULE_EXPRESSION, "!EMPTY(customer_name)"));
+        field_properties.extend(property(PROPERTY_RULE_TEXT, "A name is required"));
+        write_container(
+            &dbc_path,
+            &[
+                (1, 0, false, "Database", "Database", vec![]),
+                (2, 1, false, "Table", "customer_orders", properties),
+                (3, 2, false, "Field", "customer_name", field_properties),
+                (4, 2, false, "Field", "amount", vec![]),
+                (5, 1, true, "Table", "deleted_table", vec![]),
+                (6, 1, false, "Table", "products", vec![]),
+            ],
+        );
+
+        let mut record = Record::default();
+        record.insert(
+            "CUSTOMER_N".to_owned(),
+            FieldValue::Character(Some("Dupont".to_owned())),
+        );
+        record.insert("AMOUNT".to_owned(), FieldValue::Numeric(Some(12.0)));
+        TableWriterBuilder::new()
+            .add_character_field(FieldName::try_from("CUSTOMER_N").unwrap(), 20)
+            .add_numeric_field(FieldName::try_from("AMOUNT").unwrap(), 10, 2)
+            .build_with_file_dest(directory.join("orders.dbf"))
+            .unwrap()
+            .write(&[record])
+            .unwrap();
+
+        let database = Database::from_path(&dbc_path).unwrap();
+        let names = database
+            .tables()
+            .iter()
+            .map(DatabaseTable::name)
+            .collect::<Vec<&str>>();
+        assert_eq!(names, vec!["customer_orders", "products"]);
+
+        let table = database.table("CUSTOMER_ORDERS").unwrap();
+        assert_eq!(table.comment(), Some("All the orders"));
+        assert_eq!(table.path(), directory.join("orders.dbf").as_path());
+        assert_eq!(table.fields().len(), 2);
+        let field = &table.fields()[0];
+        assert_eq!(field.name(), "customer_name");
+        assert_eq!(field.caption(), Some("Name of the customer"));
+        assert_eq!(field.rule_expression(), Some("!EMPTY(customer_name)"));
+        assert_eq!(field.rule_text(), Some("A name is required"));
+        assert_eq!(field.default_value(), None);
+        assert_eq!(table.fields()[1].caption(), None);
+
+        // Tables without a path are expected to be named after the table
+        assert_eq!(
+            database.table("products").unwrap().path(),
+            directory.join("products.dbf").as_path()
+        );
+
+        let records = database
+            .open_table("customer_orders")
+            .unwrap()
+            .read()
+            .unwrap();
+        assert_eq!(records.len(), 1);
+        assert!(database.open_table("nope").is_err());
+    }
+}
@@ -0,0 +1,383 @@
+//! Module with the definition of fn's to read and write dBase files
+//! as [Polars](https://pola.rs) data frames
+//!
+//! The fields are mapped to Polars types as follows:
+//!
+//! | dBase type                          | Polars type              |
+//! |-------------------------------------|--------------------------|
+//! | Character, Memo                     | String                   |
+//! | Numeric, Float, Double, Currency    | Float64                  |
+//! | Integer                             | Int32                    |
+//! | Logical                             | Boolean                  |
+//! | Date                                | Date                     |
+//! | DateTime                            | Datetime(Milliseconds)   |
+use std::io::{Read, Seek, Write};
+use std::path::Path;
+
+use polars::prelude::{AnyValue, Column, DataFrame, DataType, PolarsError, PolarsResult, TimeUnit};
+
+use crate::error::{Error, ErrorKind, FieldIOError};
+use crate::reading::{Reader, RecordValues};
+use crate::record::field::{Date, DateTime, FieldType, FieldValue};
+use crate::record::FieldInfo;
+use crate::writing::{FieldWriter, TableWriter, WritableRecord};
+
+fn polars_error(error: PolarsError) -> ErrorKind {
+    ErrorKind::Message(error.to_string())
+}
+
+/// The values of a column of the data frame being read
+enum ColumnValues {
+    String(Vec<Option<String>>),
+    Float64(Vec<Option<f64>>),
+    Int32(Vec<Option<i32>>),
+    Boolean(Vec<Option<bool>>),
+    Date(Vec<Option<i32>>),
+    Datetime(Vec<Option<i64>>),
+}
+
+impl ColumnValues {
+    fn new(field_type: FieldType, capacity: usize) -> Self {
+        match field_type {
+            FieldType::Character | FieldType::Memo => {
+                ColumnValues::String(Vec::with_capacity(capacity))
+            }
+            FieldType::Numeric
+            | FieldType::Float
+            | FieldType::Double
+            | FieldType::Currency
+            | FieldType::DBase7Double => ColumnValues::Float64(Vec::with_capacity(capacity)),
+            FieldType::Integer | FieldType::Long | FieldType::Autoincrement => {
+                ColumnValues::Int32(Vec::with_capacity(capacity))
+            }
+            FieldType::Logical => ColumnValues::Boolean(Vec::with_capacity(capacity)),
+            FieldType::Date => ColumnValues::Date(Vec::with_capacity(capacity)),
+            FieldType::DateTime | FieldType::Timestamp => {
+                ColumnValues::Datetime(Vec::with_capacity(capacity))
+            }
+        }
+    }
+
+    fn push(&mut self, value: FieldValue) {
+        match (self, value) {
+            (ColumnValues::String(values), FieldValue::Character(value)) => values.push(value),
+            (ColumnValues::String(values), FieldValue::Memo(value)) => values.push(Some(value)),
+            (ColumnValues::Float64(values), FieldValue::Numeric(value)) => values.push(value),
+            (ColumnValues::Float64(values), FieldValue::NumericInteger(value)) => {
+                values.push(value.map(|value| value as f64))
+            }
+            (ColumnValues::Float64(values), FieldValue::Float(value)) => {
+                values.push(value.map(f64::from))
+            }
+            (ColumnValues::Float64(values), FieldValue::Double(value))
+            | (ColumnValues::Float64(values), FieldValue::Currency(value)) => {
+                values.push(Some(value))
+            }
+            (ColumnValues::Int32(values), FieldValue::Integer(value))
+            | (ColumnValues::Int32(values), FieldValue::Autoincrement(value)) => {
+                values.push(Some(value))
+            }
+            (ColumnValues::Int32(values), FieldValue::Long(value)) => values.push(value),
+            (ColumnValues::Float64(values), FieldValue::DBase7Double(value)) => values.push(value),
+            (ColumnValues::Boolean(values), FieldValue::Logical(value)) => values.push(value),
+            (ColumnValues::Date(values), FieldValue::Date(value)) => {
+                values.push(value.map(Date::days_since_unix_epoch))
+            }
+            (ColumnValues::Datetime(values), FieldValue::DateTime(value)) => {
+                values.push(Some(value.milliseconds_since_unix_epoch()))
+            }
+            (ColumnValues::Datetime(values), FieldValue::Timestamp(value)) => {
+                values.push(value.map(DateTime::milliseconds_since_unix_epoch))
+            }
+            (_, value) => unreachable!("The value {:?} does not match the column", value),
+        }
+    }
+
+    fn into_column(self, name: &str) -> PolarsResult<Column> {
+        let name = name.into();
+        match self {
+            ColumnValues::String(values) => Ok(Column::new(name, values)),
+            ColumnValues::Float64(values) => Ok(Column::new(name, values)),
+            ColumnValues::Int32(values) => Ok(Column::new(name, values)),
+            ColumnValues::Boolean(values) => Ok(Column::new(name, values)),
+            ColumnValues::Date(values) => Column::new(name, values).cast(&DataType::Date),
+            ColumnValues::Datetime(values) => {
+                Column::new(name, values).cast(&DataType::Datetime(TimeUnit::Milliseconds, None))
+            }
+        }
+    }
+}
+
+impl<T: Read + Seek> Reader<T> {
+    /// Reads the remaining records into a data frame, with one column per field
+    pub fn read_dataframe(&mut self) -> Result<DataFrame, Error> {
+        self.check_memory_budget(usize::MAX)?;
+        let fields_info = self
+            .fields_info
+            .iter()
+            .filter(|field_info| !field_info.is_deletion_flag())
+            .cloned()
+            .collect::<Vec<FieldInfo>>();
+        let mut columns = fields_info
+            .iter()
+            .map(|field_info| {
+                ColumnValues::new(field_info.field_type, self.header.num_records as usize)
+            })
+            .collect::<Vec<ColumnValues>>();
+
+        while let Some(values) = self.read_next_record_as::<RecordValues>() {
+            for (column, value) in columns.iter_mut().zip(values?.values) {
+                column.push(value);
+            }
+        }
+
+        let to_error = |kind| Error {
+            record_num: 0,
+            field: None,
+            kind,
+        };
+        let columns = columns
+            .into_iter()
+            .zip(&fields_info)
+            .map(|(column, field_info)| column.into_column(field_info.name()))
+            .collect::<PolarsResult<Vec<Column>>>()
+            .map_err(|error| to_error(polars_error(error)))?;
+        DataFrame::new(columns).map_err(|error| to_error(polars_error(error)))
+    }
+}
+
+/// Reads all the records of the dBase file at the given path into a data frame
+///
+/// # Example
+///
+/// ```
+/// # fn main() -> Result<(), dbase::Error> {
+/// let dataframe = dbase::read_dataframe("tests/data/line.dbf")?;
+/// assert_eq!(dataframe.get_column_names(), vec!["name"]);
+/// # Ok(())
+/// # }
+/// ```
+pub fn read_dataframe<P: AsRef<Path>>(path: P) -> Result<DataFrame, Error> {
+    Reader::from_path(path)?.read_dataframe()
+}
+
+/// Converts a value of the data frame to the value of the field
+fn to_field_value(value: AnyValue, field_info: &FieldInfo) -> Result<FieldValue, ErrorKind> {
+    let incompatible = |value: &AnyValue| {
+        ErrorKind::Message(format!(
+            "the value {} cannot be written to the field '{}' of type {:?}",
+            value,
+            field_info.name(),
+            field_info.field_type
+        ))
+    };
+    let number = |value: &AnyValue| match value {
+        AnyValue::Boolean(_) => None,
+        value => value.extract::<f64>(),
+    };
+
+    let field_value = match (field_info.field_type, value) {
+        (FieldType::Character, AnyValue::Null) => FieldValue::Character(None),
+        (FieldType::Character, value) => FieldValue::Character(Some(
+            value
+                .get_str()
+                .ok_or_else(|| incompatible(&value))?
+                .to_owned(),
+        )),
+        (FieldType::Numeric, AnyValue::Null) => FieldValue::Numeric(None),
+        (FieldType::Numeric, value) => {
+            FieldValue::Numeric(Some(number(&value).ok_or_else(|| incompatible(&value))?))
+        }
+        (FieldType::Float, AnyValue::Null) => FieldValue::Float(None),
+        (FieldType::Float, value) => FieldValue::Float(Some(
+            number(&value).ok_or_else(|| incompatible(&value))? as f32,
+        )),
+        (FieldType::Double, value) => {
+            FieldValue::Double(number(&value).ok_or_else(|| incompatible(&value))?)
+        }
+        (FieldType::Currency, value) => {
+            FieldValue::Currency(number(&value).ok_or_else(|| incompatible(&value))?)
+        }
+        (FieldType::Integer, value) => FieldValue::Integer(
+            value
+                .extract::<i32>()
+                .filter(|_| !matches!(value, AnyValue::Boolean(_)))
+                .ok_or_else(|| incompatible(&value))?,
+        ),
+        (FieldType::Logical, AnyValue::Null) => FieldValue::Logical(None),
+        (FieldType::Logical, AnyValue::Boolean(value)) => FieldValue::Logical(Some(value)),
+        (FieldType::Date, AnyValue::Null) => FieldValue::Date(None),
+        (FieldType::Date, AnyValue::Date(days)) => {
+            FieldValue::Date(Some(Date::from_days_since_unix_epoch(days)))
+        }
+        (FieldType::DateTime, AnyValue::Datetime(value, time_unit, _))
+        | (FieldType::DateTime, AnyValue::DatetimeOwned(value, time_unit, _)) => {
+            let milliseconds = match time_unit {
+                TimeUnit::Nanoseconds => value.div_euclid(1_000_000),
+                TimeUnit::Microseconds => value.div_euclid(1_000),
+                TimeUnit::Milliseconds => value,
+            };
+            FieldValue::DateTime(DateTime::from_milliseconds_since_unix_epoch(milliseconds))
+        }
+        (_, value) => return Err(incompatible(&value)),
+    };
+    Ok(field_value)
+}
+
+/// A row of a data frame, written as a record
+struct DataFrameRow<'a> {
+    dataframe: &'a DataFrame,
+    index: usize,
+}
+
+impl<'a> WritableRecord for DataFrameRow<'a> {
+    fn write_using<'b, W: Write>(
+        &self,
+        field_writer: &mut FieldWriter<'b, W>,
+    ) -> Result<(), FieldIOError> {
+        while let Some(field_info) = field_writer.fields_info.peek().copied() {
+            let to_error = |kind| FieldIOError::new(kind, Some(field_info.to_owned()));
+            let value = self
+                .dataframe
+                .column(field_info.name())
+                .and_then(|column| column.get(self.index))
+                .map_err(|error| to_error(polars_error(error)))?;
+            let value = to_field_value(value, field_info).map_err(to_error)?;
+            field_writer.write_next_field_value(&value)?;
+        }
+        Ok(())
+    }
+}
+
+/// Writes the rows of a data frame using the writer
+///
+/// The fields of the writer are the schema of the table written,
+/// each field is written with the values of the column with the same name.
+///
+/// # Example
+///
+/// ```
+/// # extern crate dbase;
+/// # extern crate polars;
+/// use dbase::{FieldName, TableWriterBuilder};
+/// use polars::prelude::*;
+/// use std::convert::TryFrom;
+/// use std::io::Cursor;
+///
+/// # fn main() -> Result<(), dbase::Error> {
+/// let dataframe = df!("NAME" => ["Ferris", "Corro"], "AGE" => [5.0, 3.0]).unwrap();
+/// let writer = TableWriterBuilder::new()
+///     .add_character_field(FieldName::try_from("NAME").unwrap(), 20)
+///     .add_numeric_field(FieldName::try_from("AGE").unwrap(), 3, 0)
+///     .build_with_dest(Cursor::new(Vec::<u8>::new()));
+/// let cursor = dbase::write_dataframe(&dataframe, writer)?;
+/// # Ok(())
+/// # }
+/// ```
+pub fn write_dataframe<W: Write + Seek>(
+    dataframe: &DataFrame,
+    writer: TableWriter<W>,
+) -> Result<W, Error> {
+    let rows = (0..dataframe.height())
+        .map(|index| DataFrameRow { dataframe, index })
+        .collect::<Vec<DataFrameRow>>();
+    writer.write(&rows)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::convert::TryFrom;
+    use std::io::Cursor;
+
+    use polars::prelude::df;
+
+    use crate::record::field::Time;
+    use crate::{FieldName, TableWriterBuilder};
+
+    #[test]
+    fn dataframe_round_trip() {
+        let dataframe = df!(
+            "NAME" => [Some("Ferris"), None],
+            "LEGS" => [10, 8],
+            "WEIGHT" => [Some(0.5), None],
+            "SWIMS" => [Some(true), None],
+        )
+        .unwrap();
+        let born = DateTime::new(Date::new(15, 5, 2015), Time::new(12, 30, 15));
+        let dataframe = dataframe
+            .hstack(&[
+                Column::new(
+                    "BORN".into(),
+                    [Some(born.date().days_since_unix_epoch()), None],
+                )
+                .cast(&DataType::Date)
+                .unwrap(),
+                Column::new(
+                    "UPDATED".into(),
+                    [born.milliseconds_since_unix_epoch() * 1000, 0],
+                )
+                .cast(&DataType::Datetime(TimeUnit::Microseconds, None))
+                .unwrap(),
+            ])
+            .unwrap();
+
+        let writer = TableWriterBuilder::new()
+            .add_datetime_field(FieldName::try_from("UPDATED").unwrap())
+            .add_character_field(FieldName::try_from("NAME").unwrap(), 20)
+            .add_integer_field(FieldName::try_from("LEGS").unwrap())
+            .add_numeric_field(FieldName::try_from("WEIGHT").unwrap(), 8, 1)
+            .add_logical_field(FieldName::try_from("SWIMS").unwrap())
+            .add_date_field(FieldName::try_from("BORN").unwrap())
+            .build_with_dest(Cursor::new(Vec::<u8>::new()));
+        let mut cursor = write_dataframe(&dataframe, writer).unwrap();
+        cursor.set_position(0);
+
+        let mut reader = Reader::new(cursor).unwrap();
+        let read = reader.read_dataframe().unwrap();
+        assert_eq!(
+            read.get_column_names(),
+            vec!["UPDATED", "NAME", "LEGS", "WEIGHT", "SWIMS", "BORN"]
+        );
+        assert_eq!(
+            read.column("NAME").unwrap(),
+            dataframe.column("NAME").unwrap()
+        );
+        assert_eq!(read.column("LEGS").unwrap().dtype(), &DataType::Int32);
+        assert_eq!(
+            read.column("WEIGHT").unwrap().get(0).unwrap(),
+            AnyValue::Float64(0.5)
+        );
+        assert_eq!(
+            read.column("SWIMS").unwrap(),
+            dataframe.column("SWIMS").unwrap()
+        );
+        assert_eq!(
+            read.column("BORN").unwrap(),
+            dataframe.column("BORN").unwrap()
+        );
+        assert_eq!(
+            read.column("UPDATED").unwrap().get(0).unwrap(),
+            AnyValue::Datetime(
+                born.milliseconds_since_unix_epoch(),
+                TimeUnit::Milliseconds,
+                None
+            )
+        );
+    }
+
+    #[test]
+    fn incompatible_column() {
+        let dataframe = df!("NAME" => [1, 2]).unwrap();
+        let writer = TableWriterBuilder::new()
+            .add_logical_field(FieldName::try_from("NAME").unwrap())
+            .build_with_dest(Cursor::new(Vec::<u8>::new()));
+        assert!(write_dataframe(&dataframe, writer).is_err());
+
+        let writer = TableWriterBuilder::new()
+            .add_logical_field(FieldName::try_from("MISSING").unwrap())
+            .build_with_dest(Cursor::new(Vec::<u8>::new()));
+        assert!(write_dataframe(&dataframe, writer).is_err());
+    }
+}
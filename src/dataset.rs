@@ -0,0 +1,250 @@
+//! Module with the definition of fn's and struct's to work with
+//! a directory of dBase files
+//!
+//! Applications built on dBase usually store their data as a directory
+//! containing one .dbf file per table, next to their memo and index files.
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+
+use crate::error::{Error, ErrorKind};
+use crate::index::Index;
+use crate::reading::{Reader, ReadingOptions};
+
+/// Extensions of the memo files
+pub(crate) const MEMO_EXTENSIONS: [&str; 2] = ["dbt", "fpt"];
+
+/// Extensions of the index files
+pub(crate) const INDEX_EXTENSIONS: [&str; 5] = ["ndx", "mdx", "cdx", "idx", "ntx"];
+
+/// A table found in the directory of a [Dataset](struct.Dataset.html)
+#[derive(Debug, Clone)]
+pub struct DatasetTable {
+    name: String,
+    path: PathBuf,
+    memo_path: Option<PathBuf>,
+    index_paths: Vec<PathBuf>,
+}
+
+impl DatasetTable {
+    /// Returns the name of the table, the name of its file without the extension
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the path of the .dbf file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the path of the memo file (.dbt / .fpt) of the table, if there is one
+    pub fn memo_path(&self) -> Option<&Path> {
+        self.memo_path.as_deref()
+    }
+
+    /// Returns the paths of the index files that have the same name as the table
+    pub fn index_paths(&self) -> &[PathBuf] {
+        &self.index_paths
+    }
+}
+
+/// The tables of a directory, opened with the same options
+///
+/// Tables are found by their name (case-insensitive), which is the name
+/// of their .dbf file without the extension.
+/// Memo and index files that have the same name as a table are associated with it.
+///
+/// # Example
+///
+/// ```
+/// # fn main() -> Result<(), dbase::Error> {
+/// let options = dbase::ReadingOptions::new().character_trim(Some(dbase::TrimOption::End));
+/// let dataset = dbase::Dataset::open_dir_with_options("tests/data", options)?;
+/// assert!(dataset.table_names().any(|name| name == "line"));
+/// let mut reader = dataset.open_table("LINE")?;
+/// let records = reader.read()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Dataset {
+    directory: PathBuf,
+    tables: BTreeMap<String, DatasetTable>,
+    options: ReadingOptions,
+}
+
+impl Dataset {
+    /// Finds the tables in the directory, they will be read using the default options
+    pub fn open_dir<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        Self::open_dir_with_options(path, ReadingOptions::default())
+    }
+
+    /// Finds the tables in the directory, they will be read using the given options
+    pub fn open_dir_with_options<P: AsRef<Path>>(
+        path: P,
+        options: ReadingOptions,
+    ) -> Result<Self, Error> {
+        let directory = path.as_ref().to_owned();
+        let mut files = Vec::<(String, String, PathBuf)>::new();
+        for entry in std::fs::read_dir(&directory).map_err(|error| Error::io_error(error, 0))? {
+            let path = entry.map_err(|error| Error::io_error(error, 0))?.path();
+            if !path.is_file() {
+                continue;
+            }
+            let stem = path.file_stem().map(|s| s.to_string_lossy().into_owned());
+            let extension = path.extension().map(|s| s.to_string_lossy().to_lowercase());
+            if let (Some(stem), Some(extension)) = (stem, extension) {
+                files.push((stem, extension, path));
+            }
+        }
+
+        let mut tables = BTreeMap::<String, DatasetTable>::new();
+        for (stem, _, path) in files.iter().filter(|(_, extension, _)| extension == "dbf") {
+            let sidecars = files
+                .iter()
+                .filter(|(other_stem, _, _)| other_stem.eq_ignore_ascii_case(stem));
+            let memo_path = sidecars
+                .clone()
+                .find(|(_, extension, _)| MEMO_EXTENSIONS.contains(&extension.as_str()))
+                .map(|(_, _, path)| path.clone());
+            let mut index_paths = sidecars
+                .filter(|(_, extension, _)| INDEX_EXTENSIONS.contains(&extension.as_str()))
+                .map(|(_, _, path)| path.clone())
+                .collect::<Vec<PathBuf>>();
+            index_paths.sort();
+            tables.insert(
+                stem.to_lowercase(),
+                DatasetTable {
+                    name: stem.clone(),
+                    path: path.clone(),
+                    memo_path,
+                    index_paths,
+                },
+            );
+        }
+
+        Ok(Self {
+            directory,
+            tables,
+            options,
+        })
+    }
+
+    /// Returns the directory of the dataset
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+
+    /// Returns the options used to read the tables
+    pub fn options(&self) -> &ReadingOptions {
+        &self.options
+    }
+
+    /// Returns the names of the tables, in alphabetical order
+    pub fn table_names(&self) -> impl Iterator<Item = &str> {
+        self.tables.values().map(DatasetTable::name)
+    }
+
+    /// Returns the tables, in alphabetical order
+    pub fn tables(&self) -> impl Iterator<Item = &DatasetTable> {
+        self.tables.values()
+    }
+
+    /// Returns the table with the given name (case-insensitive)
+    pub fn table(&self, name: &str) -> Option<&DatasetTable> {
+        self.tables.get(&name.to_lowercase())
+    }
+
+    /// Opens a reader on the table with the given name (case-insensitive)
+    pub fn open_table(&self, name: &str) -> Result<Reader<BufReader<File>>, Error> {
+        let table = self.table_or_error(name)?;
+        Reader::from_path_with_memo_path(&table.path, table.memo_path.clone(), self.options.clone())
+    }
+
+    /// Opens the .ndx index files of the table with the given name (case-insensitive)
+    pub fn open_indexes(&self, name: &str) -> Result<Vec<Index<BufReader<File>>>, Error> {
+        self.table_or_error(name)?
+            .index_paths
+            .iter()
+            .filter(|path| {
+                path.extension()
+                    .is_some_and(|extension| extension.eq_ignore_ascii_case("ndx"))
+            })
+            .map(Index::from_path)
+            .collect()
+    }
+
+    fn table_or_error(&self, name: &str) -> Result<&DatasetTable, Error> {
+        self.table(name).ok_or_else(|| Error {
+            record_num: 0,
+            field: None,
+            kind: ErrorKind::TableNotFound(name.to_owned()),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::convert::TryFrom;
+
+    use crate::{FieldName, FieldValue, IndexBuilder, Record, TableWriterBuilder};
+
+    fn write_table(path: &Path, name: &str) {
+        let mut record = Record::default();
+        record.insert(
+            "NAME".to_owned(),
+            FieldValue::Character(Some(format!("  {}", name))),
+        );
+        TableWriterBuilder::new()
+            .add_character_field(FieldName::try_from("NAME").unwrap(), 20)
+            .build_with_file_dest(path)
+            .unwrap()
+            .write(&[record])
+            .unwrap();
+    }
+
+    #[test]
+    fn open_directory() {
+        let directory = std::env::temp_dir().join("dbase_open_directory");
+        let _ = std::fs::remove_dir_all(&directory);
+        std::fs::create_dir_all(directory.join("subdirectory.dbf")).unwrap();
+        write_table(&directory.join("CUSTOMER.DBF"), "customer");
+        write_table(&directory.join("orders.dbf"), "order");
+        std::fs::write(directory.join("notes.txt"), "not a table").unwrap();
+        let mut reader = Reader::from_path(directory.join("orders.dbf")).unwrap();
+        IndexBuilder::new("NAME")
+            .build_with_file_dest(&mut reader, directory.join("ORDERS.ndx"))
+            .unwrap();
+
+        let options = ReadingOptions::new().character_trim(Some(crate::TrimOption::End));
+        let dataset = Dataset::open_dir_with_options(&directory, options).unwrap();
+        assert_eq!(
+            dataset.table_names().collect::<Vec<&str>>(),
+            vec!["CUSTOMER", "orders"]
+        );
+
+        let orders = dataset.table("Orders").unwrap();
+        assert_eq!(orders.memo_path(), None);
+        assert_eq!(orders.index_paths(), &[directory.join("ORDERS.ndx")]);
+        assert_eq!(dataset.open_indexes("orders").unwrap().len(), 1);
+        assert!(dataset.table("customer").unwrap().index_paths().is_empty());
+
+        // The options of the dataset are used
+        let records = dataset.open_table("customer").unwrap().read().unwrap();
+        assert_eq!(
+            records[0].get("NAME"),
+            Some(&FieldValue::Character(Some("  customer".to_owned())))
+        );
+
+        match dataset.open_table("products") {
+            Err(error) => match error.kind() {
+                ErrorKind::TableNotFound(name) => assert_eq!(name, "products"),
+                kind => panic!("Unexpected error kind: {:?}", kind),
+            },
+            Ok(_) => panic!("The table should not have been found"),
+        }
+    }
+}
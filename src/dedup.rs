@@ -0,0 +1,131 @@
+//! Module with the definition of the fn removing the duplicate records of a table
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::error::{Error, ErrorKind};
+use crate::reading::{Reader, Record, RecordValues};
+use crate::writing::{check_no_memo_fields, TableWriterBuilder};
+
+/// Which record of the records that have the same key values is kept by [dedup]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Keep {
+    /// The first one in the table
+    First,
+    /// The last one in the table
+    Last,
+}
+
+/// Writes the live records of the table at `src` to a new table at `dst`,
+/// keeping only one of the records that have the same values for the key fields
+///
+/// The records keep their order. Only the key values of the records are held in memory,
+/// the table is read twice, once to find the records to keep, then to write them.
+/// As memo files cannot be written, tables with memo fields cannot be deduplicated.
+/// Returns the number of records written.
+///
+/// # Example
+///
+/// ```
+/// # fn main() -> Result<(), dbase::Error> {
+/// use dbase::{FieldValue, Keep};
+///
+/// let dst = std::env::temp_dir().join("dbase_dedup_doc.dbf");
+/// assert_eq!(dbase::dedup("tests/data/stations.dbf", &dst, &["line"], Keep::Last)?, 2);
+/// let stations = dbase::read(&dst)?;
+/// assert_eq!(
+///     stations[0].get("name"),
+///     Some(&FieldValue::Character(Some("Capitol South".to_owned())))
+/// );
+/// # Ok(())
+/// # }
+/// ```
+pub fn dedup<P: AsRef<Path>, Q: AsRef<Path>>(
+    src: P,
+    dst: Q,
+    key_fields: &[&str],
+    keep: Keep,
+) -> Result<usize, Error> {
+    let mut reader = Reader::from_path(src)?;
+    check_no_memo_fields(reader.fields())?;
+    let key_indices = key_fields
+        .iter()
+        .map(|name| {
+            reader
+                .fields()
+                .iter()
+                .filter(|field| !field.is_deletion_flag())
+                .position(|field| field.name() == *name)
+                .ok_or_else(|| Error {
+                    record_num: 0,
+                    field: None,
+                    kind: ErrorKind::Message(format!("the table has no field named '{}'", name)),
+                })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // Index of the record kept for each key
+    let mut kept_of_key = HashMap::<Vec<String>, usize>::new();
+    let mut is_kept = Vec::with_capacity(reader.record_count());
+    let mut index = 0;
+    while let Some(record) = reader.read_next_record_as::<RecordValues>() {
+        let record = record?;
+        is_kept.push(false);
+        if !record.is_deleted {
+            let key = key_indices
+                .iter()
+                .map(|i| format!("{:?}", record.values[*i]))
+                .collect();
+            match (kept_of_key.get_mut(&key), keep) {
+                (Some(_), Keep::First) => {}
+                (Some(kept), Keep::Last) => *kept = index,
+                (None, _) => {
+                    kept_of_key.insert(key, index);
+                }
+            }
+        }
+        index += 1;
+    }
+    for kept in kept_of_key.values() {
+        is_kept[*kept] = true;
+    }
+    let num_records = kept_of_key.len();
+
+    reader.set_record_range(0..reader.record_count())?;
+    let builder = TableWriterBuilder::from_reader_ref(&reader);
+    let records = reader
+        .iter_records_as::<Record>()
+        .zip(is_kept)
+        .filter_map(|(record, is_kept)| if is_kept { Some(record) } else { None });
+    builder
+        .build_with_file_dest(dst)
+        .map_err(|error| Error::io_error(error, 0))?
+        .write_iter::<Record, _, _>(num_records, records)?;
+    Ok(num_records)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::FieldValue;
+
+    #[test]
+    fn keep_first() {
+        let dst = std::env::temp_dir().join("dbase_dedup_keep_first.dbf");
+        let keys = ["line", "marker-col"];
+        assert_eq!(
+            dedup("tests/data/stations.dbf", &dst, &keys, Keep::First).unwrap(),
+            2
+        );
+        let stations = crate::read(&dst).unwrap();
+        assert_eq!(
+            stations[1].get("name"),
+            Some(&FieldValue::Character(Some("Judiciary Sq".to_owned())))
+        );
+        // No key, all the records are duplicates
+        assert_eq!(
+            dedup("tests/data/stations.dbf", &dst, &[], Keep::Last).unwrap(),
+            1
+        );
+        assert!(dedup("tests/data/stations.dbf", &dst, &["NOPE"], Keep::First).is_err());
+    }
+}
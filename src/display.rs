@@ -0,0 +1,233 @@
+//! Module with the definition of the struct rendering records as a text table
+use std::fmt;
+
+use crate::reading::Record;
+use crate::record::field::{FieldType, FieldValue};
+use crate::record::FieldInfo;
+
+/// Renders records as an aligned text table, to quickly look at them
+///
+/// The columns are in the order of the fields, numbers are aligned to the right.
+/// Values wider than the maximum width of the columns are truncated, only
+/// the first rows are rendered when there are more records than the maximum.
+///
+/// # Example
+///
+/// ```
+/// # fn main() -> Result<(), dbase::Error> {
+/// let mut reader = dbase::Reader::from_path("tests/data/line.dbf")?;
+/// let records = reader.read()?;
+/// let table = dbase::RecordTable::new(reader.fields(), &records);
+/// assert_eq!(
+///     table.to_string(),
+///     "name       \n\
+///      -----------\n\
+///      linestring1\n"
+/// );
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct RecordTable<'a> {
+    fields: Vec<&'a FieldInfo>,
+    records: &'a [Record],
+    max_column_width: usize,
+    max_rows: usize,
+}
+
+impl<'a> RecordTable<'a> {
+    /// Maximum number of characters of a column, by default
+    pub const DEFAULT_MAX_COLUMN_WIDTH: usize = 40;
+    /// Maximum number of records rendered, by default
+    pub const DEFAULT_MAX_ROWS: usize = 50;
+
+    /// Creates the table of the records, which have the given fields
+    pub fn new(fields: &'a [FieldInfo], records: &'a [Record]) -> Self {
+        Self {
+            fields: fields
+                .iter()
+                .filter(|field_info| !field_info.is_deletion_flag())
+                .collect(),
+            records,
+            max_column_width: Self::DEFAULT_MAX_COLUMN_WIDTH,
+            max_rows: Self::DEFAULT_MAX_ROWS,
+        }
+    }
+
+    /// Sets the maximum number of characters of a column (at least 1)
+    pub fn max_column_width(mut self, max_column_width: usize) -> Self {
+        self.max_column_width = max_column_width.max(1);
+        self
+    }
+
+    /// Sets the maximum number of records rendered
+    pub fn max_rows(mut self, max_rows: usize) -> Self {
+        self.max_rows = max_rows;
+        self
+    }
+
+    fn truncated(&self, text: String) -> String {
+        if text.chars().count() <= self.max_column_width {
+            text
+        } else {
+            let mut truncated = text
+                .chars()
+                .take(self.max_column_width - 1)
+                .collect::<String>();
+            truncated.push('…');
+            truncated
+        }
+    }
+}
+
+fn is_right_aligned(field_type: FieldType) -> bool {
+    matches!(
+        field_type,
+        FieldType::Numeric
+            | FieldType::Float
+            | FieldType::Integer
+            | FieldType::Currency
+            | FieldType::Double
+            | FieldType::Long
+            | FieldType::DBase7Double
+            | FieldType::Autoincrement
+    )
+}
+
+fn cell_text(value: Option<&FieldValue>) -> String {
+    match value {
+        None => String::new(),
+        Some(value) => match value {
+            FieldValue::Character(text) => text.clone().unwrap_or_default(),
+            FieldValue::Memo(text) => text.clone(),
+            FieldValue::Numeric(number) => number.map(|n| n.to_string()).unwrap_or_default(),
+            FieldValue::Float(number) => number.map(|n| n.to_string()).unwrap_or_default(),
+            FieldValue::Logical(logical) => match logical {
+                Some(true) => "T".to_owned(),
+                Some(false) => "F".to_owned(),
+                None => String::new(),
+            },
+            FieldValue::Date(date) => date
+                .map(|d| format!("{:04}-{:02}-{:02}", d.year(), d.month(), d.day()))
+                .unwrap_or_default(),
+            FieldValue::Integer(integer) | FieldValue::Autoincrement(integer) => {
+                integer.to_string()
+            }
+            FieldValue::Long(integer) => integer.map(|n| n.to_string()).unwrap_or_default(),
+            FieldValue::DBase7Double(number) => {
+                number.map(|n| n.to_string()).unwrap_or_default()
+            }
+            FieldValue::NumericInteger(number) => {
+                number.map(|n| n.to_string()).unwrap_or_default()
+            }
+            FieldValue::Currency(number) | FieldValue::Double(number) => number.to_string(),
+            FieldValue::Timestamp(None) => String::new(),
+            FieldValue::DateTime(date_time) | FieldValue::Timestamp(Some(date_time)) => {
+                let (date, time) = (date_time.date(), date_time.time());
+                format!(
+                    "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+                    date.year(),
+                    date.month(),
+                    date.day(),
+                    time.hours(),
+                    time.minutes(),
+                    time.seconds()
+                )
+            }
+        },
+    }
+}
+
+impl fmt::Display for RecordTable<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let num_rows = self.records.len().min(self.max_rows);
+        let header = self
+            .fields
+            .iter()
+            .map(|field| self.truncated(field.name().to_owned()))
+            .collect::<Vec<_>>();
+        let rows = self.records[..num_rows]
+            .iter()
+            .map(|record| {
+                self.fields
+                    .iter()
+                    .map(|field| self.truncated(cell_text(record.get(field.name()))))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let widths = header
+            .iter()
+            .enumerate()
+            .map(|(i, name)| {
+                rows.iter()
+                    .map(|row| row[i].chars().count())
+                    .chain(std::iter::once(name.chars().count()))
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect::<Vec<_>>();
+
+        let write_row = |f: &mut fmt::Formatter, cells: &[String]| -> fmt::Result {
+            for (i, (cell, field)) in cells.iter().zip(&self.fields).enumerate() {
+                if i > 0 {
+                    write!(f, " | ")?;
+                }
+                let padding = widths[i] - cell.chars().count();
+                if is_right_aligned(field.field_type()) {
+                    write!(f, "{:padding$}{}", "", cell, padding = padding)?;
+                } else {
+                    write!(f, "{}{:padding$}", cell, "", padding = padding)?;
+                }
+            }
+            writeln!(f)
+        };
+
+        write_row(f, &header)?;
+        for (i, width) in widths.iter().enumerate() {
+            if i > 0 {
+                write!(f, "-+-")?;
+            }
+            write!(f, "{:-<width$}", "", width = width)?;
+        }
+        writeln!(f)?;
+        for row in &rows {
+            write_row(f, row)?;
+        }
+        if num_rows < self.records.len() {
+            writeln!(f, "({} more records)", self.records.len() - num_rows)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::record::FieldName;
+
+    #[test]
+    fn aligned_columns() {
+        let fields = vec![
+            FieldInfo::new(FieldName::new("NAME").unwrap(), FieldType::Character, 10),
+            FieldInfo::new(FieldName::new("VALUE").unwrap(), FieldType::Numeric, 10),
+        ];
+        let mut records = vec![];
+        for (name, value) in [("first", 1.5), ("a very long name", 120.25), ("last", 3.0)] {
+            let mut record = Record::default();
+            record.insert("NAME".to_owned(), name.to_owned().into());
+            record.insert("VALUE".to_owned(), value.into());
+            records.push(record);
+        }
+        let table = RecordTable::new(&fields, &records)
+            .max_column_width(8)
+            .max_rows(2);
+        assert_eq!(
+            table.to_string(),
+            "NAME     |  VALUE\n\
+             ---------+-------\n\
+             first    |    1.5\n\
+             a very … | 120.25\n\
+             (1 more records)\n"
+        );
+    }
+}
@@ -0,0 +1,345 @@
+//! Module with the definition of fn's and struct's to dump the records of damaged files
+//!
+//! Where [validate](fn.validate.html) reports the problems of a table,
+//! the dump describes each record slot of the file as it is on disk: its deletion byte
+//! and, for each field, whether its bytes parse as the type declared, with the bytes
+//! of the fields that do not.
+use std::fmt;
+use std::io::{Cursor, Read};
+use std::path::Path;
+
+use crate::encoding::UnicodeLossy;
+use crate::error::Error;
+use crate::header::Header;
+use crate::reading::{read_table_info_tolerant, Flavor, ReadingOptions};
+use crate::record::field::{read_memo_index, FieldType, FieldValue, MemoReader};
+use crate::record::FieldInfo;
+use crate::validation::expected_record_size;
+use crate::writing::FILE_TERMINATOR;
+
+/// Maximum number of bytes of a field shown in its hex snippet
+pub const HEX_SNIPPET_LENGTH: usize = 32;
+
+/// What was found in a field of a record slot
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldDump {
+    /// The name of the field
+    pub name: String,
+    /// The type the field is declared with
+    pub field_type: FieldType,
+    /// The offset of the field from the start of the file
+    pub offset: u64,
+    /// Why the bytes of the field do not parse as its type, `None` if they do
+    pub error: Option<String>,
+    /// The bytes of the field in hexadecimal, at most
+    /// [HEX_SNIPPET_LENGTH](constant.HEX_SNIPPET_LENGTH.html) of them,
+    /// only set when the field does not parse
+    pub hex: Option<String>,
+}
+
+impl FieldDump {
+    /// Returns whether the bytes of the field parse as its type
+    pub fn is_ok(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// What was found in a record slot of the file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordDump {
+    /// The index of the record slot
+    pub index: usize,
+    /// The offset of the slot from the start of the file
+    pub offset: u64,
+    /// The raw deletion byte, `b' '` for live records and `b'*'` for deleted ones
+    pub deletion_byte: u8,
+    /// The fields of the slot in order, the deletion flag excluded
+    ///
+    /// The fields after the end of a truncated slot are missing.
+    pub fields: Vec<FieldDump>,
+    /// Whether the file ends before the end of the slot
+    pub is_truncated: bool,
+}
+
+impl RecordDump {
+    /// Returns whether the deletion byte is one of the two the format allows
+    pub fn has_valid_deletion_byte(&self) -> bool {
+        self.deletion_byte == b' ' || self.deletion_byte == b'*'
+    }
+
+    /// Returns whether the slot is complete, with a valid deletion byte
+    /// and fields that all parse
+    pub fn is_ok(&self) -> bool {
+        !self.is_truncated && self.has_valid_deletion_byte() && self.failing_fields().count() == 0
+    }
+
+    /// Returns the fields whose bytes do not parse as their type
+    pub fn failing_fields(&self) -> impl Iterator<Item = &FieldDump> {
+        self.fields.iter().filter(|field| !field.is_ok())
+    }
+}
+
+impl fmt::Display for RecordDump {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "record {} at offset {}: deletion byte 0x{:02X}",
+            self.index, self.offset, self.deletion_byte
+        )?;
+        if self.is_truncated {
+            write!(f, ", truncated")?;
+        }
+        for field in self.failing_fields() {
+            write!(
+                f,
+                "\n  field '{}' ({:?}) at offset {}: {} [{}]",
+                field.name,
+                field.field_type,
+                field.offset,
+                field.error.as_deref().unwrap_or_default(),
+                field.hex.as_deref().unwrap_or_default()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Dump made by [dump](fn.dump.html)
+#[derive(Debug, Clone)]
+pub struct TableDump {
+    /// The header of the table, as read
+    pub header: Header,
+    /// The fields descriptors, the deletion flag first
+    pub fields: Vec<FieldInfo>,
+    /// Whether the fields descriptors end with their terminator before the offset
+    /// to the first record, if not the fields may be missing some descriptors
+    pub has_fields_terminator: bool,
+    /// The record slots found in the file, whatever the number of records in the header
+    pub records: Vec<RecordDump>,
+}
+
+impl TableDump {
+    /// Returns the record slots that are not fine
+    pub fn damaged_records(&self) -> impl Iterator<Item = &RecordDump> {
+        self.records.iter().filter(|record| !record.is_ok())
+    }
+}
+
+/// Formats the bytes as hexadecimal, eliding the ones after the first
+/// [HEX_SNIPPET_LENGTH](constant.HEX_SNIPPET_LENGTH.html)
+fn hex_snippet(bytes: &[u8]) -> String {
+    let mut snippet = bytes
+        .iter()
+        .take(HEX_SNIPPET_LENGTH)
+        .map(|byte| format!("{:02X}", byte))
+        .collect::<Vec<_>>()
+        .join(" ");
+    if bytes.len() > HEX_SNIPPET_LENGTH {
+        snippet.push_str(" ...");
+    }
+    snippet
+}
+
+/// Returns why the bytes do not parse as the type of the field, if they don't
+fn parse_error(bytes: &[u8], field_info: &FieldInfo, options: &ReadingOptions) -> Option<String> {
+    let mut source = Cursor::new(bytes);
+    match field_info.field_type {
+        // Reading is lenient with these, the dump is not
+        FieldType::Logical => match bytes.first() {
+            Some(
+                b' ' | b'?' | b'1' | b'0' | b'T' | b't' | b'Y' | b'y' | b'N' | b'n' | b'F' | b'f',
+            ) => None,
+            _ => Some("not a logical value".to_owned()),
+        },
+        // The memo file is not read, only the block index is checked
+        FieldType::Memo => read_memo_index(&mut &bytes[..], field_info)
+            .err()
+            .map(|error| error.to_string()),
+        _ => {
+            let mut memo_reader = None::<MemoReader<Cursor<&[u8]>>>;
+            match FieldValue::read_from(&mut source, &mut memo_reader, field_info, options) {
+                Ok(FieldValue::Date(Some(date))) if !date.is_day_of_calendar() => {
+                    Some(format!("{} is not a day of the calendar", date))
+                }
+                Ok(_) => None,
+                Err(error) => Some(error.to_string()),
+            }
+        }
+    }
+}
+
+fn dump_record(
+    index: usize,
+    offset: u64,
+    bytes: &[u8],
+    fields_info: &[FieldInfo],
+    options: &ReadingOptions,
+) -> RecordDump {
+    let mut fields = vec![];
+    let mut start = 0;
+    let mut is_truncated = false;
+    for field_info in fields_info {
+        let end = start + usize::from(field_info.length());
+        if end > bytes.len() {
+            is_truncated = true;
+            break;
+        }
+        if !field_info.is_deletion_flag() {
+            let field_bytes = &bytes[start..end];
+            let error = parse_error(field_bytes, field_info, options);
+            fields.push(FieldDump {
+                name: field_info.name().to_owned(),
+                field_type: field_info.field_type,
+                offset: offset + start as u64,
+                hex: error.as_ref().map(|_| hex_snippet(field_bytes)),
+                error,
+            });
+        }
+        start = end;
+    }
+    RecordDump {
+        index,
+        offset,
+        deletion_byte: bytes[0],
+        fields,
+        is_truncated,
+    }
+}
+
+/// Dumps the record slots of the dBase file at the given path
+///
+/// See [dump_from](fn.dump_from.html).
+///
+/// # Example
+///
+/// ```
+/// # fn main() -> Result<(), dbase::Error> {
+/// let dump = dbase::dump("tests/data/line.dbf")?;
+/// for record in dump.damaged_records() {
+///     println!("{}", record);
+/// }
+/// assert_eq!(dump.records.len(), 1);
+/// # Ok(())
+/// # }
+/// ```
+pub fn dump<P: AsRef<Path>>(path: P) -> Result<TableDump, Error> {
+    let file = std::fs::File::open(path).map_err(|error| Error::io_error(error, 0))?;
+    dump_from(std::io::BufReader::new(file))
+}
+
+/// Dumps the record slots of the dBase table read from the source
+///
+/// The slots are walked from the offset to the first record, with the length
+/// the fields descriptors give, until the end of the source: the number of records
+/// of the header and the end of file marker are not trusted. The last slot
+/// is reported as truncated if the source ends in it.
+///
+/// An error is only returned when the header or the fields descriptors cannot be read,
+/// the descriptors missing their terminator are reported with
+/// [has_fields_terminator](struct.TableDump.html#structfield.has_fields_terminator).
+pub fn dump_from<R: Read>(mut source: R) -> Result<TableDump, Error> {
+    let mut content = vec![];
+    source
+        .read_to_end(&mut content)
+        .map_err(|error| Error::io_error(error, 0))?;
+    let (header, fields_info, _, has_fields_terminator) =
+        read_table_info_tolerant(&mut Cursor::new(&content), Flavor::Standard, &UnicodeLossy)?;
+    let record_size = expected_record_size(&fields_info).max(1);
+    let first_record = usize::from(header.offset_to_first_record).min(content.len());
+    let mut records_bytes = &content[first_record..];
+    if records_bytes.last() == Some(&FILE_TERMINATOR) && records_bytes.len() % record_size == 1 {
+        records_bytes = &records_bytes[..records_bytes.len() - 1];
+    }
+
+    let options = ReadingOptions::default();
+    let records = records_bytes
+        .chunks(record_size)
+        .enumerate()
+        .map(|(index, bytes)| {
+            let offset = (first_record + index * record_size) as u64;
+            dump_record(index, offset, bytes, &fields_info, &options)
+        })
+        .collect();
+    Ok(TableDump {
+        header,
+        fields: fields_info,
+        has_fields_terminator,
+        records,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// dBase III table with the fields NAME (C, 4), COUNT (N, 3) and OPEN (L)
+    fn table(records: &[u8]) -> Vec<u8> {
+        let mut table = vec![0x03, 120, 1, 1, 3, 0, 0, 0, 129, 0, 9, 0];
+        table.resize(Header::SIZE, 0);
+        for (name, field_type, length) in [("NAME", b'C', 4), ("COUNT", b'N', 3), ("OPEN", b'L', 1)]
+        {
+            let mut descriptor = name.as_bytes().to_vec();
+            descriptor.resize(11, 0);
+            descriptor.extend_from_slice(&[field_type, 0, 0, 0, 0, length]);
+            descriptor.resize(FieldInfo::SIZE, 0);
+            table.extend_from_slice(&descriptor);
+        }
+        table.push(0x0D);
+        table.extend_from_slice(records);
+        table
+    }
+
+    #[test]
+    fn damaged_slots_are_reported() {
+        let content = table(b" Ann  12T#Bob abcX x  ");
+        let dump = dump_from(content.as_slice()).unwrap();
+        assert_eq!(dump.records.len(), 3);
+        assert!(dump.has_fields_terminator);
+        assert!(dump.records[0].is_ok());
+
+        let second = &dump.records[1];
+        assert_eq!(second.offset, 138);
+        assert_eq!(second.deletion_byte, b'#');
+        assert!(!second.has_valid_deletion_byte());
+        let failing = second.failing_fields().collect::<Vec<_>>();
+        assert_eq!(failing.len(), 2);
+        assert_eq!(failing[0].name, "COUNT");
+        assert_eq!(failing[0].offset, 143);
+        assert_eq!(failing[0].hex.as_deref(), Some("61 62 63"));
+        assert_eq!(failing[1].name, "OPEN");
+        assert_eq!(failing[1].hex.as_deref(), Some("58"));
+        assert!(second
+            .to_string()
+            .starts_with("record 1 at offset 138: deletion byte 0x23"));
+
+        let third = &dump.records[2];
+        assert!(third.is_truncated);
+        assert!(third.fields.is_empty());
+        assert_eq!(dump.damaged_records().count(), 2);
+    }
+
+    #[test]
+    fn missing_fields_terminator_is_reported() {
+        let mut content = table(b" Ann  12T\x1A");
+        // The offset ends before the descriptor of OPEN
+        content[8] -= 32;
+        let dump = dump_from(content.as_slice()).unwrap();
+        assert!(!dump.has_fields_terminator);
+        assert_eq!(dump.fields.len(), 3);
+    }
+
+    #[test]
+    fn end_of_file_marker_is_not_a_slot() {
+        let content = table(b" Ann  12T\x1A");
+        let dump = dump_from(content.as_slice()).unwrap();
+        assert_eq!(dump.records.len(), 1);
+        assert_eq!(dump.damaged_records().count(), 0);
+    }
+
+    #[test]
+    fn long_fields_are_elided() {
+        let snippet = hex_snippet(&[0x41; 40]);
+        assert!(snippet.ends_with(" ..."));
+        assert_eq!(snippet.matches("41").count(), HEX_SNIPPET_LENGTH);
+    }
+}
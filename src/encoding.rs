@@ -0,0 +1,90 @@
+//! Module with the definition of the encodings used to decode the text of dBase files
+//!
+//! dBase files are usually written using the code page of the system that
+//! wrote them, which is not recorded reliably in the files.
+//! The crate only knows about unicode, other encodings can be
+//! supported by implementing the [Encoding](trait.Encoding.html) trait.
+use alloc::borrow::Cow;
+use alloc::string::{String, ToString};
+use core::fmt;
+
+/// Error returned when bytes could not be decoded
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecodeError {
+    message: String,
+}
+
+impl DecodeError {
+    /// Creates a new error with a message describing it
+    pub fn new<S: Into<String>>(message: S) -> Self {
+        Self {
+            message: message.into(),
+        }
+    }
+
+    /// Returns the description of the error
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for DecodeError {}
+
+/// Trait for the encodings used to decode the content of
+/// Character and Memo fields, and the names of the fields
+pub trait Encoding: fmt::Debug + Send + Sync {
+    /// Decodes the bytes into a string
+    fn decode<'a>(&self, bytes: &'a [u8]) -> Result<Cow<'a, str>, DecodeError>;
+
+    /// Encodes the string into bytes, `None` if it has characters the encoding does not have
+    ///
+    /// Used for the names of the fields and the Character values written,
+    /// the default implementation encodes in UTF-8.
+    fn encode<'a>(&self, text: &'a str) -> Option<Cow<'a, [u8]>> {
+        Some(Cow::Borrowed(text.as_bytes()))
+    }
+}
+
+/// UTF-8 encoding, invalid sequences are replaced by `U+FFFD REPLACEMENT CHARACTER`
+///
+/// This is the encoding used by default.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct UnicodeLossy;
+
+impl Encoding for UnicodeLossy {
+    fn decode<'a>(&self, bytes: &'a [u8]) -> Result<Cow<'a, str>, DecodeError> {
+        Ok(String::from_utf8_lossy(bytes))
+    }
+}
+
+/// UTF-8 encoding, invalid sequences are errors
+#[derive(Debug, Copy, Clone, Default)]
+pub struct Unicode;
+
+impl Encoding for Unicode {
+    fn decode<'a>(&self, bytes: &'a [u8]) -> Result<Cow<'a, str>, DecodeError> {
+        core::str::from_utf8(bytes)
+            .map(Cow::Borrowed)
+            .map_err(|error| DecodeError::new(error.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn unicode_decoding() {
+        let bytes = b"caf\xc3\xa9 \xff";
+        assert_eq!(UnicodeLossy.decode(bytes).unwrap(), "café \u{FFFD}");
+        assert!(Unicode.decode(bytes).is_err());
+        assert_eq!(Unicode.decode(&bytes[..6]).unwrap(), "café ");
+    }
+}
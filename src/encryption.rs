@@ -0,0 +1,74 @@
+//! Module with the definition of the trait used to encrypt and decrypt the records of tables
+//!
+//! dBase IV `PROTECT` encrypts the records of tables and sets the encryption flag
+//! of their header (its 16th byte). The scheme it uses is not documented,
+//! so the crate does not implement it: applications that know the scheme of the tables
+//! they exchange implement [RecordCipher](trait.RecordCipher.html), holding its key,
+//! and give it to the [writer](struct.TableWriterBuilder.html#method.encryption)
+//! and to the [reader](struct.ReadingOptions.html#method.decryption).
+//!
+//! The header and the fields descriptors are not encrypted, nor is the deletion flag
+//! of the records: the cipher is given the bytes of the fields of each record.
+use core::fmt;
+
+/// Encryption scheme of the records of tables, with its key
+///
+/// # Example
+///
+/// ```
+/// use dbase::{FieldName, ReadingOptions, Record, RecordCipher, TableWriterBuilder};
+/// use std::convert::TryFrom;
+/// use std::io::Cursor;
+///
+/// /// Not an actual encryption, only an example
+/// #[derive(Debug)]
+/// struct Xor(Vec<u8>);
+///
+/// impl RecordCipher for Xor {
+///     fn encrypt(&self, _record_index: u32, fields: &mut [u8]) {
+///         for (byte, key) in fields.iter_mut().zip(self.0.iter().cycle()) {
+///             *byte ^= key;
+///         }
+///     }
+///
+///     fn decrypt(&self, record_index: u32, fields: &mut [u8]) {
+///         self.encrypt(record_index, fields)
+///     }
+/// }
+///
+/// # fn main() -> Result<(), dbase::Error> {
+/// let table = TableWriterBuilder::new()
+///     .add_character_field(FieldName::try_from("NAME").unwrap(), 10)
+///     .encryption(Xor(b"secret".to_vec()))
+///     .build_with_dest(Cursor::new(Vec::<u8>::new()))
+///     .write(&[Record::builder().set("NAME", "Alice").build()])?;
+///
+/// let options = ReadingOptions::default().decryption(Xor(b"secret".to_vec()));
+/// let mut reader = dbase::Reader::with_options(Cursor::new(table.into_inner()), options)?;
+/// assert!(reader.header().is_encrypted());
+/// assert_eq!(reader.read()?[0].get("NAME"), Some(&"Alice".into()));
+/// # Ok(())
+/// # }
+/// ```
+pub trait RecordCipher: fmt::Debug + Send + Sync {
+    /// Encrypts in place the bytes of the fields of the record at the given index (0-based)
+    fn encrypt(&self, record_index: u32, fields: &mut [u8]);
+
+    /// Decrypts in place the bytes of the fields of the record at the given index (0-based)
+    fn decrypt(&self, record_index: u32, fields: &mut [u8]);
+}
+
+/// Encrypts the record, its deletion flag excluded, if there is a cipher
+#[cfg(feature = "std")]
+pub(crate) fn encrypt_record(cipher: Option<&dyn RecordCipher>, index: usize, record: &mut [u8]) {
+    if let (Some(cipher), Some((_, fields))) = (cipher, record.split_first_mut()) {
+        cipher.encrypt(index as u32, fields);
+    }
+}
+
+/// Decrypts the record, its deletion flag excluded, if there is a cipher
+pub(crate) fn decrypt_record(cipher: Option<&dyn RecordCipher>, index: usize, record: &mut [u8]) {
+    if let (Some(cipher), Some((_, fields))) = (cipher, record.split_first_mut()) {
+        cipher.decrypt(index as u32, fields);
+    }
+}
@@ -1,19 +1,31 @@
+use alloc::string::String;
+use core::fmt;
+
+use crate::encoding::DecodeError;
+use crate::raw::EndOfSource;
+use crate::record::FieldNameIssue;
+#[cfg(feature = "std")]
+use crate::stream::Capability;
 use crate::{FieldConversionError, FieldInfo};
 
 #[derive(Debug)]
 pub enum ErrorKind {
     /// Wrapper of `std::io::Error` to forward any reading/writing error
+    #[cfg(feature = "std")]
     IoError(std::io::Error),
+    /// The [ByteSource](trait.ByteSource.html) of the reader ended before the end of the table
+    EndOfSource,
     /// Wrapper to forward errors whe trying to parse a float from the file
-    ParseFloatError(std::num::ParseFloatError),
+    ParseFloatError(core::num::ParseFloatError),
     /// Wrapper to forward errors whe trying to parse an integer value from the file
-    ParseIntError(std::num::ParseIntError),
+    ParseIntError(core::num::ParseIntError),
     /// The Field as an invalid FieldType
     InvalidFieldType(char),
     /// Happens when at least one field is a Memo type
     /// and the that additional memo file could not be found / was not given
     MissingMemoFile,
     /// Something went wrong when we tried to open the associated memo file
+    #[cfg(feature = "std")]
     ErrorOpeningMemoFile(std::io::Error),
     /// The conversion from a FieldValue to another type could not be made
     BadConversion(FieldConversionError),
@@ -26,9 +38,47 @@ pub enum ErrorKind {
     /// The type of the value for the field is not compatible with the
     /// dbase field's type
     IncompatibleType,
+    /// The index file is malformed or does not match the table it is used with
+    InvalidIndex(String),
+    /// The key expression could not be understood or evaluated
+    InvalidExpression(String),
+    /// The database does not have a table with this name
+    TableNotFound(String),
+    /// The text of a field could not be decoded using the encoding of the reader
+    StringDecodeError(DecodeError),
+    /// The name of a field is duplicated or does not follow the rules of the format
+    InvalidFieldName(FieldNameIssue),
+    /// The table is encrypted (dBase IV `PROTECT`) and the reader has no
+    /// [cipher](struct.ReadingOptions.html#method.decryption) to decrypt its records
+    EncryptedTable,
+    /// The text of a Date field is not a day of the calendar
+    InvalidDate(String),
+    /// The table written would exceed a limit of the format
+    FormatLimitExceeded(FormatLimit),
+    /// The text of the value written does not fit in its field
+    ValueTooLong(String),
+    /// The source of the reader cannot do what the operation needs,
+    /// eg: going back to a record of a stream
+    #[cfg(feature = "std")]
+    Unsupported(Capability),
+    /// Reading all the records at once would take more memory than the budget
+    /// of the reading options, they have to be read one at a time
+    MemoryBudgetExceeded { estimated: usize, budget: usize },
     Message(String),
 }
 
+/// A limit of the dBase format on the size of tables
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum FormatLimit {
+    /// The file cannot be larger than 2 GB, as its offsets are signed 32 bit integers,
+    /// or than the maximum size given to the writer
+    FileSize,
+    /// The number of records is a 32 bit integer
+    NumRecords,
+    /// The length of a record is a 16 bit integer
+    RecordLength,
+}
+
 /// The error type for this crate
 pub struct Error {
     pub(crate) record_num: usize,
@@ -37,6 +87,7 @@ pub struct Error {
 }
 
 impl Error {
+    #[cfg(feature = "std")]
     pub(crate) fn new(field_error: FieldIOError, current_record: usize) -> Self {
         Self {
             record_num: current_record,
@@ -45,6 +96,7 @@ impl Error {
         }
     }
 
+    #[cfg(feature = "std")]
     pub(crate) fn io_error(error: std::io::Error, current_record: usize) -> Self {
         Self {
             record_num: current_record,
@@ -53,6 +105,19 @@ impl Error {
         }
     }
 
+    /// Error returned when the record asked for is not one of the table
+    #[cfg(feature = "std")]
+    pub(crate) fn no_such_record(index: usize, num_records: usize) -> Self {
+        Self {
+            record_num: index,
+            field: None,
+            kind: ErrorKind::Message(format!(
+                "there is no record {} in a table of {} records",
+                index, num_records
+            )),
+        }
+    }
+
     /// Returns the kind of error that happened
     pub fn kind(&self) -> &ErrorKind {
         &self.kind
@@ -79,10 +144,12 @@ pub struct FieldIOError {
 }
 
 impl FieldIOError {
+    #[cfg(feature = "std")]
     pub(crate) fn new(kind: ErrorKind, field: Option<FieldInfo>) -> Self {
         Self { field, kind }
     }
 
+    #[cfg(feature = "std")]
     pub(crate) fn end_of_record() -> Self {
         Self {
             field: None,
@@ -95,20 +162,27 @@ impl FieldIOError {
     }
 }
 
+#[cfg(feature = "std")]
 impl From<std::io::Error> for ErrorKind {
     fn from(e: std::io::Error) -> Self {
         ErrorKind::IoError(e)
     }
 }
 
-impl From<std::num::ParseFloatError> for ErrorKind {
-    fn from(p: std::num::ParseFloatError) -> Self {
+impl From<EndOfSource> for ErrorKind {
+    fn from(_: EndOfSource) -> Self {
+        ErrorKind::EndOfSource
+    }
+}
+
+impl From<core::num::ParseFloatError> for ErrorKind {
+    fn from(p: core::num::ParseFloatError) -> Self {
         ErrorKind::ParseFloatError(p)
     }
 }
 
-impl From<std::num::ParseIntError> for ErrorKind {
-    fn from(p: std::num::ParseIntError) -> Self {
+impl From<core::num::ParseIntError> for ErrorKind {
+    fn from(p: core::num::ParseIntError) -> Self {
         ErrorKind::ParseIntError(p)
     }
 }
@@ -119,8 +193,8 @@ impl From<FieldConversionError> for ErrorKind {
     }
 }
 
-impl std::fmt::Debug for Error {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl fmt::Debug for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         if let Some(field_info) = &self.field {
             write!(
                 f,
@@ -137,22 +211,27 @@ impl std::fmt::Debug for Error {
     }
 }
 
-impl std::fmt::Display for Error {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{:?}", self)
     }
 }
 
-impl std::fmt::Display for ErrorKind {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{:?}", self)
     }
 }
 
+#[cfg(feature = "std")]
 impl std::error::Error for FieldIOError {
     fn description(&self) -> &str {
         match self.kind {
             ErrorKind::IoError(_) => "An I/O error happened",
+            ErrorKind::EndOfSource => "The source ended before the end of the table",
             ErrorKind::ParseFloatError(_) => "Float value could not be obtained",
             ErrorKind::ParseIntError(_) => "Float value could not be obtained",
             ErrorKind::InvalidFieldType(_) => "The FieldType code is note a valid one",
@@ -167,13 +246,26 @@ impl std::error::Error for FieldIOError {
             }
             ErrorKind::TooManyFields => "The writer expected to write more fields for the record",
             ErrorKind::IncompatibleType => "The types are not compatible",
+            ErrorKind::InvalidIndex(_) => "The index file is not valid",
+            ErrorKind::InvalidExpression(_) => "The expression is not valid",
+            ErrorKind::TableNotFound(_) => "The table is not part of the database",
+            ErrorKind::StringDecodeError(_) => "The text could not be decoded",
+            ErrorKind::InvalidFieldName(_) => "The name of a field is not valid",
+            ErrorKind::EncryptedTable => "The table is encrypted",
+            ErrorKind::InvalidDate(_) => "The date is not a day of the calendar",
+            ErrorKind::FormatLimitExceeded(_) => "The table exceeds a limit of the format",
+            ErrorKind::ValueTooLong(_) => "The value does not fit in its field",
+            ErrorKind::Unsupported(_) => "The source of the reader does not support the operation",
+            ErrorKind::MemoryBudgetExceeded { .. } => {
+                "The records would take more memory than the budget"
+            }
             ErrorKind::Message(ref msg) => msg,
         }
     }
 }
 
-impl std::fmt::Display for FieldIOError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl fmt::Display for FieldIOError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         if let Some(field_info) = &self.field {
             write!(
                 f,
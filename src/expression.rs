@@ -0,0 +1,1276 @@
+//! Module with the definition of the xBase expressions
+//!
+//! Expressions are what dBase applications use to describe index keys,
+//! like `UPPER(LASTNAME)+DTOS(BIRTHDATE)`.
+//! They are parsed once against the fields of a table and can then
+//! be evaluated on each record.
+//!
+//! Like in dBase, character fields evaluate to their full, space padded, width.
+//!
+//! Numbers can be added, subtracted (`-`), multiplied (`*`) and divided (`/`),
+//! strings concatenated with `+`, or with `-` which moves the trailing blanks of the
+//! first string after the second one, numbers of days added to or subtracted from dates.
+//!
+//! Values can be compared (`=`, `==`, `<>`, `#`, `!=`, `<`, `<=`, `>`, `>=`) and
+//! logical values combined (`AND`, `OR`, `NOT`, also written `.AND.`, `.OR.`, `.NOT.`
+//! and `!`), which makes expressions usable as filters, like `STATE = 'CA' AND AMOUNT > 1000`.
+use std::cmp::Ordering;
+use std::fmt::Write;
+
+use crate::error::ErrorKind;
+use crate::reading::Record;
+use crate::record::field::{Date, FieldType, FieldValue};
+use crate::record::{field_names_match, FieldInfo};
+
+/// The functions that can be called in an expression
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum Function {
+    /// `UPPER(string)`
+    Upper,
+    /// `DTOS(date)`, date as a `YYYYMMDD` string
+    Dtos,
+    /// `STR(number [, length [, decimals]])`
+    Str,
+    /// `SUBSTR(string, start [, count])`, start is 1-based
+    Substr,
+    /// `LOWER(string)`
+    Lower,
+    /// `TRIM(string)` or `RTRIM(string)`, without the trailing blanks
+    Trim,
+    /// `LTRIM(string)`, without the leading blanks
+    Ltrim,
+    /// `ALLTRIM(string)`, without the leading and trailing blanks
+    Alltrim,
+    /// `LEN(string)`, the number of characters
+    Len,
+    /// `VAL(string)`, the number at the start of the string, 0 if there is none
+    Val,
+    /// `IIF(condition, value if true, value if false)`,
+    /// only the value that is returned is evaluated
+    Iif,
+}
+
+impl Function {
+    fn from_name(name: &str) -> Option<Self> {
+        match name.to_uppercase().as_str() {
+            "UPPER" => Some(Function::Upper),
+            "DTOS" => Some(Function::Dtos),
+            "STR" => Some(Function::Str),
+            "SUBSTR" => Some(Function::Substr),
+            "LOWER" => Some(Function::Lower),
+            "TRIM" | "RTRIM" => Some(Function::Trim),
+            "LTRIM" => Some(Function::Ltrim),
+            "ALLTRIM" => Some(Function::Alltrim),
+            "LEN" => Some(Function::Len),
+            "VAL" => Some(Function::Val),
+            "IIF" => Some(Function::Iif),
+            _ => None,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Function::Upper => "UPPER",
+            Function::Dtos => "DTOS",
+            Function::Str => "STR",
+            Function::Substr => "SUBSTR",
+            Function::Lower => "LOWER",
+            Function::Trim => "TRIM",
+            Function::Ltrim => "LTRIM",
+            Function::Alltrim => "ALLTRIM",
+            Function::Len => "LEN",
+            Function::Val => "VAL",
+            Function::Iif => "IIF",
+        }
+    }
+
+    /// The number of arguments accepted by the function (min, max)
+    fn arity(self) -> (usize, usize) {
+        match self {
+            Function::Upper
+            | Function::Dtos
+            | Function::Lower
+            | Function::Trim
+            | Function::Ltrim
+            | Function::Alltrim
+            | Function::Len
+            | Function::Val => (1, 1),
+            Function::Str => (1, 3),
+            Function::Substr => (2, 3),
+            Function::Iif => (3, 3),
+        }
+    }
+}
+
+/// Value produced when evaluating an expression
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Value {
+    Character(String),
+    Numeric(f64),
+    Date(Option<Date>),
+    Logical(bool),
+}
+
+impl Value {
+    fn from_field_value(value: &FieldValue, field: &FieldInfo) -> Result<Self, ErrorKind> {
+        let value = match value {
+            FieldValue::Character(s) => {
+                let mut s = s.clone().unwrap_or_default();
+                let width = usize::from(field.field_length);
+                let num_chars = s.chars().count();
+                if num_chars < width {
+                    s.extend(std::iter::repeat_n(' ', width - num_chars));
+                }
+                Value::Character(s)
+            }
+            FieldValue::Memo(s) => Value::Character(s.clone()),
+            FieldValue::Numeric(v) => Value::Numeric(v.unwrap_or(0.0)),
+            FieldValue::Float(v) => Value::Numeric(f64::from(v.unwrap_or(0.0))),
+            FieldValue::Integer(v) | FieldValue::Autoincrement(v) => Value::Numeric(f64::from(*v)),
+            FieldValue::Long(v) => Value::Numeric(f64::from(v.unwrap_or(0))),
+            FieldValue::DBase7Double(v) => Value::Numeric(v.unwrap_or(0.0)),
+            FieldValue::NumericInteger(v) => Value::Numeric(v.unwrap_or(0) as f64),
+            FieldValue::Double(v) | FieldValue::Currency(v) => Value::Numeric(*v),
+            FieldValue::Date(d) => Value::Date(*d),
+            FieldValue::Logical(b) => Value::Logical(b.unwrap_or(false)),
+            FieldValue::DateTime(_) | FieldValue::Timestamp(_) => {
+                return Err(ErrorKind::InvalidExpression(format!(
+                    "the type of field '{}' is not supported in expressions",
+                    field.name
+                )))
+            }
+        };
+        Ok(value)
+    }
+
+    /// Returns whether the value is the true logical value
+    pub(crate) fn is_true(&self) -> bool {
+        *self == Value::Logical(true)
+    }
+
+    fn type_name(&self) -> &'static str {
+        match self {
+            Value::Character(_) => "character",
+            Value::Numeric(_) => "numeric",
+            Value::Date(_) => "date",
+            Value::Logical(_) => "logical",
+        }
+    }
+}
+
+impl From<Value> for FieldValue {
+    fn from(value: Value) -> Self {
+        match value {
+            Value::Character(s) => FieldValue::Character(Some(s)),
+            Value::Numeric(v) => FieldValue::Numeric(Some(v)),
+            Value::Date(d) => FieldValue::Date(d),
+            Value::Logical(b) => FieldValue::Logical(Some(b)),
+        }
+    }
+}
+
+/// Type of the value an expression evaluates to, and for character
+/// values their (maximum) length
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub(crate) enum ValueType {
+    Character(usize),
+    Numeric,
+    Date,
+    Logical,
+}
+
+impl ValueType {
+    fn name(self) -> &'static str {
+        match self {
+            ValueType::Character(_) => "character",
+            ValueType::Numeric => "numeric",
+            ValueType::Date => "date",
+            ValueType::Logical => "logical",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Identifier(String),
+    Str(String),
+    Number(f64),
+    Logical(bool),
+    Arithmetic(Operator),
+    LeftParen,
+    RightParen,
+    Comma,
+    Comparison(Comparison),
+    And,
+    Or,
+    Not,
+}
+
+/// The arithmetic operators
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum Operator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+}
+
+impl Operator {
+    fn verb(self) -> &'static str {
+        match self {
+            Operator::Add => "add",
+            Operator::Subtract => "subtract",
+            Operator::Multiply => "multiply",
+            Operator::Divide => "divide",
+        }
+    }
+}
+
+/// The comparison operators
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum Comparison {
+    /// `=`, strings are equal when the left one starts with the right one
+    /// (like dBase with `SET EXACT OFF`)
+    Equal,
+    /// `==`, strings are equal when they are the same, trailing blanks excepted
+    ExactlyEqual,
+    /// `<>`, `#` or `!=`, the opposite of `=`
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+impl Comparison {
+    fn is_ordering(self) -> bool {
+        !matches!(
+            self,
+            Comparison::Equal | Comparison::ExactlyEqual | Comparison::NotEqual
+        )
+    }
+
+    fn holds(self, ordering: Option<Ordering>) -> bool {
+        match (self, ordering) {
+            (_, None) => self == Comparison::NotEqual,
+            (Comparison::Equal, Some(o)) | (Comparison::ExactlyEqual, Some(o)) => {
+                o == Ordering::Equal
+            }
+            (Comparison::NotEqual, Some(o)) => o != Ordering::Equal,
+            (Comparison::Less, Some(o)) => o == Ordering::Less,
+            (Comparison::LessOrEqual, Some(o)) => o != Ordering::Greater,
+            (Comparison::Greater, Some(o)) => o == Ordering::Greater,
+            (Comparison::GreaterOrEqual, Some(o)) => o != Ordering::Less,
+        }
+    }
+}
+
+/// Returns the keyword of the identifier, if it is one
+fn keyword(identifier: &str) -> Option<Token> {
+    match identifier.to_uppercase().as_str() {
+        "AND" => Some(Token::And),
+        "OR" => Some(Token::Or),
+        "NOT" => Some(Token::Not),
+        _ => None,
+    }
+}
+
+fn tokenize(text: &str) -> Result<Vec<Token>, ErrorKind> {
+    let mut tokens = Vec::<Token>::new();
+    let mut chars = text.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '+' | '-' | '*' | '/' => {
+                chars.next();
+                tokens.push(Token::Arithmetic(match c {
+                    '+' => Operator::Add,
+                    '-' => Operator::Subtract,
+                    '*' => Operator::Multiply,
+                    _ => Operator::Divide,
+                }));
+            }
+            '(' => {
+                chars.next();
+                tokens.push(Token::LeftParen);
+            }
+            ')' => {
+                chars.next();
+                tokens.push(Token::RightParen);
+            }
+            ',' => {
+                chars.next();
+                tokens.push(Token::Comma);
+            }
+            '=' | '<' | '>' | '#' | '!' => {
+                chars.next();
+                let next = chars.peek().copied();
+                let (token, is_two_chars) = match (c, next) {
+                    ('=', Some('=')) => (Token::Comparison(Comparison::ExactlyEqual), true),
+                    ('=', _) => (Token::Comparison(Comparison::Equal), false),
+                    ('<', Some('>')) | ('!', Some('=')) => {
+                        (Token::Comparison(Comparison::NotEqual), true)
+                    }
+                    ('<', Some('=')) => (Token::Comparison(Comparison::LessOrEqual), true),
+                    ('<', _) => (Token::Comparison(Comparison::Less), false),
+                    ('>', Some('=')) => (Token::Comparison(Comparison::GreaterOrEqual), true),
+                    ('>', _) => (Token::Comparison(Comparison::Greater), false),
+                    ('#', _) => (Token::Comparison(Comparison::NotEqual), false),
+                    _ => (Token::Not, false),
+                };
+                if is_two_chars {
+                    chars.next();
+                }
+                tokens.push(token);
+            }
+            '.' if chars.clone().nth(1).is_some_and(char::is_alphabetic) => {
+                // .T., .F., .AND., .OR., .NOT.
+                chars.next();
+                let mut word = String::new();
+                loop {
+                    match chars.next() {
+                        Some('.') => break,
+                        Some(c) if c.is_alphabetic() => word.push(c),
+                        _ => {
+                            return Err(ErrorKind::InvalidExpression(format!(
+                                "invalid operator '.{}'",
+                                word
+                            )))
+                        }
+                    }
+                }
+                let token = match word.to_uppercase().as_str() {
+                    "T" | "Y" => Token::Logical(true),
+                    "F" | "N" => Token::Logical(false),
+                    _ => keyword(&word).ok_or_else(|| {
+                        ErrorKind::InvalidExpression(format!("invalid operator '.{}.'", word))
+                    })?,
+                };
+                tokens.push(token);
+            }
+            '\'' | '"' | '[' => {
+                chars.next();
+                let closing = if c == '[' { ']' } else { c };
+                let mut s = String::new();
+                loop {
+                    match chars.next() {
+                        Some(c) if c == closing => break,
+                        Some(c) => s.push(c),
+                        None => {
+                            return Err(ErrorKind::InvalidExpression(format!(
+                                "unterminated string '{}'",
+                                s
+                            )))
+                        }
+                    }
+                }
+                tokens.push(Token::Str(s));
+            }
+            c if c.is_ascii_digit() || c == '.' => {
+                let mut number = String::new();
+                while let Some(&c) = chars.peek() {
+                    if c.is_ascii_digit() || c == '.' {
+                        number.push(c);
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                let value = number.parse::<f64>().map_err(|_| {
+                    ErrorKind::InvalidExpression(format!("invalid number '{}'", number))
+                })?;
+                tokens.push(Token::Number(value));
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let mut identifier = String::new();
+                while let Some(&c) = chars.peek() {
+                    if c.is_alphanumeric() || c == '_' {
+                        identifier.push(c);
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                tokens.push(keyword(&identifier).unwrap_or(Token::Identifier(identifier)));
+            }
+            c => {
+                return Err(ErrorKind::InvalidExpression(format!(
+                    "unexpected character '{}'",
+                    c
+                )))
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+#[derive(Debug, Clone)]
+enum Node {
+    Field(FieldInfo),
+    Literal(Value),
+    Call(Function, Vec<Node>),
+    Arithmetic(Operator, Box<Node>, Box<Node>),
+    Negate(Box<Node>),
+    Compare(Comparison, Box<Node>, Box<Node>),
+    And(Box<Node>, Box<Node>),
+    Or(Box<Node>, Box<Node>),
+    Not(Box<Node>),
+}
+
+struct Parser<'a> {
+    tokens: std::iter::Peekable<std::vec::IntoIter<Token>>,
+    fields: &'a [FieldInfo],
+}
+
+impl<'a> Parser<'a> {
+    fn parse_expression(&mut self) -> Result<Node, ErrorKind> {
+        let mut node = self.parse_and()?;
+        while self.tokens.peek() == Some(&Token::Or) {
+            self.tokens.next();
+            let rhs = self.parse_and()?;
+            node = Node::Or(Box::new(node), Box::new(rhs));
+        }
+        Ok(node)
+    }
+
+    fn parse_and(&mut self) -> Result<Node, ErrorKind> {
+        let mut node = self.parse_not()?;
+        while self.tokens.peek() == Some(&Token::And) {
+            self.tokens.next();
+            let rhs = self.parse_not()?;
+            node = Node::And(Box::new(node), Box::new(rhs));
+        }
+        Ok(node)
+    }
+
+    fn parse_not(&mut self) -> Result<Node, ErrorKind> {
+        if self.tokens.peek() == Some(&Token::Not) {
+            self.tokens.next();
+            return Ok(Node::Not(Box::new(self.parse_not()?)));
+        }
+        let lhs = self.parse_sum()?;
+        if let Some(Token::Comparison(comparison)) = self.tokens.peek().cloned() {
+            self.tokens.next();
+            let rhs = self.parse_sum()?;
+            return Ok(Node::Compare(comparison, Box::new(lhs), Box::new(rhs)));
+        }
+        Ok(lhs)
+    }
+
+    fn parse_sum(&mut self) -> Result<Node, ErrorKind> {
+        let mut node = self.parse_product()?;
+        while let Some(Token::Arithmetic(operator @ (Operator::Add | Operator::Subtract))) =
+            self.tokens.peek().cloned()
+        {
+            self.tokens.next();
+            let rhs = self.parse_product()?;
+            node = Node::Arithmetic(operator, Box::new(node), Box::new(rhs));
+        }
+        Ok(node)
+    }
+
+    fn parse_product(&mut self) -> Result<Node, ErrorKind> {
+        let mut node = self.parse_unary()?;
+        while let Some(Token::Arithmetic(operator @ (Operator::Multiply | Operator::Divide))) =
+            self.tokens.peek().cloned()
+        {
+            self.tokens.next();
+            let rhs = self.parse_unary()?;
+            node = Node::Arithmetic(operator, Box::new(node), Box::new(rhs));
+        }
+        Ok(node)
+    }
+
+    fn parse_unary(&mut self) -> Result<Node, ErrorKind> {
+        match self.tokens.peek() {
+            Some(Token::Arithmetic(Operator::Subtract)) => {
+                self.tokens.next();
+                Ok(Node::Negate(Box::new(self.parse_unary()?)))
+            }
+            Some(Token::Arithmetic(Operator::Add)) => {
+                self.tokens.next();
+                self.parse_unary()
+            }
+            _ => self.parse_primary(),
+        }
+    }
+
+    fn parse_primary(&mut self) -> Result<Node, ErrorKind> {
+        match self.tokens.next() {
+            Some(Token::Str(s)) => Ok(Node::Literal(Value::Character(s))),
+            Some(Token::Number(v)) => Ok(Node::Literal(Value::Numeric(v))),
+            Some(Token::Logical(b)) => Ok(Node::Literal(Value::Logical(b))),
+            Some(Token::LeftParen) => {
+                let node = self.parse_expression()?;
+                self.expect(&Token::RightParen)?;
+                Ok(node)
+            }
+            Some(Token::Identifier(name)) => {
+                if self.tokens.peek() == Some(&Token::LeftParen) {
+                    self.tokens.next();
+                    let function = Function::from_name(&name).ok_or_else(|| {
+                        ErrorKind::InvalidExpression(format!("unknown function '{}'", name))
+                    })?;
+                    let arguments = self.parse_arguments()?;
+                    let (min, max) = function.arity();
+                    if arguments.len() < min || arguments.len() > max {
+                        return Err(ErrorKind::InvalidExpression(format!(
+                            "wrong number of arguments for {}",
+                            name.to_uppercase()
+                        )));
+                    }
+                    Ok(Node::Call(function, arguments))
+                } else {
+                    self.field(&name)
+                }
+            }
+            Some(token) => Err(ErrorKind::InvalidExpression(format!(
+                "unexpected {:?}",
+                token
+            ))),
+            None => Err(ErrorKind::InvalidExpression(
+                "unexpected end of expression".to_owned(),
+            )),
+        }
+    }
+
+    fn parse_arguments(&mut self) -> Result<Vec<Node>, ErrorKind> {
+        let mut arguments = Vec::<Node>::new();
+        if self.tokens.peek() == Some(&Token::RightParen) {
+            self.tokens.next();
+            return Ok(arguments);
+        }
+        loop {
+            arguments.push(self.parse_expression()?);
+            match self.tokens.next() {
+                Some(Token::Comma) => continue,
+                Some(Token::RightParen) => return Ok(arguments),
+                _ => {
+                    return Err(ErrorKind::InvalidExpression(
+                        "expected ',' or ')'".to_owned(),
+                    ))
+                }
+            }
+        }
+    }
+
+    fn expect(&mut self, expected: &Token) -> Result<(), ErrorKind> {
+        match self.tokens.next() {
+            Some(ref token) if token == expected => Ok(()),
+            _ => Err(ErrorKind::InvalidExpression(format!(
+                "expected {:?}",
+                expected
+            ))),
+        }
+    }
+
+    fn field(&self, name: &str) -> Result<Node, ErrorKind> {
+        find_field(self.fields, name)
+            .map(|info| Node::Field(info.clone()))
+            .ok_or_else(|| ErrorKind::InvalidExpression(format!("unknown field '{}'", name)))
+    }
+}
+
+fn find_field<'a>(fields: &'a [FieldInfo], name: &str) -> Option<&'a FieldInfo> {
+    fields
+        .iter()
+        .filter(|info| !info.is_deletion_flag())
+        .find(|info| field_names_match(&info.name, name))
+}
+
+fn type_error(function: &str, expected: &str, actual: ValueType) -> ErrorKind {
+    ErrorKind::InvalidExpression(format!(
+        "{} expects a {} value, got a {} one",
+        function,
+        expected,
+        actual.name()
+    ))
+}
+
+/// Returns the literal integer value of a node, used for the arguments
+/// that determine the length of the result
+fn literal_integer(node: &Node) -> Option<usize> {
+    match node {
+        Node::Literal(Value::Numeric(v)) if *v >= 0.0 => Some(*v as usize),
+        _ => None,
+    }
+}
+
+impl Node {
+    /// Computes the type of the node, checking that the functions are
+    /// given arguments of the right type
+    fn value_type(&self) -> Result<ValueType, ErrorKind> {
+        match self {
+            Node::Field(info) => Ok(match info.field_type {
+                FieldType::Character => ValueType::Character(usize::from(info.field_length)),
+                FieldType::Memo => ValueType::Character(0),
+                FieldType::Numeric
+                | FieldType::Float
+                | FieldType::Integer
+                | FieldType::Double
+                | FieldType::Currency
+                | FieldType::Long
+                | FieldType::DBase7Double
+                | FieldType::Autoincrement => ValueType::Numeric,
+                FieldType::Date => ValueType::Date,
+                FieldType::Logical => ValueType::Logical,
+                FieldType::DateTime | FieldType::Timestamp => {
+                    return Err(ErrorKind::InvalidExpression(format!(
+                        "the type of field '{}' is not supported in expressions",
+                        info.name
+                    )))
+                }
+            }),
+            Node::Literal(Value::Character(s)) => Ok(ValueType::Character(s.chars().count())),
+            Node::Literal(Value::Numeric(_)) => Ok(ValueType::Numeric),
+            Node::Literal(Value::Date(_)) => Ok(ValueType::Date),
+            Node::Literal(Value::Logical(_)) => Ok(ValueType::Logical),
+            Node::Arithmetic(operator, lhs, rhs) => {
+                match (operator, lhs.value_type()?, rhs.value_type()?) {
+                    (
+                        Operator::Add | Operator::Subtract,
+                        ValueType::Character(a),
+                        ValueType::Character(b),
+                    ) => Ok(ValueType::Character(a + b)),
+                    (_, ValueType::Numeric, ValueType::Numeric) => Ok(ValueType::Numeric),
+                    (Operator::Add | Operator::Subtract, ValueType::Date, ValueType::Numeric) => {
+                        Ok(ValueType::Date)
+                    }
+                    (Operator::Subtract, ValueType::Date, ValueType::Date) => {
+                        Ok(ValueType::Numeric)
+                    }
+                    (operator, a, b) => Err(ErrorKind::InvalidExpression(format!(
+                        "cannot {} a {} value and a {} one",
+                        operator.verb(),
+                        a.name(),
+                        b.name()
+                    ))),
+                }
+            }
+            Node::Negate(node) => match node.value_type()? {
+                ValueType::Numeric => Ok(ValueType::Numeric),
+                t => Err(type_error("-", "numeric", t)),
+            },
+            Node::Compare(comparison, lhs, rhs) => match (lhs.value_type()?, rhs.value_type()?) {
+                (ValueType::Character(_), ValueType::Character(_))
+                | (ValueType::Numeric, ValueType::Numeric)
+                | (ValueType::Date, ValueType::Date) => Ok(ValueType::Logical),
+                (ValueType::Logical, ValueType::Logical) if !comparison.is_ordering() => {
+                    Ok(ValueType::Logical)
+                }
+                (a, b) => Err(ErrorKind::InvalidExpression(format!(
+                    "cannot compare a {} value and a {} one",
+                    a.name(),
+                    b.name()
+                ))),
+            },
+            Node::And(lhs, rhs) | Node::Or(lhs, rhs) => {
+                for node in &[lhs, rhs] {
+                    match node.value_type()? {
+                        ValueType::Logical => {}
+                        t => return Err(type_error("AND/OR", "logical", t)),
+                    }
+                }
+                Ok(ValueType::Logical)
+            }
+            Node::Not(node) => match node.value_type()? {
+                ValueType::Logical => Ok(ValueType::Logical),
+                t => Err(type_error("NOT", "logical", t)),
+            },
+            Node::Call(function, arguments) => {
+                let types = arguments
+                    .iter()
+                    .map(Node::value_type)
+                    .collect::<Result<Vec<ValueType>, ErrorKind>>()?;
+                match function {
+                    Function::Upper
+                    | Function::Lower
+                    | Function::Trim
+                    | Function::Ltrim
+                    | Function::Alltrim => match types[0] {
+                        // The length of the trimmed strings is the maximum length
+                        ValueType::Character(len) => Ok(ValueType::Character(len)),
+                        t => Err(type_error(function.name(), "character", t)),
+                    },
+                    Function::Len | Function::Val => match types[0] {
+                        ValueType::Character(_) => Ok(ValueType::Numeric),
+                        t => Err(type_error(function.name(), "character", t)),
+                    },
+                    Function::Iif => match (types[0], types[1], types[2]) {
+                        (ValueType::Logical, ValueType::Character(a), ValueType::Character(b)) => {
+                            Ok(ValueType::Character(a.max(b)))
+                        }
+                        (ValueType::Logical, a, b) if a == b => Ok(a),
+                        (ValueType::Logical, a, b) => Err(ErrorKind::InvalidExpression(format!(
+                            "IIF values must have the same type, got a {} value and a {} one",
+                            a.name(),
+                            b.name()
+                        ))),
+                        (t, _, _) => Err(type_error("IIF", "logical", t)),
+                    },
+                    Function::Dtos => match types[0] {
+                        ValueType::Date => Ok(ValueType::Character(8)),
+                        t => Err(type_error("DTOS", "date", t)),
+                    },
+                    Function::Str => {
+                        if let Some(t) = types.iter().find(|t| **t != ValueType::Numeric) {
+                            return Err(type_error("STR", "numeric", *t));
+                        }
+                        let len = arguments.get(1).map_or(Some(10), literal_integer);
+                        Ok(ValueType::Character(len.unwrap_or(10)))
+                    }
+                    Function::Substr => {
+                        let len = match types[0] {
+                            ValueType::Character(len) => len,
+                            t => return Err(type_error("SUBSTR", "character", t)),
+                        };
+                        if let Some(t) = types[1..].iter().find(|t| **t != ValueType::Numeric) {
+                            return Err(type_error("SUBSTR", "numeric", *t));
+                        }
+                        let start = literal_integer(&arguments[1]).unwrap_or(1).max(1);
+                        let remaining = len.saturating_sub(start - 1);
+                        let count = arguments
+                            .get(2)
+                            .map_or(Some(remaining), literal_integer)
+                            .unwrap_or(remaining);
+                        Ok(ValueType::Character(count.min(remaining)))
+                    }
+                }
+            }
+        }
+    }
+
+    /// Adds the names of the fields the node uses, once each
+    fn add_field_names<'a>(&'a self, names: &mut Vec<&'a str>) {
+        match self {
+            Node::Field(info) => {
+                if !names.contains(&info.name()) {
+                    names.push(info.name());
+                }
+            }
+            Node::Literal(_) => {}
+            Node::Call(_, arguments) => {
+                for argument in arguments {
+                    argument.add_field_names(names);
+                }
+            }
+            Node::Arithmetic(_, lhs, rhs)
+            | Node::Compare(_, lhs, rhs)
+            | Node::And(lhs, rhs)
+            | Node::Or(lhs, rhs) => {
+                lhs.add_field_names(names);
+                rhs.add_field_names(names);
+            }
+            Node::Not(node) | Node::Negate(node) => node.add_field_names(names),
+        }
+    }
+
+    fn evaluate(&self, record: &Record) -> Result<Value, ErrorKind> {
+        match self {
+            Node::Field(info) => {
+                let value = record.get(&info.name).ok_or_else(|| {
+                    ErrorKind::InvalidExpression(format!(
+                        "the record has no field named '{}'",
+                        info.name
+                    ))
+                })?;
+                Value::from_field_value(value, info)
+            }
+            Node::Literal(value) => Ok(value.clone()),
+            Node::Arithmetic(operator, lhs, rhs) => {
+                arithmetic(*operator, lhs.evaluate(record)?, rhs.evaluate(record)?)
+            }
+            Node::Negate(node) => match node.evaluate(record)? {
+                Value::Numeric(v) => Ok(Value::Numeric(-v)),
+                value => Err(ErrorKind::InvalidExpression(format!(
+                    "cannot negate a {} value",
+                    value.type_name()
+                ))),
+            },
+            Node::Call(Function::Iif, arguments) => {
+                if arguments[0].evaluate(record)?.is_true() {
+                    arguments[1].evaluate(record)
+                } else {
+                    arguments[2].evaluate(record)
+                }
+            }
+            Node::Compare(comparison, lhs, rhs) => {
+                let (lhs, rhs) = (lhs.evaluate(record)?, rhs.evaluate(record)?);
+                Ok(Value::Logical(compare(*comparison, &lhs, &rhs)))
+            }
+            // Like dBase, the right side is not evaluated when the left one decides
+            Node::And(lhs, rhs) => Ok(Value::Logical(
+                lhs.evaluate(record)?.is_true() && rhs.evaluate(record)?.is_true(),
+            )),
+            Node::Or(lhs, rhs) => Ok(Value::Logical(
+                lhs.evaluate(record)?.is_true() || rhs.evaluate(record)?.is_true(),
+            )),
+            Node::Not(node) => Ok(Value::Logical(!node.evaluate(record)?.is_true())),
+            Node::Call(function, arguments) => {
+                let values = arguments
+                    .iter()
+                    .map(|argument| argument.evaluate(record))
+                    .collect::<Result<Vec<Value>, ErrorKind>>()?;
+                call(*function, &values)
+            }
+        }
+    }
+}
+
+fn arithmetic(operator: Operator, lhs: Value, rhs: Value) -> Result<Value, ErrorKind> {
+    let add_days = |date: Option<Date>, days: f64| {
+        date.map(|d| {
+            Date::julian_day_number_to_gregorian_date(d.to_julian_day_number() + days as i32)
+        })
+    };
+    let value = match (operator, lhs, rhs) {
+        (Operator::Add, Value::Character(mut a), Value::Character(b)) => {
+            a.push_str(&b);
+            Value::Character(a)
+        }
+        (Operator::Subtract, Value::Character(a), Value::Character(b)) => {
+            // The trailing blanks of the first string go at the end
+            let trimmed = a.trim_end_matches(' ');
+            let num_blanks = a.len() - trimmed.len();
+            let mut result = String::with_capacity(a.len() + b.len());
+            result.push_str(trimmed);
+            result.push_str(&b);
+            result.push_str(&" ".repeat(num_blanks));
+            Value::Character(result)
+        }
+        (Operator::Add, Value::Numeric(a), Value::Numeric(b)) => Value::Numeric(a + b),
+        (Operator::Subtract, Value::Numeric(a), Value::Numeric(b)) => Value::Numeric(a - b),
+        (Operator::Multiply, Value::Numeric(a), Value::Numeric(b)) => Value::Numeric(a * b),
+        (Operator::Divide, Value::Numeric(_), Value::Numeric(0.0)) => {
+            return Err(ErrorKind::InvalidExpression("division by zero".to_owned()))
+        }
+        (Operator::Divide, Value::Numeric(a), Value::Numeric(b)) => Value::Numeric(a / b),
+        (Operator::Add, Value::Date(d), Value::Numeric(n)) => Value::Date(add_days(d, n)),
+        (Operator::Subtract, Value::Date(d), Value::Numeric(n)) => Value::Date(add_days(d, -n)),
+        (Operator::Subtract, Value::Date(a), Value::Date(b)) => match (a, b) {
+            (Some(a), Some(b)) => Value::Numeric(f64::from(
+                a.to_julian_day_number() - b.to_julian_day_number(),
+            )),
+            _ => Value::Numeric(0.0),
+        },
+        (operator, a, b) => {
+            return Err(ErrorKind::InvalidExpression(format!(
+                "cannot {} a {} value and a {} one",
+                operator.verb(),
+                a.type_name(),
+                b.type_name()
+            )))
+        }
+    };
+    Ok(value)
+}
+
+/// Returns the number at the start of the string, like dBase's `VAL`
+fn leading_number(s: &str) -> f64 {
+    let s = s.trim_start_matches(' ');
+    let mut end = 0;
+    let mut has_dot = false;
+    for (i, c) in s.char_indices() {
+        match c {
+            '+' | '-' if i == 0 => {}
+            '.' if !has_dot => has_dot = true,
+            c if c.is_ascii_digit() => {}
+            _ => break,
+        }
+        end = i + c.len_utf8();
+    }
+    s[..end].parse::<f64>().unwrap_or(0.0)
+}
+
+fn compare(comparison: Comparison, lhs: &Value, rhs: &Value) -> bool {
+    let ordering = match (lhs, rhs) {
+        (Value::Character(a), Value::Character(b)) => match comparison {
+            Comparison::Equal | Comparison::NotEqual => Some(if a.starts_with(b.as_str()) {
+                Ordering::Equal
+            } else {
+                Ordering::Less
+            }),
+            _ => Some(a.trim_end_matches(' ').cmp(b.trim_end_matches(' '))),
+        },
+        (Value::Numeric(a), Value::Numeric(b)) => a.partial_cmp(b),
+        (Value::Date(a), Value::Date(b)) => Some(a.cmp(b)),
+        (Value::Logical(a), Value::Logical(b)) => Some(a.cmp(b)),
+        _ => None,
+    };
+    comparison.holds(ordering)
+}
+
+fn numeric_argument(values: &[Value], position: usize) -> Option<f64> {
+    match values.get(position) {
+        Some(Value::Numeric(v)) => Some(*v),
+        _ => None,
+    }
+}
+
+fn call(function: Function, values: &[Value]) -> Result<Value, ErrorKind> {
+    let result = match (function, &values[0]) {
+        (Function::Upper, Value::Character(s)) => Value::Character(s.to_uppercase()),
+        (Function::Lower, Value::Character(s)) => Value::Character(s.to_lowercase()),
+        (Function::Trim, Value::Character(s)) => {
+            Value::Character(s.trim_end_matches(' ').to_owned())
+        }
+        (Function::Ltrim, Value::Character(s)) => {
+            Value::Character(s.trim_start_matches(' ').to_owned())
+        }
+        (Function::Alltrim, Value::Character(s)) => {
+            Value::Character(s.trim_matches(' ').to_owned())
+        }
+        (Function::Len, Value::Character(s)) => Value::Numeric(s.chars().count() as f64),
+        (Function::Val, Value::Character(s)) => Value::Numeric(leading_number(s)),
+        (Function::Dtos, Value::Date(d)) => Value::Character(match d {
+            Some(d) => d.to_string(),
+            None => " ".repeat(8),
+        }),
+        (Function::Str, Value::Numeric(v)) => {
+            let len = numeric_argument(values, 1).map_or(10, |l| l.max(0.0) as usize);
+            let decimals = numeric_argument(values, 2).map_or(0, |d| d.max(0.0) as usize);
+            let mut s = String::new();
+            let _ = write!(s, "{:>width$.prec$}", v, width = len, prec = decimals);
+            if s.len() > len {
+                s = "*".repeat(len);
+            }
+            Value::Character(s)
+        }
+        (Function::Substr, Value::Character(s)) => {
+            let start = numeric_argument(values, 1).map_or(1, |v| v.max(1.0) as usize);
+            let chars = s.chars().skip(start - 1);
+            Value::Character(match numeric_argument(values, 2) {
+                Some(count) => chars.take(count.max(0.0) as usize).collect(),
+                None => chars.collect(),
+            })
+        }
+        (function, value) => {
+            return Err(ErrorKind::InvalidExpression(format!(
+                "{} cannot be applied to a {} value",
+                function.name(),
+                value.type_name()
+            )))
+        }
+    };
+    Ok(result)
+}
+
+/// An xBase expression parsed against the fields of a table
+///
+/// # Example
+///
+/// ```
+/// let reader = dbase::Reader::from_path("tests/data/stations.dbf").unwrap();
+/// let expression = dbase::Expression::parse("UPPER(TRIM(name)) + '!'", reader.fields()).unwrap();
+/// assert_eq!(expression.field_type(), dbase::FieldType::Character);
+///
+/// let records = dbase::read("tests/data/stations.dbf").unwrap();
+/// let value = expression.evaluate(&records[0]).unwrap();
+/// assert_eq!(value, dbase::FieldValue::Character(Some("VAN DORN STREET!".to_owned())));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Expression {
+    text: String,
+    root: Node,
+    value_type: ValueType,
+}
+
+impl Expression {
+    /// Parses the expression, field names are resolved (case-insensitively)
+    /// using the given fields
+    pub fn parse(text: &str, fields: &[FieldInfo]) -> Result<Self, ErrorKind> {
+        // Some field names cannot be written in an expression (eg: they contain spaces)
+        // but we still want them to be usable on their own
+        let root = if let Some(info) = find_field(fields, text.trim()) {
+            Node::Field(info.clone())
+        } else {
+            let mut parser = Parser {
+                tokens: tokenize(text)?.into_iter().peekable(),
+                fields,
+            };
+            let root = parser.parse_expression()?;
+            if let Some(token) = parser.tokens.next() {
+                return Err(ErrorKind::InvalidExpression(format!(
+                    "unexpected {:?}",
+                    token
+                )));
+            }
+            root
+        };
+        let value_type = root.value_type()?;
+        Ok(Self {
+            text: text.trim().to_owned(),
+            root,
+            value_type,
+        })
+    }
+
+    /// Returns the text of the expression
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Returns the type of the values the expression evaluates to
+    pub(crate) fn value_type(&self) -> ValueType {
+        self.value_type
+    }
+
+    /// Returns the type of the field that can hold the values of the expression
+    pub fn field_type(&self) -> FieldType {
+        match self.value_type {
+            ValueType::Character(_) => FieldType::Character,
+            ValueType::Numeric => FieldType::Numeric,
+            ValueType::Date => FieldType::Date,
+            ValueType::Logical => FieldType::Logical,
+        }
+    }
+
+    /// Returns the names of the fields the expression uses
+    pub fn field_names(&self) -> Vec<&str> {
+        let mut names = vec![];
+        self.root.add_field_names(&mut names);
+        names
+    }
+
+    /// Evaluates the expression on a record
+    ///
+    /// The record must have the fields used by the expression, otherwise
+    /// an error is returned.
+    pub fn evaluate(&self, record: &Record) -> Result<FieldValue, ErrorKind> {
+        self.evaluate_value(record).map(FieldValue::from)
+    }
+
+    pub(crate) fn evaluate_value(&self, record: &Record) -> Result<Value, ErrorKind> {
+        self.root.evaluate(record)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::convert::TryFrom;
+
+    use crate::record::FieldName;
+
+    fn fields() -> Vec<FieldInfo> {
+        let mut amount = FieldInfo::new(
+            FieldName::try_from("AMOUNT").unwrap(),
+            FieldType::Numeric,
+            10,
+        );
+        amount.num_decimal_places = 2;
+        vec![
+            FieldInfo::new_deletion_flag(),
+            FieldInfo::new(
+                FieldName::try_from("LastName").unwrap(),
+                FieldType::Character,
+                10,
+            ),
+            FieldInfo::new(FieldName::try_from("DOB").unwrap(), FieldType::Date, 8),
+            amount,
+        ]
+    }
+
+    fn record() -> Record {
+        let mut record = Record::default();
+        record.insert(
+            "LastName".to_owned(),
+            FieldValue::Character(Some("Dupont".to_owned())),
+        );
+        record.insert(
+            "DOB".to_owned(),
+            FieldValue::Date(Some(Date::new(3, 2, 1985))),
+        );
+        record.insert("AMOUNT".to_owned(), FieldValue::Numeric(Some(12.5)));
+        record
+    }
+
+    fn evaluate(text: &str) -> Value {
+        Expression::parse(text, &fields())
+            .unwrap()
+            .evaluate_value(&record())
+            .unwrap()
+    }
+
+    #[test]
+    fn field_is_padded() {
+        assert_eq!(
+            evaluate("lastname"),
+            Value::Character("Dupont    ".to_owned())
+        );
+    }
+
+    #[test]
+    fn concat_upper_dtos() {
+        let expression = Expression::parse("UPPER(LASTNAME)+DTOS(DOB)", &fields()).unwrap();
+        assert_eq!(expression.value_type(), ValueType::Character(18));
+        assert_eq!(
+            expression.evaluate_value(&record()).unwrap(),
+            Value::Character("DUPONT    19850203".to_owned())
+        );
+    }
+
+    #[test]
+    fn str_and_substr() {
+        assert_eq!(
+            evaluate("STR(AMOUNT)"),
+            Value::Character("        12".to_owned())
+        );
+        assert_eq!(
+            evaluate("STR(AMOUNT, 6, 2)"),
+            Value::Character(" 12.50".to_owned())
+        );
+        assert_eq!(
+            evaluate("STR(123456, 3)"),
+            Value::Character("***".to_owned())
+        );
+        assert_eq!(
+            evaluate("SUBSTR(LASTNAME, 2, 3)"),
+            Value::Character("upo".to_owned())
+        );
+        assert_eq!(
+            evaluate("SUBSTR('ABCDEF', 4)"),
+            Value::Character("DEF".to_owned())
+        );
+        let expression = Expression::parse("SUBSTR(LASTNAME, 3)", &fields()).unwrap();
+        assert_eq!(expression.value_type(), ValueType::Character(8));
+    }
+
+    #[test]
+    fn literals_and_parens() {
+        assert_eq!(
+            evaluate("('A' + \"B\") + [C]"),
+            Value::Character("ABC".to_owned())
+        );
+        assert_eq!(evaluate("AMOUNT + 1"), Value::Numeric(13.5));
+        assert_eq!(
+            evaluate("DOB + 30"),
+            Value::Date(Some(Date::new(5, 3, 1985)))
+        );
+    }
+
+    #[test]
+    fn arithmetic() {
+        assert_eq!(evaluate("AMOUNT * 2 - 10 / 4"), Value::Numeric(22.5));
+        assert_eq!(evaluate("(AMOUNT + 1) * -2"), Value::Numeric(-27.0));
+        assert_eq!(evaluate("-AMOUNT + 1"), Value::Numeric(-11.5));
+        assert_eq!(
+            evaluate("DOB - 3"),
+            Value::Date(Some(Date::new(31, 1, 1985)))
+        );
+        assert_eq!(evaluate("(DOB + 30) - DOB"), Value::Numeric(30.0));
+        assert_eq!(
+            evaluate("LASTNAME - 'X'"),
+            Value::Character("DupontX    ".to_owned())
+        );
+
+        let expression = Expression::parse("AMOUNT / (AMOUNT - 12.5)", &fields()).unwrap();
+        assert!(expression.evaluate(&record()).is_err());
+    }
+
+    #[test]
+    fn string_functions() {
+        assert_eq!(
+            evaluate("TRIM(LASTNAME) + ','"),
+            Value::Character("Dupont,".to_owned())
+        );
+        assert_eq!(
+            evaluate("RTRIM(LASTNAME) + LOWER(' ABC ')"),
+            Value::Character("Dupont abc ".to_owned())
+        );
+        assert_eq!(
+            evaluate("LTRIM('  A ') + ALLTRIM('  B  ')"),
+            Value::Character("A B".to_owned())
+        );
+        assert_eq!(evaluate("LEN(LASTNAME)"), Value::Numeric(10.0));
+        assert_eq!(evaluate("LEN(TRIM(LASTNAME))"), Value::Numeric(6.0));
+        assert_eq!(evaluate("VAL(' -12.5kg')"), Value::Numeric(-12.5));
+        assert_eq!(evaluate("VAL('abc')"), Value::Numeric(0.0));
+        assert_eq!(evaluate("VAL(STR(AMOUNT, 6, 2)) * 2"), Value::Numeric(25.0));
+    }
+
+    #[test]
+    fn iif() {
+        assert_eq!(
+            evaluate("IIF(AMOUNT > 10, 'big', 'small')"),
+            Value::Character("big".to_owned())
+        );
+        assert_eq!(evaluate("IIF(AMOUNT > 100, 1, 2) + 1"), Value::Numeric(3.0));
+        // Only the value returned is evaluated
+        assert_eq!(
+            evaluate("IIF(AMOUNT = 0, 1 / AMOUNT, AMOUNT)"),
+            Value::Numeric(12.5)
+        );
+
+        let expression = Expression::parse("IIF(.T., LASTNAME, 'A')", &fields()).unwrap();
+        assert_eq!(expression.value_type(), ValueType::Character(10));
+        assert_eq!(expression.field_type(), FieldType::Character);
+        assert_eq!(
+            expression.evaluate(&record()).unwrap(),
+            FieldValue::Character(Some("Dupont    ".to_owned()))
+        );
+    }
+
+    #[test]
+    fn comparisons_and_logical_operators() {
+        assert_eq!(evaluate("LASTNAME = 'Dup'"), Value::Logical(true));
+        assert_eq!(evaluate("LASTNAME == 'Dup'"), Value::Logical(false));
+        assert_eq!(evaluate("LASTNAME == 'Dupont'"), Value::Logical(true));
+        assert_eq!(evaluate("LASTNAME <> 'Dupont'"), Value::Logical(false));
+        assert_eq!(evaluate("LASTNAME < 'Durand'"), Value::Logical(true));
+        assert_eq!(
+            evaluate("AMOUNT > 10 AND AMOUNT <= 12.5"),
+            Value::Logical(true)
+        );
+        assert_eq!(
+            evaluate("AMOUNT # 12.5 .OR. .NOT. DOB >= DOB + 1"),
+            Value::Logical(true)
+        );
+        assert_eq!(evaluate("!(AMOUNT + 1 != 13.5)"), Value::Logical(true));
+        assert_eq!(evaluate(".T. = .F. or .t."), Value::Logical(true));
+        assert_eq!(evaluate("not AMOUNT > 1 and .F."), Value::Logical(false));
+
+        let expression = Expression::parse(
+            "UPPER(LASTNAME) = 'D' AND AMOUNT > 1 OR lastname = 'X'",
+            &fields(),
+        )
+        .unwrap();
+        assert_eq!(expression.value_type(), ValueType::Logical);
+        assert_eq!(expression.field_names(), vec!["LastName", "AMOUNT"]);
+    }
+
+    #[test]
+    fn invalid_expressions() {
+        let fields = fields();
+        for text in &[
+            "UPPER(DOB)",
+            "NOPE",
+            "LASTNAME + AMOUNT",
+            "FOO(LASTNAME)",
+            "UPPER(LASTNAME",
+            "DTOS()",
+            "'unterminated",
+            "LASTNAME LASTNAME",
+            "AMOUNT > 'A'",
+            "AMOUNT AND .T.",
+            ".T. < .F.",
+            "NOT LASTNAME",
+            ".X.",
+            "AMOUNT = ",
+            "LASTNAME * 2",
+            "DOB + DOB",
+            "-LASTNAME",
+            "TRIM(AMOUNT)",
+            "VAL(AMOUNT)",
+            "IIF(AMOUNT, 1, 2)",
+            "IIF(.T., 1, 'A')",
+            "IIF(.T., 1)",
+            "AMOUNT /",
+        ] {
+            assert!(Expression::parse(text, &fields).is_err(), "{}", text);
+        }
+    }
+}
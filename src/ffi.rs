@@ -0,0 +1,478 @@
+//! Module with the definition of the C API
+//!
+//! The declarations are in `include/dbase.h`, the shared library is built with:
+//!
+//! ```text
+//! cargo rustc --release --features ffi --crate-type cdylib
+//! ```
+//!
+//! Functions that fail return a null pointer or a negative value,
+//! the message of the error is then returned by `dbase_last_error`.
+//!
+//! Strings are UTF-8 and NUL terminated, the strings returned are owned by the reader:
+//! they stay valid until the next call with the same reader.
+use std::cell::RefCell;
+use std::ffi::{CStr, CString};
+use std::fs::File;
+use std::io::BufReader;
+use std::os::raw::{c_char, c_int};
+use std::ptr;
+
+use crate::reading::{Reader, RecordValues};
+use crate::record::field::FieldValue;
+use crate::record::FieldInfo;
+use crate::sql::text_value;
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_last_error<E: ToString>(error: E) {
+    let message = CString::new(error.to_string().replace('\0', " "))
+        .unwrap_or_else(|_| CString::new("invalid error message").unwrap());
+    LAST_ERROR.with(|last_error| *last_error.borrow_mut() = Some(message));
+}
+
+/// A reader opened by `dbase_reader_open`,
+/// along with the last record read
+pub struct DbaseReader {
+    reader: Reader<BufReader<File>>,
+    /// The fields, without the deletion flag
+    fields_info: Vec<FieldInfo>,
+    field_names: Vec<CString>,
+    record: Option<RecordValues>,
+    /// The last string returned
+    text: CString,
+}
+
+impl DbaseReader {
+    fn value(&self, index: usize) -> Option<&FieldValue> {
+        let value = self
+            .record
+            .as_ref()
+            .and_then(|record| record.values.get(index));
+        if value.is_none() {
+            set_last_error(format!("there is no field {} in the current record", index));
+        }
+        value
+    }
+}
+
+/// Returns the message of the last error that happened in the current thread,
+/// or null if there was none.
+///
+/// The message stays valid until the next error.
+#[no_mangle]
+pub extern "C" fn dbase_last_error() -> *const c_char {
+    LAST_ERROR.with(|last_error| {
+        last_error
+            .borrow()
+            .as_ref()
+            .map_or(ptr::null(), |message| message.as_ptr())
+    })
+}
+
+/// Opens the file at the given path, returns null on error.
+///
+/// The reader must be freed with `dbase_reader_free`.
+///
+/// # Safety
+///
+/// `path` must be a valid NUL terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn dbase_reader_open(path: *const c_char) -> *mut DbaseReader {
+    if path.is_null() {
+        set_last_error("the path is null");
+        return ptr::null_mut();
+    }
+    let path = match CStr::from_ptr(path).to_str() {
+        Ok(path) => path,
+        Err(error) => {
+            set_last_error(error);
+            return ptr::null_mut();
+        }
+    };
+    let reader = match Reader::from_path(path) {
+        Ok(reader) => reader,
+        Err(error) => {
+            set_last_error(error);
+            return ptr::null_mut();
+        }
+    };
+    let fields_info = reader
+        .fields()
+        .iter()
+        .filter(|field_info| !field_info.is_deletion_flag())
+        .cloned()
+        .collect::<Vec<FieldInfo>>();
+    let field_names = fields_info
+        .iter()
+        .map(|field_info| CString::new(field_info.name()).unwrap_or_default())
+        .collect();
+    Box::into_raw(Box::new(DbaseReader {
+        reader,
+        fields_info,
+        field_names,
+        record: None,
+        text: CString::default(),
+    }))
+}
+
+/// Closes the reader
+///
+/// # Safety
+///
+/// `reader` must be null or a reader returned by `dbase_reader_open`
+/// that was not freed.
+#[no_mangle]
+pub unsafe extern "C" fn dbase_reader_free(reader: *mut DbaseReader) {
+    if !reader.is_null() {
+        drop(Box::from_raw(reader));
+    }
+}
+
+/// Returns the number of records of the table, as written in its header
+///
+/// # Safety
+///
+/// `reader` must be a valid reader.
+#[no_mangle]
+pub unsafe extern "C" fn dbase_reader_num_records(reader: *const DbaseReader) -> u32 {
+    let reader = &*reader;
+    reader.reader.header().num_records
+}
+
+/// Returns the number of fields of the records
+///
+/// # Safety
+///
+/// `reader` must be a valid reader.
+#[no_mangle]
+pub unsafe extern "C" fn dbase_reader_num_fields(reader: *const DbaseReader) -> usize {
+    let reader = &*reader;
+    reader.fields_info.len()
+}
+
+/// Returns the name of the field at the given index, or null if there is none.
+///
+/// # Safety
+///
+/// `reader` must be a valid reader.
+#[no_mangle]
+pub unsafe extern "C" fn dbase_reader_field_name(
+    reader: *const DbaseReader,
+    index: usize,
+) -> *const c_char {
+    let reader = &*reader;
+    match reader.field_names.get(index) {
+        Some(name) => name.as_ptr(),
+        None => {
+            set_last_error(format!("there is no field {}", index));
+            ptr::null()
+        }
+    }
+}
+
+/// Returns the type of the field at the given index, as written in the file
+/// ('C', 'N', 'D', ...), or 0 if there is none.
+///
+/// # Safety
+///
+/// `reader` must be a valid reader.
+#[no_mangle]
+pub unsafe extern "C" fn dbase_reader_field_type(
+    reader: *const DbaseReader,
+    index: usize,
+) -> c_char {
+    let reader = &*reader;
+    match reader.fields_info.get(index) {
+        Some(field_info) => u8::from(field_info.field_type) as c_char,
+        None => {
+            set_last_error(format!("there is no field {}", index));
+            0
+        }
+    }
+}
+
+/// Returns the length of the field at the given index, or -1 if there is none.
+///
+/// # Safety
+///
+/// `reader` must be a valid reader.
+#[no_mangle]
+pub unsafe extern "C" fn dbase_reader_field_length(
+    reader: *const DbaseReader,
+    index: usize,
+) -> c_int {
+    let reader = &*reader;
+    match reader.fields_info.get(index) {
+        Some(field_info) => c_int::from(field_info.field_length),
+        None => {
+            set_last_error(format!("there is no field {}", index));
+            -1
+        }
+    }
+}
+
+/// Returns the number of decimal places of the field at the given index,
+/// or -1 if there is none.
+///
+/// # Safety
+///
+/// `reader` must be a valid reader.
+#[no_mangle]
+pub unsafe extern "C" fn dbase_reader_field_decimals(
+    reader: *const DbaseReader,
+    index: usize,
+) -> c_int {
+    let reader = &*reader;
+    match reader.fields_info.get(index) {
+        Some(field_info) => c_int::from(field_info.num_decimal_places),
+        None => {
+            set_last_error(format!("there is no field {}", index));
+            -1
+        }
+    }
+}
+
+/// Reads the next record, which becomes the current one.
+///
+/// Returns 1 if a record was read, 0 if all the records were read and -1 on error.
+///
+/// # Safety
+///
+/// `reader` must be a valid reader.
+#[no_mangle]
+pub unsafe extern "C" fn dbase_reader_next_record(reader: *mut DbaseReader) -> c_int {
+    let reader = &mut *reader;
+    match reader.reader.read_next_record_as::<RecordValues>() {
+        Some(Ok(record)) => {
+            reader.record = Some(record);
+            1
+        }
+        Some(Err(error)) => {
+            reader.record = None;
+            set_last_error(error);
+            -1
+        }
+        None => {
+            reader.record = None;
+            0
+        }
+    }
+}
+
+/// Returns 1 if the current record is marked as deleted, 0 if not,
+/// and -1 if there is no current record.
+///
+/// # Safety
+///
+/// `reader` must be a valid reader.
+#[no_mangle]
+pub unsafe extern "C" fn dbase_record_is_deleted(reader: *const DbaseReader) -> c_int {
+    let reader = &*reader;
+    match reader.record {
+        Some(ref record) => c_int::from(record.is_deleted),
+        None => {
+            set_last_error("there is no current record");
+            -1
+        }
+    }
+}
+
+/// Returns 1 if the value of the field at the given index of the current record
+/// is empty, 0 if not, and -1 on error.
+///
+/// # Safety
+///
+/// `reader` must be a valid reader.
+#[no_mangle]
+pub unsafe extern "C" fn dbase_field_is_null(reader: *const DbaseReader, index: usize) -> c_int {
+    let reader = &*reader;
+    match reader.value(index) {
+        Some(value) => c_int::from(text_value(value).is_none()),
+        None => -1,
+    }
+}
+
+/// Returns the value of the field at the given index of the current record as text,
+/// or null if the value is empty or on error.
+///
+/// Numbers are formatted with a dot as decimal separator, dates as `YYYY-MM-DD`,
+/// date times as `YYYY-MM-DD HH:MM:SS` and logicals as `t` or `f`.
+///
+/// # Safety
+///
+/// `reader` must be a valid reader.
+#[no_mangle]
+pub unsafe extern "C" fn dbase_field_as_string(
+    reader: *mut DbaseReader,
+    index: usize,
+) -> *const c_char {
+    let reader = &mut *reader;
+    let text = match reader.value(index).map(text_value) {
+        Some(Some(text)) => text,
+        _ => return ptr::null(),
+    };
+    reader.text = CString::new(text.replace('\0', "")).unwrap_or_default();
+    reader.text.as_ptr()
+}
+
+/// Writes the value of the Numeric, Float, Double, Currency or Integer field
+/// at the given index of the current record in `value`.
+///
+/// Returns 0 on success, 1 if the value is empty and -1 on error.
+///
+/// # Safety
+///
+/// `reader` must be a valid reader and `value` a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn dbase_field_as_double(
+    reader: *const DbaseReader,
+    index: usize,
+    value: *mut f64,
+) -> c_int {
+    let reader = &*reader;
+    let number = match reader.value(index) {
+        Some(FieldValue::Numeric(number)) => *number,
+        Some(FieldValue::Float(number)) => number.map(f64::from),
+        Some(FieldValue::Double(number)) | Some(FieldValue::Currency(number)) => Some(*number),
+        Some(FieldValue::Integer(number)) | Some(FieldValue::Autoincrement(number)) => {
+            Some(f64::from(*number))
+        }
+        Some(FieldValue::Long(number)) => number.map(f64::from),
+        Some(FieldValue::DBase7Double(number)) => *number,
+        Some(FieldValue::NumericInteger(number)) => number.map(|number| number as f64),
+        Some(_) => {
+            set_last_error(format!("the field {} is not a number", index));
+            return -1;
+        }
+        None => return -1,
+    };
+    match number {
+        Some(number) => {
+            *value = number;
+            0
+        }
+        None => 1,
+    }
+}
+
+/// Writes the value of the Logical field at the given index of the current record
+/// in `value`, 1 for true and 0 for false.
+///
+/// Returns 0 on success, 1 if the value is empty and -1 on error.
+///
+/// # Safety
+///
+/// `reader` must be a valid reader and `value` a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn dbase_field_as_bool(
+    reader: *const DbaseReader,
+    index: usize,
+    value: *mut c_int,
+) -> c_int {
+    let reader = &*reader;
+    match reader.value(index) {
+        Some(FieldValue::Logical(Some(logical))) => {
+            *value = c_int::from(*logical);
+            0
+        }
+        Some(FieldValue::Logical(None)) => 1,
+        Some(_) => {
+            set_last_error(format!("the field {} is not a logical", index));
+            -1
+        }
+        None => -1,
+    }
+}
+
+/// Writes the value of the Date or DateTime field at the given index
+/// of the current record in `year`, `month` and `day`.
+///
+/// Returns 0 on success, 1 if the value is empty and -1 on error.
+///
+/// # Safety
+///
+/// `reader` must be a valid reader, `year`, `month` and `day` valid pointers.
+#[no_mangle]
+pub unsafe extern "C" fn dbase_field_as_date(
+    reader: *const DbaseReader,
+    index: usize,
+    year: *mut c_int,
+    month: *mut c_int,
+    day: *mut c_int,
+) -> c_int {
+    let reader = &*reader;
+    let date = match reader.value(index) {
+        Some(FieldValue::Date(date)) => *date,
+        Some(FieldValue::DateTime(date_time)) => Some(date_time.date()),
+        Some(_) => {
+            set_last_error(format!("the field {} is not a date", index));
+            return -1;
+        }
+        None => return -1,
+    };
+    match date {
+        Some(date) => {
+            *year = date.year() as c_int;
+            *month = date.month() as c_int;
+            *day = date.day() as c_int;
+            0
+        }
+        None => 1,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn open(path: &str) -> *mut DbaseReader {
+        let path = CString::new(path).unwrap();
+        unsafe { dbase_reader_open(path.as_ptr()) }
+    }
+
+    fn to_str<'a>(text: *const c_char) -> &'a str {
+        assert!(!text.is_null());
+        unsafe { CStr::from_ptr(text) }.to_str().unwrap()
+    }
+
+    #[test]
+    fn read_through_ffi() {
+        let reader = open("tests/data/stations.dbf");
+        assert!(!reader.is_null());
+        unsafe {
+            assert_eq!(dbase_reader_num_records(reader), 6);
+            assert_eq!(dbase_reader_num_fields(reader), 4);
+            assert_eq!(to_str(dbase_reader_field_name(reader, 0)), "name");
+            assert_eq!(dbase_reader_field_type(reader, 0), b'C' as c_char);
+            assert!(dbase_reader_field_length(reader, 0) > 0);
+            assert_eq!(dbase_reader_field_length(reader, 4), -1);
+            assert!(dbase_reader_field_name(reader, 4).is_null());
+
+            assert_eq!(dbase_record_is_deleted(reader), -1);
+            assert_eq!(dbase_reader_next_record(reader), 1);
+            assert_eq!(dbase_record_is_deleted(reader), 0);
+            assert_eq!(dbase_field_is_null(reader, 0), 0);
+            assert_eq!(to_str(dbase_field_as_string(reader, 0)), "Van Dorn Street");
+            let mut number = 0.0;
+            assert_eq!(dbase_field_as_double(reader, 0, &mut number), -1);
+            assert_eq!(to_str(dbase_last_error()), "the field 0 is not a number");
+
+            let mut num_records = 1;
+            while dbase_reader_next_record(reader) == 1 {
+                num_records += 1;
+            }
+            assert_eq!(num_records, 6);
+            assert!(dbase_field_as_string(reader, 0).is_null());
+            dbase_reader_free(reader);
+        }
+    }
+
+    #[test]
+    fn open_error() {
+        assert!(open("tests/data/does-not-exist.dbf").is_null());
+        assert!(!dbase_last_error().is_null());
+    }
+}
@@ -0,0 +1,183 @@
+//! Module with the definition of the filters of records,
+//! written as xBase expressions evaluating to a logical value
+use std::io::{Read, Seek};
+
+use crate::error::{Error, ErrorKind};
+use crate::expression::{Expression, ValueType};
+use crate::reading::{Reader, Record};
+use crate::record::FieldInfo;
+
+/// A condition on the values of the fields of records,
+/// like `STATE = 'CA' AND AMOUNT > 1000`
+///
+/// The condition is an xBase expression that evaluates to a logical value, parsed once
+/// with the fields of the table. Values are compared with `=`, `==`, `<>` (or `#`, `!=`),
+/// `<`, `<=`, `>`, `>=` and conditions combined with `AND`, `OR`, `NOT`
+/// (or `.AND.`, `.OR.`, `.NOT.`, `!`), the logical literals are `.T.` and `.F.`.
+/// Field values can also go through the functions of index expressions, like `UPPER`.
+///
+/// As in dBase, character fields are padded with spaces to the length of the field,
+/// and strings compared with `=` are equal when the left one starts with the right one,
+/// `==` compares them exactly.
+///
+/// # Example
+///
+/// ```
+/// # fn main() -> Result<(), dbase::Error> {
+/// let mut reader = dbase::Reader::from_path("tests/data/stations.dbf")?;
+/// let filter = dbase::Filter::new("line = 'red' .AND. name > 'K'", reader.fields())?;
+/// let records = reader
+///     .iter_records_where(&filter)
+///     .collect::<Result<Vec<_>, _>>()?;
+/// assert_eq!(records.len(), 1);
+/// assert_eq!(
+///     records[0].get("name"),
+///     Some(&dbase::FieldValue::Character(Some("Metro Center".to_owned())))
+/// );
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Filter {
+    expression: Expression,
+}
+
+impl Filter {
+    /// Parses the condition, the names of the fields are not case sensitive
+    pub fn new(text: &str, fields: &[FieldInfo]) -> Result<Self, Error> {
+        let expression = Expression::parse(text, fields).map_err(|kind| Error {
+            record_num: 0,
+            field: None,
+            kind,
+        })?;
+        if expression.value_type() != ValueType::Logical {
+            return Err(Error {
+                record_num: 0,
+                field: None,
+                kind: ErrorKind::InvalidExpression(format!(
+                    "'{}' is not a logical expression",
+                    text
+                )),
+            });
+        }
+        Ok(Self { expression })
+    }
+
+    /// Returns the text of the condition
+    pub fn text(&self) -> &str {
+        self.expression.text()
+    }
+
+    /// Returns the names of the fields the condition uses
+    pub fn field_names(&self) -> Vec<&str> {
+        self.expression.field_names()
+    }
+
+    /// Returns whether the record matches the condition
+    ///
+    /// Fails if the record does not have one of the fields of the condition.
+    pub fn matches(&self, record: &Record) -> Result<bool, ErrorKind> {
+        self.expression
+            .evaluate_value(record)
+            .map(|value| value.is_true())
+    }
+}
+
+/// Iterator over the records that match a filter,
+/// created by [Reader::iter_records_where](struct.Reader.html#method.iter_records_where)
+pub struct FilteredRecords<'a, T: Read + Seek> {
+    reader: &'a mut Reader<T>,
+    filter: &'a Filter,
+    field_names: Vec<&'a str>,
+}
+
+impl<T: Read + Seek> Iterator for FilteredRecords<'_, T> {
+    type Item = Result<Record, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let record_num = self.reader.next_record as usize;
+            let mut record_ref = match self.reader.next_record_ref()? {
+                Ok(record_ref) => record_ref,
+                Err(error) => return Some(Err(error)),
+            };
+            // Only the fields of the filter are parsed to evaluate it
+            let mut values = Record::default();
+            for name in &self.field_names {
+                match record_ref.get(name) {
+                    Some(Ok(value)) => {
+                        values.insert((*name).to_owned(), value);
+                    }
+                    Some(Err(error)) => return Some(Err(Error::new(error, record_num))),
+                    None => {}
+                }
+            }
+            match self.filter.matches(&values) {
+                Ok(true) => {
+                    return Some(
+                        record_ref
+                            .read_as::<Record>()
+                            .map_err(|error| Error::new(error, record_num)),
+                    )
+                }
+                Ok(false) => continue,
+                Err(kind) => {
+                    return Some(Err(Error {
+                        record_num,
+                        field: None,
+                        kind,
+                    }))
+                }
+            }
+        }
+    }
+}
+
+impl<T: Read + Seek> Reader<T> {
+    /// Returns an iterator over the remaining records that match the filter
+    ///
+    /// The fields of the filter are parsed first, the other fields of the record
+    /// are only parsed when the record matches.
+    /// Like [iter_records](#method.iter_records), deleted records are not skipped.
+    pub fn iter_records_where<'a>(&'a mut self, filter: &'a Filter) -> FilteredRecords<'a, T> {
+        FilteredRecords {
+            reader: self,
+            field_names: filter.field_names(),
+            filter,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn filter_records() {
+        let mut reader = Reader::from_path("tests/data/stations.dbf").unwrap();
+        let filter = Filter::new(
+            "line = 'blue' AND NOT marker-col = 'x'",
+            reader.fields(),
+        );
+        // Field names that are not identifiers cannot be used in conditions
+        assert!(filter.is_err());
+
+        let filter = Filter::new("line == 'blue'", reader.fields()).unwrap();
+        assert_eq!(filter.field_names(), vec!["line"]);
+        assert_eq!(reader.iter_records_where(&filter).count(), 4);
+
+        let expected = Reader::from_path("tests/data/stations.dbf")
+            .unwrap()
+            .read()
+            .unwrap();
+        let mut reader = Reader::from_path("tests/data/stations.dbf").unwrap();
+        let filter = Filter::new(".T.", reader.fields()).unwrap();
+        let records = reader
+            .iter_records_where(&filter)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(records, expected);
+
+        assert!(Filter::new("name", reader.fields()).is_err());
+    }
+}
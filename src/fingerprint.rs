@@ -0,0 +1,269 @@
+//! Module with the definition of fn's to compute a fingerprint
+//! of the content of dBase files
+//!
+//! The fingerprint only depends on the fields and the values of the records
+//! that are not deleted, so two files with the same data have the same fingerprint
+//! regardless of the date of their last update, the padding of their header
+//! or the layout of their memo file.
+use std::io::{Read, Seek};
+use std::path::Path;
+
+use crate::error::Error;
+use crate::reading::{Reader, RecordValues};
+use crate::record::field::FieldValue;
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// 64 bits FNV-1a hash, unlike the hashers of the std
+/// its result does not change between versions or platforms
+struct Fnv64(u64);
+
+impl Fnv64 {
+    fn new() -> Self {
+        Self(FNV_OFFSET_BASIS)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(FNV_PRIME);
+        }
+    }
+
+    /// Writes the bytes preceded by their length, so that
+    /// consecutive strings cannot be confused
+    fn write_sized(&mut self, bytes: &[u8]) {
+        self.write(&(bytes.len() as u64).to_le_bytes());
+        self.write(bytes);
+    }
+
+    fn write_date(&mut self, year: u32, month: u32, day: u32) {
+        self.write(&year.to_le_bytes());
+        self.write(&[month as u8, day as u8]);
+    }
+
+    /// Writes a tag identifying the type of the value then the value,
+    /// empty values are only written as their tag
+    fn write_value(&mut self, value: &FieldValue) {
+        match value {
+            FieldValue::Character(s) => {
+                self.write(&[b'C', u8::from(s.is_some())]);
+                if let Some(s) = s {
+                    self.write_sized(s.as_bytes());
+                }
+            }
+            FieldValue::Memo(s) => {
+                self.write(b"M");
+                self.write_sized(s.as_bytes());
+            }
+            FieldValue::Numeric(n) => {
+                self.write(&[b'N', u8::from(n.is_some())]);
+                if let Some(n) = n {
+                    self.write(&n.to_le_bytes());
+                }
+            }
+            FieldValue::Float(f) => {
+                self.write(&[b'F', u8::from(f.is_some())]);
+                if let Some(f) = f {
+                    self.write(&f.to_le_bytes());
+                }
+            }
+            FieldValue::Logical(b) => {
+                let byte = match b {
+                    Some(true) => b'T',
+                    Some(false) => b'F',
+                    None => b'?',
+                };
+                self.write(&[b'L', byte]);
+            }
+            FieldValue::Date(d) => {
+                self.write(&[b'D', u8::from(d.is_some())]);
+                if let Some(d) = d {
+                    self.write_date(d.year(), d.month(), d.day());
+                }
+            }
+            // Exact integers are tagged apart from the numbers they would be read as,
+            // but empty values are the same
+            FieldValue::NumericInteger(n) => match n {
+                Some(n) => {
+                    self.write(&[b'N', 2]);
+                    self.write(&n.to_le_bytes());
+                }
+                None => self.write(&[b'N', 0]),
+            },
+            FieldValue::Integer(i) => {
+                self.write(b"I");
+                self.write(&i.to_le_bytes());
+            }
+            FieldValue::Currency(c) => {
+                self.write(b"Y");
+                self.write(&c.to_le_bytes());
+            }
+            FieldValue::Double(d) => {
+                self.write(b"B");
+                self.write(&d.to_le_bytes());
+            }
+            FieldValue::DateTime(dt) => {
+                let (date, time) = (dt.date(), dt.time());
+                self.write(b"T");
+                self.write_date(date.year(), date.month(), date.day());
+                self.write(&[
+                    time.hours() as u8,
+                    time.minutes() as u8,
+                    time.seconds() as u8,
+                ]);
+            }
+            // The `I` of dBase 7 tables is tagged apart from the one of Visual FoxPro
+            FieldValue::Long(i) => {
+                self.write(&[b'i', u8::from(i.is_some())]);
+                if let Some(i) = i {
+                    self.write(&i.to_le_bytes());
+                }
+            }
+            FieldValue::Autoincrement(i) => {
+                self.write(b"+");
+                self.write(&i.to_le_bytes());
+            }
+            FieldValue::DBase7Double(d) => {
+                self.write(&[b'O', u8::from(d.is_some())]);
+                if let Some(d) = d {
+                    self.write(&d.to_le_bytes());
+                }
+            }
+            FieldValue::Timestamp(dt) => {
+                self.write(&[b'@', u8::from(dt.is_some())]);
+                if let Some(dt) = dt {
+                    self.write(&dt.milliseconds_since_unix_epoch().to_le_bytes());
+                }
+            }
+        }
+    }
+}
+
+impl<T: Read + Seek> Reader<T> {
+    /// Reads the remaining records and returns a fingerprint of the fields and of
+    /// the records that are not deleted
+    ///
+    /// The fields are compared by their name (without case), type, length and number of
+    /// decimal places, the records by their values, in order.
+    /// The header bytes that change without the data changing
+    /// (eg: the date of the last update) are ignored.
+    ///
+    /// The fingerprint is the same across versions of the crate and platforms,
+    /// it is not a cryptographic hash.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), dbase::Error> {
+    /// let mut reader = dbase::Reader::from_path("tests/data/line.dbf")?;
+    /// let fingerprint = reader.fingerprint()?;
+    /// assert_eq!(fingerprint, dbase::fingerprint("tests/data/line.dbf")?);
+    /// assert_ne!(fingerprint, dbase::fingerprint("tests/data/stations.dbf")?);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn fingerprint(&mut self) -> Result<u64, Error> {
+        let mut hasher = Fnv64::new();
+        let fields_info = self
+            .fields_info
+            .iter()
+            .filter(|field_info| !field_info.is_deletion_flag());
+        for field_info in fields_info {
+            hasher.write_sized(field_info.name().to_ascii_uppercase().as_bytes());
+            let [length_low, length_high] = field_info.field_length.to_le_bytes();
+            hasher.write(&[
+                u8::from(field_info.field_type),
+                length_low,
+                field_info.num_decimal_places,
+            ]);
+            // Keeps the fingerprints of the tables without long fields unchanged
+            if length_high != 0 {
+                hasher.write(&[length_high]);
+            }
+        }
+
+        let mut num_records = 0u64;
+        while let Some(record) = self.read_next_record_as::<RecordValues>() {
+            let record = record?;
+            if record.is_deleted {
+                continue;
+            }
+            for value in &record.values {
+                hasher.write_value(value);
+            }
+            num_records += 1;
+        }
+        hasher.write(&num_records.to_le_bytes());
+        Ok(hasher.0)
+    }
+}
+
+/// One liner to compute the fingerprint of the content of the file at the given path,
+/// see [Reader::fingerprint](struct.Reader.html#method.fingerprint)
+pub fn fingerprint<P: AsRef<Path>>(path: P) -> Result<u64, Error> {
+    Reader::from_path(path)?.fingerprint()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::convert::TryFrom;
+    use std::io::Cursor;
+
+    use crate::{FieldName, Record, TableWriterBuilder};
+
+    fn table(names: &[&str]) -> Vec<u8> {
+        let records = names
+            .iter()
+            .map(|name| {
+                let mut record = Record::default();
+                record.insert(
+                    "NAME".to_owned(),
+                    FieldValue::Character(Some(name.to_string())),
+                );
+                record
+            })
+            .collect::<Vec<Record>>();
+        TableWriterBuilder::new()
+            .add_character_field(FieldName::try_from("NAME").unwrap(), 10)
+            .build_with_dest(Cursor::new(Vec::<u8>::new()))
+            .write(&records)
+            .unwrap()
+            .into_inner()
+    }
+
+    fn fingerprint_of(content: Vec<u8>) -> u64 {
+        Reader::new(Cursor::new(content))
+            .unwrap()
+            .fingerprint()
+            .unwrap()
+    }
+
+    #[test]
+    fn same_data_same_fingerprint() {
+        let content = table(&["Ferris", "Corro"]);
+        let fingerprint = fingerprint_of(content.clone());
+
+        // Another date of last update
+        let mut updated = content.clone();
+        updated[1] = 90;
+        assert_eq!(fingerprint_of(updated), fingerprint);
+
+        // Padding after the fields descriptors
+        let mut padded = content.clone();
+        padded.insert(65, 0);
+        padded[8] += 1;
+        assert_eq!(fingerprint_of(padded), fingerprint);
+
+        // A deleted record
+        let mut with_deleted = table(&["Ferris", "Pinchy", "Corro"]);
+        with_deleted[65 + 11] = b'*';
+        assert_eq!(fingerprint_of(with_deleted), fingerprint);
+
+        assert_ne!(fingerprint_of(table(&["Corro", "Ferris"])), fingerprint);
+        assert_ne!(fingerprint_of(table(&["Ferris"])), fingerprint);
+    }
+}
@@ -0,0 +1,216 @@
+//! Module with the definition of the reader of the records appended to a table
+//!
+//! Legacy applications append records to their tables while they run: they write the
+//! new records at the end of the file and update the number of records of the header.
+//! The follower reads the header again on each poll, and only reads the records
+//! that were appended since the previous poll.
+use std::convert::TryFrom;
+use std::io::{Read, Seek, SeekFrom};
+
+use crate::error::{Error, ErrorKind};
+use crate::header::Header;
+use crate::reading::{ReadableRecord, Reader, Record};
+use crate::stream::Capability;
+
+/// Reader of the records appended to a table, made by
+/// [Reader::follow](struct.Reader.html#method.follow)
+///
+/// Each [poll](#method.poll) returns the records appended since the previous one.
+pub struct Follower<T: Read + Seek> {
+    reader: Reader<T>,
+}
+
+impl<T: Read + Seek> Reader<T> {
+    /// Returns a follower of the table, whose polls return the records after the ones
+    /// already read, including the ones appended to the table in the meantime
+    ///
+    /// The follower starts at the position of the reader, use
+    /// [skip_records](#method.skip_records) beforehand to only get the records
+    /// appended from now on. The range set with [set_record_range](#method.set_record_range)
+    /// is ignored.
+    ///
+    /// The header is read again on each poll, which needs the
+    /// [RandomAccess](enum.Capability.html#variant.RandomAccess) capability.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), dbase::Error> {
+    /// let mut reader = dbase::Reader::from_path("tests/data/stations.dbf")?;
+    /// reader.skip_records(4)?;
+    /// let mut follower = reader.follow()?;
+    /// // The records after the ones skipped
+    /// assert_eq!(follower.poll()?.len(), 2);
+    /// // Nothing was appended since
+    /// assert_eq!(follower.poll()?.len(), 0);
+    /// assert_eq!(follower.next_record_index(), 6);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn follow(mut self) -> Result<Follower<T>, Error> {
+        self.require(Capability::RandomAccess, self.next_record as usize)?;
+        self.end_record = None;
+        Ok(Follower { reader: self })
+    }
+}
+
+impl<T: Read + Seek> Follower<T> {
+    /// Reads the header again and returns the records appended since the previous poll,
+    /// the records marked as deleted included
+    ///
+    /// Only the records that are complete in the file are returned, the ones the header
+    /// counts but that are not fully written yet are returned by a later poll.
+    ///
+    /// Fails if the table now has fewer records than were read (the table was packed
+    /// or emptied), or if its records are no longer of the same size (its structure
+    /// was changed).
+    pub fn poll(&mut self) -> Result<Vec<Record>, Error> {
+        self.poll_as::<Record>()
+    }
+
+    /// Same as [poll](#method.poll), reading the records as `R`
+    pub fn poll_as<R: ReadableRecord>(&mut self) -> Result<Vec<R>, Error> {
+        let header = self.read_header()?;
+        let num_records = header.num_records;
+        let next_record = self.reader.next_record;
+        if num_records < next_record {
+            return self.error(format!(
+                "the table has {} records, but {} were read, it was packed or emptied",
+                num_records, next_record
+            ));
+        }
+        self.reader.header.num_records = num_records;
+        self.reader.header.last_update = header.last_update;
+        self.reader
+            .check_memory_budget((num_records - next_record) as usize)?;
+        self.reader.seek_to_record(next_record)?;
+
+        let mut records = Vec::with_capacity((num_records - next_record) as usize);
+        while let Some(record) = self.reader.read_next_record_as::<R>() {
+            records.push(record?);
+        }
+        Ok(records)
+    }
+
+    /// Reads the header of the table, its number of records being the number
+    /// of records that are complete in the file, as the application appending records
+    /// may not have written them all yet
+    fn read_header(&mut self) -> Result<Header, Error> {
+        let record_num = self.reader.next_record as usize;
+        let source = &mut self.reader.source;
+        let io_error = |error| Error::io_error(error, record_num);
+        source.seek(SeekFrom::Start(0)).map_err(io_error)?;
+        let mut header = Header::read_from(source).map_err(io_error)?;
+        let file_len = source.seek(SeekFrom::End(0)).map_err(io_error)?;
+
+        let current = &self.reader.header;
+        if header.size_of_record != current.size_of_record
+            || header.offset_to_first_record != current.offset_to_first_record
+        {
+            return self.error("the structure of the table changed".to_owned());
+        }
+        let record_stride = self.reader.record_stride().max(1);
+        let num_written =
+            file_len.saturating_sub(u64::from(header.offset_to_first_record)) / record_stride;
+        header.num_records = header
+            .num_records
+            .min(u32::try_from(num_written).unwrap_or(u32::MAX));
+        Ok(header)
+    }
+
+    fn error<V>(&self, message: String) -> Result<V, Error> {
+        Err(Error {
+            record_num: self.reader.next_record as usize,
+            field: None,
+            kind: ErrorKind::Message(message),
+        })
+    }
+
+    /// Returns the index of the next record a poll returns
+    pub fn next_record_index(&self) -> usize {
+        self.reader.next_record as usize
+    }
+
+    /// Returns the reader of the table, the number of records and the date of last update
+    /// of its header are the ones read by the last poll
+    pub fn reader(&self) -> &Reader<T> {
+        &self.reader
+    }
+
+    /// Returns the reader of the table, positioned after the records returned
+    pub fn into_inner(self) -> Reader<T> {
+        self.reader
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::io::Cursor;
+    use std::path::{Path, PathBuf};
+
+    use crate::{FieldName, TableWriterBuilder};
+
+    fn table(num_records: usize) -> Vec<u8> {
+        let records = (0..num_records)
+            .map(|i| Record::builder().set("NUM", i as f64).build())
+            .collect::<Vec<_>>();
+        TableWriterBuilder::new()
+            .add_numeric_field(FieldName::try_from("NUM").unwrap(), 5, 0)
+            .build_with_dest(Cursor::new(Vec::<u8>::new()))
+            .write(&records)
+            .unwrap()
+            .into_inner()
+    }
+
+    fn numbers(records: Vec<Record>) -> Vec<f64> {
+        records
+            .into_iter()
+            .map(|record| match record.get("NUM") {
+                Some(crate::FieldValue::Numeric(Some(number))) => *number,
+                value => panic!("unexpected value {:?}", value),
+            })
+            .collect()
+    }
+
+    fn path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(name)
+    }
+
+    fn write_table(path: &Path, content: &[u8]) {
+        std::fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn appended_records_are_polled() {
+        let path = path("dbase_follow_appended.dbf");
+        write_table(&path, &table(2));
+        let mut follower = Reader::from_path(&path).unwrap().follow().unwrap();
+        assert_eq!(numbers(follower.poll().unwrap()), vec![0.0, 1.0]);
+        assert!(follower.poll().unwrap().is_empty());
+
+        write_table(&path, &table(5));
+        assert_eq!(numbers(follower.poll().unwrap()), vec![2.0, 3.0, 4.0]);
+
+        // The header counts a record whose bytes are not written yet
+        let mut content = table(7);
+        content.truncate(content.len() - 4);
+        write_table(&path, &content);
+        assert_eq!(numbers(follower.poll().unwrap()), vec![5.0]);
+        assert_eq!(follower.next_record_index(), 6);
+
+        write_table(&path, &table(3));
+        assert!(follower.poll().is_err());
+    }
+
+    #[test]
+    fn streams_cannot_be_followed() {
+        let table = table(1);
+        let reader = Reader::from_stream(Cursor::new(table)).unwrap();
+        assert!(matches!(
+            reader.follow().err().unwrap().kind(),
+            ErrorKind::Unsupported(Capability::RandomAccess)
+        ));
+    }
+}
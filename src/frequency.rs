@@ -0,0 +1,300 @@
+//! Module with the definition of fn's and struct's to count how often
+//! the values of each field of dBase files occur
+//!
+//! The records are read once. Each field counts at most a fixed number of distinct values:
+//! as long as a field has fewer distinct values, the counts are exact, past that
+//! the [Space-Saving](https://www.cs.ucsb.edu/sites/default/files/documents/2005-23.pdf)
+//! algorithm keeps the most frequent ones, with a bound on how much each count is overestimated.
+use std::collections::{BTreeMap, HashMap};
+use std::io::{Read, Seek};
+use std::path::Path;
+
+use crate::error::Error;
+use crate::reading::{Reader, RecordValues};
+use crate::record::field::{FieldType, FieldValue};
+
+/// Number of distinct values counted per field by default
+pub const DEFAULT_FREQUENCY_CAPACITY: usize = 10_000;
+
+/// How often a value occurs in a field
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValueCount {
+    /// The value
+    pub value: FieldValue,
+    /// The number of records with the value, possibly overestimated by `max_error`
+    pub count: usize,
+    /// How much `count` may be overestimated, always 0 when the counts are exact
+    pub max_error: usize,
+}
+
+/// The most frequent values of a field
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValueFrequencies {
+    /// The name of the field
+    pub name: String,
+    /// The type of the field
+    pub field_type: FieldType,
+    /// The number of empty values
+    /// (blank strings, numbers, dates and logicals, empty memos), which are not counted
+    pub num_nulls: usize,
+    /// Whether the field has fewer distinct values than the capacity,
+    /// in which case the counts are exact
+    pub is_exact: bool,
+    /// The most frequent values, the most frequent first
+    pub top: Vec<ValueCount>,
+}
+
+/// Frequencies of the values of the fields of a table, made by
+/// [Reader::value_frequencies](struct.Reader.html#method.value_frequencies)
+#[derive(Debug, Clone, PartialEq)]
+pub struct FrequencyReport {
+    /// The number of records counted, the records marked as deleted are not counted
+    pub num_records: usize,
+    /// The frequencies of the fields, in the order of the fields
+    pub fields: Vec<ValueFrequencies>,
+}
+
+impl FrequencyReport {
+    /// Returns the frequencies of the field with the given name
+    pub fn field(&self, name: &str) -> Option<&ValueFrequencies> {
+        self.fields
+            .iter()
+            .find(|frequencies| frequencies.name == name)
+    }
+}
+
+fn is_null(value: &FieldValue) -> bool {
+    match value {
+        FieldValue::Character(None)
+        | FieldValue::Numeric(None)
+        | FieldValue::Float(None)
+        | FieldValue::Logical(None)
+        | FieldValue::Date(None)
+        | FieldValue::NumericInteger(None) => true,
+        FieldValue::Memo(text) => text.is_empty(),
+        _ => false,
+    }
+}
+
+struct Counter {
+    count: usize,
+    max_error: usize,
+    /// Order in which the counters were created, to break ties
+    id: u64,
+}
+
+/// Counts the values of a field, with at most `capacity` counters
+struct FrequencyCounter {
+    capacity: usize,
+    counters: HashMap<FieldValue, Counter>,
+    /// The counted values, ordered by count then id, to find the least frequent
+    by_count: BTreeMap<(usize, u64), FieldValue>,
+    next_id: u64,
+    num_nulls: usize,
+    is_exact: bool,
+}
+
+impl FrequencyCounter {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            counters: HashMap::new(),
+            by_count: BTreeMap::new(),
+            next_id: 0,
+            num_nulls: 0,
+            is_exact: true,
+        }
+    }
+
+    fn add(&mut self, value: FieldValue) {
+        if is_null(&value) {
+            self.num_nulls += 1;
+            return;
+        }
+        if let Some(counter) = self.counters.get_mut(&value) {
+            let value = self.by_count.remove(&(counter.count, counter.id)).unwrap();
+            counter.count += 1;
+            self.by_count.insert((counter.count, counter.id), value);
+            return;
+        }
+        let (count, max_error) = if self.counters.len() < self.capacity {
+            (1, 0)
+        } else {
+            // The least frequent value is replaced, the new one may have
+            // occurred as many times without being counted
+            self.is_exact = false;
+            let ((min_count, _), evicted) = self.by_count.pop_first().unwrap();
+            self.counters.remove(&evicted);
+            (min_count + 1, min_count)
+        };
+        let id = self.next_id;
+        self.next_id += 1;
+        self.by_count.insert((count, id), value.clone());
+        self.counters.insert(
+            value,
+            Counter {
+                count,
+                max_error,
+                id,
+            },
+        );
+    }
+
+    fn top(self, top_k: usize) -> Vec<ValueCount> {
+        let mut counts = self
+            .counters
+            .into_iter()
+            .map(|(value, counter)| (counter.id, value, counter.count, counter.max_error))
+            .collect::<Vec<_>>();
+        counts.sort_by(|a, b| b.2.cmp(&a.2).then(a.0.cmp(&b.0)));
+        counts
+            .into_iter()
+            .take(top_k)
+            .map(|(_, value, count, max_error)| ValueCount {
+                value,
+                count,
+                max_error,
+            })
+            .collect()
+    }
+}
+
+impl<T: Read + Seek> Reader<T> {
+    /// Reads the remaining records and counts how often the values of each field occur,
+    /// returning the `top_k` most frequent values of each field
+    ///
+    /// At most `capacity` distinct values are counted per field: the counts of the fields
+    /// with fewer distinct values are exact, for the other fields the most frequent values
+    /// are kept, each with a bound on how much its count is overestimated.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), dbase::Error> {
+    /// let mut reader = dbase::Reader::from_path("tests/data/stations.dbf")?;
+    /// let report = reader.value_frequencies(3, dbase::DEFAULT_FREQUENCY_CAPACITY)?;
+    /// let marker_color = report.field("marker-col").unwrap();
+    /// assert!(marker_color.is_exact);
+    /// for value_count in &marker_color.top {
+    ///     println!("{}: {}", value_count.value, value_count.count);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn value_frequencies(
+        &mut self,
+        top_k: usize,
+        capacity: usize,
+    ) -> Result<FrequencyReport, Error> {
+        let mut counters = self
+            .fields_info
+            .iter()
+            .filter(|field_info| !field_info.is_deletion_flag())
+            .map(|field_info| {
+                (
+                    field_info.name().to_owned(),
+                    field_info.field_type,
+                    FrequencyCounter::new(capacity),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let mut num_records = 0;
+        while let Some(record) = self.read_next_record_as::<RecordValues>() {
+            let record = record?;
+            if record.is_deleted {
+                continue;
+            }
+            for ((_, _, counter), value) in counters.iter_mut().zip(record.values) {
+                counter.add(value);
+            }
+            num_records += 1;
+        }
+
+        let fields = counters
+            .into_iter()
+            .map(|(name, field_type, counter)| ValueFrequencies {
+                name,
+                field_type,
+                num_nulls: counter.num_nulls,
+                is_exact: counter.is_exact,
+                top: counter.top(top_k),
+            })
+            .collect();
+        Ok(FrequencyReport {
+            num_records,
+            fields,
+        })
+    }
+}
+
+/// One liner to count how often the values of each field of the file at the given path occur,
+/// see [Reader::value_frequencies](struct.Reader.html#method.value_frequencies)
+pub fn value_frequencies<P: AsRef<Path>>(
+    path: P,
+    top_k: usize,
+    capacity: usize,
+) -> Result<FrequencyReport, Error> {
+    Reader::from_path(path)?.value_frequencies(top_k, capacity)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn code(code: &str) -> FieldValue {
+        FieldValue::Character(Some(code.to_owned()))
+    }
+
+    #[test]
+    fn exact_counts() {
+        let mut counter = FrequencyCounter::new(10);
+        for value in ["B", "A", "B", "C", "B", "A"].iter() {
+            counter.add(code(value));
+        }
+        counter.add(FieldValue::Character(None));
+        assert!(counter.is_exact);
+        assert_eq!(counter.num_nulls, 1);
+        let top = counter
+            .top(2)
+            .into_iter()
+            .map(|value_count| (value_count.value, value_count.count, value_count.max_error))
+            .collect::<Vec<_>>();
+        assert_eq!(top, vec![(code("B"), 3, 0), (code("A"), 2, 0)]);
+    }
+
+    #[test]
+    fn frequent_values_survive_the_cap() {
+        let mut counter = FrequencyCounter::new(10);
+        for i in 0..1_000 {
+            counter.add(code("frequent"));
+            if i % 2 == 0 {
+                counter.add(code("common"));
+            }
+            counter.add(code(&format!("rare {}", i)));
+        }
+        assert!(!counter.is_exact);
+        let top = counter.top(2);
+        assert_eq!(top[0].value, code("frequent"));
+        assert_eq!(top[1].value, code("common"));
+        for value_count in &top {
+            let exact = if value_count.value == code("frequent") {
+                1_000
+            } else {
+                500
+            };
+            assert!(value_count.count >= exact);
+            assert!(value_count.count - value_count.max_error <= exact);
+        }
+    }
+
+    #[test]
+    fn frequencies_of_a_table() {
+        let mut reader = Reader::from_path("tests/data/stations.dbf").unwrap();
+        let report = reader.value_frequencies(10, 2).unwrap();
+        assert_eq!(report.num_records, 6);
+        let name = report.field("name").unwrap();
+        assert!(!name.is_exact);
+        assert_eq!(name.top.len(), 2);
+    }
+}
@@ -0,0 +1,321 @@
+//! Module with the implementation of the [geozero](https://docs.rs/geozero) traits
+//!
+//! Records are processed as the properties of features, so that tables can be
+//! the attribute source of geospatial conversions (eg: the .dbf of a shapefile):
+//!
+//! - Character and Memo values are strings
+//! - Numeric, Double and Currency values are doubles, Float values are floats
+//! - Integer values are ints, NumericInteger values are longs and Logical values are booleans
+//! - Date values are strings formatted as `YYYY-MM-DD`
+//! - DateTime values are date times formatted as `YYYY-MM-DDTHH:MM:SS`
+//! - empty values are not processed, the index of the properties stays the one of the field
+use std::io::{Read, Seek};
+
+use geozero::error::{GeozeroError, Result as GeozeroResult};
+use geozero::PropertyProcessor;
+use geozero::{ColumnValue, FeatureProcessor, FeatureProperties, GeozeroDatasource};
+
+use crate::error::FieldIOError;
+use crate::reading::{FieldIterator, ReadableRecord, Reader, RecordValues};
+use crate::record::field::FieldValue;
+
+/// Gives the value to the processor, returns whether the processing is aborted
+fn process_value<P: PropertyProcessor>(
+    processor: &mut P,
+    index: usize,
+    name: &str,
+    value: &FieldValue,
+) -> GeozeroResult<bool> {
+    match value {
+        FieldValue::Character(Some(text)) | FieldValue::Memo(text) => {
+            processor.property(index, name, &ColumnValue::String(text))
+        }
+        FieldValue::Numeric(Some(value))
+        | FieldValue::Double(value)
+        | FieldValue::Currency(value)
+        | FieldValue::DBase7Double(Some(value)) => {
+            processor.property(index, name, &ColumnValue::Double(*value))
+        }
+        FieldValue::Float(Some(value)) => {
+            processor.property(index, name, &ColumnValue::Float(*value))
+        }
+        FieldValue::Integer(value)
+        | FieldValue::Long(Some(value))
+        | FieldValue::Autoincrement(value) => {
+            processor.property(index, name, &ColumnValue::Int(*value))
+        }
+        FieldValue::NumericInteger(Some(value)) => {
+            processor.property(index, name, &ColumnValue::Long(*value))
+        }
+        FieldValue::Logical(Some(value)) => {
+            processor.property(index, name, &ColumnValue::Bool(*value))
+        }
+        FieldValue::Date(Some(date)) => {
+            let text = format!("{:04}-{:02}-{:02}", date.year(), date.month(), date.day());
+            processor.property(index, name, &ColumnValue::String(&text))
+        }
+        FieldValue::DateTime(date_time) | FieldValue::Timestamp(Some(date_time)) => {
+            let (date, time) = (date_time.date(), date_time.time());
+            let text = format!(
+                "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+                date.year(),
+                date.month(),
+                date.day(),
+                time.hours(),
+                time.minutes(),
+                time.seconds()
+            );
+            processor.property(index, name, &ColumnValue::DateTime(&text))
+        }
+        FieldValue::Character(None)
+        | FieldValue::Numeric(None)
+        | FieldValue::Float(None)
+        | FieldValue::Logical(None)
+        | FieldValue::Date(None)
+        | FieldValue::NumericInteger(None)
+        | FieldValue::Long(None)
+        | FieldValue::DBase7Double(None)
+        | FieldValue::Timestamp(None) => Ok(false),
+    }
+}
+
+/// Gives the values to the processor in the order of the fields,
+/// returns whether the processing was aborted
+fn process_values<'a, P: PropertyProcessor>(
+    processor: &mut P,
+    fields: impl Iterator<Item = (&'a str, &'a FieldValue)>,
+) -> GeozeroResult<bool> {
+    for (index, (name, value)) in fields.enumerate() {
+        if process_value(processor, index, name, value)? {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// A record whose values are processed as the properties of a geozero feature
+///
+/// Unlike [Record](struct.Record.html), the values are kept in the order of the fields,
+/// which is the order of the properties.
+///
+/// # Example
+///
+/// ```
+/// use geozero::FeatureProperties;
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let mut reader = dbase::Reader::from_path("tests/data/stations.dbf")?;
+/// let records = reader.read_as::<dbase::GeozeroRecord>()?;
+/// let name: String = records[0].property("name")?;
+/// assert_eq!(name, "Van Dorn Street");
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct GeozeroRecord {
+    is_deleted: bool,
+    fields: Vec<(String, FieldValue)>,
+}
+
+impl GeozeroRecord {
+    /// Returns whether the record is marked as deleted
+    pub fn is_deleted(&self) -> bool {
+        self.is_deleted
+    }
+
+    /// Returns the names and values of the fields, in order
+    pub fn fields(&self) -> &[(String, FieldValue)] {
+        &self.fields
+    }
+}
+
+impl ReadableRecord for GeozeroRecord {
+    fn read_using<T>(field_iterator: &mut FieldIterator<T>) -> Result<Self, FieldIOError>
+    where
+        T: Read + Seek,
+    {
+        let is_deleted = field_iterator.read_deletion_flag()?;
+        let mut fields = vec![];
+        for result in field_iterator {
+            let named_value = result?;
+            fields.push((named_value.name.to_owned(), named_value.value));
+        }
+        Ok(Self { is_deleted, fields })
+    }
+}
+
+impl FeatureProperties for GeozeroRecord {
+    fn process_properties<P: PropertyProcessor>(&self, processor: &mut P) -> GeozeroResult<bool> {
+        process_values(
+            processor,
+            self.fields
+                .iter()
+                .map(|(name, value)| (name.as_str(), value)),
+        )
+    }
+}
+
+/// The remaining records are processed as features without geometry,
+/// the records marked as deleted are skipped
+///
+/// The index of the features is the one of the records in the table.
+///
+/// # Example
+///
+/// ```
+/// use geozero::{ColumnValue, FeatureProcessor, GeomProcessor, GeozeroDatasource, PropertyProcessor};
+///
+/// #[derive(Default)]
+/// struct Names(Vec<String>);
+///
+/// impl GeomProcessor for Names {}
+/// impl FeatureProcessor for Names {}
+/// impl PropertyProcessor for Names {
+///     fn property(&mut self, _: usize, name: &str, value: &ColumnValue) -> geozero::error::Result<bool> {
+///         if name == "name" {
+///             self.0.push(value.to_string());
+///         }
+///         Ok(false)
+///     }
+/// }
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let mut reader = dbase::Reader::from_path("tests/data/stations.dbf")?;
+/// let mut names = Names::default();
+/// reader.process(&mut names)?;
+/// assert_eq!(names.0.len(), 6);
+/// # Ok(())
+/// # }
+/// ```
+impl<T: Read + Seek> GeozeroDatasource for Reader<T> {
+    fn process<P: FeatureProcessor>(&mut self, processor: &mut P) -> GeozeroResult<()> {
+        let field_names = self
+            .fields_info
+            .iter()
+            .filter(|field_info| !field_info.is_deletion_flag())
+            .map(|field_info| field_info.name().to_owned())
+            .collect::<Vec<_>>();
+        processor.dataset_begin(None)?;
+        loop {
+            let index = u64::from(self.next_record);
+            let record = match self.read_next_record_as::<RecordValues>() {
+                Some(record) => record.map_err(|error| GeozeroError::Feature(error.to_string()))?,
+                None => break,
+            };
+            if record.is_deleted {
+                continue;
+            }
+            processor.feature_begin(index)?;
+            processor.properties_begin()?;
+            let is_aborted = process_values(
+                processor,
+                field_names.iter().map(String::as_str).zip(&record.values),
+            )?;
+            processor.properties_end()?;
+            processor.feature_end(index)?;
+            if is_aborted {
+                break;
+            }
+        }
+        processor.dataset_end()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::convert::TryFrom;
+    use std::io::Cursor;
+
+    use geozero::GeomProcessor;
+
+    use crate::record::field::{Date, DateTime, Time};
+    use crate::{FieldName, Record, TableWriterBuilder};
+
+    #[derive(Default)]
+    struct Properties(Vec<String>);
+
+    impl GeomProcessor for Properties {}
+
+    impl FeatureProcessor for Properties {
+        fn feature_begin(&mut self, idx: u64) -> GeozeroResult<()> {
+            self.0.push(format!("feature {}", idx));
+            Ok(())
+        }
+    }
+
+    impl PropertyProcessor for Properties {
+        fn property(&mut self, idx: usize, name: &str, value: &ColumnValue) -> GeozeroResult<bool> {
+            self.0.push(format!("{} {} {:?}", idx, name, value));
+            Ok(false)
+        }
+    }
+
+    #[test]
+    fn records_are_processed_as_features() {
+        let mut first = Record::default();
+        first.insert(
+            "name".to_owned(),
+            FieldValue::Character(Some("Ferris".to_owned())),
+        );
+        first.insert("count".to_owned(), FieldValue::Numeric(Some(2.0)));
+        first.insert(
+            "day".to_owned(),
+            FieldValue::Date(Some(Date::new(4, 5, 2021))),
+        );
+        first.insert(
+            "at".to_owned(),
+            FieldValue::DateTime(DateTime::new(Date::new(1, 2, 2020), Time::new(13, 4, 5))),
+        );
+        let mut second = Record::default();
+        second.insert("name".to_owned(), FieldValue::Character(None));
+        second.insert("count".to_owned(), FieldValue::Numeric(None));
+        second.insert("day".to_owned(), FieldValue::Date(None));
+        second.insert(
+            "at".to_owned(),
+            FieldValue::DateTime(DateTime::new(Date::new(1, 2, 2020), Time::new(0, 0, 0))),
+        );
+
+        let mut cursor = TableWriterBuilder::new()
+            .add_character_field(FieldName::try_from("name").unwrap(), 10)
+            .add_numeric_field(FieldName::try_from("count").unwrap(), 5, 1)
+            .add_date_field(FieldName::try_from("day").unwrap())
+            .add_datetime_field(FieldName::try_from("at").unwrap())
+            .build_with_dest(Cursor::new(Vec::<u8>::new()))
+            .write(&[first, second])
+            .unwrap();
+        cursor.set_position(0);
+
+        let mut properties = Properties::default();
+        Reader::new(cursor)
+            .unwrap()
+            .process(&mut properties)
+            .unwrap();
+        assert_eq!(
+            properties.0,
+            vec![
+                "feature 0",
+                "0 name String(\"Ferris\")",
+                "1 count Double(2.0)",
+                "2 day String(\"2021-05-04\")",
+                "3 at DateTime(\"2020-02-01T13:04:05\")",
+                "feature 1",
+                "3 at DateTime(\"2020-02-01T00:00:00\")",
+            ]
+        );
+    }
+
+    #[test]
+    fn record_properties() {
+        let mut reader = Reader::from_path("tests/data/line.dbf").unwrap();
+        let records = reader.read_as::<GeozeroRecord>().unwrap();
+        let mut properties = Properties::default();
+        records[0].process_properties(&mut properties).unwrap();
+        assert_eq!(properties.0, vec!["0 name String(\"linestring1\")"]);
+        assert_eq!(
+            records[0].property::<String>("name").unwrap(),
+            "linestring1"
+        );
+    }
+}
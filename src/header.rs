@@ -1,18 +1,32 @@
-use chrono;
-
-use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+#[cfg(feature = "std")]
+use byteorder::{LittleEndian, WriteBytesExt};
 
+use alloc::string::String;
+#[cfg(feature = "std")]
 use std::io::{Read, Write};
 
-use crate::record::field::{Date, MemoFileType};
+#[cfg(feature = "std")]
+use crate::raw::IoSource;
+use crate::raw::{read_array, ByteSource};
+use crate::record::field::{Date, FieldType, MemoFileType};
 
 /// Known version of dBase files
+///
+/// The version is read from the first byte of the header,
+/// it tells which field types and memo files the table can have.
+///
+/// dBase II shares its version byte with FoxBase (its tables are told apart
+/// by the layout of their header), and dBase V tables with memo use
+/// the version byte of dBase IV.
 #[derive(Debug, Copy, Clone)]
 pub enum Version {
+    DBase2,
     FoxBase,
     DBase3 { supports_memo: bool },
     VisualFoxPro,
     DBase4 { supports_memo: bool },
+    DBase5,
+    DBase7 { supports_memo: bool },
     FoxPro2 { supports_memo: bool },
     Unknown(u8),
 }
@@ -30,6 +44,9 @@ impl Version {
             Version::VisualFoxPro => Some(MemoFileType::FoxBaseMemo),
             Version::DBase4 {
                 supports_memo: true,
+            }
+            | Version::DBase7 {
+                supports_memo: true,
             } => Some(MemoFileType::DbaseMemo4),
             Version::DBase4 {
                 supports_memo: false,
@@ -45,9 +62,74 @@ impl Version {
     }
 
     pub(crate) fn is_visual_fox_pro(self) -> bool {
-        match self {
-            Version::VisualFoxPro => true,
-            _ => false,
+        matches!(self, Version::VisualFoxPro)
+    }
+
+    /// Returns whether tables of this version have the longer header
+    /// and field descriptors of dBase 7, which allow names of up to 31 characters
+    pub(crate) fn has_dbase7_layout(self) -> bool {
+        matches!(self, Version::DBase7 { .. })
+    }
+
+    /// Returns whether tables of this version can have a memo file
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dbase::Version;
+    ///
+    /// assert!(Version::from(0x83).has_memo());
+    /// assert!(!Version::from(0x03).has_memo());
+    /// ```
+    pub fn has_memo(self) -> bool {
+        self.supported_memo_type().is_some()
+    }
+
+    /// Returns whether the fields descriptors of tables of this version
+    /// are followed by a backlink to a database container
+    pub fn has_backlink(self) -> bool {
+        self.is_visual_fox_pro()
+    }
+
+    /// Returns whether tables of this version can have fields of the given type
+    ///
+    /// Tables of an unknown version are assumed to support every type
+    /// except memo fields and the binary types of dBase 7.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dbase::{FieldType, Version};
+    ///
+    /// let version = Version::DBase3 { supports_memo: false };
+    /// assert!(version.supports_field_type(FieldType::Date));
+    /// assert!(!version.supports_field_type(FieldType::Integer));
+    /// assert!(!version.supports_field_type(FieldType::Memo));
+    /// assert!(Version::VisualFoxPro.supports_field_type(FieldType::Integer));
+    /// assert!(Version::DBase7 { supports_memo: false }.supports_field_type(FieldType::Long));
+    /// ```
+    pub fn supports_field_type(self, field_type: FieldType) -> bool {
+        match field_type {
+            FieldType::Character | FieldType::Numeric | FieldType::Logical => true,
+            FieldType::Date => !matches!(self, Version::DBase2),
+            FieldType::Memo => self.has_memo(),
+            FieldType::Float => matches!(
+                self,
+                Version::DBase4 { .. }
+                    | Version::DBase5
+                    | Version::DBase7 { .. }
+                    | Version::FoxPro2 { .. }
+                    | Version::VisualFoxPro
+                    | Version::Unknown(_)
+            ),
+            FieldType::Currency | FieldType::DateTime | FieldType::Integer | FieldType::Double => {
+                matches!(self, Version::VisualFoxPro | Version::Unknown(_))
+            }
+            // Their `I` is the Integer of the other tables
+            FieldType::Long
+            | FieldType::DBase7Double
+            | FieldType::Timestamp
+            | FieldType::Autoincrement => self.has_dbase7_layout(),
         }
     }
 }
@@ -55,7 +137,7 @@ impl Version {
 impl From<Version> for u8 {
     fn from(v: Version) -> u8 {
         match v {
-            Version::FoxBase => 0x02,
+            Version::DBase2 | Version::FoxBase => 0x02,
             Version::DBase3 {
                 supports_memo: false,
             } => 0x03,
@@ -69,6 +151,13 @@ impl From<Version> for u8 {
             Version::DBase4 {
                 supports_memo: false,
             } => 0x43,
+            Version::DBase5 => 0x05,
+            Version::DBase7 {
+                supports_memo: false,
+            } => 0x04,
+            Version::DBase7 {
+                supports_memo: true,
+            } => 0x8c,
             Version::FoxPro2 {
                 supports_memo: false,
             } => 0xfb,
@@ -83,6 +172,8 @@ impl From<Version> for u8 {
 impl From<u8> for Version {
     fn from(b: u8) -> Self {
         match b {
+            // dBase II files are told apart by the layout of their header,
+            // see Header::read_from
             0x02 => Version::FoxBase,
             0x03 => Version::DBase3 {
                 supports_memo: false,
@@ -90,9 +181,16 @@ impl From<u8> for Version {
             0x83 => Version::DBase3 {
                 supports_memo: true,
             },
+            0x04 => Version::DBase7 {
+                supports_memo: false,
+            },
+            0x8c => Version::DBase7 {
+                supports_memo: true,
+            },
+            0x05 => Version::DBase5,
             // Each version has different feature (varchar / autoincrement)
             // but we don't support that for now
-            0x30 | 0x31 | 0x32 => Version::VisualFoxPro,
+            0x30..=0x32 => Version::VisualFoxPro,
             // Same here these different version num means that some features are different
             0x8b | 0xcb => Version::DBase4 {
                 supports_memo: true,
@@ -111,26 +209,44 @@ impl From<u8> for Version {
     }
 }
 
-#[derive(Debug)]
+/// The flags of the table, stored in the header
+///
+/// The meaning of the bits depends on the version,
+/// dBase IV uses the first one for the production index (.mdx),
+/// FoxPro for the structural index (.cdx).
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
 pub struct TableFlags(u8);
 
 impl TableFlags {
+    /// Returns whether the table has a structural index (.cdx)
     pub fn has_structural_cdx(&self) -> bool {
         (self.0 & 0x01) == 1
     }
 
+    /// Returns whether the table has a production index (.mdx)
+    pub fn has_production_mdx(&self) -> bool {
+        (self.0 & 0x01) == 1
+    }
+
+    /// Returns whether the table has memo fields
     pub fn has_memo_field(&self) -> bool {
         (self.0 & 0x02) == 2
     }
 
+    /// Returns whether the table is a database container (.dbc)
     pub fn is_a_database(&self) -> bool {
-        (self.0 & 0x03) == 1
+        (self.0 & 0x04) == 4
+    }
+
+    /// Returns the byte of the flags
+    pub fn bits(&self) -> u8 {
+        self.0
     }
 }
 
 /// Definition of the header struct stored at the beginning
 /// of each dBase file
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Header {
     pub file_type: Version,
     pub last_update: Date,
@@ -141,11 +257,84 @@ pub struct Header {
     pub encryption_flag: u8,
     pub table_flags: TableFlags,
     pub code_page_mark: u8,
+    /// Name of the language driver of dBase 7 tables
+    pub(crate) language_driver_name: Option<String>,
 }
 
 impl Header {
     pub(crate) const SIZE: usize = 32;
 
+    /// Size of the header of dBase II tables, the descriptors of the fields included,
+    /// the records always start after it
+    pub(crate) const DBASE2_SIZE: u16 = 521;
+
+    /// Offset of the field descriptors in the header of dBase II tables
+    pub(crate) const DBASE2_FIELDS_OFFSET: usize = 8;
+
+    /// Size of the header of dBase 7 tables, which continues with
+    /// the name of the language driver and reserved bytes
+    pub(crate) const DBASE7_SIZE: usize = 68;
+
+    /// Size of the name of the language driver in the header of dBase 7 tables
+    const LANGUAGE_DRIVER_NAME_SIZE: usize = 32;
+
+    /// Returns whether the table is encrypted, its records being encrypted
+    ///
+    /// Such tables are read with the [RecordCipher](trait.RecordCipher.html) of their scheme,
+    /// see [ReadingOptions::decryption](struct.ReadingOptions.html#method.decryption)
+    /// and [TableWriterBuilder::encryption](struct.TableWriterBuilder.html#method.encryption).
+    pub fn is_encrypted(&self) -> bool {
+        self.encryption_flag != 0
+    }
+
+    /// Returns whether a transaction was started and not completed
+    /// when the table was last written
+    pub fn is_transaction_incomplete(&self) -> bool {
+        self.is_transaction_incomplete
+    }
+
+    /// Returns whether the table has a production index (.mdx or structural .cdx)
+    pub fn has_production_index(&self) -> bool {
+        self.table_flags.has_production_mdx()
+    }
+
+    /// Returns the name of the language driver of dBase 7 tables, if they have one
+    ///
+    /// Tables written from a reader keep it.
+    pub fn language_driver_name(&self) -> Option<&str> {
+        self.language_driver_name.as_deref()
+    }
+
+    /// Reads what follows the first `Header::SIZE` bytes in the header of dBase 7 tables
+    pub(crate) fn read_dbase7_extension<S: ByteSource + ?Sized>(
+        &mut self,
+        source: &mut S,
+    ) -> Result<(), S::Error> {
+        let bytes: [u8; Self::DBASE7_SIZE - Self::SIZE] = read_array(source)?;
+        let name = &bytes[..Self::LANGUAGE_DRIVER_NAME_SIZE];
+        let name_length = name.iter().position(|b| *b == 0).unwrap_or(name.len());
+        self.language_driver_name = if name_length == 0 {
+            None
+        } else {
+            Some(String::from_utf8_lossy(&name[..name_length]).into_owned())
+        };
+        Ok(())
+    }
+
+    /// Writes what follows the first `Header::SIZE` bytes in the header of dBase 7 tables
+    #[cfg(feature = "std")]
+    pub(crate) fn write_dbase7_extension<T: Write>(&self, dest: &mut T) -> std::io::Result<()> {
+        let mut bytes = [0u8; Self::DBASE7_SIZE - Self::SIZE];
+        if let Some(name) = &self.language_driver_name {
+            let name = name.as_bytes();
+            // The name ends with a NUL byte
+            let length = name.len().min(Self::LANGUAGE_DRIVER_NAME_SIZE - 1);
+            bytes[..length].copy_from_slice(&name[..length]);
+        }
+        dest.write_all(&bytes)
+    }
+
+    #[cfg(feature = "std")]
     pub(crate) fn new(num_records: u32, offset: u16, size_of_records: u16) -> Self {
         let current_date = Self::get_today_date();
         Self {
@@ -158,13 +347,15 @@ impl Header {
             size_of_record: size_of_records,
             is_transaction_incomplete: false,
             encryption_flag: 0,
-            table_flags: TableFlags { 0: 0 },
+            table_flags: TableFlags(0),
             code_page_mark: 0,
+            language_driver_name: None,
         }
     }
 
+    #[cfg(feature = "std")]
     fn get_today_date() -> Date {
-        let current_date: Date = chrono::Utc::now().date().into();
+        let current_date: Date = chrono::Utc::now().date_naive().into();
         // The year will be saved a a u8 offset from 1900
         if current_date.year() < 1900 || current_date.year() > 2155 {
             panic!("the year current date is out of range");
@@ -173,43 +364,74 @@ impl Header {
         }
     }
 
+    #[cfg(feature = "std")]
     pub(crate) fn update_date(&mut self) {
         self.last_update = Self::get_today_date();
     }
 
+    /// Reads the header, the first `Header::SIZE` bytes of the table
+    ///
+    /// For dBase II tables, which have a shorter header, the bytes that follow
+    /// it are the start of the field descriptors.
+    #[cfg(feature = "std")]
     pub(crate) fn read_from<T: Read>(source: &mut T) -> Result<Self, std::io::Error> {
-        let file_type = Version::from(source.read_u8()?);
-
-        let mut date_bytes = [0u8; 3];
-        source.read_exact(&mut date_bytes)?;
-        let last_update = Date {
-            year: 1900u32 + date_bytes[0] as u32,
-            month: date_bytes[1] as u32,
-            day: date_bytes[2] as u32,
-        };
-
-        let num_records = source.read_u32::<LittleEndian>()?;
-        let offset_to_first_record = source.read_u16::<LittleEndian>()?;
-        let size_of_record = source.read_u16::<LittleEndian>()?;
-
-        let _reserved = source.read_u16::<LittleEndian>()?;
+        Self::read_from_source(&mut IoSource(source))
+    }
 
-        let is_transaction_incomplete = (source.read_u8()? != 0) as bool;
-        let encryption_flag = source.read_u8()?;
+    /// Same as read_from, from a [ByteSource](trait.ByteSource.html)
+    pub(crate) fn read_from_source<S: ByteSource + ?Sized>(
+        source: &mut S,
+    ) -> Result<Self, S::Error> {
+        let bytes = read_array(source)?;
+        if is_dbase2_header(&bytes) {
+            Ok(Self::read_dbase2(&bytes))
+        } else {
+            Ok(Self::read_dbase3(&bytes))
+        }
+    }
 
-        let mut _reserved = [0u8; 12];
-        source.read_exact(&mut _reserved)?;
+    /// Reads the dBase II layout: the version, the number of records (u16),
+    /// the date of the last update (month, day, year) and the size of the records
+    fn read_dbase2(bytes: &[u8; Self::SIZE]) -> Self {
+        Self {
+            file_type: Version::DBase2,
+            last_update: Date {
+                year: 1900u32 + u32::from(bytes[5]),
+                month: u32::from(bytes[3]),
+                day: u32::from(bytes[4]),
+            },
+            num_records: u32::from(u16::from_le_bytes([bytes[1], bytes[2]])),
+            offset_to_first_record: Self::DBASE2_SIZE,
+            size_of_record: u16::from_le_bytes([bytes[6], bytes[7]]),
+            is_transaction_incomplete: false,
+            encryption_flag: 0,
+            table_flags: TableFlags(0),
+            code_page_mark: 0,
+            language_driver_name: None,
+        }
+    }
 
-        let table_flags = TableFlags {
-            0: source.read_u8()?,
+    /// Reads the layout of dBase III and later versions: the version, the date
+    /// of the last update (year, month, day), the number of records (u32), the offset
+    /// to the first record and the size of the records (u16), 2 reserved bytes,
+    /// the transaction and encryption flags, 12 reserved bytes, the table flags,
+    /// the code page mark and 2 reserved bytes
+    fn read_dbase3(bytes: &[u8; Self::SIZE]) -> Self {
+        let file_type = Version::from(bytes[0]);
+        let last_update = Date {
+            year: 1900u32 + u32::from(bytes[1]),
+            month: u32::from(bytes[2]),
+            day: u32::from(bytes[3]),
         };
+        let num_records = u32::from_le_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]);
+        let offset_to_first_record = u16::from_le_bytes([bytes[8], bytes[9]]);
+        let size_of_record = u16::from_le_bytes([bytes[10], bytes[11]]);
+        let is_transaction_incomplete = bytes[14] != 0;
+        let encryption_flag = bytes[15];
+        let table_flags = TableFlags(bytes[28]);
+        let code_page_mark = bytes[29];
 
-        let code_page_mark = source.read_u8()?;
-
-        let _reserved = source.read_u8()?;
-        let _reserved = source.read_u8()?;
-
-        Ok(Self {
+        Self {
             file_type,
             last_update,
             num_records,
@@ -219,9 +441,11 @@ impl Header {
             size_of_record,
             table_flags,
             code_page_mark,
-        })
+            language_driver_name: None,
+        }
     }
 
+    #[cfg(feature = "std")]
     pub(crate) fn write_to<T: Write>(&self, dest: &mut T) -> std::io::Result<()> {
         dest.write_u8(u8::from(self.file_type))?;
 
@@ -250,10 +474,22 @@ impl Header {
     }
 }
 
-#[cfg(test)]
+/// Returns whether the header has the dBase II layout
+///
+/// Like FoxBase tables, dBase II tables start with 0x02, but their first field descriptor
+/// comes right after the 8 bytes of their header: where FoxBase has reserved bytes
+/// (zeros), dBase II has the name of the field followed by its type.
+fn is_dbase2_header(bytes: &[u8; Header::SIZE]) -> bool {
+    const TYPE_OFFSET: usize = Header::DBASE2_FIELDS_OFFSET + 11;
+    bytes[0] == 0x02
+        && bytes[Header::DBASE2_FIELDS_OFFSET].is_ascii_alphabetic()
+        && matches!(bytes[TYPE_OFFSET], b'C' | b'N' | b'L')
+}
+
+#[cfg(all(test, feature = "std"))]
 mod test {
     use std::fs::File;
-    use std::io::{Cursor, Seek, SeekFrom};
+    use std::io::{Cursor, Seek};
 
     use super::*;
 
@@ -261,7 +497,7 @@ mod test {
     fn pos_after_reading_header() {
         let mut file = File::open("tests/data/line.dbf").unwrap();
         let _hdr = Header::read_from(&mut file).unwrap();
-        let pos_after_reading = file.seek(SeekFrom::Current(0)).unwrap();
+        let pos_after_reading = file.stream_position().unwrap();
         assert_eq!(pos_after_reading, Header::SIZE as u64);
     }
 
@@ -272,7 +508,7 @@ mod test {
 
         let mut out = Cursor::new(Vec::<u8>::with_capacity(Header::SIZE));
         hdr.write_to(&mut out).unwrap();
-        let pos_after_writing = out.seek(SeekFrom::Current(0)).unwrap();
+        let pos_after_writing = out.stream_position().unwrap();
         assert_eq!(pos_after_writing, Header::SIZE as u64);
     }
 
@@ -294,4 +530,50 @@ mod test {
 
         assert_eq!(hdr_bytes_written, hdr_bytes);
     }
+
+    #[test]
+    fn version_capabilities() {
+        for byte in [
+            0x02u8, 0x03, 0x04, 0x05, 0x30, 0x43, 0x83, 0x8b, 0x8c, 0xf5, 0xfb,
+        ] {
+            assert_eq!(u8::from(Version::from(byte)), byte);
+        }
+        assert!(matches!(
+            Version::from(0x8c),
+            Version::DBase7 {
+                supports_memo: true
+            }
+        ));
+        assert!(Version::from(0x8c).has_memo());
+        assert!(!Version::from(0x04).has_memo());
+        assert!(!Version::DBase2.supports_field_type(FieldType::Date));
+        assert!(Version::DBase5.supports_field_type(FieldType::Float));
+        assert!(!Version::from(0x03).supports_field_type(FieldType::Float));
+        assert!(Version::from(0x31).has_backlink());
+        assert!(!Version::from(0xf5).supports_field_type(FieldType::Currency));
+    }
+
+    #[test]
+    fn header_flags() {
+        let mut hdr_bytes = [0u8; Header::SIZE];
+        File::open("tests/data/line.dbf")
+            .unwrap()
+            .read_exact(&mut hdr_bytes)
+            .unwrap();
+        let hdr = Header::read_from(&mut &hdr_bytes[..]).unwrap();
+        assert!(!hdr.is_encrypted());
+        assert!(!hdr.is_transaction_incomplete());
+        assert!(!hdr.has_production_index());
+
+        hdr_bytes[14] = 1;
+        hdr_bytes[15] = 1;
+        hdr_bytes[28] = 0x03;
+        let hdr = Header::read_from(&mut &hdr_bytes[..]).unwrap();
+        assert!(hdr.is_encrypted());
+        assert!(hdr.is_transaction_incomplete());
+        assert!(hdr.has_production_index());
+        assert!(hdr.table_flags.has_memo_field());
+        assert!(!hdr.table_flags.is_a_database());
+        assert_eq!(hdr.table_flags.bits(), 0x03);
+    }
 }
@@ -0,0 +1,1512 @@
+//! Module with the definition of fn's and struct's to read and write dBase III index (.ndx) files
+//!
+//! An index file stores a B-tree of keys computed from the records of a table,
+//! each key pointing to the record it was computed from.
+//! Walking the leaves of the tree gives the records in key order.
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+
+use std::cmp::Ordering;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Cursor, Read, Seek, SeekFrom, Write};
+use std::ops::{Bound, RangeBounds};
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::encoding::{Encoding, UnicodeLossy};
+use crate::error::{Error, ErrorKind};
+use crate::expression::{Expression, Value, ValueType};
+use crate::reading::{ReadableRecord, Reader, Record};
+use crate::record::field::Date;
+use crate::record::FieldInfo;
+
+/// Size of a page (block) in an index file
+pub(crate) const PAGE_SIZE: usize = 512;
+
+/// Offset of the key expression in the header page
+const KEY_EXPRESSION_OFFSET: usize = 24;
+
+/// Longest key dBase accepts
+const MAX_KEY_LENGTH: u16 = 100;
+
+/// Trees deeper than this are certainly corrupted (they would hold way more
+/// records than a dBase file can)
+pub(crate) const MAX_DEPTH: usize = 32;
+
+/// The type of keys an index holds
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum KeyType {
+    /// Keys are strings, padded with spaces
+    Character,
+    /// Keys are numbers (dates are stored as their julian day number)
+    Numeric,
+}
+
+/// A key of an index
+///
+/// Character keys are compared byte by byte, a shorter key given
+/// as a bound matches all the keys it is a prefix of
+/// (eg: `"DU"` matches `"DUPONT"` and `"DURAND"`).
+#[derive(Debug, Clone, PartialEq)]
+pub enum IndexKey {
+    Character(String),
+    Numeric(f64),
+}
+
+impl IndexKey {
+    /// Returns the type of the key
+    pub fn key_type(&self) -> KeyType {
+        match self {
+            IndexKey::Character(_) => KeyType::Character,
+            IndexKey::Numeric(_) => KeyType::Numeric,
+        }
+    }
+}
+
+impl From<&str> for IndexKey {
+    fn from(s: &str) -> Self {
+        IndexKey::Character(s.to_owned())
+    }
+}
+
+impl From<String> for IndexKey {
+    fn from(s: String) -> Self {
+        IndexKey::Character(s)
+    }
+}
+
+impl From<f64> for IndexKey {
+    fn from(v: f64) -> Self {
+        IndexKey::Numeric(v)
+    }
+}
+
+impl From<Date> for IndexKey {
+    fn from(d: Date) -> Self {
+        IndexKey::Numeric(f64::from(d.to_julian_day_number()))
+    }
+}
+
+/// Header of an index file, stored in its first page
+#[derive(Debug, Clone)]
+pub struct IndexHeader {
+    pub(crate) root_page: u32,
+    pub(crate) num_pages: u32,
+    pub(crate) key_length: u16,
+    pub(crate) max_keys_per_page: u16,
+    pub(crate) key_type: KeyType,
+    pub(crate) key_entry_size: u16,
+    pub(crate) unique: bool,
+    pub(crate) key_expression: String,
+}
+
+impl IndexHeader {
+    pub(crate) fn new(key_expression: String, key_type: KeyType, key_length: u16) -> Self {
+        let key_entry_size = key_length.div_ceil(4) * 4 + 8;
+        Self {
+            root_page: 1,
+            num_pages: 2,
+            key_length,
+            max_keys_per_page: (PAGE_SIZE as u16 - 8) / key_entry_size,
+            key_type,
+            key_entry_size,
+            unique: false,
+            key_expression,
+        }
+    }
+
+    /// Returns the expression used to compute the keys (eg: `UPPER(NAME)`)
+    pub fn key_expression(&self) -> &str {
+        &self.key_expression
+    }
+
+    /// Returns the type of the keys
+    pub fn key_type(&self) -> KeyType {
+        self.key_type
+    }
+
+    /// Returns the length in bytes of the keys
+    pub fn key_length(&self) -> u16 {
+        self.key_length
+    }
+
+    /// Returns true if the index only keeps the first record of each key
+    pub fn is_unique(&self) -> bool {
+        self.unique
+    }
+
+    pub(crate) fn read_from<T: Read>(source: &mut T) -> Result<Self, ErrorKind> {
+        let mut page = [0u8; PAGE_SIZE];
+        source.read_exact(&mut page)?;
+        let mut cursor = &page[..];
+
+        let root_page = cursor.read_u32::<LittleEndian>()?;
+        let num_pages = cursor.read_u32::<LittleEndian>()?;
+        let _reserved = cursor.read_u32::<LittleEndian>()?;
+        let key_length = cursor.read_u16::<LittleEndian>()?;
+        let max_keys_per_page = cursor.read_u16::<LittleEndian>()?;
+        let key_type = match cursor.read_u16::<LittleEndian>()? {
+            0 => KeyType::Character,
+            _ => KeyType::Numeric,
+        };
+        let key_entry_size = cursor.read_u16::<LittleEndian>()?;
+        let unique = page[23] != 0;
+
+        let expression_bytes = &page[KEY_EXPRESSION_OFFSET..];
+        let expression_len = expression_bytes
+            .iter()
+            .position(|b| *b == 0)
+            .unwrap_or(expression_bytes.len());
+        let key_expression = String::from_utf8_lossy(&expression_bytes[..expression_len])
+            .trim()
+            .to_owned();
+
+        if key_length == 0 || usize::from(key_entry_size) < usize::from(key_length) + 8 {
+            return Err(ErrorKind::InvalidIndex(format!(
+                "key length {} does not fit in key entries of {} bytes",
+                key_length, key_entry_size
+            )));
+        }
+
+        if max_keys_per_page == 0 {
+            return Err(ErrorKind::InvalidIndex(
+                "pages cannot hold any key".to_owned(),
+            ));
+        }
+
+        Ok(Self {
+            root_page,
+            num_pages,
+            key_length,
+            max_keys_per_page,
+            key_type,
+            key_entry_size,
+            unique,
+            key_expression,
+        })
+    }
+
+    pub(crate) fn write_to<T: Write>(&self, dest: &mut T) -> std::io::Result<()> {
+        let mut page = [0u8; PAGE_SIZE];
+        {
+            let mut cursor = &mut page[..];
+            cursor.write_u32::<LittleEndian>(self.root_page)?;
+            cursor.write_u32::<LittleEndian>(self.num_pages)?;
+            cursor.write_u32::<LittleEndian>(0)?;
+            cursor.write_u16::<LittleEndian>(self.key_length)?;
+            cursor.write_u16::<LittleEndian>(self.max_keys_per_page)?;
+            cursor.write_u16::<LittleEndian>(match self.key_type {
+                KeyType::Character => 0,
+                KeyType::Numeric => 1,
+            })?;
+            cursor.write_u16::<LittleEndian>(self.key_entry_size)?;
+        }
+        page[23] = u8::from(self.unique);
+        let expression = self.key_expression.as_bytes();
+        // Keep the terminating null byte
+        let len = expression.len().min(PAGE_SIZE - KEY_EXPRESSION_OFFSET - 1);
+        page[KEY_EXPRESSION_OFFSET..KEY_EXPRESSION_OFFSET + len]
+            .copy_from_slice(&expression[..len]);
+        dest.write_all(&page)
+    }
+}
+
+/// One key stored in a page
+#[derive(Debug, Clone)]
+pub(crate) struct IndexEntry {
+    /// Page holding the keys lower or equal than this one, 0 in leaf pages
+    pub(crate) child: u32,
+    /// Record number in the table (1-based), unused in interior pages
+    pub(crate) record_number: u32,
+    pub(crate) key: Vec<u8>,
+}
+
+/// A page (node) of the B-tree
+#[derive(Debug, Clone)]
+pub(crate) struct IndexPage {
+    pub(crate) entries: Vec<IndexEntry>,
+    /// Page holding the keys greater than the last key, 0 in leaf pages
+    pub(crate) last_child: u32,
+}
+
+impl IndexPage {
+    fn is_leaf(&self) -> bool {
+        match self.entries.first() {
+            Some(entry) => entry.child == 0,
+            None => self.last_child == 0,
+        }
+    }
+
+    /// Returns the child page at the given position,
+    /// the position `entries.len()` denotes the last child.
+    fn child(&self, position: usize) -> u32 {
+        if position < self.entries.len() {
+            self.entries[position].child
+        } else {
+            self.last_child
+        }
+    }
+
+    fn read_from<T: Read>(source: &mut T, header: &IndexHeader) -> Result<Self, ErrorKind> {
+        let mut page = [0u8; PAGE_SIZE];
+        source.read_exact(&mut page)?;
+        let mut cursor = &page[..];
+
+        let num_keys = cursor.read_u32::<LittleEndian>()? as usize;
+        let entry_size = usize::from(header.key_entry_size);
+        if 4 + num_keys * entry_size > PAGE_SIZE {
+            return Err(ErrorKind::InvalidIndex(format!(
+                "page claims to hold {} keys",
+                num_keys
+            )));
+        }
+
+        let mut entries = Vec::<IndexEntry>::with_capacity(num_keys);
+        for _ in 0..num_keys {
+            let child = cursor.read_u32::<LittleEndian>()?;
+            let record_number = cursor.read_u32::<LittleEndian>()?;
+            let key = cursor[..usize::from(header.key_length)].to_vec();
+            cursor = &cursor[entry_size - 8..];
+            entries.push(IndexEntry {
+                child,
+                record_number,
+                key,
+            });
+        }
+        let last_child = if cursor.len() >= 4 {
+            cursor.read_u32::<LittleEndian>()?
+        } else {
+            0
+        };
+        Ok(Self {
+            entries,
+            last_child,
+        })
+    }
+
+    fn write_to<T: Write>(&self, dest: &mut T, header: &IndexHeader) -> std::io::Result<()> {
+        let mut page = [0u8; PAGE_SIZE];
+        {
+            let mut cursor = &mut page[..];
+            cursor.write_u32::<LittleEndian>(self.entries.len() as u32)?;
+            let padding = vec![0u8; usize::from(header.key_entry_size - 8 - header.key_length)];
+            for entry in &self.entries {
+                cursor.write_u32::<LittleEndian>(entry.child)?;
+                cursor.write_u32::<LittleEndian>(entry.record_number)?;
+                cursor.write_all(&entry.key)?;
+                cursor.write_all(&padding)?;
+            }
+            cursor.write_u32::<LittleEndian>(self.last_child)?;
+        }
+        dest.write_all(&page)
+    }
+}
+
+/// Struct with the handle to the source .ndx file
+///
+/// # Example
+///
+/// ```no_run
+/// # fn main() -> Result<(), dbase::Error> {
+/// let mut reader = dbase::Reader::from_path("customers.dbf")?;
+/// let mut index = dbase::Index::from_path("customers_by_name.ndx")?;
+/// // All the customers whose name starts with 'DU', in alphabetical order
+/// for record in index.range(&mut reader, "DU"..="DU")? {
+///     let record = record?;
+///     println!("{:?}", record.get("NAME"));
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct Index<T: Read + Seek> {
+    source: T,
+    header: IndexHeader,
+    /// Encoding of the Character keys given to record_indices
+    encoding: Arc<dyn Encoding>,
+}
+
+impl<T: Read + Seek> Index<T> {
+    /// Creates a new index from the source,
+    /// the header is read as soon as its created.
+    pub fn new(mut source: T) -> Result<Self, Error> {
+        let header = IndexHeader::read_from(&mut source).map_err(|kind| Error {
+            record_num: 0,
+            field: None,
+            kind,
+        })?;
+        Ok(Self {
+            source,
+            header,
+            encoding: Arc::new(UnicodeLossy),
+        })
+    }
+
+    /// Sets the encoding of the table, used to encode the Character keys given to
+    /// [record_indices](#method.record_indices), UTF-8 by default
+    ///
+    /// [range](#method.range) encodes them with the encoding of the reader.
+    pub fn with_encoding<E: Encoding + 'static>(mut self, encoding: E) -> Self {
+        self.encoding = Arc::new(encoding);
+        self
+    }
+
+    /// Returns the header of the index
+    pub fn header(&self) -> &IndexHeader {
+        &self.header
+    }
+
+    /// Returns an iterator over the records of the reader whose key
+    /// is in the given range, in key order.
+    ///
+    /// The index must have been built for the table the reader reads.
+    pub fn range<'a, R, K, B>(
+        &'a mut self,
+        reader: &'a mut Reader<R>,
+        bounds: B,
+    ) -> Result<IndexRange<'a, T, R, Record>, Error>
+    where
+        R: Read + Seek,
+        K: Into<IndexKey> + Clone,
+        B: RangeBounds<K>,
+    {
+        self.range_as::<Record, R, K, B>(reader, bounds)
+    }
+
+    /// Same as [range](#method.range) but the records are read as the
+    /// type you want.
+    pub fn range_as<'a, Rec, R, K, B>(
+        &'a mut self,
+        reader: &'a mut Reader<R>,
+        bounds: B,
+    ) -> Result<IndexRange<'a, T, R, Rec>, Error>
+    where
+        Rec: ReadableRecord,
+        R: Read + Seek,
+        K: Into<IndexKey> + Clone,
+        B: RangeBounds<K>,
+    {
+        let encoding = Arc::clone(&reader.options.encoding);
+        let lower = self.convert_bound(bounds.start_bound(), &*encoding)?;
+        let upper = self.convert_bound(bounds.end_bound(), &*encoding)?;
+        let cursor = IndexCursor::seek(self, lower, upper)?;
+        Ok(IndexRange {
+            index: self,
+            reader,
+            cursor,
+            record_type: std::marker::PhantomData,
+        })
+    }
+
+    /// Returns the indices (0-based) of the records whose key is in the given range,
+    /// in key order, without reading the records.
+    ///
+    /// This is useful when the records are not read with a [Reader](struct.Reader.html).
+    pub fn record_indices<K, B>(&mut self, bounds: B) -> Result<Vec<usize>, Error>
+    where
+        K: Into<IndexKey> + Clone,
+        B: RangeBounds<K>,
+    {
+        let encoding = Arc::clone(&self.encoding);
+        let lower = self.convert_bound(bounds.start_bound(), &*encoding)?;
+        let upper = self.convert_bound(bounds.end_bound(), &*encoding)?;
+        let mut cursor = IndexCursor::seek(self, lower, upper)?;
+        let mut indices = vec![];
+        while let Some(entry) = cursor.next_entry(self)? {
+            if entry.record_number == 0 {
+                return Err(Error {
+                    record_num: 0,
+                    field: None,
+                    kind: ErrorKind::InvalidIndex("key points to record 0".to_owned()),
+                });
+            }
+            indices.push(entry.record_number as usize - 1);
+        }
+        Ok(indices)
+    }
+
+    fn convert_bound<K>(
+        &self,
+        bound: Bound<&K>,
+        encoding: &dyn Encoding,
+    ) -> Result<Bound<Vec<u8>>, Error>
+    where
+        K: Into<IndexKey> + Clone,
+    {
+        let convert = |key: &K| -> Result<Vec<u8>, Error> {
+            self.key_to_bytes(&key.clone().into(), encoding)
+                .map_err(|kind| Error {
+                    record_num: 0,
+                    field: None,
+                    kind,
+                })
+        };
+        Ok(match bound {
+            Bound::Included(key) => Bound::Included(convert(key)?),
+            Bound::Excluded(key) => Bound::Excluded(convert(key)?),
+            Bound::Unbounded => Bound::Unbounded,
+        })
+    }
+
+    /// Converts a key to the representation used by the index, Character keys
+    /// being encoded with the encoding of the table
+    ///
+    /// Fails with IncompatibleType if the type of key does not match the index one
+    fn key_to_bytes(&self, key: &IndexKey, encoding: &dyn Encoding) -> Result<Vec<u8>, ErrorKind> {
+        match (key, self.header.key_type) {
+            (IndexKey::Character(s), KeyType::Character) => encode_key(s, encoding, None),
+            (IndexKey::Numeric(v), KeyType::Numeric) => Ok(v.to_le_bytes().to_vec()),
+            _ => Err(ErrorKind::IncompatibleType),
+        }
+    }
+
+    /// Compares a key of the index with a (possibly shorter) bound key
+    fn compare(&self, key: &[u8], bound: &[u8]) -> Ordering {
+        match self.header.key_type {
+            KeyType::Character => key[..key.len().min(bound.len())].cmp(bound),
+            KeyType::Numeric => {
+                let key = numeric_key_value(key);
+                let bound = numeric_key_value(bound);
+                key.partial_cmp(&bound).unwrap_or(Ordering::Equal)
+            }
+        }
+    }
+
+    fn read_page(&mut self, page_number: u32) -> Result<IndexPage, Error> {
+        if page_number == 0 || (self.header.num_pages != 0 && page_number >= self.header.num_pages)
+        {
+            return Err(Error {
+                record_num: 0,
+                field: None,
+                kind: ErrorKind::InvalidIndex(format!("page {} is out of bounds", page_number)),
+            });
+        }
+        self.source
+            .seek(SeekFrom::Start(u64::from(page_number) * PAGE_SIZE as u64))
+            .map_err(|error| Error::io_error(error, 0))?;
+        IndexPage::read_from(&mut self.source, &self.header).map_err(|kind| Error {
+            record_num: 0,
+            field: None,
+            kind,
+        })
+    }
+}
+
+impl<T: Read + Seek> Index<T> {
+    /// Checks that the index is up to date with the content of the table:
+    /// the keys are recomputed from the records (using the key expression)
+    /// and compared with the keys stored in the index.
+    ///
+    /// Returns `Ok(false)` if the index needs to be [rebuilt](#method.reindex).
+    pub fn verify<R: Read + Seek>(&mut self, reader: &mut Reader<R>) -> Result<bool, Error> {
+        let mut expected = IndexBuilder::from(&self.header)
+            .updater_with_records(reader)?
+            .entries;
+        sort_entries(&self.header, &mut expected);
+
+        let mut cursor = IndexCursor::seek(self, Bound::Unbounded, Bound::Unbounded)?;
+        let mut expected = expected.into_iter();
+        loop {
+            match (cursor.next_entry(self)?, expected.next()) {
+                (None, None) => return Ok(true),
+                (Some(entry), Some(expected_entry)) => {
+                    if entry.record_number != expected_entry.record_number
+                        || entry.key != expected_entry.key
+                    {
+                        return Ok(false);
+                    }
+                }
+                _ => return Ok(false),
+            }
+        }
+    }
+}
+
+/// Sources whose length can be set, so that an index rebuilt in place
+/// does not keep the pages of the previous one past its end
+pub trait SetLen {
+    /// Truncates or extends the source to `len` bytes
+    fn set_len(&mut self, len: u64) -> std::io::Result<()>;
+}
+
+impl SetLen for File {
+    fn set_len(&mut self, len: u64) -> std::io::Result<()> {
+        File::set_len(self, len)
+    }
+}
+
+impl SetLen for Cursor<Vec<u8>> {
+    fn set_len(&mut self, len: u64) -> std::io::Result<()> {
+        self.get_mut().resize(len as usize, 0);
+        Ok(())
+    }
+}
+
+impl SetLen for Cursor<&mut Vec<u8>> {
+    fn set_len(&mut self, len: u64) -> std::io::Result<()> {
+        self.get_mut().resize(len as usize, 0);
+        Ok(())
+    }
+}
+
+impl<T: Read + Write + Seek + SetLen> Index<T> {
+    /// Rebuilds the index from the current content of the table, keeping
+    /// its key expression and unique flag.
+    ///
+    /// The index must have been built for the table the reader reads.
+    /// The source is truncated after the rebuilt index, which may be shorter.
+    pub fn reindex<R: Read + Seek>(&mut self, reader: &mut Reader<R>) -> Result<(), Error> {
+        let io_error = |error| Error::io_error(error, 0);
+        self.source.seek(SeekFrom::Start(0)).map_err(io_error)?;
+        IndexBuilder::from(&self.header).build_with_dest(reader, &mut self.source)?;
+        let len = self.source.stream_position().map_err(io_error)?;
+        self.source.set_len(len).map_err(io_error)?;
+        self.source.seek(SeekFrom::Start(0)).map_err(io_error)?;
+        self.header = IndexHeader::read_from(&mut self.source).map_err(|kind| Error {
+            record_num: 0,
+            field: None,
+            kind,
+        })?;
+        Ok(())
+    }
+}
+
+impl Index<BufReader<File>> {
+    /// Creates a new index from a path
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let file = File::open(path).map_err(|error| Error::io_error(error, 0))?;
+        Index::new(BufReader::new(file))
+    }
+}
+
+/// Rebuilds the index files of a table from its current content
+///
+/// Each index is first written next to the old one and then renamed,
+/// so that an error does not leave a half-written index behind.
+///
+/// Only dBase III indexes (.ndx) can be rebuilt: the production indexes of dBase IV (.mdx)
+/// and FoxPro (.cdx) are an error, as their tags can only be read.
+///
+/// # Example
+///
+/// ```
+/// # fn main() -> Result<(), dbase::Error> {
+/// # let index_path = std::env::temp_dir().join("dbase_reindex_doc.ndx");
+/// let mut reader = dbase::Reader::from_path("tests/data/line.dbf")?;
+/// dbase::IndexBuilder::new("NAME").build_with_file_dest(&mut reader, &index_path)?;
+///
+/// dbase::reindex("tests/data/line.dbf", &[&index_path])?;
+/// # Ok(())
+/// # }
+/// ```
+pub fn reindex<P: AsRef<Path>, I: AsRef<Path>>(
+    table_path: P,
+    index_paths: &[I],
+) -> Result<(), Error> {
+    let mut reader = Reader::from_path(table_path)?;
+    for index_path in index_paths {
+        let index_path = index_path.as_ref();
+        let extension = index_path
+            .extension()
+            .map(|extension| extension.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        if extension == "mdx" || extension == "cdx" {
+            return Err(Error {
+                record_num: 0,
+                field: None,
+                kind: ErrorKind::InvalidIndex(format!(
+                    "'{}' is a production index, only .ndx indexes can be rebuilt",
+                    index_path.display()
+                )),
+            });
+        }
+        let builder = IndexBuilder::from(Index::from_path(index_path)?.header());
+
+        let mut tmp_path = index_path.as_os_str().to_owned();
+        tmp_path.push(".tmp");
+        builder.build_with_file_dest(&mut reader, &tmp_path)?;
+        std::fs::rename(&tmp_path, index_path).map_err(|error| Error::io_error(error, 0))?;
+    }
+    Ok(())
+}
+
+/// Encodes a Character key with the encoding of the table
+///
+/// With a length, the key is cut on a character boundary if its bytes are longer,
+/// and padded with spaces if they are shorter.
+fn encode_key(
+    text: &str,
+    encoding: &dyn Encoding,
+    length: Option<usize>,
+) -> Result<Vec<u8>, ErrorKind> {
+    let mut key = encoding
+        .encode(text)
+        .ok_or_else(|| ErrorKind::Message(format!("'{}' cannot be encoded", text)))?
+        .into_owned();
+    if let Some(length) = length {
+        let mut end = text.len();
+        while key.len() > length {
+            end = text[..end].char_indices().last().map_or(0, |(i, _)| i);
+            key = encoding.encode(&text[..end]).unwrap_or_default().into_owned();
+        }
+        key.resize(length, b' ');
+    }
+    Ok(key)
+}
+
+fn numeric_key_value(bytes: &[u8]) -> f64 {
+    let mut value = [0u8; 8];
+    let len = bytes.len().min(8);
+    value[..len].copy_from_slice(&bytes[..len]);
+    f64::from_le_bytes(value)
+}
+
+/// Builder to create a new index over the records of a table
+///
+/// The key expression is the name of the field the records will be sorted by.
+///
+/// # Example
+///
+/// ```
+/// # fn main() -> Result<(), dbase::Error> {
+/// use std::io::Cursor;
+///
+/// let mut reader = dbase::Reader::from_path("tests/data/line.dbf")?;
+/// let index_bytes = dbase::IndexBuilder::new("NAME")
+///     .build_with_dest(&mut reader, Cursor::new(Vec::<u8>::new()))?;
+///
+/// let mut index = dbase::Index::new(Cursor::new(index_bytes.into_inner()))?;
+/// let records = index.range(&mut reader, "line"..="line")?.collect::<Result<Vec<_>, _>>()?;
+/// assert_eq!(records.len(), 1);
+/// # Ok(())
+/// # }
+/// ```
+pub struct IndexBuilder {
+    key_expression: String,
+    unique: bool,
+}
+
+impl IndexBuilder {
+    /// Creates a builder for an index whose keys are computed with the expression
+    pub fn new(key_expression: &str) -> Self {
+        Self {
+            key_expression: key_expression.to_owned(),
+            unique: false,
+        }
+    }
+
+    /// Sets whether only the first record of each key should be indexed
+    pub fn unique(mut self, unique: bool) -> Self {
+        self.unique = unique;
+        self
+    }
+
+    /// Computes the keys of all the records of the reader
+    /// and writes the index to the destination, which is returned once finished
+    pub fn build_with_dest<T: Read + Seek, W: Write>(
+        &self,
+        reader: &mut Reader<T>,
+        mut dst: W,
+    ) -> Result<W, Error> {
+        let num_records = reader.header.num_records;
+        self.updater_with_records(reader)?
+            .write_to(&mut dst)
+            .map_err(|error| Error::io_error(error, num_records as usize))?;
+        Ok(dst)
+    }
+
+    /// Returns an updater to which all the records of the reader were added
+    fn updater_with_records<T: Read + Seek>(
+        &self,
+        reader: &mut Reader<T>,
+    ) -> Result<IndexUpdater, Error> {
+        let encoding = Arc::clone(&reader.options.encoding);
+        let mut updater = self.updater(&reader.fields_info, encoding).map_err(|kind| Error {
+            record_num: 0,
+            field: None,
+            kind,
+        })?;
+
+        for i in 0..reader.header.num_records {
+            let record = reader.read_record_at_as::<Record>(i)?;
+            updater.add(&record, i).map_err(|kind| Error {
+                record_num: i as usize,
+                field: None,
+                kind,
+            })?;
+        }
+        Ok(updater)
+    }
+
+    /// Returns the updater that computes the entries of the index for a table
+    /// made of the given fields, whose Character values are in the encoding
+    pub(crate) fn updater(
+        &self,
+        fields: &[FieldInfo],
+        encoding: Arc<dyn Encoding>,
+    ) -> Result<IndexUpdater, ErrorKind> {
+        let key_expression = KeyExpression::new(&self.key_expression, fields, encoding)?;
+        let mut header = key_expression.header();
+        header.unique = self.unique;
+        Ok(IndexUpdater {
+            key_expression,
+            header,
+            entries: vec![],
+        })
+    }
+
+    /// Helper function to create the index in a file at the given path
+    pub fn build_with_file_dest<T: Read + Seek, P: AsRef<Path>>(
+        &self,
+        reader: &mut Reader<T>,
+        path: P,
+    ) -> Result<(), Error> {
+        let file = File::create(path).map_err(|error| Error::io_error(error, 0))?;
+        self.build_with_dest(reader, BufWriter::new(file))?
+            .flush()
+            .map_err(|error| Error::io_error(error, 0))
+    }
+}
+
+impl<'a> From<&'a IndexHeader> for IndexBuilder {
+    fn from(header: &'a IndexHeader) -> Self {
+        IndexBuilder::new(&header.key_expression).unique(header.unique)
+    }
+}
+
+/// Keeps track of the entries of an index while records are added to a table
+pub(crate) struct IndexUpdater {
+    key_expression: KeyExpression,
+    header: IndexHeader,
+    entries: Vec<IndexEntry>,
+}
+
+impl IndexUpdater {
+    /// Adds the key of the record at the given index (0-based)
+    pub(crate) fn add(&mut self, record: &Record, index: u32) -> Result<(), ErrorKind> {
+        let key = self.key_expression.key_of(record)?;
+        self.entries.push(IndexEntry {
+            child: 0,
+            record_number: index + 1,
+            key,
+        });
+        Ok(())
+    }
+
+    /// Writes the index with all the added entries
+    pub(crate) fn write_to<W: Write>(self, dst: &mut W) -> std::io::Result<()> {
+        write_index(dst, self.header, self.entries)
+    }
+}
+
+/// Computes the index key of records
+pub(crate) struct KeyExpression {
+    expression: Expression,
+    key_type: KeyType,
+    key_length: u16,
+    /// The encoding of the table, Character keys are compared as the bytes it gives
+    encoding: Arc<dyn Encoding>,
+}
+
+impl KeyExpression {
+    /// Parses the expression against the fields of the table
+    pub(crate) fn new(
+        expression: &str,
+        fields: &[FieldInfo],
+        encoding: Arc<dyn Encoding>,
+    ) -> Result<Self, ErrorKind> {
+        let expression = Expression::parse(expression, fields)?;
+        let (key_type, key_length) = match expression.value_type() {
+            ValueType::Character(len) if len > 0 => (KeyType::Character, len),
+            ValueType::Numeric | ValueType::Date => (KeyType::Numeric, 8),
+            value_type => {
+                return Err(ErrorKind::InvalidExpression(format!(
+                    "{:?} values cannot be used as keys",
+                    value_type
+                )))
+            }
+        };
+        if key_length > usize::from(MAX_KEY_LENGTH) {
+            return Err(ErrorKind::InvalidExpression(format!(
+                "keys cannot be longer than {} bytes",
+                MAX_KEY_LENGTH
+            )));
+        }
+        Ok(Self {
+            expression,
+            key_type,
+            key_length: key_length as u16,
+            encoding,
+        })
+    }
+
+    /// Returns the key of the record, in its index representation
+    pub(crate) fn key_of(&self, record: &Record) -> Result<Vec<u8>, ErrorKind> {
+        let key = match self.expression.evaluate_value(record)? {
+            Value::Character(s) => {
+                encode_key(&s, &*self.encoding, Some(usize::from(self.key_length)))?
+            }
+            Value::Numeric(v) => v.to_le_bytes().to_vec(),
+            Value::Date(d) => d
+                .map_or(0.0, |d| f64::from(d.to_julian_day_number()))
+                .to_le_bytes()
+                .to_vec(),
+            Value::Logical(_) => return Err(ErrorKind::IncompatibleType),
+        };
+        Ok(key)
+    }
+
+    pub(crate) fn header(&self) -> IndexHeader {
+        IndexHeader::new(
+            self.expression.text().to_uppercase(),
+            self.key_type,
+            self.key_length,
+        )
+    }
+}
+
+fn compare_keys(key_type: KeyType, a: &[u8], b: &[u8]) -> Ordering {
+    match key_type {
+        KeyType::Character => a.cmp(b),
+        KeyType::Numeric => numeric_key_value(a)
+            .partial_cmp(&numeric_key_value(b))
+            .unwrap_or(Ordering::Equal),
+    }
+}
+
+/// Puts the entries in the order they have in the index
+///
+/// `entries` are expected to be in record order, so that records
+/// with the same key stay in that order.
+fn sort_entries(header: &IndexHeader, entries: &mut Vec<IndexEntry>) {
+    let key_type = header.key_type;
+    entries.sort_by(|a, b| compare_keys(key_type, &a.key, &b.key));
+    if header.unique {
+        entries.dedup_by(|b, a| compare_keys(key_type, &a.key, &b.key) == Ordering::Equal);
+    }
+}
+
+/// Sorts the entries and writes them as a B-tree
+///
+/// `entries` are expected to be in record order, so that records
+/// with the same key stay in that order.
+pub(crate) fn write_index<W: Write>(
+    dst: &mut W,
+    mut header: IndexHeader,
+    mut entries: Vec<IndexEntry>,
+) -> std::io::Result<()> {
+    sort_entries(&header, &mut entries);
+
+    let max_keys = usize::from(header.max_keys_per_page);
+    let mut pages = Vec::<IndexPage>::new();
+    // page number and greatest entry of each page of the level being built
+    let mut level = Vec::<(u32, IndexEntry)>::new();
+    if entries.is_empty() {
+        pages.push(IndexPage {
+            entries: vec![],
+            last_child: 0,
+        });
+    }
+    for chunk in entries.chunks(max_keys) {
+        pages.push(IndexPage {
+            entries: chunk.to_vec(),
+            last_child: 0,
+        });
+        level.push((pages.len() as u32, chunk[chunk.len() - 1].clone()));
+    }
+
+    while level.len() > 1 {
+        let num_parents = (level.len() + max_keys) / (max_keys + 1);
+        let mut parents = Vec::<(u32, IndexEntry)>::with_capacity(num_parents);
+        let mut children = level.into_iter();
+        for i in 0..num_parents {
+            // Spread the children evenly so that no page ends up with a single child
+            let remaining_parents = num_parents - i;
+            let num_children = children.len().div_ceil(remaining_parents);
+            let mut group = children.by_ref().take(num_children).collect::<Vec<_>>();
+            let (last_page, last_entry) = group.pop().expect("parent page without children");
+            let page_entries = group
+                .into_iter()
+                .map(|(page_number, entry)| IndexEntry {
+                    child: page_number,
+                    ..entry
+                })
+                .collect::<Vec<_>>();
+            pages.push(IndexPage {
+                entries: page_entries,
+                last_child: last_page,
+            });
+            parents.push((pages.len() as u32, last_entry));
+        }
+        level = parents;
+    }
+
+    header.root_page = level.first().map_or(1, |(page_number, _)| *page_number);
+    header.num_pages = pages.len() as u32 + 1;
+    header.write_to(dst)?;
+    for page in &pages {
+        page.write_to(dst, &header)?;
+    }
+    Ok(())
+}
+
+/// Position in the B-tree, as the path from the root page to a leaf page
+struct IndexCursor {
+    /// The pages from the root to the current leaf and the position
+    /// of the child (or of the key, for the leaf) we are at in each one of them
+    stack: Vec<(IndexPage, usize)>,
+    upper: Bound<Vec<u8>>,
+}
+
+impl IndexCursor {
+    /// Positions the cursor on the first key that is not below the lower bound
+    fn seek<T: Read + Seek>(
+        index: &mut Index<T>,
+        lower: Bound<Vec<u8>>,
+        upper: Bound<Vec<u8>>,
+    ) -> Result<Self, Error> {
+        let is_below_lower = |index: &Index<T>, key: &[u8]| match &lower {
+            Bound::Included(bound) => index.compare(key, bound) == Ordering::Less,
+            Bound::Excluded(bound) => index.compare(key, bound) != Ordering::Greater,
+            Bound::Unbounded => false,
+        };
+
+        let mut stack = Vec::<(IndexPage, usize)>::new();
+        let mut page_number = index.header.root_page;
+        loop {
+            if stack.len() > MAX_DEPTH {
+                return Err(Error {
+                    record_num: 0,
+                    field: None,
+                    kind: ErrorKind::InvalidIndex("the tree is too deep".to_owned()),
+                });
+            }
+            let page = index.read_page(page_number)?;
+            let position = page
+                .entries
+                .iter()
+                .position(|e| !is_below_lower(index, &e.key))
+                .unwrap_or(page.entries.len());
+            if page.is_leaf() {
+                stack.push((page, position));
+                break;
+            }
+            page_number = page.child(position);
+            stack.push((page, position));
+        }
+        Ok(Self { stack, upper })
+    }
+
+    /// Returns the next entry of the leaves, stopping at the upper bound
+    fn next_entry<T: Read + Seek>(
+        &mut self,
+        index: &mut Index<T>,
+    ) -> Result<Option<IndexEntry>, Error> {
+        loop {
+            let descend_to = match self.stack.last_mut() {
+                None => return Ok(None),
+                Some((page, position)) if page.is_leaf() => {
+                    if *position < page.entries.len() {
+                        let entry = page.entries[*position].clone();
+                        *position += 1;
+                        let is_above_upper = match &self.upper {
+                            Bound::Included(bound) => {
+                                index.compare(&entry.key, bound) == Ordering::Greater
+                            }
+                            Bound::Excluded(bound) => {
+                                index.compare(&entry.key, bound) != Ordering::Less
+                            }
+                            Bound::Unbounded => false,
+                        };
+                        if is_above_upper {
+                            self.stack.clear();
+                            return Ok(None);
+                        }
+                        return Ok(Some(entry));
+                    }
+                    None
+                }
+                Some((page, position)) => {
+                    *position += 1;
+                    if *position <= page.entries.len() {
+                        Some(page.child(*position))
+                    } else {
+                        None
+                    }
+                }
+            };
+
+            match descend_to {
+                None => {
+                    self.stack.pop();
+                }
+                Some(mut page_number) => loop {
+                    if self.stack.len() > MAX_DEPTH {
+                        return Err(Error {
+                            record_num: 0,
+                            field: None,
+                            kind: ErrorKind::InvalidIndex("the tree is too deep".to_owned()),
+                        });
+                    }
+                    let page = index.read_page(page_number)?;
+                    let is_leaf = page.is_leaf();
+                    page_number = page.child(0);
+                    self.stack.push((page, 0));
+                    if is_leaf {
+                        break;
+                    }
+                },
+            }
+        }
+    }
+}
+
+/// Iterator over the records of a table in the order given by an index
+///
+/// Created by [Index::range](struct.Index.html#method.range)
+pub struct IndexRange<'a, T: Read + Seek, R: Read + Seek, Rec: ReadableRecord> {
+    index: &'a mut Index<T>,
+    reader: &'a mut Reader<R>,
+    cursor: IndexCursor,
+    record_type: std::marker::PhantomData<Rec>,
+}
+
+impl<'a, T: Read + Seek, R: Read + Seek, Rec: ReadableRecord> Iterator
+    for IndexRange<'a, T, R, Rec>
+{
+    type Item = Result<Rec, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let entry = match self.cursor.next_entry(self.index) {
+            Ok(Some(entry)) => entry,
+            Ok(None) => return None,
+            Err(error) => {
+                self.cursor.stack.clear();
+                return Some(Err(error));
+            }
+        };
+
+        let num_records = self.reader.header.num_records;
+        if entry.record_number == 0 || entry.record_number > num_records {
+            self.cursor.stack.clear();
+            return Some(Err(Error {
+                record_num: entry.record_number as usize,
+                field: None,
+                kind: ErrorKind::InvalidIndex(format!(
+                    "key points to record {} but the table has {} records",
+                    entry.record_number, num_records
+                )),
+            }));
+        }
+        Some(
+            self.reader
+                .read_record_at_as::<Rec>(entry.record_number - 1),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use byteorder::WriteBytesExt;
+    use std::convert::TryFrom;
+    use std::io::{Cursor, Write};
+
+    use crate::{FieldName, FieldValue, ReadingOptions, TableWriterBuilder};
+
+    const NAMES: [&str; 6] = ["Alice", "Bob", "Brenda", "Bruno", "Carl", "Dave"];
+
+    fn write_header(dst: &mut Vec<u8>, root: u32, num_pages: u32) {
+        let start = dst.len();
+        dst.write_u32::<LittleEndian>(root).unwrap();
+        dst.write_u32::<LittleEndian>(num_pages).unwrap();
+        dst.write_u32::<LittleEndian>(0).unwrap();
+        dst.write_u16::<LittleEndian>(8).unwrap(); // key length
+        dst.write_u16::<LittleEndian>(31).unwrap(); // keys per page
+        dst.write_u16::<LittleEndian>(0).unwrap(); // character
+        dst.write_u16::<LittleEndian>(16).unwrap(); // entry size
+        dst.write_all(&[0u8; 4]).unwrap();
+        dst.write_all(b"NAME").unwrap();
+        dst.resize(start + PAGE_SIZE, 0);
+    }
+
+    fn write_page(dst: &mut Vec<u8>, entries: &[(u32, u32, &str)], last_child: u32) {
+        let start = dst.len();
+        dst.write_u32::<LittleEndian>(entries.len() as u32).unwrap();
+        for (child, record_number, key) in entries {
+            dst.write_u32::<LittleEndian>(*child).unwrap();
+            dst.write_u32::<LittleEndian>(*record_number).unwrap();
+            dst.write_all(format!("{:8}", key).as_bytes()).unwrap();
+        }
+        dst.write_u32::<LittleEndian>(last_child).unwrap();
+        dst.resize(start + PAGE_SIZE, 0);
+    }
+
+    type InMemory = Cursor<Vec<u8>>;
+
+    /// The table stores the names in reverse order, the index
+    /// is a root with two leaves
+    fn table_and_index() -> (Reader<InMemory>, Index<InMemory>) {
+        let records = NAMES
+            .iter()
+            .rev()
+            .map(|name| {
+                let mut record = Record::default();
+                record.insert(
+                    "NAME".to_owned(),
+                    FieldValue::Character(Some(name.to_string())),
+                );
+                record
+            })
+            .collect::<Vec<Record>>();
+        let table = TableWriterBuilder::new()
+            .add_character_field(FieldName::try_from("NAME").unwrap(), 8)
+            .build_with_dest(Cursor::new(Vec::<u8>::new()))
+            .write(&records)
+            .unwrap();
+        let reader = Reader::new(Cursor::new(table.into_inner())).unwrap();
+
+        let record_number = |name: &str| -> u32 {
+            (NAMES.len() - NAMES.iter().position(|n| *n == name).unwrap()) as u32
+        };
+        let mut bytes = Vec::<u8>::new();
+        write_header(&mut bytes, 3, 4);
+        let first_leaf = NAMES[..3]
+            .iter()
+            .map(|name| (0, record_number(name), *name))
+            .collect::<Vec<_>>();
+        write_page(&mut bytes, &first_leaf, 0);
+        let second_leaf = NAMES[3..]
+            .iter()
+            .map(|name| (0, record_number(name), *name))
+            .collect::<Vec<_>>();
+        write_page(&mut bytes, &second_leaf, 0);
+        write_page(&mut bytes, &[(1, 0, NAMES[2])], 2);
+
+        let index = Index::new(Cursor::new(bytes)).unwrap();
+        (reader, index)
+    }
+
+    fn names_in<B: RangeBounds<&'static str>>(bounds: B) -> Vec<String> {
+        let (mut reader, mut index) = table_and_index();
+        index
+            .range(&mut reader, bounds)
+            .unwrap()
+            .map(|record| match record.unwrap().get("NAME") {
+                Some(FieldValue::Character(Some(name))) => name.clone(),
+                _ => panic!("Record without name"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn read_index_header() {
+        let (_, index) = table_and_index();
+        assert_eq!(index.header().key_expression(), "NAME");
+        assert_eq!(index.header().key_type(), KeyType::Character);
+        assert_eq!(index.header().key_length(), 8);
+        assert!(!index.header().is_unique());
+    }
+
+    #[test]
+    fn full_range_is_in_key_order() {
+        assert_eq!(names_in::<std::ops::RangeFull>(..), NAMES);
+    }
+
+    #[test]
+    fn record_indices_in_key_order() {
+        let (_, mut index) = table_and_index();
+        // Bob, Brenda and Bruno, the records are in reverse order
+        assert_eq!(index.record_indices("B".."C").unwrap(), vec![4, 3, 2]);
+    }
+
+    #[test]
+    fn range_across_leaves() {
+        assert_eq!(names_in("Bo".."C"), ["Bob", "Brenda", "Bruno"]);
+        assert_eq!(names_in("Brenda"..="Carl"), ["Brenda", "Bruno", "Carl"]);
+    }
+
+    #[test]
+    fn prefix_range() {
+        assert_eq!(names_in("Br"..="Br"), ["Brenda", "Bruno"]);
+        assert_eq!(names_in("D"..), ["Dave"]);
+        assert!(names_in("E"..).is_empty());
+    }
+
+    /// Table with one record per day starting on the 1st of January 2000,
+    /// the codes are not unique and not in order
+    fn big_table(num_records: u32) -> Reader<InMemory> {
+        let records = (0..num_records)
+            .map(|i| {
+                let mut record = Record::default();
+                record.insert(
+                    "CODE".to_owned(),
+                    FieldValue::Character(Some(format!("K{:04}", (i * 7919) % 500))),
+                );
+                record.insert(
+                    "DAY".to_owned(),
+                    FieldValue::Date(Some(Date::julian_day_number_to_gregorian_date(
+                        2_451_545 + i as i32,
+                    ))),
+                );
+                record
+            })
+            .collect::<Vec<Record>>();
+        let table = TableWriterBuilder::new()
+            .add_character_field(FieldName::try_from("CODE").unwrap(), 10)
+            .add_date_field(FieldName::try_from("DAY").unwrap())
+            .build_with_dest(Cursor::new(Vec::<u8>::new()))
+            .write(&records)
+            .unwrap();
+        Reader::new(Cursor::new(table.into_inner())).unwrap()
+    }
+
+    fn build_index(reader: &mut Reader<InMemory>, builder: IndexBuilder) -> Index<InMemory> {
+        let bytes = builder
+            .build_with_dest(reader, Cursor::new(Vec::<u8>::new()))
+            .unwrap()
+            .into_inner();
+        assert_eq!(bytes.len() % PAGE_SIZE, 0);
+        Index::new(Cursor::new(bytes)).unwrap()
+    }
+
+    fn code_of(record: Result<Record, Error>) -> String {
+        match record.unwrap().get("CODE") {
+            Some(FieldValue::Character(Some(code))) => code.clone(),
+            _ => panic!("Record without code"),
+        }
+    }
+
+    #[test]
+    fn build_multi_level_character_index() {
+        let mut reader = big_table(3000);
+        let mut index = build_index(&mut reader, IndexBuilder::new("code"));
+        assert_eq!(index.header().key_expression(), "CODE");
+        assert_eq!(index.header().key_length(), 10);
+        // 3000 keys, 25 per page means at least 3 levels
+        assert!(index.header().num_pages > 125);
+
+        let codes = index
+            .range::<_, &str, _>(&mut reader, ..)
+            .unwrap()
+            .map(code_of)
+            .collect::<Vec<String>>();
+        assert_eq!(codes.len(), 3000);
+        let mut expected = codes.clone();
+        expected.sort();
+        assert_eq!(codes, expected);
+
+        let codes = index
+            .range(&mut reader, "K0010"..="K0011")
+            .unwrap()
+            .map(code_of)
+            .collect::<Vec<String>>();
+        assert_eq!(codes.len(), 12);
+        assert!(codes[..6].iter().all(|c| c == "K0010"));
+        assert!(codes[6..].iter().all(|c| c == "K0011"));
+    }
+
+    #[test]
+    fn build_unique_index() {
+        let mut reader = big_table(3000);
+        let mut index = build_index(&mut reader, IndexBuilder::new("CODE").unique(true));
+        assert!(index.header().is_unique());
+        let codes = index
+            .range::<_, &str, _>(&mut reader, ..)
+            .unwrap()
+            .map(code_of)
+            .collect::<Vec<String>>();
+        assert_eq!(codes.len(), 500);
+    }
+
+    #[test]
+    fn build_date_index() {
+        let mut reader = big_table(100);
+        let mut index = build_index(&mut reader, IndexBuilder::new("DAY"));
+        assert_eq!(index.header().key_type(), KeyType::Numeric);
+        let days = index
+            .range(&mut reader, Date::new(10, 1, 2000)..Date::new(20, 1, 2000))
+            .unwrap()
+            .map(|record| match record.unwrap().get("DAY") {
+                Some(FieldValue::Date(Some(day))) => day.day(),
+                _ => panic!("Record without day"),
+            })
+            .collect::<Vec<u32>>();
+        assert_eq!(days, (10..20).collect::<Vec<u32>>());
+    }
+
+    #[test]
+    fn build_index_of_empty_table() {
+        let mut reader = big_table(0);
+        let mut index = build_index(&mut reader, IndexBuilder::new("CODE"));
+        assert_eq!(
+            index.range::<_, &str, _>(&mut reader, ..).unwrap().count(),
+            0
+        );
+    }
+
+    #[test]
+    fn build_index_on_unknown_field() {
+        let mut reader = big_table(1);
+        let result = IndexBuilder::new("NOPE").build_with_dest(&mut reader, Vec::<u8>::new());
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn reindex_in_place() {
+        let mut reader = big_table(200);
+        let mut source = build_index(&mut reader, IndexBuilder::new("CODE").unique(true)).source;
+        source.set_position(0);
+        let mut index = Index::new(source).unwrap();
+
+        // The table changed, its records are now the first 30 ones
+        let mut reader = big_table(30);
+        index.reindex(&mut reader).unwrap();
+        // The pages of the bigger index are not kept
+        let rebuilt = build_index(&mut reader, IndexBuilder::new("CODE").unique(true));
+        assert_eq!(index.source.get_ref().len(), rebuilt.source.get_ref().len());
+        assert!(index.header().is_unique());
+        assert_eq!(index.header().key_expression(), "CODE");
+
+        let codes = index
+            .range::<_, &str, _>(&mut reader, ..)
+            .unwrap()
+            .map(code_of)
+            .collect::<Vec<String>>();
+        let mut expected = codes.clone();
+        expected.sort();
+        assert_eq!(codes.len(), 30);
+        assert_eq!(codes, expected);
+    }
+
+    #[test]
+    fn production_indexes_are_not_rebuilt() {
+        let index_path = std::env::temp_dir().join("dbase_reindex_production.mdx");
+        let error = reindex("tests/data/line.dbf", &[&index_path]).unwrap_err();
+        assert!(matches!(error.kind(), ErrorKind::InvalidIndex(_)));
+    }
+
+    #[test]
+    fn build_index_on_expression() {
+        let mut reader = big_table(3000);
+        let mut index = build_index(&mut reader, IndexBuilder::new("code + dtos(DAY)"));
+        assert_eq!(index.header().key_expression(), "CODE + DTOS(DAY)");
+        assert_eq!(index.header().key_length(), 18);
+
+        let days = index
+            .range(&mut reader, "K0010"..="K0010")
+            .unwrap()
+            .map(|record| match record.unwrap().get("DAY") {
+                Some(FieldValue::Date(Some(day))) => day.to_julian_day_number(),
+                _ => panic!("Record without day"),
+            })
+            .collect::<Vec<i32>>();
+        assert_eq!(days.len(), 6);
+        let mut expected = days.clone();
+        expected.sort();
+        assert_eq!(days, expected);
+    }
+
+    fn root_keys(index: &mut Index<InMemory>) -> Vec<Vec<u8>> {
+        let root = index.read_page(index.header.root_page).unwrap();
+        root.entries.into_iter().map(|e| e.key).collect()
+    }
+
+    #[test]
+    fn keys_are_in_the_encoding_of_the_table() {
+        #[derive(Debug)]
+        struct Cp1252;
+
+        impl Encoding for Cp1252 {
+            fn decode<'a>(
+                &self,
+                bytes: &'a [u8],
+            ) -> Result<std::borrow::Cow<'a, str>, crate::DecodeError> {
+                Ok(bytes.iter().map(|b| char::from(*b)).collect::<String>().into())
+            }
+
+            fn encode<'a>(&self, text: &'a str) -> Option<std::borrow::Cow<'a, [u8]>> {
+                text.chars()
+                    .map(|c| u8::try_from(u32::from(c)).ok())
+                    .collect::<Option<Vec<u8>>>()
+                    .map(Into::into)
+            }
+        }
+
+        let records = ["Zoé", "Émile", "Adèle"]
+            .iter()
+            .map(|name| Record::builder().set("NAME", *name).build())
+            .collect::<Vec<_>>();
+        let index_path = std::env::temp_dir().join("dbase_cp1252_keys.ndx");
+        let table = TableWriterBuilder::new()
+            .add_character_field(FieldName::try_from("NAME").unwrap(), 6)
+            .encoding(Cp1252)
+            .build_with_dest(Cursor::new(Vec::<u8>::new()))
+            .add_index_with_file_dest(IndexBuilder::new("UPPER(NAME)"), &index_path)
+            .unwrap()
+            .write(&records)
+            .unwrap();
+        let mut reader = Reader::with_options(
+            Cursor::new(table.into_inner()),
+            ReadingOptions::default().encoding(Cp1252),
+        )
+        .unwrap();
+        let mut index = build_index(&mut reader, IndexBuilder::new("UPPER(NAME)"));
+        let expected_keys = vec![
+            b"AD\xC8LE ".to_vec(),
+            b"ZO\xC9   ".to_vec(),
+            b"\xC9MILE ".to_vec(),
+        ];
+        assert_eq!(root_keys(&mut index), expected_keys);
+        // The index the writer updated as it wrote the records
+        let mut written_index = Index::from_path(&index_path).unwrap();
+        let root = written_index
+            .read_page(written_index.header.root_page)
+            .unwrap();
+        let written_keys = root.entries.into_iter().map(|e| e.key).collect::<Vec<_>>();
+        assert_eq!(written_keys, expected_keys);
+        let names = index
+            .range(&mut reader, "ÉMILE"..="ÉMILE")
+            .unwrap()
+            .map(|record| match record.unwrap().get("NAME") {
+                Some(FieldValue::Character(Some(name))) => name.clone(),
+                _ => panic!("Record without name"),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["Émile"]);
+        let mut index = index.with_encoding(Cp1252);
+        assert_eq!(index.record_indices("ZOÉ"..="ZOÉ").unwrap(), vec![0]);
+    }
+
+    #[test]
+    fn expression_keys_are_cut_between_characters() {
+        let records = [Record::builder().set("NAME", "Zoé").build()];
+        let table = TableWriterBuilder::new()
+            .add_character_field(FieldName::try_from("NAME").unwrap(), 4)
+            .build_with_dest(Cursor::new(Vec::<u8>::new()))
+            .write(&records)
+            .unwrap();
+        let mut reader = Reader::new(Cursor::new(table.into_inner())).unwrap();
+        // The key has 5 characters, but "é" and "Zoé" are 6 bytes in UTF-8
+        let mut index = build_index(&mut reader, IndexBuilder::new("'é' + UPPER(NAME)"));
+        assert_eq!(index.header().key_length(), 5);
+        assert_eq!(root_keys(&mut index), vec!["éZO ".as_bytes().to_vec()]);
+    }
+
+    #[test]
+    fn verify_index() {
+        let mut reader = big_table(200);
+        let mut index = build_index(&mut reader, IndexBuilder::new("UPPER(CODE)"));
+        assert!(index.verify(&mut reader).unwrap());
+
+        let mut reader = big_table(100);
+        assert!(!index.verify(&mut reader).unwrap());
+        index.reindex(&mut reader).unwrap();
+        assert!(index.verify(&mut reader).unwrap());
+    }
+
+    #[test]
+    fn key_type_mismatch() {
+        let (mut reader, mut index) = table_and_index();
+        assert!(index.range(&mut reader, 1.0..2.0).is_err());
+    }
+}
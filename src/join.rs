@@ -0,0 +1,289 @@
+//! Module with the definition of the struct's joining the records
+//! of two tables on the values of key fields
+use std::collections::{HashMap, HashSet};
+use std::io::{Read, Seek};
+
+use crate::error::{Error, ErrorKind};
+use crate::reading::{Reader, Record, RecordIterator};
+
+/// Whether the records that have no matching record in the lookup table are kept
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum JoinKind {
+    /// Only the records that have matching records are emitted
+    Inner,
+    /// The records that have no matching record are emitted without
+    /// the fields of the lookup table
+    Left,
+}
+
+/// Hash join of the records of a table with the records of a small lookup table
+///
+/// The records of the lookup table are read in memory, grouped by the values
+/// of their key fields. The records of the other table are then read one at a time
+/// by [join](#method.join), and emitted with the fields of each lookup record
+/// that has the same key values, in the order of the lookup table.
+///
+/// Key values are matched when they are equal and of the same type,
+/// the key fields of the lookup table are not added to the records.
+/// The other fields of the lookup table keep their name, unless renamed
+/// with [rename](#method.rename), or prefixed with [prefix](#method.prefix) when the
+/// other table has a field with the same name.
+///
+/// # Example
+///
+/// ```
+/// # fn main() -> Result<(), dbase::Error> {
+/// use dbase::FieldValue;
+///
+/// // Pairs of stations of the same line
+/// let mut lookup = dbase::Reader::from_path("tests/data/stations.dbf")?;
+/// let mut stations = dbase::Reader::from_path("tests/data/stations.dbf")?;
+/// let join = dbase::HashJoin::new(&mut lookup, &["line"])?;
+/// let pairs = join
+///     .join(&mut stations, &["line"])?
+///     .collect::<Result<Vec<_>, _>>()?;
+/// // 4 blue and 2 red stations
+/// assert_eq!(pairs.len(), 4 * 4 + 2 * 2);
+/// assert_eq!(
+///     pairs[0].get("LOOKUP_name"),
+///     Some(&FieldValue::Character(Some("Van Dorn Street".to_owned())))
+/// );
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct HashJoin {
+    records: HashMap<Vec<String>, Vec<Record>>,
+    /// The fields of the lookup records that are added, in order
+    field_names: Vec<String>,
+    renames: HashMap<String, String>,
+    prefix: String,
+    kind: JoinKind,
+}
+
+impl HashJoin {
+    /// Prefix of the fields of the lookup table that collide, by default
+    pub const DEFAULT_PREFIX: &'static str = "LOOKUP_";
+
+    /// Reads the remaining records of the lookup table,
+    /// to be joined on the values of the key fields
+    pub fn new<T: Read + Seek>(lookup: &mut Reader<T>, key_fields: &[&str]) -> Result<Self, Error> {
+        check_fields(lookup, key_fields)?;
+        let field_names = lookup
+            .fields()
+            .iter()
+            .filter(|field| !field.is_deletion_flag() && !key_fields.contains(&field.name()))
+            .map(|field| field.name().to_owned())
+            .collect();
+        let mut records = HashMap::<Vec<String>, Vec<Record>>::new();
+        for record in lookup.iter_records() {
+            let mut record = record?;
+            let key = key_of(&record, key_fields);
+            for name in key_fields {
+                record.remove(name);
+            }
+            records.entry(key).or_default().push(record);
+        }
+        Ok(Self {
+            records,
+            field_names,
+            renames: HashMap::new(),
+            prefix: Self::DEFAULT_PREFIX.to_owned(),
+            kind: JoinKind::Inner,
+        })
+    }
+
+    /// Sets the prefix added to the names of the fields of the lookup table
+    /// that the other table also has
+    pub fn prefix<S: Into<String>>(mut self, prefix: S) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    /// Renames a field of the lookup table in the joined records
+    pub fn rename<S: Into<String>, D: Into<String>>(mut self, name: S, new_name: D) -> Self {
+        self.renames.insert(name.into(), new_name.into());
+        self
+    }
+
+    /// Sets whether the records without matching records are kept, [JoinKind::Inner] by default
+    pub fn kind(mut self, kind: JoinKind) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    /// Returns the number of records of the lookup table
+    pub fn num_lookup_records(&self) -> usize {
+        self.records.values().map(Vec::len).sum()
+    }
+
+    /// Returns an iterator over the joined records of the remaining records of the reader,
+    /// the key fields are matched in order with the key fields of the lookup table
+    pub fn join<'a, T: Read + Seek>(
+        &'a self,
+        reader: &'a mut Reader<T>,
+        key_fields: &'a [&'a str],
+    ) -> Result<JoinIterator<'a, T>, Error> {
+        check_fields(reader, key_fields)?;
+        let names = reader
+            .fields()
+            .iter()
+            .map(|field| field.name().to_owned())
+            .collect::<HashSet<_>>();
+        let field_names = self
+            .field_names
+            .iter()
+            .map(|name| {
+                let new_name = match self.renames.get(name) {
+                    Some(new_name) => new_name.clone(),
+                    None if names.contains(name) => format!("{}{}", self.prefix, name),
+                    None => name.clone(),
+                };
+                (name.clone(), new_name)
+            })
+            .collect();
+        Ok(JoinIterator {
+            join: self,
+            records: reader.iter_records(),
+            key_fields,
+            field_names,
+            pending: vec![],
+        })
+    }
+}
+
+fn check_fields<T: Read + Seek>(reader: &Reader<T>, names: &[&str]) -> Result<(), Error> {
+    match names
+        .iter()
+        .find(|name| !reader.fields().iter().any(|field| field.name() == **name))
+    {
+        Some(name) => Err(Error {
+            record_num: 0,
+            field: None,
+            kind: ErrorKind::Message(format!("the table has no field named '{}'", name)),
+        }),
+        None => Ok(()),
+    }
+}
+
+fn key_of(record: &Record, key_fields: &[&str]) -> Vec<String> {
+    key_fields
+        .iter()
+        .map(|name| format!("{:?}", record.get(name)))
+        .collect()
+}
+
+/// Iterator over joined records, created by [HashJoin::join](struct.HashJoin.html#method.join)
+pub struct JoinIterator<'a, T: Read + Seek> {
+    join: &'a HashJoin,
+    records: RecordIterator<'a, T, Record>,
+    key_fields: &'a [&'a str],
+    /// Names of the fields of the lookup records, and their names in the joined records
+    field_names: Vec<(String, String)>,
+    /// Joined records not yet emitted, in reverse order
+    pending: Vec<Record>,
+}
+
+impl<T: Read + Seek> Iterator for JoinIterator<'_, T> {
+    type Item = Result<Record, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(record) = self.pending.pop() {
+                return Some(Ok(record));
+            }
+            let record = match self.records.next()? {
+                Ok(record) => record,
+                Err(error) => return Some(Err(error)),
+            };
+            let matches = match self.join.records.get(&key_of(&record, self.key_fields)) {
+                Some(matches) => matches,
+                None if self.join.kind == JoinKind::Left => return Some(Ok(record)),
+                None => continue,
+            };
+            for lookup_record in matches.iter().rev() {
+                let mut joined = record.clone();
+                for (name, new_name) in &self.field_names {
+                    if let Some(value) = lookup_record.get(name) {
+                        joined.insert(new_name.clone(), value.clone());
+                    }
+                }
+                self.pending.push(joined);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::FieldValue;
+    use std::io::Cursor;
+
+    fn table(fields: &[(&str, u8)], rows: &[&[&str]]) -> Reader<Cursor<Vec<u8>>> {
+        let mut builder = crate::TableWriterBuilder::new();
+        for (name, length) in fields {
+            builder = builder.add_character_field(crate::FieldName::new(name).unwrap(), *length);
+        }
+        let records = rows
+            .iter()
+            .map(|row| {
+                let mut record = Record::default();
+                for ((name, _), value) in fields.iter().zip(row.iter()) {
+                    record.insert((*name).to_owned(), (*value).to_owned().into());
+                }
+                record
+            })
+            .collect::<Vec<_>>();
+        let cursor = builder
+            .build_with_dest(Cursor::new(Vec::<u8>::new()))
+            .write(&records)
+            .unwrap();
+        Reader::new(Cursor::new(cursor.into_inner())).unwrap()
+    }
+
+    fn text(record: &Record, name: &str) -> Option<String> {
+        match record.get(name) {
+            Some(FieldValue::Character(text)) => text.clone(),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn join_with_collisions() {
+        let mut cities = table(
+            &[("CODE", 2), ("NAME", 10)],
+            &[&["FR", "Paris"], &["FR", "Lyon"], &["DE", "Berlin"]],
+        );
+        let mut people = table(
+            &[("NAME", 10), ("COUNTRY", 2)],
+            &[&["Ada", "DE"], &["Bob", "FR"], &["Eve", "US"]],
+        );
+
+        let join = HashJoin::new(&mut cities, &["CODE"]).unwrap();
+        assert_eq!(join.num_lookup_records(), 3);
+        let joined = join
+            .join(&mut people, &["COUNTRY"])
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(joined.len(), 3);
+        assert_eq!(text(&joined[0], "NAME").as_deref(), Some("Ada"));
+        assert_eq!(text(&joined[0], "LOOKUP_NAME").as_deref(), Some("Berlin"));
+        assert_eq!(text(&joined[1], "LOOKUP_NAME").as_deref(), Some("Paris"));
+        assert_eq!(text(&joined[2], "LOOKUP_NAME").as_deref(), Some("Lyon"));
+        assert_eq!(joined[0].get("CODE"), None);
+
+        people.set_record_range(0..3).unwrap();
+        let join = join.rename("NAME", "CITY").kind(JoinKind::Left);
+        let joined = join
+            .join(&mut people, &["COUNTRY"])
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(joined.len(), 4);
+        assert_eq!(text(&joined[0], "CITY").as_deref(), Some("Berlin"));
+        assert_eq!(text(&joined[3], "NAME").as_deref(), Some("Eve"));
+        assert_eq!(joined[3].get("CITY"), None);
+    }
+}
@@ -0,0 +1,229 @@
+//! Module with the definition of fn's to export dBase files as [JSON Lines](https://jsonlines.org)
+//!
+//! Each record is written as a JSON object on its own line, with one member per field:
+//!
+//! - Character and Memo values are strings
+//! - Numeric, Float, Double, Currency and Integer values are numbers
+//! - Logical values are booleans
+//! - Date values are strings formatted as `YYYY-MM-DD`
+//! - DateTime values are strings formatted as `YYYY-MM-DDTHH:MM:SS`
+//! - empty values (and numbers that are not finite) are `null`
+use std::fmt::Display;
+use std::io::{Read, Seek, Write};
+
+use crate::error::Error;
+use crate::reading::{Reader, RecordValues};
+use crate::record::field::FieldValue;
+use crate::record::FieldInfo;
+
+fn write_string<W: Write>(dst: &mut W, text: &str) -> std::io::Result<()> {
+    dst.write_all(b"\"")?;
+    let bytes = text.as_bytes();
+    let mut start = 0;
+    // Characters that must be escaped are all ASCII,
+    // so the bytes of multi-byte UTF-8 characters are never escaped
+    for (i, byte) in bytes.iter().enumerate() {
+        let escaped: &[u8] = match byte {
+            b'"' => b"\\\"",
+            b'\\' => b"\\\\",
+            b'\n' => b"\\n",
+            b'\r' => b"\\r",
+            b'\t' => b"\\t",
+            0x00..=0x1F => b"",
+            _ => continue,
+        };
+        dst.write_all(&bytes[start..i])?;
+        if escaped.is_empty() {
+            write!(dst, "\\u{:04x}", byte)?;
+        } else {
+            dst.write_all(escaped)?;
+        }
+        start = i + 1;
+    }
+    dst.write_all(&bytes[start..])?;
+    dst.write_all(b"\"")
+}
+
+fn write_number<W: Write, N: Display>(
+    dst: &mut W,
+    value: N,
+    is_finite: bool,
+) -> std::io::Result<()> {
+    if is_finite {
+        write!(dst, "{}", value)
+    } else {
+        dst.write_all(b"null")
+    }
+}
+
+fn write_value<W: Write>(dst: &mut W, value: &FieldValue) -> std::io::Result<()> {
+    match value {
+        FieldValue::Character(Some(text)) | FieldValue::Memo(text) => write_string(dst, text),
+        FieldValue::Numeric(Some(value))
+        | FieldValue::Double(value)
+        | FieldValue::Currency(value)
+        | FieldValue::DBase7Double(Some(value)) => write_number(dst, value, value.is_finite()),
+        FieldValue::Float(Some(value)) => write_number(dst, value, value.is_finite()),
+        FieldValue::Integer(value)
+        | FieldValue::Long(Some(value))
+        | FieldValue::Autoincrement(value) => write!(dst, "{}", value),
+        FieldValue::NumericInteger(Some(value)) => write!(dst, "{}", value),
+        FieldValue::Logical(Some(value)) => write!(dst, "{}", value),
+        FieldValue::Date(Some(date)) => write!(
+            dst,
+            "\"{:04}-{:02}-{:02}\"",
+            date.year(),
+            date.month(),
+            date.day()
+        ),
+        FieldValue::DateTime(date_time) | FieldValue::Timestamp(Some(date_time)) => {
+            let (date, time) = (date_time.date(), date_time.time());
+            write!(
+                dst,
+                "\"{:04}-{:02}-{:02}T{:02}:{:02}:{:02}\"",
+                date.year(),
+                date.month(),
+                date.day(),
+                time.hours(),
+                time.minutes(),
+                time.seconds()
+            )
+        }
+        FieldValue::Character(None)
+        | FieldValue::Numeric(None)
+        | FieldValue::Float(None)
+        | FieldValue::Logical(None)
+        | FieldValue::Date(None)
+        | FieldValue::NumericInteger(None)
+        | FieldValue::Long(None)
+        | FieldValue::DBase7Double(None)
+        | FieldValue::Timestamp(None) => dst.write_all(b"null"),
+    }
+}
+
+impl<T: Read + Seek> Reader<T> {
+    /// Writes the remaining records as JSON Lines, one object per record,
+    /// the records marked as deleted are not written
+    ///
+    /// The records are written as they are read, the destination is
+    /// not flushed. Returns the number of records written.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), dbase::Error> {
+    /// let mut reader = dbase::Reader::from_path("tests/data/line.dbf")?;
+    /// let mut lines = Vec::<u8>::new();
+    /// reader.to_jsonl(&mut lines)?;
+    /// assert!(lines.starts_with(b"{\"name\":"));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn to_jsonl<W: Write>(&mut self, mut dst: W) -> Result<usize, Error> {
+        let field_names = self
+            .fields_info
+            .iter()
+            .filter(|field_info| !field_info.is_deletion_flag())
+            .map(FieldInfo::name)
+            .map(|name| {
+                let mut encoded = Vec::<u8>::new();
+                write_string(&mut encoded, name).map(|_| encoded)
+            })
+            .collect::<std::io::Result<Vec<Vec<u8>>>>()
+            .map_err(|error| Error::io_error(error, 0))?;
+
+        let mut num_records = 0;
+        let mut line = Vec::<u8>::new();
+        while let Some(record) = self.read_next_record_as::<RecordValues>() {
+            let record = record?;
+            if record.is_deleted {
+                continue;
+            }
+            line.clear();
+            line.push(b'{');
+            for (i, (name, value)) in field_names.iter().zip(&record.values).enumerate() {
+                if i > 0 {
+                    line.push(b',');
+                }
+                line.extend_from_slice(name);
+                line.push(b':');
+                write_value(&mut line, value)
+                    .map_err(|error| Error::io_error(error, num_records))?;
+            }
+            line.extend_from_slice(b"}\n");
+            dst.write_all(&line)
+                .map_err(|error| Error::io_error(error, num_records))?;
+            num_records += 1;
+        }
+        Ok(num_records)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::convert::TryFrom;
+    use std::io::Cursor;
+
+    use crate::record::field::{Date, DateTime, Time};
+    use crate::{FieldName, Record, TableWriterBuilder};
+
+    #[test]
+    fn string_escaping() {
+        let mut encoded = Vec::<u8>::new();
+        write_string(&mut encoded, "a \"quoted\"\\path\n\u{1}é").unwrap();
+        assert_eq!(
+            String::from_utf8(encoded).unwrap(),
+            "\"a \\\"quoted\\\"\\\\path\\n\\u0001é\""
+        );
+    }
+
+    #[test]
+    fn export_to_json_lines() {
+        let records = [
+            (Some("Ferris"), 3, Some(true), Some(Date::new(15, 5, 2015))),
+            (None, -1, None, None),
+        ]
+        .iter()
+        .map(|(name, count, swims, born)| {
+            let mut record = Record::default();
+            record.insert(
+                "NAME".to_owned(),
+                FieldValue::Character(name.map(str::to_owned)),
+            );
+            record.insert("COUNT".to_owned(), FieldValue::Integer(*count));
+            record.insert("SWIMS".to_owned(), FieldValue::Logical(*swims));
+            record.insert("BORN".to_owned(), FieldValue::Date(*born));
+            record.insert(
+                "SEEN".to_owned(),
+                FieldValue::DateTime(DateTime::new(Date::new(1, 2, 2020), Time::new(13, 4, 5))),
+            );
+            record.insert("WEIGHT".to_owned(), FieldValue::Double(0.25));
+            record
+        })
+        .collect::<Vec<Record>>();
+        let mut cursor = TableWriterBuilder::new()
+            .add_character_field(FieldName::try_from("NAME").unwrap(), 20)
+            .add_integer_field(FieldName::try_from("COUNT").unwrap())
+            .add_logical_field(FieldName::try_from("SWIMS").unwrap())
+            .add_date_field(FieldName::try_from("BORN").unwrap())
+            .add_datetime_field(FieldName::try_from("SEEN").unwrap())
+            .add_double_field(FieldName::try_from("WEIGHT").unwrap())
+            .build_with_dest(Cursor::new(Vec::<u8>::new()))
+            .write(&records)
+            .unwrap();
+        cursor.set_position(0);
+
+        let mut lines = Vec::<u8>::new();
+        let num_records = Reader::new(cursor).unwrap().to_jsonl(&mut lines).unwrap();
+        assert_eq!(num_records, 2);
+        assert_eq!(
+            String::from_utf8(lines).unwrap(),
+            "{\"NAME\":\"Ferris\",\"COUNT\":3,\"SWIMS\":true,\"BORN\":\"2015-05-15\",\
+             \"SEEN\":\"2020-02-01T13:04:05\",\"WEIGHT\":0.25}\n\
+             {\"NAME\":null,\"COUNT\":-1,\"SWIMS\":null,\"BORN\":null,\
+             \"SEEN\":\"2020-02-01T13:04:05\",\"WEIGHT\":0.25}\n"
+        );
+    }
+}
@@ -0,0 +1,343 @@
+//! Module with the definition of the reader meant for sources where
+//! each seek + read is expensive (HTTP range requests, FUSE mounts, ...)
+//!
+//! The source is read by fixed size blocks, the last blocks read are kept in memory
+//! so that the header and neighbouring records do not cause more reads.
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+
+use crate::error::{Error, ErrorKind, FieldIOError};
+use crate::header::Header;
+use crate::reading::{FieldIterator, ReadableRecord, Reader, ReadingOptions, Record};
+use crate::record::FieldInfo;
+
+/// Default size of the blocks read from the source
+pub const DEFAULT_BLOCK_SIZE: usize = 64 * 1024;
+
+/// Default number of blocks kept in memory
+pub const DEFAULT_NUM_CACHED_BLOCKS: usize = 16;
+
+/// Source that reads the wrapped source by blocks and keeps the last blocks read
+///
+/// Reads and seeks are served from the blocks in memory, the wrapped source
+/// is only read (with one seek and one read of a block) when the position is in
+/// a block that is not in memory. The least recently used block is then dropped.
+#[derive(Debug)]
+pub struct BlockCache<S: Read + Seek> {
+    inner: S,
+    block_size: usize,
+    num_cached_blocks: usize,
+    /// The index and content of the blocks in memory, the most recently used last
+    blocks: VecDeque<(u64, Vec<u8>)>,
+    position: u64,
+    num_fetches: usize,
+}
+
+impl<S: Read + Seek> BlockCache<S> {
+    /// Wraps the source, with the default block size and number of blocks
+    pub fn new(inner: S) -> Self {
+        Self::with_capacity(inner, DEFAULT_BLOCK_SIZE, DEFAULT_NUM_CACHED_BLOCKS)
+    }
+
+    /// Wraps the source, reading it by blocks of `block_size` bytes
+    /// and keeping at most `num_cached_blocks` of them in memory (at least one is kept)
+    pub fn with_capacity(inner: S, block_size: usize, num_cached_blocks: usize) -> Self {
+        Self {
+            inner,
+            block_size: block_size.max(1),
+            num_cached_blocks: num_cached_blocks.max(1),
+            blocks: VecDeque::with_capacity(num_cached_blocks.max(1)),
+            position: 0,
+            num_fetches: 0,
+        }
+    }
+
+    /// Returns the number of blocks that were read from the wrapped source
+    pub fn num_fetches(&self) -> usize {
+        self.num_fetches
+    }
+
+    /// Returns the wrapped source
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+
+    /// Returns the block with the given index, reading it if it is not in memory
+    fn block(&mut self, block_index: u64) -> std::io::Result<&[u8]> {
+        match self
+            .blocks
+            .iter()
+            .position(|(index, _)| *index == block_index)
+        {
+            Some(position) => {
+                let block = self.blocks.remove(position).unwrap();
+                self.blocks.push_back(block);
+            }
+            None => {
+                self.inner
+                    .seek(SeekFrom::Start(block_index * self.block_size as u64))?;
+                let mut block = Vec::with_capacity(self.block_size);
+                (&mut self.inner)
+                    .take(self.block_size as u64)
+                    .read_to_end(&mut block)?;
+                self.num_fetches += 1;
+                if self.blocks.len() == self.num_cached_blocks {
+                    self.blocks.pop_front();
+                }
+                self.blocks.push_back((block_index, block));
+            }
+        }
+        Ok(&self.blocks.back().unwrap().1)
+    }
+}
+
+impl<S: Read + Seek> Read for BlockCache<S> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        let block_size = self.block_size as u64;
+        let offset = (self.position % block_size) as usize;
+        let block = self.block(self.position / block_size)?;
+        let available = block.get(offset..).unwrap_or_default();
+        let num_read = available.len().min(buf.len());
+        buf[..num_read].copy_from_slice(&available[..num_read]);
+        self.position += num_read as u64;
+        Ok(num_read)
+    }
+}
+
+impl<S: Read + Seek> Seek for BlockCache<S> {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        let position = match pos {
+            SeekFrom::Start(position) => Some(position),
+            SeekFrom::Current(offset) => self.position.checked_add_signed(offset),
+            SeekFrom::End(offset) => self.inner.seek(SeekFrom::End(offset)).map(Some)?,
+        };
+        match position {
+            Some(position) => {
+                self.position = position;
+                Ok(position)
+            }
+            None => Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "invalid seek to a negative position",
+            )),
+        }
+    }
+}
+
+/// Reader of the records at the given indices, for sources where reads are expensive
+///
+/// The header and the fields information are read once, when the reader is created,
+/// then only the bytes of the records (and of the columns) asked for are read,
+/// through a [BlockCache](struct.BlockCache.html).
+///
+/// # Example
+///
+/// ```
+/// # fn main() -> Result<(), dbase::Error> {
+/// let mut reader = dbase::LazyReader::from_path("tests/data/stations.dbf")?;
+/// let record = reader.read_record(5)?;
+/// let names = reader.read_columns(2, &["name"])?;
+/// assert_eq!(names.into_iter().count(), 1);
+/// # Ok(())
+/// # }
+/// ```
+pub struct LazyReader<S: Read + Seek> {
+    reader: Reader<BlockCache<S>>,
+}
+
+impl<S: Read + Seek> LazyReader<S> {
+    /// Creates a new reader from the source, wrapped in
+    /// a [BlockCache](struct.BlockCache.html) with the default capacity.
+    pub fn new(source: S) -> Result<Self, Error> {
+        Self::with_cache(BlockCache::new(source), ReadingOptions::default())
+    }
+
+    /// Creates a new reader that reads from the given cache,
+    /// using the given options.
+    pub fn with_cache(cache: BlockCache<S>, options: ReadingOptions) -> Result<Self, Error> {
+        Ok(Self {
+            reader: Reader::with_options(cache, options)?,
+        })
+    }
+
+    /// Returns the header of the file
+    pub fn header(&self) -> &Header {
+        self.reader.header()
+    }
+
+    /// Returns the information about fields present in the records
+    pub fn fields(&self) -> &[FieldInfo] {
+        self.reader.fields()
+    }
+
+    /// Returns the number of records of the table
+    pub fn num_records(&self) -> usize {
+        self.reader.header.num_records as usize
+    }
+
+    /// Returns the cache through which the table is read
+    pub fn cache(&self) -> &BlockCache<S> {
+        &self.reader.source
+    }
+
+    fn seek_to_record(&mut self, index: usize) -> Result<(), Error> {
+        if index >= self.num_records() {
+            return Err(Error::no_such_record(index, self.num_records()));
+        }
+        self.reader.seek_to_record(index as u32)
+    }
+
+    /// Reads the record at the given index (0-based)
+    pub fn read_record_as<R: ReadableRecord>(&mut self, index: usize) -> Result<R, Error> {
+        self.seek_to_record(index)?;
+        self.reader.read_current_record_as::<R>()
+    }
+
+    /// Reads the [Record](struct.Record.html) at the given index (0-based)
+    pub fn read_record(&mut self, index: usize) -> Result<Record, Error> {
+        self.read_record_as::<Record>(index)
+    }
+
+    /// Reads only the given columns of the record at the given index (0-based),
+    /// the bytes of the other fields are not read.
+    ///
+    /// Fails if one of the columns is not a field of the table.
+    pub fn read_columns(&mut self, index: usize, columns: &[&str]) -> Result<Record, Error> {
+        if let Some(column) = columns
+            .iter()
+            .find(|column| !self.fields().iter().any(|field| field.name() == **column))
+        {
+            return Err(Error {
+                record_num: index,
+                field: None,
+                kind: ErrorKind::Message(format!("the table has no field named '{}'", column)),
+            });
+        }
+        // The bytes after the last of the columns are not read
+        let num_fields = self
+            .fields()
+            .iter()
+            .rposition(|field| columns.contains(&field.name()))
+            .map_or(1, |position| position + 1);
+        self.seek_to_record(index)?;
+        self.reader
+            .read_current_record_start_with(num_fields, |field_iterator| {
+                read_columns(field_iterator, columns)
+            })
+    }
+}
+
+/// Reads the fields whose name is one of the columns, the others are skipped
+fn read_columns<T: Read + Seek>(
+    field_iterator: &mut FieldIterator<T>,
+    columns: &[&str],
+) -> Result<Record, FieldIOError> {
+    field_iterator.read_deletion_flag()?;
+    let mut record = Record::default();
+    while let Some(field_info) = field_iterator.fields_info.peek() {
+        if columns.contains(&field_info.name()) {
+            let field = field_iterator.read_next_field()?;
+            record.insert(field.name.to_owned(), field.value);
+        } else {
+            field_iterator
+                .skip_next_field()
+                .map_err(|error| FieldIOError::new(ErrorKind::IoError(error), None))?;
+        }
+    }
+    Ok(record)
+}
+
+impl LazyReader<File> {
+    /// Creates a new reader from a path, the file and its memo file (if any)
+    /// are read through caches with the default capacity.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        Self::from_path_with_options(path, ReadingOptions::default())
+    }
+
+    /// Creates a new reader from a path, that reads the content
+    /// using the given options.
+    pub fn from_path_with_options<P: AsRef<Path>>(
+        path: P,
+        options: ReadingOptions,
+    ) -> Result<Self, Error> {
+        Ok(Self {
+            reader: Reader::from_path_with_source(path, None, options, BlockCache::new)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::io::Cursor;
+
+    use crate::FieldValue;
+
+    #[test]
+    fn cache_reads_by_blocks() {
+        let content = (0..=255u8).collect::<Vec<u8>>();
+        let mut cache = BlockCache::with_capacity(Cursor::new(content.clone()), 100, 2);
+        let mut bytes = vec![0u8; 150];
+        cache.read_exact(&mut bytes).unwrap();
+        assert_eq!(bytes, content[..150]);
+        assert_eq!(cache.num_fetches(), 2);
+
+        cache.seek(SeekFrom::Start(10)).unwrap();
+        cache.read_exact(&mut bytes[..10]).unwrap();
+        assert_eq!(cache.num_fetches(), 2);
+
+        // The second block is the least recently used one
+        cache.seek(SeekFrom::End(-6)).unwrap();
+        cache.read_exact(&mut bytes[..6]).unwrap();
+        assert_eq!(bytes[..6], content[250..]);
+        cache.seek(SeekFrom::Start(120)).unwrap();
+        cache.read_exact(&mut bytes[..1]).unwrap();
+        assert_eq!(cache.num_fetches(), 4);
+        assert_eq!(cache.read(&mut bytes).unwrap(), 79);
+        cache.seek(SeekFrom::Start(256)).unwrap();
+        assert_eq!(cache.read(&mut bytes).unwrap(), 0);
+    }
+
+    #[test]
+    fn read_records_at_indices() {
+        let content = std::fs::read("tests/data/stations.dbf").unwrap();
+        let expected = Reader::new(Cursor::new(content.clone()))
+            .unwrap()
+            .read()
+            .unwrap();
+        let cache = BlockCache::with_capacity(Cursor::new(content), 512, 4);
+        let mut reader = LazyReader::with_cache(cache, ReadingOptions::default()).unwrap();
+        let num_fetches = reader.cache().num_fetches();
+        assert_eq!(reader.read_record(4).unwrap(), expected[4]);
+        assert_eq!(reader.read_record(1).unwrap(), expected[1]);
+        assert!(reader.cache().num_fetches() > num_fetches);
+        assert!(reader.read_record(6).is_err());
+
+        // The name is the first field, only the start of the record is read
+        let num_fetches = reader.cache().num_fetches();
+        let record = reader.read_columns(3, &["name"]).unwrap();
+        assert_eq!(record.get("name"), expected[3].get("name"));
+        assert_eq!(record.get("line"), None);
+        assert_eq!(reader.cache().num_fetches(), num_fetches + 1);
+        assert!(matches!(
+            reader.read_columns(3, &["nothing"]),
+            Err(Error {
+                kind: ErrorKind::Message(_),
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn read_record_from_path() {
+        let mut reader = LazyReader::from_path("tests/data/line.dbf").unwrap();
+        let record = reader.read_record(0).unwrap();
+        assert!(matches!(record.get("name"), Some(FieldValue::Character(_))));
+    }
+}
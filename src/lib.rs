@@ -137,8 +137,9 @@
 //! let mut reader = dbase::Reader::from_path("tests/data/stations.dbf")?;
 //! let mut stations = reader.read()?;
 //!
+//! # let path = std::env::temp_dir().join("stations.dbf");
 //! let mut writer = dbase::TableWriterBuilder::from_reader(reader)
-//!     .build_with_file_dest("stations.dbf").unwrap();
+//!     .build_with_file_dest(&path).unwrap();
 //!
 //! stations[0].get_mut("line").and_then(|_old| Some("Red".to_string()));
 //! writer.write(&stations)?;
@@ -216,33 +217,303 @@
 //! # #[cfg(not(feature = "serde"))]
 //! # fn main() {}
 //! ```
+//!
+//! # Platform support
+//!
+//! The readers and writers of the crate are built on `std::io::Read`, `Write` and `Seek`,
+//! which the default `std` feature enables. Tables do not need a file system though,
+//! they can be read from (and written to) memory:
+//!
+//! ```
+//! # fn main() -> Result<(), dbase::Error> {
+//! let bytes = std::fs::read("tests/data/line.dbf").unwrap();
+//! let mut reader = dbase::Reader::new(std::io::Cursor::new(bytes))?;
+//! assert_eq!(reader.read()?.len(), 1);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Without the `std` feature, the crate builds on `no_std` targets that have an allocator,
+//! for example to read the attributes of shapefiles received by an embedded device.
+//! It then has the [RawReader](struct.RawReader.html), which reads the fields and
+//! the records of tables from a [ByteSource](trait.ByteSource.html) (eg: a byte slice),
+//! with the [ReadingOptions](struct.ReadingOptions.html) and the parsers of the
+//! [Reader](struct.Reader.html): its values are [FieldValue](enum.FieldValue.html)s.
+//! Memo files are not read, their fields follow the
+//! [MissingMemoPolicy](enum.MissingMemoPolicy.html) of the options.
 
 #![deny(unstable_features)]
+#![cfg_attr(not(feature = "std"), no_std)]
 
+extern crate alloc;
+
+#[cfg(feature = "avro")]
+extern crate apache_avro;
+#[cfg(feature = "arrow")]
+extern crate arrow_array;
+#[cfg(feature = "arrow")]
+extern crate arrow_schema;
+#[cfg(feature = "std")]
 extern crate byteorder;
+#[cfg(feature = "std")]
 extern crate chrono;
+#[cfg(feature = "csv")]
+extern crate csv;
+#[cfg(feature = "datafusion")]
+extern crate datafusion;
+#[cfg(any(feature = "datafusion", feature = "tokio"))]
+extern crate futures;
+#[cfg(feature = "geozero")]
+extern crate geozero;
+#[cfg(feature = "object_store")]
+extern crate object_store;
+#[cfg(feature = "parquet")]
+extern crate parquet;
+#[cfg(feature = "polars")]
+extern crate polars;
+#[cfg(feature = "rusqlite")]
+extern crate rusqlite;
+#[cfg(feature = "xlsx")]
+extern crate rust_xlsxwriter;
 #[cfg(feature = "serde")]
 extern crate serde;
+#[cfg(feature = "tokio")]
+extern crate tokio;
+#[cfg(feature = "zip")]
+extern crate zip;
 
+#[cfg(feature = "zip")]
+mod archive;
+#[cfg(feature = "tokio")]
+mod async_reader;
+#[cfg(feature = "tokio")]
+mod async_writer;
+#[cfg(feature = "avro")]
+mod avro_export;
+#[cfg(feature = "arrow")]
+mod batch;
+#[cfg(feature = "arrow")]
+mod batch_writer;
+#[cfg(feature = "csv")]
+mod csv_export;
+#[cfg(feature = "polars")]
+mod dataframe;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "geozero")]
+mod geozero_properties;
+#[cfg(feature = "object_store")]
+mod object_store_reader;
+#[cfg(feature = "parquet")]
+mod parquet_export;
 #[cfg(feature = "serde")]
 mod de;
+#[cfg(feature = "rusqlite")]
+mod sqlite;
 #[cfg(feature = "serde")]
 mod ser;
+#[cfg(feature = "datafusion")]
+mod table_provider;
+#[cfg(feature = "xlsx")]
+mod xlsx_export;
+
+/// Declares the items that need the standard library
+macro_rules! std_items {
+    ($($item:item)*) => {
+        $(
+            #[cfg(feature = "std")]
+            $item
+        )*
+    };
+}
 
+mod encoding;
+mod encryption;
 mod error;
 mod header;
-mod reading;
+mod options;
+mod raw;
 mod record;
-mod writing;
 
-pub use crate::error::{Error, ErrorKind, FieldIOError};
-pub use crate::reading::{read, FieldIterator, NamedValue, ReadableRecord, Reader, Record, RecordIterator};
-pub use crate::record::field::{Date, DateTime, FieldValue, Time};
-pub use crate::record::{FieldConversionError, FieldInfo, FieldName};
-pub use crate::writing::{FieldWriter, TableWriter, TableWriterBuilder, WritableRecord};
+std_items! {
+    mod background;
+    mod backup;
+    mod column;
+    mod conversion;
+    mod database;
+    mod dataset;
+    mod dedup;
+    mod display;
+    mod dump;
+    mod expression;
+    mod filter;
+    mod fingerprint;
+    mod follow;
+    mod frequency;
+    mod index;
+    mod join;
+    mod json_lines;
+    mod lazy_reader;
+    mod memo_compaction;
+    mod merge;
+    mod ogr;
+    mod parallel_writing;
+    mod partition;
+    mod production_index;
+    mod reading;
+    mod record_diff;
+    mod record_ref;
+    mod repair;
+    mod sampling;
+    mod schema;
+    mod schema_diff;
+    mod sort;
+    mod split;
+    mod sql;
+    mod stream;
+    mod summary;
+    mod validation;
+    mod writing;
+}
+
+pub use crate::encoding::{DecodeError, Encoding, Unicode, UnicodeLossy};
+pub use crate::encryption::RecordCipher;
+pub use crate::error::{Error, ErrorKind, FieldIOError, FormatLimit};
+pub use crate::header::{Header, TableFlags, Version};
+pub use crate::options::{
+    BlankPolicy, ErrorPolicy, FieldNameCase, FieldNamePolicy, Flavor, InvalidDatePolicy,
+    MissingMemoPolicy, ReadingOptions, TrimOption, DEFAULT_BUFFER_SIZE,
+};
+#[cfg(feature = "std")]
+pub use crate::raw::IoSource;
+pub use crate::raw::{ByteSource, EndOfSource, RawReader, RawRecord};
+pub use crate::record::field::{Date, DateTime, FieldType, FieldValue, MemoFormat, Time};
+pub use crate::record::{
+    FieldConversionError, FieldInfo, FieldName, FieldNameError, FieldNameIssue,
+};
+
+std_items! {
+    #[cfg(feature = "zip")]
+    pub use crate::archive::dbf_files_in_zip;
+    #[cfg(feature = "tokio")]
+    pub use crate::async_reader::AsyncReader;
+    #[cfg(feature = "tokio")]
+    pub use crate::async_writer::AsyncTableWriter;
+    #[cfg(feature = "avro")]
+    pub use crate::avro_export::avro_schema;
+    #[cfg(feature = "arrow")]
+    pub use crate::batch::{arrow_data_type, arrow_schema, RecordBatches, DEFAULT_BATCH_SIZE};
+    #[cfg(feature = "arrow")]
+    pub use crate::batch_writer::{write_record_batches, RecordBatchWriter};
+    #[cfg(feature = "csv")]
+    pub use crate::csv_export::CsvOptions;
+    #[cfg(feature = "polars")]
+    pub use crate::dataframe::{read_dataframe, write_dataframe};
+    pub use crate::background::{BackgroundReader, DEFAULT_CHANNEL_CAPACITY};
+    pub use crate::backup::{backup, Backup, BACKUP_ATTEMPTS};
+    pub use crate::conversion::{convert, ConversionReport, FieldConversion};
+    pub use crate::database::{Database, DatabaseField, DatabaseTable};
+    pub use crate::dataset::{Dataset, DatasetTable};
+    pub use crate::dedup::{dedup, Keep};
+    pub use crate::display::RecordTable;
+    pub use crate::dump::{dump, dump_from, FieldDump, RecordDump, TableDump, HEX_SNIPPET_LENGTH};
+    pub use crate::expression::Expression;
+    pub use crate::filter::{Filter, FilteredRecords};
+    pub use crate::fingerprint::fingerprint;
+    pub use crate::follow::Follower;
+    pub use crate::frequency::{
+        value_frequencies, FrequencyReport, ValueCount, ValueFrequencies,
+        DEFAULT_FREQUENCY_CAPACITY,
+    };
+    pub use crate::index::{
+        reindex, Index, IndexBuilder, IndexHeader, IndexKey, IndexRange, KeyType, SetLen,
+    };
+    pub use crate::join::{HashJoin, JoinIterator, JoinKind};
+    pub use crate::lazy_reader::{
+        BlockCache, LazyReader, DEFAULT_BLOCK_SIZE, DEFAULT_NUM_CACHED_BLOCKS,
+    };
+    #[cfg(feature = "geozero")]
+    pub use crate::geozero_properties::GeozeroRecord;
+    #[cfg(feature = "object_store")]
+    pub use crate::object_store_reader::{open_index_from_object_store, ObjectStoreReader};
+    pub use crate::memo_compaction::{compact_memo, MemoCompaction};
+    pub use crate::merge::merge;
+    pub use crate::ogr::{
+        OgrField, OgrFieldType, OGR_DEFAULT_INTEGER64_WIDTH, OGR_DEFAULT_INTEGER_WIDTH,
+        OGR_DEFAULT_REAL_PRECISION, OGR_DEFAULT_REAL_WIDTH, OGR_DEFAULT_STRING_WIDTH,
+    };
+    pub use crate::partition::{open_partitions, split_records};
+    pub use crate::production_index::{IndexTag, ProductionIndex, ProductionIndexFormat, TagRange};
+    pub use crate::reading::{
+        read, read_schema, read_schema_from, FieldIterator, NamedValue, ReadableRecord, Reader,
+        ReaderBuilder, Record, RecordBuilder, RecordIterator, RecordMetadata,
+        RecordMetadataIterator, RecordReader, TableInfo,
+    };
+    pub use crate::record::field::{MemoHandle, MemoStream};
+    pub use crate::record::CompactString;
+    pub use crate::record_diff::{diff, RecordChange, RecordDiff, ValueChange};
+    pub use crate::record_ref::RecordRef;
+    pub use crate::repair::{repair, Repair, RepairMode};
+    pub use crate::schema::{
+        FieldAttributes, SchemaField, TableSchema, DEFAULT_CHARACTER_LENGTH, DEFAULT_NUMERIC_LENGTH,
+        DEFAULT_NUM_DECIMALS,
+    };
+    pub use crate::schema_diff::{diff_schemas, FieldChange, SchemaDiff};
+    pub use crate::sort::{sort_records, sort_table, Order};
+    pub use crate::split::{split, SplitBy, SplitTable};
+    pub use crate::sql::{SqlDialect, SqlExport};
+    pub use crate::stream::{Capability, SequentialSource};
+    pub use crate::summary::{summarize, FieldSummary, TableSummary};
+    pub use crate::validation::{validate, ValidationIssue, ValidationReport};
+    #[cfg(feature = "datafusion")]
+    pub use crate::table_provider::DbaseTableProvider;
+    pub use crate::writing::{
+        FieldWriter, MissingFieldPolicy, OverflowPolicy, TableWriter, TableWriterBuilder,
+        WritableRecord,
+    };
+    #[cfg(feature = "xlsx")]
+    pub use crate::xlsx_export::XlsxWorkbook;
+}
+
+/// macro to build a [Record](struct.Record.html) from field names and values
+///
+/// The values are converted with `Into<FieldValue>`,
+/// like [RecordBuilder::set](struct.RecordBuilder.html#method.set) does.
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use] extern crate dbase;
+/// # fn main() {
+/// let record = record! {
+///     "NAME" => "Smith",
+///     "AMOUNT" => 12.5,
+///     "CITY" => None::<&str>,
+/// };
+/// assert_eq!(
+///     record.get("NAME"),
+///     Some(&dbase::FieldValue::Character(Some("Smith".to_owned())))
+/// );
+/// assert_eq!(record.get("CITY"), Some(&dbase::FieldValue::Character(None)));
+/// # }
+/// ```
+#[cfg(feature = "std")]
+#[macro_export]
+macro_rules! record {
+    ($($field_name:expr => $value:expr),* $(,)?) => {{
+        let builder = $crate::Record::builder();
+        $(let builder = builder.set($field_name, $value);)*
+        builder.build()
+    }};
+}
 
 /// macro to define a struct that implements the ReadableRecord and WritableRecord
 ///
+/// The struct also implements [TableSchema](trait.TableSchema.html): the fields of the
+/// table are inferred from the types of the struct fields, the `#[dbase(...)]` attribute
+/// sets the `name`, `len`, `decimals` or `field_type` of a field
+/// (see [FieldAttributes](struct.FieldAttributes.html)).
+///
 /// # Examples
 ///
 /// ```
@@ -251,19 +522,26 @@ pub use crate::writing::{FieldWriter, TableWriter, TableWriterBuilder, WritableR
 /// dbase_record!(
 ///     #[derive(Debug)]
 ///     struct UserRecord {
+///         #[dbase(len = 30)]
 ///         first_name: String,
+///         #[dbase(name = "LASTNAME", len = 30)]
 ///         last_name: String,
+///         #[dbase(len = 3, decimals = 0)]
 ///         age: f64
 ///     }
 /// );
 /// # }
 /// ```
+#[cfg(feature = "std")]
 #[macro_export]
 macro_rules! dbase_record {
     (
         $(#[derive($($derives:meta),*)])?
         struct $name:ident {
-            $( $field_name:ident: $field_type:ty),+
+            $(
+                $(#[dbase($($key:ident = $value:expr),* $(,)?)])?
+                $field_name:ident: $field_type:ty
+            ),+
             $(,)?
         }
     ) => {
@@ -295,5 +573,16 @@ macro_rules! dbase_record {
                 Ok(())
            }
         }
+
+        impl dbase::TableSchema for $name {
+            fn add_fields(builder: dbase::TableWriterBuilder) -> Result<dbase::TableWriterBuilder, dbase::Error> {
+                $(
+                    let builder = dbase::FieldAttributes::new(stringify!($field_name))
+                        $($(.$key($value))*)?
+                        .add_to(builder, <$field_type as dbase::SchemaField>::FIELD_TYPE)?;
+                )+
+                Ok(builder)
+            }
+        }
     };
 }
@@ -0,0 +1,319 @@
+//! Module with the definition of fn's and struct's to compact memo files
+//!
+//! Editing or deleting memo values leaves their blocks in the memo file,
+//! compacting rewrites the memo file with only the blocks live records point to.
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use byteorder::{LittleEndian, WriteBytesExt};
+
+use crate::error::{Error, ErrorKind};
+use crate::reading::Reader;
+use crate::record::field::{read_memo_index, FieldType, MemoReader};
+use crate::record::FieldInfo;
+
+/// What [compact_memo](fn.compact_memo.html) did to the memo file
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct MemoCompaction {
+    /// The number of memo values kept
+    pub num_memos: usize,
+    /// The size in bytes of the memo file before the compaction
+    pub size_before: u64,
+    /// The size in bytes of the memo file after the compaction
+    pub size_after: u64,
+}
+
+impl MemoCompaction {
+    /// Returns the number of bytes the compaction reclaimed
+    pub fn reclaimed(&self) -> u64 {
+        self.size_before.saturating_sub(self.size_after)
+    }
+}
+
+/// A memo field of a record whose block index changes
+struct Patch {
+    /// The offset of the field from the start of the table
+    offset: u64,
+    field_info: FieldInfo,
+    block_index: Option<u32>,
+}
+
+impl Patch {
+    /// Writes the block index the way the field stores it:
+    /// right aligned text, or a 4 bytes integer in Visual FoxPro tables
+    fn write_to<W: Write>(&self, dst: &mut W) -> std::io::Result<()> {
+        let field_length = usize::from(self.field_info.length());
+        if field_length > 4 {
+            let text = match self.block_index {
+                Some(index) => format!("{:>width$}", index, width = field_length),
+                None => " ".repeat(field_length),
+            };
+            dst.write_all(text.as_bytes())
+        } else {
+            dst.write_u32::<LittleEndian>(self.block_index.unwrap_or(0))
+        }
+    }
+}
+
+/// Writes the blocks the live records point to in `dst`
+///
+/// Returns the number of memo values written, the index of the next
+/// available block in `dst` and the patches to apply to the table.
+fn write_compacted_memo(
+    reader: &mut Reader<std::io::BufReader<File>>,
+    memo_reader: &mut MemoReader<std::io::BufReader<File>>,
+    dst: &mut File,
+) -> Result<(usize, u32, Vec<Patch>), Error> {
+    let io_error = |error: std::io::Error| Error::io_error(error, 0);
+    let mut memo_fields = vec![];
+    let mut start = 0;
+    for field_info in &reader.fields_info {
+        if field_info.field_type == FieldType::Memo {
+            memo_fields.push((start, field_info.clone()));
+        }
+        start += usize::from(field_info.length());
+    }
+
+    let mut writer = BufWriter::new(&mut *dst);
+    memo_reader
+        .copy_header_to(&mut writer, 0)
+        .map_err(io_error)?;
+    let mut next_block = memo_reader.first_data_block();
+    let mut new_indices = HashMap::<u32, u32>::new();
+    let mut patches = vec![];
+
+    let mut record = vec![0u8; reader.record_length()];
+    let mut record_offset = reader.record_offset(0);
+    reader
+        .source
+        .seek(SeekFrom::Start(record_offset))
+        .map_err(io_error)?;
+    for record_num in 0..reader.header.num_records as usize {
+        reader
+            .source
+            .read_exact(&mut record)
+            .map_err(|error| Error::io_error(error, record_num))?;
+        let is_deleted = record[0] == b'*';
+        for (start, field_info) in &memo_fields {
+            let error = |kind| Error {
+                record_num,
+                field: Some(field_info.clone()),
+                kind,
+            };
+            let bytes = &record[*start..*start + usize::from(field_info.length())];
+            let old_index =
+                match read_memo_index(&mut &bytes[..], field_info).map_err(error)? {
+                    Some(index) => index,
+                    None => continue,
+                };
+            let new_index = if is_deleted {
+                None
+            } else if !memo_reader.contains_block(old_index).map_err(io_error)? {
+                return Err(error(ErrorKind::Message(format!(
+                    "block {} is not in the memo file",
+                    old_index
+                ))));
+            } else {
+                match new_indices.entry(old_index) {
+                    Entry::Occupied(entry) => Some(*entry.get()),
+                    Entry::Vacant(entry) => {
+                        let num_blocks = memo_reader
+                            .copy_blocks_to(old_index, &mut writer)
+                            .map_err(|error| Error::io_error(error, record_num))?;
+                        let new_index = next_block;
+                        next_block += num_blocks;
+                        Some(*entry.insert(new_index))
+                    }
+                }
+            };
+            if new_index != Some(old_index) {
+                patches.push(Patch {
+                    offset: record_offset + *start as u64,
+                    field_info: field_info.clone(),
+                    block_index: new_index,
+                });
+            }
+        }
+        record_offset += record.len() as u64;
+    }
+    writer.flush().map_err(io_error)?;
+    drop(writer);
+
+    dst.seek(SeekFrom::Start(0)).map_err(io_error)?;
+    memo_reader
+        .copy_header_to(dst, next_block)
+        .map_err(io_error)?;
+    dst.flush().map_err(io_error)?;
+    Ok((new_indices.len(), next_block, patches))
+}
+
+/// Compacts the memo file (.dbt / .fpt) of the dBase file at the given path
+///
+/// The memo file is rewritten with only the blocks the records that are not deleted
+/// point to, and the memo fields of the table are updated with the new block indices.
+/// The memo fields of deleted records are emptied, as their blocks are not kept.
+///
+/// The compacted memo file is written next to the memo file then renamed over it,
+/// so an error while reading the table leaves both files untouched.
+/// The table is patched after the rename: a copy of both files should be made
+/// before compacting when a crash cannot be ruled out.
+///
+/// # Errors
+///
+/// [MissingMemoFile](enum.ErrorKind.html#variant.MissingMemoFile) is returned
+/// when the table has no memo fields. Memo fields of live records pointing
+/// after the end of the memo file are an error, [validate](fn.validate.html)
+/// reports them.
+///
+/// # Example
+///
+/// ```no_run
+/// # fn main() -> Result<(), dbase::Error> {
+/// let compaction = dbase::compact_memo("notes.dbf")?;
+/// println!("{} bytes reclaimed", compaction.reclaimed());
+/// # Ok(())
+/// # }
+/// ```
+pub fn compact_memo<P: AsRef<Path>>(path: P) -> Result<MemoCompaction, Error> {
+    let path = path.as_ref();
+    let io_error = |error: std::io::Error| Error::io_error(error, 0);
+    let mut reader = Reader::from_path(path)?;
+    let (memo_type, mut memo_reader) = match (
+        reader.header.file_type.supported_memo_type(),
+        reader.memo_reader.take(),
+    ) {
+        (Some(memo_type), Some(memo_reader)) => (memo_type, memo_reader),
+        _ => {
+            return Err(Error {
+                record_num: 0,
+                field: None,
+                kind: ErrorKind::MissingMemoFile,
+            })
+        }
+    };
+    let memo_path = path.with_extension(memo_type.extension());
+    let compacted_path = PathBuf::from(format!("{}.tmp", memo_path.display()));
+    let size_before = std::fs::metadata(&memo_path).map_err(io_error)?.len();
+
+    let mut compacted = File::create(&compacted_path).map_err(io_error)?;
+    let written = write_compacted_memo(&mut reader, &mut memo_reader, &mut compacted);
+    drop(compacted);
+    let (num_memos, next_block, patches) = match written {
+        Ok(written) => written,
+        Err(error) => {
+            let _ = std::fs::remove_file(&compacted_path);
+            return Err(error);
+        }
+    };
+    let size_after = u64::from(next_block) * u64::from(memo_reader.block_size());
+    drop(memo_reader);
+    drop(reader);
+    std::fs::rename(&compacted_path, &memo_path).map_err(io_error)?;
+
+    let mut table = OpenOptions::new()
+        .write(true)
+        .open(path)
+        .map_err(io_error)?;
+    for patch in &patches {
+        table
+            .seek(SeekFrom::Start(patch.offset))
+            .map_err(io_error)?;
+        patch.write_to(&mut table).map_err(io_error)?;
+    }
+    table.flush().map_err(io_error)?;
+
+    Ok(MemoCompaction {
+        num_memos,
+        size_before,
+        size_after,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::Header;
+
+    #[test]
+    fn unreferenced_blocks_are_removed() {
+        // dBase III table with a memo field, the first record is deleted,
+        // the second value spans the blocks 2 and 3, the block 4 is not referenced
+        let mut table = vec![0x83, 120, 1, 1, 3, 0, 0, 0, 65, 0, 11, 0];
+        table.resize(Header::SIZE, 0);
+        table.extend_from_slice(b"NOTES\0\0\0\0\0\0M");
+        table.extend_from_slice(&[0, 0, 0, 0, 10, 0]);
+        table.resize(Header::SIZE + FieldInfo::SIZE, 0);
+        table.push(0x0D);
+        table.extend_from_slice(b"*         1          2          5");
+        table.push(0x1A);
+        let long_note = "Corro the unsafe urchin ".repeat(30);
+        let mut memo = vec![6, 0, 0, 0];
+        memo.resize(512, 0);
+        memo.extend_from_slice(b"Deleted\x1A\x1A");
+        memo.resize(1024, 0);
+        memo.extend_from_slice(long_note.as_bytes());
+        memo.extend_from_slice(b"\x1A\x1A");
+        memo.resize(2048, 0);
+        memo.extend_from_slice(b"Edited\x1A\x1A");
+        memo.resize(2560, 0);
+        memo.extend_from_slice(b"Ferris the crab\x1A\x1A");
+        memo.resize(3072, 0);
+
+        let path = std::env::temp_dir().join("dbase_compact_memo.dbf");
+        let memo_path = path.with_extension("dbt");
+        std::fs::write(&path, &table).unwrap();
+        std::fs::write(&memo_path, &memo).unwrap();
+        let compaction = compact_memo(&path).unwrap();
+        let mut reader = Reader::from_path(&path).unwrap();
+        let mut handles = vec![];
+        while let Some(record) = reader.next_record_ref() {
+            handles.push(record.unwrap().memo_handle("NOTES").unwrap().unwrap());
+        }
+        let mut notes = vec![];
+        for handle in &handles {
+            let mut note = String::new();
+            reader
+                .memo_reader(handle)
+                .unwrap()
+                .read_to_string(&mut note)
+                .unwrap();
+            notes.push(note);
+        }
+        let compacted_table = std::fs::read(&path).unwrap();
+        let compacted_memo = std::fs::read(&memo_path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        std::fs::remove_file(&memo_path).unwrap();
+
+        assert_eq!(
+            compaction,
+            MemoCompaction {
+                num_memos: 2,
+                size_before: 3072,
+                size_after: 2048,
+            }
+        );
+        assert_eq!(compaction.reclaimed(), 1024);
+        assert_eq!(compacted_memo.len(), 2048);
+        assert_eq!(&compacted_memo[..4], &[4, 0, 0, 0]);
+        assert_eq!(
+            &compacted_table[65..],
+            format!("*{:10} {:>10} {:>10}\x1A", "", 1, 3).as_bytes()
+        );
+        assert_eq!(
+            notes,
+            vec![String::new(), long_note, "Ferris the crab".to_owned()]
+        );
+    }
+
+    #[test]
+    fn table_without_memo() {
+        assert!(matches!(
+            compact_memo("tests/data/line.dbf").unwrap_err().kind(),
+            ErrorKind::MissingMemoFile
+        ));
+    }
+}
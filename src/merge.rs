@@ -0,0 +1,165 @@
+//! Module with the definition of the fn merging tables into one
+use std::collections::HashSet;
+use std::path::Path;
+
+use crate::error::{Error, ErrorKind};
+use crate::reading::{Reader, Record, RecordValues};
+use crate::schema_diff::diff_schemas;
+use crate::writing::TableWriterBuilder;
+
+/// Writes the live records of the tables at the given paths, one table after the other,
+/// to a new table at `dst` that has the fields of the first table
+///
+/// The records of the other tables must be writable in the first table (see
+/// [SchemaDiff::is_compatible](struct.SchemaDiff.html#method.is_compatible)): they must have
+/// all the fields of the first table with the same types, values longer than the fields
+/// of the first table are cropped. As memo files cannot be written, tables with memo fields
+/// cannot be merged.
+///
+/// When `key_fields` is not empty, only the first record of those that have the same
+/// values for the key fields is written.
+///
+/// The tables are read twice, once to count the records to write, which
+/// is needed to write the header, then to write them.
+/// Returns the number of records written.
+///
+/// # Example
+///
+/// ```
+/// # fn main() -> Result<(), dbase::Error> {
+/// let merged_path = std::env::temp_dir().join("dbase_merge_doc.dbf");
+/// let tables = ["tests/data/stations.dbf", "tests/data/stations.dbf"];
+/// assert_eq!(dbase::merge(&tables, &merged_path, &[])?, 12);
+/// assert_eq!(dbase::merge(&tables, &merged_path, &["name"])?, 6);
+/// # Ok(())
+/// # }
+/// ```
+pub fn merge<P: AsRef<Path>, Q: AsRef<Path>>(
+    sources: &[P],
+    dst: Q,
+    key_fields: &[&str],
+) -> Result<usize, Error> {
+    let mut readers = sources
+        .iter()
+        .map(Reader::from_path)
+        .collect::<Result<Vec<_>, _>>()?;
+    let first = match readers.first() {
+        Some(first) => first,
+        None => {
+            return Err(Error {
+                record_num: 0,
+                field: None,
+                kind: ErrorKind::Message("there are no tables to merge".to_owned()),
+            })
+        }
+    };
+    for (source, reader) in sources.iter().zip(&readers) {
+        if !diff_schemas(reader.fields(), first.fields()).is_compatible() {
+            return Err(Error {
+                record_num: 0,
+                field: None,
+                kind: ErrorKind::Message(format!(
+                    "the records of '{}' cannot be written with the fields of '{}'",
+                    source.as_ref().display(),
+                    sources[0].as_ref().display()
+                )),
+            });
+        }
+    }
+    if let Some(name) = key_fields
+        .iter()
+        .find(|name| !first.fields().iter().any(|field| field.name() == **name))
+    {
+        return Err(Error {
+            record_num: 0,
+            field: None,
+            kind: ErrorKind::Message(format!("the table has no field named '{}'", name)),
+        });
+    }
+    let builder = TableWriterBuilder::from_reader_ref(first);
+
+    // Which records are written, for each table
+    let mut keys = HashSet::<Vec<String>>::new();
+    let mut kept = Vec::with_capacity(readers.len());
+    for reader in &mut readers {
+        let key_indices = key_fields
+            .iter()
+            .filter_map(|name| {
+                reader
+                    .fields()
+                    .iter()
+                    .filter(|field| !field.is_deletion_flag())
+                    .position(|field| field.name() == *name)
+            })
+            .collect::<Vec<_>>();
+        let mut kept_records = Vec::with_capacity(reader.record_count());
+        while let Some(record) = reader.read_next_record_as::<RecordValues>() {
+            let record = record?;
+            let is_kept = !record.is_deleted
+                && (key_indices.is_empty()
+                    || keys.insert(
+                        key_indices
+                            .iter()
+                            .map(|i| format!("{:?}", record.values[*i]))
+                            .collect(),
+                    ));
+            kept_records.push(is_kept);
+        }
+        kept.push(kept_records);
+    }
+    let num_records = kept
+        .iter()
+        .map(|kept_records| kept_records.iter().filter(|is_kept| **is_kept).count())
+        .sum();
+
+    for reader in &mut readers {
+        reader.set_record_range(0..reader.record_count())?;
+    }
+    let records = readers
+        .iter_mut()
+        .zip(kept)
+        .flat_map(|(reader, kept_records)| {
+            reader
+                .iter_records_as::<Record>()
+                .zip(kept_records)
+                .filter_map(|(record, is_kept)| if is_kept { Some(record) } else { None })
+        });
+    builder
+        .build_with_file_dest(dst)
+        .map_err(|error| Error::io_error(error, 0))?
+        .write_iter::<Record, _, _>(num_records, records)?;
+    Ok(num_records)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn merge_compatible_tables() {
+        let path = std::env::temp_dir().join("dbase_merge_compatible.dbf");
+        let num_records = merge(
+            &["tests/data/line.dbf", "tests/data/stations.dbf"],
+            &path,
+            &[],
+        )
+        .unwrap();
+        assert_eq!(num_records, 7);
+        let mut reader = Reader::from_path(&path).unwrap();
+        assert_eq!(reader.fields().len(), 2);
+        assert_eq!(reader.read().unwrap().len(), 7);
+    }
+
+    #[test]
+    fn merge_incompatible_tables() {
+        let path = std::env::temp_dir().join("dbase_merge_incompatible.dbf");
+        let result = merge(
+            &["tests/data/stations.dbf", "tests/data/line.dbf"],
+            &path,
+            &[],
+        );
+        assert!(result.is_err());
+        let no_tables: [&str; 0] = [];
+        assert!(merge(&no_tables, &path, &[]).is_err());
+    }
+}
@@ -0,0 +1,641 @@
+//! Module with the definition of the reader of dBase files
+//! stored in an [object store](https://docs.rs/object_store) (S3, GCS, Azure, ...)
+//!
+//! Nothing is downloaded wholesale: the header, the records and the memo blocks
+//! are fetched with ranged requests, only when they are read.
+use std::io::{Cursor, Read, Seek, SeekFrom};
+use std::ops::Range;
+use std::sync::Arc;
+
+use object_store::path::Path;
+use object_store::ObjectStore;
+
+use crate::error::{Error, ErrorKind};
+use crate::header::Header;
+use crate::index::Index;
+use crate::reading::{
+    check_encryption, check_field_names, read_table_info, ErrorPolicy, FieldIterator,
+    ReadableRecord, ReadingOptions, Record,
+};
+use crate::record::field::{read_memo_index, FieldType, MemoFileType, MemoHeader, MemoReader};
+use crate::record::{FieldInfo, FieldNameIssue};
+
+fn object_store_error(error: object_store::Error, record_num: usize) -> Error {
+    Error::io_error(std::io::Error::other(error), record_num)
+}
+
+/// Bytes of an object that were fetched, at their position in the object
+///
+/// Reading bytes that were not fetched behaves as if the end of the object was reached.
+#[derive(Debug, Default)]
+struct FetchedRanges {
+    /// The fetched bytes and their position, they do not overlap
+    ranges: Vec<(u64, Vec<u8>)>,
+    position: u64,
+}
+
+impl FetchedRanges {
+    fn insert(&mut self, start: u64, bytes: Vec<u8>) {
+        self.ranges.push((start, bytes));
+    }
+
+    fn contains(&self, position: u64) -> bool {
+        self.ranges
+            .iter()
+            .any(|(start, bytes)| *start <= position && position < *start + bytes.len() as u64)
+    }
+}
+
+impl Read for FetchedRanges {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let position = self.position;
+        let range = self
+            .ranges
+            .iter()
+            .find(|(start, bytes)| *start <= position && position < *start + bytes.len() as u64);
+        let num_read = match range {
+            Some((start, bytes)) => {
+                let available = &bytes[(position - start) as usize..];
+                let num_read = available.len().min(buf.len());
+                buf[..num_read].copy_from_slice(&available[..num_read]);
+                num_read
+            }
+            None => 0,
+        };
+        self.position += num_read as u64;
+        Ok(num_read)
+    }
+}
+
+impl Seek for FetchedRanges {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        let position = match pos {
+            SeekFrom::Start(position) => Some(position),
+            SeekFrom::Current(offset) => self.position.checked_add_signed(offset),
+            SeekFrom::End(_) => None,
+        };
+        match position {
+            Some(position) => {
+                self.position = position;
+                Ok(position)
+            }
+            None => Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "invalid seek in the fetched ranges",
+            )),
+        }
+    }
+}
+
+/// The memo file of a table
+#[derive(Debug)]
+struct MemoObject {
+    location: Path,
+    memo_type: MemoFileType,
+    size: u64,
+    /// The bytes of the memo header
+    header: Vec<u8>,
+    block_size: u32,
+}
+
+/// Reads dBase files stored in an object store
+///
+/// The header is fetched when the reader is opened, then each read fetches
+/// only the bytes of the records asked for and of the memo blocks they point to.
+/// As the records can be read in any order, the reader does not need to be mutable.
+///
+/// # Example
+///
+/// ```
+/// # extern crate dbase;
+/// # extern crate object_store;
+/// # extern crate tokio;
+/// use object_store::memory::InMemory;
+/// use object_store::path::Path;
+/// use object_store::{ObjectStore, PutPayload};
+/// use std::sync::Arc;
+///
+/// # fn main() -> Result<(), dbase::Error> {
+/// # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+/// let store = Arc::new(InMemory::new());
+/// let location = Path::from("tables/stations.dbf");
+/// # let content = std::fs::read("tests/data/stations.dbf").unwrap();
+/// # store.put(&location, PutPayload::from(content)).await.unwrap();
+/// let reader = dbase::ObjectStoreReader::open(store, location).await?;
+/// let record = reader.read_record(3).await?;
+/// let records = reader.read_records(1..4).await?;
+/// assert_eq!(records.len(), 3);
+/// assert_eq!(records[2], record);
+/// # Ok(())
+/// # })
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct ObjectStoreReader {
+    store: Arc<dyn ObjectStore>,
+    location: Path,
+    memo: Option<MemoObject>,
+    header: Header,
+    fields_info: Vec<FieldInfo>,
+    backlink: Option<String>,
+    options: ReadingOptions,
+    field_name_issues: Vec<FieldNameIssue>,
+    /// Number of bytes read for each record, like the Reader reads them
+    record_size: usize,
+}
+
+impl ObjectStoreReader {
+    /// Opens the table at the given location, its memo file (if any) is
+    /// next to it with the extension of the dBase version (.dbt / .fpt)
+    pub async fn open(store: Arc<dyn ObjectStore>, location: Path) -> Result<Self, Error> {
+        Self::open_with_options(store, location, ReadingOptions::default()).await
+    }
+
+    /// Opens the table at the given location, that reads the content
+    /// using the given options.
+    pub async fn open_with_options(
+        store: Arc<dyn ObjectStore>,
+        location: Path,
+        options: ReadingOptions,
+    ) -> Result<Self, Error> {
+        let mut table_info = store
+            .get_range(&location, 0..Header::SIZE as u64)
+            .await
+            .map_err(|error| object_store_error(error, 0))?
+            .to_vec();
+        let header = Header::read_from(&mut table_info.as_slice())
+            .map_err(|error| Error::io_error(error, 0))?;
+        let offset_to_first_record = u64::from(header.offset_to_first_record);
+        if offset_to_first_record > Header::SIZE as u64 {
+            let descriptors = store
+                .get_range(&location, Header::SIZE as u64..offset_to_first_record)
+                .await
+                .map_err(|error| object_store_error(error, 0))?;
+            table_info.extend_from_slice(&descriptors);
+        }
+        let (header, mut fields_info, backlink) =
+            read_table_info(&mut table_info.as_slice(), options.flavor, &*options.encoding)?;
+        check_encryption(&header)?;
+        let field_name_issues = check_field_names(&mut fields_info, &options)?;
+        let record_size = fields_info
+            .iter()
+            .map(|field_info| usize::from(field_info.field_length))
+            .sum();
+
+        let at_least_one_field_is_memo = fields_info
+            .iter()
+            .any(|f_info| f_info.field_type == FieldType::Memo);
+        let memo = match options.memo_file_type(&header) {
+            Some(memo_type) if at_least_one_field_is_memo => {
+                let extension = match memo_type {
+                    MemoFileType::DbaseMemo | MemoFileType::DbaseMemo4 => "dbt",
+                    MemoFileType::FoxBaseMemo => "fpt",
+                };
+                let memo_location = memo_location(&location, extension)?;
+                let detect = options.memo_format.is_none();
+                match Self::open_memo(&*store, memo_location, memo_type, detect).await {
+                    Ok(memo) => Some(memo),
+                    Err(Error {
+                        kind: ErrorKind::ErrorOpeningMemoFile(error),
+                        ..
+                    }) if options.tolerates_missing_memo(&error) => None,
+                    Err(error) => return Err(error),
+                }
+            }
+            _ => None,
+        };
+
+        Ok(Self {
+            store,
+            location,
+            memo,
+            header,
+            fields_info,
+            backlink,
+            options,
+            field_name_issues,
+            record_size,
+        })
+    }
+
+    async fn open_memo(
+        store: &dyn ObjectStore,
+        location: Path,
+        memo_type: MemoFileType,
+        detect: bool,
+    ) -> Result<MemoObject, Error> {
+        let opening_error = |error: object_store::Error| Error {
+            record_num: 0,
+            field: None,
+            kind: ErrorKind::ErrorOpeningMemoFile(match error {
+                object_store::Error::NotFound { .. } => {
+                    std::io::Error::new(std::io::ErrorKind::NotFound, error)
+                }
+                error => std::io::Error::other(error),
+            }),
+        };
+        let size = store.head(&location).await.map_err(opening_error)?.size;
+        // The header is at most 8 bytes long
+        let header = store
+            .get_range(&location, 0..size.min(8))
+            .await
+            .map_err(opening_error)?
+            .to_vec();
+        let block_size = MemoHeader::read_from(&mut header.as_slice(), memo_type)
+            .map_err(|error| Error::io_error(error, 0))?
+            .block_size();
+        // The layout of .dbt files is told by their header and their first block
+        let memo_type = if detect && memo_type != MemoFileType::FoxBaseMemo {
+            let first_block = 512_u64.div_ceil(u64::from(block_size.max(1))).max(1);
+            let end = (first_block * u64::from(block_size) + 4).min(size);
+            let start_of_file = store
+                .get_range(&location, 0..end)
+                .await
+                .map_err(opening_error)?
+                .to_vec();
+            MemoReader::new(memo_type, Cursor::new(start_of_file))
+                .map_err(|error| Error::io_error(error, 0))?
+                .memo_file_type()
+        } else {
+            memo_type
+        };
+        Ok(MemoObject {
+            location,
+            memo_type,
+            size,
+            header,
+            block_size,
+        })
+    }
+
+    /// Returns the header of the file
+    pub fn header(&self) -> &Header {
+        &self.header
+    }
+
+    /// Returns the path of the database container (.dbc) the table belongs to,
+    /// relative to the table.
+    ///
+    /// Only Visual FoxPro tables can belong to a database.
+    pub fn backlink(&self) -> Option<&str> {
+        self.backlink.as_deref()
+    }
+
+    /// Returns the problems with the names of the fields, see
+    /// [Reader::field_name_issues](struct.Reader.html#method.field_name_issues)
+    pub fn field_name_issues(&self) -> &[FieldNameIssue] {
+        &self.field_name_issues
+    }
+
+    /// Returns the information about fields present in the records
+    pub fn fields(&self) -> &[FieldInfo] {
+        &self.fields_info
+    }
+
+    /// Returns the number of records of the table
+    pub fn num_records(&self) -> usize {
+        self.header.num_records as usize
+    }
+
+    fn check_record_index(&self, index: usize) -> Result<(), Error> {
+        if index < self.num_records() {
+            Ok(())
+        } else {
+            Err(Error::no_such_record(index, self.num_records()))
+        }
+    }
+
+    /// Position of the record in the file
+    ///
+    /// Like the [Reader](struct.Reader.html), the records are the size of the fields,
+    /// whatever the size of the records in the header.
+    fn record_position(&self, index: usize) -> u64 {
+        u64::from(self.header.offset_to_first_record) + index as u64 * self.record_size as u64
+    }
+
+    /// Fetches the memo blocks the memo fields of the record point to
+    async fn fetch_memo_blocks(
+        &self,
+        memo: &MemoObject,
+        record: &[u8],
+        record_num: usize,
+    ) -> Result<FetchedRanges, Error> {
+        let mut blocks = FetchedRanges::default();
+        blocks.insert(0, memo.header.clone());
+
+        let mut position = 0usize;
+        for field_info in &self.fields_info {
+            let field_end = position + usize::from(field_info.field_length);
+            let field = record.get(position..field_end).unwrap_or_default();
+            position = field_end;
+            if field_info.field_type != FieldType::Memo {
+                continue;
+            }
+            // Invalid values are reported when the record is read
+            let block_index = match read_memo_index(&mut &*field, field_info) {
+                Ok(Some(block_index)) => block_index,
+                _ => continue,
+            };
+            let start = u64::from(block_index) * u64::from(memo.block_size);
+            if start >= memo.size || blocks.contains(start) {
+                continue;
+            }
+            let mut block = self
+                .store
+                .get_range(
+                    &memo.location,
+                    start..(start + u64::from(memo.block_size)).min(memo.size),
+                )
+                .await
+                .map_err(|error| object_store_error(error, record_num))?
+                .to_vec();
+
+            // Data that is longer than a block starts with its length
+            let data_size = match (memo.memo_type, block.get(4..8)) {
+                (MemoFileType::FoxBaseMemo, Some(length)) => {
+                    8 + u64::from(u32::from_be_bytes([
+                        length[0], length[1], length[2], length[3],
+                    ]))
+                }
+                (MemoFileType::DbaseMemo4, Some(length)) => {
+                    8 + u64::from(u32::from_le_bytes([
+                        length[0], length[1], length[2], length[3],
+                    ]))
+                }
+                _ => 0,
+            };
+            let end = (start + data_size).min(memo.size);
+            if end > start + block.len() as u64 {
+                let rest = self
+                    .store
+                    .get_range(&memo.location, start + block.len() as u64..end)
+                    .await
+                    .map_err(|error| object_store_error(error, record_num))?;
+                block.extend_from_slice(&rest);
+            }
+            blocks.insert(start, block);
+        }
+        Ok(blocks)
+    }
+
+    /// Reads the record from its bytes
+    async fn decode_record<R: ReadableRecord>(
+        &self,
+        record: Vec<u8>,
+        record_num: usize,
+    ) -> Result<R, Error> {
+        let mut memo_reader = match &self.memo {
+            Some(memo) => {
+                let blocks = self.fetch_memo_blocks(memo, &record, record_num).await?;
+                Some(
+                    MemoReader::of_type(memo.memo_type, blocks)
+                        .map_err(|error| Error::io_error(error, record_num))?,
+                )
+            }
+            None => None,
+        };
+        let mut source = Cursor::new(&record[..]);
+        let mut iter = FieldIterator {
+            source: &mut source,
+            fields_info: self.fields_info.iter().peekable(),
+            memo_reader: &mut memo_reader,
+            options: &self.options,
+        };
+        R::read_using(&mut iter).map_err(|error| Error::new(error, record_num))
+    }
+
+    /// Reads the record at the given index (0-based)
+    pub async fn read_record_as<R: ReadableRecord>(&self, index: usize) -> Result<R, Error> {
+        self.check_record_index(index)?;
+        let start = self.record_position(index);
+        let record = self
+            .store
+            .get_range(&self.location, start..start + self.record_size as u64)
+            .await
+            .map_err(|error| object_store_error(error, index))?;
+        self.decode_record(record.to_vec(), index).await
+    }
+
+    /// Reads the [Record](struct.Record.html) at the given index (0-based)
+    pub async fn read_record(&self, index: usize) -> Result<Record, Error> {
+        self.read_record_as::<Record>(index).await
+    }
+
+    /// Reads the records in the given range of indices, following the error policy
+    /// of the reader.
+    ///
+    /// The records are fetched with a single request.
+    pub async fn read_records_as<R: ReadableRecord>(
+        &self,
+        indices: Range<usize>,
+    ) -> Result<Vec<R>, Error> {
+        if indices.is_empty() {
+            return Ok(vec![]);
+        }
+        self.check_record_index(indices.end - 1)?;
+        let start = self.record_position(indices.start);
+        let end = self.record_position(indices.end - 1) + self.record_size as u64;
+        let content = self
+            .store
+            .get_range(&self.location, start..end)
+            .await
+            .map_err(|error| object_store_error(error, indices.start))?;
+
+        let mut records = Vec::<R>::with_capacity(indices.len());
+        for index in indices {
+            let record_start = (self.record_position(index) - start) as usize;
+            let record = content
+                .get(record_start..record_start + self.record_size)
+                .unwrap_or_default()
+                .to_vec();
+            match self.decode_record::<R>(record, index).await {
+                Ok(record) => records.push(record),
+                Err(error)
+                    if self.options.error_policy == ErrorPolicy::SkipRecord
+                        && !matches!(error.kind, ErrorKind::IoError(_)) => {}
+                Err(error) => return Err(error),
+            }
+        }
+        Ok(records)
+    }
+
+    /// Reads the [Records](struct.Record.html) in the given range of indices
+    pub async fn read_records(&self, indices: Range<usize>) -> Result<Vec<Record>, Error> {
+        self.read_records_as::<Record>(indices).await
+    }
+
+    /// Reads the records at the given indices (0-based), in the order of the indices,
+    /// for example the ones returned by [Index::record_indices](struct.Index.html#method.record_indices)
+    pub async fn read_records_at_as<R: ReadableRecord>(
+        &self,
+        indices: &[usize],
+    ) -> Result<Vec<R>, Error> {
+        let mut records = Vec::<R>::with_capacity(indices.len());
+        for index in indices {
+            records.push(self.read_record_as::<R>(*index).await?);
+        }
+        Ok(records)
+    }
+}
+
+/// Returns the location with its extension replaced
+fn memo_location(location: &Path, extension: &str) -> Result<Path, Error> {
+    let location = location.as_ref();
+    let file_start = location.rfind('/').map_or(0, |position| position + 1);
+    let stem = match location[file_start..].rfind('.') {
+        Some(position) => &location[..file_start + position],
+        None => location,
+    };
+    Path::parse(format!("{}.{}", stem, extension)).map_err(|error| Error {
+        record_num: 0,
+        field: None,
+        kind: ErrorKind::Message(error.to_string()),
+    })
+}
+
+/// Opens the .ndx index at the given location of the object store,
+/// the index is read entirely in memory.
+///
+/// The indices of the records can then be given to
+/// [ObjectStoreReader::read_records_at_as](struct.ObjectStoreReader.html#method.read_records_at_as).
+pub async fn open_index_from_object_store(
+    store: &dyn ObjectStore,
+    location: &Path,
+) -> Result<Index<Cursor<Vec<u8>>>, Error> {
+    let content = store
+        .get(location)
+        .await
+        .map_err(|error| object_store_error(error, 0))?
+        .bytes()
+        .await
+        .map_err(|error| object_store_error(error, 0))?;
+    Index::new(Cursor::new(content.to_vec()))
+}
+
+#[cfg(test)]
+mod test {
+    extern crate tokio;
+
+    use super::*;
+
+    use object_store::memory::InMemory;
+    use object_store::PutPayload;
+
+    use crate::{FieldValue, Reader};
+
+    fn block_on<F: std::future::Future>(future: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap()
+            .block_on(future)
+    }
+
+    fn store_with(files: Vec<(&str, Vec<u8>)>) -> Arc<dyn ObjectStore> {
+        let store = Arc::new(InMemory::new());
+        block_on(async {
+            for (location, content) in files {
+                store
+                    .put(&Path::from(location), PutPayload::from(content))
+                    .await
+                    .unwrap();
+            }
+        });
+        store
+    }
+
+    #[test]
+    fn memo_location_next_to_table() {
+        assert_eq!(
+            memo_location(&Path::from("data/v1.2/table.dbf"), "fpt")
+                .unwrap()
+                .as_ref(),
+            "data/v1.2/table.fpt"
+        );
+        assert_eq!(
+            memo_location(&Path::from("data.d/table"), "dbt")
+                .unwrap()
+                .as_ref(),
+            "data.d/table.dbt"
+        );
+    }
+
+    #[test]
+    fn read_records_in_any_order() {
+        let content = std::fs::read("tests/data/stations.dbf").unwrap();
+        let expected = Reader::new(Cursor::new(content.clone()))
+            .unwrap()
+            .read()
+            .unwrap();
+        let store = store_with(vec![("stations.dbf", content)]);
+
+        block_on(async {
+            let reader = ObjectStoreReader::open(store, Path::from("stations.dbf"))
+                .await
+                .unwrap();
+            assert_eq!(reader.num_records(), 6);
+            assert_eq!(reader.read_record(5).await.unwrap(), expected[5]);
+            assert_eq!(reader.read_record(0).await.unwrap(), expected[0]);
+            assert_eq!(reader.read_records(2..5).await.unwrap(), expected[2..5]);
+            let records = reader.read_records_at_as::<Record>(&[4, 1]).await.unwrap();
+            assert_eq!(records[0], expected[4]);
+            assert_eq!(records[1], expected[1]);
+            assert!(reader.read_record(6).await.is_err());
+        });
+    }
+
+    #[test]
+    fn read_memo_blocks() {
+        // dBase III table with a memo field, the values are in the blocks 1 and 2 of the memo
+        let mut table = vec![0x83, 120, 1, 1, 2, 0, 0, 0, 65, 0, 11, 0];
+        table.resize(Header::SIZE, 0);
+        table.extend_from_slice(b"NOTES\0\0\0\0\0\0M");
+        table.extend_from_slice(&[0, 0, 0, 0, 10, 0]);
+        table.resize(Header::SIZE + FieldInfo::SIZE, 0);
+        table.push(0x0D);
+        table.extend_from_slice(b"          1          2");
+        let mut memo = vec![3, 0, 0, 0];
+        memo.resize(512, 0);
+        memo.extend_from_slice(b"Ferris the crab\x1A\x1A");
+        memo.resize(1024, 0);
+        memo.extend_from_slice(b"Corro the unsafe urchin\x1A\x1A");
+        memo.resize(1536, 0);
+        let store = store_with(vec![("notes.dbf", table), ("notes.dbt", memo)]);
+
+        let records = block_on(async {
+            let reader = ObjectStoreReader::open(store, Path::from("notes.dbf"))
+                .await
+                .unwrap();
+            let last = reader.read_record(1).await.unwrap();
+            let mut records = reader.read_records(0..1).await.unwrap();
+            records.push(last);
+            records
+        });
+        assert_eq!(
+            records[0].get("NOTES"),
+            Some(&FieldValue::Memo("Ferris the crab".to_owned()))
+        );
+        assert_eq!(
+            records[1].get("NOTES"),
+            Some(&FieldValue::Memo("Corro the unsafe urchin".to_owned()))
+        );
+    }
+
+    #[test]
+    fn missing_memo_file() {
+        let mut table = vec![0x83, 120, 1, 1, 0, 0, 0, 0, 65, 0, 11, 0];
+        table.resize(Header::SIZE, 0);
+        table.extend_from_slice(b"NOTES\0\0\0\0\0\0M");
+        table.extend_from_slice(&[0, 0, 0, 0, 10, 0]);
+        table.resize(Header::SIZE + FieldInfo::SIZE, 0);
+        table.push(0x0D);
+        let store = store_with(vec![("notes.dbf", table)]);
+        let result = block_on(ObjectStoreReader::open(store, Path::from("notes.dbf")));
+        assert!(matches!(
+            result,
+            Err(Error {
+                kind: ErrorKind::ErrorOpeningMemoFile(_),
+                ..
+            })
+        ));
+    }
+}
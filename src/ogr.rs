@@ -0,0 +1,302 @@
+//! Module with the definition of the mapping between dBase fields and the
+//! fields of [OGR](https://gdal.org/en/stable/development/ogr/ogr_arch.html)
+//!
+//! The mapping is the one of the shapefile driver of GDAL, so that a table
+//! described and written here is read with the same types, widths and
+//! precisions by GDAL based tools, and the other way around.
+use std::convert::TryFrom;
+
+use crate::error::{Error, ErrorKind};
+use crate::record::field::FieldType;
+use crate::record::{FieldInfo, FieldName};
+use crate::writing::TableWriterBuilder;
+
+/// Width of the Character fields made from String fields without a width
+pub const OGR_DEFAULT_STRING_WIDTH: u8 = 80;
+/// Width of the Numeric fields made from Integer fields without a width
+pub const OGR_DEFAULT_INTEGER_WIDTH: u8 = 9;
+/// Width of the Numeric fields made from Integer64 fields without a width
+pub const OGR_DEFAULT_INTEGER64_WIDTH: u8 = 18;
+/// Width of the Numeric fields made from Real fields without a width
+pub const OGR_DEFAULT_REAL_WIDTH: u8 = 24;
+/// Precision of the Numeric fields made from Real fields without a width
+pub const OGR_DEFAULT_REAL_PRECISION: u8 = 15;
+
+/// The types of OGR fields the shapefile driver uses
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum OgrFieldType {
+    /// `OFTString`
+    String,
+    /// `OFTInteger`
+    Integer,
+    /// `OFTInteger64`
+    Integer64,
+    /// `OFTReal`
+    Real,
+    /// `OFTDate`
+    Date,
+    /// `OFTDateTime`
+    DateTime,
+}
+
+impl OgrFieldType {
+    /// Returns the name of the type in the OGR API, eg: `"OFTString"`
+    pub fn name(&self) -> &'static str {
+        match self {
+            OgrFieldType::String => "OFTString",
+            OgrFieldType::Integer => "OFTInteger",
+            OgrFieldType::Integer64 => "OFTInteger64",
+            OgrFieldType::Real => "OFTReal",
+            OgrFieldType::Date => "OFTDate",
+            OgrFieldType::DateTime => "OFTDateTime",
+        }
+    }
+}
+
+/// The definition of an OGR field: name, type, width and precision
+///
+/// A width of 0 means the width is not set, and the default of
+/// the type is used when the field is added to a table.
+///
+/// # Example
+///
+/// ```
+/// # fn main() -> Result<(), dbase::Error> {
+/// let reader = dbase::Reader::from_path("tests/data/stations.dbf")?;
+/// for field_info in reader.fields() {
+///     let ogr_field = dbase::OgrField::from(field_info);
+///     println!("{}: {}", ogr_field.name, ogr_field.field_type.name());
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OgrField {
+    /// The name of the field
+    pub name: String,
+    /// The type of the field
+    pub field_type: OgrFieldType,
+    /// The width of the values, 0 if not set
+    pub width: u16,
+    /// The number of decimals of Real values
+    pub precision: u8,
+    /// Whether the integers are booleans (the `OFSTBoolean` subtype)
+    pub is_boolean: bool,
+}
+
+impl OgrField {
+    /// Creates the definition of a field without width nor precision
+    pub fn new(name: &str, field_type: OgrFieldType) -> Self {
+        Self {
+            name: name.to_owned(),
+            field_type,
+            width: 0,
+            precision: 0,
+            is_boolean: false,
+        }
+    }
+
+    /// Sets the width of the values
+    pub fn width(mut self, width: u16) -> Self {
+        self.width = width;
+        self
+    }
+
+    /// Sets the number of decimals of Real values
+    pub fn precision(mut self, precision: u8) -> Self {
+        self.precision = precision;
+        self
+    }
+
+    /// Makes an Integer field store booleans, which are Logical fields in dBase
+    pub fn boolean(mut self) -> Self {
+        self.is_boolean = true;
+        self
+    }
+
+    /// Returns the dBase type, length and number of decimals of the field
+    /// the shapefile driver of GDAL creates for this definition
+    ///
+    /// - String: Character field of the width, 80 by default, at most 254
+    /// - Integer: Numeric field of the width, 9 by default, without decimals
+    /// - Integer64: Numeric field of the width, 18 by default, without decimals
+    /// - Real: Numeric field of the width and precision, 24 and 15 by default
+    /// - Date: Date field
+    /// - DateTime: Character field of 19 characters, shapefiles have no date times
+    /// - Integer of the boolean subtype: Logical field
+    pub fn dbase_field(&self) -> (FieldType, u8, u8) {
+        let width = |default: u8| match self.width {
+            0 => default,
+            width => u8::try_from(width).unwrap_or(u8::MAX),
+        };
+        match self.field_type {
+            OgrFieldType::String => (
+                FieldType::Character,
+                width(OGR_DEFAULT_STRING_WIDTH).min(254),
+                0,
+            ),
+            OgrFieldType::Integer if self.is_boolean => (FieldType::Logical, 1, 0),
+            OgrFieldType::Integer => (FieldType::Numeric, width(OGR_DEFAULT_INTEGER_WIDTH), 0),
+            OgrFieldType::Integer64 => (FieldType::Numeric, width(OGR_DEFAULT_INTEGER64_WIDTH), 0),
+            OgrFieldType::Real if self.width == 0 => (
+                FieldType::Numeric,
+                OGR_DEFAULT_REAL_WIDTH,
+                OGR_DEFAULT_REAL_PRECISION,
+            ),
+            OgrFieldType::Real => (FieldType::Numeric, width(0), self.precision),
+            OgrFieldType::Date => (FieldType::Date, 8, 0),
+            OgrFieldType::DateTime => (FieldType::Character, 19, 0),
+        }
+    }
+
+    /// Adds the dBase field made from the definition to the builder,
+    /// see [dbase_field](#method.dbase_field)
+    pub fn add_to(&self, builder: TableWriterBuilder) -> Result<TableWriterBuilder, Error> {
+        let name = FieldName::try_from(self.name.as_str()).map_err(|error| Error {
+            record_num: 0,
+            field: None,
+            kind: ErrorKind::Message(error.to_string()),
+        })?;
+        let builder = match self.dbase_field() {
+            (FieldType::Character, length, _) => builder.add_character_field(name, length),
+            (FieldType::Logical, _, _) => builder.add_logical_field(name),
+            (FieldType::Date, _, _) => builder.add_date_field(name),
+            (_, length, num_decimals) => builder.add_numeric_field(name, length, num_decimals),
+        };
+        Ok(builder)
+    }
+}
+
+/// The OGR field GDAL reads from the dBase field
+///
+/// - Character and Memo fields are Strings
+/// - Numeric and Float fields with decimals are Reals, without decimals they are
+///   Integers when shorter than 10 characters, Integer64 when shorter than 19
+///   and Reals otherwise
+/// - Date fields are Dates, Logical fields are Integers of the boolean subtype
+/// - the Visual FoxPro Integer fields are Integers, Double and Currency fields
+///   are Reals and DateTime fields are DateTimes, like the Long (and Autoincrement),
+///   Double and Timestamp fields of dBase 7
+impl From<&FieldInfo> for OgrField {
+    fn from(field_info: &FieldInfo) -> Self {
+        let width = field_info.length();
+        let precision = field_info.num_decimal_places;
+        let (field_type, width, precision) = match field_info.field_type {
+            FieldType::Character | FieldType::Memo => (OgrFieldType::String, width, 0),
+            FieldType::Numeric | FieldType::Float if precision == 0 && width < 10 => {
+                (OgrFieldType::Integer, width, 0)
+            }
+            FieldType::Numeric | FieldType::Float if precision == 0 && width < 19 => {
+                (OgrFieldType::Integer64, width, 0)
+            }
+            FieldType::Numeric | FieldType::Float => (OgrFieldType::Real, width, precision),
+            FieldType::Date => (OgrFieldType::Date, width, 0),
+            FieldType::Logical => {
+                return OgrField::new(field_info.name(), OgrFieldType::Integer)
+                    .width(1)
+                    .boolean()
+            }
+            FieldType::Integer | FieldType::Long | FieldType::Autoincrement => {
+                (OgrFieldType::Integer, 11, 0)
+            }
+            FieldType::Double | FieldType::Currency | FieldType::DBase7Double => {
+                (OgrFieldType::Real, 0, precision)
+            }
+            FieldType::DateTime | FieldType::Timestamp => (OgrFieldType::DateTime, 0, 0),
+        };
+        OgrField::new(field_info.name(), field_type)
+            .width(width)
+            .precision(precision)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn numeric_fields_follow_gdal() {
+        let fields = TableWriterBuilder::new()
+            .add_numeric_field(FieldName::try_from("small").unwrap(), 9, 0)
+            .add_numeric_field(FieldName::try_from("medium").unwrap(), 10, 0)
+            .add_numeric_field(FieldName::try_from("large").unwrap(), 19, 0)
+            .add_float_field(FieldName::try_from("decimal").unwrap(), 8, 2)
+            .add_logical_field(FieldName::try_from("flag").unwrap())
+            .v;
+        let ogr_fields = fields
+            .iter()
+            .map(OgrField::from)
+            .map(|field| (field.field_type, field.width, field.precision))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            ogr_fields,
+            vec![
+                (OgrFieldType::Integer, 9, 0),
+                (OgrFieldType::Integer64, 10, 0),
+                (OgrFieldType::Real, 19, 0),
+                (OgrFieldType::Real, 8, 2),
+                (OgrFieldType::Integer, 1, 0),
+            ]
+        );
+        assert!(OgrField::from(&fields[4]).is_boolean);
+    }
+
+    #[test]
+    fn default_widths() {
+        let dbase_field = |field: OgrField| field.dbase_field();
+        assert_eq!(
+            dbase_field(OgrField::new("name", OgrFieldType::String)),
+            (FieldType::Character, 80, 0)
+        );
+        assert_eq!(
+            dbase_field(OgrField::new("name", OgrFieldType::String).width(300)),
+            (FieldType::Character, 254, 0)
+        );
+        assert_eq!(
+            dbase_field(OgrField::new("count", OgrFieldType::Integer)),
+            (FieldType::Numeric, 9, 0)
+        );
+        assert_eq!(
+            dbase_field(OgrField::new("id", OgrFieldType::Integer64)),
+            (FieldType::Numeric, 18, 0)
+        );
+        assert_eq!(
+            dbase_field(OgrField::new("area", OgrFieldType::Real)),
+            (FieldType::Numeric, 24, 15)
+        );
+        assert_eq!(
+            dbase_field(
+                OgrField::new("area", OgrFieldType::Real)
+                    .width(12)
+                    .precision(3)
+            ),
+            (FieldType::Numeric, 12, 3)
+        );
+        assert_eq!(
+            dbase_field(OgrField::new("seen", OgrFieldType::DateTime)),
+            (FieldType::Character, 19, 0)
+        );
+    }
+
+    #[test]
+    fn fields_round_trip() {
+        let definitions = vec![
+            OgrField::new("name", OgrFieldType::String).width(30),
+            OgrField::new("count", OgrFieldType::Integer).width(9),
+            OgrField::new("id", OgrFieldType::Integer64).width(18),
+            OgrField::new("area", OgrFieldType::Real)
+                .width(24)
+                .precision(15),
+            OgrField::new("day", OgrFieldType::Date).width(8),
+            OgrField::new("open", OgrFieldType::Integer)
+                .width(1)
+                .boolean(),
+        ];
+        let mut builder = TableWriterBuilder::new();
+        for definition in &definitions {
+            builder = definition.add_to(builder).unwrap();
+        }
+        let read_back = builder.v.iter().map(OgrField::from).collect::<Vec<_>>();
+        assert_eq!(read_back, definitions);
+    }
+}
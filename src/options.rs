@@ -0,0 +1,438 @@
+//! Module with the definition of the options of the readers
+//!
+//! They are shared by the [Reader](struct.Reader.html) of the standard library
+//! and the [RawReader](struct.RawReader.html) of `no_std` targets.
+use alloc::borrow::ToOwned;
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::encoding::{Encoding, UnicodeLossy};
+use crate::encryption::RecordCipher;
+use crate::record::field::{FieldType, MemoFormat};
+use crate::record::{field_names_match, FieldInfo};
+
+/// How the padding of character fields is trimmed
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum TrimOption {
+    /// Only the padding at the beginning is removed
+    Begin,
+    /// Only the padding at the end is removed
+    End,
+    /// The padding at both ends is removed
+    BeginEnd,
+}
+
+/// What character fields whose content is only blanks are read as
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum BlankPolicy {
+    /// The value is `FieldValue::Character(None)`
+    #[default]
+    Null,
+    /// The value is an empty string
+    Empty,
+    /// The value is the padding as it is in the file, whatever the trimming
+    Padded,
+}
+
+/// What the reader does of Date fields that are not days of the calendar,
+/// like `00000000`, `19999999` or text
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum InvalidDatePolicy {
+    /// Dates written with digits are kept even when they are not days of the calendar,
+    /// so that [validate](fn.validate.html) can report them, the others are an error
+    #[default]
+    Lenient,
+    /// Dates that are not days of the calendar are an
+    /// [InvalidDate](enum.ErrorKind.html#variant.InvalidDate) error
+    Fail,
+    /// Dates that are not days of the calendar are read as `FieldValue::Date(None)`
+    Null,
+    /// Dates that are not days of the calendar are read as the text of the field,
+    /// in a `FieldValue::Character`
+    Raw,
+}
+
+/// What the reader does when the memo file of a table with memo fields cannot be found
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum MissingMemoPolicy {
+    /// Opening the table is an
+    /// [ErrorOpeningMemoFile](enum.ErrorKind.html#variant.ErrorOpeningMemoFile) error
+    #[default]
+    Fail,
+    /// The table is opened, its memo fields are read as `FieldValue::Memo` with
+    /// an empty string
+    Empty,
+    /// The table is opened, its memo fields are read as `FieldValue::Character(None)`
+    Null,
+}
+
+/// What the reader does of records that cannot be read
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ErrorPolicy {
+    /// The error is returned
+    Fail,
+    /// Records that contain values that cannot be read are skipped,
+    /// I/O errors are still returned.
+    SkipRecord,
+}
+
+/// What the reader does of fields whose names are duplicated
+/// or do not follow the rules of the format
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum FieldNamePolicy {
+    /// The names are kept, the problems are given by
+    /// [field_name_issues](struct.Reader.html#method.field_name_issues)
+    Warn,
+    /// Same as `Warn`, but the duplicated names get a `_2`, `_3`, ... suffix
+    /// so that [Records](struct.Record.html) have a value for each field
+    RenameDuplicates,
+    /// The reader is not created, the first problem is returned as an error
+    Fail,
+}
+
+/// Case the reader gives to the names of the fields
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum FieldNameCase {
+    /// The names are kept as written in the table
+    #[default]
+    Unchanged,
+    /// The names are converted to uppercase, like dBase writes them
+    Upper,
+    /// The names are converted to lowercase
+    Lower,
+}
+
+/// The dialects of the format, whose differences cannot be told from the files
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum Flavor {
+    /// Tables made by dBase, FoxPro and most other applications
+    #[default]
+    Standard,
+    /// Tables made by Clipper (5.x) applications, which differ in that:
+    ///
+    /// - the byte of the decimal places of character fields is the high byte
+    ///   of their length, so that they can be longer than 255 characters
+    ///   (tables written with such fields use the same layout)
+    /// - the terminator of the field descriptors may be missing, or be a NUL byte
+    ///
+    /// Their indexes (.ntx) cannot be read, only dBase III indexes (.ndx) can.
+    Clipper,
+}
+
+/// Options that change how the content of files is read
+///
+/// # Example
+///
+/// ```
+/// # fn main() -> Result<(), dbase::Error> {
+/// let options = dbase::ReadingOptions::new()
+///     .encoding(dbase::Unicode)
+///     .character_trim(Some(dbase::TrimOption::End))
+///     .error_policy(dbase::ErrorPolicy::SkipRecord);
+/// let mut reader = dbase::Reader::from_path_with_options("tests/data/line.dbf", options)?;
+/// let records = reader.read()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ReadingOptions {
+    pub(crate) encoding: Arc<dyn Encoding>,
+    pub(crate) field_encodings: Vec<(String, Arc<dyn Encoding>)>,
+    pub(crate) character_trim: Option<TrimOption>,
+    pub(crate) nul_padding: bool,
+    pub(crate) blank_policy: BlankPolicy,
+    pub(crate) decimal_separator: char,
+    pub(crate) thousands_separator: Option<char>,
+    pub(crate) integer_numerics: bool,
+    pub(crate) invalid_date_policy: InvalidDatePolicy,
+    pub(crate) missing_memo_policy: MissingMemoPolicy,
+    pub(crate) error_policy: ErrorPolicy,
+    pub(crate) field_name_policy: FieldNamePolicy,
+    pub(crate) field_name_case: FieldNameCase,
+    pub(crate) buffer_size: usize,
+    pub(crate) flavor: Flavor,
+    pub(crate) memo_format: Option<MemoFormat>,
+    pub(crate) production_index: bool,
+    pub(crate) memory_budget: Option<usize>,
+    pub(crate) cipher: Option<Arc<dyn RecordCipher>>,
+}
+
+/// Size of the buffers of the files opened by path, the one of `BufReader`
+pub const DEFAULT_BUFFER_SIZE: usize = 8 * 1024;
+
+impl ReadingOptions {
+    /// Creates the default options: text is decoded as UTF-8 (invalid sequences
+    /// are replaced), character fields are trimmed at both ends and errors are returned.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the encoding used to decode Character and Memo fields,
+    /// and the names of the fields
+    pub fn encoding<E: Encoding + 'static>(mut self, encoding: E) -> Self {
+        self.encoding = Arc::new(encoding);
+        self
+    }
+
+    /// Sets the encoding used to decode the field with the given name,
+    /// instead of the encoding of the other fields
+    ///
+    /// Names are compared ignoring the ASCII case, like dBase does.
+    pub fn field_encoding<E: Encoding + 'static>(mut self, name: &str, encoding: E) -> Self {
+        self.field_encodings
+            .retain(|(field_name, _)| !field_names_match(field_name, name));
+        self.field_encodings
+            .push((name.to_owned(), Arc::new(encoding)));
+        self
+    }
+
+    /// Sets the cipher the records of encrypted tables are decrypted with,
+    /// see [RecordCipher](trait.RecordCipher.html)
+    ///
+    /// Without one, encrypted tables are an
+    /// [EncryptedTable](enum.ErrorKind.html#variant.EncryptedTable) error.
+    /// The records of the tables that are not encrypted are not decrypted.
+    pub fn decryption<C: RecordCipher + 'static>(mut self, cipher: C) -> Self {
+        self.cipher = Some(Arc::new(cipher));
+        self
+    }
+
+    /// Returns the encoding used to decode the field
+    pub(crate) fn encoding_of(&self, field_info: &FieldInfo) -> &dyn Encoding {
+        self.field_encodings
+            .iter()
+            .find(|(name, _)| field_names_match(name, field_info.name()))
+            .map_or(&*self.encoding, |(_, encoding)| &**encoding)
+    }
+
+    /// Sets how character fields are trimmed, `None` keeps the padding
+    pub fn character_trim(mut self, trim: Option<TrimOption>) -> Self {
+        self.character_trim = trim;
+        self
+    }
+
+    /// Sets whether NUL bytes are padding of character fields like spaces, `true` by default
+    ///
+    /// Some applications pad the fields with NULs instead of spaces, or with both.
+    /// When `false`, the NULs are kept in the strings.
+    pub fn nul_padding(mut self, is_padding: bool) -> Self {
+        self.nul_padding = is_padding;
+        self
+    }
+
+    /// Returns whether the character is padding of character fields
+    pub(crate) fn is_padding(&self, c: char) -> bool {
+        c.is_whitespace() || (self.nul_padding && c == '\0')
+    }
+
+    /// Sets what character fields that are only blanks are read as,
+    /// [Null](enum.BlankPolicy.html#variant.Null) by default
+    ///
+    /// Databases the records are loaded in may tell empty strings and NULL apart.
+    pub fn blank_policy(mut self, policy: BlankPolicy) -> Self {
+        self.blank_policy = policy;
+        self
+    }
+
+    /// Sets the decimal separator of Numeric and Float fields, `.` by default
+    ///
+    /// Some tables written by European applications use commas.
+    pub fn decimal_separator(mut self, separator: char) -> Self {
+        self.decimal_separator = separator;
+        self
+    }
+
+    /// Sets the thousands separator of Numeric and Float fields, which is ignored,
+    /// `None` (no separator) by default
+    pub fn thousands_separator(mut self, separator: Option<char>) -> Self {
+        self.thousands_separator = separator;
+        self
+    }
+
+    /// Sets whether Numeric fields without decimals, of at most 18 characters,
+    /// are read as exact integers, `false` by default
+    ///
+    /// Their values are then [NumericInteger](enum.FieldValue.html#variant.NumericInteger)s
+    /// instead of `f64`s, which cannot hold all the integers of more than 15 digits:
+    /// identifiers and account numbers are read as they are stored.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), dbase::Error> {
+    /// let options = dbase::ReadingOptions::new().integer_numerics(true);
+    /// let mut reader = dbase::Reader::from_path_with_options("tests/data/line.dbf", options)?;
+    /// let records = reader.read()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn integer_numerics(mut self, is_integer: bool) -> Self {
+        self.integer_numerics = is_integer;
+        self
+    }
+
+    /// Returns whether the values of the field are read as
+    /// [NumericInteger](enum.FieldValue.html#variant.NumericInteger)s
+    pub(crate) fn reads_as_integer(&self, field_info: &FieldInfo) -> bool {
+        self.integer_numerics
+            && field_info.field_type == FieldType::Numeric
+            && field_info.num_decimal_places == 0
+            && field_info.field_length <= 18
+    }
+
+    /// Sets what Date fields that are not days of the calendar are read as,
+    /// [Lenient](enum.InvalidDatePolicy.html#variant.Lenient) by default
+    pub fn invalid_date_policy(mut self, policy: InvalidDatePolicy) -> Self {
+        self.invalid_date_policy = policy;
+        self
+    }
+
+    /// Sets what to do when the memo file of a table cannot be found,
+    /// the memo handles of the records can still be read
+    /// ([RecordRef::memo_handle](struct.RecordRef.html#method.memo_handle))
+    pub fn missing_memo_policy(mut self, policy: MissingMemoPolicy) -> Self {
+        self.missing_memo_policy = policy;
+        self
+    }
+
+    /// Returns whether numbers are written with a decimal point and no thousands separator
+    pub(crate) fn has_standard_separators(&self) -> bool {
+        self.decimal_separator == '.' && self.thousands_separator.is_none()
+    }
+
+    /// Sets what to do of records that cannot be read
+    pub fn error_policy(mut self, policy: ErrorPolicy) -> Self {
+        self.error_policy = policy;
+        self
+    }
+
+    /// Sets what to do of fields whose names are duplicated or invalid
+    pub fn field_name_policy(mut self, policy: FieldNamePolicy) -> Self {
+        self.field_name_policy = policy;
+        self
+    }
+
+    /// Sets the case of the names of the fields, they are kept as written by default
+    ///
+    /// The names are the keys of the [Records](struct.Record.html) read, converting them
+    /// lets tables written by producers that do not agree on the case be read the same way.
+    /// The names are converted before the [FieldNamePolicy](enum.FieldNamePolicy.html)
+    /// is applied.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), dbase::Error> {
+    /// use dbase::{FieldNameCase, ReadingOptions};
+    ///
+    /// let options = ReadingOptions::new().field_name_case(FieldNameCase::Upper);
+    /// let mut reader = dbase::Reader::from_path_with_options("tests/data/stations.dbf", options)?;
+    /// let records = reader.read()?;
+    /// assert!(records[0].get("NAME").is_some());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn field_name_case(mut self, case: FieldNameCase) -> Self {
+        self.field_name_case = case;
+        self
+    }
+
+    /// Sets the size of the buffers the files opened by path (the table and its memo file)
+    /// are read through, [DEFAULT_BUFFER_SIZE](constant.DEFAULT_BUFFER_SIZE.html) by default
+    ///
+    /// Bigger buffers make fewer reads, which suits network file systems,
+    /// smaller ones suit targets with little memory.
+    pub fn buffer_size(mut self, size: usize) -> Self {
+        self.buffer_size = size;
+        self
+    }
+
+    /// Sets the dialect of the tables, [Standard](enum.Flavor.html#variant.Standard) by default
+    pub fn flavor(mut self, flavor: Flavor) -> Self {
+        self.flavor = flavor;
+        self
+    }
+
+    /// Sets the layout of the memo file, instead of the one told by the memo file
+    /// and by the version of the table
+    ///
+    /// Tables whose version does not have memo files have their memo fields read
+    /// from a memo file of this layout.
+    pub fn memo_format(mut self, format: Option<MemoFormat>) -> Self {
+        self.memo_format = format;
+        self
+    }
+
+    /// Sets whether the production index of tables opened by path is opened with them,
+    /// `false` by default
+    ///
+    /// The index is the .mdx file next to the table when its header says it has one,
+    /// the .cdx file for FoxPro tables: its tags are then given by
+    /// [Reader::index_tags](struct.Reader.html#method.index_tags).
+    /// Tables whose index file is missing are opened without it.
+    pub fn production_index(mut self, open: bool) -> Self {
+        self.production_index = open;
+        self
+    }
+
+    /// Sets how much memory the records read at once may take, `None` (no limit) by default
+    ///
+    /// [Reader::read](struct.Reader.html#method.read),
+    /// [read_as](struct.Reader.html#method.read_as),
+    /// [read_column](struct.Reader.html#method.read_column), the data frames and each Arrow
+    /// record batch first estimate the memory the records will take (see
+    /// [Reader::estimated_memory_size](struct.Reader.html#method.estimated_memory_size)).
+    /// Over the budget, they fail with
+    /// [MemoryBudgetExceeded](enum.ErrorKind.html#variant.MemoryBudgetExceeded)
+    /// without reading anything: the records of unexpectedly large tables are then
+    /// read one at a time, with [iter_records](struct.Reader.html#method.iter_records).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), dbase::Error> {
+    /// let options = dbase::ReadingOptions::new().memory_budget(Some(1024));
+    /// let mut reader = dbase::Reader::from_path_with_options("tests/data/stations.dbf", options)?;
+    /// assert!(matches!(
+    ///     reader.read().unwrap_err().kind(),
+    ///     dbase::ErrorKind::MemoryBudgetExceeded { .. }
+    /// ));
+    /// for record in reader.iter_records() {
+    ///     let record = record?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn memory_budget(mut self, budget: Option<usize>) -> Self {
+        self.memory_budget = budget;
+        self
+    }
+}
+
+impl Default for ReadingOptions {
+    fn default() -> Self {
+        Self {
+            encoding: Arc::new(UnicodeLossy),
+            field_encodings: vec![],
+            character_trim: Some(TrimOption::BeginEnd),
+            nul_padding: true,
+            blank_policy: BlankPolicy::Null,
+            decimal_separator: '.',
+            thousands_separator: None,
+            integer_numerics: false,
+            invalid_date_policy: InvalidDatePolicy::Lenient,
+            missing_memo_policy: MissingMemoPolicy::Fail,
+            error_policy: ErrorPolicy::Fail,
+            field_name_policy: FieldNamePolicy::Warn,
+            field_name_case: FieldNameCase::Unchanged,
+            buffer_size: DEFAULT_BUFFER_SIZE,
+            flavor: Flavor::Standard,
+            memo_format: None,
+            production_index: false,
+            memory_budget: None,
+            cipher: None,
+        }
+    }
+}
@@ -0,0 +1,173 @@
+//! Module with the definition of the writing of records encoded by threads
+//!
+//! The records are split in chunks that threads encode (validating their values and
+//! converting them to their bytes in the table), while the calling thread writes
+//! the encoded chunks to the destination, in the order of the records.
+use std::io::Write;
+use std::sync::mpsc::sync_channel;
+
+use crate::error::Error;
+use crate::writing::{RecordEncoder, TableWriter, WritableRecord};
+
+/// Number of records of the chunks encoded by the threads
+const CHUNK_SIZE: usize = 256;
+
+/// Returns the bytes of the records, one after the other,
+/// `first_record_num` being the index of the first one in the table
+fn encode_chunk<R: WritableRecord>(
+    encoder: &RecordEncoder,
+    records: &[R],
+    first_record_num: usize,
+) -> Result<Vec<u8>, Error> {
+    let mut bytes = Vec::with_capacity(records.len() * encoder.record_size());
+    let mut record_buffer = Vec::with_capacity(encoder.record_size());
+    for (i, record) in records.iter().enumerate() {
+        encoder.encode(record, first_record_num + i, &mut record_buffer)?;
+        bytes.extend_from_slice(&record_buffer);
+    }
+    Ok(bytes)
+}
+
+impl<W: Write> TableWriter<W> {
+    /// Writes the records to the inner destination and returns it once finished,
+    /// the records being encoded by `num_threads` threads
+    ///
+    /// The table written is the same as the one [write](struct.TableWriter.html#method.write)
+    /// writes, and fails on the same records: this is only faster when encoding the records
+    /// is what takes time, eg: for tables with many Character fields. Only the calling
+    /// thread writes to the destination, which therefore does not need to be `Send`.
+    ///
+    /// Each thread encodes the chunks of records it is given one after the other,
+    /// at most two chunks of records per thread are encoded ahead of the one being written.
+    /// `num_threads` is at least 1.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dbase::{FieldName, Record, TableWriterBuilder};
+    /// use std::convert::TryFrom;
+    /// use std::io::Cursor;
+    ///
+    /// # fn main() -> Result<(), dbase::Error> {
+    /// let records = (0..1000)
+    ///     .map(|i| Record::builder().set("NAME", format!("station {}", i)).build())
+    ///     .collect::<Vec<_>>();
+    /// let table = TableWriterBuilder::new()
+    ///     .add_character_field(FieldName::try_from("NAME").unwrap(), 200)
+    ///     .build_with_dest(Cursor::new(Vec::<u8>::new()))
+    ///     .write_parallel(&records, 4)?;
+    ///
+    /// let mut reader = dbase::Reader::new(Cursor::new(table.into_inner()))?;
+    /// assert_eq!(reader.read()?, records);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn write_parallel<R: WritableRecord + Sync>(
+        self,
+        records: &[R],
+        num_threads: usize,
+    ) -> Result<W, Error> {
+        let num_threads = num_threads.max(1);
+        let mut writer = self.start(records.len())?;
+        let encoder = writer.encoder();
+        let record_size = encoder.record_size();
+        let num_chunks = records.len().div_ceil(CHUNK_SIZE);
+
+        std::thread::scope(|scope| {
+            // The chunk i is encoded by the thread i % num_threads
+            let receivers = (0..num_threads)
+                .map(|thread_index| {
+                    let (sender, receiver) = sync_channel(1);
+                    let encoder = &encoder;
+                    scope.spawn(move || {
+                        for chunk_index in (thread_index..num_chunks).step_by(num_threads) {
+                            let start = chunk_index * CHUNK_SIZE;
+                            let end = records.len().min(start + CHUNK_SIZE);
+                            let encoded = encode_chunk(encoder, &records[start..end], start);
+                            // The writing stopped
+                            if sender.send(encoded).is_err() {
+                                break;
+                            }
+                        }
+                    });
+                    receiver
+                })
+                .collect::<Vec<_>>();
+
+            for chunk_index in 0..num_chunks {
+                // The thread only hangs up if encoding panicked,
+                // the panic is resumed once the scope joins the threads
+                let encoded = match receivers[chunk_index % num_threads].recv() {
+                    Ok(encoded) => encoded?,
+                    Err(_) => break,
+                };
+                for record in encoded.chunks(record_size) {
+                    writer.write_encoded(record)?;
+                }
+            }
+            Ok(())
+        })?;
+        writer.finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::convert::TryFrom;
+    use std::io::Cursor;
+
+    use crate::{ErrorKind, FieldName, FieldValue, Record, TableWriterBuilder};
+
+    fn builder() -> TableWriterBuilder {
+        TableWriterBuilder::new()
+            .add_character_field(FieldName::try_from("NAME").unwrap(), 120)
+            .add_numeric_field(FieldName::try_from("AMOUNT").unwrap(), 10, 2)
+    }
+
+    fn records(num_records: usize) -> Vec<Record> {
+        (0..num_records)
+            .map(|i| {
+                Record::builder()
+                    .set("NAME", format!("record number {}", i))
+                    .set("AMOUNT", Some(i as f64 / 4.0))
+                    .build()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn same_table_as_sequential_writing() {
+        for num_records in [0, 1, CHUNK_SIZE, 3 * CHUNK_SIZE + 17].iter() {
+            let records = records(*num_records);
+            let expected = builder()
+                .build_with_dest(Cursor::new(Vec::<u8>::new()))
+                .write(&records)
+                .unwrap()
+                .into_inner();
+            for num_threads in [0, 1, 3, 8].iter() {
+                let table = builder()
+                    .build_with_dest(Cursor::new(Vec::<u8>::new()))
+                    .write_parallel(&records, *num_threads)
+                    .unwrap()
+                    .into_inner();
+                assert_eq!(table, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn first_invalid_record_is_reported() {
+        let mut records = records(4 * CHUNK_SIZE);
+        for i in [CHUNK_SIZE + 5, 3 * CHUNK_SIZE].iter() {
+            records[*i].insert("AMOUNT".to_owned(), FieldValue::Logical(Some(true)));
+        }
+        let error = builder()
+            .build_with_dest(Cursor::new(Vec::<u8>::new()))
+            .write_parallel(&records, 4)
+            .unwrap_err();
+        assert_eq!(error.record_num(), CHUNK_SIZE + 5);
+        assert!(matches!(error.kind(), ErrorKind::IncompatibleType));
+    }
+}
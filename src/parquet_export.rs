@@ -0,0 +1,137 @@
+//! Module with the definition of fn's to convert dBase files to
+//! [Parquet](https://parquet.apache.org) files
+//!
+//! The conversion is built on the [Arrow mapping](struct.RecordBatches.html) of the fields.
+use std::fs::File;
+use std::io::{Read, Seek, Write};
+use std::path::Path;
+use std::sync::Arc;
+
+use arrow_schema::ArrowError;
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::errors::ParquetError;
+use parquet::file::properties::WriterProperties;
+
+use crate::batch::DEFAULT_BATCH_SIZE;
+use crate::error::{Error, ErrorKind};
+use crate::reading::Reader;
+
+/// Converts the error of the external library, giving back our errors (eg: I/O errors
+/// while reading the records) that were wrapped
+fn external_error(error: Box<dyn std::error::Error + Send + Sync>) -> Error {
+    match error.downcast::<Error>() {
+        Ok(error) => *error,
+        Err(error) => Error {
+            record_num: 0,
+            field: None,
+            kind: ErrorKind::Message(error.to_string()),
+        },
+    }
+}
+
+fn arrow_error(error: ArrowError) -> Error {
+    match error {
+        ArrowError::ExternalError(error) => external_error(error),
+        error => external_error(Box::new(error)),
+    }
+}
+
+fn parquet_error(error: ParquetError) -> Error {
+    match error {
+        ParquetError::External(error) => external_error(error),
+        error => external_error(Box::new(error)),
+    }
+}
+
+impl<T: Read + Seek> Reader<T> {
+    /// Writes all the records of the table to a new Parquet file at the given path,
+    /// returns the number of records written
+    ///
+    /// The file is compressed using snappy.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # fn main() -> Result<(), dbase::Error> {
+    /// let reader = dbase::Reader::from_path("tests/data/line.dbf")?;
+    /// let num_records = reader.to_parquet("line.parquet")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn to_parquet<P: AsRef<Path>>(self, path: P) -> Result<usize, Error> {
+        let file = File::create(path).map_err(|error| Error::io_error(error, 0))?;
+        let properties = WriterProperties::builder()
+            .set_compression(Compression::SNAPPY)
+            .build();
+        self.to_parquet_writer(file, properties)
+    }
+
+    /// Writes all the records of the table as Parquet to the destination
+    /// using the given properties, returns the number of records written
+    pub fn to_parquet_writer<W: Write + Send>(
+        self,
+        dst: W,
+        properties: WriterProperties,
+    ) -> Result<usize, Error> {
+        let schema = Arc::new(self.arrow_schema());
+        let mut writer =
+            ArrowWriter::try_new(dst, schema, Some(properties)).map_err(parquet_error)?;
+        let mut num_records = 0;
+        for batch in self.into_record_batches(DEFAULT_BATCH_SIZE) {
+            let batch = batch.map_err(arrow_error)?;
+            writer.write(&batch).map_err(parquet_error)?;
+            num_records += batch.num_rows();
+        }
+        writer.close().map_err(parquet_error)?;
+        Ok(num_records)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::io::Cursor;
+
+    use arrow_array::RecordBatch;
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+    #[test]
+    fn convert_to_parquet() {
+        let path = std::env::temp_dir().join("dbase_convert_to_parquet.parquet");
+        let num_records = Reader::from_path("tests/data/line.dbf")
+            .unwrap()
+            .to_parquet(&path)
+            .unwrap();
+
+        let expected = Reader::from_path("tests/data/line.dbf")
+            .unwrap()
+            .into_record_batches(DEFAULT_BATCH_SIZE)
+            .collect::<Result<Vec<RecordBatch>, ArrowError>>()
+            .unwrap();
+        assert_eq!(num_records, expected[0].num_rows());
+
+        let batches = ParquetRecordBatchReaderBuilder::try_new(File::open(&path).unwrap())
+            .unwrap()
+            .build()
+            .unwrap()
+            .collect::<Result<Vec<RecordBatch>, ArrowError>>()
+            .unwrap();
+        assert_eq!(batches, expected);
+    }
+
+    #[test]
+    fn errors_are_forwarded() {
+        let mut content = std::fs::read("tests/data/line.dbf").unwrap();
+        // Truncate the file in the middle of the records
+        let header_size = u16::from_le_bytes([content[8], content[9]]) as usize;
+        content.truncate(header_size + 2);
+        let reader = Reader::new(Cursor::new(content)).unwrap();
+
+        let error = reader
+            .to_parquet_writer(Vec::<u8>::new(), WriterProperties::default())
+            .unwrap_err();
+        assert!(matches!(error.kind(), ErrorKind::IoError(_)));
+    }
+}
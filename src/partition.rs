@@ -0,0 +1,124 @@
+//! Module with the definition of fn's to split a table in ranges of records
+//! read independently
+//!
+//! Each partition has its own reader, so partitions can be read
+//! from different threads, or by different machines from the ranges computed
+//! from the number of records in the header.
+use std::fs::File;
+use std::io::BufReader;
+use std::ops::Range;
+use std::path::Path;
+
+use crate::error::Error;
+use crate::reading::{Reader, ReadingOptions};
+
+/// Splits the indices of the records in at most `num_partitions` contiguous ranges,
+/// whose numbers of records differ by one at most
+///
+/// There are fewer ranges than partitions when there are fewer records,
+/// ranges are never empty.
+///
+/// # Example
+///
+/// ```
+/// assert_eq!(dbase::split_records(10, 3), vec![0..4, 4..7, 7..10]);
+/// assert_eq!(dbase::split_records(2, 3), vec![0..1, 1..2]);
+/// ```
+pub fn split_records(num_records: usize, num_partitions: usize) -> Vec<Range<usize>> {
+    let num_partitions = num_partitions.min(num_records);
+    if num_partitions == 0 {
+        return vec![];
+    }
+    let size = num_records / num_partitions;
+    let num_bigger = num_records % num_partitions;
+    let mut start = 0;
+    (0..num_partitions)
+        .map(|partition| {
+            let end = start + size + usize::from(partition < num_bigger);
+            let range = start..end;
+            start = end;
+            range
+        })
+        .collect()
+}
+
+/// Opens a reader for each of the partitions of the table at the given path,
+/// as split by [split_records](fn.split_records.html)
+///
+/// The readers only read the records of their partition, the memo file (if any)
+/// is opened by each of them.
+///
+/// # Example
+///
+/// ```
+/// # fn main() -> Result<(), dbase::Error> {
+/// let readers = dbase::open_partitions(
+///     "tests/data/stations.dbf",
+///     4,
+///     dbase::ReadingOptions::default(),
+/// )?;
+/// let num_records = std::thread::scope(|scope| {
+///     readers
+///         .into_iter()
+///         .map(|mut reader| scope.spawn(move || reader.read().unwrap().len()))
+///         .collect::<Vec<_>>()
+///         .into_iter()
+///         .map(|handle| handle.join().unwrap())
+///         .sum::<usize>()
+/// });
+/// assert_eq!(num_records, 6);
+/// # Ok(())
+/// # }
+/// ```
+pub fn open_partitions<P: AsRef<Path>>(
+    path: P,
+    num_partitions: usize,
+    options: ReadingOptions,
+) -> Result<Vec<Reader<BufReader<File>>>, Error> {
+    let path = path.as_ref();
+    let reader = Reader::from_path_with_options(path, options.clone())?;
+    let ranges = split_records(reader.header().num_records as usize, num_partitions);
+    let mut readers = Vec::with_capacity(ranges.len());
+    let mut reader = Some(reader);
+    for range in ranges {
+        let mut partition_reader = match reader.take() {
+            Some(reader) => reader,
+            None => Reader::from_path_with_options(path, options.clone())?,
+        };
+        partition_reader.set_record_range(range)?;
+        readers.push(partition_reader);
+    }
+    Ok(readers)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn balanced_ranges() {
+        assert_eq!(split_records(0, 4), Vec::<Range<usize>>::new());
+        assert_eq!(split_records(5, 0), Vec::<Range<usize>>::new());
+        assert_eq!(split_records(6, 1), vec![0..6]);
+        assert_eq!(split_records(7, 7).len(), 7);
+        let ranges = split_records(1003, 10);
+        assert_eq!(ranges.first().unwrap().start, 0);
+        assert_eq!(ranges.last().unwrap().end, 1003);
+        assert!(ranges.windows(2).all(|pair| pair[0].end == pair[1].start));
+        assert!(ranges
+            .iter()
+            .all(|range| range.len() == 100 || range.len() == 101));
+    }
+
+    #[test]
+    fn partitions_read_the_whole_table() {
+        let expected = crate::read("tests/data/stations.dbf").unwrap();
+        let mut records = vec![];
+        for mut reader in
+            open_partitions("tests/data/stations.dbf", 4, ReadingOptions::default()).unwrap()
+        {
+            records.extend(reader.read().unwrap());
+        }
+        assert_eq!(records, expected);
+    }
+}
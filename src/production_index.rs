@@ -0,0 +1,1189 @@
+//! Module with the definition of fn's and struct's to read the production index of tables
+//!
+//! A production index is opened with its table and holds several indexes, called tags,
+//! each one a B-tree of the keys computed from the records with its key expression.
+//! dBase IV keeps it in a .mdx file next to the table, the FoxPros in a structural .cdx file.
+//!
+//! The tags are only read: writing records does not update them,
+//! and [reindex](fn.reindex.html) cannot rebuild them.
+use byteorder::{BigEndian, LittleEndian, ReadBytesExt};
+
+use std::cmp::Ordering;
+use std::io::{Read, Seek, SeekFrom};
+use std::marker::PhantomData;
+use std::ops::{Bound, RangeBounds};
+use std::path::{Path, PathBuf};
+
+use crate::error::{Error, ErrorKind};
+use crate::expression::{Expression, ValueType};
+use crate::header::{Header, Version};
+use crate::index::{IndexKey, KeyType, MAX_DEPTH};
+use crate::reading::{ReadableRecord, Reader, Record};
+use crate::record::FieldInfo;
+
+/// Size of the pages of .mdx files, the tags and the nodes are addressed by page number
+const MDX_PAGE_SIZE: usize = 512;
+/// Size of the header of .mdx files, the tag table follows it
+const MDX_HEADER_SIZE: usize = 544;
+/// Size of the entries of the tag table of .mdx files
+const MDX_TAG_ENTRY_SIZE: usize = 32;
+/// Number of entries of the tag table of .mdx files
+const MDX_MAX_TAGS: u16 = 48;
+/// Size of the nodes of .cdx files
+const CDX_NODE_SIZE: usize = 512;
+/// Size of the headers of .cdx tags, the expressions included
+const CDX_HEADER_SIZE: usize = 1024;
+/// Longest key of .cdx tags
+const CDX_MAX_KEY_LENGTH: u16 = 240;
+
+/// The formats of production indexes
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ProductionIndexFormat {
+    /// dBase IV multiple index (.mdx)
+    Mdx,
+    /// FoxPro compound index (.cdx)
+    Cdx,
+}
+
+impl ProductionIndexFormat {
+    /// Returns the extension of the files of the format, eg: `"mdx"`
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ProductionIndexFormat::Mdx => "mdx",
+            ProductionIndexFormat::Cdx => "cdx",
+        }
+    }
+
+    /// Returns the format of the production index of the tables of the version
+    pub fn of_version(version: Version) -> Self {
+        match version {
+            Version::FoxBase | Version::VisualFoxPro | Version::FoxPro2 { .. } => {
+                ProductionIndexFormat::Cdx
+            }
+            _ => ProductionIndexFormat::Mdx,
+        }
+    }
+}
+
+/// Returns the path and the format of the production index of the table
+/// at the given path, if it has one
+///
+/// The header tells whether the table has one, FoxPro tables also have one
+/// when a .cdx file is next to them.
+pub(crate) fn production_index_path(
+    path: &Path,
+    header: &Header,
+) -> Option<(PathBuf, ProductionIndexFormat)> {
+    let format = ProductionIndexFormat::of_version(header.file_type);
+    let index_path = path.with_extension(format.extension());
+    let has_index = header.has_production_index()
+        || (format == ProductionIndexFormat::Cdx && index_path.is_file());
+    if has_index {
+        Some((index_path, format))
+    } else {
+        None
+    }
+}
+
+/// How the keys of a tag are stored
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum KeyEncoding {
+    /// Strings, compared byte by byte
+    Character,
+    /// Binary coded decimal numbers of dBase IV
+    Bcd,
+    /// Little endian doubles, the dates of .mdx tags
+    Double,
+    /// Big endian doubles whose bits are flipped so that they compare byte by byte,
+    /// the numbers and dates of .cdx tags
+    OrderedDouble,
+}
+
+/// A tag of a production index
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexTag {
+    name: String,
+    key_expression: String,
+    for_expression: Option<String>,
+    key_length: u16,
+    unique: bool,
+    descending: bool,
+    encoding: KeyEncoding,
+    /// Offset of the root node in the file
+    root: u64,
+    /// Size of the entries of the nodes of .mdx tags, 0 for .cdx tags
+    entry_size: usize,
+}
+
+impl IndexTag {
+    /// Returns the name of the tag
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the expression used to compute the keys (eg: `UPPER(NAME)`)
+    pub fn key_expression(&self) -> &str {
+        &self.key_expression
+    }
+
+    /// Returns the expression telling which records have a key, if there is one
+    ///
+    /// Only .cdx tags give it.
+    pub fn for_expression(&self) -> Option<&str> {
+        self.for_expression.as_deref()
+    }
+
+    /// Returns the type of the keys
+    pub fn key_type(&self) -> KeyType {
+        match self.encoding {
+            KeyEncoding::Character => KeyType::Character,
+            _ => KeyType::Numeric,
+        }
+    }
+
+    /// Returns the length in bytes of the keys
+    pub fn key_length(&self) -> u16 {
+        self.key_length
+    }
+
+    /// Returns true if the tag only keeps the first record of each key
+    pub fn is_unique(&self) -> bool {
+        self.unique
+    }
+
+    /// Returns true if the keys are in descending order
+    pub fn is_descending(&self) -> bool {
+        self.descending
+    }
+
+    /// Returns the number the key compares with, for numeric keys
+    fn numeric_value(&self, key: &[u8]) -> f64 {
+        let mut bytes = [0u8; 8];
+        let len = key.len().min(8);
+        bytes[..len].copy_from_slice(&key[..len]);
+        match self.encoding {
+            KeyEncoding::Character => 0.0,
+            KeyEncoding::Bcd => bcd_value(key),
+            KeyEncoding::Double => f64::from_le_bytes(bytes),
+            KeyEncoding::OrderedDouble => {
+                let bits = u64::from_be_bytes(bytes);
+                if bits & (1 << 63) != 0 {
+                    f64::from_bits(bits ^ (1 << 63))
+                } else {
+                    f64::from_bits(!bits)
+                }
+            }
+        }
+    }
+
+    /// Compares a key of the tag with a bound, a character bound shorter than
+    /// the keys matches all the keys it is a prefix of
+    fn compare(&self, key: &[u8], bound: &BoundKey) -> Ordering {
+        match bound {
+            BoundKey::Character(bound) => key[..key.len().min(bound.len())].cmp(bound),
+            BoundKey::Numeric(bound) => self
+                .numeric_value(key)
+                .partial_cmp(bound)
+                .unwrap_or(Ordering::Equal),
+        }
+    }
+
+    fn convert_bound<K>(&self, bound: Bound<&K>) -> Result<Bound<BoundKey>, Error>
+    where
+        K: Into<IndexKey> + Clone,
+    {
+        let convert = |key: &K| match (key.clone().into(), self.key_type()) {
+            (IndexKey::Character(s), KeyType::Character) => Ok(BoundKey::Character(s.into_bytes())),
+            (IndexKey::Numeric(v), KeyType::Numeric) => Ok(BoundKey::Numeric(v)),
+            _ => Err(Error {
+                record_num: 0,
+                field: None,
+                kind: ErrorKind::IncompatibleType,
+            }),
+        };
+        Ok(match bound {
+            Bound::Included(key) => Bound::Included(convert(key)?),
+            Bound::Excluded(key) => Bound::Excluded(convert(key)?),
+            Bound::Unbounded => Bound::Unbounded,
+        })
+    }
+}
+
+/// A bound of a range of keys, converted to the type of keys of a tag
+enum BoundKey {
+    Character(Vec<u8>),
+    Numeric(f64),
+}
+
+/// Decodes the numbers of dBase IV numeric keys: the first byte is the exponent
+/// (0x34 for 0.d), the second the sign and the number of digits, then come the digits
+fn bcd_value(key: &[u8]) -> f64 {
+    if key.len() < 2 {
+        return 0.0;
+    }
+    let exponent = i32::from(key[0]) - 0x34;
+    let num_digits = usize::from((key[1] >> 2) & 0x1F);
+    let digits = key[2..]
+        .iter()
+        .flat_map(|byte| [byte >> 4, byte & 0x0F])
+        .take(num_digits)
+        .map(|digit| char::from(b'0' + digit.min(9)))
+        .collect::<String>();
+    if digits.is_empty() {
+        return 0.0;
+    }
+    let value = format!("0.{}e{}", digits, exponent)
+        .parse::<f64>()
+        .unwrap_or(0.0);
+    if key[1] & 0x80 != 0 {
+        -value
+    } else {
+        value
+    }
+}
+
+/// A node of the B-tree of a tag
+enum Node {
+    /// The keys and the numbers (1-based) of the records they were computed from
+    Leaf(Vec<(Vec<u8>, u32)>),
+    /// The greatest key and the offset of each child,
+    /// the key of the last child of .mdx nodes is not stored
+    Interior(Vec<(Option<Vec<u8>>, u64)>),
+}
+
+fn invalid_index(message: String) -> ErrorKind {
+    ErrorKind::InvalidIndex(message)
+}
+
+fn read_bytes<T: Read + Seek>(
+    source: &mut T,
+    offset: u64,
+    len: usize,
+) -> Result<Vec<u8>, ErrorKind> {
+    source.seek(SeekFrom::Start(offset))?;
+    let mut bytes = vec![0u8; len];
+    source.read_exact(&mut bytes)?;
+    Ok(bytes)
+}
+
+/// Returns the text of the null terminated string at the start of the bytes
+fn read_string(bytes: &[u8]) -> String {
+    let len = bytes.iter().position(|b| *b == 0).unwrap_or(bytes.len());
+    String::from_utf8_lossy(&bytes[..len]).trim().to_owned()
+}
+
+fn read_mdx_node(bytes: &[u8], tag: &IndexTag) -> Result<Node, ErrorKind> {
+    let num_keys = (&bytes[..4]).read_u32::<LittleEndian>()? as usize;
+    let entries_end = num_keys
+        .checked_mul(tag.entry_size)
+        .and_then(|len| len.checked_add(8))
+        .filter(|end| *end <= bytes.len())
+        .ok_or_else(|| invalid_index(format!("node claims to hold {} keys", num_keys)))?;
+    let key_length = usize::from(tag.key_length);
+    let entries = bytes[8..entries_end]
+        .chunks(tag.entry_size)
+        .map(|entry| {
+            let pointer = u32::from_le_bytes([entry[0], entry[1], entry[2], entry[3]]);
+            (entry[4..4 + key_length].to_vec(), pointer)
+        })
+        .collect::<Vec<_>>();
+    // Interior nodes have one more child than keys, leaves have no pointer there
+    let last_child = match bytes.get(entries_end..entries_end + 4) {
+        Some(mut pointer) => pointer.read_u32::<LittleEndian>()?,
+        None => 0,
+    };
+    if last_child == 0 {
+        return Ok(Node::Leaf(entries));
+    }
+    let page_offset = |page: u32| u64::from(page) * MDX_PAGE_SIZE as u64;
+    let mut children = entries
+        .into_iter()
+        .map(|(key, page)| (Some(key), page_offset(page)))
+        .collect::<Vec<_>>();
+    children.push((None, page_offset(last_child)));
+    Ok(Node::Interior(children))
+}
+
+fn read_cdx_node(bytes: &[u8], tag: &IndexTag) -> Result<Node, ErrorKind> {
+    let mut cursor = bytes;
+    let attributes = cursor.read_u16::<LittleEndian>()?;
+    let num_keys = usize::from(cursor.read_u16::<LittleEndian>()?);
+    let key_length = usize::from(tag.key_length);
+    if attributes & 0x02 == 0 {
+        // Interior node: the key, the record number and the child of each entry
+        let entry_size = key_length + 8;
+        if 12 + num_keys * entry_size > bytes.len() {
+            return Err(invalid_index(format!(
+                "node claims to hold {} keys",
+                num_keys
+            )));
+        }
+        let children = bytes[12..12 + num_keys * entry_size]
+            .chunks(entry_size)
+            .map(|entry| {
+                let mut child = &entry[key_length + 4..];
+                let child = child.read_u32::<BigEndian>()?;
+                Ok((Some(entry[..key_length].to_vec()), u64::from(child)))
+            })
+            .collect::<Result<Vec<_>, ErrorKind>>()?;
+        return Ok(Node::Interior(children));
+    }
+
+    // Leaf: the record number and the number of bytes shared with the previous key
+    // and of trailing blanks of each key are bit packed after the header,
+    // the rest of the keys are stored from the end of the node
+    let mut cursor = &bytes[14..];
+    let record_mask = cursor.read_u32::<LittleEndian>()?;
+    let duplicate_mask = usize::from(bytes[18]);
+    let trailing_mask = usize::from(bytes[19]);
+    let record_bits = u32::from(bytes[20]);
+    let duplicate_bits = u32::from(bytes[21]);
+    let info_size = usize::from(bytes[23]);
+    let infos_end = 24 + num_keys * info_size;
+    if info_size == 0
+        || info_size > 8
+        || infos_end > bytes.len()
+        || record_bits + duplicate_bits >= 64
+    {
+        return Err(invalid_index(format!(
+            "leaf of {} keys with invalid key information",
+            num_keys
+        )));
+    }
+    let fill = match tag.encoding {
+        KeyEncoding::Character => b' ',
+        _ => 0,
+    };
+    let mut entries = Vec::with_capacity(num_keys);
+    let mut previous = vec![fill; key_length];
+    let mut keys_start = bytes.len();
+    for info in bytes[24..infos_end].chunks(info_size) {
+        let mut info_bytes = [0u8; 8];
+        info_bytes[..info_size].copy_from_slice(info);
+        let info = u64::from_le_bytes(info_bytes);
+        let record_number = (info as u32) & record_mask;
+        let duplicates = (info >> record_bits) as usize & duplicate_mask;
+        let trailing = (info >> (record_bits + duplicate_bits)) as usize & trailing_mask;
+        let stored = key_length
+            .checked_sub(duplicates + trailing)
+            .filter(|stored| keys_start >= infos_end + stored)
+            .ok_or_else(|| invalid_index("leaf key does not fit in its node".to_owned()))?;
+        let mut key = previous[..duplicates].to_vec();
+        key.extend_from_slice(&bytes[keys_start - stored..keys_start]);
+        key.resize(key_length, fill);
+        keys_start -= stored;
+        previous.clone_from(&key);
+        entries.push((key, record_number));
+    }
+    Ok(Node::Leaf(entries))
+}
+
+/// Walk of the keys of a tag that are in a range, in the order of the tag,
+/// the nodes are read when the walk reaches them
+///
+/// The children whose keys are all below the lower bound are skipped, and
+/// the walk stops at the first key above the upper bound. The keys of
+/// descending tags are all visited.
+struct TagCursor {
+    tag: IndexTag,
+    node_size: usize,
+    lower: Bound<BoundKey>,
+    upper: Bound<BoundKey>,
+    /// The nodes from the root to the one of the next key, with the position
+    /// of their next entry
+    stack: Vec<(Node, usize)>,
+}
+
+/// What comes next in the walk of a tag
+enum Step {
+    Key(Vec<u8>, u32),
+    Child(Option<Vec<u8>>, u64),
+    Up,
+}
+
+impl TagCursor {
+    fn new<T: Read + Seek>(
+        source: &mut T,
+        tag: IndexTag,
+        node_size: usize,
+        lower: Bound<BoundKey>,
+        upper: Bound<BoundKey>,
+    ) -> Result<Self, ErrorKind> {
+        let root = tag.root;
+        let mut cursor = Self {
+            tag,
+            node_size,
+            lower,
+            upper,
+            stack: vec![],
+        };
+        cursor.push_node(source, root)?;
+        Ok(cursor)
+    }
+
+    fn push_node<T: Read + Seek>(&mut self, source: &mut T, offset: u64) -> Result<(), ErrorKind> {
+        if self.stack.len() > MAX_DEPTH {
+            return Err(invalid_index("the tree is too deep".to_owned()));
+        }
+        let bytes = read_bytes(source, offset, self.node_size)?;
+        let node = match self.tag.entry_size {
+            0 => read_cdx_node(&bytes, &self.tag)?,
+            _ => read_mdx_node(&bytes, &self.tag)?,
+        };
+        self.stack.push((node, 0));
+        Ok(())
+    }
+
+    fn is_below(&self, key: &[u8]) -> bool {
+        match &self.lower {
+            Bound::Included(bound) => self.tag.compare(key, bound) == Ordering::Less,
+            Bound::Excluded(bound) => self.tag.compare(key, bound) != Ordering::Greater,
+            Bound::Unbounded => false,
+        }
+    }
+
+    fn is_above(&self, key: &[u8]) -> bool {
+        match &self.upper {
+            Bound::Included(bound) => self.tag.compare(key, bound) == Ordering::Greater,
+            Bound::Excluded(bound) => self.tag.compare(key, bound) != Ordering::Less,
+            Bound::Unbounded => false,
+        }
+    }
+
+    /// Returns the next key of the range and the number (1-based) of its record,
+    /// `None` once the range is walked
+    fn next_entry<T: Read + Seek>(
+        &mut self,
+        source: &mut T,
+    ) -> Result<Option<(Vec<u8>, u32)>, ErrorKind> {
+        let is_ascending = !self.tag.descending;
+        loop {
+            let step = match self.stack.last_mut() {
+                None => return Ok(None),
+                Some((Node::Leaf(entries), position)) => match entries.get_mut(*position) {
+                    Some((key, record_number)) => {
+                        *position += 1;
+                        Step::Key(std::mem::take(key), *record_number)
+                    }
+                    None => Step::Up,
+                },
+                Some((Node::Interior(children), position)) => match children.get_mut(*position) {
+                    Some((key, child)) => {
+                        *position += 1;
+                        Step::Child(key.take(), *child)
+                    }
+                    None => Step::Up,
+                },
+            };
+            match step {
+                Step::Up => {
+                    self.stack.pop();
+                }
+                Step::Child(key, child) => {
+                    if !(is_ascending && key.as_deref().is_some_and(|key| self.is_below(key))) {
+                        self.push_node(source, child)?;
+                    }
+                }
+                Step::Key(key, record_number) => {
+                    if self.is_below(&key) {
+                        continue;
+                    }
+                    if self.is_above(&key) {
+                        if is_ascending {
+                            self.stack.clear();
+                            return Ok(None);
+                        }
+                        continue;
+                    }
+                    return Ok(Some((key, record_number)));
+                }
+            }
+        }
+    }
+}
+
+/// Returns the index (0-based) of the record with the number found in a tag
+fn record_index(record_number: u32) -> Result<u32, ErrorKind> {
+    record_number
+        .checked_sub(1)
+        .ok_or_else(|| invalid_index("key points to record 0".to_owned()))
+}
+
+fn read_mdx_tags<T: Read + Seek>(source: &mut T) -> Result<(Vec<IndexTag>, usize), ErrorKind> {
+    let header = read_bytes(source, 0, MDX_HEADER_SIZE)?;
+    if header[0] != 2 {
+        return Err(invalid_index(format!(
+            "unknown version {} of .mdx file",
+            header[0]
+        )));
+    }
+    let node_size = usize::from((&header[22..24]).read_u16::<LittleEndian>()?);
+    if node_size == 0 || node_size % MDX_PAGE_SIZE != 0 {
+        return Err(invalid_index(format!("invalid block size {}", node_size)));
+    }
+    let entry_size = usize::from(header[26]).max(MDX_TAG_ENTRY_SIZE);
+    let num_tags = (&header[28..30]).read_u16::<LittleEndian>()?;
+    if num_tags > MDX_MAX_TAGS {
+        return Err(invalid_index(format!(
+            "the file claims to hold {} tags",
+            num_tags
+        )));
+    }
+
+    let mut tags = Vec::with_capacity(usize::from(num_tags));
+    for i in 0..usize::from(num_tags) {
+        let offset = (MDX_HEADER_SIZE + i * entry_size) as u64;
+        let entry = read_bytes(source, offset, MDX_TAG_ENTRY_SIZE)?;
+        let page = (&entry[..4]).read_u32::<LittleEndian>()?;
+        let name = read_string(&entry[4..15]);
+
+        let tag_header = read_bytes(
+            source,
+            u64::from(page) * MDX_PAGE_SIZE as u64,
+            MDX_PAGE_SIZE,
+        )?;
+        let root_page = (&tag_header[..4]).read_u32::<LittleEndian>()?;
+        let key_format = tag_header[8];
+        let key_length = (&tag_header[12..14]).read_u16::<LittleEndian>()?;
+        let key_entry_size = usize::from((&tag_header[18..20]).read_u16::<LittleEndian>()?);
+        let encoding = match (tag_header[9], key_length) {
+            (b'C', _) => KeyEncoding::Character,
+            (b'N', 12) => KeyEncoding::Bcd,
+            (b'D', 8) => KeyEncoding::Double,
+            (key_type, _) => {
+                return Err(invalid_index(format!(
+                    "tag {} has keys of type '{}' and length {}",
+                    name,
+                    char::from(key_type),
+                    key_length
+                )))
+            }
+        };
+        if key_length == 0 || key_entry_size < usize::from(key_length) + 4 {
+            return Err(invalid_index(format!(
+                "key length {} does not fit in key entries of {} bytes",
+                key_length, key_entry_size
+            )));
+        }
+        tags.push(IndexTag {
+            name,
+            key_expression: read_string(&tag_header[24..]),
+            for_expression: None,
+            key_length,
+            unique: tag_header[23] != 0 || key_format & 0x40 != 0,
+            descending: key_format & 0x08 != 0,
+            encoding,
+            root: u64::from(root_page) * MDX_PAGE_SIZE as u64,
+            entry_size: key_entry_size,
+        });
+    }
+    Ok((tags, node_size))
+}
+
+/// Reads the header of the .cdx tag at the offset,
+/// the name of the tag is not in its header
+fn read_cdx_tag<T: Read + Seek>(
+    source: &mut T,
+    offset: u64,
+    fields: &[FieldInfo],
+) -> Result<IndexTag, ErrorKind> {
+    let header = read_bytes(source, offset, CDX_HEADER_SIZE)?;
+    let root = (&header[..4]).read_u32::<LittleEndian>()?;
+    let key_length = (&header[12..14]).read_u16::<LittleEndian>()?;
+    let options = header[14];
+    let descending = (&header[502..504]).read_u16::<LittleEndian>()? != 0;
+    let for_length = usize::from((&header[506..508]).read_u16::<LittleEndian>()?);
+    let key_expression_length = usize::from((&header[510..512]).read_u16::<LittleEndian>()?);
+    if options & 0x20 == 0 {
+        return Err(invalid_index("the tag is not a compact index".to_owned()));
+    }
+    if key_length == 0 || key_length > CDX_MAX_KEY_LENGTH {
+        return Err(invalid_index(format!("invalid key length {}", key_length)));
+    }
+
+    // The pool holds the key expression then the FOR expression
+    let pool = &header[CDX_NODE_SIZE..];
+    let key_end = key_expression_length.min(pool.len());
+    let for_end = (key_end + for_length).min(pool.len());
+    let key_expression = read_string(&pool[..key_end]);
+    let for_expression = Some(read_string(&pool[key_end..for_end])).filter(|e| !e.is_empty());
+
+    // The type of the keys is not stored, it is the one of the expression
+    let encoding = match Expression::parse(&key_expression, fields).map(|e| e.value_type()) {
+        Ok(ValueType::Numeric) | Ok(ValueType::Date) if key_length == 8 => {
+            KeyEncoding::OrderedDouble
+        }
+        _ => KeyEncoding::Character,
+    };
+    Ok(IndexTag {
+        name: String::new(),
+        key_expression,
+        for_expression,
+        key_length,
+        unique: options & 0x01 != 0,
+        descending,
+        encoding,
+        root: u64::from(root),
+        entry_size: 0,
+    })
+}
+
+fn read_cdx_tags<T: Read + Seek>(
+    source: &mut T,
+    fields: &[FieldInfo],
+) -> Result<Vec<IndexTag>, ErrorKind> {
+    // The header of the file is the one of the tag of the tag names,
+    // whose keys point to the headers of the other tags
+    let directory = read_cdx_tag(source, 0, &[])?;
+    let mut entries = vec![];
+    let mut cursor = TagCursor::new(
+        source,
+        directory,
+        CDX_NODE_SIZE,
+        Bound::Unbounded,
+        Bound::Unbounded,
+    )?;
+    while let Some((name, offset)) = cursor.next_entry(source)? {
+        entries.push((read_string(&name), u64::from(offset)));
+    }
+    entries
+        .into_iter()
+        .map(|(name, offset)| {
+            let tag = read_cdx_tag(source, offset, fields)?;
+            Ok(IndexTag { name, ..tag })
+        })
+        .collect()
+}
+
+/// Struct with the handle to the production index of a table
+///
+/// It is opened with the table when [ReadingOptions::production_index](struct.ReadingOptions.html#method.production_index)
+/// is set, its tags are then given by [Reader::index_tags](struct.Reader.html#method.index_tags).
+///
+/// # Example
+///
+/// ```no_run
+/// # fn main() -> Result<(), dbase::Error> {
+/// let options = dbase::ReadingOptions::new().production_index(true);
+/// let mut reader = dbase::Reader::from_path_with_options("customers.dbf", options)?;
+/// for tag in reader.index_tags() {
+///     println!("{}: {}", tag.name(), tag.key_expression());
+/// }
+/// // All the customers whose name starts with 'DU', in the order of the NAME tag
+/// for record in reader.read_tag_range("NAME", "DU"..="DU")? {
+///     println!("{:?}", record?.get("NAME"));
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct ProductionIndex<T: Read + Seek> {
+    source: T,
+    format: ProductionIndexFormat,
+    tags: Vec<IndexTag>,
+    node_size: usize,
+}
+
+impl<T: Read + Seek> ProductionIndex<T> {
+    /// Creates a new production index from the source, its tags are read as soon as its created
+    ///
+    /// The fields of the table tell the type of the keys of .cdx tags,
+    /// which is not stored in the file: keys whose expression cannot be understood
+    /// (see [Expression](struct.Expression.html)) are read as characters.
+    pub fn new(
+        mut source: T,
+        format: ProductionIndexFormat,
+        fields: &[FieldInfo],
+    ) -> Result<Self, Error> {
+        let tags = match format {
+            ProductionIndexFormat::Mdx => read_mdx_tags(&mut source),
+            ProductionIndexFormat::Cdx => {
+                read_cdx_tags(&mut source, fields).map(|tags| (tags, CDX_NODE_SIZE))
+            }
+        };
+        let (tags, node_size) = tags.map_err(|kind| Error {
+            record_num: 0,
+            field: None,
+            kind,
+        })?;
+        Ok(Self {
+            source,
+            format,
+            tags,
+            node_size,
+        })
+    }
+
+    /// Returns the format of the index
+    pub fn format(&self) -> ProductionIndexFormat {
+        self.format
+    }
+
+    /// Returns the tags of the index
+    pub fn tags(&self) -> &[IndexTag] {
+        &self.tags
+    }
+
+    /// Returns the tag with the given name, names are compared ignoring the ASCII case
+    pub fn tag(&self, name: &str) -> Option<&IndexTag> {
+        self.tags
+            .iter()
+            .find(|tag| tag.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Returns the indices (0-based) of the records whose key in the tag
+    /// is in the given range, in the order of the tag, without reading the records
+    pub fn record_indices<K, B>(&mut self, tag: &str, bounds: B) -> Result<Vec<usize>, Error>
+    where
+        K: Into<IndexKey> + Clone,
+        B: RangeBounds<K>,
+    {
+        let mut cursor = self.cursor(tag, bounds)?;
+        let mut indices = vec![];
+        loop {
+            let entry = cursor.next_entry(&mut self.source).and_then(|entry| {
+                entry
+                    .map(|(_, record_number)| record_index(record_number))
+                    .transpose()
+            });
+            match entry {
+                Ok(Some(index)) => indices.push(index as usize),
+                Ok(None) => return Ok(indices),
+                Err(kind) => {
+                    return Err(Error {
+                        record_num: 0,
+                        field: None,
+                        kind,
+                    })
+                }
+            }
+        }
+    }
+
+    /// Returns the walk of the keys of the tag that are in the range
+    fn cursor<K, B>(&mut self, tag: &str, bounds: B) -> Result<TagCursor, Error>
+    where
+        K: Into<IndexKey> + Clone,
+        B: RangeBounds<K>,
+    {
+        let tag = self.tag(tag).cloned().ok_or_else(|| Error {
+            record_num: 0,
+            field: None,
+            kind: invalid_index(format!("the index has no tag named {}", tag)),
+        })?;
+        let lower = tag.convert_bound(bounds.start_bound())?;
+        let upper = tag.convert_bound(bounds.end_bound())?;
+        TagCursor::new(&mut self.source, tag, self.node_size, lower, upper).map_err(|kind| Error {
+            record_num: 0,
+            field: None,
+            kind,
+        })
+    }
+}
+
+/// Iterator over the records of a table in the order of a tag of its production index
+///
+/// Created by [Reader::read_tag_range](struct.Reader.html#method.read_tag_range),
+/// the nodes of the tag and the records are read as the iteration goes.
+pub struct TagRange<'a, T: Read + Seek, R: ReadableRecord> {
+    reader: &'a mut Reader<T>,
+    cursor: TagCursor,
+    record_type: PhantomData<R>,
+}
+
+impl<T: Read + Seek, R: ReadableRecord> Iterator for TagRange<'_, T, R> {
+    type Item = Result<R, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let production_index = self.reader.production_index.as_mut()?;
+        let num_records = self.reader.header.num_records;
+        let entry = self
+            .cursor
+            .next_entry(&mut production_index.source)
+            .and_then(|entry| {
+                entry
+                    .map(|(_, record_number)| record_index(record_number))
+                    .transpose()
+            })
+            .and_then(|index| match index {
+                Some(index) if index >= num_records => Err(invalid_index(format!(
+                    "key points to record {} but the table has {} records",
+                    index + 1,
+                    num_records
+                ))),
+                index => Ok(index),
+            });
+        match entry {
+            Ok(Some(index)) => Some(self.reader.read_record_at_as::<R>(index)),
+            Ok(None) => None,
+            Err(kind) => {
+                self.cursor.stack.clear();
+                Some(Err(Error {
+                    record_num: 0,
+                    field: None,
+                    kind,
+                }))
+            }
+        }
+    }
+}
+
+impl<T: Read + Seek> Reader<T> {
+    /// Returns the tags of the production index opened with the table,
+    /// none if no index was opened
+    ///
+    /// See [ReadingOptions::production_index](struct.ReadingOptions.html#method.production_index).
+    pub fn index_tags(&self) -> &[IndexTag] {
+        self.production_index
+            .as_ref()
+            .map_or(&[], |index| index.tags())
+    }
+
+    /// Returns the production index opened with the table, if any
+    pub fn production_index(&mut self) -> Option<&mut ProductionIndex<T>> {
+        self.production_index.as_mut()
+    }
+
+    /// Returns an iterator over the records whose key in the tag of the production index
+    /// is in the given range, in the order of the tag
+    ///
+    /// The index must have been opened with the table.
+    pub fn read_tag_range<K, B>(
+        &mut self,
+        tag: &str,
+        bounds: B,
+    ) -> Result<TagRange<'_, T, Record>, Error>
+    where
+        K: Into<IndexKey> + Clone,
+        B: RangeBounds<K>,
+    {
+        self.read_tag_range_as::<Record, K, B>(tag, bounds)
+    }
+
+    /// Same as [read_tag_range](#method.read_tag_range) but the records are read as the
+    /// type you want.
+    pub fn read_tag_range_as<R, K, B>(
+        &mut self,
+        tag: &str,
+        bounds: B,
+    ) -> Result<TagRange<'_, T, R>, Error>
+    where
+        R: ReadableRecord,
+        K: Into<IndexKey> + Clone,
+        B: RangeBounds<K>,
+    {
+        let cursor = match self.production_index.as_mut() {
+            Some(index) => index.cursor(tag, bounds)?,
+            None => {
+                return Err(Error {
+                    record_num: 0,
+                    field: None,
+                    kind: invalid_index("no production index was opened with the table".to_owned()),
+                })
+            }
+        };
+        Ok(TagRange {
+            reader: self,
+            cursor,
+            record_type: PhantomData,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::convert::TryFrom;
+    use std::io::Cursor;
+
+    use crate::record::field::FieldValue;
+    use crate::record::FieldName;
+    use crate::writing::TableWriterBuilder;
+
+    /// Records of the tables of the tests: NAME (C, 10) and CODE (N, 5)
+    const RECORDS: [(&str, f64); 4] = [("ZOE", 30.0), ("ANN", -5.0), ("BOB", 12.0), ("BOBBY", 7.0)];
+
+    fn fields() -> Vec<FieldInfo> {
+        TableWriterBuilder::new()
+            .add_character_field(FieldName::try_from("NAME").unwrap(), 10)
+            .add_numeric_field(FieldName::try_from("CODE").unwrap(), 5, 0)
+            .v
+    }
+
+    fn put_u16(bytes: &mut [u8], offset: usize, value: u16) {
+        bytes[offset..offset + 2].copy_from_slice(&value.to_le_bytes());
+    }
+
+    fn put_u32(bytes: &mut [u8], offset: usize, value: u32) {
+        bytes[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+    }
+
+    fn character_key(name: &str, length: usize) -> Vec<u8> {
+        let mut key = name.as_bytes().to_vec();
+        key.resize(length, b' ');
+        key
+    }
+
+    fn cdx_header(root: u32, key_length: u16, options: u8, key_expression: &str) -> Vec<u8> {
+        let mut header = vec![0u8; CDX_HEADER_SIZE];
+        put_u32(&mut header, 0, root);
+        put_u16(&mut header, 12, key_length);
+        header[14] = options;
+        put_u16(&mut header, 510, key_expression.len() as u16 + 1);
+        header[512..512 + key_expression.len()].copy_from_slice(key_expression.as_bytes());
+        header
+    }
+
+    /// Leaf with 16 bits record numbers and 8 bits counts of duplicate and trailing bytes
+    fn cdx_leaf(keys: &[(Vec<u8>, u32)], fill: u8) -> Vec<u8> {
+        let mut node = vec![0u8; CDX_NODE_SIZE];
+        put_u16(&mut node, 0, 3);
+        put_u16(&mut node, 2, keys.len() as u16);
+        put_u32(&mut node, 14, 0xFFFF);
+        node[18] = 0xFF;
+        node[19] = 0xFF;
+        node[20] = 16;
+        node[21] = 8;
+        node[22] = 8;
+        node[23] = 4;
+        let mut previous: &[u8] = &[];
+        let mut keys_start = CDX_NODE_SIZE;
+        for (i, (key, record_number)) in keys.iter().enumerate() {
+            let duplicates = key.iter().zip(previous).take_while(|(a, b)| a == b).count();
+            let trailing = key[duplicates..]
+                .iter()
+                .rev()
+                .take_while(|b| **b == fill)
+                .count();
+            let stored = &key[duplicates..key.len() - trailing];
+            node[keys_start - stored.len()..keys_start].copy_from_slice(stored);
+            keys_start -= stored.len();
+            let info = record_number | (duplicates as u32) << 16 | (trailing as u32) << 24;
+            put_u32(&mut node, 24 + i * 4, info);
+            previous = key;
+        }
+        node
+    }
+
+    fn cdx_interior(children: &[(Vec<u8>, u32)]) -> Vec<u8> {
+        let mut node = vec![0u8; CDX_NODE_SIZE];
+        put_u16(&mut node, 2, children.len() as u16);
+        let mut offset = 12;
+        for (key, child) in children {
+            node[offset..offset + key.len()].copy_from_slice(key);
+            offset += key.len() + 4;
+            node[offset..offset + 4].copy_from_slice(&child.to_be_bytes());
+            offset += 4;
+        }
+        node
+    }
+
+    fn ordered_double(value: f64) -> Vec<u8> {
+        let bits = value.to_bits();
+        let bits = if value >= 0.0 {
+            bits | (1 << 63)
+        } else {
+            !bits
+        };
+        bits.to_be_bytes().to_vec()
+    }
+
+    fn cdx_file() -> Vec<u8> {
+        let name_key = |name: &str| character_key(name, 10);
+        let mut codes = RECORDS
+            .iter()
+            .enumerate()
+            .map(|(i, (_, code))| (*code, i as u32 + 1))
+            .collect::<Vec<_>>();
+        codes.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+        let code_keys = codes
+            .into_iter()
+            .map(|(code, record_number)| (ordered_double(code), record_number))
+            .collect::<Vec<_>>();
+
+        let mut file = cdx_header(1024, 10, 0xE0, "");
+        file.extend(cdx_leaf(
+            &[(name_key("CODE"), 1536), (name_key("NAME"), 2560)],
+            b' ',
+        ));
+        file.extend(cdx_header(4096, 8, 0x60, "CODE"));
+        file.extend(cdx_header(3584, 10, 0x60, "NAME"));
+        file.extend(cdx_interior(&[
+            (name_key("BOB"), 4608),
+            (name_key("ZOE"), 5120),
+        ]));
+        file.extend(cdx_leaf(&code_keys, 0));
+        file.extend(cdx_leaf(
+            &[(name_key("ANN"), 2), (name_key("BOB"), 3)],
+            b' ',
+        ));
+        file.extend(cdx_leaf(
+            &[(name_key("BOBBY"), 4), (name_key("ZOE"), 1)],
+            b' ',
+        ));
+        file
+    }
+
+    fn bcd(value: f64) -> Vec<u8> {
+        let text = format!("{}", value.abs());
+        let integer_digits = text.split('.').next().unwrap().len();
+        let digits = text.replace('.', "");
+        let mut key = vec![0x34 + integer_digits as u8, (digits.len() as u8) << 2];
+        if value < 0.0 {
+            key[1] |= 0x80;
+        }
+        for pair in digits.as_bytes().chunks(2) {
+            let low = pair.get(1).map_or(0, |digit| digit - b'0');
+            key.push((pair[0] - b'0') << 4 | low);
+        }
+        key.resize(12, 0);
+        key
+    }
+
+    /// .mdx file with blocks of 2 pages
+    fn mdx_file() -> Vec<u8> {
+        let mut file = vec![0u8; 6 * 1024];
+        file[0] = 2;
+        put_u16(&mut file, 20, 2);
+        put_u16(&mut file, 22, 1024);
+        file[26] = 32;
+        put_u16(&mut file, 28, 2);
+        for (i, (name, page)) in [("NAME", 2), ("CODE", 3)].iter().enumerate() {
+            let entry = MDX_HEADER_SIZE + i * MDX_TAG_ENTRY_SIZE;
+            put_u32(&mut file, entry, *page);
+            file[entry + 4..entry + 4 + name.len()].copy_from_slice(name.as_bytes());
+        }
+        for (page, root, key_type, key_length, expression) in
+            [(2, 4, b'C', 10, "NAME"), (3, 10, b'N', 12, "CODE")].iter()
+        {
+            let header = page * MDX_PAGE_SIZE;
+            put_u32(&mut file, header, *root);
+            file[header + 9] = *key_type;
+            put_u16(&mut file, header + 12, *key_length);
+            put_u16(&mut file, header + 18, 16);
+            file[header + 24..header + 24 + expression.len()]
+                .copy_from_slice(expression.as_bytes());
+        }
+
+        let mut put_node = |page: usize, entries: &[(u32, Vec<u8>)], last_child: u32| {
+            let node = page * MDX_PAGE_SIZE;
+            put_u32(&mut file, node, entries.len() as u32);
+            for (i, (pointer, key)) in entries.iter().enumerate() {
+                let entry = node + 8 + i * 16;
+                put_u32(&mut file, entry, *pointer);
+                file[entry + 4..entry + 4 + key.len()].copy_from_slice(key);
+            }
+            put_u32(&mut file, node + 8 + entries.len() * 16, last_child);
+        };
+        let name_key = |name: &str| character_key(name, 10);
+        put_node(4, &[(6, name_key("BOB"))], 8);
+        put_node(6, &[(2, name_key("ANN")), (3, name_key("BOB"))], 0);
+        put_node(8, &[(4, name_key("BOBBY")), (1, name_key("ZOE"))], 0);
+        put_node(
+            10,
+            &[
+                (2, bcd(-5.0)),
+                (4, bcd(7.0)),
+                (3, bcd(12.0)),
+                (1, bcd(30.0)),
+            ],
+            0,
+        );
+        file
+    }
+
+    fn check_tags<T: Read + Seek>(index: &mut ProductionIndex<T>) {
+        let mut names = index.tags().iter().map(IndexTag::name).collect::<Vec<_>>();
+        names.sort_unstable();
+        assert_eq!(names, vec!["CODE", "NAME"]);
+        let name_tag = index.tag("name").unwrap();
+        assert_eq!(name_tag.key_expression(), "NAME");
+        assert_eq!(name_tag.key_type(), KeyType::Character);
+        assert_eq!(index.tag("CODE").unwrap().key_type(), KeyType::Numeric);
+
+        assert_eq!(
+            index.record_indices::<&str, _>("NAME", ..).unwrap(),
+            vec![1, 2, 3, 0]
+        );
+        assert_eq!(index.record_indices("NAME", "B"..="B").unwrap(), vec![2, 3]);
+        assert_eq!(index.record_indices("NAME", "C"..).unwrap(), vec![0]);
+        assert_eq!(index.record_indices("CODE", 0.0..).unwrap(), vec![3, 2, 0]);
+        assert_eq!(
+            index.record_indices("CODE", -10.0..=7.0).unwrap(),
+            vec![1, 3]
+        );
+        assert!(index.record_indices("CODE", "A"..).is_err());
+        assert!(index.record_indices("PRICE", "A"..).is_err());
+    }
+
+    #[test]
+    fn cdx_tags() {
+        let mut index = ProductionIndex::new(
+            Cursor::new(cdx_file()),
+            ProductionIndexFormat::Cdx,
+            &fields(),
+        )
+        .unwrap();
+        assert_eq!(index.tag("NAME").unwrap().for_expression(), None);
+        check_tags(&mut index);
+    }
+
+    #[test]
+    fn mdx_tags() {
+        assert_eq!(bcd_value(&bcd(-123.5)), -123.5);
+        let mut index = ProductionIndex::new(
+            Cursor::new(mdx_file()),
+            ProductionIndexFormat::Mdx,
+            &fields(),
+        )
+        .unwrap();
+        check_tags(&mut index);
+    }
+
+    #[test]
+    fn open_with_the_table() {
+        let records = RECORDS
+            .iter()
+            .map(|(name, code)| {
+                let mut record = Record::default();
+                record.insert(
+                    "NAME".to_owned(),
+                    FieldValue::Character(Some(name.to_string())),
+                );
+                record.insert("CODE".to_owned(), FieldValue::Numeric(Some(*code)));
+                record
+            })
+            .collect::<Vec<_>>();
+        let path = std::env::temp_dir().join("dbase_production_index.dbf");
+        let mut table = TableWriterBuilder::new()
+            .add_character_field(FieldName::try_from("NAME").unwrap(), 10)
+            .add_numeric_field(FieldName::try_from("CODE").unwrap(), 5, 0)
+            .build_with_dest(Cursor::new(Vec::<u8>::new()))
+            .write(&records)
+            .unwrap()
+            .into_inner();
+        // The flag of the production index
+        table[28] |= 0x01;
+        std::fs::write(&path, table).unwrap();
+        std::fs::write(path.with_extension("mdx"), mdx_file()).unwrap();
+
+        let reader = Reader::from_path(&path).unwrap();
+        assert!(reader.index_tags().is_empty());
+
+        let options = crate::ReadingOptions::new().production_index(true);
+        let mut reader = Reader::from_path_with_options(&path, options).unwrap();
+        assert_eq!(reader.index_tags().len(), 2);
+        let names = reader
+            .read_tag_range("NAME", "B"..="B")
+            .unwrap()
+            .map(|record| record.unwrap().get("NAME").cloned())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            vec![
+                Some(FieldValue::Character(Some("BOB".to_owned()))),
+                Some(FieldValue::Character(Some("BOBBY".to_owned())))
+            ]
+        );
+    }
+}
@@ -0,0 +1,745 @@
+//! Module with the definition of the reader that does not need the standard library
+//!
+//! The [RawReader](struct.RawReader.html) is the reader of the crate built on `no_std`
+//! targets (the `std` feature disabled), as long as they have an allocator:
+//! it reads the records of a table from a [ByteSource](trait.ByteSource.html),
+//! from the start to the end.
+//!
+//! The header, the fields descriptors and the values are parsed by the same functions
+//! as for the [Reader](struct.Reader.html) of the standard library, which reads
+//! through an [IoSource](struct.IoSource.html).
+use alloc::borrow::ToOwned;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::encoding::Encoding;
+use crate::encryption::decrypt_record;
+use crate::error::{Error, ErrorKind};
+use crate::header::{Header, Version};
+use crate::options::{FieldNameCase, FieldNamePolicy, Flavor, ReadingOptions};
+use crate::record::field::{FieldType, FieldValue};
+use crate::record::{
+    field_name_issues, field_names_match, rename_duplicated_fields, FieldInfo, FieldNameIssue,
+};
+
+/// Value of the byte between the last RecordFieldInfo and the first record
+pub(crate) const TERMINATOR_VALUE: u8 = 0x0D;
+
+/// Size of the path to the database container in Visual FoxPro tables
+pub(crate) const BACKLINK_SIZE: u16 = 263;
+
+/// Minimal source of bytes, the tables read by the [RawReader](struct.RawReader.html)
+/// are read from it from the start to the end
+///
+/// It is implemented for byte slices, and for the readers of the standard library
+/// wrapped in an [IoSource](struct.IoSource.html). The errors of the source
+/// are converted to the [ErrorKind](enum.ErrorKind.html) of the errors of the reader.
+pub trait ByteSource {
+    /// The error of the source
+    type Error: Into<ErrorKind>;
+
+    /// Fills the buffer with the next bytes of the source,
+    /// fails if the source ends before the buffer is filled
+    fn read_bytes(&mut self, buffer: &mut [u8]) -> Result<(), Self::Error>;
+}
+
+/// Error of the byte slices that end before the bytes asked for,
+/// it is an [EndOfSource](enum.ErrorKind.html#variant.EndOfSource) error of the reader
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct EndOfSource;
+
+impl fmt::Display for EndOfSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "the source ended before the end of the table")
+    }
+}
+
+impl ByteSource for &[u8] {
+    type Error = EndOfSource;
+
+    fn read_bytes(&mut self, buffer: &mut [u8]) -> Result<(), EndOfSource> {
+        if self.len() < buffer.len() {
+            return Err(EndOfSource);
+        }
+        let (bytes, rest) = self.split_at(buffer.len());
+        buffer.copy_from_slice(bytes);
+        *self = rest;
+        Ok(())
+    }
+}
+
+impl<S: ByteSource + ?Sized> ByteSource for &mut S {
+    type Error = S::Error;
+
+    fn read_bytes(&mut self, buffer: &mut [u8]) -> Result<(), S::Error> {
+        (**self).read_bytes(buffer)
+    }
+}
+
+/// [ByteSource](trait.ByteSource.html) reading from a `std::io::Read`
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub struct IoSource<R>(pub R);
+
+#[cfg(feature = "std")]
+impl<R: std::io::Read> ByteSource for IoSource<R> {
+    type Error = std::io::Error;
+
+    fn read_bytes(&mut self, buffer: &mut [u8]) -> Result<(), std::io::Error> {
+        self.0.read_exact(buffer)
+    }
+}
+
+/// Source whose errors are converted to the [ErrorKind](enum.ErrorKind.html) of the reader,
+/// for the parsers that also fail for other reasons than the source
+pub(crate) struct KindSource<'a, S: ?Sized>(pub(crate) &'a mut S);
+
+impl<S: ByteSource + ?Sized> ByteSource for KindSource<'_, S> {
+    type Error = ErrorKind;
+
+    fn read_bytes(&mut self, buffer: &mut [u8]) -> Result<(), ErrorKind> {
+        self.0.read_bytes(buffer).map_err(Into::into)
+    }
+}
+
+/// Reads the next bytes of the source
+pub(crate) fn read_array<S: ByteSource + ?Sized, const N: usize>(
+    source: &mut S,
+) -> Result<[u8; N], S::Error> {
+    let mut bytes = [0u8; N];
+    source.read_bytes(&mut bytes)?;
+    Ok(bytes)
+}
+
+pub(crate) fn read_u8<S: ByteSource + ?Sized>(source: &mut S) -> Result<u8, S::Error> {
+    let [byte] = read_array(source)?;
+    Ok(byte)
+}
+
+/// Reads and drops the next bytes of the source, which cannot seek
+pub(crate) fn skip<S: ByteSource + ?Sized>(
+    source: &mut S,
+    mut num_bytes: usize,
+) -> Result<(), S::Error> {
+    let mut buffer = [0u8; 64];
+    while num_bytes > 0 {
+        let length = num_bytes.min(buffer.len());
+        source.read_bytes(&mut buffer[..length])?;
+        num_bytes -= length;
+    }
+    Ok(())
+}
+
+/// Source giving bytes that were already read before the ones of the source
+struct Chain<'a, S: ?Sized> {
+    first: &'a [u8],
+    second: &'a mut S,
+}
+
+impl<S: ByteSource + ?Sized> ByteSource for Chain<'_, S> {
+    type Error = S::Error;
+
+    fn read_bytes(&mut self, buffer: &mut [u8]) -> Result<(), S::Error> {
+        let length = self.first.len().min(buffer.len());
+        let (first, second) = buffer.split_at_mut(length);
+        first.copy_from_slice(&self.first[..length]);
+        self.first = &self.first[length..];
+        self.second.read_bytes(second)
+    }
+}
+
+/// Source counting the bytes read from it
+struct Counted<'a, S: ?Sized> {
+    source: &'a mut S,
+    num_bytes: usize,
+}
+
+impl<S: ByteSource + ?Sized> ByteSource for Counted<'_, S> {
+    type Error = S::Error;
+
+    fn read_bytes(&mut self, buffer: &mut [u8]) -> Result<(), S::Error> {
+        self.source.read_bytes(buffer)?;
+        self.num_bytes += buffer.len();
+        Ok(())
+    }
+}
+
+/// Error of the header or of the fields information, before the first record
+fn table_error<E: Into<ErrorKind>>(error: E) -> Error {
+    Error {
+        record_num: 0,
+        field: None,
+        kind: error.into(),
+    }
+}
+
+/// Reads the header, the fields information and the backlink of a table,
+/// the source is left positioned after the fields information (and backlink).
+///
+/// The names of the fields are decoded with the encoding.
+///
+/// Fails if the fields descriptors do not end with their terminator
+/// before the offset to the first record.
+pub(crate) fn read_table_info<S: ByteSource + ?Sized>(
+    source: &mut S,
+    flavor: Flavor,
+    encoding: &dyn Encoding,
+) -> Result<(Header, Vec<FieldInfo>, Option<String>), Error> {
+    let (header, fields_info, backlink, has_terminator) =
+        read_table_info_tolerant(source, flavor, encoding)?;
+    if !has_terminator {
+        return Err(table_error(ErrorKind::Message(
+            "the fields descriptors do not end with their terminator".to_owned(),
+        )));
+    }
+    Ok((header, fields_info, backlink))
+}
+
+/// Same as read_table_info, for damaged tables: the missing terminator
+/// of the fields descriptors is not an error, the last value returned
+/// tells whether it was found.
+#[allow(clippy::type_complexity)]
+pub(crate) fn read_table_info_tolerant<S: ByteSource + ?Sized>(
+    source: &mut S,
+    flavor: Flavor,
+    encoding: &dyn Encoding,
+) -> Result<(Header, Vec<FieldInfo>, Option<String>, bool), Error> {
+    let header_bytes: [u8; Header::SIZE] = read_array(source).map_err(table_error)?;
+    let mut header = Header::read_from_source(&mut &header_bytes[..]).map_err(table_error)?;
+    if let Version::DBase2 = header.file_type {
+        let mut descriptors = Chain {
+            first: &header_bytes[Header::DBASE2_FIELDS_OFFSET..],
+            second: source,
+        };
+        let fields_info = read_dbase2_fields_info(&mut descriptors, encoding)?;
+        return Ok((header, fields_info, None, true));
+    }
+
+    // The descriptors end with the terminator, the offset to the first record
+    // only gives an upper bound as other data may follow (eg: the VFP backlink)
+    let is_dbase7 = header.file_type.has_dbase7_layout();
+    let (header_size, descriptor_size) = if is_dbase7 {
+        header.read_dbase7_extension(source).map_err(table_error)?;
+        (Header::DBASE7_SIZE, FieldInfo::DBASE7_SIZE)
+    } else {
+        (Header::SIZE, FieldInfo::SIZE)
+    };
+    let terminator_size = match flavor {
+        Flavor::Standard => core::mem::size_of::<u8>(),
+        Flavor::Clipper => 0,
+    };
+    let max_num_fields = (header.offset_to_first_record as usize)
+        .saturating_sub(header_size + terminator_size)
+        / descriptor_size;
+
+    let mut fields_info = Vec::<FieldInfo>::with_capacity(max_num_fields + 1);
+    fields_info.push(FieldInfo::new_deletion_flag());
+    let mut position = header_size;
+    let mut has_terminator = flavor == Flavor::Clipper;
+    for _ in 0..max_num_fields {
+        let first_byte = read_u8(source).map_err(table_error)?;
+        position += core::mem::size_of::<u8>();
+        if first_byte == TERMINATOR_VALUE || (flavor == Flavor::Clipper && first_byte == 0) {
+            has_terminator = true;
+            break;
+        }
+        let first_byte = [first_byte];
+        let mut descriptor = Chain {
+            first: &first_byte[..],
+            second: &mut *source,
+        };
+        let info = if is_dbase7 {
+            FieldInfo::read_dbase7_from(&mut descriptor, encoding)
+        } else {
+            FieldInfo::read_from(&mut descriptor, encoding)
+        };
+        let mut info = info.map_err(table_error)?;
+        if flavor == Flavor::Clipper && info.field_type == FieldType::Character {
+            let length_low = info.field_length as u8;
+            info.field_length = u16::from_le_bytes([length_low, info.num_decimal_places]);
+            info.num_decimal_places = 0;
+        }
+        position += descriptor_size - core::mem::size_of::<u8>();
+        fields_info.push(info);
+    }
+    if fields_info.len() == max_num_fields + 1 && flavor == Flavor::Standard {
+        let terminator = read_u8(source).map_err(table_error)?;
+        position += core::mem::size_of::<u8>();
+        has_terminator = terminator == TERMINATOR_VALUE;
+    }
+
+    let backlink = if header.file_type.has_backlink() {
+        let backlink_size = (header.offset_to_first_record as usize)
+            .saturating_sub(position)
+            .min(usize::from(BACKLINK_SIZE));
+        let mut backlink = vec![0u8; backlink_size];
+        source.read_bytes(&mut backlink).map_err(table_error)?;
+        let backlink = String::from_utf8_lossy(&backlink)
+            .trim_end_matches('\u{0}')
+            .to_owned();
+        if backlink.is_empty() {
+            None
+        } else {
+            Some(backlink)
+        }
+    } else {
+        None
+    };
+
+    Ok((header, fields_info, backlink, has_terminator))
+}
+
+/// Reads the field descriptors of a dBase II table, up to their terminator
+fn read_dbase2_fields_info<S: ByteSource + ?Sized>(
+    source: &mut S,
+    encoding: &dyn Encoding,
+) -> Result<Vec<FieldInfo>, Error> {
+    // dBase II tables have at most 32 fields
+    const MAX_NUM_FIELDS: usize = 32;
+
+    let mut fields_info = vec![FieldInfo::new_deletion_flag()];
+    for _ in 0..MAX_NUM_FIELDS {
+        let first_byte = read_u8(source).map_err(table_error)?;
+        if first_byte == TERMINATOR_VALUE {
+            break;
+        }
+        let mut descriptor = Chain {
+            first: &[first_byte][..],
+            second: &mut *source,
+        };
+        let info = FieldInfo::read_dbase2_from(&mut descriptor, encoding).map_err(table_error)?;
+        fields_info.push(info);
+    }
+    Ok(fields_info)
+}
+
+/// Converts the names of the fields to the case of the options, checks them
+/// and applies the policy of the options, returns the problems found
+pub(crate) fn check_field_names(
+    fields_info: &mut [FieldInfo],
+    options: &ReadingOptions,
+) -> Result<Vec<FieldNameIssue>, Error> {
+    for field_info in fields_info.iter_mut().filter(|info| !info.is_deletion_flag()) {
+        match options.field_name_case {
+            FieldNameCase::Unchanged => {}
+            FieldNameCase::Upper => field_info.name = field_info.name.to_uppercase(),
+            FieldNameCase::Lower => field_info.name = field_info.name.to_lowercase(),
+        }
+    }
+    let issues = field_name_issues(fields_info);
+    match options.field_name_policy {
+        FieldNamePolicy::Warn => {}
+        FieldNamePolicy::RenameDuplicates => rename_duplicated_fields(fields_info),
+        FieldNamePolicy::Fail => {
+            if let Some(issue) = issues.first() {
+                return Err(table_error(ErrorKind::InvalidFieldName(issue.clone())));
+            }
+        }
+    }
+    Ok(issues)
+}
+
+/// Fails when the header marks the table as encrypted, as the records
+/// would be read as garbage
+pub(crate) fn check_encryption(header: &Header) -> Result<(), Error> {
+    if header.is_encrypted() {
+        return Err(table_error(ErrorKind::EncryptedTable));
+    }
+    Ok(())
+}
+
+/// Reader of the records of a table, that does not need the standard library
+///
+/// The tables and their values are read like the [Reader](struct.Reader.html) reads them,
+/// with the same [ReadingOptions](struct.ReadingOptions.html): the dBase II and dBase 7
+/// layouts, the Clipper flavor, the encodings of the fields, the trimming and the policies
+/// for blanks and invalid dates apply. There are no memo files, the memo fields
+/// follow the [MissingMemoPolicy](enum.MissingMemoPolicy.html) of the options.
+///
+/// # Example
+///
+/// ```
+/// # fn main() -> Result<(), dbase::Error> {
+/// // The bytes received over a serial link, for instance
+/// let bytes = std::fs::read("tests/data/stations.dbf").unwrap();
+/// let mut reader = dbase::RawReader::new(bytes.as_slice())?;
+/// assert_eq!(reader.fields()[0].name(), "name");
+/// while let Some(record) = reader.next_record() {
+///     let record = record?;
+///     if let Some(dbase::FieldValue::Character(Some(name))) = record.get("name").transpose()? {
+///         println!("{}", name);
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct RawReader<S: ByteSource> {
+    source: S,
+    header: Header,
+    /// The fields of the table, the deletion flag first
+    fields_info: Vec<FieldInfo>,
+    /// Offset of each field in the records
+    field_offsets: Vec<usize>,
+    options: ReadingOptions,
+    field_name_issues: Vec<FieldNameIssue>,
+    next_record: u32,
+    /// The bytes of the last record read, the deletion flag first
+    record: Vec<u8>,
+}
+
+impl<S: ByteSource> RawReader<S> {
+    /// Creates a reader of the table the source starts with, whose header
+    /// and fields descriptors are read right away
+    pub fn new(source: S) -> Result<Self, Error> {
+        Self::with_options(source, ReadingOptions::default())
+    }
+
+    /// Same as [new](#method.new), the table being read with the given options
+    pub fn with_options(mut source: S, options: ReadingOptions) -> Result<Self, Error> {
+        let mut counted = Counted {
+            source: &mut source,
+            num_bytes: 0,
+        };
+        let (header, mut fields_info, _) =
+            read_table_info(&mut counted, options.flavor, &*options.encoding)?;
+        let position = counted.num_bytes;
+        if options.cipher.is_none() {
+            check_encryption(&header)?;
+        }
+        let field_name_issues = check_field_names(&mut fields_info, &options)?;
+
+        // The source cannot seek, the bytes up to the first record are skipped
+        let num_skipped = usize::from(header.offset_to_first_record).saturating_sub(position);
+        skip(&mut source, num_skipped).map_err(table_error)?;
+        // Like for the Reader, the size of the records is the sum of the lengths of the fields
+        let field_offsets = fields_info
+            .iter()
+            .scan(0, |offset, field_info| {
+                let field_offset = *offset;
+                *offset += usize::from(field_info.field_length);
+                Some(field_offset)
+            })
+            .collect::<Vec<usize>>();
+        let record_size = fields_info
+            .iter()
+            .map(|field_info| usize::from(field_info.field_length))
+            .sum();
+        Ok(Self {
+            source,
+            header,
+            fields_info,
+            field_offsets,
+            options,
+            field_name_issues,
+            next_record: 0,
+            record: vec![0u8; record_size],
+        })
+    }
+
+    /// Returns the header of the table
+    pub fn header(&self) -> &Header {
+        &self.header
+    }
+
+    /// Returns the fields of the table, the deletion flag excluded
+    pub fn fields(&self) -> &[FieldInfo] {
+        &self.fields_info[1..]
+    }
+
+    /// Returns the problems with the names of the fields,
+    /// what was done of them depends on the [FieldNamePolicy](enum.FieldNamePolicy.html)
+    pub fn field_name_issues(&self) -> &[FieldNameIssue] {
+        &self.field_name_issues
+    }
+
+    /// Reads the next record, `None` once all the records of the header were read
+    ///
+    /// The records marked as deleted are returned too,
+    /// see [is_deleted](struct.RawRecord.html#method.is_deleted).
+    pub fn next_record(&mut self) -> Option<Result<RawRecord<'_>, Error>> {
+        if self.next_record >= self.header.num_records {
+            return None;
+        }
+        let index = self.next_record;
+        if let Err(error) = self.source.read_bytes(&mut self.record) {
+            self.next_record = self.header.num_records;
+            return Some(Err(Error {
+                record_num: index as usize,
+                field: None,
+                kind: error.into(),
+            }));
+        }
+        self.next_record += 1;
+        if self.header.is_encrypted() {
+            let cipher = self.options.cipher.as_deref();
+            decrypt_record(cipher, index as usize, &mut self.record);
+        }
+        Some(Ok(RawRecord {
+            fields_info: &self.fields_info[1..],
+            field_offsets: &self.field_offsets[1..],
+            options: &self.options,
+            index,
+            bytes: &self.record,
+        }))
+    }
+
+    /// Returns the source, positioned after the last record read
+    pub fn into_inner(self) -> S {
+        self.source
+    }
+}
+
+/// A record read by the [RawReader](struct.RawReader.html), borrowed from its buffer
+///
+/// The values are parsed when asked for.
+pub struct RawRecord<'a> {
+    fields_info: &'a [FieldInfo],
+    field_offsets: &'a [usize],
+    options: &'a ReadingOptions,
+    /// Index of the record in the table (0-based)
+    index: u32,
+    bytes: &'a [u8],
+}
+
+impl<'a> RawRecord<'a> {
+    /// Returns whether the record is marked as deleted
+    pub fn is_deleted(&self) -> bool {
+        self.bytes[0] == b'*'
+    }
+
+    /// Returns the bytes of the record, the deletion flag first
+    pub fn bytes(&self) -> &'a [u8] {
+        self.bytes
+    }
+
+    /// Returns the bytes of the field at the given index, as they are in the record
+    pub fn field_bytes(&self, index: usize) -> Option<&'a [u8]> {
+        let field_info = self.fields_info.get(index)?;
+        let start = self.field_offsets[index];
+        Some(&self.bytes[start..start + usize::from(field_info.field_length)])
+    }
+
+    /// Parses the value of the field at the given index, `None` if there is no such field
+    pub fn value(&self, index: usize) -> Option<Result<FieldValue, Error>> {
+        let mut bytes = self.field_bytes(index)?;
+        let field_info = &self.fields_info[index];
+        let value = FieldValue::read_from_source(&mut bytes, field_info, self.options);
+        Some(value.map_err(|kind| Error {
+            record_num: self.index as usize,
+            field: Some(field_info.clone()),
+            kind,
+        }))
+    }
+
+    /// Parses the value of the field with the given name,
+    /// names are compared ignoring the case, like dBase does
+    pub fn get(&self, name: &str) -> Option<Result<FieldValue, Error>> {
+        let index = self
+            .fields_info
+            .iter()
+            .position(|field_info| field_names_match(field_info.name(), name))?;
+        self.value(index)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[cfg(feature = "std")]
+    use std::convert::TryFrom;
+    #[cfg(feature = "std")]
+    use std::io::Cursor;
+
+    use crate::Date;
+    #[cfg(feature = "std")]
+    use crate::{
+        BlankPolicy, FieldName, InvalidDatePolicy, MissingMemoPolicy, Reader, TableWriterBuilder,
+        TrimOption,
+    };
+
+    /// Reads the values of all the records, like the Reader does
+    #[cfg(feature = "std")]
+    fn read_values(reader: &mut RawReader<&[u8]>) -> Vec<Vec<(String, FieldValue)>> {
+        let mut records = vec![];
+        while let Some(record) = reader.next_record() {
+            let record = record.unwrap();
+            let values = (0..record.fields_info.len())
+                .map(|index| {
+                    let name = record.fields_info[index].name().to_owned();
+                    (name, record.value(index).unwrap().unwrap())
+                })
+                .collect();
+            records.push(values);
+        }
+        records
+    }
+
+    #[test]
+    fn stations() {
+        let table: &[u8] = include_bytes!("../tests/data/stations.dbf");
+        let mut reader = RawReader::new(table).unwrap();
+        assert_eq!(reader.header().num_records, 6);
+        let names = reader
+            .fields()
+            .iter()
+            .map(FieldInfo::name)
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["name", "marker-col", "marker-sym", "line"]);
+        assert_eq!(reader.fields()[0].field_type(), FieldType::Character);
+        assert_eq!(reader.fields()[0].length(), 254);
+        assert_eq!(reader.field_name_issues().len(), 2);
+
+        let record = reader.next_record().unwrap().unwrap();
+        assert!(!record.is_deleted());
+        assert_eq!(
+            record.get("NAME").unwrap().unwrap(),
+            FieldValue::Character(Some(String::from("Van Dorn Street")))
+        );
+        assert_eq!(record.field_bytes(3).unwrap(), &record.bytes()[763..1017]);
+        assert_eq!(
+            record.value(3).unwrap().unwrap(),
+            FieldValue::Character(Some(String::from("blue")))
+        );
+        assert!(record.value(4).is_none());
+        let mut num_records = 1;
+        while let Some(record) = reader.next_record() {
+            record.unwrap();
+            num_records += 1;
+        }
+        assert_eq!(num_records, 6);
+        assert_eq!(reader.into_inner(), &[0x1A]);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn same_values_as_the_reader() {
+        for path in [
+            "tests/data/line.dbf",
+            "tests/data/stations.dbf",
+            "tests/data/contain_none_float.dbf",
+        ] {
+            let table = std::fs::read(path).unwrap();
+            let options = ReadingOptions::default()
+                .character_trim(Some(TrimOption::End))
+                .blank_policy(BlankPolicy::Empty);
+            let records = Reader::with_options(Cursor::new(&table), options.clone())
+                .unwrap()
+                .read()
+                .unwrap();
+            let mut reader = RawReader::with_options(table.as_slice(), options).unwrap();
+            let raw_records = read_values(&mut reader);
+            assert_eq!(raw_records.len(), records.len());
+            for (raw_record, record) in raw_records.iter().zip(&records) {
+                for (name, value) in raw_record {
+                    assert_eq!(Some(value), record.get(name), "{}", path);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn dbase2_tables() {
+        let mut table = vec![0x02, 1, 0, 3, 14, 85, 16, 0];
+        let mut descriptor = [0u8; 16];
+        descriptor[..4].copy_from_slice(b"NAME");
+        descriptor[11] = b'C';
+        descriptor[12] = 10;
+        table.extend_from_slice(&descriptor);
+        table.push(TERMINATOR_VALUE);
+        table.resize(usize::from(Header::DBASE2_SIZE), 0);
+        table.extend_from_slice(b" Dupont    ");
+        let mut reader = RawReader::new(table.as_slice()).unwrap();
+        assert_eq!(reader.header().last_update, Date::new(14, 3, 1985));
+        let record = reader.next_record().unwrap().unwrap();
+        assert_eq!(
+            record.get("name").unwrap().unwrap(),
+            FieldValue::Character(Some(String::from("Dupont")))
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn clipper_tables() {
+        let mut builder = TableWriterBuilder::new()
+            .add_character_field(FieldName::try_from("TEXT").unwrap(), 10);
+        builder.v[0].field_length = 300;
+        let table = builder
+            .build_with_dest(Cursor::new(Vec::<u8>::new()))
+            .write(&[crate::Record::builder().set("TEXT", "x".repeat(300)).build()])
+            .unwrap()
+            .into_inner();
+        let options = ReadingOptions::default().flavor(Flavor::Clipper);
+        let mut reader = RawReader::with_options(table.as_slice(), options).unwrap();
+        assert_eq!(reader.fields()[0].length(), 300);
+        assert_eq!(
+            read_values(&mut reader)[0][0].1,
+            FieldValue::Character(Some("x".repeat(300)))
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn dates_and_memos() {
+        let mut table = TableWriterBuilder::new()
+            .add_date_field(FieldName::try_from("DAY").unwrap())
+            .add_character_field(FieldName::try_from("NOTES").unwrap(), 10)
+            .build_with_dest(Cursor::new(Vec::<u8>::new()))
+            .write(&[crate::Record::builder()
+                .set("DAY", Date::new(29, 2, 2024))
+                .set("NOTES", "         1")
+                .build()])
+            .unwrap()
+            .into_inner();
+        // The second field is made a memo field, whose memo file is missing
+        table[Header::SIZE + FieldInfo::SIZE + 11] = b'M';
+        let mut reader = RawReader::new(table.as_slice()).unwrap();
+        let record = reader.next_record().unwrap().unwrap();
+        assert_eq!(
+            record.value(0).unwrap().unwrap(),
+            FieldValue::Date(Some(Date::new(29, 2, 2024)))
+        );
+        assert!(matches!(
+            record.value(1).unwrap().unwrap_err().kind(),
+            ErrorKind::MissingMemoFile
+        ));
+
+        // The 30th of February
+        let day_start = usize::from(reader.header().offset_to_first_record) + 1 + 6;
+        table[day_start..day_start + 2].copy_from_slice(b"30");
+        let options = ReadingOptions::default()
+            .invalid_date_policy(InvalidDatePolicy::Null)
+            .missing_memo_policy(MissingMemoPolicy::Empty);
+        let mut reader = RawReader::with_options(table.as_slice(), options).unwrap();
+        let record = reader.next_record().unwrap().unwrap();
+        assert_eq!(record.value(0).unwrap().unwrap(), FieldValue::Date(None));
+        assert_eq!(
+            record.value(1).unwrap().unwrap(),
+            FieldValue::Memo(String::new())
+        );
+        let options = ReadingOptions::default().invalid_date_policy(InvalidDatePolicy::Fail);
+        let mut reader = RawReader::with_options(table.as_slice(), options).unwrap();
+        let record = reader.next_record().unwrap().unwrap();
+        assert!(matches!(
+            record.value(0).unwrap().unwrap_err().kind(),
+            ErrorKind::InvalidDate(_)
+        ));
+    }
+
+    #[test]
+    fn truncated_tables() {
+        let table: &[u8] = include_bytes!("../tests/data/line.dbf");
+        assert!(matches!(
+            RawReader::new(&table[..40]).err().unwrap().kind(),
+            ErrorKind::EndOfSource
+        ));
+        let mut reader = RawReader::new(&table[..table.len() - 10]).unwrap();
+        assert!(matches!(
+            reader.next_record().unwrap().err().unwrap().kind(),
+            ErrorKind::EndOfSource
+        ));
+        assert!(reader.next_record().is_none());
+    }
+}
@@ -6,20 +6,255 @@ use std::collections::hash_map::RandomState;
 use std::collections::HashMap;
 use std::convert::TryFrom;
 use std::fs::File;
-use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::hash::{Hash, Hasher};
+use std::io::{BufReader, Cursor, Read, Seek, SeekFrom};
 use std::iter::FusedIterator;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
+use crate::encoding::{Encoding, UnicodeLossy};
+use crate::encryption::{decrypt_record, RecordCipher};
 use crate::error::{Error, ErrorKind, FieldIOError};
 use crate::header::Header;
-use crate::record::field::{FieldType, FieldValue, MemoFileType, MemoReader};
-use crate::record::FieldInfo;
+use crate::production_index::{production_index_path, ProductionIndex};
+use crate::raw::{self, IoSource};
+use crate::record::field::{
+    read_memo_bytes, FieldType, FieldValue, MemoFileType, MemoFormat, MemoHandle, MemoReader,
+    MemoStream,
+};
+use crate::record::{field_names_match, FieldInfo, FieldNameIssue};
+use crate::stream::Capability;
 use crate::FieldConversionError;
 
-/// Value of the byte between the last RecordFieldInfo and the first record
-pub(crate) const TERMINATOR_VALUE: u8 = 0x0D;
+pub use crate::options::{
+    BlankPolicy, ErrorPolicy, FieldNameCase, FieldNamePolicy, Flavor, InvalidDatePolicy,
+    MissingMemoPolicy, ReadingOptions, TrimOption,
+};
+pub(crate) use crate::raw::{
+    check_encryption, check_field_names, BACKLINK_SIZE, TERMINATOR_VALUE,
+};
 
-const BACKLINK_SIZE: u16 = 263;
+impl ReadingOptions {
+    /// Returns the layout of the memo file of the table with the header,
+    /// `None` if the table cannot have a memo file
+    pub(crate) fn memo_file_type(&self, header: &Header) -> Option<MemoFileType> {
+        self.memo_format
+            .map(MemoFileType::from)
+            .or_else(|| header.file_type.supported_memo_type())
+    }
+
+    /// Returns whether the table is opened anyway when the memo file cannot be found
+    pub(crate) fn tolerates_missing_memo(&self, error: &std::io::Error) -> bool {
+        self.missing_memo_policy != MissingMemoPolicy::Fail
+            && error.kind() == std::io::ErrorKind::NotFound
+    }
+
+    /// Creates the reader of the memo file, which tells its own layout
+    /// unless one was given
+    pub(crate) fn open_memo<M: Read + Seek>(
+        &self,
+        memo_type: MemoFileType,
+        memo_source: M,
+    ) -> std::io::Result<MemoReader<M>> {
+        if self.memo_format.is_some() {
+            MemoReader::of_type(memo_type, memo_source)
+        } else {
+            MemoReader::new(memo_type, memo_source)
+        }
+    }
+}
+
+/// Builder of [Reader](struct.Reader.html)s, where all the ways
+/// of reading tables are configured
+///
+/// [Reader::new](struct.Reader.html#method.new) and
+/// [Reader::from_path](struct.Reader.html#method.from_path) stay the way
+/// to read tables with the default options.
+///
+/// # Example
+///
+/// ```
+/// # fn main() -> Result<(), dbase::Error> {
+/// let mut reader = dbase::ReaderBuilder::new()
+///     .encoding(dbase::Unicode)
+///     .character_trim(Some(dbase::TrimOption::End))
+///     .error_policy(dbase::ErrorPolicy::SkipRecord)
+///     .build_from_path("tests/data/line.dbf")?;
+/// let records = reader.read()?;
+/// assert_eq!(records.len(), 1);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ReaderBuilder {
+    options: ReadingOptions,
+    memo_path: Option<PathBuf>,
+}
+
+impl ReaderBuilder {
+    /// Creates a builder with the default options
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replaces all the options by the given ones
+    pub fn options(mut self, options: ReadingOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Sets the encoding used to decode Character and Memo fields,
+    /// and the names of the fields
+    pub fn encoding<E: Encoding + 'static>(mut self, encoding: E) -> Self {
+        self.options = self.options.encoding(encoding);
+        self
+    }
+
+    /// Sets the encoding used to decode the field with the given name
+    pub fn field_encoding<E: Encoding + 'static>(mut self, name: &str, encoding: E) -> Self {
+        self.options = self.options.field_encoding(name, encoding);
+        self
+    }
+
+    /// Sets the cipher the records of encrypted tables are decrypted with
+    pub fn decryption<C: RecordCipher + 'static>(mut self, cipher: C) -> Self {
+        self.options = self.options.decryption(cipher);
+        self
+    }
+
+    /// Sets how character fields are trimmed, `None` keeps the padding
+    pub fn character_trim(mut self, trim: Option<TrimOption>) -> Self {
+        self.options = self.options.character_trim(trim);
+        self
+    }
+
+    /// Sets whether NUL bytes are padding of character fields like spaces
+    pub fn nul_padding(mut self, is_padding: bool) -> Self {
+        self.options = self.options.nul_padding(is_padding);
+        self
+    }
+
+    /// Sets what character fields that are only blanks are read as
+    pub fn blank_policy(mut self, policy: BlankPolicy) -> Self {
+        self.options = self.options.blank_policy(policy);
+        self
+    }
+
+    /// Sets the decimal separator of Numeric and Float fields
+    pub fn decimal_separator(mut self, separator: char) -> Self {
+        self.options = self.options.decimal_separator(separator);
+        self
+    }
+
+    /// Sets the thousands separator of Numeric and Float fields
+    pub fn thousands_separator(mut self, separator: Option<char>) -> Self {
+        self.options = self.options.thousands_separator(separator);
+        self
+    }
+
+    /// Sets whether Numeric fields without decimals are read as exact integers
+    pub fn integer_numerics(mut self, is_integer: bool) -> Self {
+        self.options = self.options.integer_numerics(is_integer);
+        self
+    }
+
+    /// Sets what Date fields that are not days of the calendar are read as
+    pub fn invalid_date_policy(mut self, policy: InvalidDatePolicy) -> Self {
+        self.options = self.options.invalid_date_policy(policy);
+        self
+    }
+
+    /// Sets what to do when the memo file of a table cannot be found
+    pub fn missing_memo_policy(mut self, policy: MissingMemoPolicy) -> Self {
+        self.options = self.options.missing_memo_policy(policy);
+        self
+    }
+
+    /// Sets what to do of records that cannot be read
+    pub fn error_policy(mut self, policy: ErrorPolicy) -> Self {
+        self.options = self.options.error_policy(policy);
+        self
+    }
+
+    /// Sets what to do of fields whose names are duplicated or invalid
+    pub fn field_name_policy(mut self, policy: FieldNamePolicy) -> Self {
+        self.options = self.options.field_name_policy(policy);
+        self
+    }
+
+    /// Sets the case of the names of the fields
+    pub fn field_name_case(mut self, case: FieldNameCase) -> Self {
+        self.options = self.options.field_name_case(case);
+        self
+    }
+
+    /// Sets the size of the buffers the files opened by path are read through
+    pub fn buffer_size(mut self, size: usize) -> Self {
+        self.options = self.options.buffer_size(size);
+        self
+    }
+
+    /// Sets the dialect of the tables
+    pub fn flavor(mut self, flavor: Flavor) -> Self {
+        self.options = self.options.flavor(flavor);
+        self
+    }
+
+    /// Sets the path of the memo file of tables opened by path,
+    /// instead of the path next to the table with the extension of its version
+    pub fn memo_path<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.memo_path = Some(path.as_ref().to_owned());
+        self
+    }
+
+    /// Sets the layout of the memo file, instead of the one told by the memo file
+    /// and by the version of the table
+    pub fn memo_format(mut self, format: MemoFormat) -> Self {
+        self.options = self.options.memo_format(Some(format));
+        self
+    }
+
+    /// Sets whether the production index of tables opened by path is opened with them
+    pub fn production_index(mut self, open: bool) -> Self {
+        self.options = self.options.production_index(open);
+        self
+    }
+
+    /// Sets how much memory the records read at once may take,
+    /// see [ReadingOptions::memory_budget](struct.ReadingOptions.html#method.memory_budget)
+    pub fn memory_budget(mut self, budget: Option<usize>) -> Self {
+        self.options = self.options.memory_budget(budget);
+        self
+    }
+
+    /// Returns the options the readers are built with
+    pub fn reading_options(&self) -> &ReadingOptions {
+        &self.options
+    }
+
+    /// Builds a reader of the content of `source`, a table without memo file
+    pub fn build_with_source<T: Read + Seek>(self, source: T) -> Result<Reader<T>, Error> {
+        Reader::with_options(source, self.options)
+    }
+
+    /// Builds a reader of the content of `source`, with the content of its memo file
+    /// read from `memo_source`
+    pub fn build_with_memo_source<T: Read + Seek>(
+        self,
+        source: T,
+        memo_source: T,
+    ) -> Result<Reader<T>, Error> {
+        Reader::with_memo(source, memo_source, self.options)
+    }
+
+    /// Builds a reader of the file at the given path, and of its memo file (if any)
+    pub fn build_from_path<P: AsRef<Path>>(
+        self,
+        path: P,
+    ) -> Result<Reader<BufReader<File>>, Error> {
+        Reader::from_path_with_memo_path(path, self.memo_path, self.options)
+    }
+}
 
 /// Trait to be implemented by structs that represent records read from a
 /// dBase file.
@@ -38,11 +273,31 @@ pub trait ReadableRecord: Sized {
 
 /// Type definition of a generic record.
 /// A .dbf file is composed of many records
-#[derive(Debug, PartialEq)]
+///
+/// Records are equal when they have the same field names (compared with their case)
+/// with values that are equal, as [FieldValue](enum.FieldValue.html#impl-PartialEq-for-FieldValue)
+/// defines it, whatever the order the values were inserted in.
+/// They can be put in `HashSet`s, or be the keys of `HashMap`s.
+#[derive(Debug, Clone, PartialEq, Default)]
 pub struct Record {
     map: HashMap<String, FieldValue>,
 }
 
+impl Eq for Record {}
+
+impl Hash for Record {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // The order of the map depends on its hasher, the names are sorted to not depend on it
+        let mut entries = self.map.iter().collect::<Vec<_>>();
+        entries.sort_unstable_by_key(|(name, _)| *name);
+        entries.len().hash(state);
+        for (name, value) in entries {
+            name.hash(state);
+            value.hash(state);
+        }
+    }
+}
+
 impl ReadableRecord for Record {
     fn read_using<T>(field_iterator: &mut FieldIterator<T>) -> Result<Self, FieldIOError>
     where
@@ -58,6 +313,24 @@ impl ReadableRecord for Record {
 }
 
 impl Record {
+    /// Returns a builder of records, to set their values one after the other
+    ///
+    /// See also the [record](macro.record.html) macro.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let record = dbase::Record::builder()
+    ///     .set("NAME", "Smith")
+    ///     .set("AMOUNT", 12.5)
+    ///     .set("PAID", true)
+    ///     .build();
+    /// assert_eq!(record.get("AMOUNT"), Some(&dbase::FieldValue::Numeric(Some(12.5))));
+    /// ```
+    pub fn builder() -> RecordBuilder {
+        RecordBuilder::default()
+    }
+
     /// Inserts a new value in the record, returning the old one if there was any
     ///
     /// # Example
@@ -80,6 +353,40 @@ impl Record {
         self.map.get_mut(field_name)
     }
 
+    /// Returns the [FieldValue](enum.FieldValue.html) for the given field name,
+    /// compared ignoring the case like dBase does
+    ///
+    /// The value of the field with exactly that name is returned if there is one,
+    /// otherwise the one of any field whose name only differs by its case.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let record = dbase::Record::builder().set("FirstName", "Yoshi").build();
+    /// assert_eq!(record.get("FIRSTNAME"), None);
+    /// assert_eq!(record.get_ignore_case("FIRSTNAME"), record.get("FirstName"));
+    /// ```
+    pub fn get_ignore_case(&self, field_name: &str) -> Option<&FieldValue> {
+        self.map.get(field_name).or_else(|| {
+            self.map
+                .iter()
+                .find(|(name, _)| field_names_match(name, field_name))
+                .map(|(_, value)| value)
+        })
+    }
+
+    /// Returns the mutable [FieldValue](enum.FieldValue.html) for the given field name,
+    /// compared ignoring the case like [get_ignore_case](#method.get_ignore_case) does
+    pub fn get_mut_ignore_case(&mut self, field_name: &str) -> Option<&mut FieldValue> {
+        if self.map.contains_key(field_name) {
+            return self.map.get_mut(field_name);
+        }
+        self.map
+            .iter_mut()
+            .find(|(name, _)| field_names_match(name, field_name))
+            .map(|(_, value)| value)
+    }
+
     /// Removes the [FieldValue](enum.FieldValue.html) for the given field name
     pub fn remove(&mut self, field_name: &str) -> Option<FieldValue> {
         self.map.remove(field_name)
@@ -95,28 +402,101 @@ impl IntoIterator for Record {
     }
 }
 
-impl Default for Record {
-    fn default() -> Self {
-        Self {
-            map: Default::default(),
-        }
-    }
-}
-
 impl From<HashMap<String, FieldValue>> for Record {
     fn from(map: HashMap<String, FieldValue, RandomState>) -> Self {
         Self { map }
     }
 }
 
+/// Builder of [Record](struct.Record.html)s, made by
+/// [Record::builder](struct.Record.html#method.builder)
+#[derive(Debug, Clone, Default)]
+pub struct RecordBuilder {
+    record: Record,
+}
+
+impl RecordBuilder {
+    /// Sets the value of the field with the given name, replacing the previous one if any
+    ///
+    /// The values are converted to the [FieldValue](enum.FieldValue.html) of the type
+    /// of field they are written to: strings to Character, `f64`s, `i64`s and `i32`s
+    /// to Numeric, `f32`s to Float, `bool`s to Logical and dates to Date fields,
+    /// `None`s to empty values.
+    pub fn set<N: Into<String>, V: Into<FieldValue>>(mut self, field_name: N, value: V) -> Self {
+        self.record.insert(field_name.into(), value.into());
+        self
+    }
+
+    /// Returns the record with the values set
+    pub fn build(self) -> Record {
+        self.record
+    }
+}
+
+/// The values of a record in the order of the fields, along with its deletion flag
+///
+/// Used by the conversions to other formats, which work column by column.
+pub(crate) struct RecordValues {
+    pub(crate) is_deleted: bool,
+    pub(crate) values: Vec<FieldValue>,
+}
+
+impl ReadableRecord for RecordValues {
+    fn read_using<T>(field_iterator: &mut FieldIterator<T>) -> Result<Self, FieldIOError>
+    where
+        T: Read + Seek,
+    {
+        let is_deleted = field_iterator.read_deletion_flag()?;
+        let mut values = Vec::<FieldValue>::with_capacity(field_iterator.fields_info.len());
+        for result in field_iterator {
+            values.push(result?.value);
+        }
+        Ok(Self { is_deleted, values })
+    }
+}
+
+/// Reads the header, the fields information and the backlink of a table,
+/// like `raw::read_table_info` from a `Read`
+pub(crate) fn read_table_info<R: Read>(
+    source: &mut R,
+    flavor: Flavor,
+    encoding: &dyn Encoding,
+) -> Result<(Header, Vec<FieldInfo>, Option<String>), Error> {
+    raw::read_table_info(&mut IoSource(source), flavor, encoding)
+}
+
+/// Same as read_table_info, for damaged tables,
+/// like `raw::read_table_info_tolerant` from a `Read`
+#[allow(clippy::type_complexity)]
+pub(crate) fn read_table_info_tolerant<R: Read>(
+    source: &mut R,
+    flavor: Flavor,
+    encoding: &dyn Encoding,
+) -> Result<(Header, Vec<FieldInfo>, Option<String>, bool), Error> {
+    raw::read_table_info_tolerant(&mut IoSource(source), flavor, encoding)
+}
+
 /// Struct with the handle to the source .dbf file
 /// Responsible for reading the content
 pub struct Reader<T: Read + Seek> {
     /// Where the data is read from
-    source: T,
-    memo_reader: Option<MemoReader<T>>,
+    pub(crate) source: T,
+    pub(crate) memo_reader: Option<MemoReader<T>>,
+    pub(crate) production_index: Option<ProductionIndex<T>>,
     pub(crate) header: Header,
     pub(crate) fields_info: Vec<FieldInfo>,
+    /// Path to the database (.dbc) a Visual FoxPro table belongs to
+    pub(crate) backlink: Option<String>,
+    pub(crate) options: ReadingOptions,
+    pub(crate) field_name_issues: Vec<FieldNameIssue>,
+    /// Index of the record the source is positioned at
+    pub(crate) next_record: u32,
+    /// Buffer the records are read in
+    pub(crate) record_buffer: Vec<u8>,
+    /// Index of the record after the last one to read, if not the end of the table
+    pub(crate) end_record: Option<u32>,
+    /// Whether the source is a stream, that cannot go back
+    pub(crate) is_stream: bool,
 }
 
 impl<T: Read + Seek> Reader<T> {
@@ -146,75 +526,351 @@ impl<T: Read + Seek> Reader<T> {
     /// # Ok(())
     /// # }
     /// ```
-    pub fn new(mut source: T) -> Result<Self, Error> {
-        let header = Header::read_from(&mut source).map_err(|error| Error::io_error(error, 0))?;
+    pub fn new(source: T) -> Result<Self, Error> {
+        Self::with_options(source, ReadingOptions::default())
+    }
 
-        let offset_to_first_record = if header.file_type.is_visual_fox_pro() {
-            header.offset_to_first_record - BACKLINK_SIZE
-        } else {
-            header.offset_to_first_record
-        };
-        let num_fields =
-            (offset_to_first_record as usize - Header::SIZE - std::mem::size_of::<u8>())
-                / FieldInfo::SIZE;
-
-        let mut fields_info = Vec::<FieldInfo>::with_capacity(num_fields as usize + 1);
-        fields_info.push(FieldInfo::new_deletion_flag());
-        for _ in 0..num_fields {
-            let info = FieldInfo::read_from(&mut source).map_err(|error| Error {
-                record_num: 0,
-                field: None,
-                kind: error,
-            })?;
-            fields_info.push(info);
+    /// Creates a new reader from the source, that reads the content
+    /// using the given options.
+    pub fn with_options(mut source: T, options: ReadingOptions) -> Result<Self, Error> {
+        let (header, mut fields_info, backlink) =
+            read_table_info(&mut source, options.flavor, &*options.encoding)?;
+        if options.cipher.is_none() {
+            check_encryption(&header)?;
         }
+        let field_name_issues = check_field_names(&mut fields_info, &options)?;
 
-        let terminator = source
-            .read_u8()
+        source
+            .seek(SeekFrom::Start(u64::from(header.offset_to_first_record)))
             .map_err(|error| Error::io_error(error, 0))?;
-
-        debug_assert_eq!(terminator, TERMINATOR_VALUE);
-
-        if header.file_type.is_visual_fox_pro() {
-            source
-                .seek(SeekFrom::Current(i64::from(BACKLINK_SIZE)))
-                .map_err(|error| Error::io_error(error, 0))?;
-        }
+        // Like the fields are read one after the other,
+        // the size of the records is the sum of the lengths of the fields
+        let record_size = fields_info
+            .iter()
+            .map(|field_info| usize::from(field_info.field_length))
+            .sum();
+        let record_buffer = vec![0u8; record_size];
 
         Ok(Self {
             source,
             memo_reader: None,
+            production_index: None,
             header,
             fields_info,
+            backlink,
+            options,
+            field_name_issues,
+            next_record: 0,
+            record_buffer,
+            end_record: None,
+            is_stream: false,
         })
     }
 
+    /// Creates a new reader from the source of the table and the source
+    /// of its memo file, that reads the content using the given options.
+    ///
+    /// Like with [from_path](struct.Reader.html#method.from_path), the memo source
+    /// is only read if the table has memo fields.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::io::Cursor;
+    /// # fn main() -> Result<(), dbase::Error> {
+    /// let dbf = std::fs::read("tests/data/line.dbf").unwrap();
+    /// let mut reader = dbase::Reader::with_memo(
+    ///     Cursor::new(dbf.as_slice()),
+    ///     Cursor::new(&[][..]),
+    ///     dbase::ReadingOptions::default(),
+    /// )?;
+    /// assert_eq!(reader.read()?.len(), 1);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_memo(source: T, memo_source: T, options: ReadingOptions) -> Result<Self, Error> {
+        let mut reader = Self::with_options(source, options)?;
+        let at_least_one_field_is_memo = reader
+            .fields_info
+            .iter()
+            .any(|f_info| f_info.field_type == FieldType::Memo);
+        if !at_least_one_field_is_memo {
+            return Ok(reader);
+        }
+        if let Some(memo_type) = reader.options.memo_file_type(&reader.header) {
+            let memo_reader = reader
+                .options
+                .open_memo(memo_type, memo_source)
+                .map_err(|error| Error::io_error(error, 0))?;
+            reader.memo_reader = Some(memo_reader);
+        }
+        Ok(reader)
+    }
+
+    /// Returns the problems with the names of the fields,
+    /// what was done of them depends on the [FieldNamePolicy](enum.FieldNamePolicy.html)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), dbase::Error> {
+    /// let reader = dbase::Reader::from_path("tests/data/stations.dbf")?;
+    /// // '-' is not allowed in names
+    /// assert_eq!(
+    ///     reader.field_name_issues()[0],
+    ///     dbase::FieldNameIssue::InvalidCharacters { index: 1, name: "marker-col".to_owned() }
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn field_name_issues(&self) -> &[FieldNameIssue] {
+        &self.field_name_issues
+    }
+
     /// Returns the header of the file
     pub fn header(&self) -> &Header {
         &self.header
     }
 
+    /// Returns the number of records in the table, deleted records included,
+    /// as written in the header
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), dbase::Error> {
+    /// let reader = dbase::Reader::from_path("tests/data/stations.dbf")?;
+    /// assert_eq!(reader.record_count(), 6);
+    /// assert_eq!(reader.len(), 6);
+    /// assert!(!reader.is_empty());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn record_count(&self) -> usize {
+        self.header.num_records as usize
+    }
+
+    /// Returns the number of records in the table, same as [record_count](#method.record_count)
+    pub fn len(&self) -> usize {
+        self.record_count()
+    }
+
+    /// Returns true if the table has no records
+    pub fn is_empty(&self) -> bool {
+        self.header.num_records == 0
+    }
+
+    /// Returns the cipher the records are decrypted with, if the table is encrypted
+    pub(crate) fn cipher(&self) -> Option<&Arc<dyn RecordCipher>> {
+        self.options
+            .cipher
+            .as_ref()
+            .filter(|_| self.header.is_encrypted())
+    }
+
+    /// Decrypts the record at the given index (0-based) read in the buffer,
+    /// if the table is encrypted
+    pub(crate) fn decrypt_record_buffer(&mut self, index: u32) {
+        if self.header.is_encrypted() {
+            let cipher = self.options.cipher.as_deref();
+            decrypt_record(cipher, index as usize, &mut self.record_buffer);
+        }
+    }
+
+    /// Returns the length in bytes of a record as it is read,
+    /// that is the deletion flag and the fields
+    pub fn record_length(&self) -> usize {
+        self.record_buffer.len()
+    }
+
+    /// Returns the path of the database container (.dbc) the table belongs to,
+    /// relative to the table.
+    ///
+    /// Only Visual FoxPro tables that are part of a database have one.
+    pub fn backlink(&self) -> Option<&str> {
+        self.backlink.as_deref()
+    }
+
     /// Returns the fields contained in the opened file
     pub fn fields(&self) -> &[FieldInfo] {
         &self.fields_info
     }
 
+    /// Returns a reader of the data of a memo field, that streams the blocks of
+    /// the memo file instead of loading the whole value in memory
+    ///
+    /// Large memo and General fields can thus be copied out,
+    /// the handle is obtained with [RecordRef::memo_handle](struct.RecordRef.html#method.memo_handle).
+    ///
+    /// # Errors
+    ///
+    /// [MissingMemoFile](enum.ErrorKind.html#variant.MissingMemoFile) is returned
+    /// when the reader has no memo file.
+    pub fn memo_reader(&mut self, handle: &MemoHandle) -> Result<MemoStream<'_, T>, Error> {
+        let record_num = self.next_record as usize;
+        match self.memo_reader.as_mut() {
+            Some(memo_reader) => memo_reader
+                .stream_at(handle)
+                .map_err(|error| Error::io_error(error, record_num)),
+            None => Err(Error {
+                record_num,
+                field: None,
+                kind: ErrorKind::MissingMemoFile,
+            }),
+        }
+    }
+
+    /// Returns the source the reader reads from, dropping the reader
+    ///
+    /// The source stays positioned after the last record that was read,
+    /// the memo reader (if any) is dropped.
+    pub fn into_inner(self) -> T {
+        self.source
+    }
+
+    /// Deconstructs the reader into its source, header and fields
+    ///
+    /// Like [into_inner](#method.into_inner), the source stays positioned after
+    /// the last record that was read, the memo reader (if any) is dropped.
+    ///
+    /// # Example
+    ///
+    /// Reading the bytes some applications append after the records:
+    ///
+    /// ```
+    /// # fn main() -> Result<(), dbase::Error> {
+    /// use std::io::Read;
+    ///
+    /// let mut reader = dbase::Reader::from_path("tests/data/line.dbf")?;
+    /// let _records = reader.read()?;
+    /// let (mut source, header, fields) = reader.into_parts();
+    /// assert_eq!(header.num_records, 1);
+    /// assert_eq!(fields.len(), 2);
+    /// let mut trailer = vec![];
+    /// source.read_to_end(&mut trailer).unwrap();
+    /// // Past the (optional) end of file marker
+    /// let custom_data = trailer.strip_prefix(&[0x1A]).unwrap_or(&trailer);
+    /// assert!(custom_data.is_empty());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn into_parts(self) -> (T, Header, Vec<FieldInfo>) {
+        (self.source, self.header, self.fields_info)
+    }
+
     /// Creates an iterator of records of the type you want
-    pub fn iter_records_as<R: ReadableRecord>(&mut self) -> RecordIterator<T, R> {
+    ///
+    /// The iterator starts at the record following the last one that was read.
+    pub fn iter_records_as<R: ReadableRecord>(&mut self) -> RecordIterator<'_, T, R> {
         RecordIterator {
             reader: self,
             record_type: std::marker::PhantomData,
-            current_record: 0,
         }
     }
 
     /// Shortcut function to get an iterator over the [Records](struct.Record.html) in the file
-    pub fn iter_records(&mut self) -> RecordIterator<T, Record> {
+    pub fn iter_records(&mut self) -> RecordIterator<'_, T, Record> {
         self.iter_records_as::<Record>()
     }
 
+    /// Makes an iterator over the records and their [metadata](struct.RecordMetadata.html),
+    /// the records are read as R
+    pub fn iter_records_with_metadata_as<R: ReadableRecord>(
+        &mut self,
+    ) -> RecordMetadataIterator<'_, T, R> {
+        RecordMetadataIterator {
+            reader: self,
+            record_type: std::marker::PhantomData,
+        }
+    }
+
+    /// Makes an iterator over the records and their [metadata](struct.RecordMetadata.html)
+    ///
+    /// Deleted records are not skipped.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), dbase::Error> {
+    /// let mut reader = dbase::Reader::from_path("tests/data/line.dbf")?;
+    /// for result in reader.iter_records_with_metadata() {
+    ///     let (metadata, record) = result?;
+    ///     assert_eq!(metadata.record_num, 0);
+    ///     assert_eq!(metadata.offset, 65);
+    ///     assert!(!metadata.is_deleted);
+    ///     assert!(record.get("name").is_some());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn iter_records_with_metadata(&mut self) -> RecordMetadataIterator<'_, T, Record> {
+        self.iter_records_with_metadata_as::<Record>()
+    }
+
+    /// Returns an estimate of the memory the records left to read
+    /// take once read as [Record](struct.Record.html)s
+    ///
+    /// Each value counts as the name of its field and a [FieldValue](enum.FieldValue.html),
+    /// plus the length of the field for Character fields. The text of memos
+    /// is not known before it is read, it is not counted.
+    pub fn estimated_memory_size(&self) -> usize {
+        let num_records = self.end_record().saturating_sub(self.next_record) as usize;
+        self.estimated_size_of(num_records)
+    }
+
+    fn estimated_size_of(&self, num_records: usize) -> usize {
+        let record_size = self
+            .fields_info
+            .iter()
+            .filter(|field_info| !field_info.is_deletion_flag())
+            .map(|field_info| {
+                let text_size = match field_info.field_type {
+                    FieldType::Character => usize::from(field_info.field_length),
+                    _ => 0,
+                };
+                std::mem::size_of::<String>()
+                    + field_info.name.len()
+                    + std::mem::size_of::<FieldValue>()
+                    + text_size
+            })
+            .sum::<usize>()
+            + std::mem::size_of::<Record>();
+        record_size.saturating_mul(num_records)
+    }
+
+    /// Returns an error if the records would take more memory than the budget of the options
+    pub(crate) fn check_memory_budget(&self, num_records: usize) -> Result<(), Error> {
+        if self.options.memory_budget.is_none() {
+            return Ok(());
+        }
+        let remaining = self.end_record().saturating_sub(self.next_record) as usize;
+        let estimated = self.estimated_size_of(num_records.min(remaining));
+        self.check_estimate(estimated)
+    }
+
+    /// Returns an error if the estimated memory is over the budget of the options
+    pub(crate) fn check_estimate(&self, estimated: usize) -> Result<(), Error> {
+        let budget = match self.options.memory_budget {
+            Some(budget) => budget,
+            None => return Ok(()),
+        };
+        if estimated > budget {
+            Err(Error {
+                record_num: self.next_record as usize,
+                field: None,
+                kind: ErrorKind::MemoryBudgetExceeded { estimated, budget },
+            })
+        } else {
+            Ok(())
+        }
+    }
+
     /// Reads all the records of the file inside a `Vec`
+    ///
+    /// Fails without reading anything if the records would take more than the
+    /// [memory budget](struct.ReadingOptions.html#method.memory_budget) of the options.
     pub fn read_as<R: ReadableRecord>(&mut self) -> Result<Vec<R>, Error> {
+        self.check_memory_budget(usize::MAX)?;
         // We don't read the file terminator
         self.iter_records_as::<R>()
             .collect::<Result<Vec<R>, Error>>()
@@ -222,20 +878,462 @@ impl<T: Read + Seek> Reader<T> {
 
     /// Make the `Reader` read the [Records](struct.Record.html)
     ///
-    /// # Examples
+    /// Fails without reading anything if the records would take more than the
+    /// [memory budget](struct.ReadingOptions.html#method.memory_budget) of the options.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::fs::File;
+    /// # fn main() -> Result<(), dbase::Error> {
+    /// let mut reader = dbase::Reader::from_path("tests/data/line.dbf")?;
+    /// let records = reader.read()?;
+    /// assert_eq!(records.len(), 1);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn read(&mut self) -> Result<Vec<Record>, Error> {
+        self.check_memory_budget(usize::MAX)?;
+        // We don't read the file terminator
+        self.iter_records().collect::<Result<Vec<Record>, Error>>()
+    }
+
+    /// Reads the next record into the given one, reusing its allocations,
+    /// returns `false` when there are no more records
+    ///
+    /// The names of the fields are only allocated the first time, as they are the
+    /// keys of the record, so reading all the records in the same one
+    /// allocates less than [iter_records](#method.iter_records).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), dbase::Error> {
+    /// let mut reader = dbase::Reader::from_path("tests/data/stations.dbf")?;
+    /// let mut record = dbase::Record::default();
+    /// let mut num_records = 0;
+    /// while reader.read_record_into(&mut record)? {
+    ///     assert!(record.get("name").is_some());
+    ///     num_records += 1;
+    /// }
+    /// assert_eq!(num_records, 6);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn read_record_into(&mut self, record: &mut Record) -> Result<bool, Error> {
+        let num_fields = self.fields_info.len() - 1;
+        let result = self.read_next_record_with(|field_iterator| {
+            for result in field_iterator {
+                let NamedValue { name, value } = result?;
+                match record.map.get_mut(name) {
+                    Some(old_value) => *old_value = value,
+                    None => {
+                        record.map.insert(name.to_owned(), value);
+                    }
+                }
+            }
+            Ok(())
+        });
+        if record.map.len() > num_fields {
+            // The record was not read from this table before
+            let fields_info = &self.fields_info;
+            record
+                .map
+                .retain(|name, _| fields_info.iter().any(|info| info.name() == name));
+        }
+        result.transpose().map(|read| read.is_some())
+    }
+
+    /// Reads the record at the given index (0-based)
+    ///
+    /// The next records are read from the one following it.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), dbase::Error> {
+    /// let mut reader = dbase::Reader::from_path("tests/data/stations.dbf")?;
+    /// let record = reader.record_at(3)?;
+    /// assert_eq!(
+    ///     record.get("name"),
+    ///     Some(&dbase::FieldValue::Character(Some("Judiciary Sq".to_owned())))
+    /// );
+    /// assert_eq!(reader.read()?.len(), 2);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn record_at(&mut self, index: u32) -> Result<Record, Error> {
+        self.record_at_as::<Record>(index)
+    }
+
+    /// Reads the record at the given index (0-based) as the type `R`
+    ///
+    /// The next records are read from the one following it.
+    pub fn record_at_as<R: ReadableRecord>(&mut self, index: u32) -> Result<R, Error> {
+        if index >= self.header.num_records {
+            return Err(Error {
+                record_num: index as usize,
+                field: None,
+                kind: ErrorKind::Message(format!(
+                    "there is no record {}, the table has {} records",
+                    index, self.header.num_records
+                )),
+            });
+        }
+        self.read_record_at_as::<R>(index)
+    }
+
+    /// Reads the values of a field in one pass and returns, for each distinct value,
+    /// the indices (0-based) of the records having it
+    ///
+    /// Combined with [record_at](#method.record_at), this gives fast lookups
+    /// on tables that have no index file. Deleted records are not skipped, the reader
+    /// is positioned back where it was before the call.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), dbase::Error> {
+    /// use dbase::FieldValue;
+    ///
+    /// let mut reader = dbase::Reader::from_path("tests/data/stations.dbf")?;
+    /// let lines = reader.index_by("line")?;
+    /// let red = FieldValue::Character(Some("red".to_owned()));
+    /// assert_eq!(lines[&red], vec![3, 5]);
+    /// let record = reader.record_at(lines[&red][1])?;
+    /// assert_eq!(
+    ///     record.get("name"),
+    ///     Some(&FieldValue::Character(Some("Metro Center".to_owned())))
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn index_by(&mut self, field_name: &str) -> Result<HashMap<FieldValue, Vec<u32>>, Error> {
+        if !self
+            .fields_info
+            .iter()
+            .any(|info| !info.is_deletion_flag() && info.name() == field_name)
+        {
+            return Err(Error {
+                record_num: 0,
+                field: None,
+                kind: ErrorKind::Message(format!(
+                    "the table has no field named '{}'",
+                    field_name
+                )),
+            });
+        }
+        self.require(Capability::RandomAccess, 0)?;
+        let position = self.next_record;
+        self.seek_to_record(0)?;
+        let mut map: HashMap<FieldValue, Vec<u32>> = HashMap::new();
+        let mut result = Ok(());
+        loop {
+            let record_num = self.next_record;
+            let value = match self.next_record_ref() {
+                None => break,
+                Some(Err(error)) => Err(error),
+                Some(Ok(mut record)) => match record.get(field_name) {
+                    Some(value) => value.map_err(|error| Error::new(error, record_num as usize)),
+                    None => unreachable!("the field was found in the fields of the table"),
+                },
+            };
+            match value {
+                Ok(value) => map.entry(value).or_default().push(record_num),
+                Err(error) => {
+                    result = Err(error);
+                    break;
+                }
+            }
+        }
+        self.seek_to_record(position)?;
+        result.map(|_| map)
+    }
+
+    /// Restricts the reader to the records in the range (0-based indices),
+    /// and positions it at the first of them
+    ///
+    /// The indices past the end of the table are ignored.
+    /// See [open_partitions](fn.open_partitions.html) to read a table from several threads.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), dbase::Error> {
+    /// let mut reader = dbase::Reader::from_path("tests/data/stations.dbf")?;
+    /// reader.set_record_range(2..4)?;
+    /// assert_eq!(reader.read()?.len(), 2);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_record_range(&mut self, range: std::ops::Range<usize>) -> Result<(), Error> {
+        let num_records = self.header.num_records;
+        let start = u32::try_from(range.start).unwrap_or(u32::MAX).min(num_records);
+        let end = u32::try_from(range.end).unwrap_or(u32::MAX).clamp(start, num_records);
+        self.end_record = None;
+        self.seek_to_record(0)?;
+        self.skip_records(start as usize)?;
+        self.end_record = Some(end);
+        Ok(())
+    }
+
+    /// Skips the next `n` records with a single seek, without reading them,
+    /// returns the number of records skipped (less than `n` at the end of the table)
+    ///
+    /// The records of streams are read and discarded, see
+    /// [Capability::FastSkip](enum.Capability.html#variant.FastSkip).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), dbase::Error> {
+    /// let mut reader = dbase::Reader::from_path("tests/data/stations.dbf")?;
+    /// assert_eq!(reader.skip_records(4)?, 4);
+    /// assert_eq!(reader.read()?.len(), 2);
+    /// assert_eq!(reader.skip_records(4)?, 0);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn skip_records(&mut self, n: usize) -> Result<usize, Error> {
+        let remaining = self.end_record().saturating_sub(self.next_record);
+        let n = u32::try_from(n).unwrap_or(u32::MAX).min(remaining);
+        if n > 0 {
+            // The records are the size of the fields, wherever
+            // the previous ones were read from
+            let offset = i64::from(n) * self.record_stride() as i64;
+            self.source
+                .seek(SeekFrom::Current(offset))
+                .map_err(|error| Error::io_error(error, self.next_record as usize))?;
+            self.next_record += n;
+        }
+        Ok(n as usize)
+    }
+
+    /// Reads the bytes of the next record into the given buffer, which is resized to
+    /// the size of a record, returns `false` when there are no more records
+    ///
+    /// The first byte is the deletion flag, the fields follow in order,
+    /// the values are left as they are in the file.
+    pub fn read_record_bytes_into(&mut self, buffer: &mut Vec<u8>) -> Result<bool, Error> {
+        if self.next_record >= self.end_record() {
+            return Ok(false);
+        }
+        buffer.resize(self.record_buffer.len(), 0);
+        self.source
+            .read_exact(buffer)
+            .map_err(|error| Error::io_error(error, self.next_record as usize))?;
+        self.next_record += 1;
+        Ok(true)
+    }
+
+    /// Reads the next record in the buffer of the reader and returns its bytes,
+    /// as they are in the file
+    ///
+    /// Returns `None` once all the records were read, deleted records are not skipped.
+    /// The records of encrypted tables are not decrypted.
+    /// The first byte is the deletion flag, the fields follow in the order
+    /// of [fields](#method.fields), each one as long as its length.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), dbase::Error> {
+    /// let mut reader = dbase::Reader::from_path("tests/data/stations.dbf")?;
+    /// let record_length = reader.record_length();
+    /// while let Some(bytes) = reader.next_raw() {
+    ///     assert_eq!(bytes?.len(), record_length);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn next_raw(&mut self) -> Option<Result<&[u8], Error>> {
+        if self.next_record >= self.end_record() {
+            return None;
+        }
+        if let Err(error) = self.source.read_exact(&mut self.record_buffer) {
+            return Some(Err(Error::io_error(error, self.next_record as usize)));
+        }
+        self.next_record += 1;
+        Some(Ok(&self.record_buffer))
+    }
+
+    /// Returns the bytes of the record at the given index (0-based),
+    /// as they are in the file, see [next_raw](#method.next_raw)
+    ///
+    /// The reader is then positioned at the record that follows.
+    pub fn record_bytes_at(&mut self, index: usize) -> Result<&[u8], Error> {
+        let num_records = self.header.num_records as usize;
+        if index >= num_records {
+            return Err(Error::no_such_record(index, num_records));
+        }
+        if index < self.next_record as usize {
+            self.require(Capability::RandomAccess, index)?;
+        }
+        let position = self.record_offset(index as u32);
+        self.source
+            .seek(SeekFrom::Start(position))
+            .and_then(|_| self.source.read_exact(&mut self.record_buffer))
+            .map_err(|error| Error::io_error(error, index))?;
+        self.next_record = index as u32 + 1;
+        Ok(&self.record_buffer)
+    }
+
+    /// Reads the next record, the source must be positioned at its start.
+    ///
+    /// On error, the source is positioned at the start of the following record.
+    pub(crate) fn read_current_record_as<R: ReadableRecord>(&mut self) -> Result<R, Error> {
+        self.read_current_record_with(R::read_using)
+    }
+
+    /// Reads the next record using the given fn, the source must be positioned at its start.
+    ///
+    /// The whole record is read at once in the buffer of the reader, the fields are
+    /// read from it, so on error the source is positioned at the start of the following record.
+    pub(crate) fn read_current_record_with<R, F>(&mut self, read: F) -> Result<R, Error>
+    where
+        F: FnOnce(&mut FieldIterator<T>) -> Result<R, FieldIOError>,
+    {
+        self.read_current_record_start_with(self.fields_info.len(), read)
+    }
+
+    /// Same as `read_current_record_with`, but only the
+    /// bytes of the given number of fields (the deletion flag included) are read,
+    /// the source is then moved to the start of the following record.
+    pub(crate) fn read_current_record_start_with<R, F>(
+        &mut self,
+        num_fields: usize,
+        read: F,
+    ) -> Result<R, Error>
+    where
+        F: FnOnce(&mut FieldIterator<T>) -> Result<R, FieldIOError>,
+    {
+        let record_num = self.next_record;
+        // The records are encrypted as a whole
+        let size = if num_fields == self.fields_info.len() || self.cipher().is_some() {
+            self.record_buffer.len()
+        } else {
+            self.fields_info[..num_fields]
+                .iter()
+                .map(|field_info| usize::from(field_info.field_length))
+                .sum()
+        };
+        let remaining = self.record_buffer.len() - size;
+        self.source
+            .read_exact(&mut self.record_buffer[..size])
+            .and_then(|_| match remaining {
+                0 => Ok(()),
+                _ => self
+                    .source
+                    .seek(SeekFrom::Current(remaining as i64))
+                    .map(|_| ()),
+            })
+            .map_err(|error| Error::io_error(error, record_num as usize))?;
+        self.next_record += 1;
+        self.decrypt_record_buffer(record_num);
+
+        let mut source = Cursor::new(&self.record_buffer[..size]);
+        let mut iter = FieldIterator {
+            source: &mut source,
+            fields_info: self.fields_info[..num_fields].iter().peekable(),
+            memo_reader: &mut self.memo_reader,
+            options: &self.options,
+        };
+        // The fields that are not read do not need to be skipped,
+        // the next record is read in the buffer from its start
+        read(&mut iter).map_err(|error| Error::new(error, record_num as usize))
+    }
+
+    /// Reads the next record, following the error policy of the reader
+    pub(crate) fn read_next_record_as<R: ReadableRecord>(&mut self) -> Option<Result<R, Error>> {
+        self.read_next_record_with(R::read_using)
+    }
+
+    /// Reads the next record using the given fn, following the error policy of the reader
+    pub(crate) fn read_next_record_with<R, F>(&mut self, mut read: F) -> Option<Result<R, Error>>
+    where
+        F: FnMut(&mut FieldIterator<T>) -> Result<R, FieldIOError>,
+    {
+        while self.next_record < self.end_record() {
+            match self.read_current_record_with(&mut read) {
+                Err(error) => {
+                    let is_io_error = matches!(error.kind, ErrorKind::IoError(_));
+                    if is_io_error || self.options.error_policy == ErrorPolicy::Fail {
+                        return Some(Err(error));
+                    }
+                }
+                record => return Some(record),
+            }
+        }
+        None
+    }
+
+    /// Seeks to the record at the given index (0-based) and reads it.
+    pub(crate) fn read_record_at_as<R: ReadableRecord>(&mut self, index: u32) -> Result<R, Error> {
+        self.seek_to_record(index)?;
+        self.read_current_record_as::<R>()
+    }
+
+    /// Returns the index of the record after the last one to read
+    pub(crate) fn end_record(&self) -> u32 {
+        self.end_record
+            .map_or(self.header.num_records, |end| end.min(self.header.num_records))
+    }
+
+    /// Returns the offset from the start of the file of the record at the given index (0-based)
+    ///
+    /// Like when they are read one after the other, the records are the size of the fields,
+    /// whatever the size of the records in the header.
+    pub(crate) fn record_offset(&self, index: u32) -> u64 {
+        u64::from(self.header.offset_to_first_record) + u64::from(index) * self.record_stride()
+    }
+
+    /// Returns the distance between the starts of two consecutive records
+    pub(crate) fn record_stride(&self) -> u64 {
+        self.record_buffer.len() as u64
+    }
+
+    /// Returns the metadata of the last record read in the buffer
+    fn last_record_metadata(&self) -> RecordMetadata {
+        let index = self.next_record - 1;
+        RecordMetadata {
+            record_num: index as usize,
+            offset: self.record_offset(index),
+            is_deleted: self.record_buffer.first() == Some(&b'*'),
+        }
+    }
+
+    /// Positions the source at the start of the record at the given index (0-based)
+    pub(crate) fn seek_to_record(&mut self, index: u32) -> Result<(), Error> {
+        if index < self.next_record {
+            self.require(Capability::RandomAccess, index as usize)?;
+        }
+        let position = self.record_offset(index);
+        self.source
+            .seek(SeekFrom::Start(position))
+            .map_err(|error| Error::io_error(error, index as usize))?;
+        self.next_record = index;
+        Ok(())
+    }
+}
+
+impl<'a> Reader<Cursor<&'a [u8]>> {
+    /// Creates a new reader of the table stored in the bytes, without copying them
+    ///
+    /// This does not need a file system, which makes it the way to read
+    /// tables in a browser (`wasm32-unknown-unknown`), see `examples/browser-viewer.rs`.
+    ///
+    /// # Example
     ///
     /// ```
-    /// use std::fs::File;
     /// # fn main() -> Result<(), dbase::Error> {
-    /// let mut reader = dbase::Reader::from_path("tests/data/line.dbf")?;
-    /// let records = reader.read()?;
-    /// assert_eq!(records.len(), 1);
+    /// let bytes = std::fs::read("tests/data/line.dbf").unwrap();
+    /// let mut reader = dbase::Reader::from_bytes(&bytes)?;
+    /// assert_eq!(reader.read()?.len(), 1);
     /// # Ok(())
     /// # }
     /// ```
-    pub fn read(&mut self) -> Result<Vec<Record>, Error> {
-        // We don't read the file terminator
-        self.iter_records().collect::<Result<Vec<Record>, Error>>()
+    pub fn from_bytes(bytes: &'a [u8]) -> Result<Self, Error> {
+        Self::new(Cursor::new(bytes))
     }
 }
 
@@ -251,32 +1349,100 @@ impl Reader<BufReader<File>> {
     /// # }
     /// ```
     pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        Self::from_path_with_options(path, ReadingOptions::default())
+    }
+
+    /// Creates a new dbase Reader from a path, that reads the content
+    /// using the given options.
+    pub fn from_path_with_options<P: AsRef<Path>>(
+        path: P,
+        options: ReadingOptions,
+    ) -> Result<Self, Error> {
+        Self::from_path_with_memo_path(path, None, options)
+    }
+
+    /// Creates a new dbase Reader from a path, the memo file (if any) is read
+    /// from `memo_path` instead of the path next to the file with
+    /// the extension of the dBase version (.dbt / .fpt)
+    pub(crate) fn from_path_with_memo_path<P: AsRef<Path>>(
+        path: P,
+        memo_path: Option<PathBuf>,
+        options: ReadingOptions,
+    ) -> Result<Self, Error> {
+        let buffer_size = options.buffer_size;
+        Reader::from_path_with_source(path, memo_path, options, |file| {
+            BufReader::with_capacity(buffer_size, file)
+        })
+    }
+}
+
+impl<T: Read + Seek> Reader<T> {
+    /// Creates a new dbase Reader from a path, the file and the memo file (if any)
+    /// are read through the source `wrap` makes of them.
+    ///
+    /// The memo file is read from `memo_path` if given.
+    pub(crate) fn from_path_with_source<P, F>(
+        path: P,
+        memo_path: Option<PathBuf>,
+        options: ReadingOptions,
+        wrap: F,
+    ) -> Result<Self, Error>
+    where
+        P: AsRef<Path>,
+        F: Fn(File) -> T,
+    {
         let p = path.as_ref().to_owned();
-        let bufreader =
-            BufReader::new(File::open(path).map_err(|error| Error::io_error(error, 0))?);
-        let mut reader = Reader::new(bufreader)?;
+        let source = wrap(File::open(path).map_err(|error| Error::io_error(error, 0))?);
+        let mut reader = Reader::with_options(source, options)?;
+        if reader.options.production_index {
+            if let Some((index_path, format)) = production_index_path(&p, &reader.header) {
+                match File::open(index_path) {
+                    Ok(index_file) => {
+                        reader.production_index = Some(ProductionIndex::new(
+                            wrap(index_file),
+                            format,
+                            &reader.fields_info,
+                        )?)
+                    }
+                    Err(error) if error.kind() == std::io::ErrorKind::NotFound => {}
+                    Err(error) => return Err(Error::io_error(error, 0)),
+                }
+            }
+        }
+
         let at_least_one_field_is_memo = reader
             .fields_info
             .iter()
             .any(|f_info| f_info.field_type == FieldType::Memo);
 
         if at_least_one_field_is_memo {
-            let memo_type = dbg!(reader.header.file_type.supported_memo_type());
+            let memo_type = reader.options.memo_file_type(&reader.header);
             if let Some(mt) = memo_type {
-                let memo_path = match mt {
-                    MemoFileType::DbaseMemo | MemoFileType::DbaseMemo4 => p.with_extension("dbt"),
-                    MemoFileType::FoxBaseMemo => p.with_extension("fpt"),
+                let memo_path = match (memo_path, mt) {
+                    (Some(memo_path), _) => memo_path,
+                    (None, MemoFileType::DbaseMemo) | (None, MemoFileType::DbaseMemo4) => {
+                        p.with_extension("dbt")
+                    }
+                    (None, MemoFileType::FoxBaseMemo) => p.with_extension("fpt"),
                 };
 
-                let memo_file = File::open(memo_path)
-                    .map_err(|error|
-                        Error {
+                let memo_file = match File::open(memo_path) {
+                    Ok(memo_file) => memo_file,
+                    Err(error) if reader.options.tolerates_missing_memo(&error) => {
+                        return Ok(reader)
+                    }
+                    Err(error) => {
+                        return Err(Error {
                             record_num: 0,
                             field: None,
                             kind: ErrorKind::ErrorOpeningMemoFile(error),
-                        })?;
+                        })
+                    }
+                };
 
-                let memo_reader = MemoReader::new(mt, BufReader::new(memo_file))
+                let memo_reader = reader
+                    .options
+                    .open_memo(mt, wrap(memo_file))
                     .map_err(|error| Error::io_error(error, 0))?;
                 reader.memo_reader = Some(memo_reader);
             }
@@ -285,6 +1451,65 @@ impl Reader<BufReader<File>> {
     }
 }
 
+/// Object safe interface to read the records of a table one at a time
+///
+/// Code that needs to work with tables from different kinds of sources,
+/// like the attribute table of a shapefile which must stay aligned with its
+/// shapes, can use a `Box<dyn RecordReader>`.
+///
+/// # Example
+///
+/// ```
+/// use dbase::RecordReader;
+/// # fn main() -> Result<(), dbase::Error> {
+/// let reader = dbase::Reader::from_path("tests/data/line.dbf")?;
+/// let mut reader: Box<dyn RecordReader> = Box::new(reader);
+/// assert_eq!(reader.num_records(), 1);
+/// while let Some(record) = reader.read_next_record() {
+///     let record = record?;
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub trait RecordReader {
+    /// Returns the fields of the records, the first one being the deletion flag
+    fn fields(&self) -> &[FieldInfo];
+
+    /// Returns the number of records of the table
+    fn num_records(&self) -> usize;
+
+    /// Returns the index (0-based) of the record that will be read next
+    fn next_record_index(&self) -> usize;
+
+    /// Reads the next record, returns `None` once all the records were read
+    fn read_next_record(&mut self) -> Option<Result<Record, Error>>;
+
+    /// Skips the next record, without reading it
+    fn skip_next_record(&mut self) -> Result<(), Error>;
+}
+
+impl<T: Read + Seek> RecordReader for Reader<T> {
+    fn fields(&self) -> &[FieldInfo] {
+        &self.fields_info
+    }
+
+    fn num_records(&self) -> usize {
+        self.record_count()
+    }
+
+    fn next_record_index(&self) -> usize {
+        self.next_record as usize
+    }
+
+    fn read_next_record(&mut self) -> Option<Result<Record, Error>> {
+        self.read_next_record_as::<Record>()
+    }
+
+    fn skip_next_record(&mut self) -> Result<(), Error> {
+        self.skip_records(1).map(|_| ())
+    }
+}
+
 /// Simple struct to wrap together the value with the name
 /// of the field it belongs to
 pub struct NamedValue<'a, T> {
@@ -301,12 +1526,14 @@ pub struct NamedValue<'a, T> {
 /// When trying to read more fields than there are, an EndOfRecord error
 /// will be returned.
 pub struct FieldIterator<'a, T: Read + Seek> {
-    /// The source from where we read the data
-    pub(crate) source: &'a mut T,
+    /// The bytes of the record, from where we read the data
+    pub(crate) source: &'a mut Cursor<&'a [u8]>,
     /// The fields that make the record
     pub(crate) fields_info: std::iter::Peekable<std::slice::Iter<'a, FieldInfo>>,
     /// The source where the Memo field data is read
     pub(crate) memo_reader: &'a mut Option<MemoReader<T>>,
+    /// How the values are read
+    pub(crate) options: &'a ReadingOptions,
 }
 
 impl<'a, T: Read + Seek> FieldIterator<'a, T> {
@@ -375,24 +1602,6 @@ impl<'a, T: Read + Seek> FieldIterator<'a, T> {
         }
     }
 
-    /// Skips all the remaining field of the record
-    ///
-    /// used internally to make sure the data stream is at the right position
-    /// when we will start reading the next record
-    ///
-    /// Does nothing if the last field of the record was already skipped or read.
-    fn skip_remaining_fields(&mut self) -> Result<(), FieldIOError> {
-        while let Some(field_info) = self.fields_info.next() {
-            if let Err(error) = self.skip_field(field_info) {
-                return Err(FieldIOError {
-                    field: Some(field_info.clone()),
-                    kind: error.into(),
-                });
-            }
-        }
-        Ok(())
-    }
-
     /// Reads the raw bytes of the next field without doing any filtering or trimming
     #[cfg(feature = "serde")]
     pub(crate) fn read_next_field_raw(&mut self) -> Result<Vec<u8>, FieldIOError> {
@@ -443,6 +1652,39 @@ impl<'a, T: Read + Seek> FieldIterator<'a, T> {
         })
     }
 
+    /// Reads whether the record is marked as deleted, the deletion flag
+    /// must be the next field
+    pub(crate) fn read_deletion_flag(&mut self) -> Result<bool, FieldIOError> {
+        match self.fields_info.next() {
+            Some(field_info) if field_info.is_deletion_flag() => {
+                let flag = self.source.read_u8().map_err(|error| {
+                    FieldIOError::new(ErrorKind::IoError(error), Some(field_info.to_owned()))
+                })?;
+                Ok(flag == b'*')
+            }
+            field_info => Err(FieldIOError::new(
+                ErrorKind::Message("expected the deletion flag".to_owned()),
+                field_info.cloned(),
+            )),
+        }
+    }
+
+    /// Reads the raw content of the next field, which must be a memo field
+    pub(crate) fn read_next_field_memo_bytes(&mut self) -> Result<Vec<u8>, FieldIOError> {
+        let field_info = self
+            .fields_info
+            .next()
+            .ok_or(FieldIOError::end_of_record())?;
+        if field_info.field_type != FieldType::Memo {
+            return Err(FieldIOError::new(
+                ErrorKind::IncompatibleType,
+                Some(field_info.to_owned()),
+            ));
+        }
+        read_memo_bytes(self.source, self.memo_reader, field_info)
+            .map_err(|kind| FieldIOError::new(kind, Some(field_info.to_owned())))
+    }
+
     /// Advance the source to skip the field
     fn skip_field(&mut self, field_info: &FieldInfo) -> std::io::Result<()> {
         self.source
@@ -452,7 +1694,7 @@ impl<'a, T: Read + Seek> FieldIterator<'a, T> {
 
     /// read the next field using the given info
     fn read_field(&mut self, field_info: &'a FieldInfo) -> Result<FieldValue, FieldIOError> {
-        match FieldValue::read_from(self.source, self.memo_reader, field_info) {
+        match FieldValue::read_from(self.source, self.memo_reader, field_info, self.options) {
             Ok(value) => Ok(value),
             Err(kind) => Err(FieldIOError {
                 field: Some(field_info.clone()),
@@ -482,28 +1724,54 @@ impl<'a, T: Read + Seek> FusedIterator for FieldIterator<'a, T> {}
 pub struct RecordIterator<'a, T: Read + Seek, R: ReadableRecord> {
     reader: &'a mut Reader<T>,
     record_type: std::marker::PhantomData<R>,
-    current_record: u32,
 }
 
 impl<'a, T: Read + Seek, R: ReadableRecord> Iterator for RecordIterator<'a, T, R> {
     type Item = Result<R, Error>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.current_record >= self.reader.header.num_records {
-            None
-        } else {
-            let mut iter = FieldIterator {
-                source: &mut self.reader.source,
-                fields_info: self.reader.fields_info.iter().peekable(),
-                memo_reader: &mut self.reader.memo_reader,
-            };
+        self.reader.read_next_record_as::<R>()
+    }
+
+    /// Skips the records with a seek instead of reading them
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        match self.reader.skip_records(n) {
+            Ok(_) => self.next(),
+            Err(error) => Some(Err(error)),
+        }
+    }
+}
+
+/// Where a record is in the file, and whether it is marked as deleted
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct RecordMetadata {
+    /// Index (0-based) of the record in the table
+    pub record_num: usize,
+    /// Offset in bytes of the record (its deletion flag) from the start of the file
+    pub offset: u64,
+    /// Whether the deletion flag of the record is set
+    pub is_deleted: bool,
+}
 
-            let record = R::read_using(&mut iter)
-                .and_then(|record| iter.skip_remaining_fields().and(Ok(record)))
-                .map_err(|error| Error::new(error, self.current_record as usize));
+/// Iterator over the records contained in the dBase, with their metadata
+pub struct RecordMetadataIterator<'a, T: Read + Seek, R: ReadableRecord> {
+    reader: &'a mut Reader<T>,
+    record_type: std::marker::PhantomData<R>,
+}
+
+impl<'a, T: Read + Seek, R: ReadableRecord> Iterator for RecordMetadataIterator<'a, T, R> {
+    type Item = Result<(RecordMetadata, R), Error>;
 
-            self.current_record += 1;
-            Some(record)
+    fn next(&mut self) -> Option<Self::Item> {
+        let result = self.reader.read_next_record_as::<R>()?;
+        Some(result.map(|record| (self.reader.last_record_metadata(), record)))
+    }
+
+    /// Skips the records with a seek instead of reading them
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        match self.reader.skip_records(n) {
+            Ok(_) => self.next(),
+            Err(error) => Some(Err(error)),
         }
     }
 }
@@ -521,18 +1789,107 @@ pub fn read<P: AsRef<Path>>(path: P) -> Result<Vec<Record>, Error> {
     reader.read()
 }
 
+/// The header and the fields of a table, without its records
+///
+/// Returned by [read_schema](fn.read_schema.html).
+#[derive(Debug, Clone)]
+pub struct TableInfo {
+    /// The header of the table
+    pub header: Header,
+    /// The fields of the table, like [Reader::fields](struct.Reader.html#method.fields)
+    /// the first one is the deletion flag
+    pub fields: Vec<FieldInfo>,
+    /// Path to the database (.dbc) a Visual FoxPro table belongs to
+    pub backlink: Option<String>,
+}
+
+/// Reads the header and the fields of the .dbf file at the given path
+///
+/// Only the start of the file is read, the records do not have to be there,
+/// so it is a cheap way to list the fields of many tables.
+/// The names of the fields are not checked, and encrypted tables are not an error.
+///
+/// # Example
+///
+/// ```
+/// # fn main() -> Result<(), dbase::Error> {
+/// let info = dbase::read_schema("tests/data/stations.dbf")?;
+/// assert_eq!(info.header.num_records, 6);
+/// assert_eq!(info.fields[1].name(), "name");
+/// # Ok(())
+/// # }
+/// ```
+pub fn read_schema<P: AsRef<Path>>(path: P) -> Result<TableInfo, Error> {
+    let file = File::open(path).map_err(|error| Error::io_error(error, 0))?;
+    read_schema_from(BufReader::new(file))
+}
+
+/// Reads the header and the fields of a table from the source,
+/// which does not need to be seekable
+///
+/// See [read_schema](fn.read_schema.html).
+pub fn read_schema_from<R: Read>(mut source: R) -> Result<TableInfo, Error> {
+    let (header, fields, backlink) = read_table_info(&mut source, Flavor::Standard, &UnicodeLossy)?;
+    Ok(TableInfo {
+        header,
+        fields,
+        backlink,
+    })
+}
+
 #[cfg(test)]
 mod test {
+    use std::convert::TryInto;
     use std::fs::File;
-    use std::io::{Seek, SeekFrom};
+    use std::io::{Cursor, Seek};
 
     use super::*;
 
+    use crate::header::Version;
+    use crate::TableWriterBuilder;
+
+    #[test]
+    fn records_in_sets() {
+        let first = Record::builder()
+            .set("NAME", "Ferris")
+            .set("AMOUNT", f64::NAN)
+            .build();
+        let same = Record::builder()
+            .set("AMOUNT", f64::NAN)
+            .set("NAME", "Ferris")
+            .build();
+        let other = Record::builder()
+            .set("name", "Ferris")
+            .set("AMOUNT", f64::NAN)
+            .build();
+        assert_eq!(first, same);
+        assert_ne!(first, other);
+
+        let mut records = std::collections::HashSet::new();
+        assert!(records.insert(first));
+        assert!(!records.insert(same));
+        assert!(records.insert(other));
+        assert_eq!(records.len(), 2);
+    }
+
+    #[test]
+    fn schema_without_the_records() {
+        let table = std::fs::read("tests/data/stations.dbf").unwrap();
+        let reader = Reader::new(Cursor::new(table.as_slice())).unwrap();
+        let offset = usize::from(reader.header().offset_to_first_record);
+        let info = read_schema_from(&table[..offset]).unwrap();
+        assert_eq!(info.header.num_records, 6);
+        assert_eq!(info.fields, reader.fields());
+        assert_eq!(info.backlink, None);
+
+        assert!(read_schema_from(&table[..Header::SIZE + 10]).is_err());
+    }
+
     #[test]
     fn pos_after_reading() {
         let file = File::open("tests/data/line.dbf").unwrap();
         let mut reader = Reader::new(file).unwrap();
-        let pos_after_reading = reader.source.seek(SeekFrom::Current(0)).unwrap();
+        let pos_after_reading = reader.source.stream_position().unwrap();
 
         // Do not count the the "DeletionFlag record info that is added
         let mut expected_pos = Header::SIZE + ((reader.fields_info.len() - 1) * FieldInfo::SIZE);
@@ -540,4 +1897,924 @@ mod test {
         expected_pos += std::mem::size_of::<u8>();
         assert_eq!(pos_after_reading, expected_pos as u64);
     }
+
+    #[test]
+    fn visual_fox_pro_backlink() {
+        let mut table = std::fs::read("tests/data/line.dbf").unwrap();
+        let num_fields = Reader::new(Cursor::new(&table)).unwrap().fields().len() - 1;
+        let backlink_pos = Header::SIZE + num_fields * FieldInfo::SIZE + 1;
+        let mut backlink = b"..\\data\\sales.dbc".to_vec();
+        backlink.resize(usize::from(BACKLINK_SIZE), 0);
+        table.splice(backlink_pos..backlink_pos, backlink);
+        table[0] = 0x30;
+        let offset = u16::from_le_bytes([table[8], table[9]]) + BACKLINK_SIZE;
+        table[8..10].copy_from_slice(&offset.to_le_bytes());
+
+        let mut reader = Reader::new(Cursor::new(table)).unwrap();
+        assert_eq!(reader.fields().len(), num_fields + 1);
+        assert_eq!(reader.backlink(), Some("..\\data\\sales.dbc"));
+        let records = reader.read().unwrap();
+        assert_eq!(records, read("tests/data/line.dbf").unwrap());
+
+        // The backlink is kept when writing the table back
+        let writer =
+            TableWriterBuilder::from_reader(reader).build_with_dest(Cursor::new(Vec::<u8>::new()));
+        let written = writer.write(&records).unwrap().into_inner();
+        let mut reader = Reader::new(Cursor::new(written)).unwrap();
+        assert_eq!(reader.backlink(), Some("..\\data\\sales.dbc"));
+        assert_eq!(reader.read().unwrap(), records);
+    }
+
+    fn table_with_invalid_second_record() -> Vec<u8> {
+        let records = ["1", "2", "3"]
+            .iter()
+            .map(|value| {
+                let mut record = Record::default();
+                record.insert(
+                    "NAME".to_owned(),
+                    FieldValue::Character(Some(format!(" name{}", value))),
+                );
+                record.insert(
+                    "VALUE".to_owned(),
+                    FieldValue::Numeric(Some(value.parse::<f64>().unwrap())),
+                );
+                record
+            })
+            .collect::<Vec<Record>>();
+        let mut table = TableWriterBuilder::new()
+            .add_character_field("NAME".try_into().unwrap(), 10)
+            .add_numeric_field("VALUE".try_into().unwrap(), 5, 0)
+            .build_with_dest(Cursor::new(Vec::<u8>::new()))
+            .write(&records)
+            .unwrap()
+            .into_inner();
+        let reader = Reader::new(Cursor::new(&table)).unwrap();
+        let position = reader.header.offset_to_first_record as usize
+            + reader.header.size_of_record as usize
+            + 12;
+        table[position] = b'x';
+        table
+    }
+
+    #[test]
+    fn error_policy() {
+        let table = table_with_invalid_second_record();
+        let mut reader = Reader::new(Cursor::new(&table)).unwrap();
+        let results = reader.iter_records().collect::<Vec<_>>();
+        assert_eq!(results.len(), 3);
+        assert!(results[0].is_ok());
+        assert_eq!(results[1].as_ref().unwrap_err().record_num(), 1);
+        // The reader was not lost by the error
+        assert!(results[2].is_ok());
+
+        let options = ReadingOptions::new().error_policy(ErrorPolicy::SkipRecord);
+        let mut reader = Reader::with_options(Cursor::new(&table), options).unwrap();
+        let records = reader.read().unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(
+            records[1].get("VALUE"),
+            Some(&FieldValue::Numeric(Some(3.0)))
+        );
+    }
+
+    #[test]
+    fn read_into_buffers() {
+        let table = table_with_invalid_second_record();
+        let options = ReadingOptions::new().error_policy(ErrorPolicy::SkipRecord);
+        let expected = Reader::with_options(Cursor::new(&table), options.clone())
+            .unwrap()
+            .read()
+            .unwrap();
+        let mut reader = Reader::with_options(Cursor::new(&table), options).unwrap();
+        let mut record = Record::default();
+        record.insert("OTHER".to_owned(), FieldValue::Logical(None));
+        let mut records = vec![];
+        while reader.read_record_into(&mut record).unwrap() {
+            assert!(record.get("OTHER").is_none());
+            records.push(std::mem::take(&mut record));
+        }
+        assert_eq!(records, expected);
+
+        let mut reader = Reader::new(Cursor::new(&table)).unwrap();
+        let mut buffer = vec![];
+        let mut num_records = 0;
+        while reader.read_record_bytes_into(&mut buffer).unwrap() {
+            assert_eq!(buffer.len(), 16);
+            assert_eq!(buffer[0], b' ');
+            num_records += 1;
+        }
+        assert_eq!(num_records, 3);
+    }
+
+    #[test]
+    fn skip_records() {
+        let table = table_with_invalid_second_record();
+        let mut reader = Reader::new(Cursor::new(&table)).unwrap();
+        // The invalid record is skipped over without being read
+        let record = reader.iter_records().nth(2).unwrap().unwrap();
+        assert_eq!(record.get("VALUE"), Some(&FieldValue::Numeric(Some(3.0))));
+        assert!(reader.iter_records().nth(1).is_none());
+
+        let mut reader = Reader::new(Cursor::new(&table)).unwrap();
+        assert_eq!(reader.skip_records(1).unwrap(), 1);
+        assert!(reader.iter_records().next().unwrap().is_err());
+        assert_eq!(reader.skip_records(5).unwrap(), 1);
+        assert!(reader.read().unwrap().is_empty());
+    }
+
+    #[test]
+    fn character_trim() {
+        let table = table_with_invalid_second_record();
+        let name_of_first_record = |trim| {
+            let options = ReadingOptions::new().character_trim(trim);
+            let mut reader = Reader::with_options(Cursor::new(&table), options).unwrap();
+            let mut record = reader.iter_records().next().unwrap().unwrap();
+            match record.remove("NAME") {
+                Some(FieldValue::Character(Some(name))) => name,
+                value => panic!("Unexpected value {:?}", value),
+            }
+        };
+        assert_eq!(name_of_first_record(Some(TrimOption::BeginEnd)), "name1");
+        assert_eq!(name_of_first_record(Some(TrimOption::Begin)), "name1    ");
+        assert_eq!(name_of_first_record(Some(TrimOption::End)), " name1");
+        assert_eq!(name_of_first_record(None), " name1    ");
+    }
+
+    #[test]
+    fn numeric_separators() {
+        let mut record = Record::default();
+        record.insert(
+            "VALUE".to_owned(),
+            FieldValue::Character(Some("-1.234,5".to_owned())),
+        );
+        let mut table = TableWriterBuilder::new()
+            .add_character_field("VALUE".try_into().unwrap(), 10)
+            .build_with_dest(Cursor::new(Vec::<u8>::new()))
+            .write(&[record])
+            .unwrap()
+            .into_inner();
+        // The field type is changed to Numeric
+        table[Header::SIZE + 11] = b'N';
+        assert!(Reader::new(Cursor::new(&table)).unwrap().read().is_err());
+
+        let options = ReadingOptions::new()
+            .decimal_separator(',')
+            .thousands_separator(Some('.'));
+        let mut reader = Reader::with_options(Cursor::new(&table), options).unwrap();
+        let records = reader.read().unwrap();
+        assert_eq!(
+            records[0].get("VALUE"),
+            Some(&FieldValue::Numeric(Some(-1234.5)))
+        );
+    }
+
+    #[test]
+    fn invalid_date_policy() {
+        let dates = ["00000000", "19999999", "garbage!", "20240229", "        "];
+        let records = dates
+            .iter()
+            .map(|date| {
+                let mut record = Record::default();
+                record.insert(
+                    "DATE".to_owned(),
+                    FieldValue::Character(Some(date.to_string())),
+                );
+                record
+            })
+            .collect::<Vec<Record>>();
+        let mut table = TableWriterBuilder::new()
+            .add_character_field("DATE".try_into().unwrap(), 8)
+            .build_with_dest(Cursor::new(Vec::<u8>::new()))
+            .write(&records)
+            .unwrap()
+            .into_inner();
+        // The field type is changed to Date
+        table[Header::SIZE + 11] = b'D';
+        let dates = |policy| {
+            let options = ReadingOptions::new().invalid_date_policy(policy);
+            Reader::with_options(Cursor::new(&table), options)
+                .unwrap()
+                .iter_records()
+                .map(|record| record.map(|mut record| record.remove("DATE").unwrap()))
+                .collect::<Vec<Result<FieldValue, Error>>>()
+        };
+        let leap_day = FieldValue::Date(Some(crate::Date::new(29, 2, 2024)));
+
+        let lenient = dates(InvalidDatePolicy::Lenient);
+        match lenient[1].as_ref().unwrap() {
+            FieldValue::Date(Some(date)) => assert_eq!(date.to_string(), "19999999"),
+            value => panic!("Unexpected value {:?}", value),
+        }
+        assert!(lenient[2].is_err());
+
+        let strict = dates(InvalidDatePolicy::Fail);
+        assert!(matches!(
+            strict[0].as_ref().unwrap_err().kind(),
+            ErrorKind::InvalidDate(text) if text == "00000000"
+        ));
+        assert!(strict[1].is_err() && strict[2].is_err());
+        assert_eq!(strict[3].as_ref().unwrap(), &leap_day);
+
+        let null = dates(InvalidDatePolicy::Null)
+            .into_iter()
+            .map(Result::unwrap)
+            .collect::<Vec<FieldValue>>();
+        assert_eq!(
+            null,
+            vec![
+                FieldValue::Date(None),
+                FieldValue::Date(None),
+                FieldValue::Date(None),
+                leap_day.clone(),
+                FieldValue::Date(None)
+            ]
+        );
+
+        let raw = dates(InvalidDatePolicy::Raw);
+        assert_eq!(
+            raw[2].as_ref().unwrap(),
+            &FieldValue::Character(Some("garbage!".to_owned()))
+        );
+        assert_eq!(raw[3].as_ref().unwrap(), &leap_day);
+        assert_eq!(raw[4].as_ref().unwrap(), &FieldValue::Date(None));
+    }
+
+    #[test]
+    fn reader_builder() {
+        let table = table_with_invalid_second_record();
+        let mut reader = ReaderBuilder::new()
+            .character_trim(None)
+            .error_policy(ErrorPolicy::SkipRecord)
+            .build_with_source(Cursor::new(&table))
+            .unwrap();
+        let records = reader.read().unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(
+            records[0].get("NAME"),
+            Some(&FieldValue::Character(Some(" name1    ".to_owned())))
+        );
+
+        let builder = ReaderBuilder::new().options(ReadingOptions::new().flavor(Flavor::Clipper));
+        assert_eq!(builder.reading_options().flavor, Flavor::Clipper);
+        let reader = builder.build_from_path("tests/data/line.dbf").unwrap();
+        assert_eq!(reader.header().num_records, 1);
+    }
+
+    #[test]
+    fn memo_path_and_format() {
+        // dBase III table flagged as having no memo file, with a memo field
+        let mut table = vec![0x03, 120, 1, 1, 1, 0, 0, 0, 65, 0, 11, 0];
+        table.resize(Header::SIZE, 0);
+        table.extend_from_slice(b"NOTES\0\0\0\0\0\0M");
+        table.extend_from_slice(&[0, 0, 0, 0, 10, 0]);
+        table.resize(Header::SIZE + FieldInfo::SIZE, 0);
+        table.push(0x0D);
+        table.extend_from_slice(b"          1");
+        table.push(0x1A);
+        let mut memo = vec![2, 0, 0, 0];
+        memo.resize(512, 0);
+        memo.extend_from_slice(b"Ferris\x1A\x1A");
+        memo.resize(1024, 0);
+        let expected = Some(&FieldValue::Memo("Ferris".to_owned()));
+
+        let error = ReaderBuilder::new()
+            .build_with_memo_source(Cursor::new(table.clone()), Cursor::new(memo.clone()))
+            .unwrap()
+            .read()
+            .unwrap_err();
+        assert!(matches!(error.kind(), ErrorKind::MissingMemoFile));
+        let records = ReaderBuilder::new()
+            .memo_format(MemoFormat::DBase3)
+            .build_with_memo_source(Cursor::new(table.clone()), Cursor::new(memo.clone()))
+            .unwrap()
+            .read()
+            .unwrap();
+        assert_eq!(records[0].get("NOTES"), expected);
+
+        let path = std::env::temp_dir().join("dbase_memo_path.dbf");
+        let memo_path = std::env::temp_dir().join("dbase_memo_path_notes.bin");
+        std::fs::write(&path, &table).unwrap();
+        std::fs::write(&memo_path, &memo).unwrap();
+        let records = ReaderBuilder::new()
+            .memo_path(&memo_path)
+            .memo_format(MemoFormat::DBase3)
+            .build_from_path(&path)
+            .unwrap()
+            .read()
+            .unwrap();
+        std::fs::remove_file(&path).unwrap();
+        std::fs::remove_file(&memo_path).unwrap();
+        assert_eq!(records[0].get("NOTES"), expected);
+    }
+
+    #[test]
+    fn missing_memo_policy() {
+        // dBase III table with a memo field, whose memo file is lost
+        let mut table = vec![0x83, 120, 1, 1, 1, 0, 0, 0, 97, 0, 22, 0];
+        table.resize(Header::SIZE, 0);
+        table.extend_from_slice(b"NOTES\0\0\0\0\0\0M");
+        table.extend_from_slice(&[0, 0, 0, 0, 10, 0]);
+        table.resize(Header::SIZE + FieldInfo::SIZE, 0);
+        table.extend_from_slice(b"NAME\0\0\0\0\0\0\0C");
+        table.extend_from_slice(&[0, 0, 0, 0, 11, 0]);
+        table.resize(Header::SIZE + 2 * FieldInfo::SIZE, 0);
+        table.push(0x0D);
+        table.extend_from_slice(b"          1Ferris     ");
+        table.push(0x1A);
+        let path = std::env::temp_dir().join("dbase_missing_memo_policy.dbf");
+        std::fs::write(&path, &table).unwrap();
+
+        assert!(matches!(
+            ReaderBuilder::new().build_from_path(&path),
+            Err(Error {
+                kind: ErrorKind::ErrorOpeningMemoFile(_),
+                ..
+            })
+        ));
+        let read = |policy| {
+            ReaderBuilder::new()
+                .missing_memo_policy(policy)
+                .build_from_path(&path)
+                .unwrap()
+                .read()
+                .unwrap()
+                .remove(0)
+        };
+        let empty = read(MissingMemoPolicy::Empty);
+        let null = read(MissingMemoPolicy::Null);
+        let mut reader = ReaderBuilder::new()
+            .missing_memo_policy(MissingMemoPolicy::Null)
+            .build_from_path(&path)
+            .unwrap();
+        let handle = reader
+            .next_record_ref()
+            .unwrap()
+            .unwrap()
+            .memo_handle("NOTES")
+            .unwrap()
+            .unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(empty.get("NOTES"), Some(&FieldValue::Memo(String::new())));
+        assert_eq!(null.get("NOTES"), Some(&FieldValue::Character(None)));
+        assert_eq!(
+            null.get("NAME"),
+            Some(&FieldValue::Character(Some("Ferris".to_owned())))
+        );
+        assert_eq!(handle.block_index(), Some(1));
+        assert!(matches!(
+            reader.memo_reader(&handle).unwrap_err().kind(),
+            ErrorKind::MissingMemoFile
+        ));
+    }
+
+    #[test]
+    fn field_encoding() {
+        #[derive(Debug)]
+        struct Latin1;
+
+        impl Encoding for Latin1 {
+            fn decode<'a>(
+                &self,
+                bytes: &'a [u8],
+            ) -> Result<std::borrow::Cow<'a, str>, crate::DecodeError> {
+                Ok(bytes.iter().map(|b| char::from(*b)).collect::<String>().into())
+            }
+        }
+
+        let mut record = Record::default();
+        record.insert("NAME".to_owned(), FieldValue::from("Ren".to_owned()));
+        record.insert("CITY".to_owned(), FieldValue::from("Gen".to_owned()));
+        let mut table = TableWriterBuilder::new()
+            .add_character_field("NAME".try_into().unwrap(), 4)
+            .add_character_field("CITY".try_into().unwrap(), 4)
+            .build_with_dest(Cursor::new(Vec::<u8>::new()))
+            .write(&[record])
+            .unwrap()
+            .into_inner();
+        // An é in Latin-1 in both fields
+        let first_record = table.len() - 10;
+        table[first_record + 4] = 0xE9;
+        table[first_record + 8] = 0xE9;
+
+        let record = ReaderBuilder::new()
+            .field_encoding("name", Latin1)
+            .build_with_source(Cursor::new(&table))
+            .unwrap()
+            .read()
+            .unwrap()
+            .remove(0);
+        assert_eq!(
+            record.get("NAME"),
+            Some(&FieldValue::Character(Some("René".to_owned())))
+        );
+        assert_eq!(
+            record.get("CITY"),
+            Some(&FieldValue::Character(Some("Gen\u{FFFD}".to_owned())))
+        );
+    }
+
+    #[test]
+    fn blank_policy() {
+        let mut record = Record::default();
+        record.insert("NAME".to_owned(), FieldValue::Character(None));
+        let table = TableWriterBuilder::new()
+            .add_character_field("NAME".try_into().unwrap(), 4)
+            .build_with_dest(Cursor::new(Vec::<u8>::new()))
+            .write(&[record])
+            .unwrap()
+            .into_inner();
+        let name = |policy| {
+            let options = ReadingOptions::new().blank_policy(policy);
+            let mut reader = Reader::with_options(Cursor::new(&table), options).unwrap();
+            let mut record = reader.iter_records().next().unwrap().unwrap();
+            record.remove("NAME").unwrap()
+        };
+        assert_eq!(name(BlankPolicy::Null), FieldValue::Character(None));
+        assert_eq!(
+            name(BlankPolicy::Empty),
+            FieldValue::Character(Some(String::new()))
+        );
+        assert_eq!(
+            name(BlankPolicy::Padded),
+            FieldValue::Character(Some("    ".to_owned()))
+        );
+    }
+
+    #[test]
+    fn raw_records() {
+        let table = std::fs::read("tests/data/stations.dbf").unwrap();
+        let mut reader = Reader::new(Cursor::new(table.as_slice())).unwrap();
+        let start = usize::from(reader.header().offset_to_first_record);
+        let length = reader.record_length();
+        let mut records = vec![];
+        while let Some(bytes) = reader.next_raw() {
+            records.push(bytes.unwrap().to_vec());
+        }
+        assert_eq!(records.len(), 6);
+        assert_eq!(records.concat(), &table[start..start + 6 * length]);
+
+        assert_eq!(reader.record_bytes_at(2).unwrap(), records[2].as_slice());
+        assert_eq!(reader.next_raw().unwrap().unwrap(), records[3].as_slice());
+        assert!(reader.record_bytes_at(6).is_err());
+    }
+
+    #[test]
+    fn records_are_the_size_of_the_fields() {
+        let records = ["AAA", "BBB", "CCC", "DDD"]
+            .iter()
+            .map(|name| Record::builder().set("NAME", name.to_string()).build())
+            .collect::<Vec<_>>();
+        let mut table = TableWriterBuilder::new()
+            .add_character_field("NAME".try_into().unwrap(), 3)
+            .build_with_dest(Cursor::new(Vec::<u8>::new()))
+            .write(&records)
+            .unwrap()
+            .into_inner();
+        // The size of the records in the header is one byte short
+        table[10] -= 1;
+
+        let name = |record: Record| record.get("NAME").cloned().unwrap();
+        let mut reader = Reader::new(Cursor::new(table.as_slice())).unwrap();
+        assert_eq!(reader.read().unwrap(), records);
+        assert_eq!(name(reader.record_at(3).unwrap()), name(records[3].clone()));
+        assert_eq!(reader.record_bytes_at(1).unwrap(), b" BBB");
+
+        let mut reader = Reader::new(Cursor::new(table.as_slice())).unwrap();
+        reader.skip_records(2).unwrap();
+        assert_eq!(reader.read().unwrap(), &records[2..]);
+        let mut follower = reader.follow().unwrap();
+        assert!(follower.poll().unwrap().is_empty());
+    }
+
+    #[test]
+    fn nul_padding() {
+        let mut record = Record::default();
+        record.insert(
+            "NAME".to_owned(),
+            FieldValue::Character(Some("Ed".to_owned())),
+        );
+        record.insert("CITY".to_owned(), FieldValue::Character(None));
+        let mut table = TableWriterBuilder::new()
+            .add_character_field("NAME".try_into().unwrap(), 6)
+            .add_character_field("CITY".try_into().unwrap(), 4)
+            .build_with_dest(Cursor::new(Vec::<u8>::new()))
+            .write(&[record])
+            .unwrap()
+            .into_inner();
+        // NULs mixed with the spaces of the name, only NULs in the city
+        let first_record = table.len() - 12;
+        table[first_record + 4] = 0;
+        table[first_record + 6] = 0;
+        for byte in &mut table[first_record + 7..first_record + 11] {
+            *byte = 0;
+        }
+
+        let read = |is_padding| {
+            let options = ReadingOptions::new().nul_padding(is_padding);
+            let mut reader = Reader::with_options(Cursor::new(&table), options).unwrap();
+            reader.read().unwrap().remove(0)
+        };
+        let record = read(true);
+        assert_eq!(
+            record.get("NAME"),
+            Some(&FieldValue::Character(Some("Ed".to_owned())))
+        );
+        assert_eq!(record.get("CITY"), Some(&FieldValue::Character(None)));
+        let record = read(false);
+        assert_eq!(
+            record.get("NAME"),
+            Some(&FieldValue::Character(Some("Ed \0 \0".to_owned())))
+        );
+        assert_eq!(
+            record.get("CITY"),
+            Some(&FieldValue::Character(Some("\0\0\0\0".to_owned())))
+        );
+    }
+
+    #[test]
+    fn step_through_records() {
+        let table = table_with_invalid_second_record();
+        let mut reader: Box<dyn RecordReader> = Box::new(Reader::new(Cursor::new(table)).unwrap());
+        assert_eq!(reader.num_records(), 3);
+        assert_eq!(reader.fields().len(), 3);
+        assert!(reader.read_next_record().unwrap().is_ok());
+        reader.skip_next_record().unwrap();
+        assert_eq!(reader.next_record_index(), 2);
+        let record = reader.read_next_record().unwrap().unwrap();
+        assert_eq!(record.get("VALUE"), Some(&FieldValue::Numeric(Some(3.0))));
+        assert!(reader.read_next_record().is_none());
+        reader.skip_next_record().unwrap();
+        assert_eq!(reader.next_record_index(), 3);
+    }
+
+    #[test]
+    fn records_with_metadata() {
+        let mut table = table_with_invalid_second_record();
+        let reader = Reader::new(Cursor::new(&table)).unwrap();
+        let first_record = u64::from(reader.header.offset_to_first_record);
+        let record_size = u64::from(reader.header.size_of_record);
+        table[(first_record + 2 * record_size) as usize] = b'*';
+
+        let options = ReadingOptions::new().error_policy(ErrorPolicy::SkipRecord);
+        let mut reader = Reader::with_options(Cursor::new(&table), options).unwrap();
+        let metadata = reader
+            .iter_records_with_metadata()
+            .map(|result| result.unwrap().0)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            metadata,
+            vec![
+                RecordMetadata {
+                    record_num: 0,
+                    offset: first_record,
+                    is_deleted: false,
+                },
+                RecordMetadata {
+                    record_num: 2,
+                    offset: first_record + 2 * record_size,
+                    is_deleted: true,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn clipper_long_character_fields() {
+        let mut builder =
+            TableWriterBuilder::new().add_character_field("TEXT".try_into().unwrap(), 10);
+        builder.v[0].field_length = 300;
+        let mut record = Record::default();
+        record.insert(
+            "TEXT".to_owned(),
+            FieldValue::Character(Some("x".repeat(300))),
+        );
+        let mut table = builder
+            .build_with_dest(Cursor::new(Vec::<u8>::new()))
+            .write(&[record])
+            .unwrap()
+            .into_inner();
+        // Elsewhere, it is a shorter field with decimal places
+        let reader = Reader::new(Cursor::new(&table)).unwrap();
+        assert_eq!(reader.fields()[1].length(), 44);
+
+        // Without the terminator
+        let terminator_position = Header::SIZE + FieldInfo::SIZE;
+        assert_eq!(table.remove(terminator_position), TERMINATOR_VALUE);
+        let offset_to_first_record = (terminator_position as u16).to_le_bytes();
+        table[8..10].copy_from_slice(&offset_to_first_record);
+
+        let options = ReadingOptions::new().flavor(Flavor::Clipper);
+        let mut reader = Reader::with_options(Cursor::new(&table), options).unwrap();
+        assert_eq!(reader.fields()[1].length(), 300);
+        let records = reader.read().unwrap();
+        assert_eq!(
+            records[0].get("TEXT"),
+            Some(&FieldValue::Character(Some("x".repeat(300))))
+        );
+    }
+
+    #[test]
+    fn dbase2_tables() {
+        let mut table = vec![0x02, 2, 0, 3, 14, 85, 16, 0];
+        for (name, field_type, length, num_decimal_places) in
+            [("NAME", b'C', 10, 0), ("AMOUNT", b'N', 5, 1)]
+        {
+            let mut descriptor = [0u8; 16];
+            descriptor[..name.len()].copy_from_slice(name.as_bytes());
+            descriptor[11] = field_type;
+            descriptor[12] = length;
+            descriptor[15] = num_decimal_places;
+            table.extend_from_slice(&descriptor);
+        }
+        table.push(TERMINATOR_VALUE);
+        table.resize(usize::from(Header::DBASE2_SIZE), 0);
+        table.extend_from_slice(b" Dupont     12.5*Durand    100.0\x1A");
+
+        let mut reader = Reader::new(Cursor::new(&table)).unwrap();
+        assert!(matches!(reader.header().file_type, Version::DBase2));
+        assert_eq!(reader.header().num_records, 2);
+        assert_eq!(reader.header().last_update, crate::Date::new(14, 3, 1985));
+        assert_eq!(reader.fields().len(), 3);
+        let records = reader
+            .iter_records_with_metadata()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert!(!records[0].0.is_deleted);
+        assert_eq!(
+            records[0].1.get("NAME"),
+            Some(&FieldValue::Character(Some("Dupont".to_owned())))
+        );
+        assert!(records[1].0.is_deleted);
+        assert_eq!(
+            records[1].1.get("AMOUNT"),
+            Some(&FieldValue::Numeric(Some(100.0)))
+        );
+
+        // FoxBase tables have the same version byte
+        let mut table = std::fs::read("tests/data/line.dbf").unwrap();
+        table[0] = 0x02;
+        let mut reader = Reader::new(Cursor::new(&table)).unwrap();
+        assert!(matches!(reader.header().file_type, Version::FoxBase));
+        assert_eq!(reader.read().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn dbase7_tables() {
+        let mut builder = TableWriterBuilder::new()
+            .add_character_field("NAME".try_into().unwrap(), 10)
+            .add_numeric_field("AMOUNT".try_into().unwrap(), 5, 1);
+        builder.hdr.file_type = Version::DBase7 {
+            supports_memo: false,
+        };
+        builder.hdr.language_driver_name = Some("DBWINUS0".to_owned());
+        builder.v[0].name = "NAME_OF_THE_CUSTOMER".to_owned();
+        let mut record = Record::default();
+        record.insert(
+            "NAME_OF_THE_CUSTOMER".to_owned(),
+            FieldValue::Character(Some("Dupont".to_owned())),
+        );
+        record.insert("AMOUNT".to_owned(), FieldValue::Numeric(Some(12.5)));
+        let table = builder
+            .build_with_dest(Cursor::new(Vec::<u8>::new()))
+            .write(&[record.clone()])
+            .unwrap()
+            .into_inner();
+
+        let mut reader = Reader::new(Cursor::new(&table)).unwrap();
+        let header = reader.header().clone();
+        assert!(matches!(header.file_type, Version::DBase7 { .. }));
+        assert_eq!(header.language_driver_name(), Some("DBWINUS0"));
+        assert_eq!(
+            usize::from(header.offset_to_first_record),
+            Header::DBASE7_SIZE + 2 * FieldInfo::DBASE7_SIZE + 1
+        );
+        assert_eq!(reader.fields()[1].name(), "NAME_OF_THE_CUSTOMER");
+        assert_eq!(reader.read().unwrap(), vec![record]);
+
+        // Written back the same
+        let written = TableWriterBuilder::from_reader(reader)
+            .build_with_dest(Cursor::new(Vec::<u8>::new()))
+            .write(&Reader::new(Cursor::new(&table)).unwrap().read().unwrap())
+            .unwrap()
+            .into_inner();
+        assert_eq!(written, table);
+    }
+
+    #[test]
+    fn dbase7_binary_fields() {
+        use crate::{Date, DateTime, Time};
+
+        let table = TableWriterBuilder::new()
+            .add_autoincrement_field("ID".try_into().unwrap())
+            .add_long_field("QUANTITY".try_into().unwrap())
+            .add_dbase7_double_field("PRICE".try_into().unwrap())
+            .add_timestamp_field("ORDERED_AT".try_into().unwrap())
+            .build_with_dest(Cursor::new(Vec::<u8>::new()))
+            .write(&[
+                Record::builder()
+                    .set("ID", FieldValue::Autoincrement(1))
+                    .set("QUANTITY", FieldValue::Long(Some(-2)))
+                    .set("PRICE", FieldValue::DBase7Double(Some(12.5)))
+                    .set(
+                        "ORDERED_AT",
+                        FieldValue::Timestamp(Some(DateTime::new(
+                            Date::new(14, 10, 2026),
+                            Time::new(12, 30, 15),
+                        ))),
+                    )
+                    .build(),
+                Record::builder()
+                    .set("ID", FieldValue::Autoincrement(2))
+                    .set("QUANTITY", FieldValue::Long(None))
+                    .set("PRICE", FieldValue::DBase7Double(Some(-0.5)))
+                    .set(
+                        "ORDERED_AT",
+                        FieldValue::Timestamp(Some(DateTime::from_milliseconds_since_unix_epoch(
+                            0,
+                        ))),
+                    )
+                    .build(),
+            ])
+            .unwrap()
+            .into_inner();
+
+        let header_size = Header::DBASE7_SIZE + 4 * FieldInfo::DBASE7_SIZE + 1;
+        let field_types = (0..4)
+            .map(|i| table[Header::DBASE7_SIZE + i * FieldInfo::DBASE7_SIZE + 32])
+            .collect::<Vec<u8>>();
+        assert_eq!(field_types, b"+IO@");
+        // Big endian, the sign bit flipped, all the bits of negative doubles flipped
+        let second_record = &table[header_size + 25..header_size + 50];
+        assert_eq!(&second_record[1..5], &[0x80, 0, 0, 2]);
+        assert_eq!(&second_record[5..9], &[0, 0, 0, 0]);
+        assert_eq!(&second_record[9..17], &(!(-0.5f64).to_bits()).to_be_bytes());
+        let unix_epoch = 2_440_588.0 * 86_400_000.0f64;
+        assert_eq!(
+            &second_record[17..25],
+            &(unix_epoch.to_bits() | (1 << 63)).to_be_bytes()
+        );
+
+        let mut reader = Reader::new(Cursor::new(&table)).unwrap();
+        assert!(matches!(
+            reader.header().file_type,
+            Version::DBase7 {
+                supports_memo: false
+            }
+        ));
+        let types = reader
+            .fields()
+            .iter()
+            .skip(1)
+            .map(|field_info| field_info.field_type())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            types,
+            vec![
+                FieldType::Autoincrement,
+                FieldType::Long,
+                FieldType::DBase7Double,
+                FieldType::Timestamp
+            ]
+        );
+        let records = reader.read().unwrap();
+        assert_eq!(records[0].get("QUANTITY"), Some(&FieldValue::Long(Some(-2))));
+        assert_eq!(
+            records[0].get("PRICE"),
+            Some(&FieldValue::DBase7Double(Some(12.5)))
+        );
+        assert_eq!(
+            records[0].get("ORDERED_AT"),
+            Some(&FieldValue::Timestamp(Some(DateTime::new(
+                Date::new(14, 10, 2026),
+                Time::new(12, 30, 15)
+            ))))
+        );
+        assert_eq!(records[1].get("ID"), Some(&FieldValue::Autoincrement(2)));
+        assert_eq!(records[1].get("QUANTITY"), Some(&FieldValue::Long(None)));
+        assert_eq!(
+            records[1].get("PRICE"),
+            Some(&FieldValue::DBase7Double(Some(-0.5)))
+        );
+
+        // Written back the same
+        let written = TableWriterBuilder::from_reader(reader)
+            .build_with_dest(Cursor::new(Vec::<u8>::new()))
+            .write(&records)
+            .unwrap()
+            .into_inner();
+        assert_eq!(written, table);
+    }
+
+    #[test]
+    fn record_count_and_length() {
+        let reader = Reader::new(Cursor::new(table_with_invalid_second_record())).unwrap();
+        assert_eq!(reader.record_count(), 3);
+        assert_eq!(reader.len(), 3);
+        assert_eq!(
+            reader.record_length(),
+            reader.header().size_of_record as usize
+        );
+
+        let reader = Reader::from_path("tests/data/stations.dbf").unwrap();
+        // The header of this file is off by one
+        assert_eq!(reader.record_length(), 1017);
+    }
+
+    #[test]
+    fn data_after_records() {
+        let mut table = table_with_invalid_second_record();
+        table.extend_from_slice(b"custom");
+        let mut reader = Reader::new(Cursor::new(table)).unwrap();
+        assert_eq!(reader.iter_records().count(), 3);
+        let (mut source, header, fields) = reader.into_parts();
+        assert_eq!(header.num_records, 3);
+        assert_eq!(fields.len(), 3);
+        let mut trailer = vec![];
+        source.read_to_end(&mut trailer).unwrap();
+        assert!(trailer.ends_with(b"custom"));
+    }
+
+    #[test]
+    fn integer_numerics() {
+        let records = [Some(123_456_789_012_345_678), None]
+            .iter()
+            .map(|id| {
+                let mut record = Record::default();
+                record.insert("ID".to_owned(), FieldValue::NumericInteger(*id));
+                record.insert("AREA".to_owned(), FieldValue::Numeric(Some(1.5)));
+                record
+            })
+            .collect::<Vec<Record>>();
+        let table = TableWriterBuilder::new()
+            .add_numeric_field("ID".try_into().unwrap(), 18, 0)
+            .add_numeric_field("AREA".try_into().unwrap(), 10, 2)
+            .build_with_dest(Cursor::new(Vec::<u8>::new()))
+            .write(&records)
+            .unwrap()
+            .into_inner();
+
+        let options = ReadingOptions::new().integer_numerics(true);
+        let records = Reader::with_options(Cursor::new(&table), options)
+            .unwrap()
+            .read()
+            .unwrap();
+        assert_eq!(
+            records[0].get("ID"),
+            Some(&FieldValue::NumericInteger(Some(123_456_789_012_345_678)))
+        );
+        assert_eq!(records[0].get("AREA"), Some(&FieldValue::Numeric(Some(1.5))));
+        assert_eq!(records[1].get("ID"), Some(&FieldValue::NumericInteger(None)));
+
+        let records = Reader::new(Cursor::new(&table)).unwrap().read().unwrap();
+        assert!(matches!(records[0].get("ID"), Some(FieldValue::Numeric(Some(_)))));
+
+        // Shorter values are written with a decimal point
+        let mut record = Record::default();
+        record.insert("COUNT".to_owned(), FieldValue::NumericInteger(Some(3)));
+        let table = TableWriterBuilder::new()
+            .add_numeric_field("COUNT".try_into().unwrap(), 5, 0)
+            .build_with_dest(Cursor::new(Vec::<u8>::new()))
+            .write(&[record])
+            .unwrap()
+            .into_inner();
+        let options = ReadingOptions::new().integer_numerics(true);
+        let records = Reader::with_options(Cursor::new(&table), options)
+            .unwrap()
+            .read()
+            .unwrap();
+        assert_eq!(records[0].get("COUNT"), Some(&FieldValue::NumericInteger(Some(3))));
+    }
+
+    #[test]
+    fn memory_budget() {
+        let reader = Reader::from_path("tests/data/stations.dbf").unwrap();
+        let estimated = reader.estimated_memory_size();
+        assert!(estimated > 6 * 4 * 254);
+
+        let options = ReadingOptions::new().memory_budget(Some(estimated));
+        let mut reader =
+            Reader::from_path_with_options("tests/data/stations.dbf", options).unwrap();
+        assert_eq!(reader.read().unwrap().len(), 6);
+
+        let options = ReadingOptions::new().memory_budget(Some(estimated - 1));
+        let mut reader =
+            Reader::from_path_with_options("tests/data/stations.dbf", options).unwrap();
+        match reader.read().unwrap_err().kind() {
+            ErrorKind::MemoryBudgetExceeded { estimated: e, budget } => {
+                assert_eq!((*e, *budget), (estimated, estimated - 1))
+            }
+            kind => panic!("unexpected error {:?}", kind),
+        }
+        // Nothing was read, the records fit once some are skipped
+        assert_eq!(reader.iter_records().count(), 6);
+        reader.set_record_range(3..6).unwrap();
+        assert_eq!(reader.read().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn no_backlink() {
+        let reader = Reader::from_path("tests/data/line.dbf").unwrap();
+        assert_eq!(reader.backlink(), None);
+    }
 }
@@ -0,0 +1,424 @@
+//! Casts of field values from a type of field to another
+//!
+//! The casts follow the functions dBase programs use to migrate fields:
+//! `STR` / `VAL` between numbers and text, `DTOS` / `CTOD` between dates and text,
+//! `TTOC` / `CTOT` between date times and text, `TTOD` / `DTOT` between dates and date times.
+use std::convert::TryFrom;
+
+use super::field::{Date, DateTime, FieldType, FieldValue, Time};
+use super::FieldConversionError;
+
+/// Returns the text of the number, without padding
+fn number_text(value: f64) -> String {
+    value.to_string()
+}
+
+fn date_time_text(date_time: &DateTime) -> String {
+    let (date, time) = (date_time.date(), date_time.time());
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+        date.year(),
+        date.month(),
+        date.day(),
+        time.hours(),
+        time.minutes(),
+        time.seconds()
+    )
+}
+
+/// Parses the date, `YYYYMMDD` (`DTOS`), `YYYY-MM-DD` or `MM/DD/YYYY` (`CTOD`)
+fn parse_date(text: &str) -> Option<Date> {
+    let numbers = |parts: &[&str]| {
+        parts
+            .iter()
+            .map(|part| part.parse::<u32>().ok())
+            .collect::<Option<Vec<_>>>()
+    };
+    let (year, month, day) = if text.len() == 8 && text.bytes().all(|b| b.is_ascii_digit()) {
+        let date = text.parse::<Date>().ok()?;
+        (date.year(), date.month(), date.day())
+    } else if let Some(parts) = numbers(&text.split('-').collect::<Vec<_>>()) {
+        match parts[..] {
+            [year, month, day] => (year, month, day),
+            _ => return None,
+        }
+    } else if let Some(parts) = numbers(&text.split('/').collect::<Vec<_>>()) {
+        match parts[..] {
+            [month, day, year] => (year, month, day),
+            _ => return None,
+        }
+    } else {
+        return None;
+    };
+    if year > 9999 || month > 12 || day > 31 {
+        return None;
+    }
+    let date = Date::new(day, month, year);
+    if date.is_day_of_calendar() {
+        Some(date)
+    } else {
+        None
+    }
+}
+
+/// Parses the date time, `YYYY-MM-DD HH:MM:SS` or a date at midnight
+fn parse_date_time(text: &str) -> Option<DateTime> {
+    let (date, time) = match text.split_once(' ') {
+        Some((date, time)) => (date, Some(time.trim())),
+        None => (text, None),
+    };
+    let date = parse_date(date)?;
+    let time = match time {
+        Some(time) => {
+            let parts = time
+                .split(':')
+                .map(|part| part.parse::<u32>().ok())
+                .collect::<Option<Vec<_>>>()?;
+            match parts[..] {
+                [hours, minutes, seconds] if hours < 24 && minutes < 60 && seconds < 60 => {
+                    Time::new(hours, minutes, seconds)
+                }
+                _ => return None,
+            }
+        }
+        None => Time::new(0, 0, 0),
+    };
+    Some(DateTime::new(date, time))
+}
+
+fn parse_logical(text: &str) -> Option<bool> {
+    match text.trim_matches('.') {
+        "T" | "t" | "Y" | "y" => Some(true),
+        "F" | "f" | "N" | "n" => Some(false),
+        _ => None,
+    }
+}
+
+impl FieldValue {
+    /// Casts the value to a value of the given type of field
+    ///
+    /// | From              | To                | Cast                                          |
+    /// |-------------------|-------------------|-----------------------------------------------|
+    /// | number            | number            | integers must be whole and in range           |
+    /// | number            | Character, Memo   | shortest text of the number, like `12.5`      |
+    /// | Character, Memo   | number            | the text is parsed, it must be a number       |
+    /// | Date              | Character, Memo   | `YYYYMMDD`, like `DTOS`                       |
+    /// | Character, Memo   | Date              | `YYYYMMDD`, `YYYY-MM-DD`, or `MM/DD/YYYY`     |
+    /// | date time         | Character, Memo   | `YYYY-MM-DD HH:MM:SS`                         |
+    /// | Character, Memo   | date time         | `YYYY-MM-DD HH:MM:SS`, or a date at midnight  |
+    /// | Date              | date time         | at midnight                                   |
+    /// | date time         | date time         | the same date time                            |
+    /// | date time         | Date              | the day                                       |
+    /// | Logical           | Character, Memo   | `T` or `F`                                    |
+    /// | Character, Memo   | Logical           | `T`, `Y`, `F` or `N` (any case, dots allowed) |
+    /// | Character         | Memo              | the text                                      |
+    ///
+    /// The numbers are the values of Numeric, Float, Integer, Double and Currency fields,
+    /// and of the Long, DBase7Double and Autoincrement fields of dBase 7, the date times
+    /// the values of DateTime and Timestamp fields.
+    /// Empty values stay empty, Character text is trimmed before being parsed and blank
+    /// text is empty. The values of Integer, Double, Currency, DateTime and Autoincrement
+    /// fields cannot be empty: casting an empty value to them fails with
+    /// [NoneValue](enum.FieldConversionError.html#variant.NoneValue).
+    ///
+    /// Other casts, eg: from Logical to Numeric, fail with
+    /// [InvalidCast](enum.FieldConversionError.html#variant.InvalidCast), text that
+    /// is not a value of the type and numbers out of the range of the type with
+    /// [InvalidValue](enum.FieldConversionError.html#variant.InvalidValue).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dbase::{Date, FieldType, FieldValue};
+    ///
+    /// let amount = FieldValue::Character(Some("12.50".to_owned()));
+    /// assert_eq!(amount.cast_to(FieldType::Numeric).unwrap(), FieldValue::Numeric(Some(12.5)));
+    ///
+    /// let day = FieldValue::Date(Some(Date::new(14, 10, 2026)));
+    /// assert_eq!(
+    ///     day.cast_to(FieldType::Character).unwrap(),
+    ///     FieldValue::Character(Some("20261014".to_owned()))
+    /// );
+    /// assert!(FieldValue::Logical(Some(true)).cast_to(FieldType::Numeric).is_err());
+    /// ```
+    pub fn cast_to(&self, field_type: FieldType) -> Result<FieldValue, FieldConversionError> {
+        if self.field_type() == field_type {
+            return Ok(self.clone());
+        }
+        let invalid_cast = || FieldConversionError::InvalidCast {
+            from: self.field_type(),
+            to: field_type,
+        };
+        match field_type {
+            FieldType::Character => Ok(FieldValue::Character(self.cast_to_text()?)),
+            FieldType::Memo => Ok(FieldValue::Memo(self.cast_to_text()?.unwrap_or_default())),
+            FieldType::Numeric
+            | FieldType::Float
+            | FieldType::Integer
+            | FieldType::Double
+            | FieldType::Currency
+            | FieldType::Long
+            | FieldType::DBase7Double
+            | FieldType::Autoincrement => {
+                let number = match self {
+                    FieldValue::Character(text) => parse_text(text.as_deref(), |text| {
+                        text.parse::<f64>().ok().filter(|number| number.is_finite())
+                    })?,
+                    FieldValue::Memo(text) => parse_text(Some(text), |text| {
+                        text.parse::<f64>().ok().filter(|number| number.is_finite())
+                    })?,
+                    FieldValue::NumericInteger(value) => {
+                        return cast_integer(*value, field_type);
+                    }
+                    _ => self.number().ok_or_else(invalid_cast)?,
+                };
+                cast_number(number, field_type)
+            }
+            FieldType::Logical => match self {
+                FieldValue::Character(text) => {
+                    parse_text(text.as_deref(), parse_logical).map(FieldValue::Logical)
+                }
+                FieldValue::Memo(text) => {
+                    parse_text(Some(text), parse_logical).map(FieldValue::Logical)
+                }
+                _ => Err(invalid_cast()),
+            },
+            FieldType::Date => match self {
+                FieldValue::Character(text) => {
+                    parse_text(text.as_deref(), parse_date).map(FieldValue::Date)
+                }
+                FieldValue::Memo(text) => parse_text(Some(text), parse_date).map(FieldValue::Date),
+                FieldValue::DateTime(date_time) => Ok(FieldValue::Date(Some(date_time.date()))),
+                FieldValue::Timestamp(date_time) => {
+                    Ok(FieldValue::Date(date_time.map(|date_time| date_time.date())))
+                }
+                _ => Err(invalid_cast()),
+            },
+            FieldType::DateTime | FieldType::Timestamp => {
+                let date_time = match self {
+                    FieldValue::Character(text) => parse_text(text.as_deref(), parse_date_time)?,
+                    FieldValue::Memo(text) => parse_text(Some(text), parse_date_time)?,
+                    FieldValue::Date(date) => {
+                        date.map(|date| DateTime::new(date, Time::new(0, 0, 0)))
+                    }
+                    FieldValue::DateTime(date_time) => Some(*date_time),
+                    FieldValue::Timestamp(date_time) => *date_time,
+                    _ => return Err(invalid_cast()),
+                };
+                if field_type == FieldType::Timestamp {
+                    Ok(FieldValue::Timestamp(date_time))
+                } else {
+                    date_time
+                        .map(FieldValue::DateTime)
+                        .ok_or(FieldConversionError::NoneValue)
+                }
+            }
+        }
+    }
+
+    /// Returns the number of numeric values, `None` for the values of other types
+    fn number(&self) -> Option<Option<f64>> {
+        match self {
+            FieldValue::Numeric(value) => Some(*value),
+            FieldValue::Float(value) => Some(value.map(f64::from)),
+            FieldValue::Integer(value) | FieldValue::Autoincrement(value) => {
+                Some(Some(f64::from(*value)))
+            }
+            FieldValue::Long(value) => Some(value.map(f64::from)),
+            FieldValue::Double(value) | FieldValue::Currency(value) => Some(Some(*value)),
+            FieldValue::DBase7Double(value) => Some(*value),
+            FieldValue::NumericInteger(value) => Some(value.map(|value| value as f64)),
+            _ => None,
+        }
+    }
+
+    /// Returns the text of the value, as cast to Character
+    fn cast_to_text(&self) -> Result<Option<String>, FieldConversionError> {
+        let text = match self {
+            FieldValue::Character(text) => text.clone(),
+            FieldValue::Memo(text) if text.trim().is_empty() => None,
+            FieldValue::Memo(text) => Some(text.clone()),
+            FieldValue::NumericInteger(value) => value.map(|value| value.to_string()),
+            FieldValue::Integer(value) | FieldValue::Autoincrement(value) => {
+                Some(value.to_string())
+            }
+            FieldValue::Long(value) => value.map(|value| value.to_string()),
+            FieldValue::Logical(value) => {
+                value.map(|value| if value { "T" } else { "F" }.to_owned())
+            }
+            FieldValue::Date(date) => date.map(|date| date.to_string()),
+            FieldValue::DateTime(date_time) => Some(date_time_text(date_time)),
+            FieldValue::Timestamp(date_time) => date_time.as_ref().map(date_time_text),
+            _ => match self.number() {
+                Some(Some(number)) => Some(number_text(number)),
+                Some(None) => None,
+                None => {
+                    return Err(FieldConversionError::InvalidCast {
+                        from: self.field_type(),
+                        to: FieldType::Character,
+                    })
+                }
+            },
+        };
+        Ok(text)
+    }
+}
+
+/// Parses the trimmed text, blank text is `None`
+fn parse_text<V, F: Fn(&str) -> Option<V>>(
+    text: Option<&str>,
+    parse: F,
+) -> Result<Option<V>, FieldConversionError> {
+    match text.map(str::trim) {
+        None | Some("") => Ok(None),
+        Some(text) => parse(text)
+            .map(Some)
+            .ok_or_else(|| FieldConversionError::InvalidValue(text.to_owned())),
+    }
+}
+
+fn cast_number(
+    number: Option<f64>,
+    field_type: FieldType,
+) -> Result<FieldValue, FieldConversionError> {
+    let out_of_range = |number: f64| FieldConversionError::InvalidValue(number_text(number));
+    match (field_type, number) {
+        (FieldType::Numeric, number) => Ok(FieldValue::Numeric(number)),
+        (FieldType::Float, number) => Ok(FieldValue::Float(number.map(|number| number as f32))),
+        (FieldType::Integer | FieldType::Long | FieldType::Autoincrement, Some(number)) => {
+            let is_integer = number.fract() == 0.0
+                && number >= f64::from(i32::MIN)
+                && number <= f64::from(i32::MAX);
+            if is_integer {
+                Ok(integer_value(number as i32, field_type))
+            } else {
+                Err(out_of_range(number))
+            }
+        }
+        (FieldType::Long, None) => Ok(FieldValue::Long(None)),
+        (FieldType::DBase7Double, number) => Ok(FieldValue::DBase7Double(number)),
+        (FieldType::Double, Some(number)) => Ok(FieldValue::Double(number)),
+        (FieldType::Currency, Some(number)) => Ok(FieldValue::Currency(number)),
+        _ => Err(FieldConversionError::NoneValue),
+    }
+}
+
+fn cast_integer(
+    value: Option<i64>,
+    field_type: FieldType,
+) -> Result<FieldValue, FieldConversionError> {
+    match (field_type, value) {
+        (FieldType::Integer | FieldType::Long | FieldType::Autoincrement, Some(value)) => {
+            i32::try_from(value)
+                .map(|value| integer_value(value, field_type))
+                .map_err(|_| FieldConversionError::InvalidValue(value.to_string()))
+        }
+        (field_type, value) => cast_number(value.map(|value| value as f64), field_type),
+    }
+}
+
+/// Returns the value of the integer field of the given type
+fn integer_value(value: i32, field_type: FieldType) -> FieldValue {
+    match field_type {
+        FieldType::Long => FieldValue::Long(Some(value)),
+        FieldType::Autoincrement => FieldValue::Autoincrement(value),
+        _ => FieldValue::Integer(value),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn text(text: &str) -> FieldValue {
+        FieldValue::Character(Some(text.to_owned()))
+    }
+
+    #[test]
+    fn numbers_and_text() {
+        assert_eq!(
+            FieldValue::Numeric(Some(12.5))
+                .cast_to(FieldType::Character)
+                .unwrap(),
+            text("12.5")
+        );
+        assert_eq!(
+            text(" 42 ").cast_to(FieldType::Integer).unwrap(),
+            FieldValue::Integer(42)
+        );
+        assert_eq!(
+            text("   ").cast_to(FieldType::Numeric).unwrap(),
+            FieldValue::Numeric(None)
+        );
+        assert!(matches!(
+            text("12,5").cast_to(FieldType::Numeric),
+            Err(FieldConversionError::InvalidValue(_))
+        ));
+        assert!(matches!(
+            FieldValue::Numeric(Some(1.5)).cast_to(FieldType::Integer),
+            Err(FieldConversionError::InvalidValue(_))
+        ));
+        assert!(matches!(
+            FieldValue::Numeric(None).cast_to(FieldType::Currency),
+            Err(FieldConversionError::NoneValue)
+        ));
+        assert_eq!(
+            FieldValue::NumericInteger(Some(123_456_789_012_345_678))
+                .cast_to(FieldType::Character)
+                .unwrap(),
+            text("123456789012345678")
+        );
+    }
+
+    #[test]
+    fn dates_and_text() {
+        let day = FieldValue::Date(Some(Date::new(29, 2, 2024)));
+        assert_eq!(day.cast_to(FieldType::Character).unwrap(), text("20240229"));
+        for date in ["20240229", "2024-02-29", "02/29/2024"].iter() {
+            assert_eq!(text(date).cast_to(FieldType::Date).unwrap(), day);
+        }
+        assert!(text("20230229").cast_to(FieldType::Date).is_err());
+
+        let date_time = text("2024-02-29 13:45:10")
+            .cast_to(FieldType::DateTime)
+            .unwrap();
+        assert_eq!(date_time.cast_to(FieldType::Date).unwrap(), day);
+        assert_eq!(
+            date_time.cast_to(FieldType::Character).unwrap(),
+            text("2024-02-29 13:45:10")
+        );
+        assert_eq!(
+            day.cast_to(FieldType::DateTime).unwrap(),
+            FieldValue::DateTime(DateTime::new(Date::new(29, 2, 2024), Time::new(0, 0, 0)))
+        );
+    }
+
+    #[test]
+    fn logicals_and_impossible_casts() {
+        assert_eq!(
+            FieldValue::Logical(Some(false))
+                .cast_to(FieldType::Character)
+                .unwrap(),
+            text("F")
+        );
+        assert_eq!(
+            text(".t.").cast_to(FieldType::Logical).unwrap(),
+            FieldValue::Logical(Some(true))
+        );
+        assert!(matches!(
+            text("maybe").cast_to(FieldType::Logical),
+            Err(FieldConversionError::InvalidValue(_))
+        ));
+        assert!(matches!(
+            FieldValue::Date(None).cast_to(FieldType::Numeric),
+            Err(FieldConversionError::InvalidCast {
+                from: FieldType::Date,
+                to: FieldType::Numeric
+            })
+        ));
+        assert_eq!(
+            text("note").cast_to(FieldType::Memo).unwrap(),
+            FieldValue::Memo("note".to_owned())
+        );
+    }
+}
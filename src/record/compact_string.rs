@@ -0,0 +1,288 @@
+//! Definition of the string type that stores short values inline
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::io::Write;
+use std::ops::Deref;
+
+use super::field::{FieldType, FieldValue};
+use super::FieldConversionError;
+use crate::writing::WritableAsDbaseField;
+use crate::ErrorKind;
+
+/// Maximum number of bytes stored without allocating
+const INLINE_CAPACITY: usize = 22;
+
+#[derive(Clone)]
+enum Repr {
+    Inline {
+        len: u8,
+        bytes: [u8; INLINE_CAPACITY],
+    },
+    Heap(Box<str>),
+}
+
+/// Immutable string that stores strings of at most 22 bytes inline,
+/// longer strings are stored in a `Box<str>`.
+///
+/// It has the size of a `String`, most Character values are short enough to not
+/// allocate, and the longer ones do not keep unused capacity.
+/// Use it in place of `String` in your records when many of them are kept in memory.
+///
+/// # Example
+///
+/// ```
+/// use dbase::{CompactString, FieldValue};
+/// use std::convert::TryFrom;
+///
+/// let name = CompactString::try_from(FieldValue::Character(Some("Ferris".to_owned()))).unwrap();
+/// assert_eq!(name, "Ferris");
+/// assert!(name.is_inline());
+/// assert_eq!(std::mem::size_of::<CompactString>(), std::mem::size_of::<String>());
+/// ```
+#[derive(Clone)]
+pub struct CompactString(Repr);
+
+impl CompactString {
+    /// Creates a string with the content of the given one
+    pub fn new(s: &str) -> Self {
+        if s.len() <= INLINE_CAPACITY {
+            let mut bytes = [0u8; INLINE_CAPACITY];
+            bytes[..s.len()].copy_from_slice(s.as_bytes());
+            Self(Repr::Inline {
+                len: s.len() as u8,
+                bytes,
+            })
+        } else {
+            Self(Repr::Heap(s.into()))
+        }
+    }
+
+    /// Returns the content of the string
+    pub fn as_str(&self) -> &str {
+        match &self.0 {
+            Repr::Inline { len, bytes } => {
+                // The bytes were copied from a str
+                std::str::from_utf8(&bytes[..usize::from(*len)]).unwrap()
+            }
+            Repr::Heap(s) => s,
+        }
+    }
+
+    /// Returns whether the string is stored without allocation
+    pub fn is_inline(&self) -> bool {
+        matches!(self.0, Repr::Inline { .. })
+    }
+}
+
+impl Default for CompactString {
+    fn default() -> Self {
+        Self::new("")
+    }
+}
+
+impl Deref for CompactString {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl AsRef<str> for CompactString {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl Borrow<str> for CompactString {
+    fn borrow(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl fmt::Debug for CompactString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl fmt::Display for CompactString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self.as_str(), f)
+    }
+}
+
+impl PartialEq for CompactString {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl Eq for CompactString {}
+
+impl PartialEq<str> for CompactString {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<&str> for CompactString {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl PartialOrd for CompactString {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for CompactString {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_str().cmp(other.as_str())
+    }
+}
+
+impl Hash for CompactString {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_str().hash(state)
+    }
+}
+
+impl From<&str> for CompactString {
+    fn from(s: &str) -> Self {
+        Self::new(s)
+    }
+}
+
+impl From<String> for CompactString {
+    fn from(s: String) -> Self {
+        if s.len() <= INLINE_CAPACITY {
+            Self::new(&s)
+        } else {
+            Self(Repr::Heap(s.into_boxed_str()))
+        }
+    }
+}
+
+impl From<CompactString> for String {
+    fn from(s: CompactString) -> Self {
+        match s.0 {
+            Repr::Heap(s) => s.into_string(),
+            Repr::Inline { .. } => s.as_str().to_owned(),
+        }
+    }
+}
+
+impl From<CompactString> for FieldValue {
+    fn from(s: CompactString) -> Self {
+        FieldValue::Character(Some(s.into()))
+    }
+}
+
+impl From<Option<CompactString>> for FieldValue {
+    fn from(s: Option<CompactString>) -> Self {
+        FieldValue::Character(s.map(String::from))
+    }
+}
+
+impl TryFrom<FieldValue> for Option<CompactString> {
+    type Error = FieldConversionError;
+
+    fn try_from(value: FieldValue) -> Result<Self, Self::Error> {
+        match value {
+            FieldValue::Character(s) => Ok(s.map(CompactString::from)),
+            FieldValue::Memo(s) => Ok(Some(CompactString::from(s))),
+            _ => Err(FieldConversionError::FieldTypeNotAsExpected {
+                expected: FieldType::Character,
+                actual: value.field_type(),
+            }),
+        }
+    }
+}
+
+impl TryFrom<FieldValue> for CompactString {
+    type Error = FieldConversionError;
+
+    fn try_from(value: FieldValue) -> Result<Self, Self::Error> {
+        Option::<CompactString>::try_from(value)?.ok_or(FieldConversionError::NoneValue)
+    }
+}
+
+impl WritableAsDbaseField for CompactString {
+    fn write_as<W: Write>(&self, field_type: FieldType, dst: &mut W) -> Result<(), ErrorKind> {
+        self.as_str().write_as(field_type, dst)
+    }
+}
+
+impl WritableAsDbaseField for Option<CompactString> {
+    fn write_as<W: Write>(&self, field_type: FieldType, dst: &mut W) -> Result<(), ErrorKind> {
+        if field_type == FieldType::Character {
+            if let Some(s) = self {
+                s.write_as(field_type, dst)?;
+            }
+            Ok(())
+        } else {
+            Err(ErrorKind::IncompatibleType)
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for CompactString {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for CompactString {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct CompactStringVisitor;
+
+        impl serde::de::Visitor<'_> for CompactStringVisitor {
+            type Value = CompactString;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a string")
+            }
+
+            fn visit_str<E: serde::de::Error>(self, s: &str) -> Result<Self::Value, E> {
+                Ok(CompactString::new(s))
+            }
+
+            fn visit_string<E: serde::de::Error>(self, s: String) -> Result<Self::Value, E> {
+                Ok(CompactString::from(s))
+            }
+        }
+
+        deserializer.deserialize_string(CompactStringVisitor)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn short_strings_are_inline() {
+        let short = CompactString::from("a".repeat(INLINE_CAPACITY));
+        assert!(short.is_inline());
+        assert_eq!(short.len(), INLINE_CAPACITY);
+        let long = CompactString::from("é".repeat(INLINE_CAPACITY));
+        assert!(!long.is_inline());
+        assert_eq!(String::from(long.clone()), "é".repeat(INLINE_CAPACITY));
+        assert!(short < long);
+        assert_eq!(CompactString::default(), "");
+
+        assert_eq!(
+            Option::<CompactString>::try_from(FieldValue::Character(None)).unwrap(),
+            None
+        );
+        assert!(CompactString::try_from(FieldValue::Integer(1)).is_err());
+    }
+}
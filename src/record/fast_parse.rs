@@ -0,0 +1,213 @@
+//! Parsing of fixed width ASCII numbers and dates, 8 digits at a time
+//!
+//! The digits are loaded in a `u64` and combined with a few multiplications
+//! (SWAR: SIMD within a register) instead of one by one.
+//! Values that are not plain decimal numbers, or that would not be exactly rounded,
+//! are left to the parsers of the std, the fast path gives the same results.
+use core::convert::TryInto;
+
+const ZEROS: u64 = 0x3030_3030_3030_3030;
+
+/// Largest integer below which all integers are exactly represented by an f64
+const MAX_EXACT_F64_INTEGER: u64 = 1 << 53;
+/// Largest power of ten exactly represented by an f64
+const MAX_EXACT_F64_POWER: usize = 22;
+const POWERS_OF_TEN: [f64; MAX_EXACT_F64_POWER + 1] = [
+    1e0, 1e1, 1e2, 1e3, 1e4, 1e5, 1e6, 1e7, 1e8, 1e9, 1e10, 1e11, 1e12, 1e13, 1e14, 1e15, 1e16,
+    1e17, 1e18, 1e19, 1e20, 1e21, 1e22,
+];
+
+/// Same for f32
+const MAX_EXACT_F32_INTEGER: u64 = 1 << 24;
+const MAX_EXACT_F32_POWER: usize = 10;
+
+/// Returns whether the 8 bytes are all ASCII digits
+fn are_all_digits(chunk: u64) -> bool {
+    // Adding 0x46 carries into the high bit for bytes above '9',
+    // subtracting 0x30 borrows into it for bytes below '0'
+    ((chunk.wrapping_add(0x4646_4646_4646_4646) | chunk.wrapping_sub(ZEROS))
+        & 0x8080_8080_8080_8080)
+        == 0
+}
+
+/// Converts 8 ASCII digits (the first digit in the lowest byte) to their value
+fn parse_eight_digits(chunk: u64) -> u64 {
+    let digits = chunk - ZEROS;
+    // Bytes now hold the values of pairs, then of groups of four, then the whole
+    let pairs = (digits.wrapping_mul(10) + (digits >> 8)) & 0x00ff_00ff_00ff_00ff;
+    let fours = (pairs.wrapping_mul(100) + (pairs >> 16)) & 0x0000_ffff_0000_ffff;
+    (fours.wrapping_mul(10_000) + (fours >> 32)) & 0xffff_ffff
+}
+
+/// Reads the bytes, which must be ASCII digits, as a number
+/// (at most 19 digits so that it fits in a u64)
+fn parse_digits(bytes: &[u8]) -> Option<u64> {
+    let mut value = 0u64;
+    let mut chunks = bytes.chunks_exact(8);
+    for chunk in chunks.by_ref() {
+        let chunk = u64::from_le_bytes(chunk.try_into().unwrap());
+        if !are_all_digits(chunk) {
+            return None;
+        }
+        value = value * 100_000_000 + parse_eight_digits(chunk);
+    }
+    for byte in chunks.remainder() {
+        if !byte.is_ascii_digit() {
+            return None;
+        }
+        value = value * 10 + u64::from(byte - b'0');
+    }
+    Some(value)
+}
+
+/// The parts of a decimal number: `[-]digits[.digits]`
+struct Decimal {
+    is_negative: bool,
+    mantissa: u64,
+    num_decimals: usize,
+}
+
+fn parse_decimal(bytes: &[u8]) -> Option<Decimal> {
+    let (is_negative, bytes) = match bytes.split_first() {
+        Some((b'-', rest)) => (true, rest),
+        Some((b'+', rest)) => (false, rest),
+        _ => (false, bytes),
+    };
+    let (integer, decimals) = match bytes.iter().position(|byte| *byte == b'.') {
+        Some(position) => (&bytes[..position], &bytes[position + 1..]),
+        None => (bytes, &bytes[bytes.len()..]),
+    };
+    let num_digits = integer.len() + decimals.len();
+    if num_digits == 0 || num_digits > 19 {
+        return None;
+    }
+    let integer_value = parse_digits(integer)?;
+    let decimals_value = parse_digits(decimals)?;
+    let mantissa = integer_value
+        .checked_mul(10u64.checked_pow(decimals.len() as u32)?)?
+        .checked_add(decimals_value)?;
+    Some(Decimal {
+        is_negative,
+        mantissa,
+        num_decimals: decimals.len(),
+    })
+}
+
+/// Removes the leading and trailing spaces
+fn trim_spaces(bytes: &[u8]) -> &[u8] {
+    let start = bytes
+        .iter()
+        .position(|byte| *byte != b' ')
+        .unwrap_or(bytes.len());
+    let end = bytes
+        .iter()
+        .rposition(|byte| *byte != b' ')
+        .map_or(start, |position| position + 1);
+    &bytes[start..end]
+}
+
+/// Parses a number written with digits and an optional decimal point,
+/// returns `None` when the number is written otherwise or cannot be exactly rounded
+pub(crate) fn parse_f64(bytes: &[u8]) -> Option<f64> {
+    let decimal = parse_decimal(trim_spaces(bytes))?;
+    if decimal.mantissa > MAX_EXACT_F64_INTEGER || decimal.num_decimals > MAX_EXACT_F64_POWER {
+        return None;
+    }
+    // Both are exact, so the division is correctly rounded
+    let value = decimal.mantissa as f64 / POWERS_OF_TEN[decimal.num_decimals];
+    Some(if decimal.is_negative { -value } else { value })
+}
+
+/// Same as [parse_f64] for f32
+pub(crate) fn parse_f32(bytes: &[u8]) -> Option<f32> {
+    let decimal = parse_decimal(trim_spaces(bytes))?;
+    if decimal.mantissa > MAX_EXACT_F32_INTEGER || decimal.num_decimals > MAX_EXACT_F32_POWER {
+        return None;
+    }
+    let value = decimal.mantissa as f32 / POWERS_OF_TEN[decimal.num_decimals] as f32;
+    Some(if decimal.is_negative { -value } else { value })
+}
+
+/// Parses a date written as `YYYYMMDD`, returns the year, month and day
+pub(crate) fn parse_date(bytes: &[u8]) -> Option<(u32, u32, u32)> {
+    let chunk = u64::from_le_bytes(bytes.try_into().ok()?);
+    if !are_all_digits(chunk) {
+        return None;
+    }
+    let digits = chunk - ZEROS;
+    let pairs = (digits.wrapping_mul(10) + (digits >> 8)) & 0x00ff_00ff_00ff_00ff;
+    let pair = |index: u32| (pairs >> (16 * index)) as u32 & 0xff;
+    Some((pair(0) * 100 + pair(1), pair(2), pair(3)))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use alloc::format;
+    use alloc::string::ToString;
+
+    #[test]
+    fn numbers_are_parsed_like_the_std() {
+        let values = [
+            "0",
+            "  42",
+            "-3.25 ",
+            "+7",
+            "123456789.123456",
+            "0.1",
+            "-0.000001",
+            "9007199254740993",
+            "1234567890123456789",
+            "       1.5",
+            ".5",
+            "5.",
+            "3.4028235",
+            "16777217",
+        ];
+        for value in &values {
+            let expected = value.trim().parse::<f64>().unwrap();
+            if let Some(parsed) = parse_f64(value.as_bytes()) {
+                assert_eq!(parsed, expected, "{}", value);
+            }
+            let expected = value.trim().parse::<f32>().unwrap();
+            if let Some(parsed) = parse_f32(value.as_bytes()) {
+                assert_eq!(parsed, expected, "{}", value);
+            }
+        }
+        let mut mantissa = 1u64;
+        for num_decimals in 0..12 {
+            for _ in 0..1000 {
+                mantissa = mantissa.wrapping_mul(6364136223846793005).wrapping_add(1) >> 20;
+                let digits = mantissa.to_string();
+                let value = if num_decimals < digits.len() {
+                    let (integer, decimals) = digits.split_at(digits.len() - num_decimals);
+                    format!("{}.{}", integer, decimals)
+                } else {
+                    digits
+                };
+                assert_eq!(parse_f64(value.as_bytes()), value.parse::<f64>().ok());
+                if let Some(parsed) = parse_f32(value.as_bytes()) {
+                    assert_eq!(parsed, value.parse::<f32>().unwrap(), "{}", value);
+                }
+            }
+        }
+        assert_eq!(parse_f64(b"123456789.123456"), Some(123456789.123456));
+        assert_eq!(parse_f64(b"-3.25 "), Some(-3.25));
+        // Left to the std
+        assert_eq!(parse_f64(b"1e5"), None);
+        assert_eq!(parse_f64(b"9007199254740993"), None);
+        assert_eq!(parse_f64(b"1.2.3"), None);
+        assert_eq!(parse_f64(b"-"), None);
+        assert_eq!(parse_f64(b"1 2"), None);
+    }
+
+    #[test]
+    fn dates_are_parsed() {
+        assert_eq!(parse_date(b"20200131"), Some((2020, 1, 31)));
+        assert_eq!(parse_date(b"19991209"), Some((1999, 12, 9)));
+        assert_eq!(parse_date(b"2020013 "), None);
+        assert_eq!(parse_date(b"2020/1/3"), None);
+        assert_eq!(parse_date(b"2020013"), None);
+    }
+}
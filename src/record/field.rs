@@ -1,14 +1,34 @@
-use std::convert::{TryFrom, TryInto};
-use std::fmt;
+use alloc::borrow::ToOwned;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+#[cfg(feature = "std")]
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::convert::TryFrom;
+#[cfg(feature = "std")]
+use core::convert::TryInto;
+use core::fmt;
+use core::hash::{Hash, Hasher};
+use core::str::FromStr;
+#[cfg(feature = "std")]
 use std::io::{Read, Seek, SeekFrom, Write};
-use std::str::FromStr;
 
+#[cfg(feature = "std")]
 use byteorder::{BigEndian, LittleEndian, ReadBytesExt, WriteBytesExt};
 
+#[cfg(feature = "std")]
 use chrono::Datelike;
 
 use crate::error::ErrorKind;
-use crate::record::FieldInfo;
+use crate::options::{
+    BlankPolicy, InvalidDatePolicy, MissingMemoPolicy, ReadingOptions, TrimOption,
+};
+#[cfg(feature = "std")]
+use crate::raw::IoSource;
+use crate::raw::{read_array, read_u8, skip, ByteSource, KindSource};
+use crate::record::{fast_parse, FieldInfo};
+#[cfg(feature = "std")]
 use crate::writing::WritableAsDbaseField;
 
 /// The different types of Memo file structure there seem to exist
@@ -19,14 +39,48 @@ pub(crate) enum MemoFileType {
     FoxBaseMemo,
 }
 
+/// The layout of a memo file
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum MemoFormat {
+    /// dBase III .dbt files, where values end with 0x1A
+    DBase3,
+    /// dBase IV .dbt files, where values start with their length
+    DBase4,
+    /// FoxPro and Visual FoxPro .fpt files
+    FoxPro,
+}
+
+impl From<MemoFormat> for MemoFileType {
+    fn from(format: MemoFormat) -> Self {
+        match format {
+            MemoFormat::DBase3 => MemoFileType::DbaseMemo,
+            MemoFormat::DBase4 => MemoFileType::DbaseMemo4,
+            MemoFormat::FoxPro => MemoFileType::FoxBaseMemo,
+        }
+    }
+}
+
+impl MemoFileType {
+    /// Returns the extension of the memo files of this type
+    #[cfg(feature = "std")]
+    pub(crate) fn extension(self) -> &'static str {
+        match self {
+            MemoFileType::DbaseMemo | MemoFileType::DbaseMemo4 => "dbt",
+            MemoFileType::FoxBaseMemo => "fpt",
+        }
+    }
+}
+
 /// Although there are different memo file type with each a different
 /// header organisation, we use the same struct internally
+#[cfg(feature = "std")]
 #[derive(Debug, Copy, Clone)]
 pub(crate) struct MemoHeader {
     next_available_block_index: u32,
     block_size: u32,
 }
 
+#[cfg(feature = "std")]
 impl MemoHeader {
     pub(crate) fn read_from<R: Read>(
         src: &mut R,
@@ -51,9 +105,16 @@ impl MemoHeader {
             block_size,
         })
     }
+
+    /// Returns the size, in bytes, of the blocks of the memo file
+    #[cfg(feature = "object_store")]
+    pub(crate) fn block_size(&self) -> u32 {
+        self.block_size
+    }
 }
 
 /// Struct that reads knows how to read data from a memo source
+#[cfg(feature = "std")]
 #[derive(Debug)]
 pub(crate) struct MemoReader<T: Read + Seek> {
     memo_file_type: MemoFileType,
@@ -62,8 +123,19 @@ pub(crate) struct MemoReader<T: Read + Seek> {
     internal_buffer: Vec<u8>,
 }
 
+#[cfg(feature = "std")]
 impl<T: Read + Seek> MemoReader<T> {
-    pub(crate) fn new(memo_type: MemoFileType, mut src: T) -> std::io::Result<Self> {
+    pub(crate) fn new(memo_type: MemoFileType, src: T) -> std::io::Result<Self> {
+        let mut memo_reader = Self::of_type(memo_type, src)?;
+        if memo_type != MemoFileType::FoxBaseMemo {
+            memo_reader.memo_file_type = memo_reader.detect_dbt_type()?;
+        }
+        Ok(memo_reader)
+    }
+
+    /// Creates a reader of a memo file whose layout is known,
+    /// the file is not looked at to tell it
+    pub(crate) fn of_type(memo_type: MemoFileType, mut src: T) -> std::io::Result<Self> {
         let header = MemoHeader::read_from(&mut src, memo_type)?;
         let internal_buffer = vec![0u8; header.block_size as usize];
         Ok(Self {
@@ -74,6 +146,51 @@ impl<T: Read + Seek> MemoReader<T> {
         })
     }
 
+    /// Returns the layout of the memo file
+    #[cfg(feature = "object_store")]
+    pub(crate) fn memo_file_type(&self) -> MemoFileType {
+        self.memo_file_type
+    }
+
+    /// Returns the layout of the .dbt file, told by its header and its first block
+    /// rather than by the version of the table, as tables and memo files
+    /// that went through different tools do not always agree
+    ///
+    /// The version byte of the header is 0x03 for dBase III and 0x8B for dBase IV,
+    /// when it is not set the blocks of dBase IV are recognized by their marker.
+    fn detect_dbt_type(&mut self) -> std::io::Result<MemoFileType> {
+        const DBASE4_BLOCK_MARKER: [u8; 4] = [0xFF, 0xFF, 0x08, 0x00];
+        self.source.seek(SeekFrom::Start(16))?;
+        let mut version = [0u8; 1];
+        if self.source.read(&mut version)? == 1 {
+            match version[0] {
+                0x03 => return Ok(MemoFileType::DbaseMemo),
+                0x8B => return Ok(MemoFileType::DbaseMemo4),
+                _ => {}
+            }
+        }
+        if self.header.next_available_block_index <= self.first_data_block() {
+            return Ok(self.memo_file_type);
+        }
+        let offset = u64::from(self.first_data_block()) * u64::from(self.header.block_size);
+        self.source.seek(SeekFrom::Start(offset))?;
+        let mut marker = [0u8; 4];
+        match self.source.read_exact(&mut marker) {
+            Ok(()) if marker == DBASE4_BLOCK_MARKER => Ok(MemoFileType::DbaseMemo4),
+            Ok(()) => Ok(MemoFileType::DbaseMemo),
+            Err(error) if error.kind() == std::io::ErrorKind::UnexpectedEof => {
+                Ok(self.memo_file_type)
+            }
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Returns whether the block is one of the blocks of data of the memo file
+    pub(crate) fn contains_block(&mut self, index: u32) -> std::io::Result<bool> {
+        let size = self.source.seek(SeekFrom::End(0))?;
+        Ok(index > 0 && u64::from(index) * u64::from(self.header.block_size) < size)
+    }
+
     fn read_data_at(&mut self, index: u32) -> std::io::Result<&[u8]> {
         let byte_offset = index * self.header.block_size;
         self.source.seek(SeekFrom::Start(u64::from(byte_offset)))?;
@@ -128,6 +245,262 @@ impl<T: Read + Seek> MemoReader<T> {
     }
 }
 
+/// Reads the index of the block of the memo file the memo field points to,
+/// `None` if the field is empty
+pub(crate) fn read_memo_index<S: ByteSource + ?Sized>(
+    source: &mut S,
+    field_info: &FieldInfo,
+) -> Result<Option<u32>, ErrorKind> {
+    let source = &mut KindSource(source);
+    if field_info.field_length > 4 {
+        let mut bytes = vec![0u8; usize::from(field_info.field_length)];
+        source.read_bytes(&mut bytes)?;
+        let string = String::from_utf8_lossy(&bytes);
+        let trimmed_str = string.trim();
+        if trimmed_str.is_empty() {
+            Ok(None)
+        } else {
+            Ok(Some(trimmed_str.parse::<u32>()?))
+        }
+    } else {
+        Ok(Some(u32::from_le_bytes(read_array(source)?)))
+    }
+}
+
+/// Reads the memo data the memo field points to, as raw bytes
+#[cfg(feature = "std")]
+pub(crate) fn read_memo_bytes<S: Read + Seek, T: Read + Seek>(
+    source: &mut S,
+    memo_reader: &mut Option<MemoReader<T>>,
+    field_info: &FieldInfo,
+) -> Result<Vec<u8>, ErrorKind> {
+    let index_in_memo = match read_memo_index(&mut IoSource(source), field_info)? {
+        Some(index_in_memo) => index_in_memo,
+        None => return Ok(vec![]),
+    };
+
+    if let Some(memo_reader) = memo_reader {
+        Ok(memo_reader.read_data_at(index_in_memo)?.to_vec())
+    } else {
+        Err(ErrorKind::MissingMemoFile)
+    }
+}
+
+/// Designates the data of a memo field in the memo file
+///
+/// Made by [RecordRef::memo_handle](../struct.RecordRef.html#method.memo_handle),
+/// its data is read with [Reader::memo_reader](../struct.Reader.html#method.memo_reader).
+#[cfg(feature = "std")]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct MemoHandle {
+    block_index: Option<u32>,
+}
+
+#[cfg(feature = "std")]
+impl MemoHandle {
+    /// Creates a handle to the data starting at the given block of the memo file
+    pub fn from_block_index(block_index: u32) -> Self {
+        Self {
+            block_index: Some(block_index),
+        }
+    }
+
+    pub(crate) fn read_from<S: ByteSource + ?Sized>(
+        source: &mut S,
+        field_info: &FieldInfo,
+    ) -> Result<Self, ErrorKind> {
+        Ok(Self {
+            block_index: read_memo_index(source, field_info)?,
+        })
+    }
+
+    /// Returns the index of the block of the memo file where the data starts,
+    /// `None` if the memo field is empty
+    pub fn block_index(&self) -> Option<u32> {
+        self.block_index
+    }
+}
+
+/// Reads the data of a memo field straight from the memo file, block by block
+///
+/// Made by [Reader::memo_reader](../struct.Reader.html#method.memo_reader),
+/// the bytes are the ones stored in the memo file: their text is not decoded
+/// and, unlike [FieldValue::Memo](enum.FieldValue.html#variant.Memo),
+/// the NUL padding some writers leave in FoxPro memos is not trimmed.
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub struct MemoStream<'a, T: Read + Seek> {
+    source: Option<&'a mut T>,
+    remaining: Option<u64>,
+    terminator: Option<u8>,
+}
+
+#[cfg(feature = "std")]
+impl<'a, T: Read + Seek> MemoStream<'a, T> {
+    fn empty() -> Self {
+        Self {
+            source: None,
+            remaining: None,
+            terminator: None,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: Read + Seek> MemoReader<T> {
+    /// Positions the memo file at the data starting at the given block
+    /// and returns a stream of this data
+    pub(crate) fn stream_at(&mut self, handle: &MemoHandle) -> std::io::Result<MemoStream<'_, T>> {
+        let index = match handle.block_index {
+            Some(index) if self.contains_block(index)? => index,
+            Some(index) => {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::UnexpectedEof,
+                    format!("the memo file has no block {}", index),
+                ))
+            }
+            None => return Ok(MemoStream::empty()),
+        };
+        let byte_offset = u64::from(index) * u64::from(self.header.block_size);
+        self.source.seek(SeekFrom::Start(byte_offset))?;
+
+        let (remaining, terminator) = match self.memo_file_type {
+            MemoFileType::FoxBaseMemo => {
+                let _type = self.source.read_u32::<BigEndian>()?;
+                let length = self.source.read_u32::<BigEndian>()?;
+                (Some(u64::from(length)), None)
+            }
+            MemoFileType::DbaseMemo4 => {
+                let _ = self.source.read_u32::<LittleEndian>()?;
+                let length = self.source.read_u32::<LittleEndian>()?;
+                (Some(u64::from(length)), Some(0x1F))
+            }
+            MemoFileType::DbaseMemo => (None, Some(0x1A)),
+        };
+        Ok(MemoStream {
+            source: Some(&mut self.source),
+            remaining,
+            terminator,
+        })
+    }
+
+    /// Returns the size, in bytes, of the blocks of the memo file
+    pub(crate) fn block_size(&self) -> u32 {
+        self.header.block_size
+    }
+
+    /// Returns the index of the first block after the 512 bytes of the header
+    pub(crate) fn first_data_block(&self) -> u32 {
+        let block_size = self.header.block_size.max(1);
+        512_u32.div_ceil(block_size).max(1)
+    }
+
+    /// Returns the number of blocks the data starting at the given block spans,
+    /// its header and terminator included
+    fn num_blocks_at(&mut self, index: u32) -> std::io::Result<u32> {
+        let block_size = u64::from(self.header.block_size);
+        let mut stream = self.stream_at(&MemoHandle::from_block_index(index))?;
+        let num_bytes = std::io::copy(&mut stream, &mut std::io::sink())?;
+        let num_bytes = match self.memo_file_type {
+            MemoFileType::FoxBaseMemo => num_bytes + 8,
+            MemoFileType::DbaseMemo4 => num_bytes + 9,
+            MemoFileType::DbaseMemo => num_bytes + 1,
+        };
+        Ok(u32::try_from(num_bytes.div_ceil(block_size)).unwrap_or(u32::MAX))
+    }
+
+    /// Copies the blocks of the data starting at the given block to `dst`,
+    /// the last block is padded with zeros if the memo file ends before it
+    ///
+    /// Returns the number of blocks copied.
+    pub(crate) fn copy_blocks_to<W: Write>(
+        &mut self,
+        index: u32,
+        dst: &mut W,
+    ) -> std::io::Result<u32> {
+        let num_blocks = self.num_blocks_at(index)?;
+        let num_bytes = u64::from(num_blocks) * u64::from(self.header.block_size);
+        self.source.seek(SeekFrom::Start(
+            u64::from(index) * u64::from(self.header.block_size),
+        ))?;
+        let copied = std::io::copy(&mut (&mut self.source).take(num_bytes), dst)?;
+        std::io::copy(&mut std::io::repeat(0).take(num_bytes - copied), dst)?;
+        Ok(num_blocks)
+    }
+
+    /// Copies the header of the memo file to `dst`, up to the first data block,
+    /// with the given index of the next available block
+    pub(crate) fn copy_header_to<W: Write>(
+        &mut self,
+        dst: &mut W,
+        next_available_block_index: u32,
+    ) -> std::io::Result<()> {
+        let header_size = u64::from(self.first_data_block()) * u64::from(self.header.block_size);
+        self.source.seek(SeekFrom::Start(4))?;
+        match self.memo_file_type {
+            MemoFileType::FoxBaseMemo => dst.write_u32::<BigEndian>(next_available_block_index)?,
+            MemoFileType::DbaseMemo | MemoFileType::DbaseMemo4 => {
+                dst.write_u32::<LittleEndian>(next_available_block_index)?
+            }
+        }
+        let copied = std::io::copy(&mut (&mut self.source).take(header_size - 4), dst)?;
+        std::io::copy(&mut std::io::repeat(0).take(header_size - 4 - copied), dst)?;
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'a, T: Read + Seek> Read for MemoStream<'a, T> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let source = match self.source.as_mut() {
+            Some(source) if !buf.is_empty() => source,
+            _ => return Ok(0),
+        };
+        let max_len = match self.remaining {
+            Some(remaining) => buf
+                .len()
+                .min(usize::try_from(remaining).unwrap_or(usize::MAX)),
+            None => buf.len(),
+        };
+        let mut num_read = source.read(&mut buf[..max_len])?;
+        if let Some(pos) = self
+            .terminator
+            .and_then(|terminator| buf[..num_read].iter().position(|b| *b == terminator))
+        {
+            num_read = pos;
+            self.source = None;
+        } else if num_read == 0 {
+            self.source = None;
+        }
+        if let Some(remaining) = self.remaining.as_mut() {
+            *remaining -= num_read as u64;
+            if *remaining == 0 {
+                self.source = None;
+            }
+        }
+        Ok(num_read)
+    }
+}
+
+/// Parses the text of a Numeric or Float field, written with the separators of the options
+fn parse_number<F: FromStr>(text: &str, options: &ReadingOptions) -> Result<F, F::Err> {
+    if options.has_standard_separators() {
+        return text.parse::<F>();
+    }
+    let text = text
+        .chars()
+        .filter(|c| Some(*c) != options.thousands_separator)
+        .map(|c| {
+            if c == options.decimal_separator {
+                '.'
+            } else {
+                c
+            }
+        })
+        .collect::<String>();
+    text.parse::<F>()
+}
+
 /// Enum listing all the field types we know of
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum FieldType {
@@ -144,6 +517,11 @@ pub enum FieldType {
     // Unknown
     Double,
     Memo,
+    // dBase 7, 'I' is Long in its tables
+    Long,
+    DBase7Double,
+    Timestamp,
+    Autoincrement,
     //General,
     //BinaryCharacter,
     //BinaryMemo,
@@ -162,6 +540,10 @@ impl From<FieldType> for u8 {
             FieldType::Integer => 'I',
             FieldType::Double => 'B',
             FieldType::Memo => 'M',
+            FieldType::Long => 'I',
+            FieldType::DBase7Double => 'O',
+            FieldType::Timestamp => '@',
+            FieldType::Autoincrement => '+',
         };
         v as u8
     }
@@ -185,6 +567,10 @@ impl FieldType {
             // unknown version
             'B' => Some(FieldType::Double),
             'M' => Some(FieldType::Memo),
+            // dBase 7 field types
+            'O' => Some(FieldType::DBase7Double),
+            '@' => Some(FieldType::Timestamp),
+            '+' => Some(FieldType::Autoincrement),
             //'G' => Some(FieldType::General),
             //'C' => Some(FieldType::BinaryCharacter), ??
             //'M' => Some(FieldType::BinaryMemo),
@@ -198,14 +584,19 @@ impl FieldType {
     /// (the in-file size depends on the field data)
     ///
     /// This could/should be a const fn but they are not stable yet
+    #[cfg(feature = "std")]
     pub(crate) fn size(self) -> Option<u8> {
         match self {
             FieldType::Logical => Some(1),
             FieldType::Date => Some(8),
-            FieldType::Integer => Some(std::mem::size_of::<i32>() as u8),
-            FieldType::Currency => Some(std::mem::size_of::<f64>() as u8),
-            FieldType::DateTime => Some(2 * std::mem::size_of::<i32>() as u8),
-            FieldType::Double => Some(std::mem::size_of::<f64>() as u8),
+            FieldType::Integer => Some(core::mem::size_of::<i32>() as u8),
+            FieldType::Currency => Some(core::mem::size_of::<f64>() as u8),
+            FieldType::DateTime => Some(2 * core::mem::size_of::<i32>() as u8),
+            FieldType::Double => Some(core::mem::size_of::<f64>() as u8),
+            FieldType::Long | FieldType::Autoincrement => Some(core::mem::size_of::<i32>() as u8),
+            FieldType::DBase7Double | FieldType::Timestamp => {
+                Some(core::mem::size_of::<f64>() as u8)
+            }
             _ => None,
         }
     }
@@ -222,14 +613,14 @@ impl TryFrom<char> for FieldType {
     }
 }
 
-impl std::fmt::Display for FieldType {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+impl fmt::Display for FieldType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
         write!(f, "dbase::{:?}", self)
     }
 }
 
 /// Enum where each variant stores the record value
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone)]
 pub enum FieldValue {
     // dBase III fields
     // Stored as strings, fully padded (ie only space char) strings
@@ -257,80 +648,196 @@ pub enum FieldValue {
     /// These strings are stored in an external file
     /// called the `Memo file`
     Memo(String),
+    /// Numeric value without decimals read as an exact integer,
+    /// see [ReadingOptions::integer_numerics](struct.ReadingOptions.html#method.integer_numerics)
+    NumericInteger(Option<i64>),
+    // dBase 7 fields
+    // Stored in binary formats that sort like the values,
+    // fields of zero bytes are interpreted as None
+    /// dBase 7 type for integers (`I` in dBase 7 tables)
+    Long(Option<i32>),
+    /// dBase 7 type for floating point numbers
+    DBase7Double(Option<f64>),
+    /// dBase 7 type for date times, with the milliseconds
+    Timestamp(Option<DateTime>),
+    /// dBase 7 type for the integers numbering records
+    ///
+    /// The writer writes the given values, it does not number the records.
+    Autoincrement(i32),
 }
 
 impl FieldValue {
-    pub(crate) fn read_from<T: Read + Seek>(
-        mut source: &mut T,
+    /// Reads the value of the field, the values of memo fields are read
+    /// from the memo file of the reader
+    #[cfg(feature = "std")]
+    pub(crate) fn read_from<S: Read + Seek, T: Read + Seek>(
+        source: &mut S,
         memo_reader: &mut Option<MemoReader<T>>,
         field_info: &FieldInfo,
+        options: &ReadingOptions,
     ) -> Result<Self, ErrorKind> {
+        match field_info.field_type {
+            FieldType::Memo if memo_reader.is_some() => {
+                let data_from_memo = read_memo_bytes(source, memo_reader, field_info)?;
+                let value = options
+                    .encoding_of(field_info)
+                    .decode(&data_from_memo)
+                    .map_err(ErrorKind::StringDecodeError)?;
+                Ok(FieldValue::Memo(value.into_owned()))
+            }
+            _ => Self::read_from_source(&mut IoSource(source), field_info, options),
+        }
+    }
+
+    /// Reads the value of the field from the source, without a memo file:
+    /// the values of memo fields follow the missing memo policy of the options
+    pub(crate) fn read_from_source<S: ByteSource + ?Sized>(
+        source: &mut S,
+        field_info: &FieldInfo,
+        options: &ReadingOptions,
+    ) -> Result<Self, ErrorKind> {
+        let source = &mut KindSource(source);
         let value = match field_info.field_type {
-            FieldType::Logical => match source.read_u8()? as char {
+            FieldType::Logical => match read_u8(source)? as char {
                 ' ' | '?' => FieldValue::Logical(None),
                 '1' | '0' | 'T' | 't' | 'Y' | 'y' => FieldValue::Logical(Some(true)),
                 'N' | 'n' | 'F' | 'f' => FieldValue::Logical(Some(false)),
                 _ => FieldValue::Logical(None),
             },
             FieldType::Character => {
-                let value = read_string_of_len(&mut source, field_info.field_length)?;
-                let trimmed_value = value.trim();
-                if trimmed_value.is_empty() {
-                    FieldValue::Character(None)
+                let mut bytes = vec![0u8; field_info.field_length as usize];
+                source.read_bytes(&mut bytes)?;
+                let value = options
+                    .encoding_of(field_info)
+                    .decode(&bytes)
+                    .map_err(ErrorKind::StringDecodeError)?;
+                let is_padding = |c| options.is_padding(c);
+                if value.trim_matches(is_padding).is_empty() {
+                    match options.blank_policy {
+                        BlankPolicy::Null => FieldValue::Character(None),
+                        BlankPolicy::Empty => FieldValue::Character(Some(String::new())),
+                        BlankPolicy::Padded => FieldValue::Character(Some(value.into_owned())),
+                    }
                 } else {
-                    FieldValue::Character(Some(trimmed_value.to_owned()))
+                    let value = match options.character_trim {
+                        Some(TrimOption::Begin) => value.trim_start_matches(is_padding),
+                        Some(TrimOption::End) => value.trim_end_matches(is_padding),
+                        Some(TrimOption::BeginEnd) => value.trim_matches(is_padding),
+                        None => &value,
+                    };
+                    FieldValue::Character(Some(value.to_owned()))
                 }
             }
-            FieldType::Numeric => {
-                let value = read_string_of_len(&mut source, field_info.field_length)?;
+            FieldType::Numeric if options.reads_as_integer(field_info) => {
+                let mut buffer = [0u8; u8::MAX as usize];
+                let bytes = &mut buffer[..usize::from(field_info.field_length)];
+                source.read_bytes(bytes)?;
+                let value = String::from_utf8_lossy(bytes);
+                // The values written by this crate end with a decimal point
                 let trimmed_value = value.trim();
+                let trimmed_value = trimmed_value.strip_suffix('.').unwrap_or(trimmed_value);
                 if trimmed_value.is_empty() || value.chars().all(|c| c == '*') {
-                    FieldValue::Numeric(None)
+                    FieldValue::NumericInteger(None)
+                } else if let Some(separator) = options.thousands_separator {
+                    let digits = trimmed_value.replace(separator, "");
+                    FieldValue::NumericInteger(Some(digits.parse::<i64>()?))
                 } else {
-                    FieldValue::Numeric(Some(trimmed_value.parse::<f64>()?))
+                    FieldValue::NumericInteger(Some(trimmed_value.parse::<i64>()?))
                 }
             }
-            FieldType::Float => {
-                let value = read_string_of_len(&mut source, field_info.field_length)?;
-                let trimmed_value = value.trim();
-                if trimmed_value.is_empty() || value.chars().all(|c| c == '*') {
-                    FieldValue::Float(None)
+            FieldType::Numeric => {
+                let mut buffer = [0u8; u8::MAX as usize];
+                let bytes = &mut buffer[..usize::from(field_info.field_length)];
+                source.read_bytes(bytes)?;
+                let fast_value = if options.has_standard_separators() {
+                    fast_parse::parse_f64(bytes)
                 } else {
-                    FieldValue::Float(Some(trimmed_value.parse::<f32>()?))
+                    None
+                };
+                if let Some(value) = fast_value {
+                    FieldValue::Numeric(Some(value))
+                } else {
+                    let value = String::from_utf8_lossy(bytes);
+                    let trimmed_value = value.trim();
+                    if trimmed_value.is_empty() || value.chars().all(|c| c == '*') {
+                        FieldValue::Numeric(None)
+                    } else {
+                        FieldValue::Numeric(Some(parse_number::<f64>(trimmed_value, options)?))
+                    }
                 }
             }
-            FieldType::Date => {
-                let value = read_string_of_len(&mut source, field_info.field_length)?;
-                if value.chars().all(|c| c == ' ') {
-                    FieldValue::Date(None)
+            FieldType::Float => {
+                let mut buffer = [0u8; u8::MAX as usize];
+                let bytes = &mut buffer[..usize::from(field_info.field_length)];
+                source.read_bytes(bytes)?;
+                let fast_value = if options.has_standard_separators() {
+                    fast_parse::parse_f32(bytes)
+                } else {
+                    None
+                };
+                if let Some(value) = fast_value {
+                    FieldValue::Float(Some(value))
                 } else {
-                    FieldValue::Date(Some(value.parse::<Date>()?))
+                    let value = String::from_utf8_lossy(bytes);
+                    let trimmed_value = value.trim();
+                    if trimmed_value.is_empty() || value.chars().all(|c| c == '*') {
+                        FieldValue::Float(None)
+                    } else {
+                        FieldValue::Float(Some(parse_number::<f32>(trimmed_value, options)?))
+                    }
                 }
             }
-            FieldType::Integer => FieldValue::Integer(source.read_i32::<LittleEndian>()?),
-            FieldType::Double => FieldValue::Double(source.read_f64::<LittleEndian>()?),
-            FieldType::Currency => FieldValue::Currency(source.read_f64::<LittleEndian>()?),
-            FieldType::DateTime => FieldValue::DateTime(DateTime::read_from(&mut source)?),
-            FieldType::Memo => {
-                let index_in_memo = if field_info.field_length > 4 {
-                    let string = read_string_of_len(&mut source, field_info.field_length)?;
-                    let trimmed_str = string.trim();
-                    if trimmed_str.is_empty() {
-                        return Ok(FieldValue::Memo(String::from("")));
-                    } else {
-                        trimmed_str.parse::<u32>()?
+            FieldType::Date => {
+                let mut buffer = [0u8; u8::MAX as usize];
+                let bytes = &mut buffer[..usize::from(field_info.field_length)];
+                source.read_bytes(bytes)?;
+                let date = match fast_parse::parse_date(bytes) {
+                    Some((year, month, day)) => Ok(Date { year, month, day }),
+                    None => {
+                        let value = String::from_utf8_lossy(bytes);
+                        if value.chars().all(|c| c == ' ') {
+                            return Ok(FieldValue::Date(None));
+                        }
+                        value.parse::<Date>()
                     }
-                } else {
-                    source.read_u32::<LittleEndian>()?
                 };
-
-                if let Some(memo_reader) = memo_reader {
-                    let data_from_memo = memo_reader.read_data_at(index_in_memo)?;
-                    FieldValue::Memo(String::from_utf8_lossy(data_from_memo).to_string())
-                } else {
-                    return Err(ErrorKind::MissingMemoFile);
+                let text = || String::from_utf8_lossy(bytes).into_owned();
+                match (date, options.invalid_date_policy) {
+                    (Ok(date), InvalidDatePolicy::Lenient) => FieldValue::Date(Some(date)),
+                    (Err(error), InvalidDatePolicy::Lenient) => return Err(error.into()),
+                    (Ok(date), _) if date.is_day_of_calendar() => FieldValue::Date(Some(date)),
+                    (_, InvalidDatePolicy::Fail) => return Err(ErrorKind::InvalidDate(text())),
+                    (_, InvalidDatePolicy::Null) => FieldValue::Date(None),
+                    (_, InvalidDatePolicy::Raw) => FieldValue::Character(Some(text())),
                 }
             }
+            FieldType::Integer => FieldValue::Integer(i32::from_le_bytes(read_array(source)?)),
+            FieldType::Double => FieldValue::Double(f64::from_le_bytes(read_array(source)?)),
+            FieldType::Currency => FieldValue::Currency(f64::from_le_bytes(read_array(source)?)),
+            FieldType::DateTime => FieldValue::DateTime(DateTime::read_from(source)?),
+            FieldType::Long => FieldValue::Long(read_dbase7_long(source)?),
+            FieldType::Autoincrement => {
+                FieldValue::Autoincrement(read_dbase7_long(source)?.unwrap_or(0))
+            }
+            FieldType::DBase7Double => FieldValue::DBase7Double(read_dbase7_double(source)?),
+            FieldType::Timestamp => FieldValue::Timestamp(
+                read_dbase7_double(source)?.map(DateTime::from_dbase7_timestamp),
+            ),
+            FieldType::Memo => match options.missing_memo_policy {
+                // Empty memo fields do not need the memo file
+                MissingMemoPolicy::Fail => match read_memo_index(source, field_info)? {
+                    Some(_) => return Err(ErrorKind::MissingMemoFile),
+                    None => FieldValue::Memo(String::new()),
+                },
+                MissingMemoPolicy::Null => {
+                    skip(source, usize::from(field_info.field_length))?;
+                    FieldValue::Character(None)
+                }
+                _ => {
+                    skip(source, usize::from(field_info.field_length))?;
+                    FieldValue::Memo(String::new())
+                }
+            },
         };
         Ok(value)
     }
@@ -348,13 +855,358 @@ impl FieldValue {
             FieldValue::Memo(_) => FieldType::Memo,
             FieldValue::Currency(_) => FieldType::Currency,
             FieldValue::DateTime(_) => FieldType::DateTime,
+            FieldValue::NumericInteger(_) => FieldType::Numeric,
+            FieldValue::Long(_) => FieldType::Long,
+            FieldValue::DBase7Double(_) => FieldType::DBase7Double,
+            FieldValue::Timestamp(_) => FieldType::Timestamp,
+            FieldValue::Autoincrement(_) => FieldType::Autoincrement,
+        }
+    }
+}
+
+impl FieldValue {
+    /// Position of the variant in the total order
+    fn rank(&self) -> u8 {
+        match self {
+            FieldValue::Character(_) => 0,
+            FieldValue::Numeric(_) => 1,
+            FieldValue::Logical(_) => 2,
+            FieldValue::Date(_) => 3,
+            FieldValue::Float(_) => 4,
+            FieldValue::Integer(_) => 5,
+            FieldValue::Currency(_) => 6,
+            FieldValue::DateTime(_) => 7,
+            FieldValue::Double(_) => 8,
+            FieldValue::Memo(_) => 9,
+            FieldValue::NumericInteger(_) => 10,
+            FieldValue::Long(_) => 11,
+            FieldValue::DBase7Double(_) => 12,
+            FieldValue::Timestamp(_) => 13,
+            FieldValue::Autoincrement(_) => 14,
+        }
+    }
+
+    /// Compares two values with a total order, usable to sort values of any kind
+    ///
+    /// - Values of different variants are ordered by variant, in the order
+    ///   of declaration of the variants (Character < Numeric < Logical < Date < Float
+    ///   < Integer < Currency < DateTime < Double < Memo < NumericInteger < Long
+    ///   < DBase7Double < Timestamp < Autoincrement), even numbers of different variants.
+    /// - Empty values (`None`) are before all the other values of their variant.
+    /// - Floating point numbers are ordered with the IEEE 754 total order
+    ///   (see [f64::total_cmp]), so `-0.0 < 0.0` and NaNs are at the ends.
+    ///
+    /// Unlike [partial_cmp](#method.partial_cmp), it never fails,
+    /// which makes it the one to use with `sort_by`, `dedup_by` or `binary_search_by`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dbase::FieldValue;
+    ///
+    /// let mut values = vec![
+    ///     FieldValue::Numeric(Some(2.0)),
+    ///     FieldValue::Character(Some("b".to_owned())),
+    ///     FieldValue::Numeric(None),
+    ///     FieldValue::Character(Some("a".to_owned())),
+    /// ];
+    /// values.sort_by(FieldValue::total_cmp);
+    /// assert_eq!(
+    ///     values,
+    ///     vec![
+    ///         FieldValue::Character(Some("a".to_owned())),
+    ///         FieldValue::Character(Some("b".to_owned())),
+    ///         FieldValue::Numeric(None),
+    ///         FieldValue::Numeric(Some(2.0)),
+    ///     ]
+    /// );
+    /// ```
+    pub fn total_cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (FieldValue::Numeric(a), FieldValue::Numeric(b)) => match (a, b) {
+                (Some(a), Some(b)) => a.total_cmp(b),
+                _ => a.is_some().cmp(&b.is_some()),
+            },
+            (FieldValue::Float(a), FieldValue::Float(b)) => match (a, b) {
+                (Some(a), Some(b)) => a.total_cmp(b),
+                _ => a.is_some().cmp(&b.is_some()),
+            },
+            (FieldValue::DBase7Double(a), FieldValue::DBase7Double(b)) => match (a, b) {
+                (Some(a), Some(b)) => a.total_cmp(b),
+                _ => a.is_some().cmp(&b.is_some()),
+            },
+            (FieldValue::Currency(a), FieldValue::Currency(b))
+            | (FieldValue::Double(a), FieldValue::Double(b)) => a.total_cmp(b),
+            _ => self
+                .partial_cmp(other)
+                .unwrap_or_else(|| self.rank().cmp(&other.rank())),
         }
     }
 }
 
+/// Returns whether the numbers are equal, NaNs being equal to each other
+fn floats_eq(a: f64, b: f64) -> bool {
+    a == b || (a.is_nan() && b.is_nan())
+}
+
+/// Compares the numbers, NaNs being equal to each other,
+/// so that the comparison agrees with [floats_eq]
+fn floats_partial_cmp(a: f64, b: f64) -> Option<Ordering> {
+    if a.is_nan() && b.is_nan() {
+        Some(Ordering::Equal)
+    } else {
+        a.partial_cmp(&b)
+    }
+}
+
+/// Values are equal when they are of the same variant and have the same content.
+///
+/// - Values of different variants are never equal, even numbers:
+///   `Numeric(Some(1.0))` is neither equal to `Integer(1)` nor to `NumericInteger(Some(1))`.
+/// - Numbers are compared as numbers, so `0.0 == -0.0`, except that all NaNs
+///   are equal to each other (unlike the `f64` comparison).
+/// - Empty values (`None`) are only equal to empty values, `Character(None)`
+///   is not equal to `Character(Some(String::new()))`.
+/// - Dates are equal when their year, month and day are, even if they are not
+///   days of the calendar (eg: the 30th of February of invalid tables),
+///   date times when their date and time (to the second) are.
+impl PartialEq for FieldValue {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (FieldValue::Character(a), FieldValue::Character(b)) => a == b,
+            (FieldValue::Numeric(a), FieldValue::Numeric(b)) => match (a, b) {
+                (Some(a), Some(b)) => floats_eq(*a, *b),
+                _ => a.is_none() && b.is_none(),
+            },
+            (FieldValue::Logical(a), FieldValue::Logical(b)) => a == b,
+            (FieldValue::Date(a), FieldValue::Date(b)) => a == b,
+            (FieldValue::Float(a), FieldValue::Float(b)) => match (a, b) {
+                (Some(a), Some(b)) => floats_eq(f64::from(*a), f64::from(*b)),
+                _ => a.is_none() && b.is_none(),
+            },
+            (FieldValue::Integer(a), FieldValue::Integer(b)) => a == b,
+            (FieldValue::Currency(a), FieldValue::Currency(b))
+            | (FieldValue::Double(a), FieldValue::Double(b)) => floats_eq(*a, *b),
+            (FieldValue::DateTime(a), FieldValue::DateTime(b)) => a == b,
+            (FieldValue::Memo(a), FieldValue::Memo(b)) => a == b,
+            (FieldValue::NumericInteger(a), FieldValue::NumericInteger(b)) => a == b,
+            (FieldValue::Long(a), FieldValue::Long(b)) => a == b,
+            (FieldValue::DBase7Double(a), FieldValue::DBase7Double(b)) => match (a, b) {
+                (Some(a), Some(b)) => floats_eq(*a, *b),
+                _ => a.is_none() && b.is_none(),
+            },
+            (FieldValue::Timestamp(a), FieldValue::Timestamp(b)) => a == b,
+            (FieldValue::Autoincrement(a), FieldValue::Autoincrement(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+/// Values can be used as keys of maps and in sets,
+/// with the equality of [PartialEq](#impl-PartialEq-for-FieldValue).
+impl Eq for FieldValue {}
+
+impl Hash for FieldValue {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // 0.0 and -0.0 are equal, as are all the NaNs, so they must have the same hash
+        fn float_bits(v: f64) -> u64 {
+            if v == 0.0 {
+                0
+            } else if v.is_nan() {
+                f64::NAN.to_bits()
+            } else {
+                v.to_bits()
+            }
+        }
+        core::mem::discriminant(self).hash(state);
+        match self {
+            FieldValue::Character(s) => s.hash(state),
+            FieldValue::Numeric(v) => v.map(float_bits).hash(state),
+            FieldValue::Logical(b) => b.hash(state),
+            FieldValue::Date(d) => d.hash(state),
+            FieldValue::Float(v) => v.map(|v| float_bits(f64::from(v))).hash(state),
+            FieldValue::Integer(v) => v.hash(state),
+            FieldValue::Currency(v) | FieldValue::Double(v) => float_bits(*v).hash(state),
+            FieldValue::DateTime(dt) => dt.hash(state),
+            FieldValue::Memo(s) => s.hash(state),
+            FieldValue::NumericInteger(v) => v.hash(state),
+            FieldValue::Long(v) => v.hash(state),
+            FieldValue::DBase7Double(v) => v.map(float_bits).hash(state),
+            FieldValue::Timestamp(dt) => dt.hash(state),
+            FieldValue::Autoincrement(v) => v.hash(state),
+        }
+    }
+}
+
+/// Values are only comparable to values of the same variant,
+/// empty values (`None`) are less than the other values.
+///
+/// Comparing a NaN number with another number or a value of another variant gives `None`,
+/// NaNs being equal to each other like [eq](#method.eq) says,
+/// see [total_cmp](enum.FieldValue.html#method.total_cmp) for a total order.
+impl PartialOrd for FieldValue {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        match (self, other) {
+            (FieldValue::Character(a), FieldValue::Character(b)) => a.partial_cmp(b),
+            (FieldValue::Numeric(a), FieldValue::Numeric(b)) => match (a, b) {
+                (Some(a), Some(b)) => floats_partial_cmp(*a, *b),
+                _ => a.is_some().partial_cmp(&b.is_some()),
+            },
+            (FieldValue::Logical(a), FieldValue::Logical(b)) => a.partial_cmp(b),
+            (FieldValue::Date(a), FieldValue::Date(b)) => a.partial_cmp(b),
+            (FieldValue::Float(a), FieldValue::Float(b)) => match (a, b) {
+                (Some(a), Some(b)) => floats_partial_cmp(f64::from(*a), f64::from(*b)),
+                _ => a.is_some().partial_cmp(&b.is_some()),
+            },
+            (FieldValue::Integer(a), FieldValue::Integer(b)) => a.partial_cmp(b),
+            (FieldValue::Currency(a), FieldValue::Currency(b))
+            | (FieldValue::Double(a), FieldValue::Double(b)) => floats_partial_cmp(*a, *b),
+            (FieldValue::DateTime(a), FieldValue::DateTime(b)) => a.partial_cmp(b),
+            (FieldValue::Memo(a), FieldValue::Memo(b)) => a.partial_cmp(b),
+            (FieldValue::NumericInteger(a), FieldValue::NumericInteger(b)) => a.partial_cmp(b),
+            (FieldValue::Long(a), FieldValue::Long(b)) => a.partial_cmp(b),
+            (FieldValue::DBase7Double(a), FieldValue::DBase7Double(b)) => match (a, b) {
+                (Some(a), Some(b)) => floats_partial_cmp(*a, *b),
+                _ => a.is_some().partial_cmp(&b.is_some()),
+            },
+            (FieldValue::Timestamp(a), FieldValue::Timestamp(b)) => a.partial_cmp(b),
+            (FieldValue::Autoincrement(a), FieldValue::Autoincrement(b)) => a.partial_cmp(b),
+            _ => None,
+        }
+    }
+}
+
+/// Renders the values like dBase does
+///
+/// - Empty values are rendered as nothing (or spaces, with a width).
+/// - Logicals are rendered as `T` or `F`.
+/// - Dates are rendered as `YYYYMMDD`, date times as `YYYYMMDDhhmmss`.
+/// - Currencies are rendered with 4 decimals.
+/// - Numbers are aligned to the right when a width is given, and use
+///   the precision when one is given.
+///
+/// Use [display_as](#method.display_as) to render a value with the length and
+/// the number of decimals of its field.
+///
+/// # Example
+///
+/// ```
+/// use dbase::FieldValue;
+///
+/// assert_eq!(FieldValue::Logical(Some(true)).to_string(), "T");
+/// assert_eq!(FieldValue::Date(Some(dbase::Date::new(14, 7, 1789))).to_string(), "17890714");
+/// assert_eq!(format!("{:8.2}", FieldValue::Numeric(Some(3.14159))), "    3.14");
+/// assert_eq!(format!("{:6}|", FieldValue::Character(Some("abc".to_owned()))), "abc   |");
+/// ```
 impl fmt::Display for FieldValue {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{:?}", self)
+        fn number<N: fmt::Display>(f: &mut fmt::Formatter, number: N) -> fmt::Result {
+            let text = match f.precision() {
+                Some(precision) => format!("{:.*}", precision, number),
+                None => number.to_string(),
+            };
+            match (f.align(), f.width()) {
+                (None, Some(width)) => write!(f, "{:>width$}", text, width = width),
+                _ => f.pad(&text),
+            }
+        }
+
+        match self {
+            FieldValue::Character(Some(text)) | FieldValue::Memo(text) => f.pad(text),
+            FieldValue::Numeric(Some(value))
+            | FieldValue::Double(value)
+            | FieldValue::DBase7Double(Some(value)) => number(f, value),
+            FieldValue::Float(Some(value)) => number(f, value),
+            FieldValue::Integer(value)
+            | FieldValue::Long(Some(value))
+            | FieldValue::Autoincrement(value) => number(f, value),
+            FieldValue::NumericInteger(Some(value)) => number(f, value),
+            FieldValue::Currency(value) => match f.precision() {
+                Some(_) => number(f, value),
+                None => number(f, format_args!("{:.4}", value)),
+            },
+            FieldValue::Logical(Some(value)) => f.pad(if *value { "T" } else { "F" }),
+            FieldValue::Date(Some(date)) => f.pad(&date.to_string()),
+            FieldValue::DateTime(date_time) | FieldValue::Timestamp(Some(date_time)) => {
+                let (date, time) = (date_time.date(), date_time.time());
+                f.pad(&format!(
+                    "{}{:02}{:02}{:02}",
+                    date, time.hours, time.minutes, time.seconds
+                ))
+            }
+            FieldValue::Character(None)
+            | FieldValue::Numeric(None)
+            | FieldValue::Float(None)
+            | FieldValue::Logical(None)
+            | FieldValue::Date(None)
+            | FieldValue::NumericInteger(None)
+            | FieldValue::Long(None)
+            | FieldValue::DBase7Double(None)
+            | FieldValue::Timestamp(None) => match (f.align(), f.width()) {
+                (None, Some(width))
+                    if matches!(
+                        self,
+                        FieldValue::Numeric(_)
+                            | FieldValue::Float(_)
+                            | FieldValue::NumericInteger(_)
+                            | FieldValue::Long(_)
+                            | FieldValue::DBase7Double(_)
+                    ) =>
+                {
+                    write!(f, "{:>width$}", "", width = width)
+                }
+                _ => f.pad(""),
+            },
+        }
+    }
+}
+
+impl FieldValue {
+    /// Returns a value that renders this value like dBase does (see the
+    /// [Display](#impl-Display-for-FieldValue) implementation), using the length
+    /// of the field as the width and its number of decimals for Numeric and Float fields
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), dbase::Error> {
+    /// let mut reader = dbase::Reader::from_path("tests/data/line.dbf")?;
+    /// let field_info = reader.fields()[1].clone();
+    /// let record = reader.iter_records().next().unwrap()?;
+    /// let value = record.get("name").unwrap();
+    /// assert_eq!(
+    ///     value.display_as(&field_info).to_string(),
+    ///     format!("{:50}", "linestring1")
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn display_as<'a>(&'a self, field_info: &'a FieldInfo) -> impl fmt::Display + 'a {
+        DisplayAs {
+            value: self,
+            field_info,
+        }
+    }
+}
+
+struct DisplayAs<'a> {
+    value: &'a FieldValue,
+    field_info: &'a FieldInfo,
+}
+
+impl fmt::Display for DisplayAs<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let width = usize::from(self.field_info.field_length);
+        match self.value {
+            FieldValue::Numeric(_) | FieldValue::Float(_) => write!(
+                f,
+                "{:width$.precision$}",
+                self.value,
+                width = width,
+                precision = usize::from(self.field_info.num_decimal_places)
+            ),
+            _ => write!(f, "{:width$}", self.value, width = width),
+        }
     }
 }
 
@@ -366,7 +1218,7 @@ impl fmt::Display for FieldValue {
 /// with just a very few checks.
 ///
 /// Also, dBase files do not have concept of timezones.
-#[derive(Debug, Copy, Clone, PartialEq)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Date {
     pub(crate) year: u32,
     pub(crate) month: u32,
@@ -392,6 +1244,21 @@ impl Date {
         Self { year, month, day }
     }
 
+    /// Returns whether the date is a day of the (proleptic Gregorian) calendar
+    pub(crate) fn is_day_of_calendar(&self) -> bool {
+        let is_leap_year = self.year.is_multiple_of(4)
+            && (!self.year.is_multiple_of(100) || self.year.is_multiple_of(400));
+        let days_in_month = match self.month {
+            1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+            4 | 6 | 9 | 11 => 30,
+            2 if is_leap_year => 29,
+            2 => 28,
+            _ => return false,
+        };
+        // The dates convert to chrono::NaiveDate, whose years go up to 262143
+        self.year <= 262_143 && (1..=days_in_month).contains(&self.day)
+    }
+
     /// Returns the year
     pub fn year(&self) -> u32 {
         self.year
@@ -409,7 +1276,7 @@ impl Date {
 
     // https://en.wikipedia.org/wiki/Julian_day
     // at "Julian or Gregorian calendar from Julian day number"
-    fn julian_day_number_to_gregorian_date(jdn: i32) -> Date {
+    pub(crate) fn julian_day_number_to_gregorian_date(jdn: i32) -> Date {
         const Y: i32 = 4716;
         const J: i32 = 1401;
         const M: i32 = 2;
@@ -439,7 +1306,20 @@ impl Date {
         }
     }
 
-    fn to_julian_day_number(&self) -> i32 {
+    /// Julian day number of the unix epoch (1970-01-01)
+    const UNIX_EPOCH_JULIAN_DAY: i32 = 2_440_588;
+
+    /// Creates the date that is the given number of days after the unix epoch (1970-01-01)
+    pub fn from_days_since_unix_epoch(days: i32) -> Date {
+        Self::julian_day_number_to_gregorian_date(days + Self::UNIX_EPOCH_JULIAN_DAY)
+    }
+
+    /// Returns the number of days since the unix epoch (1970-01-01)
+    pub fn days_since_unix_epoch(self) -> i32 {
+        self.to_julian_day_number() - Self::UNIX_EPOCH_JULIAN_DAY
+    }
+
+    pub(crate) fn to_julian_day_number(self) -> i32 {
         let (month, year) = if self.month > 2 {
             (self.month - 3, self.year)
         } else {
@@ -458,29 +1338,32 @@ impl Date {
 }
 
 impl FromStr for Date {
-    type Err = std::num::ParseIntError;
+    type Err = core::num::ParseIntError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let year = s[0..4].parse::<u32>()?;
-        let month = s[4..6].parse::<u32>()?;
-        let day = s[6..8].parse::<u32>()?;
+        // Text that is too short or not ASCII fails to parse instead of panicking
+        let year = s.get(0..4).unwrap_or_default().parse::<u32>()?;
+        let month = s.get(4..6).unwrap_or_default().parse::<u32>()?;
+        let day = s.get(6..8).unwrap_or_default().parse::<u32>()?;
 
         Ok(Self { year, month, day })
     }
 }
 
-impl std::string::ToString for Date {
-    fn to_string(&self) -> String {
-        format!("{:04}{:02}{:02}", self.year, self.month, self.day)
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:04}{:02}{:02}", self.year, self.month, self.day)
     }
 }
 
+#[cfg(feature = "std")]
 impl From<Date> for chrono::NaiveDate {
     fn from(d: Date) -> Self {
-        Self::from_ymd(d.year as i32, d.month, d.day)
+        Self::from_ymd_opt(d.year as i32, d.month, d.day).expect("invalid date")
     }
 }
 
+#[cfg(feature = "std")]
 impl From<chrono::NaiveDate> for Date {
     fn from(d: chrono::NaiveDate) -> Self {
         Self {
@@ -491,6 +1374,8 @@ impl From<chrono::NaiveDate> for Date {
     }
 }
 
+#[cfg(feature = "std")]
+#[allow(deprecated)]
 impl<Tz: chrono::TimeZone> From<chrono::Date<Tz>> for Date {
     fn from(d: chrono::Date<Tz>) -> Self {
         Self {
@@ -506,7 +1391,7 @@ impl<Tz: chrono::TimeZone> From<chrono::Date<Tz>> for Date {
 ///
 /// This is a very naive Time struct, very minimal verifications are done.
 ///
-#[derive(Debug, Copy, Clone, PartialEq)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Time {
     hours: u32,
     minutes: u32,
@@ -534,6 +1419,21 @@ impl Time {
         }
     }
 
+    /// Returns the hours
+    pub fn hours(&self) -> u32 {
+        self.hours
+    }
+
+    /// Returns the minutes
+    pub fn minutes(&self) -> u32 {
+        self.minutes
+    }
+
+    /// Returns the seconds
+    pub fn seconds(&self) -> u32 {
+        self.seconds
+    }
+
     fn from_word(mut time_word: i32) -> Self {
         let hours: u32 = (time_word / Self::HOURS_FACTOR) as u32;
         time_word -= (hours * Self::HOURS_FACTOR as u32) as i32;
@@ -547,7 +1447,7 @@ impl Time {
         }
     }
 
-    fn to_time_word(&self) -> i32 {
+    fn to_time_word(self) -> i32 {
         let mut time_word = self.hours * Self::HOURS_FACTOR as u32;
         time_word += self.minutes * Self::MINUTES_FACTOR as u32;
         time_word += self.seconds * Self::SECONDS_FACTOR as u32;
@@ -556,7 +1456,7 @@ impl Time {
 }
 
 /// FoxBase representation of a DateTime
-#[derive(Debug, Copy, Clone, PartialEq)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct DateTime {
     date: Date,
     time: Time,
@@ -568,21 +1468,113 @@ impl DateTime {
         Self { date, time }
     }
 
-    fn read_from<T: Read>(src: &mut T) -> Result<Self, ErrorKind> {
-        let julian_day_number = src.read_i32::<LittleEndian>()?;
-        let time_word = src.read_i32::<LittleEndian>()?;
+    const MILLISECONDS_PER_DAY: i64 = 86_400_000;
+
+    /// Creates the date time that is the given number of milliseconds
+    /// after the unix epoch (1970-01-01 00:00:00)
+    pub fn from_milliseconds_since_unix_epoch(milliseconds: i64) -> Self {
+        let days = milliseconds.div_euclid(Self::MILLISECONDS_PER_DAY);
+        let time_word = milliseconds.rem_euclid(Self::MILLISECONDS_PER_DAY);
+        Self {
+            date: Date::from_days_since_unix_epoch(days as i32),
+            time: Time::from_word(time_word as i32),
+        }
+    }
+
+    /// Returns the number of milliseconds since the unix epoch (1970-01-01 00:00:00)
+    pub fn milliseconds_since_unix_epoch(self) -> i64 {
+        i64::from(self.date.days_since_unix_epoch()) * Self::MILLISECONDS_PER_DAY
+            + i64::from(self.time.to_time_word())
+    }
+
+    /// Returns the date
+    pub fn date(&self) -> Date {
+        self.date
+    }
+
+    /// Returns the time
+    pub fn time(&self) -> Time {
+        self.time
+    }
+
+    fn read_from<S: ByteSource + ?Sized>(src: &mut S) -> Result<Self, S::Error> {
+        let julian_day_number = i32::from_le_bytes(read_array(src)?);
+        let time_word = i32::from_le_bytes(read_array(src)?);
         let time = Time::from_word(time_word);
         let date = Date::julian_day_number_to_gregorian_date(julian_day_number);
         Ok(Self { date, time })
     }
 
+    #[cfg(feature = "std")]
     fn write_to<W: Write>(&self, dest: &mut W) -> std::io::Result<()> {
         dest.write_i32::<LittleEndian>(self.date.to_julian_day_number())?;
         dest.write_i32::<LittleEndian>(self.time.to_time_word())?;
         Ok(())
     }
+
+    /// Creates the date time from the number of milliseconds since the start
+    /// of the julian day 0, stored by the timestamp fields of dBase 7 tables
+    fn from_dbase7_timestamp(milliseconds: f64) -> Self {
+        let since_unix_epoch = milliseconds as i64
+            - i64::from(Date::UNIX_EPOCH_JULIAN_DAY) * Self::MILLISECONDS_PER_DAY;
+        Self::from_milliseconds_since_unix_epoch(since_unix_epoch)
+    }
+
+    /// Returns the number of milliseconds since the start of the julian day 0
+    #[cfg(feature = "std")]
+    fn to_dbase7_timestamp(self) -> f64 {
+        let since_julian_day_0 = self.milliseconds_since_unix_epoch()
+            + i64::from(Date::UNIX_EPOCH_JULIAN_DAY) * Self::MILLISECONDS_PER_DAY;
+        since_julian_day_0 as f64
+    }
+}
+
+/// Bit of the sign of the numbers
+const SIGN_BIT_32: u32 = 0x8000_0000;
+const SIGN_BIT_64: u64 = 0x8000_0000_0000_0000;
+
+/// Reads an integer of a dBase 7 table: stored big endian with the sign bit flipped,
+/// so that the bytes sort like the numbers, zero bytes being None
+fn read_dbase7_long<S: ByteSource + ?Sized>(src: &mut S) -> Result<Option<i32>, S::Error> {
+    let bits = u32::from_be_bytes(read_array(src)?);
+    Ok(if bits == 0 {
+        None
+    } else {
+        Some((bits ^ SIGN_BIT_32) as i32)
+    })
+}
+
+#[cfg(feature = "std")]
+fn write_dbase7_long<W: Write>(dest: &mut W, value: Option<i32>) -> std::io::Result<()> {
+    dest.write_u32::<BigEndian>(value.map_or(0, |value| value as u32 ^ SIGN_BIT_32))
+}
+
+/// Reads a double of a dBase 7 table: stored big endian with the sign bit flipped
+/// for the positive numbers and all the bits flipped for the negative numbers,
+/// so that the bytes sort like the numbers, zero bytes being None
+fn read_dbase7_double<S: ByteSource + ?Sized>(src: &mut S) -> Result<Option<f64>, S::Error> {
+    let bits = u64::from_be_bytes(read_array(src)?);
+    Ok(match bits {
+        0 => None,
+        bits if bits & SIGN_BIT_64 != 0 => Some(f64::from_bits(bits ^ SIGN_BIT_64)),
+        bits => Some(f64::from_bits(!bits)),
+    })
+}
+
+#[cfg(feature = "std")]
+fn write_dbase7_double<W: Write>(dest: &mut W, value: Option<f64>) -> std::io::Result<()> {
+    let bits = value.map_or(0, |value| {
+        let bits = value.to_bits();
+        if bits & SIGN_BIT_64 == 0 {
+            bits ^ SIGN_BIT_64
+        } else {
+            !bits
+        }
+    });
+    dest.write_u64::<BigEndian>(bits)
 }
 
+#[cfg(feature = "std")]
 impl WritableAsDbaseField for FieldValue {
     fn write_as<W: Write>(&self, field_type: FieldType, dst: &mut W) -> Result<(), ErrorKind> {
         if self.field_type() != field_type {
@@ -599,11 +1591,17 @@ impl WritableAsDbaseField for FieldValue {
                 FieldValue::DateTime(value) => value.write_as(field_type, dst),
                 FieldValue::Double(value) => value.write_as(field_type, dst),
                 FieldValue::Memo(_) => unimplemented!("Cannot write memo"),
+                FieldValue::NumericInteger(value) => value.write_as(field_type, dst),
+                FieldValue::Long(value) => value.write_as(field_type, dst),
+                FieldValue::DBase7Double(value) => value.write_as(field_type, dst),
+                FieldValue::Timestamp(value) => value.write_as(field_type, dst),
+                FieldValue::Autoincrement(value) => value.write_as(field_type, dst),
             }
         }
     }
 }
 
+#[cfg(feature = "std")]
 impl WritableAsDbaseField for f64 {
     fn write_as<W: Write>(&self, field_type: FieldType, dst: &mut W) -> Result<(), ErrorKind> {
         match field_type {
@@ -615,11 +1613,16 @@ impl WritableAsDbaseField for f64 {
                 dst.write_f64::<LittleEndian>(*self)?;
                 Ok(())
             }
+            FieldType::DBase7Double => {
+                write_dbase7_double(dst, Some(*self))?;
+                Ok(())
+            }
             _ => Err(ErrorKind::IncompatibleType),
         }
     }
 }
 
+#[cfg(feature = "std")]
 impl WritableAsDbaseField for Date {
     fn write_as<W: Write>(&self, field_type: FieldType, dst: &mut W) -> Result<(), ErrorKind> {
         if field_type == FieldType::Date {
@@ -631,6 +1634,7 @@ impl WritableAsDbaseField for Date {
     }
 }
 
+#[cfg(feature = "std")]
 impl WritableAsDbaseField for Option<Date> {
     fn write_as<W: Write>(&self, field_type: FieldType, dst: &mut W) -> Result<(), ErrorKind> {
         if field_type == FieldType::Date {
@@ -648,20 +1652,53 @@ impl WritableAsDbaseField for Option<Date> {
     }
 }
 
+#[cfg(feature = "std")]
 impl WritableAsDbaseField for Option<f64> {
+    fn write_as<W: Write>(&self, field_type: FieldType, dst: &mut W) -> Result<(), ErrorKind> {
+        match field_type {
+            FieldType::Numeric => {
+                if let Some(value) = self {
+                    value.write_as(field_type, dst)
+                } else {
+                    Ok(())
+                }
+            }
+            FieldType::DBase7Double => {
+                write_dbase7_double(dst, *self)?;
+                Ok(())
+            }
+            _ => Err(ErrorKind::IncompatibleType),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl WritableAsDbaseField for i64 {
+    fn write_as<W: Write>(&self, field_type: FieldType, dst: &mut W) -> Result<(), ErrorKind> {
+        if field_type == FieldType::Numeric {
+            write!(dst, "{}", self)?;
+            Ok(())
+        } else {
+            Err(ErrorKind::IncompatibleType)
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl WritableAsDbaseField for Option<i64> {
     fn write_as<W: Write>(&self, field_type: FieldType, dst: &mut W) -> Result<(), ErrorKind> {
         if field_type == FieldType::Numeric {
             if let Some(value) = self {
-                value.write_as(field_type, dst)
-            } else {
-                Ok(())
+                value.write_as(field_type, dst)?;
             }
+            Ok(())
         } else {
             Err(ErrorKind::IncompatibleType)
         }
     }
 }
 
+#[cfg(feature = "std")]
 impl WritableAsDbaseField for f32 {
     fn write_as<W: Write>(&self, field_type: FieldType, dst: &mut W) -> Result<(), ErrorKind> {
         if field_type == FieldType::Float {
@@ -673,6 +1710,7 @@ impl WritableAsDbaseField for f32 {
     }
 }
 
+#[cfg(feature = "std")]
 impl WritableAsDbaseField for Option<f32> {
     fn write_as<W: Write>(&self, field_type: FieldType, dst: &mut W) -> Result<(), ErrorKind> {
         if field_type == FieldType::Float {
@@ -686,6 +1724,7 @@ impl WritableAsDbaseField for Option<f32> {
     }
 }
 
+#[cfg(feature = "std")]
 impl WritableAsDbaseField for String {
     fn write_as<W: Write>(&self, field_type: FieldType, dst: &mut W) -> Result<(), ErrorKind> {
         if field_type == FieldType::Character {
@@ -697,6 +1736,7 @@ impl WritableAsDbaseField for String {
     }
 }
 
+#[cfg(feature = "std")]
 impl WritableAsDbaseField for Option<String> {
     fn write_as<W: Write>(&self, field_type: FieldType, dst: &mut W) -> Result<(), ErrorKind> {
         if field_type == FieldType::Character {
@@ -710,6 +1750,7 @@ impl WritableAsDbaseField for Option<String> {
     }
 }
 
+#[cfg(feature = "std")]
 impl WritableAsDbaseField for &str {
     fn write_as<W: Write>(&self, field_type: FieldType, dst: &mut W) -> Result<(), ErrorKind> {
         if field_type == FieldType::Character {
@@ -721,6 +1762,7 @@ impl WritableAsDbaseField for &str {
     }
 }
 
+#[cfg(feature = "std")]
 impl WritableAsDbaseField for bool {
     fn write_as<W: Write>(&self, field_type: FieldType, dst: &mut W) -> Result<(), ErrorKind> {
         if field_type == FieldType::Logical {
@@ -736,6 +1778,7 @@ impl WritableAsDbaseField for bool {
     }
 }
 
+#[cfg(feature = "std")]
 impl WritableAsDbaseField for Option<bool> {
     fn write_as<W: Write>(&self, field_type: FieldType, dst: &mut W) -> Result<(), ErrorKind> {
         if field_type == FieldType::Logical {
@@ -749,10 +1792,28 @@ impl WritableAsDbaseField for Option<bool> {
     }
 }
 
+#[cfg(feature = "std")]
 impl WritableAsDbaseField for i32 {
     fn write_as<W: Write>(&self, field_type: FieldType, dst: &mut W) -> Result<(), ErrorKind> {
-        if field_type == FieldType::Integer {
-            dst.write_i32::<LittleEndian>(*self)?;
+        match field_type {
+            FieldType::Integer => {
+                dst.write_i32::<LittleEndian>(*self)?;
+                Ok(())
+            }
+            FieldType::Long | FieldType::Autoincrement => {
+                write_dbase7_long(dst, Some(*self))?;
+                Ok(())
+            }
+            _ => Err(ErrorKind::IncompatibleType),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl WritableAsDbaseField for Option<i32> {
+    fn write_as<W: Write>(&self, field_type: FieldType, dst: &mut W) -> Result<(), ErrorKind> {
+        if field_type == FieldType::Long {
+            write_dbase7_long(dst, *self)?;
             Ok(())
         } else {
             Err(ErrorKind::IncompatibleType)
@@ -760,10 +1821,28 @@ impl WritableAsDbaseField for i32 {
     }
 }
 
+#[cfg(feature = "std")]
 impl WritableAsDbaseField for DateTime {
     fn write_as<W: Write>(&self, field_type: FieldType, dst: &mut W) -> Result<(), ErrorKind> {
-        if field_type == FieldType::DateTime {
-            self.write_to(dst)?;
+        match field_type {
+            FieldType::DateTime => {
+                self.write_to(dst)?;
+                Ok(())
+            }
+            FieldType::Timestamp => {
+                write_dbase7_double(dst, Some(self.to_dbase7_timestamp()))?;
+                Ok(())
+            }
+            _ => Err(ErrorKind::IncompatibleType),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl WritableAsDbaseField for Option<DateTime> {
+    fn write_as<W: Write>(&self, field_type: FieldType, dst: &mut W) -> Result<(), ErrorKind> {
+        if field_type == FieldType::Timestamp {
+            write_dbase7_double(dst, self.map(DateTime::to_dbase7_timestamp))?;
             Ok(())
         } else {
             Err(ErrorKind::IncompatibleType)
@@ -776,7 +1855,6 @@ mod de {
     use super::*;
     use serde::de::{Deserialize, Visitor};
     use serde::Deserializer;
-    use std::io::Cursor;
 
     impl<'de> Deserialize<'de> for Date {
         fn deserialize<D>(deserializer: D) -> Result<Self, <D as Deserializer<'de>>::Error>
@@ -816,8 +1894,7 @@ mod de {
         where
             E: serde::de::Error,
         {
-            let mut cursor = Cursor::new(v);
-            match DateTime::read_from(&mut cursor) {
+            match DateTime::read_from(&mut v.as_slice()) {
                 Ok(d) => Ok(d),
                 Err(e) => Err(E::custom(e)),
             }
@@ -869,28 +1946,147 @@ mod ser {
     }
 }
 
-fn read_string_of_len<T: Read>(source: &mut T, len: u8) -> Result<String, std::io::Error> {
-    let mut bytes = Vec::<u8>::new();
-    bytes.resize(len as usize, 0u8);
-    source.read_exact(&mut bytes)?;
-    Ok(String::from_utf8_lossy(&bytes).into_owned())
-}
-
-#[cfg(test)]
+#[cfg(all(test, feature = "std"))]
 mod test {
     use super::*;
 
-    use record::FieldFlags;
+    use crate::record::FieldFlags;
     use std::io::Cursor;
 
+    #[test]
+    fn dbase_display() {
+        let mut field_info = create_temp_field_info(FieldType::Numeric, 8);
+        field_info.num_decimal_places = 3;
+        let value = FieldValue::Numeric(Some(-2.5));
+        assert_eq!(value.to_string(), "-2.5");
+        assert_eq!(value.display_as(&field_info).to_string(), "  -2.500");
+        assert_eq!(
+            FieldValue::Numeric(None)
+                .display_as(&field_info)
+                .to_string(),
+            "        "
+        );
+        assert_eq!(FieldValue::Logical(Some(false)).to_string(), "F");
+        assert_eq!(FieldValue::Logical(None).to_string(), "");
+        assert_eq!(FieldValue::Currency(12.5).to_string(), "12.5000");
+        assert_eq!(format!("{:>5}", FieldValue::Integer(42)), "   42");
+        assert_eq!(
+            FieldValue::DateTime(DateTime::new(Date::new(2, 1, 2003), Time::new(4, 5, 6)))
+                .to_string(),
+            "20030102040506"
+        );
+    }
+
+    #[test]
+    fn dbt_type_from_the_memo_file() {
+        // dBase IV layout, with an unset version byte, read for a dBase III table
+        let mut dbase4 = vec![2, 0, 0, 0];
+        dbase4.resize(512, 0);
+        dbase4.extend_from_slice(&[0xFF, 0xFF, 0x08, 0x00, 7, 0, 0, 0]);
+        dbase4.extend_from_slice(b"Ferris\x1F");
+        dbase4.resize(1024, 0);
+        let mut memo_reader =
+            MemoReader::new(MemoFileType::DbaseMemo, Cursor::new(dbase4)).unwrap();
+        assert_eq!(memo_reader.memo_file_type, MemoFileType::DbaseMemo4);
+        assert_eq!(memo_reader.read_data_at(1).unwrap(), b"Ferris");
+
+        // dBase III layout, with its version byte, read for a dBase IV table
+        let mut dbase3 = vec![2, 0, 0, 0];
+        dbase3.resize(16, 0);
+        dbase3.push(0x03);
+        dbase3.resize(512, 0);
+        dbase3.extend_from_slice(b"Ferris\x1A\x1A");
+        dbase3.resize(1024, 0);
+        let mut memo_reader =
+            MemoReader::new(MemoFileType::DbaseMemo4, Cursor::new(dbase3)).unwrap();
+        assert_eq!(memo_reader.memo_file_type, MemoFileType::DbaseMemo);
+        assert_eq!(memo_reader.read_data_at(1).unwrap(), b"Ferris");
+
+        // Nothing tells the layout of an empty memo file
+        let mut empty = vec![1, 0, 0, 0];
+        empty.resize(512, 0);
+        let memo_reader = MemoReader::new(MemoFileType::DbaseMemo4, Cursor::new(empty)).unwrap();
+        assert_eq!(memo_reader.memo_file_type, MemoFileType::DbaseMemo4);
+    }
+
+    #[test]
+    fn values_as_keys() {
+        let mut map = std::collections::HashMap::new();
+        map.insert(FieldValue::Numeric(Some(0.0)), 1);
+        map.insert(FieldValue::Double(0.0), 2);
+        map.insert(FieldValue::Character(None), 3);
+        assert_eq!(map.get(&FieldValue::Numeric(Some(-0.0))), Some(&1));
+        assert_eq!(map.get(&FieldValue::Double(-0.0)), Some(&2));
+        assert_eq!(map.get(&FieldValue::Character(None)), Some(&3));
+        assert_eq!(map.get(&FieldValue::Numeric(None)), None);
+
+        map.insert(FieldValue::Double(f64::NAN), 4);
+        assert_eq!(map.get(&FieldValue::Double(-f64::NAN)), Some(&4));
+        assert_eq!(map.get(&FieldValue::Currency(f64::NAN)), None);
+    }
+
+    #[test]
+    fn equality() {
+        assert_eq!(FieldValue::Numeric(Some(0.0)), FieldValue::Numeric(Some(-0.0)));
+        assert_eq!(FieldValue::Float(Some(f32::NAN)), FieldValue::Float(Some(f32::NAN)));
+        assert_eq!(
+            FieldValue::Double(f64::NAN).partial_cmp(&FieldValue::Double(f64::NAN)),
+            Some(Ordering::Equal)
+        );
+        assert_ne!(FieldValue::Numeric(Some(f64::NAN)), FieldValue::Numeric(None));
+        assert_ne!(FieldValue::Numeric(Some(1.0)), FieldValue::NumericInteger(Some(1)));
+        assert_ne!(
+            FieldValue::Character(None),
+            FieldValue::Character(Some(String::new()))
+        );
+        assert_eq!(
+            FieldValue::Date(Some(Date::new(30, 2, 2000))),
+            FieldValue::Date(Some(Date::new(30, 2, 2000)))
+        );
+    }
+
+    #[test]
+    fn ordering() {
+        assert!(FieldValue::Numeric(None) < FieldValue::Numeric(Some(-1.0)));
+        assert!(
+            FieldValue::Date(Some(Date::new(31, 12, 1999)))
+                < FieldValue::Date(Some(Date::new(1, 1, 2000)))
+        );
+        assert_eq!(
+            FieldValue::Integer(1).partial_cmp(&FieldValue::Numeric(Some(1.0))),
+            None
+        );
+        assert_eq!(
+            FieldValue::Double(f64::NAN).partial_cmp(&FieldValue::Double(1.0)),
+            None
+        );
+
+        assert_eq!(
+            FieldValue::Integer(1).total_cmp(&FieldValue::Numeric(Some(1.0))),
+            Ordering::Greater
+        );
+        assert_eq!(
+            FieldValue::Double(f64::NAN).total_cmp(&FieldValue::Double(1.0)),
+            Ordering::Greater
+        );
+        assert_eq!(
+            FieldValue::Float(None).total_cmp(&FieldValue::Float(None)),
+            Ordering::Equal
+        );
+        assert_eq!(
+            FieldValue::Memo("b".to_owned()).total_cmp(&FieldValue::Memo("a".to_owned())),
+            Ordering::Greater
+        );
+    }
+
     fn create_temp_field_info(field_type: FieldType, len: u8) -> FieldInfo {
         FieldInfo {
             name: "".to_owned(),
             field_type,
             displacement_field: [0u8; 4],
-            field_length: len,
+            field_length: u16::from(len),
             num_decimal_places: 0,
-            flags: FieldFlags { 0: 0u8 },
+            flags: FieldFlags(0u8),
             autoincrement_next_val: [0u8; 5],
             autoincrement_step: 0u8,
         }
@@ -902,7 +2098,13 @@ mod test {
 
         out.set_position(0);
 
-        let read_value = FieldValue::read_from(&mut out, &mut None, field_info).unwrap();
+        let read_value = FieldValue::read_from(
+            &mut out,
+            &mut None::<MemoReader<Cursor<Vec<u8>>>>,
+            field_info,
+            &ReadingOptions::default(),
+        )
+        .unwrap();
         assert_eq!(value, &read_value);
     }
 
@@ -910,8 +2112,8 @@ mod test {
     fn write_read_date() {
         let date = FieldValue::from(Date {
             year: 2019,
-            month: 01,
-            day: 01,
+            month: 1,
+            day: 1,
         });
 
         let field_info = create_temp_field_info(FieldType::Date, FieldType::Date.size().unwrap());
@@ -944,11 +2146,18 @@ mod test {
         let record_info = create_temp_field_info(FieldType::Character, out.position() as u8);
         out.set_position(0);
 
-        match FieldValue::read_from(&mut out, &mut None, &record_info).unwrap() {
+        match FieldValue::read_from(
+            &mut out,
+            &mut None::<MemoReader<Cursor<Vec<u8>>>>,
+            &record_info,
+            &ReadingOptions::default(),
+        )
+        .unwrap()
+        {
             FieldValue::Character(s) => {
                 assert_eq!(s, Some(String::from("🤔")));
             }
-            _ => assert!(false, "Did not read a Character field ??"),
+            _ => panic!("Did not read a Character field ??"),
         }
     }
 
@@ -980,7 +2189,7 @@ mod test {
     fn test_from_julian_day_number() {
         let date = Date::julian_day_number_to_gregorian_date(2458685);
         assert_eq!(date.year, 2019);
-        assert_eq!(date.month, 07);
+        assert_eq!(date.month, 7);
         assert_eq!(date.day, 20);
     }
 
@@ -988,9 +2197,24 @@ mod test {
     fn test_to_julian_day_number() {
         let date = Date {
             year: 2019,
-            month: 07,
+            month: 7,
             day: 20,
         };
         assert_eq!(date.to_julian_day_number(), 2458685);
     }
+
+    #[test]
+    fn test_unix_epoch_conversions() {
+        let date = Date::new(20, 7, 2019);
+        assert_eq!(Date::from_days_since_unix_epoch(0), Date::new(1, 1, 1970));
+        assert_eq!(date.days_since_unix_epoch(), 18097);
+        assert_eq!(Date::from_days_since_unix_epoch(18097), date);
+
+        let date_time = DateTime::new(Date::new(31, 12, 1969), Time::new(23, 59, 59));
+        assert_eq!(date_time.milliseconds_since_unix_epoch(), -1000);
+        assert_eq!(
+            DateTime::from_milliseconds_since_unix_epoch(-1000),
+            date_time
+        );
+    }
 }
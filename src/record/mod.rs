@@ -1,43 +1,331 @@
-use std::convert::TryFrom;
-use std::io::{Read, Write};
-
-use byteorder::{ReadBytesExt, WriteBytesExt};
-
+use alloc::borrow::ToOwned;
+#[cfg(feature = "std")]
+use alloc::borrow::Cow;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+use core::fmt;
+#[cfg(feature = "std")]
+use std::io::Write;
+
+#[cfg(feature = "std")]
+use byteorder::WriteBytesExt;
+
+#[cfg(feature = "std")]
+mod cast;
+#[cfg(feature = "std")]
+mod compact_string;
+mod fast_parse;
 pub mod field;
 
+#[cfg(feature = "std")]
+pub use self::compact_string::CompactString;
+
 use self::field::{Date, DateTime, FieldType};
-use crate::{ErrorKind, FieldValue};
+use crate::encoding::Encoding;
+use crate::error::ErrorKind;
+use crate::raw::{read_array, ByteSource, KindSource};
+use crate::FieldValue;
 
 const DELETION_FLAG_NAME: &str = "DeletionFlag";
 const FIELD_NAME_LENGTH: usize = 11;
 
-#[derive(Debug)]
 /// Wrapping struct to create a FieldName from a String.
 ///
-/// FieldNames in the dBase format cannot exceed 11 bytes (not char).
+/// FieldNames in the dBase format are stored in 11 bytes (not char),
+/// the last one being the terminator.
+///
+/// [new](#method.new) follows the rules of the format: the name has at most
+/// [MAX_LENGTH](#associatedconstant.MAX_LENGTH) characters (the descriptor keeps one byte
+/// for the terminator), starts with a letter and only has letters,
+/// digits and underscores. Names are uppercase by convention,
+/// [uppercase](#method.uppercase) creates names that follow it.
+///
+/// The bytes of a name depend on the [encoding](struct.TableWriterBuilder.html#method.encoding)
+/// of the table: letters that are not ASCII (`AÑO`) take one byte in the code pages
+/// of dBase but more in UTF-8, the writer checks the length of the names once encoded.
+///
+/// `TryFrom` has the same limit of [MAX_LENGTH](#associatedconstant.MAX_LENGTH) characters,
+/// so the names it accepts keep the terminator in their descriptor, but it accepts
+/// any character: the tables of other applications have names with spaces
+/// or other characters, and they are kept as is when their tables are rewritten.
 ///
 /// # Examples
 ///
 /// ```
-/// use dbase::FieldName;
+/// use dbase::{FieldName, FieldNameError};
 /// use std::convert::TryFrom;
 ///
 /// let name = FieldName::try_from("Small Name");
-/// assert!(name.is_ok())
+/// assert!(name.is_ok());
+///
+/// assert_eq!(
+///     FieldName::new("Small Name"),
+///     Err(FieldNameError::InvalidCharacters("Small Name".to_owned()))
+/// );
+/// assert_eq!(FieldName::uppercase("first_name").unwrap().as_str(), "FIRST_NAME");
+/// assert!(FieldName::new("AÑO").is_ok());
 /// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct FieldName(String);
 
+impl FieldName {
+    /// Maximum number of characters of the names that follow the rules of the format
+    pub const MAX_LENGTH: usize = FIELD_NAME_LENGTH - 1;
+
+    /// Creates a name, fails if it does not follow the rules of the format
+    pub fn new(name: &str) -> Result<Self, FieldNameError> {
+        if name.is_empty() {
+            Err(FieldNameError::Empty)
+        } else if name.chars().count() > Self::MAX_LENGTH {
+            Err(FieldNameError::TooLong {
+                name: name.to_owned(),
+                max_length: Self::MAX_LENGTH,
+            })
+        } else if !is_valid_field_name(name) {
+            Err(FieldNameError::InvalidCharacters(name.to_owned()))
+        } else {
+            Ok(Self(name.to_owned()))
+        }
+    }
+
+    /// Creates a name from the uppercase version of the given one,
+    /// fails if it does not follow the rules of the format
+    pub fn uppercase(name: &str) -> Result<Self, FieldNameError> {
+        Self::new(&name.to_uppercase())
+    }
+
+    /// Returns the name as a string slice
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
 impl TryFrom<&str> for FieldName {
-    type Error = &'static str;
+    type Error = FieldNameError;
 
     fn try_from(name: &str) -> Result<Self, Self::Error> {
-        if name.as_bytes().len() > FIELD_NAME_LENGTH {
-            Err("FieldName byte representation cannot exceed 11 bytes")
-        } else {
-            Ok(Self {
-                0: name.to_string(),
+        if name.is_empty() {
+            Err(FieldNameError::Empty)
+        } else if name.chars().count() > Self::MAX_LENGTH {
+            Err(FieldNameError::TooLong {
+                name: name.to_owned(),
+                max_length: Self::MAX_LENGTH,
             })
+        } else {
+            Ok(Self(name.to_string()))
+        }
+    }
+}
+
+impl TryFrom<String> for FieldName {
+    type Error = FieldNameError;
+
+    fn try_from(name: String) -> Result<Self, Self::Error> {
+        Self::try_from(name.as_str())
+    }
+}
+
+impl AsRef<str> for FieldName {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for FieldName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Reasons a [FieldName](struct.FieldName.html) cannot be created
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FieldNameError {
+    /// The name is empty
+    Empty,
+    /// The name has more characters than the maximum length
+    TooLong { name: String, max_length: usize },
+    /// The name does not start with a letter or has characters other than
+    /// letters, digits and underscores
+    InvalidCharacters(String),
+}
+
+impl fmt::Display for FieldNameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FieldNameError::Empty => write!(f, "the name of a field cannot be empty"),
+            FieldNameError::TooLong { name, max_length } => write!(
+                f,
+                "the name '{}' is longer than {} characters",
+                name, max_length
+            ),
+            FieldNameError::InvalidCharacters(name) => write!(
+                f,
+                "the name '{}' must start with a letter and only have ASCII letters, digits and underscores",
+                name
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for FieldNameError {}
+
+/// Problem with the name of a field read from a file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FieldNameIssue {
+    /// The name of the field at the index is empty
+    Empty { index: usize },
+    /// The name does not start with a letter or has characters other than
+    /// letters, digits and underscores
+    ///
+    /// Letters are not only the ASCII ones, as the names are decoded
+    /// with the encoding of the reader (`AÑO`, `STRAßE`).
+    InvalidCharacters { index: usize, name: String },
+    /// A field before has the same name (names are compared without case)
+    Duplicate { index: usize, name: String },
+}
+
+impl FieldNameIssue {
+    /// Returns the index of the field, in the order of the fields of the table
+    pub fn index(&self) -> usize {
+        match self {
+            FieldNameIssue::Empty { index }
+            | FieldNameIssue::InvalidCharacters { index, .. }
+            | FieldNameIssue::Duplicate { index, .. } => *index,
+        }
+    }
+}
+
+impl fmt::Display for FieldNameIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FieldNameIssue::Empty { index } => write!(f, "the field {} has no name", index),
+            FieldNameIssue::InvalidCharacters { index, name } => write!(
+                f,
+                "the name '{}' of the field {} has invalid characters",
+                name, index
+            ),
+            FieldNameIssue::Duplicate { index, name } => {
+                write!(
+                    f,
+                    "the name '{}' of the field {} is duplicated",
+                    name, index
+                )
+            }
+        }
+    }
+}
+
+/// Returns whether the name follows the rules of the format: it starts with a letter
+/// and only has letters, digits and underscores
+pub(crate) fn is_valid_field_name(name: &str) -> bool {
+    name.starts_with(char::is_alphabetic)
+        && name.chars().all(|c| c.is_alphanumeric() || c == '_')
+}
+
+/// Returns whether the names are the same, without case
+///
+/// The names may not be ASCII, their case is then compared with the Unicode rules.
+pub(crate) fn field_names_match(name: &str, other: &str) -> bool {
+    name.eq_ignore_ascii_case(other)
+        || (!name.is_ascii() && name.to_lowercase() == other.to_lowercase())
+}
+
+/// Decodes the name of a field without the NULs around it,
+/// names the encoding cannot decode are decoded as UTF-8
+fn decode_field_name(bytes: &[u8], encoding: &dyn Encoding) -> String {
+    let start = bytes.iter().position(|b| *b != 0).unwrap_or(bytes.len());
+    let end = bytes
+        .iter()
+        .rposition(|b| *b != 0)
+        .map_or(start, |end| end + 1);
+    let bytes = &bytes[start..end];
+    encoding
+        .decode(bytes)
+        .unwrap_or_else(|_| String::from_utf8_lossy(bytes))
+        .into_owned()
+}
+
+/// Encodes the name of a field, fails if the encoding does not have
+/// all its characters or if it is longer than `max_length` once encoded
+#[cfg(feature = "std")]
+fn encode_field_name<'a>(
+    name: &'a str,
+    encoding: &dyn Encoding,
+    max_length: usize,
+) -> std::io::Result<Cow<'a, [u8]>> {
+    let invalid_name = |reason| {
+        std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("the name of the field '{}' {}", name, reason),
+        )
+    };
+    let bytes = encoding
+        .encode(name)
+        .ok_or_else(|| invalid_name("cannot be encoded"))?;
+    if bytes.len() > max_length {
+        return Err(invalid_name("is too long once encoded"));
+    }
+    Ok(bytes)
+}
+
+/// Returns the problems with the names of the fields,
+/// the deletion flag is not a field and is ignored
+pub(crate) fn field_name_issues(fields_info: &[FieldInfo]) -> Vec<FieldNameIssue> {
+    let fields_info = fields_info
+        .iter()
+        .filter(|field_info| !field_info.is_deletion_flag())
+        .collect::<Vec<&FieldInfo>>();
+    let mut issues = vec![];
+    for (index, field_info) in fields_info.iter().enumerate() {
+        let name = field_info.name();
+        if name.is_empty() {
+            issues.push(FieldNameIssue::Empty { index });
+            continue;
+        }
+        let has_valid_characters = name.starts_with(char::is_alphabetic)
+            && name.chars().all(|c| c.is_alphanumeric() || c == '_');
+        if !has_valid_characters {
+            issues.push(FieldNameIssue::InvalidCharacters {
+                index,
+                name: name.to_owned(),
+            });
+        }
+        if fields_info[..index]
+            .iter()
+            .any(|other| field_names_match(other.name(), name))
+        {
+            issues.push(FieldNameIssue::Duplicate {
+                index,
+                name: name.to_owned(),
+            });
+        }
+    }
+    issues
+}
+
+/// Renames the fields whose name is the same as the one of a field before
+/// by adding a `_2`, `_3`, ... suffix
+pub(crate) fn rename_duplicated_fields(fields_info: &mut [FieldInfo]) {
+    for index in 1..fields_info.len() {
+        let (before, after) = fields_info.split_at_mut(index);
+        let field_info = &mut after[0];
+        let is_taken = |name: &str| {
+            before
+                .iter()
+                .any(|other| field_names_match(other.name(), name))
+        };
+        if field_info.is_deletion_flag() || !is_taken(&field_info.name) {
+            continue;
         }
+        let new_name = (2..)
+            .map(|n| format!("{}_{}", field_info.name, n))
+            .find(|name| !is_taken(name))
+            .unwrap();
+        field_info.name = new_name;
     }
 }
 
@@ -49,7 +337,7 @@ pub struct FieldInfo {
     /// The field type
     pub(crate) field_type: FieldType,
     pub(crate) displacement_field: [u8; 4],
-    pub(crate) field_length: u8,
+    pub(crate) field_length: u16,
     pub(crate) num_decimal_places: u8,
     pub(crate) flags: FieldFlags,
     pub(crate) autoincrement_next_val: [u8; 5],
@@ -59,6 +347,12 @@ pub struct FieldInfo {
 impl FieldInfo {
     pub(crate) const SIZE: usize = 32;
 
+    /// Size of the field descriptors of dBase 7 tables
+    pub(crate) const DBASE7_SIZE: usize = 48;
+
+    /// Size of the names of the fields in the descriptors of dBase 7 tables
+    const DBASE7_NAME_LENGTH: usize = 32;
+
     pub fn name(&self) -> &str {
         &self.name
     }
@@ -67,16 +361,22 @@ impl FieldInfo {
         self.field_type
     }
 
-    pub fn length(&self) -> u8 {
+    pub fn length(&self) -> u16 {
         self.field_length
     }
 
+    /// Returns the number of decimals of the values of the field
+    pub fn num_decimal_places(&self) -> u8 {
+        self.num_decimal_places
+    }
+
+    #[cfg(feature = "std")]
     pub(crate) fn new(name: FieldName, field_type: FieldType, length: u8) -> Self {
         Self {
             name: name.0,
             field_type,
             displacement_field: [0u8; 4],
-            field_length: length,
+            field_length: u16::from(length),
             num_decimal_places: 0,
             flags: FieldFlags::default(),
             autoincrement_next_val: [0u8; 5],
@@ -84,57 +384,137 @@ impl FieldInfo {
         }
     }
 
-    pub(crate) fn read_from<T: Read>(source: &mut T) -> Result<Self, ErrorKind> {
-        let mut name = [0u8; FIELD_NAME_LENGTH];
-        source.read_exact(&mut name)?;
-        let field_type = source.read_u8()?;
+    /// Reads the 32 bytes long descriptor of a field: the name (11 bytes), the type,
+    /// the displacement of the field (4 bytes), the length, the number of decimal places,
+    /// the flags, the next value of autoincrement fields (5 bytes), their step
+    /// and 7 reserved bytes
+    pub(crate) fn read_from<S: ByteSource + ?Sized>(
+        source: &mut S,
+        encoding: &dyn Encoding,
+    ) -> Result<Self, ErrorKind> {
+        let bytes: [u8; Self::SIZE] = read_array(&mut KindSource(source))?;
+        let field_type = FieldType::try_from(bytes[11] as char)?;
 
         let mut displacement_field = [0u8; 4];
-        source.read_exact(&mut displacement_field)?;
-
-        let record_length = source.read_u8()?;
-        let num_decimal_places = source.read_u8()?;
-
-        let flags = FieldFlags {
-            0: source.read_u8()?,
-        };
-
+        displacement_field.copy_from_slice(&bytes[12..16]);
         let mut autoincrement_next_val = [0u8; 5];
+        autoincrement_next_val.copy_from_slice(&bytes[19..24]);
 
-        source.read_exact(&mut autoincrement_next_val)?;
-        let autoincrement_step = source.read_u8()?;
-
-        let mut _reserved = [0u8; 7];
-        source.read_exact(&mut _reserved)?;
+        Ok(Self {
+            name: decode_field_name(&bytes[..FIELD_NAME_LENGTH], encoding),
+            field_type,
+            displacement_field,
+            field_length: u16::from(bytes[16]),
+            num_decimal_places: bytes[17],
+            flags: FieldFlags(bytes[18]),
+            autoincrement_next_val,
+            autoincrement_step: bytes[24],
+        })
+    }
 
-        let s = String::from_utf8_lossy(&name)
-            .trim_matches(|c| c == '\u{0}')
-            .to_owned();
+    /// Reads the 16 bytes long descriptor of a field of a dBase II table:
+    /// the name (11 bytes), the type, the length, the address
+    /// of the field in memory (2 bytes, unused) and the number of decimal places
+    pub(crate) fn read_dbase2_from<S: ByteSource + ?Sized>(
+        source: &mut S,
+        encoding: &dyn Encoding,
+    ) -> Result<Self, ErrorKind> {
+        let bytes: [u8; 16] = read_array(&mut KindSource(source))?;
+        let name = &bytes[..FIELD_NAME_LENGTH];
+        let field_type = FieldType::try_from(bytes[11] as char)?;
+        let field_length = bytes[12];
+        let num_decimal_places = bytes[15];
+
+        // The name ends with a NUL byte, what follows it is not always zeroed
+        let name_length = name.iter().position(|b| *b == 0).unwrap_or(name.len());
+        Ok(Self {
+            name: decode_field_name(&name[..name_length], encoding),
+            field_type,
+            displacement_field: [0u8; 4],
+            field_length: u16::from(field_length),
+            num_decimal_places,
+            flags: FieldFlags::default(),
+            autoincrement_next_val: [0u8; 5],
+            autoincrement_step: 0u8,
+        })
+    }
 
-        let field_type = FieldType::try_from(field_type as char)?;
+    /// Reads the 48 bytes long descriptor of a field of a dBase 7 table:
+    /// the name (32 bytes), the type, the length, the number of decimal places,
+    /// 2 reserved bytes, the production index flag, 2 reserved bytes,
+    /// the next value of autoincrement fields (4 bytes) and 4 reserved bytes
+    pub(crate) fn read_dbase7_from<S: ByteSource + ?Sized>(
+        source: &mut S,
+        encoding: &dyn Encoding,
+    ) -> Result<Self, ErrorKind> {
+        let bytes: [u8; Self::DBASE7_SIZE] = read_array(&mut KindSource(source))?;
+        let name = &bytes[..Self::DBASE7_NAME_LENGTH];
+        let n = Self::DBASE7_NAME_LENGTH;
+        // The integers of dBase 7 are not encoded like the ones of Visual FoxPro
+        let field_type = match bytes[n] {
+            b'I' => FieldType::Long,
+            field_type => FieldType::try_from(field_type as char)?,
+        };
+        let field_length = bytes[n + 1];
+        let num_decimal_places = bytes[n + 2];
+        let mut autoincrement_next_val = [0u8; 5];
+        autoincrement_next_val[..4].copy_from_slice(&bytes[n + 8..n + 12]);
 
+        let name_length = name.iter().position(|b| *b == 0).unwrap_or(name.len());
         Ok(Self {
-            name: s,
+            name: decode_field_name(&name[..name_length], encoding),
             field_type,
-            displacement_field,
-            field_length: record_length,
+            displacement_field: [0u8; 4],
+            field_length: u16::from(field_length),
             num_decimal_places,
-            flags,
+            flags: FieldFlags::default(),
             autoincrement_next_val,
-            autoincrement_step,
+            autoincrement_step: 0u8,
         })
     }
 
-    pub(crate) fn write_to<T: Write>(&self, dest: &mut T) -> std::io::Result<()> {
-        let num_bytes = self.name.as_bytes().len();
+    #[cfg(feature = "std")]
+    pub(crate) fn write_dbase7_to<T: Write>(
+        &self,
+        dest: &mut T,
+        encoding: &dyn Encoding,
+    ) -> std::io::Result<()> {
+        let mut name_bytes = [0u8; Self::DBASE7_NAME_LENGTH];
+        // The name ends with a NUL byte
+        let name = encode_field_name(&self.name, encoding, Self::DBASE7_NAME_LENGTH - 1)?;
+        name_bytes[..name.len()].copy_from_slice(&name);
+        dest.write_all(&name_bytes)?;
+        dest.write_u8(u8::from(self.field_type))?;
+        dest.write_u8(self.field_length as u8)?;
+        dest.write_u8(self.num_decimal_places)?;
+        dest.write_all(&[0u8; 5])?;
+        dest.write_all(&self.autoincrement_next_val[..4])?;
+        dest.write_all(&[0u8; 4])?;
+        Ok(())
+    }
+
+    #[cfg(feature = "std")]
+    pub(crate) fn write_to<T: Write>(
+        &self,
+        dest: &mut T,
+        encoding: &dyn Encoding,
+    ) -> std::io::Result<()> {
+        let name = encode_field_name(&self.name, encoding, FIELD_NAME_LENGTH)?;
         let mut name_bytes = [0u8; FIELD_NAME_LENGTH];
-        name_bytes[..num_bytes.min(FIELD_NAME_LENGTH)].copy_from_slice(self.name.as_bytes());
+        name_bytes[..name.len()].copy_from_slice(&name);
         dest.write_all(&name_bytes)?;
 
         dest.write_u8(u8::from(self.field_type))?;
         dest.write_all(&self.displacement_field)?;
-        dest.write_u8(self.field_length)?;
-        dest.write_u8(self.num_decimal_places)?;
+        // Like Clipper, the length of long character fields
+        // continues in the byte of the decimal places
+        let [length_low, length_high] = self.field_length.to_le_bytes();
+        dest.write_u8(length_low)?;
+        if self.field_type == FieldType::Character && length_high != 0 {
+            dest.write_u8(length_high)?;
+        } else {
+            dest.write_u8(self.num_decimal_places)?;
+        }
         dest.write_u8(self.flags.0)?;
         dest.write_all(&self.autoincrement_next_val)?;
         dest.write_u8(self.autoincrement_step)?;
@@ -152,19 +532,21 @@ impl FieldInfo {
             displacement_field: [0u8; 4],
             field_length: 1,
             num_decimal_places: 0,
-            flags: FieldFlags { 0: 0u8 },
+            flags: FieldFlags(0u8),
             autoincrement_next_val: [0u8; 5],
             autoincrement_step: 0u8,
         }
     }
 
-    pub(crate) fn is_deletion_flag(&self) -> bool {
+    /// Returns whether the field is the deletion flag that starts each record,
+    /// which is not a field of the table
+    pub fn is_deletion_flag(&self) -> bool {
         self.name == DELETION_FLAG_NAME
     }
 }
 
-impl std::fmt::Display for FieldInfo {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl fmt::Display for FieldInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
             f,
             "FieldInfo {{ Name: {}, Field Type: {} }}",
@@ -174,15 +556,9 @@ impl std::fmt::Display for FieldInfo {
 }
 
 /// Flags describing a field
-#[derive(Debug, Copy, Clone, PartialEq)]
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
 pub(crate) struct FieldFlags(u8);
 
-impl Default for FieldFlags {
-    fn default() -> Self {
-        Self { 0: 0 }
-    }
-}
-
 /// Errors that can happen when trying to convert a FieldValue into
 /// a more concrete type
 #[derive(Debug)]
@@ -199,6 +575,16 @@ pub enum FieldConversionError {
     /// The value written is the file was only pad bytes / uninitialized
     /// and the user tried to convert it into a non Option-Type
     NoneValue,
+    /// Happens when a value of the type cannot be cast to the other type,
+    /// see [FieldValue::cast_to](enum.FieldValue.html#method.cast_to)
+    InvalidCast {
+        /// The FieldType of the value
+        from: FieldType,
+        /// The FieldType the value was cast to
+        to: FieldType,
+    },
+    /// Happens when the value, or its text, is not a value of the type it was cast to
+    InvalidValue(String),
 }
 
 macro_rules! impl_try_from_field_value_for_ {
@@ -250,7 +636,54 @@ impl_try_from_field_value_for_!(FieldValue::Character(Some(string)) => String);
 impl_try_from_field_value_for_!(FieldValue::Logical => Option<bool>);
 impl_try_from_field_value_for_!(FieldValue::Logical(Some(b)) => bool);
 
-impl_try_from_field_value_for_!(FieldValue::Integer => i32);
+impl TryFrom<FieldValue> for i32 {
+    type Error = FieldConversionError;
+
+    fn try_from(value: FieldValue) -> Result<Self, Self::Error> {
+        match value {
+            FieldValue::Integer(v) | FieldValue::Long(Some(v)) | FieldValue::Autoincrement(v) => {
+                Ok(v)
+            }
+            FieldValue::Long(None) => Err(FieldConversionError::NoneValue),
+            _ => Err(FieldConversionError::FieldTypeNotAsExpected {
+                expected: FieldType::Integer,
+                actual: value.field_type(),
+            }),
+        }
+    }
+}
+
+impl_try_from_field_value_for_!(FieldValue::Long => Option<i32>);
+
+impl TryFrom<FieldValue> for Option<i64> {
+    type Error = FieldConversionError;
+
+    fn try_from(value: FieldValue) -> Result<Self, Self::Error> {
+        match value {
+            FieldValue::NumericInteger(v) => Ok(v),
+            _ => Err(FieldConversionError::FieldTypeNotAsExpected {
+                expected: FieldType::Numeric,
+                actual: value.field_type(),
+            }),
+        }
+    }
+}
+
+impl TryFrom<FieldValue> for i64 {
+    type Error = FieldConversionError;
+
+    fn try_from(value: FieldValue) -> Result<Self, Self::Error> {
+        match value {
+            FieldValue::NumericInteger(Some(v)) => Ok(v),
+            FieldValue::NumericInteger(None) => Err(FieldConversionError::NoneValue),
+            FieldValue::Integer(v) | FieldValue::Long(Some(v)) | FieldValue::Autoincrement(v) => {
+                Ok(i64::from(v))
+            }
+            FieldValue::Long(None) => Err(FieldConversionError::NoneValue),
+            _ => Err(FieldConversionError::IncompatibleType),
+        }
+    }
+}
 
 impl TryFrom<FieldValue> for f64 {
     type Error = FieldConversionError;
@@ -258,9 +691,12 @@ impl TryFrom<FieldValue> for f64 {
     fn try_from(value: FieldValue) -> Result<Self, Self::Error> {
         match value {
             FieldValue::Numeric(Some(v)) => Ok(v),
-            FieldValue::Numeric(None) => Err(FieldConversionError::NoneValue.into()),
+            FieldValue::Numeric(None) => Err(FieldConversionError::NoneValue),
+            FieldValue::NumericInteger(Some(v)) => Ok(v as f64),
+            FieldValue::NumericInteger(None) => Err(FieldConversionError::NoneValue),
             FieldValue::Currency(c) => Ok(c),
-            FieldValue::Double(d) => Ok(d),
+            FieldValue::Double(d) | FieldValue::DBase7Double(Some(d)) => Ok(d),
+            FieldValue::DBase7Double(None) => Err(FieldConversionError::NoneValue),
             _ => Err(FieldConversionError::IncompatibleType),
         }
     }
@@ -269,6 +705,9 @@ impl TryFrom<FieldValue> for f64 {
 // Fox Pro types
 impl_try_from_field_value_for_!(FieldValue::DateTime => DateTime);
 
+// dBase 7 types
+impl_try_from_field_value_for_!(FieldValue::Timestamp => Option<DateTime>);
+
 macro_rules! impl_from_type_for_field_value (
     ($t:ty => FieldValue::$variant:ident) => {
         impl From<$t> for FieldValue {
@@ -292,6 +731,9 @@ impl_from_type_for_field_value!(String => FieldValue::Character(Some(s)));
 impl_from_type_for_field_value!(Option<f64> => FieldValue::Numeric);
 impl_from_type_for_field_value!(f64 => FieldValue::Numeric(Some(v)));
 
+impl_from_type_for_field_value!(Option<i64> => FieldValue::NumericInteger);
+impl_from_type_for_field_value!(i64 => FieldValue::NumericInteger(Some(v)));
+
 impl_from_type_for_field_value!(Option<f32> => FieldValue::Float);
 impl_from_type_for_field_value!(f32 => FieldValue::Float(Some(v)));
 
@@ -304,7 +746,33 @@ impl_from_type_for_field_value!(Date => FieldValue::Date(Some(v)));
 // Fox Pro types
 impl_from_type_for_field_value!(DateTime => FieldValue::DateTime);
 
-#[cfg(test)]
+impl From<&str> for FieldValue {
+    fn from(v: &str) -> Self {
+        FieldValue::Character(Some(v.to_owned()))
+    }
+}
+
+impl From<Option<&str>> for FieldValue {
+    fn from(v: Option<&str>) -> Self {
+        FieldValue::Character(v.map(str::to_owned))
+    }
+}
+
+/// Integers are values of Numeric fields, like `i64`s,
+/// the Integer fields of Visual FoxPro take `FieldValue::Integer`s
+impl From<i32> for FieldValue {
+    fn from(v: i32) -> Self {
+        FieldValue::NumericInteger(Some(i64::from(v)))
+    }
+}
+
+impl From<Option<i32>> for FieldValue {
+    fn from(v: Option<i32>) -> Self {
+        FieldValue::NumericInteger(v.map(i64::from))
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
 mod test {
     use super::*;
     use std::io::Cursor;
@@ -317,12 +785,119 @@ mod test {
             30,
         );
         let mut cursor = Cursor::new(Vec::<u8>::with_capacity(FieldInfo::SIZE));
-        field_info.write_to(&mut cursor).unwrap();
+        field_info
+            .write_to(&mut cursor, &crate::UnicodeLossy)
+            .unwrap();
 
         cursor.set_position(0);
 
-        let read_field_info = FieldInfo::read_from(&mut cursor).unwrap();
+        let read_field_info =
+            FieldInfo::read_from(&mut &cursor.get_ref()[..], &crate::UnicodeLossy).unwrap();
 
         assert_eq!(read_field_info, field_info);
     }
+
+    #[test]
+    fn invalid_and_duplicated_names() {
+        let mut fields_info = ["CODE", "code", "1ST", "", "CODE_2"]
+            .iter()
+            .map(|name| {
+                let mut field_info =
+                    FieldInfo::new(FieldName::try_from("X").unwrap(), FieldType::Logical, 1);
+                field_info.name = name.to_string();
+                field_info
+            })
+            .collect::<Vec<FieldInfo>>();
+        fields_info.insert(0, FieldInfo::new_deletion_flag());
+        assert_eq!(
+            field_name_issues(&fields_info),
+            vec![
+                FieldNameIssue::Duplicate {
+                    index: 1,
+                    name: "code".to_owned()
+                },
+                FieldNameIssue::InvalidCharacters {
+                    index: 2,
+                    name: "1ST".to_owned()
+                },
+                FieldNameIssue::Empty { index: 3 },
+            ]
+        );
+
+        rename_duplicated_fields(&mut fields_info);
+        let names = fields_info
+            .iter()
+            .skip(1)
+            .map(FieldInfo::name)
+            .collect::<Vec<&str>>();
+        assert_eq!(names, vec!["CODE", "code_2", "1ST", "", "CODE_2_2"]);
+    }
+
+    #[test]
+    fn field_name_rules() {
+        assert_eq!(FieldName::new("CUSTOMER_N").unwrap().as_str(), "CUSTOMER_N");
+        assert_eq!(FieldName::new("price").unwrap().to_string(), "price");
+        assert_eq!(FieldName::new(""), Err(FieldNameError::Empty));
+        assert_eq!(
+            FieldName::new("CUSTOMER_ID"),
+            Err(FieldNameError::TooLong {
+                name: "CUSTOMER_ID".to_owned(),
+                max_length: 10
+            })
+        );
+        assert_eq!(
+            FieldName::new("_ID"),
+            Err(FieldNameError::InvalidCharacters("_ID".to_owned()))
+        );
+        assert_eq!(
+            FieldName::uppercase("prix_été").unwrap().as_str(),
+            "PRIX_ÉTÉ"
+        );
+        // The length is the one of the names in the single byte code pages
+        assert!(FieldName::new("STRAßENAME").is_ok());
+        assert_eq!(
+            FieldName::new("PRIX-ÉTÉ"),
+            Err(FieldNameError::InvalidCharacters("PRIX-ÉTÉ".to_owned()))
+        );
+
+        // TryFrom has the same limit, but accepts any character
+        assert!(FieldName::try_from("CUSTOMER_N").is_ok());
+        assert!(FieldName::try_from("Nick Name").is_ok());
+        assert_eq!(
+            FieldName::try_from("CUSTOMER_ID"),
+            Err(FieldNameError::TooLong {
+                name: "CUSTOMER_ID".to_owned(),
+                max_length: 10
+            })
+        );
+        assert_eq!(FieldName::try_from(""), Err(FieldNameError::Empty));
+    }
+
+    #[test]
+    fn non_ascii_names() {
+        let mut fields_info = ["AÑO", "año", "STRAßE", "1ÈRE"]
+            .iter()
+            .map(|name| {
+                let mut field_info =
+                    FieldInfo::new(FieldName::try_from("X").unwrap(), FieldType::Logical, 1);
+                field_info.name = name.to_string();
+                field_info
+            })
+            .collect::<Vec<FieldInfo>>();
+        assert_eq!(
+            field_name_issues(&fields_info),
+            vec![
+                FieldNameIssue::Duplicate {
+                    index: 1,
+                    name: "año".to_owned()
+                },
+                FieldNameIssue::InvalidCharacters {
+                    index: 3,
+                    name: "1ÈRE".to_owned()
+                },
+            ]
+        );
+        rename_duplicated_fields(&mut fields_info);
+        assert_eq!(fields_info[1].name(), "año_2");
+    }
 }
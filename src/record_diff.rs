@@ -0,0 +1,399 @@
+//! Module with the definition of fn's and struct's to compare
+//! the records of two tables
+//!
+//! Where [diff_schemas](fn.diff_schemas.html) compares the fields of two versions of a table,
+//! the diff made here compares their records, matched by the values of key fields.
+//! Both tables are read once, side by side, so they must be sorted by the key fields
+//! (see [sort_table](fn.sort_table.html)), which also have to identify the records.
+use std::cmp::Ordering;
+use std::fmt;
+use std::fs::File;
+use std::io::{BufReader, Read, Seek};
+use std::path::Path;
+
+use crate::error::{Error, ErrorKind};
+use crate::reading::{Reader, Record, RecordValues};
+use crate::record::field::FieldValue;
+
+/// A value of a record that changed between the two tables
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValueChange {
+    /// The name of the field
+    pub field_name: String,
+    /// The value in the first table
+    pub old: FieldValue,
+    /// The value in the second table
+    pub new: FieldValue,
+}
+
+/// A difference between the records of two tables, made by [RecordDiff]
+#[derive(Debug, Clone, PartialEq)]
+pub enum RecordChange {
+    /// The record is only in the second table
+    Added {
+        key: Vec<FieldValue>,
+        record: Record,
+    },
+    /// The record is only in the first table
+    Removed {
+        key: Vec<FieldValue>,
+        record: Record,
+    },
+    /// The record has other values in the second table,
+    /// only the fields both tables have are compared
+    Changed {
+        key: Vec<FieldValue>,
+        changes: Vec<ValueChange>,
+    },
+}
+
+impl RecordChange {
+    /// Returns the values of the key fields of the record that changed
+    pub fn key(&self) -> &[FieldValue] {
+        match self {
+            RecordChange::Added { key, .. }
+            | RecordChange::Removed { key, .. }
+            | RecordChange::Changed { key, .. } => key,
+        }
+    }
+}
+
+impl fmt::Display for RecordChange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let key = self
+            .key()
+            .iter()
+            .map(|value| value.to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+        match self {
+            RecordChange::Added { .. } => write!(f, "record ({}) added", key),
+            RecordChange::Removed { .. } => write!(f, "record ({}) removed", key),
+            RecordChange::Changed { changes, .. } => {
+                write!(f, "record ({}) changed", key)?;
+                for change in changes {
+                    write!(
+                        f,
+                        "\n  {}: {} -> {}",
+                        change.field_name, change.old, change.new
+                    )?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Compares the keys value by value, with
+/// [FieldValue::total_cmp](enum.FieldValue.html#method.total_cmp)
+fn compare_keys(a: &[FieldValue], b: &[FieldValue]) -> Ordering {
+    a.iter()
+        .zip(b)
+        .map(|(a, b)| a.total_cmp(b))
+        .find(|ordering| *ordering != Ordering::Equal)
+        .unwrap_or(Ordering::Equal)
+}
+
+/// The live records of one of the tables, read one at a time
+struct Side<T: Read + Seek> {
+    reader: Reader<T>,
+    /// Which table it is, for the error messages
+    name: &'static str,
+    names: Vec<String>,
+    key_indices: Vec<usize>,
+    /// The key and values of the record read last, not yet compared
+    head: Option<(Vec<FieldValue>, Vec<FieldValue>)>,
+    last_key: Option<Vec<FieldValue>>,
+}
+
+impl<T: Read + Seek> Side<T> {
+    fn new(reader: Reader<T>, name: &'static str, key_fields: &[&str]) -> Result<Self, Error> {
+        let names = reader
+            .fields()
+            .iter()
+            .filter(|field| !field.is_deletion_flag())
+            .map(|field| field.name().to_owned())
+            .collect::<Vec<_>>();
+        let key_indices = key_fields
+            .iter()
+            .map(|key_field| {
+                names
+                    .iter()
+                    .position(|name| name == key_field)
+                    .ok_or_else(|| Error {
+                        record_num: 0,
+                        field: None,
+                        kind: ErrorKind::Message(format!(
+                            "the {} table has no field named '{}'",
+                            name, key_field
+                        )),
+                    })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut side = Self {
+            reader,
+            name,
+            names,
+            key_indices,
+            head: None,
+            last_key: None,
+        };
+        side.advance()?;
+        Ok(side)
+    }
+
+    /// Reads the next live record, checking it comes after the previous one
+    fn advance(&mut self) -> Result<(), Error> {
+        self.head = None;
+        while let Some(record) = self.reader.read_next_record_as::<RecordValues>() {
+            let record = record?;
+            if record.is_deleted {
+                continue;
+            }
+            let key = self
+                .key_indices
+                .iter()
+                .map(|i| record.values[*i].clone())
+                .collect::<Vec<_>>();
+            if let Some(last_key) = &self.last_key {
+                if compare_keys(last_key, &key) != Ordering::Less {
+                    return Err(Error {
+                        record_num: self.reader.next_record as usize - 1,
+                        field: None,
+                        kind: ErrorKind::Message(format!(
+                            "the records of the {} table are not sorted by the key fields, \
+                             or their keys are not unique",
+                            self.name
+                        )),
+                    });
+                }
+            }
+            self.last_key = Some(key.clone());
+            self.head = Some((key, record.values));
+            break;
+        }
+        Ok(())
+    }
+
+    fn record(&self, values: Vec<FieldValue>) -> Record {
+        Record::from(
+            self.names
+                .iter()
+                .cloned()
+                .zip(values)
+                .collect::<std::collections::HashMap<_, _>>(),
+        )
+    }
+}
+
+/// Iterator over the differences between the records of two tables, made by
+/// [Reader::diff](struct.Reader.html#method.diff) or [diff](fn.diff.html)
+///
+/// The changes come in the order of the keys. The records marked as deleted are ignored.
+/// The iteration ends after the first error, eg: when the records of a table
+/// are not sorted by the key fields.
+pub struct RecordDiff<A: Read + Seek, B: Read + Seek> {
+    first: Side<A>,
+    second: Side<B>,
+    /// For the fields both tables have, their index in the first and the second table
+    common_fields: Vec<(usize, usize)>,
+    has_failed: bool,
+}
+
+impl<A: Read + Seek, B: Read + Seek> RecordDiff<A, B> {
+    fn next_change(&mut self) -> Result<Option<RecordChange>, Error> {
+        loop {
+            let ordering = match (&self.first.head, &self.second.head) {
+                (None, None) => return Ok(None),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (Some((first_key, _)), Some((second_key, _))) => {
+                    compare_keys(first_key, second_key)
+                }
+            };
+            match ordering {
+                Ordering::Less => {
+                    let (key, values) = self.first.head.take().unwrap();
+                    let record = self.first.record(values);
+                    self.first.advance()?;
+                    return Ok(Some(RecordChange::Removed { key, record }));
+                }
+                Ordering::Greater => {
+                    let (key, values) = self.second.head.take().unwrap();
+                    let record = self.second.record(values);
+                    self.second.advance()?;
+                    return Ok(Some(RecordChange::Added { key, record }));
+                }
+                Ordering::Equal => {
+                    let (key, old_values) = self.first.head.take().unwrap();
+                    let (_, new_values) = self.second.head.take().unwrap();
+                    let changes = self
+                        .common_fields
+                        .iter()
+                        .filter(|(i, j)| old_values[*i] != new_values[*j])
+                        .map(|(i, j)| ValueChange {
+                            field_name: self.first.names[*i].clone(),
+                            old: old_values[*i].clone(),
+                            new: new_values[*j].clone(),
+                        })
+                        .collect::<Vec<_>>();
+                    self.first.advance()?;
+                    self.second.advance()?;
+                    if !changes.is_empty() {
+                        return Ok(Some(RecordChange::Changed { key, changes }));
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl<A: Read + Seek, B: Read + Seek> Iterator for RecordDiff<A, B> {
+    type Item = Result<RecordChange, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.has_failed {
+            return None;
+        }
+        let change = self.next_change().transpose();
+        self.has_failed = matches!(change, Some(Err(_)));
+        change
+    }
+}
+
+impl<T: Read + Seek> Reader<T> {
+    /// Compares the remaining records of this table with the ones of the other table,
+    /// matching the records by the values of the key fields
+    ///
+    /// Both tables are read once, at the same time: their records must be sorted by the
+    /// key fields, in ascending order (as [sort_table](fn.sort_table.html) sorts them),
+    /// and no two records of a table may have the same key. Both tables must have
+    /// the key fields.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), dbase::Error> {
+    /// use dbase::Order;
+    ///
+    /// let sorted_path = std::env::temp_dir().join("dbase_record_diff_doc.dbf");
+    /// dbase::sort_table("tests/data/stations.dbf", &sorted_path, &[("name", Order::Ascending)], 10)?;
+    /// let first = dbase::Reader::from_path(&sorted_path)?;
+    /// let second = dbase::Reader::from_path(&sorted_path)?;
+    /// assert_eq!(first.diff(second, &["name"])?.count(), 0);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn diff<B: Read + Seek>(
+        self,
+        other: Reader<B>,
+        key_fields: &[&str],
+    ) -> Result<RecordDiff<T, B>, Error> {
+        let first = Side::new(self, "first", key_fields)?;
+        let second = Side::new(other, "second", key_fields)?;
+        let common_fields = first
+            .names
+            .iter()
+            .enumerate()
+            .filter_map(|(i, name)| {
+                second
+                    .names
+                    .iter()
+                    .position(|other_name| other_name == name)
+                    .map(|j| (i, j))
+            })
+            .collect();
+        Ok(RecordDiff {
+            first,
+            second,
+            common_fields,
+            has_failed: false,
+        })
+    }
+}
+
+/// One liner to compare the records of the tables at the given paths,
+/// see [Reader::diff](struct.Reader.html#method.diff)
+pub fn diff<P: AsRef<Path>, Q: AsRef<Path>>(
+    first: P,
+    second: Q,
+    key_fields: &[&str],
+) -> Result<RecordDiff<BufReader<File>, BufReader<File>>, Error> {
+    Reader::from_path(first)?.diff(Reader::from_path(second)?, key_fields)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{FieldName, TableWriterBuilder};
+    use std::convert::TryFrom;
+    use std::io::Cursor;
+
+    fn table(rows: &[(f64, &str)]) -> Reader<Cursor<Vec<u8>>> {
+        let records = rows
+            .iter()
+            .map(|(id, name)| {
+                let mut record = Record::default();
+                record.insert("id".to_owned(), FieldValue::Numeric(Some(*id)));
+                record.insert(
+                    "name".to_owned(),
+                    FieldValue::Character(Some((*name).to_owned())),
+                );
+                record
+            })
+            .collect::<Vec<_>>();
+        let mut dst = TableWriterBuilder::new()
+            .add_numeric_field(FieldName::try_from("id").unwrap(), 4, 0)
+            .add_character_field(FieldName::try_from("name").unwrap(), 10)
+            .build_with_dest(Cursor::new(Vec::<u8>::new()))
+            .write(&records)
+            .unwrap();
+        dst.set_position(0);
+        Reader::new(dst).unwrap()
+    }
+
+    #[test]
+    fn added_removed_and_changed() {
+        let first = table(&[(1.0, "Ann"), (2.0, "Bob"), (4.0, "Dan")]);
+        let second = table(&[(1.0, "Ann"), (2.0, "Bobby"), (3.0, "Cid")]);
+        let changes = first
+            .diff(second, &["id"])
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(changes.len(), 3);
+        assert_eq!(
+            changes[0],
+            RecordChange::Changed {
+                key: vec![FieldValue::Numeric(Some(2.0))],
+                changes: vec![ValueChange {
+                    field_name: "name".to_owned(),
+                    old: FieldValue::Character(Some("Bob".to_owned())),
+                    new: FieldValue::Character(Some("Bobby".to_owned())),
+                }],
+            }
+        );
+        assert!(
+            matches!(&changes[1], RecordChange::Added { key, .. } if key[0] == FieldValue::Numeric(Some(3.0)))
+        );
+        match &changes[2] {
+            RecordChange::Removed { record, .. } => assert_eq!(
+                record.get("name"),
+                Some(&FieldValue::Character(Some("Dan".to_owned())))
+            ),
+            change => panic!("unexpected change {:?}", change),
+        }
+    }
+
+    #[test]
+    fn unsorted_tables_are_an_error() {
+        let first = table(&[(2.0, "Bob"), (1.0, "Ann")]);
+        let second = table(&[(1.0, "Ann"), (2.0, "Bob")]);
+        let results = first.diff(second, &["id"]).unwrap().collect::<Vec<_>>();
+        assert!(results.last().unwrap().is_err());
+
+        let first = table(&[(1.0, "Ann")]);
+        let second = table(&[(1.0, "Ann")]);
+        assert!(first.diff(second, &["code"]).is_err());
+    }
+}
@@ -0,0 +1,273 @@
+//! Module with the definition of the record borrowed from the buffer of the reader
+//!
+//! Nothing is allocated per record, the values are only parsed when asked for.
+use std::io::{Cursor, Read, Seek};
+
+use crate::error::{Error, ErrorKind, FieldIOError};
+use crate::reading::{FieldIterator, ReadableRecord, Reader, ReadingOptions};
+use crate::record::field::{FieldType, FieldValue, MemoHandle, MemoReader};
+use crate::record::FieldInfo;
+
+/// A record borrowed from the reader, valid until the next record is read
+///
+/// Made by [Reader::next_record_ref](struct.Reader.html#method.next_record_ref).
+pub struct RecordRef<'a, T: Read + Seek> {
+    bytes: &'a [u8],
+    fields_info: &'a [FieldInfo],
+    memo_reader: &'a mut Option<MemoReader<T>>,
+    options: &'a ReadingOptions,
+}
+
+impl<'a, T: Read + Seek> RecordRef<'a, T> {
+    /// Returns whether the record is marked as deleted
+    pub fn is_deleted(&self) -> bool {
+        self.bytes.first() == Some(&b'*')
+    }
+
+    /// Returns the fields of the record, the deletion flag not included
+    pub fn fields(&self) -> &'a [FieldInfo] {
+        &self.fields_info[1..]
+    }
+
+    /// Returns the field with the given name and its bytes in the record
+    fn find(&self, name: &str) -> Option<(&'a FieldInfo, &'a [u8])> {
+        let mut start = 0;
+        for field_info in self.fields_info {
+            let end = start + usize::from(field_info.field_length);
+            if !field_info.is_deletion_flag() && field_info.name() == name {
+                return Some((field_info, &self.bytes[start..end]));
+            }
+            start = end;
+        }
+        None
+    }
+
+    /// Returns the bytes of the field with the given name, as they are in the file
+    ///
+    /// For memo fields, these are the bytes of the index of the memo data.
+    pub fn raw(&self, name: &str) -> Option<&'a [u8]> {
+        self.find(name).map(|(_, bytes)| bytes)
+    }
+
+    /// Returns the handle to the data of the memo field with the given name,
+    /// `None` if the record has no such field
+    ///
+    /// The data is then read with [Reader::memo_reader](struct.Reader.html#method.memo_reader).
+    pub fn memo_handle(&self, name: &str) -> Option<Result<MemoHandle, FieldIOError>> {
+        let (field_info, bytes) = self.find(name)?;
+        let handle = match field_info.field_type {
+            FieldType::Memo => MemoHandle::read_from(&mut &bytes[..], field_info),
+            _ => Err(ErrorKind::IncompatibleType),
+        };
+        Some(handle.map_err(|kind| FieldIOError::new(kind, Some(field_info.clone()))))
+    }
+
+    /// Parses the value of the field with the given name,
+    /// `None` if the record has no such field
+    pub fn get(&mut self, name: &str) -> Option<Result<FieldValue, FieldIOError>> {
+        let (field_info, bytes) = self.find(name)?;
+        Some(
+            FieldValue::read_from(
+                &mut Cursor::new(bytes),
+                self.memo_reader,
+                field_info,
+                self.options,
+            )
+            .map_err(|kind| FieldIOError::new(kind, Some(field_info.clone()))),
+        )
+    }
+
+    /// Returns the text of the Character field with the given name, trimmed of its padding,
+    /// without copying it
+    ///
+    /// Only the text of tables whose encoding is compatible with ASCII can be borrowed,
+    /// an error is returned when the text is not valid UTF-8.
+    /// The text of empty values is empty.
+    pub fn get_str(&self, name: &str) -> Option<Result<&'a str, FieldIOError>> {
+        let (field_info, bytes) = self.find(name)?;
+        if field_info.field_type != FieldType::Character {
+            return Some(Err(FieldIOError::new(
+                ErrorKind::IncompatibleType,
+                Some(field_info.clone()),
+            )));
+        }
+        let text = std::str::from_utf8(bytes)
+            .map(|text| text.trim_matches(|c| c == ' ' || c == '\0'))
+            .map_err(|_| {
+                let error = std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    "the text is not valid UTF-8",
+                );
+                FieldIOError::new(ErrorKind::IoError(error), Some(field_info.clone()))
+            });
+        Some(text)
+    }
+}
+
+impl<'a, T: Read + Seek> RecordRef<'a, T> {
+    /// Parses all the fields of the record as a record of the given type
+    pub(crate) fn read_as<R: ReadableRecord>(&mut self) -> Result<R, FieldIOError> {
+        let mut source = Cursor::new(self.bytes);
+        R::read_using(&mut FieldIterator {
+            source: &mut source,
+            fields_info: self.fields_info.iter().peekable(),
+            memo_reader: self.memo_reader,
+            options: self.options,
+        })
+    }
+}
+
+impl<T: Read + Seek> Reader<T> {
+    /// Reads the next record in the buffer of the reader and returns it without
+    /// allocating: the values are parsed when asked for
+    ///
+    /// Returns `None` once all the records were read, deleted records are not skipped.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), dbase::Error> {
+    /// let mut reader = dbase::Reader::from_path("tests/data/stations.dbf")?;
+    /// let mut num_lines = 0;
+    /// while let Some(record) = reader.next_record_ref() {
+    ///     let record = record?;
+    ///     if let Some(line) = record.get_str("line") {
+    ///         assert!(!line.unwrap().is_empty());
+    ///         num_lines += 1;
+    ///     }
+    /// }
+    /// assert_eq!(num_lines, 6);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn next_record_ref(&mut self) -> Option<Result<RecordRef<'_, T>, Error>> {
+        if self.next_record >= self.end_record() {
+            return None;
+        }
+        if let Err(error) = self.source.read_exact(&mut self.record_buffer) {
+            return Some(Err(Error::io_error(error, self.next_record as usize)));
+        }
+        self.decrypt_record_buffer(self.next_record);
+        self.next_record += 1;
+        Some(Ok(RecordRef {
+            bytes: &self.record_buffer,
+            fields_info: &self.fields_info,
+            memo_reader: &mut self.memo_reader,
+            options: &self.options,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::Record;
+
+    #[test]
+    fn records_borrowed_from_the_reader() {
+        let expected = Reader::from_path("tests/data/stations.dbf")
+            .unwrap()
+            .read()
+            .unwrap();
+        let mut reader = Reader::from_path("tests/data/stations.dbf").unwrap();
+        let mut records = vec![];
+        while let Some(record) = reader.next_record_ref() {
+            let mut record = record.unwrap();
+            assert!(!record.is_deleted());
+            let mut owned = Record::default();
+            let names = record
+                .fields()
+                .iter()
+                .map(|field_info| field_info.name().to_owned())
+                .collect::<Vec<String>>();
+            for name in names {
+                let value = record.get(&name).unwrap().unwrap();
+                owned.insert(name, value);
+            }
+            assert!(record.get("nothing").is_none());
+            assert_eq!(
+                record.get_str("name").unwrap().ok(),
+                expected[records.len()]
+                    .get("name")
+                    .and_then(|value| match value {
+                        FieldValue::Character(name) => name.as_deref(),
+                        _ => None,
+                    })
+            );
+            records.push(owned);
+        }
+        assert_eq!(records, expected);
+    }
+
+    #[test]
+    fn memo_data_streamed_from_the_memo_file() {
+        use crate::{ErrorKind, FieldInfo, Header, ReadingOptions};
+        use std::io::Read;
+
+        // dBase III table with a memo field, the first value spans the blocks 1 and 2
+        let mut table = vec![0x83, 120, 1, 1, 2, 0, 0, 0, 65, 0, 11, 0];
+        table.resize(Header::SIZE, 0);
+        table.extend_from_slice(b"NOTES\0\0\0\0\0\0M");
+        table.extend_from_slice(&[0, 0, 0, 0, 10, 0]);
+        table.resize(Header::SIZE + FieldInfo::SIZE, 0);
+        table.push(0x0D);
+        table.extend_from_slice(b"          1           ");
+        let document = "Ferris the crab ".repeat(40);
+        let mut memo = vec![3, 0, 0, 0];
+        memo.resize(512, 0);
+        memo.extend_from_slice(document.as_bytes());
+        memo.extend_from_slice(b"\x1A\x1A");
+        memo.resize(1536, 0);
+
+        let mut reader = Reader::with_memo(
+            Cursor::new(table.as_slice()),
+            Cursor::new(memo.as_slice()),
+            ReadingOptions::default(),
+        )
+        .unwrap();
+        let mut handles = vec![];
+        while let Some(record) = reader.next_record_ref() {
+            handles.push(record.unwrap().memo_handle("NOTES").unwrap().unwrap());
+        }
+        assert_eq!(handles[0].block_index(), Some(1));
+        assert_eq!(handles[1].block_index(), None);
+
+        let mut data = vec![];
+        let mut stream = reader.memo_reader(&handles[0]).unwrap();
+        let mut chunk = [0u8; 100];
+        loop {
+            let num_read = stream.read(&mut chunk).unwrap();
+            if num_read == 0 {
+                break;
+            }
+            data.extend_from_slice(&chunk[..num_read]);
+        }
+        assert_eq!(data, document.as_bytes());
+
+        let mut data = vec![];
+        reader
+            .memo_reader(&handles[1])
+            .unwrap()
+            .read_to_end(&mut data)
+            .unwrap();
+        assert!(data.is_empty());
+        assert!(reader
+            .memo_reader(&MemoHandle::from_block_index(7))
+            .is_err());
+
+        let mut reader = Reader::from_path("tests/data/line.dbf").unwrap();
+        let record = reader.next_record_ref().unwrap().unwrap();
+        assert!(matches!(
+            record.memo_handle("name").unwrap().unwrap_err().kind(),
+            ErrorKind::IncompatibleType
+        ));
+        assert!(matches!(
+            reader
+                .memo_reader(&MemoHandle::from_block_index(1))
+                .unwrap_err()
+                .kind(),
+            ErrorKind::MissingMemoFile
+        ));
+    }
+}
@@ -0,0 +1,313 @@
+//! Module with the definition of fn's and struct's to repair
+//! the header of dBase files
+//!
+//! Only the corruptions that can be fixed from the content of the file are repaired:
+//! the number of records, the size of the header, the size of the records
+//! and the end of file marker.
+use std::convert::TryFrom;
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::io::{BufReader, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+
+use crate::error::Error;
+use crate::header::Header;
+use crate::reading::{read_table_info, Flavor};
+use crate::validation::{expected_header_size, expected_record_size};
+use crate::writing::FILE_TERMINATOR;
+
+/// Position of the number of records in the header
+const NUM_RECORDS_POSITION: u64 = 4;
+/// Position of the offset to the first record in the header
+const OFFSET_TO_FIRST_RECORD_POSITION: u64 = 8;
+/// Position of the size of the records in the header
+const SIZE_OF_RECORD_POSITION: u64 = 10;
+
+/// What [repair](fn.repair.html) does with the repairs found
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RepairMode {
+    /// Only returns the repairs, the file is not modified
+    DryRun,
+    /// Patches the file
+    InPlace,
+    /// Writes a repaired copy of the file at the given path,
+    /// the file is not modified
+    CopyTo(PathBuf),
+}
+
+/// A repair made (or to be made) by [repair](fn.repair.html)
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Repair {
+    /// The number of records is set to the number of records the file contains
+    NumRecords { from: u32, to: u32 },
+    /// The offset to the first record is set to the size of the fields descriptors
+    HeaderSize { from: u16, to: u16 },
+    /// The size of the records is set to the sum of the lengths of the fields
+    RecordSize { from: u16, to: u16 },
+    /// The end of file marker is written after the last record
+    EndOfFileMarker { position: u64 },
+}
+
+impl fmt::Display for Repair {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Repair::NumRecords { from, to } => {
+                write!(f, "number of records changed from {} to {}", from, to)
+            }
+            Repair::HeaderSize { from, to } => {
+                write!(
+                    f,
+                    "offset to the first record changed from {} to {}",
+                    from, to
+                )
+            }
+            Repair::RecordSize { from, to } => {
+                write!(f, "size of the records changed from {} to {}", from, to)
+            }
+            Repair::EndOfFileMarker { position } => {
+                write!(f, "end of file marker written at {}", position)
+            }
+        }
+    }
+}
+
+/// Returns whether the bytes after the header are a whole number of records,
+/// with or without the end of file marker
+fn records_fit(file_size: u64, offset_to_first_record: u64, size_of_record: u64) -> bool {
+    file_size >= offset_to_first_record
+        && (file_size - offset_to_first_record) % size_of_record <= 1
+}
+
+/// Finds the repairs the file at the given path needs
+fn find_repairs(path: &Path) -> Result<Vec<Repair>, Error> {
+    let io_error = |error: std::io::Error| Error::io_error(error, 0);
+    let mut source = BufReader::new(File::open(path).map_err(io_error)?);
+    let file_size = source.seek(SeekFrom::End(0)).map_err(io_error)?;
+    source.seek(SeekFrom::Start(0)).map_err(io_error)?;
+    let mut header_bytes = [0u8; Header::SIZE];
+    source.read_exact(&mut header_bytes).map_err(io_error)?;
+    let offset_position = OFFSET_TO_FIRST_RECORD_POSITION as usize;
+    let offset_to_first_record = u16::from_le_bytes([
+        header_bytes[offset_position],
+        header_bytes[offset_position + 1],
+    ]);
+    // The offset bounds the number of descriptors read, as it may be the corrupted value,
+    // the descriptors are read up to their terminator instead
+    header_bytes[offset_position..offset_position + 2].copy_from_slice(&u16::MAX.to_le_bytes());
+    let (mut header, fields_info, _) = read_table_info(
+        &mut (&header_bytes[..]).chain(&mut source),
+        Flavor::Standard,
+        &crate::UnicodeLossy,
+    )?;
+    header.offset_to_first_record = offset_to_first_record;
+    let mut repairs = vec![];
+
+    let mut size_of_record = header.size_of_record;
+    if let Ok(expected) = u16::try_from(expected_record_size(&fields_info)) {
+        if expected != size_of_record {
+            repairs.push(Repair::RecordSize {
+                from: size_of_record,
+                to: expected,
+            });
+            size_of_record = expected;
+        }
+    }
+    let record_size = u64::from(size_of_record);
+
+    // Bytes after the descriptors may be padding, so a bigger offset is only
+    // repaired when the records do not fit after it
+    let mut offset = header.offset_to_first_record;
+    if let Ok(expected) = u16::try_from(expected_header_size(&header, &fields_info)) {
+        if offset < expected
+            || (offset > expected
+                && !records_fit(file_size, u64::from(offset), record_size)
+                && records_fit(file_size, u64::from(expected), record_size))
+        {
+            repairs.push(Repair::HeaderSize {
+                from: offset,
+                to: expected,
+            });
+            offset = expected;
+        }
+    }
+
+    let records_size = file_size.saturating_sub(u64::from(offset));
+    let num_records = u32::try_from(records_size / record_size.max(1)).unwrap_or(u32::MAX);
+    if num_records != header.num_records {
+        repairs.push(Repair::NumRecords {
+            from: header.num_records,
+            to: num_records,
+        });
+    }
+
+    let end_of_records = u64::from(offset) + u64::from(num_records) * record_size;
+    let marker = source
+        .seek(SeekFrom::Start(end_of_records))
+        .and_then(|_| source.read_u8())
+        .ok();
+    if marker != Some(FILE_TERMINATOR) {
+        repairs.push(Repair::EndOfFileMarker {
+            position: end_of_records,
+        });
+    }
+    Ok(repairs)
+}
+
+/// Patches the file at the given path with the repairs
+fn apply_repairs(path: &Path, repairs: &[Repair]) -> std::io::Result<()> {
+    let mut file = OpenOptions::new().write(true).open(path)?;
+    for repair in repairs {
+        match *repair {
+            Repair::NumRecords { to, .. } => {
+                file.seek(SeekFrom::Start(NUM_RECORDS_POSITION))?;
+                file.write_u32::<LittleEndian>(to)?;
+            }
+            Repair::HeaderSize { to, .. } => {
+                file.seek(SeekFrom::Start(OFFSET_TO_FIRST_RECORD_POSITION))?;
+                file.write_u16::<LittleEndian>(to)?;
+            }
+            Repair::RecordSize { to, .. } => {
+                file.seek(SeekFrom::Start(SIZE_OF_RECORD_POSITION))?;
+                file.write_u16::<LittleEndian>(to)?;
+            }
+            Repair::EndOfFileMarker { position } => {
+                file.seek(SeekFrom::Start(position))?;
+                file.write_u8(FILE_TERMINATOR)?;
+            }
+        }
+    }
+    file.flush()
+}
+
+/// Repairs the header of the dBase file at the given path,
+/// returning the repairs made (or that would be made in [DryRun](enum.RepairMode.html#variant.DryRun) mode).
+///
+/// The values are recomputed from the fields descriptors and the size of the file:
+///
+/// - the size of the records is the sum of the lengths of the fields
+/// - the offset to the first record is the size of the header and fields descriptors
+/// - the number of records is the number of records that fit in the file
+/// - the end of file marker is written after the last record if it is missing
+///
+/// Only the bytes of the repaired values are written, the records are left untouched.
+///
+/// # Example
+///
+/// ```
+/// use dbase::{Repair, RepairMode};
+///
+/// # fn main() -> Result<(), dbase::Error> {
+/// let repairs = dbase::repair("tests/data/stations.dbf", RepairMode::DryRun)?;
+/// assert_eq!(repairs, vec![Repair::RecordSize { from: 1016, to: 1017 }]);
+///
+/// let repaired_path = std::env::temp_dir().join("repaired_stations.dbf");
+/// dbase::repair("tests/data/stations.dbf", RepairMode::CopyTo(repaired_path.clone()))?;
+/// assert_eq!(dbase::repair(&repaired_path, RepairMode::DryRun)?, vec![]);
+/// # std::fs::remove_file(&repaired_path).unwrap();
+/// # Ok(())
+/// # }
+/// ```
+pub fn repair<P: AsRef<Path>>(path: P, mode: RepairMode) -> Result<Vec<Repair>, Error> {
+    let path = path.as_ref();
+    let repairs = find_repairs(path)?;
+    match mode {
+        RepairMode::DryRun => {}
+        RepairMode::InPlace => {
+            apply_repairs(path, &repairs).map_err(|error| Error::io_error(error, 0))?
+        }
+        RepairMode::CopyTo(dst_path) => {
+            std::fs::copy(path, &dst_path).map_err(|error| Error::io_error(error, 0))?;
+            apply_repairs(&dst_path, &repairs).map_err(|error| Error::io_error(error, 0))?
+        }
+    }
+    Ok(repairs)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::io::Cursor;
+
+    use crate::{FieldName, FieldValue, Reader, Record, TableWriterBuilder};
+
+    fn records() -> Vec<Record> {
+        ["Ferris", "Corro", "Pinchy"]
+            .iter()
+            .map(|name| {
+                let mut record = Record::default();
+                record.insert(
+                    "NAME".to_owned(),
+                    FieldValue::Character(Some(name.to_string())),
+                );
+                record
+            })
+            .collect()
+    }
+
+    fn table() -> Vec<u8> {
+        TableWriterBuilder::new()
+            .add_character_field(FieldName::try_from("NAME").unwrap(), 10)
+            .build_with_dest(Cursor::new(Vec::<u8>::new()))
+            .write(&records())
+            .unwrap()
+            .into_inner()
+    }
+
+    #[test]
+    fn valid_table_needs_no_repair() {
+        let path = std::env::temp_dir().join("dbase_repair_valid.dbf");
+        std::fs::write(&path, table()).unwrap();
+        let repairs = repair(&path, RepairMode::InPlace).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(repairs, vec![]);
+    }
+
+    #[test]
+    fn repair_corrupted_header() {
+        let mut content = table();
+        content[4] = 7;
+        content[8] = 60;
+        content[10] = 12;
+        content.pop();
+        let path = std::env::temp_dir().join("dbase_repair_corrupted.dbf");
+        std::fs::write(&path, &content).unwrap();
+
+        let expected = vec![
+            Repair::RecordSize { from: 12, to: 11 },
+            Repair::HeaderSize { from: 60, to: 65 },
+            Repair::NumRecords { from: 7, to: 3 },
+            Repair::EndOfFileMarker { position: 98 },
+        ];
+        assert_eq!(repair(&path, RepairMode::DryRun).unwrap(), expected);
+        assert_eq!(std::fs::read(&path).unwrap(), content);
+
+        assert_eq!(repair(&path, RepairMode::InPlace).unwrap(), expected);
+        let repaired = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(repaired, table());
+        assert_eq!(
+            Reader::new(Cursor::new(repaired)).unwrap().read().unwrap(),
+            records()
+        );
+    }
+
+    #[test]
+    fn padding_after_descriptors_is_kept() {
+        let mut content = table();
+        // One padding byte after the terminator of the descriptors
+        content.insert(65, 0);
+        content[8] = 66;
+        let path = std::env::temp_dir().join("dbase_repair_padding.dbf");
+        let copy_path = std::env::temp_dir().join("dbase_repair_padding_copy.dbf");
+        std::fs::write(&path, &content).unwrap();
+        let repairs = repair(&path, RepairMode::CopyTo(copy_path.clone())).unwrap();
+        let copy = std::fs::read(&copy_path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        std::fs::remove_file(&copy_path).unwrap();
+        assert_eq!(repairs, vec![]);
+        assert_eq!(copy, content);
+    }
+}
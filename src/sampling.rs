@@ -0,0 +1,118 @@
+//! Module with the definition of fn's to read a random sample
+//! of the records of dBase files
+//!
+//! The records are read by index, skipping over the others,
+//! so sampling a big table is as fast as reading the sampled records.
+use std::collections::hash_map::RandomState;
+use std::collections::BTreeSet;
+use std::convert::TryFrom;
+use std::hash::{BuildHasher, Hasher};
+use std::io::{Read, Seek};
+
+use crate::error::Error;
+use crate::reading::{Reader, Record};
+
+/// [SplitMix64](https://prng.di.unimi.it/splitmix64.c) generator,
+/// good enough to pick records and reproducible from its seed
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a number in `0..=max`
+    fn next_up_to(&mut self, max: u32) -> u32 {
+        ((u128::from(self.next()) * (u128::from(max) + 1)) >> 64) as u32
+    }
+}
+
+/// Picks `n` distinct indices in `0..num_records`, each set of indices
+/// being as likely, using the algorithm of Floyd
+fn sample_indices(num_records: u32, n: u32, seed: u64) -> BTreeSet<u32> {
+    let mut rng = SplitMix64(seed);
+    let mut indices = BTreeSet::new();
+    for j in (num_records - n)..num_records {
+        let index = rng.next_up_to(j);
+        if !indices.insert(index) {
+            indices.insert(j);
+        }
+    }
+    indices
+}
+
+impl<T: Read + Seek> Reader<T> {
+    /// Reads `n` records picked at random, in the order they are in the file
+    ///
+    /// All the records are returned when the table has no more than `n` records.
+    /// Deleted records may be picked, like they are read by [read](#method.read).
+    /// The reader is left positioned where it was.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), dbase::Error> {
+    /// let mut reader = dbase::Reader::from_path("tests/data/stations.dbf")?;
+    /// let sample = reader.sample(3)?;
+    /// assert_eq!(sample.len(), 3);
+    /// // The reader can still read the whole table
+    /// assert_eq!(reader.read()?.len(), 6);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn sample(&mut self, n: usize) -> Result<Vec<Record>, Error> {
+        let seed = RandomState::new().build_hasher().finish();
+        self.sample_with_seed(n, seed)
+    }
+
+    /// Same as [sample](#method.sample), the records picked only depend on the seed
+    /// (and the number of records), so a sample can be reproduced
+    pub fn sample_with_seed(&mut self, n: usize, seed: u64) -> Result<Vec<Record>, Error> {
+        let num_records = self.header.num_records;
+        let n = u32::try_from(n).unwrap_or(u32::MAX).min(num_records);
+        let position = self.next_record;
+        let records = sample_indices(num_records, n, seed)
+            .into_iter()
+            .map(|index| self.read_record_at_as::<Record>(index))
+            .collect::<Result<Vec<Record>, Error>>();
+        self.seek_to_record(position)?;
+        records
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn sample_is_uniform() {
+        let mut counts = [0usize; 10];
+        for seed in 0..10_000 {
+            let indices = sample_indices(10, 3, seed);
+            assert_eq!(indices.len(), 3);
+            for index in indices {
+                counts[index as usize] += 1;
+            }
+        }
+        // Each index is expected to be picked 3000 times
+        assert!(counts.iter().all(|count| (2800..3200).contains(count)));
+        assert_eq!(sample_indices(5, 5, 42).len(), 5);
+        assert!(sample_indices(5, 0, 42).is_empty());
+    }
+
+    #[test]
+    fn sample_with_seed_is_reproducible() {
+        let mut reader = Reader::from_path("tests/data/stations.dbf").unwrap();
+        let first = reader.sample_with_seed(2, 7).unwrap();
+        let second = reader.sample_with_seed(2, 7).unwrap();
+        assert_eq!(first, second);
+        assert_eq!(
+            reader.sample_with_seed(100, 7).unwrap(),
+            reader.read().unwrap()
+        );
+    }
+}
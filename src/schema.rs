@@ -0,0 +1,235 @@
+//! Module with the definition of the traits used to infer the fields of a table
+//! from the fields of a struct
+//!
+//! The [dbase_record](macro.dbase_record.html) macro implements
+//! [TableSchema](trait.TableSchema.html) for the structs it defines.
+use std::convert::TryFrom;
+
+use crate::error::{Error, ErrorKind};
+use crate::record::field::{Date, DateTime, FieldType, FieldValue};
+use crate::record::{CompactString, FieldName};
+use crate::writing::TableWriterBuilder;
+
+/// Length of the Character fields when the attributes do not give one
+pub const DEFAULT_CHARACTER_LENGTH: u8 = 254;
+/// Length of the Numeric and Float fields when the attributes do not give one
+pub const DEFAULT_NUMERIC_LENGTH: u8 = 20;
+/// Number of decimals of the Numeric and Float fields when the attributes do not give one
+pub const DEFAULT_NUM_DECIMALS: u8 = 6;
+
+/// Types whose values are stored in a field of a known type
+pub trait SchemaField {
+    /// The type of the field the values are stored in,
+    /// unless the attributes of the struct field say otherwise
+    ///
+    /// `None` when the type has to be given by the attributes.
+    const FIELD_TYPE: Option<FieldType>;
+}
+
+macro_rules! impl_schema_field_for {
+    ($field_type:expr => $($type:ty),+) => {
+        $(
+            impl SchemaField for $type {
+                const FIELD_TYPE: Option<FieldType> = Some($field_type);
+            }
+        )+
+    };
+}
+
+impl_schema_field_for!(
+    FieldType::Character => String, Option<String>, CompactString, Option<CompactString>
+);
+impl_schema_field_for!(FieldType::Numeric => f64, Option<f64>);
+impl_schema_field_for!(FieldType::Float => f32, Option<f32>);
+impl_schema_field_for!(FieldType::Logical => bool, Option<bool>);
+impl_schema_field_for!(FieldType::Date => Date, Option<Date>);
+impl_schema_field_for!(FieldType::Integer => i32);
+impl_schema_field_for!(FieldType::DateTime => DateTime);
+impl_schema_field_for!(FieldType::Long => Option<i32>);
+impl_schema_field_for!(FieldType::Timestamp => Option<DateTime>);
+
+impl SchemaField for FieldValue {
+    const FIELD_TYPE: Option<FieldType> = None;
+}
+
+/// The description of a field of a struct, from which its dBase field is made
+///
+/// The methods are named after the keys of the `#[dbase(...)]` attribute
+/// accepted by the [dbase_record](macro.dbase_record.html) macro.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldAttributes {
+    name: String,
+    field_type: Option<FieldType>,
+    length: Option<u8>,
+    num_decimals: Option<u8>,
+}
+
+impl FieldAttributes {
+    /// Creates the attributes of the field of a struct, named after it
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_owned(),
+            field_type: None,
+            length: None,
+            num_decimals: None,
+        }
+    }
+
+    /// Sets the name of the dBase field, `#[dbase(name = "FIRSTNAME")]`
+    pub fn name(mut self, name: &str) -> Self {
+        self.name = name.to_owned();
+        self
+    }
+
+    /// Sets the length of a Character, Numeric or Float field, `#[dbase(len = 30)]`
+    pub fn len(mut self, length: u8) -> Self {
+        self.length = Some(length);
+        self
+    }
+
+    /// Sets the number of decimals of a Numeric or Float field, `#[dbase(decimals = 2)]`
+    pub fn decimals(mut self, num_decimals: u8) -> Self {
+        self.num_decimals = Some(num_decimals);
+        self
+    }
+
+    /// Sets the type of the field instead of the one inferred from the Rust type,
+    /// `#[dbase(field_type = dbase::FieldType::Currency)]`
+    pub fn field_type(mut self, field_type: FieldType) -> Self {
+        self.field_type = Some(field_type);
+        self
+    }
+
+    /// Adds the field described to the builder,
+    /// `default_type` is used when the attributes do not set a type
+    pub fn add_to(
+        self,
+        builder: TableWriterBuilder,
+        default_type: Option<FieldType>,
+    ) -> Result<TableWriterBuilder, Error> {
+        let field_type = match self.field_type.or(default_type) {
+            Some(field_type) => field_type,
+            None => {
+                return Err(Error {
+                    record_num: 0,
+                    field: None,
+                    kind: ErrorKind::Message(format!(
+                        "the type of the field '{}' has to be given",
+                        self.name
+                    )),
+                })
+            }
+        };
+        let name = FieldName::try_from(self.name.as_str()).map_err(|error| Error {
+            record_num: 0,
+            field: None,
+            kind: ErrorKind::Message(error.to_string()),
+        })?;
+        let length = self.length.unwrap_or(DEFAULT_NUMERIC_LENGTH);
+        let num_decimals = self.num_decimals.unwrap_or(DEFAULT_NUM_DECIMALS);
+        let builder = match field_type {
+            FieldType::Character => {
+                builder.add_character_field(name, self.length.unwrap_or(DEFAULT_CHARACTER_LENGTH))
+            }
+            FieldType::Numeric => builder.add_numeric_field(name, length, num_decimals),
+            FieldType::Float => builder.add_float_field(name, length, num_decimals),
+            FieldType::Logical => builder.add_logical_field(name),
+            FieldType::Date => builder.add_date_field(name),
+            FieldType::Integer => builder.add_integer_field(name),
+            FieldType::DateTime => builder.add_datetime_field(name),
+            FieldType::Double => builder.add_double_field(name),
+            FieldType::Currency => builder.add_currency_field(name),
+            FieldType::Long => builder.add_long_field(name),
+            FieldType::DBase7Double => builder.add_dbase7_double_field(name),
+            FieldType::Timestamp => builder.add_timestamp_field(name),
+            FieldType::Autoincrement => builder.add_autoincrement_field(name),
+            _ => {
+                return Err(Error {
+                    record_num: 0,
+                    field: None,
+                    kind: ErrorKind::Message(format!(
+                        "the field '{}' cannot be a {:?} field",
+                        self.name, field_type
+                    )),
+                })
+            }
+        };
+        Ok(builder)
+    }
+}
+
+/// Trait implemented by structs whose fields describe the fields of a table
+///
+/// It is implemented by the [dbase_record](macro.dbase_record.html) macro,
+/// see [TableWriterBuilder::from_schema](struct.TableWriterBuilder.html#method.from_schema).
+pub trait TableSchema {
+    /// Adds the fields of the struct, in order, to the builder
+    fn add_fields(builder: TableWriterBuilder) -> Result<TableWriterBuilder, Error>;
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn attributes_override_the_defaults() {
+        let builder = FieldAttributes::new("price")
+            .len(10)
+            .decimals(2)
+            .add_to(TableWriterBuilder::new(), Some(FieldType::Numeric))
+            .unwrap();
+        let builder = FieldAttributes::new("name")
+            .add_to(builder, Some(FieldType::Character))
+            .unwrap();
+        let builder = FieldAttributes::new("total")
+            .field_type(FieldType::Currency)
+            .add_to(builder, Some(FieldType::Numeric))
+            .unwrap();
+        let fields = builder
+            .v
+            .iter()
+            .map(|info| {
+                (
+                    info.name().to_owned(),
+                    info.field_type,
+                    info.length(),
+                    info.num_decimal_places,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            fields,
+            vec![
+                ("price".to_owned(), FieldType::Numeric, 10, 2),
+                ("name".to_owned(), FieldType::Character, 254, 0),
+                ("total".to_owned(), FieldType::Currency, 8, 0),
+            ]
+        );
+    }
+
+    #[test]
+    fn invalid_fields() {
+        assert!(matches!(
+            FieldAttributes::new("a_name_too_long")
+                .add_to(TableWriterBuilder::new(), Some(FieldType::Character)),
+            Err(Error {
+                kind: ErrorKind::Message(_),
+                ..
+            })
+        ));
+        assert!(matches!(
+            FieldAttributes::new("notes").add_to(TableWriterBuilder::new(), Some(FieldType::Memo)),
+            Err(Error {
+                kind: ErrorKind::Message(_),
+                ..
+            })
+        ));
+        assert!(matches!(
+            FieldAttributes::new("value").add_to(TableWriterBuilder::new(), FieldValue::FIELD_TYPE),
+            Err(Error {
+                kind: ErrorKind::Message(_),
+                ..
+            })
+        ));
+    }
+}
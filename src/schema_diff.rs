@@ -0,0 +1,304 @@
+//! Module with the definition of fn's and struct's to compare
+//! the fields of two tables
+//!
+//! Used to know how the schema of a table changed between two versions of a file,
+//! and whether the records of one can be written in the other.
+use std::fmt;
+
+use crate::record::field::FieldType;
+use crate::record::FieldInfo;
+
+/// A difference between the fields of two tables
+#[derive(Debug, Clone, PartialEq)]
+pub enum FieldChange {
+    /// The field is only in the second table
+    Added {
+        name: String,
+        field_type: FieldType,
+        length: u16,
+    },
+    /// The field is only in the first table
+    Removed {
+        name: String,
+        field_type: FieldType,
+        length: u16,
+    },
+    /// The field has another type in the second table
+    Retyped {
+        name: String,
+        from: FieldType,
+        to: FieldType,
+    },
+    /// The field has another length in the second table
+    Resized { name: String, from: u16, to: u16 },
+    /// The field has another number of decimal places in the second table
+    DecimalPlacesChanged { name: String, from: u8, to: u8 },
+}
+
+impl FieldChange {
+    /// Returns the name of the field that changed
+    pub fn name(&self) -> &str {
+        match self {
+            FieldChange::Added { name, .. }
+            | FieldChange::Removed { name, .. }
+            | FieldChange::Retyped { name, .. }
+            | FieldChange::Resized { name, .. }
+            | FieldChange::DecimalPlacesChanged { name, .. } => name,
+        }
+    }
+}
+
+impl fmt::Display for FieldChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FieldChange::Added {
+                name,
+                field_type,
+                length,
+            } => write!(
+                f,
+                "added '{}' ({:?} of length {})",
+                name, field_type, length
+            ),
+            FieldChange::Removed {
+                name,
+                field_type,
+                length,
+            } => write!(
+                f,
+                "removed '{}' ({:?} of length {})",
+                name, field_type, length
+            ),
+            FieldChange::Retyped { name, from, to } => {
+                write!(f, "'{}' changed from {:?} to {:?}", name, from, to)
+            }
+            FieldChange::Resized { name, from, to } => {
+                write!(f, "'{}' resized from {} to {}", name, from, to)
+            }
+            FieldChange::DecimalPlacesChanged { name, from, to } => write!(
+                f,
+                "'{}' decimal places changed from {} to {}",
+                name, from, to
+            ),
+        }
+    }
+}
+
+/// The differences between the fields of two tables, made by [diff_schemas](fn.diff_schemas.html)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SchemaDiff {
+    /// The changes, the removed fields first then the other ones
+    /// in the order of the fields of the second table
+    pub changes: Vec<FieldChange>,
+    /// Whether the second table has memo fields, which the writers cannot write
+    has_memo_fields: bool,
+}
+
+impl SchemaDiff {
+    /// Returns whether both tables have the same fields
+    ///
+    /// The order of the fields is not compared.
+    pub fn is_identical(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Returns whether the records read from the first table
+    /// can be written with a writer of the second table.
+    ///
+    /// The fields of the second table must all be in the first one, with the same type,
+    /// the fields that are only in the first table are not written.
+    /// Values that are longer than the fields of the second table are cropped,
+    /// see [is_lossless](#method.is_lossless).
+    pub fn is_compatible(&self) -> bool {
+        !self.has_memo_fields
+            && self.changes.iter().all(|change| match change {
+                FieldChange::Removed { .. }
+                | FieldChange::Resized { .. }
+                | FieldChange::DecimalPlacesChanged { .. } => true,
+                FieldChange::Added { .. } | FieldChange::Retyped { .. } => false,
+            })
+    }
+
+    /// Returns whether the records read from the first table can be written
+    /// with a writer of the second table without cropping or rounding values
+    pub fn is_lossless(&self) -> bool {
+        self.is_compatible()
+            && self.changes.iter().all(|change| match change {
+                FieldChange::Resized { from, to, .. } => to >= from,
+                FieldChange::DecimalPlacesChanged { from, to, .. } => to >= from,
+                _ => true,
+            })
+    }
+}
+
+/// Compares the fields of two tables
+///
+/// Fields are matched by name, names of fields are compared with case.
+///
+/// # Example
+///
+/// ```
+/// # fn main() -> Result<(), dbase::Error> {
+/// let stations = dbase::Reader::from_path("tests/data/stations.dbf")?;
+/// let line = dbase::Reader::from_path("tests/data/line.dbf")?;
+///
+/// let diff = dbase::diff_schemas(line.fields(), stations.fields());
+/// for change in &diff.changes {
+///     println!("{}", change);
+/// }
+/// assert!(!diff.is_identical());
+/// assert!(!diff.is_compatible());
+/// // The fields of line.dbf are all in stations.dbf
+/// assert!(dbase::diff_schemas(stations.fields(), line.fields()).is_compatible());
+/// assert!(dbase::diff_schemas(line.fields(), line.fields()).is_identical());
+/// # Ok(())
+/// # }
+/// ```
+pub fn diff_schemas(first: &[FieldInfo], second: &[FieldInfo]) -> SchemaDiff {
+    let first = first
+        .iter()
+        .filter(|field_info| !field_info.is_deletion_flag())
+        .collect::<Vec<&FieldInfo>>();
+    let second = second
+        .iter()
+        .filter(|field_info| !field_info.is_deletion_flag())
+        .collect::<Vec<&FieldInfo>>();
+
+    let mut changes = first
+        .iter()
+        .filter(|old| !second.iter().any(|new| new.name() == old.name()))
+        .map(|old| FieldChange::Removed {
+            name: old.name().to_owned(),
+            field_type: old.field_type,
+            length: old.field_length,
+        })
+        .collect::<Vec<FieldChange>>();
+
+    for new in &second {
+        let name = new.name().to_owned();
+        let old = match first.iter().find(|old| old.name() == new.name()) {
+            Some(old) => old,
+            None => {
+                changes.push(FieldChange::Added {
+                    name,
+                    field_type: new.field_type,
+                    length: new.field_length,
+                });
+                continue;
+            }
+        };
+        if old.field_type != new.field_type {
+            changes.push(FieldChange::Retyped {
+                name,
+                from: old.field_type,
+                to: new.field_type,
+            });
+            continue;
+        }
+        if old.field_length != new.field_length {
+            changes.push(FieldChange::Resized {
+                name: name.clone(),
+                from: old.field_length,
+                to: new.field_length,
+            });
+        }
+        if old.num_decimal_places != new.num_decimal_places {
+            changes.push(FieldChange::DecimalPlacesChanged {
+                name,
+                from: old.num_decimal_places,
+                to: new.num_decimal_places,
+            });
+        }
+    }
+
+    SchemaDiff {
+        changes,
+        has_memo_fields: second
+            .iter()
+            .any(|field_info| field_info.field_type == FieldType::Memo),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::convert::TryFrom;
+
+    use crate::FieldName;
+
+    fn field(name: &str, field_type: FieldType, length: u8, num_decimal_places: u8) -> FieldInfo {
+        let mut field_info = FieldInfo::new(FieldName::try_from(name).unwrap(), field_type, length);
+        field_info.num_decimal_places = num_decimal_places;
+        field_info
+    }
+
+    #[test]
+    fn changes_between_schemas() {
+        let first = vec![
+            FieldInfo::new_deletion_flag(),
+            field("NAME", FieldType::Character, 20, 0),
+            field("PRICE", FieldType::Numeric, 10, 2),
+            field("STOCK", FieldType::Numeric, 5, 0),
+            field("OLD", FieldType::Logical, 1, 0),
+        ];
+        let second = vec![
+            FieldInfo::new_deletion_flag(),
+            field("NAME", FieldType::Character, 30, 0),
+            field("PRICE", FieldType::Numeric, 10, 1),
+            field("STOCK", FieldType::Integer, 4, 0),
+            field("NEW", FieldType::Date, 8, 0),
+        ];
+        let diff = diff_schemas(&first, &second);
+        assert_eq!(
+            diff.changes,
+            vec![
+                FieldChange::Removed {
+                    name: "OLD".to_owned(),
+                    field_type: FieldType::Logical,
+                    length: 1
+                },
+                FieldChange::Resized {
+                    name: "NAME".to_owned(),
+                    from: 20,
+                    to: 30
+                },
+                FieldChange::DecimalPlacesChanged {
+                    name: "PRICE".to_owned(),
+                    from: 2,
+                    to: 1
+                },
+                FieldChange::Retyped {
+                    name: "STOCK".to_owned(),
+                    from: FieldType::Numeric,
+                    to: FieldType::Integer
+                },
+                FieldChange::Added {
+                    name: "NEW".to_owned(),
+                    field_type: FieldType::Date,
+                    length: 8
+                },
+            ]
+        );
+        assert!(!diff.is_compatible());
+
+        // Without the retyped and added fields, records can be written
+        let diff = diff_schemas(&first, &second[..3]);
+        assert!(diff.is_compatible());
+        assert!(!diff.is_lossless());
+        let diff = diff_schemas(&first, &[first[0].clone(), second[1].clone()]);
+        assert!(diff.is_lossless());
+        assert!(diff_schemas(&first, &first).is_identical());
+    }
+
+    #[test]
+    fn memo_fields_cannot_be_written() {
+        let fields = vec![
+            FieldInfo::new_deletion_flag(),
+            field("NOTES", FieldType::Memo, 10, 0),
+        ];
+        let diff = diff_schemas(&fields, &fields);
+        assert!(diff.is_identical());
+        assert!(!diff.is_compatible());
+    }
+}
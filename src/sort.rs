@@ -0,0 +1,301 @@
+//! Module with the definition of fn's to sort records by the values of fields
+//!
+//! Records in memory are sorted with [sort_records], tables that do not fit
+//! in memory are sorted with [sort_table], which sorts runs of records
+//! in temporary files before merging them.
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+
+use crate::error::{Error, ErrorKind};
+use crate::reading::{Reader, Record};
+use crate::writing::{check_no_memo_fields, TableWriterBuilder};
+
+/// The order in which the values of a field are sorted
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Order {
+    /// Smallest values first
+    Ascending,
+    /// Greatest values first
+    Descending,
+}
+
+/// Compares two records by the values of the fields, the first field that differs decides,
+/// as described in [sort_records]
+pub(crate) fn compare_records(a: &Record, b: &Record, keys: &[(&str, Order)]) -> Ordering {
+    for (name, order) in keys {
+        let ordering = match (a.get(name), b.get(name)) {
+            (Some(a), Some(b)) => a.total_cmp(b),
+            (a, b) => a.is_some().cmp(&b.is_some()),
+        };
+        let ordering = match order {
+            Order::Ascending => ordering,
+            Order::Descending => ordering.reverse(),
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+    Ordering::Equal
+}
+
+/// Sorts the records by the values of the fields
+///
+/// Values are compared with [FieldValue::total_cmp](enum.FieldValue.html#method.total_cmp),
+/// records that do not have a field come before the ones that have it.
+/// The sort is stable, records with the same values keep their order.
+///
+/// # Example
+///
+/// ```
+/// # fn main() -> Result<(), dbase::Error> {
+/// use dbase::Order;
+///
+/// let mut stations = dbase::read("tests/data/stations.dbf")?;
+/// dbase::sort_records(&mut stations, &[("line", Order::Descending), ("name", Order::Ascending)]);
+/// assert_eq!(
+///     stations[0].get("name"),
+///     Some(&dbase::FieldValue::Character(Some("Judiciary Sq".to_owned())))
+/// );
+/// # Ok(())
+/// # }
+/// ```
+pub fn sort_records(records: &mut [Record], keys: &[(&str, Order)]) {
+    records.sort_by(|a, b| compare_records(a, b, keys));
+}
+
+/// Temporary files, removed when dropped
+struct TemporaryFiles(Vec<PathBuf>);
+
+impl TemporaryFiles {
+    fn create_path(&mut self) -> PathBuf {
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |duration| duration.subsec_nanos());
+        let path = std::env::temp_dir().join(format!(
+            "dbase_sort_{}_{}_{}.dbf",
+            std::process::id(),
+            nanos,
+            self.0.len()
+        ));
+        self.0.push(path.clone());
+        path
+    }
+}
+
+impl Drop for TemporaryFiles {
+    fn drop(&mut self) {
+        for path in &self.0 {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+/// Next record of a sorted run, the heap pops the smallest one first
+struct Head<'k> {
+    record: Record,
+    run: usize,
+    keys: &'k [(&'k str, Order)],
+}
+
+impl Ord for Head<'_> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Reversed, as the heap is a max-heap,
+        // equal records are taken from the first run to keep the sort stable
+        compare_records(&self.record, &other.record, self.keys)
+            .then(self.run.cmp(&other.run))
+            .reverse()
+    }
+}
+
+impl PartialOrd for Head<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Head<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Head<'_> {}
+
+/// Writes the records of the table at `src` sorted by the values of the fields
+/// to a new table at `dst`, keeping at most `max_records_in_memory` records in memory
+///
+/// When the table has more records, it is read by runs of `max_records_in_memory` records
+/// that are sorted and written to temporary files, which are then merged.
+/// The sort is stable, like [sort_records].
+///
+/// The deletion flags are not kept, and tables with memo fields
+/// cannot be sorted as memos cannot be written.
+///
+/// # Example
+///
+/// ```
+/// # fn main() -> Result<(), dbase::Error> {
+/// use dbase::Order;
+///
+/// let sorted_path = std::env::temp_dir().join("dbase_sort_table_doc.dbf");
+/// dbase::sort_table("tests/data/stations.dbf", &sorted_path, &[("name", Order::Ascending)], 2)?;
+/// let stations = dbase::read(&sorted_path)?;
+/// assert_eq!(
+///     stations[0].get("name"),
+///     Some(&dbase::FieldValue::Character(Some("Capitol South".to_owned())))
+/// );
+/// # Ok(())
+/// # }
+/// ```
+pub fn sort_table<P: AsRef<Path>, Q: AsRef<Path>>(
+    src: P,
+    dst: Q,
+    keys: &[(&str, Order)],
+    max_records_in_memory: usize,
+) -> Result<(), Error> {
+    let max_records_in_memory = max_records_in_memory.max(1);
+    let mut reader = Reader::from_path(src)?;
+    check_no_memo_fields(reader.fields())?;
+    if let Some((name, _)) = keys
+        .iter()
+        .find(|(name, _)| !reader.fields().iter().any(|field| field.name() == *name))
+    {
+        return Err(Error {
+            record_num: 0,
+            field: None,
+            kind: ErrorKind::Message(format!("the table has no field named '{}'", name)),
+        });
+    }
+    let num_records = reader.record_count();
+
+    let mut runs = TemporaryFiles(vec![]);
+    loop {
+        let mut records = reader
+            .iter_records()
+            .take(max_records_in_memory)
+            .collect::<Result<Vec<_>, _>>()?;
+        sort_records(&mut records, keys);
+        if runs.0.is_empty() && records.len() == num_records {
+            // Everything fits in memory
+            TableWriterBuilder::from_reader_ref(&reader)
+                .build_with_file_dest(dst)
+                .map_err(|error| Error::io_error(error, 0))?
+                .write(&records)?;
+            return Ok(());
+        }
+        if records.is_empty() {
+            break;
+        }
+        let path = runs.create_path();
+        TableWriterBuilder::from_reader_ref(&reader)
+            .build_with_file_dest(&path)
+            .map_err(|error| Error::io_error(error, 0))?
+            .write(&records)?;
+    }
+
+    let mut run_readers = runs
+        .0
+        .iter()
+        .map(Reader::from_path)
+        .collect::<Result<Vec<Reader<BufReader<File>>>, _>>()?;
+    let mut heads = BinaryHeap::with_capacity(run_readers.len());
+    for (run, run_reader) in run_readers.iter_mut().enumerate() {
+        if let Some(record) = run_reader.iter_records().next() {
+            heads.push(Head {
+                record: record?,
+                run,
+                keys,
+            });
+        }
+    }
+    let sorted_records = std::iter::from_fn(|| {
+        let Head { record, run, .. } = heads.pop()?;
+        match run_readers[run].iter_records().next() {
+            Some(Ok(next)) => heads.push(Head {
+                record: next,
+                run,
+                keys,
+            }),
+            Some(Err(error)) => return Some(Err(error)),
+            None => {}
+        }
+        Some(Ok(record))
+    });
+    TableWriterBuilder::from_reader_ref(&reader)
+        .build_with_file_dest(dst)
+        .map_err(|error| Error::io_error(error, 0))?
+        .write_iter::<Record, _, _>(num_records, sorted_records)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::FieldValue;
+
+    fn names(records: &[Record]) -> Vec<String> {
+        records
+            .iter()
+            .map(|record| match record.get("name") {
+                Some(FieldValue::Character(Some(name))) => name.clone(),
+                value => panic!("unexpected name {:?}", value),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn sort_in_memory() {
+        let mut stations = crate::read("tests/data/stations.dbf").unwrap();
+        sort_records(&mut stations, &[("line", Order::Ascending)]);
+        // Stable
+        assert_eq!(
+            names(&stations),
+            vec![
+                "Van Dorn Street",
+                "Franconia-Springfield",
+                "Federal Center SW",
+                "Capitol South",
+                "Judiciary Sq",
+                "Metro Center",
+            ]
+        );
+    }
+
+    #[test]
+    fn external_sort_matches_in_memory_sort() {
+        let keys = [
+            ("marker-col", Order::Descending),
+            ("name", Order::Ascending),
+        ];
+        let mut expected = crate::read("tests/data/stations.dbf").unwrap();
+        sort_records(&mut expected, &keys);
+        for max_records_in_memory in [1, 4, 6, 10] {
+            let path = std::env::temp_dir()
+                .join(format!("dbase_external_sort_{}.dbf", max_records_in_memory));
+            sort_table(
+                "tests/data/stations.dbf",
+                &path,
+                &keys,
+                max_records_in_memory,
+            )
+            .unwrap();
+            let sorted = crate::read(&path).unwrap();
+            assert_eq!(names(&sorted), names(&expected));
+        }
+    }
+
+    #[test]
+    fn unknown_sort_field() {
+        let path = std::env::temp_dir().join("dbase_external_sort_unknown.dbf");
+        let error = sort_table(
+            "tests/data/stations.dbf",
+            &path,
+            &[("NOPE", Order::Ascending)],
+            2,
+        );
+        assert!(error.is_err());
+    }
+}
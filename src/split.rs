@@ -0,0 +1,223 @@
+//! Module with the definition of the fn splitting a table into several ones
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+
+use crate::error::{Error, ErrorKind};
+use crate::reading::{Reader, Record, RecordValues};
+use crate::record::FieldInfo;
+use crate::writing::{check_no_memo_fields, table_info_size, RecordsWriter, TableWriterBuilder};
+
+/// How the records are distributed in the tables made by [split]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SplitBy<'a> {
+    /// Tables of at most this number of records
+    NumRecords(usize),
+    /// Tables of at most this number of bytes, tables have at least one record
+    Size(u64),
+    /// One table for each value of the field with the given name
+    FieldValue(&'a str),
+}
+
+/// A table made by [split]
+#[derive(Debug, Clone, PartialEq)]
+pub struct SplitTable {
+    /// Path of the table
+    pub path: PathBuf,
+    /// Number of records in the table
+    pub num_records: usize,
+    /// The value of the field of the records, as displayed by dBase,
+    /// for tables split by [SplitBy::FieldValue]
+    pub value: Option<String>,
+}
+
+/// Splits the live records of the table at `src` into tables with the same fields
+///
+/// The tables are written in the `dst_dir` directory, they are named after the table
+/// followed by their number (`stations_1.dbf`, `stations_2.dbf`, ...),
+/// tables split by the value of a field are in the order of the first record
+/// with each value. The records keep their order.
+///
+/// The table is read twice, once to count the records of each table, which
+/// is needed to write their header, then to write them.
+/// As memo files cannot be written, tables with memo fields cannot be split.
+///
+/// # Example
+///
+/// ```
+/// # fn main() -> Result<(), dbase::Error> {
+/// use dbase::SplitBy;
+///
+/// let dst_dir = std::env::temp_dir();
+/// let tables = dbase::split("tests/data/stations.dbf", &dst_dir, SplitBy::NumRecords(4))?;
+/// assert_eq!(tables.len(), 2);
+/// assert_eq!(tables[0].path, dst_dir.join("stations_1.dbf"));
+/// assert_eq!(tables[1].num_records, 2);
+///
+/// let tables = dbase::split("tests/data/stations.dbf", &dst_dir, SplitBy::FieldValue("line"))?;
+/// assert_eq!(tables[0].value.as_deref(), Some("blue"));
+/// assert_eq!(tables[0].num_records, 4);
+/// assert_eq!(tables[1].value.as_deref(), Some("red"));
+/// # Ok(())
+/// # }
+/// ```
+pub fn split<P: AsRef<Path>, Q: AsRef<Path>>(
+    src: P,
+    dst_dir: Q,
+    by: SplitBy,
+) -> Result<Vec<SplitTable>, Error> {
+    let src = src.as_ref();
+    let mut reader = Reader::from_path(src)?;
+    let fields = reader
+        .fields()
+        .iter()
+        .filter(|field| !field.is_deletion_flag())
+        .collect::<Vec<&FieldInfo>>();
+    check_no_memo_fields(reader.fields())?;
+
+    let key_index = match by {
+        SplitBy::FieldValue(name) => Some(
+            fields
+                .iter()
+                .position(|field| field.name() == name)
+                .ok_or_else(|| message_error(format!("the table has no field named '{}'", name)))?,
+        ),
+        _ => None,
+    };
+    let max_num_records = match by {
+        SplitBy::NumRecords(num_records) => num_records.max(1),
+        SplitBy::Size(size) => {
+            let size_of_table_info = table_info_size(reader.header(), fields.len());
+            // The file ends with a terminator
+            let size_of_records = size.saturating_sub(size_of_table_info as u64 + 1);
+            let size_of_record = fields
+                .iter()
+                .fold(1u64, |size, field| size + u64::from(field.length()));
+            ((size_of_records / size_of_record) as usize).max(1)
+        }
+        SplitBy::FieldValue(_) => usize::MAX,
+    };
+
+    // The table of each live record
+    let mut record_tables = Vec::<Option<usize>>::with_capacity(reader.record_count());
+    let mut tables = Vec::<SplitTable>::new();
+    let mut table_of_value = HashMap::<String, usize>::new();
+    let stem = src
+        .file_stem()
+        .map_or_else(|| "table".into(), |stem| stem.to_string_lossy());
+    let new_table = |tables: &mut Vec<SplitTable>, value: Option<String>| {
+        tables.push(SplitTable {
+            path: dst_dir
+                .as_ref()
+                .join(format!("{}_{}.dbf", stem, tables.len() + 1)),
+            num_records: 0,
+            value,
+        });
+        tables.len() - 1
+    };
+    while let Some(record) = reader.read_next_record_as::<RecordValues>() {
+        let record = record?;
+        if record.is_deleted {
+            record_tables.push(None);
+            continue;
+        }
+        let table = match key_index {
+            Some(key_index) => {
+                let value = &record.values[key_index];
+                match table_of_value.get(&format!("{:?}", value)) {
+                    Some(table) => *table,
+                    None => {
+                        let table = new_table(&mut tables, Some(value.to_string()));
+                        table_of_value.insert(format!("{:?}", value), table);
+                        table
+                    }
+                }
+            }
+            None => match tables.last() {
+                Some(last) if last.num_records < max_num_records => tables.len() - 1,
+                _ => new_table(&mut tables, None),
+            },
+        };
+        tables[table].num_records += 1;
+        record_tables.push(Some(table));
+    }
+
+    // The writers are opened on the first record of their table,
+    // and closed once all the records of their table are written
+    reader.set_record_range(0..reader.record_count())?;
+    let builder = TableWriterBuilder::from_reader_ref(&reader);
+    let mut writers = Vec::<Option<RecordsWriter<BufWriter<File>>>>::new();
+    writers.resize_with(tables.len(), || None);
+    let mut num_written = vec![0usize; tables.len()];
+    for (record, table) in reader.iter_records_as::<Record>().zip(record_tables) {
+        let record = record?;
+        let table = match table {
+            Some(table) => table,
+            None => continue,
+        };
+        if writers[table].is_none() {
+            writers[table] = Some(
+                builder
+                    .clone()
+                    .build_with_file_dest(&tables[table].path)
+                    .map_err(|error| Error::io_error(error, 0))?
+                    .start(tables[table].num_records)?,
+            );
+        }
+        if let Some(writer) = writers[table].as_mut() {
+            writer.write_record(&record)?;
+        }
+        num_written[table] += 1;
+        if num_written[table] == tables[table].num_records {
+            if let Some(writer) = writers[table].take() {
+                writer.finish()?;
+            }
+        }
+    }
+    Ok(tables)
+}
+
+fn message_error(message: String) -> Error {
+    Error {
+        record_num: 0,
+        field: None,
+        kind: ErrorKind::Message(message),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn split_by_size() {
+        let dst_dir = std::env::temp_dir().join("dbase_split_by_size");
+        std::fs::create_dir_all(&dst_dir).unwrap();
+        let expected = crate::read("tests/data/stations.dbf").unwrap();
+        // Room for 2 records
+        let size = 32 + 4 * 32 + 1 + 2 * 1017 + 1;
+        let tables = split("tests/data/stations.dbf", &dst_dir, SplitBy::Size(size)).unwrap();
+        assert_eq!(tables.len(), 3);
+        let mut records = vec![];
+        for table in &tables {
+            assert_eq!(std::fs::metadata(&table.path).unwrap().len(), size);
+            records.extend(crate::read(&table.path).unwrap());
+        }
+        assert_eq!(records, expected);
+
+        // Tables have at least one record
+        let tables = split("tests/data/stations.dbf", &dst_dir, SplitBy::Size(0)).unwrap();
+        assert_eq!(tables.len(), 6);
+    }
+
+    #[test]
+    fn split_by_unknown_field() {
+        let result = split(
+            "tests/data/stations.dbf",
+            std::env::temp_dir(),
+            SplitBy::FieldValue("NOPE"),
+        );
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,434 @@
+//! Module with the definition of fn's and struct's to export dBase files
+//! as SQL statements
+//!
+//! The [SqlExport](struct.SqlExport.html) creates the `CREATE TABLE` statement matching
+//! the fields of a table and writes its records as `INSERT` statements,
+//! or as text that can be loaded with PostgreSQL's `COPY` (or MySQL's `LOAD DATA`).
+use std::io::{Read, Seek, Write};
+
+use crate::error::Error;
+use crate::reading::{Reader, RecordValues};
+use crate::record::field::{FieldType, FieldValue};
+use crate::record::FieldInfo;
+
+/// The SQL dialects statements can be generated for
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SqlDialect {
+    Postgres,
+    MySql,
+    Sqlite,
+}
+
+impl SqlDialect {
+    /// Returns the identifier quoted for the dialect
+    pub fn quote_identifier(self, identifier: &str) -> String {
+        match self {
+            SqlDialect::Postgres | SqlDialect::Sqlite => {
+                format!("\"{}\"", identifier.replace('"', "\"\""))
+            }
+            SqlDialect::MySql => format!("`{}`", identifier.replace('`', "``")),
+        }
+    }
+
+    /// Returns the SQL type of the column the field is mapped to
+    pub fn column_type(self, field_info: &FieldInfo) -> String {
+        let length = field_info.field_length;
+        let decimals = u16::from(field_info.num_decimal_places).min(length);
+        match (self, field_info.field_type) {
+            (SqlDialect::Sqlite, FieldType::Character) | (SqlDialect::Sqlite, FieldType::Memo) => {
+                "TEXT".to_owned()
+            }
+            (_, FieldType::Character) => format!("VARCHAR({})", length.max(1)),
+            (SqlDialect::MySql, FieldType::Memo) => "LONGTEXT".to_owned(),
+            (_, FieldType::Memo) => "TEXT".to_owned(),
+            (SqlDialect::Sqlite, FieldType::Numeric)
+            | (SqlDialect::Sqlite, FieldType::Currency) => "NUMERIC".to_owned(),
+            (SqlDialect::Postgres, FieldType::Numeric) => {
+                format!("NUMERIC({}, {})", length.max(1), decimals)
+            }
+            (SqlDialect::MySql, FieldType::Numeric) => {
+                format!("DECIMAL({}, {})", length.clamp(1, 65), decimals.min(30))
+            }
+            (SqlDialect::Postgres, FieldType::Currency) => "NUMERIC(19, 4)".to_owned(),
+            (SqlDialect::MySql, FieldType::Currency) => "DECIMAL(19, 4)".to_owned(),
+            (SqlDialect::Postgres, FieldType::Float)
+            | (SqlDialect::Postgres, FieldType::Double)
+            | (SqlDialect::Postgres, FieldType::DBase7Double) => "DOUBLE PRECISION".to_owned(),
+            (SqlDialect::MySql, FieldType::Float)
+            | (SqlDialect::MySql, FieldType::Double)
+            | (SqlDialect::MySql, FieldType::DBase7Double) => "DOUBLE".to_owned(),
+            (SqlDialect::Sqlite, FieldType::Float)
+            | (SqlDialect::Sqlite, FieldType::Double)
+            | (SqlDialect::Sqlite, FieldType::DBase7Double) => "REAL".to_owned(),
+            (SqlDialect::MySql, FieldType::Integer)
+            | (SqlDialect::MySql, FieldType::Long)
+            | (SqlDialect::MySql, FieldType::Autoincrement) => "INT".to_owned(),
+            (_, FieldType::Integer) | (_, FieldType::Long) | (_, FieldType::Autoincrement) => {
+                "INTEGER".to_owned()
+            }
+            (SqlDialect::Sqlite, FieldType::Logical) => "INTEGER".to_owned(),
+            (_, FieldType::Logical) => "BOOLEAN".to_owned(),
+            (SqlDialect::Sqlite, FieldType::Date)
+            | (SqlDialect::Sqlite, FieldType::DateTime)
+            | (SqlDialect::Sqlite, FieldType::Timestamp) => "TEXT".to_owned(),
+            (_, FieldType::Date) => "DATE".to_owned(),
+            (SqlDialect::Postgres, FieldType::DateTime)
+            | (SqlDialect::Postgres, FieldType::Timestamp) => "TIMESTAMP".to_owned(),
+            (SqlDialect::MySql, FieldType::DateTime)
+            | (SqlDialect::MySql, FieldType::Timestamp) => "DATETIME".to_owned(),
+        }
+    }
+
+    fn string_literal(self, text: &str) -> String {
+        let text = text.replace('\'', "''");
+        match self {
+            // MySQL treats backslashes as escape characters in strings
+            SqlDialect::MySql => format!("'{}'", text.replace('\\', "\\\\")),
+            SqlDialect::Postgres | SqlDialect::Sqlite => format!("'{}'", text),
+        }
+    }
+
+    /// Returns the value as a SQL literal
+    pub fn literal(self, value: &FieldValue) -> String {
+        match value {
+            FieldValue::Character(Some(text)) | FieldValue::Memo(text) => self.string_literal(text),
+            FieldValue::Numeric(Some(value))
+            | FieldValue::Double(value)
+            | FieldValue::Currency(value)
+                if value.is_finite() =>
+            {
+                value.to_string()
+            }
+            FieldValue::Float(Some(value)) if value.is_finite() => value.to_string(),
+            FieldValue::Integer(value) => value.to_string(),
+            FieldValue::NumericInteger(Some(value)) => value.to_string(),
+            FieldValue::Logical(Some(value)) => match (self, value) {
+                (SqlDialect::Sqlite, true) => "1".to_owned(),
+                (SqlDialect::Sqlite, false) => "0".to_owned(),
+                (_, true) => "TRUE".to_owned(),
+                (_, false) => "FALSE".to_owned(),
+            },
+            FieldValue::Date(Some(_)) | FieldValue::DateTime(_) => {
+                self.string_literal(&text_value(value).unwrap_or_default())
+            }
+            _ => "NULL".to_owned(),
+        }
+    }
+}
+
+/// Returns the text of the value, as used in literals and `COPY` text,
+/// or `None` if the value is empty
+pub(crate) fn text_value(value: &FieldValue) -> Option<String> {
+    match value {
+        FieldValue::Character(Some(text)) | FieldValue::Memo(text) => Some(text.clone()),
+        FieldValue::Numeric(Some(value))
+        | FieldValue::Double(value)
+        | FieldValue::Currency(value)
+        | FieldValue::DBase7Double(Some(value))
+            if value.is_finite() =>
+        {
+            Some(value.to_string())
+        }
+        FieldValue::Float(Some(value)) if value.is_finite() => Some(value.to_string()),
+        FieldValue::Integer(value)
+        | FieldValue::Long(Some(value))
+        | FieldValue::Autoincrement(value) => Some(value.to_string()),
+        FieldValue::NumericInteger(Some(value)) => Some(value.to_string()),
+        FieldValue::Logical(Some(value)) => Some(if *value { "t" } else { "f" }.to_owned()),
+        FieldValue::Date(Some(date)) => Some(format!(
+            "{:04}-{:02}-{:02}",
+            date.year(),
+            date.month(),
+            date.day()
+        )),
+        FieldValue::DateTime(date_time) | FieldValue::Timestamp(Some(date_time)) => {
+            let (date, time) = (date_time.date(), date_time.time());
+            Some(format!(
+                "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+                date.year(),
+                date.month(),
+                date.day(),
+                time.hours(),
+                time.minutes(),
+                time.seconds()
+            ))
+        }
+        _ => None,
+    }
+}
+
+/// Generates the SQL statements to create a table and insert the records of a dBase file
+///
+/// The records marked as deleted are not exported.
+///
+/// # Example
+///
+/// ```
+/// use dbase::{SqlDialect, SqlExport};
+/// # fn main() -> Result<(), dbase::Error> {
+/// let mut reader = dbase::Reader::from_path("tests/data/line.dbf")?;
+/// let export = SqlExport::new("lines", SqlDialect::Postgres);
+///
+/// let mut sql = export.create_table(reader.fields()).into_bytes();
+/// export.write_inserts(&mut reader, &mut sql)?;
+/// assert!(sql.starts_with(b"CREATE TABLE \"lines\" (\n    \"name\" VARCHAR"));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct SqlExport {
+    table_name: String,
+    dialect: SqlDialect,
+    batch_size: usize,
+}
+
+impl SqlExport {
+    /// Number of records inserted by an `INSERT` statement, when not specified
+    pub const DEFAULT_BATCH_SIZE: usize = 500;
+
+    /// Creates the export to the table with the given name
+    pub fn new<S: Into<String>>(table_name: S, dialect: SqlDialect) -> Self {
+        Self {
+            table_name: table_name.into(),
+            dialect,
+            batch_size: Self::DEFAULT_BATCH_SIZE,
+        }
+    }
+
+    /// Sets the maximum number of records inserted by each `INSERT` statement
+    pub fn batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Returns the dialect of the statements
+    pub fn dialect(&self) -> SqlDialect {
+        self.dialect
+    }
+
+    fn columns<'a>(&self, fields_info: &'a [FieldInfo]) -> impl Iterator<Item = &'a FieldInfo> {
+        fields_info
+            .iter()
+            .filter(|field_info| !field_info.is_deletion_flag())
+    }
+
+    /// Returns the `CREATE TABLE` statement for a table with the given fields
+    pub fn create_table(&self, fields_info: &[FieldInfo]) -> String {
+        let columns = self
+            .columns(fields_info)
+            .map(|field_info| {
+                format!(
+                    "    {} {}",
+                    self.dialect.quote_identifier(field_info.name()),
+                    self.dialect.column_type(field_info)
+                )
+            })
+            .collect::<Vec<String>>();
+        format!(
+            "CREATE TABLE {} (\n{}\n);\n",
+            self.dialect.quote_identifier(&self.table_name),
+            columns.join(",\n")
+        )
+    }
+
+    /// Writes the remaining records of the reader as `INSERT` statements
+    /// of at most `batch_size` records each.
+    ///
+    /// Returns the number of records written.
+    pub fn write_inserts<T: Read + Seek, W: Write>(
+        &self,
+        reader: &mut Reader<T>,
+        mut dst: W,
+    ) -> Result<usize, Error> {
+        let column_names = self
+            .columns(reader.fields())
+            .map(|field_info| self.dialect.quote_identifier(field_info.name()))
+            .collect::<Vec<String>>();
+        let statement_start = format!(
+            "INSERT INTO {} ({}) VALUES\n",
+            self.dialect.quote_identifier(&self.table_name),
+            column_names.join(", ")
+        );
+
+        let mut num_records = 0;
+        let mut num_in_statement = 0;
+        while let Some(record) = reader.read_next_record_as::<RecordValues>() {
+            let record = record?;
+            if record.is_deleted {
+                continue;
+            }
+            let values = record
+                .values
+                .iter()
+                .map(|value| self.dialect.literal(value))
+                .collect::<Vec<String>>();
+            let result = if num_in_statement == 0 {
+                write!(dst, "{}({})", statement_start, values.join(", "))
+            } else {
+                write!(dst, ",\n({})", values.join(", "))
+            };
+            result.map_err(|error| Error::io_error(error, num_records))?;
+            num_in_statement += 1;
+            num_records += 1;
+            if num_in_statement == self.batch_size {
+                writeln!(dst, ";").map_err(|error| Error::io_error(error, num_records))?;
+                num_in_statement = 0;
+            }
+        }
+        if num_in_statement > 0 {
+            writeln!(dst, ";").map_err(|error| Error::io_error(error, num_records))?;
+        }
+        Ok(num_records)
+    }
+
+    /// Writes the remaining records of the reader in PostgreSQL's `COPY` text format:
+    /// one line per record, values separated by tabs and `\N` for empty values.
+    ///
+    /// The text can also be loaded using MySQL's `LOAD DATA`, in which case
+    /// logical values have to be converted.
+    ///
+    /// Returns the number of records written.
+    pub fn write_copy_text<T: Read + Seek, W: Write>(
+        &self,
+        reader: &mut Reader<T>,
+        mut dst: W,
+    ) -> Result<usize, Error> {
+        let mut num_records = 0;
+        let mut line = String::new();
+        while let Some(record) = reader.read_next_record_as::<RecordValues>() {
+            let record = record?;
+            if record.is_deleted {
+                continue;
+            }
+            line.clear();
+            for (i, value) in record.values.iter().enumerate() {
+                if i > 0 {
+                    line.push('\t');
+                }
+                match text_value(value) {
+                    Some(text) => {
+                        for c in text.chars() {
+                            match c {
+                                '\\' => line.push_str("\\\\"),
+                                '\t' => line.push_str("\\t"),
+                                '\n' => line.push_str("\\n"),
+                                '\r' => line.push_str("\\r"),
+                                c => line.push(c),
+                            }
+                        }
+                    }
+                    None => line.push_str("\\N"),
+                }
+            }
+            line.push('\n');
+            dst.write_all(line.as_bytes())
+                .map_err(|error| Error::io_error(error, num_records))?;
+            num_records += 1;
+        }
+        Ok(num_records)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::convert::TryFrom;
+    use std::io::Cursor;
+
+    use crate::record::field::Date;
+    use crate::{FieldName, Record, TableWriterBuilder};
+
+    fn table() -> Reader<Cursor<Vec<u8>>> {
+        let records = [
+            (
+                Some("O'Brien\\"),
+                Some(1.5),
+                Some(true),
+                Some(Date::new(2, 3, 2001)),
+            ),
+            (None, None, None, None),
+            (Some("Tab\there"), Some(-2.0), Some(false), None),
+        ]
+        .iter()
+        .map(|(name, price, paid, date)| {
+            let mut record = Record::default();
+            record.insert(
+                "NAME".to_owned(),
+                FieldValue::Character(name.map(str::to_owned)),
+            );
+            record.insert("PRICE".to_owned(), FieldValue::Numeric(*price));
+            record.insert("PAID".to_owned(), FieldValue::Logical(*paid));
+            record.insert("ON".to_owned(), FieldValue::Date(*date));
+            record
+        })
+        .collect::<Vec<Record>>();
+        let mut cursor = TableWriterBuilder::new()
+            .add_character_field(FieldName::try_from("NAME").unwrap(), 20)
+            .add_numeric_field(FieldName::try_from("PRICE").unwrap(), 8, 2)
+            .add_logical_field(FieldName::try_from("PAID").unwrap())
+            .add_date_field(FieldName::try_from("ON").unwrap())
+            .build_with_dest(Cursor::new(Vec::<u8>::new()))
+            .write(&records)
+            .unwrap();
+        cursor.set_position(0);
+        Reader::new(cursor).unwrap()
+    }
+
+    #[test]
+    fn create_table() {
+        let reader = table();
+        assert_eq!(
+            SqlExport::new("my \"table\"", SqlDialect::Postgres).create_table(reader.fields()),
+            "CREATE TABLE \"my \"\"table\"\"\" (\n    \"NAME\" VARCHAR(20),\n    \
+             \"PRICE\" NUMERIC(8, 2),\n    \"PAID\" BOOLEAN,\n    \"ON\" DATE\n);\n"
+        );
+        assert_eq!(
+            SqlExport::new("items", SqlDialect::MySql).create_table(reader.fields()),
+            "CREATE TABLE `items` (\n    `NAME` VARCHAR(20),\n    \
+             `PRICE` DECIMAL(8, 2),\n    `PAID` BOOLEAN,\n    `ON` DATE\n);\n"
+        );
+        assert_eq!(
+            SqlExport::new("items", SqlDialect::Sqlite).create_table(reader.fields()),
+            "CREATE TABLE \"items\" (\n    \"NAME\" TEXT,\n    \
+             \"PRICE\" NUMERIC,\n    \"PAID\" INTEGER,\n    \"ON\" TEXT\n);\n"
+        );
+    }
+
+    #[test]
+    fn insert_statements() {
+        let mut sql = Vec::<u8>::new();
+        let num_records = SqlExport::new("items", SqlDialect::Postgres)
+            .batch_size(2)
+            .write_inserts(&mut table(), &mut sql)
+            .unwrap();
+        assert_eq!(num_records, 3);
+        assert_eq!(
+            String::from_utf8(sql).unwrap(),
+            "INSERT INTO \"items\" (\"NAME\", \"PRICE\", \"PAID\", \"ON\") VALUES\n\
+             ('O''Brien\\', 1.5, TRUE, '2001-03-02'),\n\
+             (NULL, NULL, NULL, NULL);\n\
+             INSERT INTO \"items\" (\"NAME\", \"PRICE\", \"PAID\", \"ON\") VALUES\n\
+             ('Tab\there', -2, FALSE, NULL);\n"
+        );
+
+        let mut sql = Vec::<u8>::new();
+        SqlExport::new("items", SqlDialect::MySql)
+            .write_inserts(&mut table(), &mut sql)
+            .unwrap();
+        let sql = String::from_utf8(sql).unwrap();
+        assert!(sql.contains("('O''Brien\\\\', 1.5, TRUE, '2001-03-02'),\n"));
+    }
+
+    #[test]
+    fn copy_text() {
+        let mut text = Vec::<u8>::new();
+        SqlExport::new("items", SqlDialect::Postgres)
+            .write_copy_text(&mut table(), &mut text)
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(text).unwrap(),
+            "O'Brien\\\\\t1.5\tt\t2001-03-02\n\\N\t\\N\t\\N\t\\N\nTab\\there\t-2\tf\t\\N\n"
+        );
+    }
+}
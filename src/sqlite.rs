@@ -0,0 +1,205 @@
+//! Module with the definition of fn's to export dBase files to SQLite databases
+//!
+//! The columns of the table created are the ones of the
+//! [Sqlite dialect](enum.SqlDialect.html) of the SQL export.
+use std::io::{Read, Seek};
+use std::path::Path;
+
+use rusqlite::types::Value;
+use rusqlite::Connection;
+
+use crate::error::{Error, ErrorKind};
+use crate::reading::{Reader, RecordValues};
+use crate::record::field::FieldValue;
+use crate::sql::{text_value, SqlDialect, SqlExport};
+
+fn sqlite_error(error: rusqlite::Error, record_num: usize) -> Error {
+    Error {
+        record_num,
+        field: None,
+        kind: ErrorKind::Message(error.to_string()),
+    }
+}
+
+fn sqlite_value(value: &FieldValue) -> Value {
+    match value {
+        FieldValue::Numeric(Some(value))
+        | FieldValue::Double(value)
+        | FieldValue::Currency(value)
+        | FieldValue::DBase7Double(Some(value))
+            if value.is_finite() =>
+        {
+            Value::Real(*value)
+        }
+        FieldValue::Float(Some(value)) if value.is_finite() => Value::Real(f64::from(*value)),
+        FieldValue::Integer(value)
+        | FieldValue::Long(Some(value))
+        | FieldValue::Autoincrement(value) => Value::Integer(i64::from(*value)),
+        FieldValue::NumericInteger(Some(value)) => Value::Integer(*value),
+        FieldValue::Logical(Some(value)) => Value::Integer(i64::from(*value)),
+        value => text_value(value).map_or(Value::Null, Value::Text),
+    }
+}
+
+impl<T: Read + Seek> Reader<T> {
+    /// Creates the table `table_name` in the SQLite database at `db_path` (which is
+    /// created if needed) and inserts the remaining records in it, in one transaction.
+    ///
+    /// The records marked as deleted are not inserted.
+    /// Returns the number of records inserted.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # fn main() -> Result<(), dbase::Error> {
+    /// let mut reader = dbase::Reader::from_path("tests/data/line.dbf")?;
+    /// let num_records = reader.export_to_sqlite("lines.sqlite", "lines")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn export_to_sqlite<P: AsRef<Path>>(
+        &mut self,
+        db_path: P,
+        table_name: &str,
+    ) -> Result<usize, Error> {
+        let mut connection = Connection::open(db_path).map_err(|error| sqlite_error(error, 0))?;
+        self.export_to_sqlite_connection(&mut connection, table_name)
+    }
+
+    /// Same as [export_to_sqlite](#method.export_to_sqlite) but using an opened connection
+    pub fn export_to_sqlite_connection(
+        &mut self,
+        connection: &mut Connection,
+        table_name: &str,
+    ) -> Result<usize, Error> {
+        let export = SqlExport::new(table_name, SqlDialect::Sqlite);
+        let transaction = connection
+            .transaction()
+            .map_err(|error| sqlite_error(error, 0))?;
+        transaction
+            .execute_batch(&export.create_table(&self.fields_info))
+            .map_err(|error| sqlite_error(error, 0))?;
+
+        let columns = self
+            .fields_info
+            .iter()
+            .filter(|field_info| !field_info.is_deletion_flag())
+            .map(|field_info| SqlDialect::Sqlite.quote_identifier(field_info.name()))
+            .collect::<Vec<String>>();
+        let insert = format!(
+            "INSERT INTO {} ({}) VALUES ({})",
+            SqlDialect::Sqlite.quote_identifier(table_name),
+            columns.join(", "),
+            vec!["?"; columns.len()].join(", ")
+        );
+
+        let mut num_records = 0;
+        {
+            let mut statement = transaction
+                .prepare(&insert)
+                .map_err(|error| sqlite_error(error, 0))?;
+            while let Some(record) = self.read_next_record_as::<RecordValues>() {
+                let record = record?;
+                if record.is_deleted {
+                    continue;
+                }
+                statement
+                    .execute(rusqlite::params_from_iter(
+                        record.values.iter().map(sqlite_value),
+                    ))
+                    .map_err(|error| sqlite_error(error, num_records))?;
+                num_records += 1;
+            }
+        }
+        transaction
+            .commit()
+            .map_err(|error| sqlite_error(error, num_records))?;
+        Ok(num_records)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::convert::TryFrom;
+    use std::io::Cursor;
+
+    use crate::record::field::Date;
+    use crate::{FieldName, Record, TableWriterBuilder};
+
+    #[test]
+    fn export_to_sqlite() {
+        let records = [
+            (
+                Some("Ferris"),
+                Some(1.5),
+                Some(true),
+                Some(Date::new(15, 5, 2015)),
+            ),
+            (None, None, None, None),
+        ]
+        .iter()
+        .map(|(name, price, paid, date)| {
+            let mut record = Record::default();
+            record.insert(
+                "NAME".to_owned(),
+                FieldValue::Character(name.map(str::to_owned)),
+            );
+            record.insert("PRICE".to_owned(), FieldValue::Numeric(*price));
+            record.insert("PAID".to_owned(), FieldValue::Logical(*paid));
+            record.insert("ON".to_owned(), FieldValue::Date(*date));
+            record
+        })
+        .collect::<Vec<Record>>();
+        let mut cursor = TableWriterBuilder::new()
+            .add_character_field(FieldName::try_from("NAME").unwrap(), 20)
+            .add_numeric_field(FieldName::try_from("PRICE").unwrap(), 8, 2)
+            .add_logical_field(FieldName::try_from("PAID").unwrap())
+            .add_date_field(FieldName::try_from("ON").unwrap())
+            .build_with_dest(Cursor::new(Vec::<u8>::new()))
+            .write(&records)
+            .unwrap();
+        cursor.set_position(0);
+
+        let path = std::env::temp_dir().join("dbase_export_to_sqlite.sqlite");
+        let _ = std::fs::remove_file(&path);
+        let num_records = Reader::new(cursor)
+            .unwrap()
+            .export_to_sqlite(&path, "items")
+            .unwrap();
+        assert_eq!(num_records, 2);
+
+        let connection = Connection::open(&path).unwrap();
+        let rows = connection
+            .prepare("SELECT NAME, PRICE, PAID, \"ON\" FROM items ORDER BY rowid")
+            .unwrap()
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, Option<String>>(0)?,
+                    row.get::<_, Option<f64>>(1)?,
+                    row.get::<_, Option<bool>>(2)?,
+                    row.get::<_, Option<String>>(3)?,
+                ))
+            })
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(
+            rows,
+            vec![
+                (
+                    Some("Ferris".to_owned()),
+                    Some(1.5),
+                    Some(true),
+                    Some("2015-05-15".to_owned())
+                ),
+                (None, None, None, None),
+            ]
+        );
+
+        // The table already exists, nothing is inserted
+        let mut reader = Reader::from_path("tests/data/line.dbf").unwrap();
+        assert!(reader.export_to_sqlite(&path, "items").is_err());
+    }
+}
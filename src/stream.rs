@@ -0,0 +1,317 @@
+//! Module with the definition of the source used to read dBase files
+//! from streams that cannot seek
+//!
+//! This is the case of network bodies, pipes or decompressors (eg: `flate2::read::GzDecoder`).
+//!
+//! The records of streams can only be read in order: what needs more than that
+//! is a [Capability](enum.Capability.html) the readers of streams do not have.
+use std::io::{Read, Seek, SeekFrom};
+
+use crate::error::{Error, ErrorKind};
+use crate::reading::{Reader, ReadingOptions};
+
+/// Number of the last bytes read kept to be able to seek back,
+/// enough to go back by one field
+const HISTORY_SIZE: usize = u8::MAX as usize;
+
+/// Adapter that provides a [Seek](https://doc.rust-lang.org/std/io/trait.Seek.html)
+/// implementation on top of a stream that can only be read sequentially.
+///
+/// Seeking forward is done by reading and discarding the bytes,
+/// seeking back is only possible within the last bytes read
+/// (which is enough to go back by one field), any other seek is an error.
+///
+/// This means that the records can only be read in order with
+/// a [Reader](struct.Reader.html), and that memo fields are not available
+/// as their content is stored in another file.
+#[derive(Debug)]
+pub struct SequentialSource<R: Read> {
+    inner: R,
+    /// Position in the stream of the next byte that will be read
+    position: u64,
+    /// The last bytes read, in order
+    history: Vec<u8>,
+    /// Number of bytes at the end of the history that have to be read again
+    num_replayed: usize,
+}
+
+impl<R: Read> SequentialSource<R> {
+    /// Wraps the stream, it is expected to be at its start
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner,
+            position: 0,
+            history: Vec::with_capacity(HISTORY_SIZE * 2),
+            num_replayed: 0,
+        }
+    }
+
+    /// Returns the position in the stream
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// Returns the wrapped stream
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    fn remember(&mut self, bytes: &[u8]) {
+        self.history.extend_from_slice(bytes);
+        if self.history.len() > HISTORY_SIZE * 2 {
+            let excess = self.history.len() - HISTORY_SIZE;
+            self.history.drain(..excess);
+        }
+    }
+
+    fn skip(&mut self, mut num_bytes: u64) -> std::io::Result<()> {
+        let mut buffer = [0u8; 512];
+        while num_bytes > 0 {
+            let chunk_size = num_bytes.min(buffer.len() as u64) as usize;
+            self.read_exact(&mut buffer[..chunk_size])?;
+            num_bytes -= chunk_size as u64;
+        }
+        Ok(())
+    }
+}
+
+impl<R: Read> Read for SequentialSource<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let num_read = if self.num_replayed > 0 {
+            let start = self.history.len() - self.num_replayed;
+            let num_read = self.num_replayed.min(buf.len());
+            buf[..num_read].copy_from_slice(&self.history[start..start + num_read]);
+            self.num_replayed -= num_read;
+            num_read
+        } else {
+            let num_read = self.inner.read(buf)?;
+            self.remember(&buf[..num_read]);
+            num_read
+        };
+        self.position += num_read as u64;
+        Ok(num_read)
+    }
+}
+
+impl<R: Read> Seek for SequentialSource<R> {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        let target = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::Current(offset) => self.position.checked_add_signed(offset),
+            SeekFrom::End(_) => None,
+        };
+        let target = target.ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                "cannot seek relative to the end of a sequential stream",
+            )
+        })?;
+
+        if target >= self.position {
+            self.skip(target - self.position)?;
+        } else {
+            let num_back = self.position - target;
+            let num_available = (self.history.len() - self.num_replayed) as u64;
+            if num_back > num_available {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::Unsupported,
+                    "cannot seek back in a sequential stream",
+                ));
+            }
+            self.num_replayed += num_back as usize;
+            self.position = target;
+        }
+        Ok(self.position)
+    }
+}
+
+/// What a reader can do depending on its source,
+/// see [Reader::supports](struct.Reader.html#method.supports)
+///
+/// The operations that need a capability the reader does not have return an
+/// [Unsupported](enum.ErrorKind.html#variant.Unsupported) error.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Capability {
+    /// Going back to records already read: [record_at](struct.Reader.html#method.record_at)
+    /// for an earlier record, [index_by](struct.Reader.html#method.index_by),
+    /// the lookups of indexes and [set_record_range](struct.Reader.html#method.set_record_range)
+    /// once records were read
+    RandomAccess,
+    /// Skipping records without reading them,
+    /// [skip_records](struct.Reader.html#method.skip_records) reads the records of streams
+    FastSkip,
+    /// Reading the content of memo fields, which is stored in the memo file
+    Memo,
+}
+
+impl Capability {
+    /// Returns what is not available without the capability
+    pub fn description(&self) -> &'static str {
+        match self {
+            Capability::RandomAccess => "the records can only be read in order",
+            Capability::FastSkip => "the records are read to be skipped",
+            Capability::Memo => "the content of memo fields cannot be read",
+        }
+    }
+}
+
+impl<T: Read + Seek> Reader<T> {
+    /// Returns whether the reader has the capability
+    ///
+    /// The readers made with [from_stream](#method.from_stream) can only read the records
+    /// in order, the readers without a memo file cannot read memo fields.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), dbase::Error> {
+    /// use dbase::Capability;
+    ///
+    /// let reader = dbase::Reader::from_path("tests/data/line.dbf")?;
+    /// assert!(reader.supports(Capability::RandomAccess));
+    ///
+    /// let file = std::fs::File::open("tests/data/line.dbf").unwrap();
+    /// let reader = dbase::Reader::from_stream(file)?;
+    /// assert!(!reader.supports(Capability::RandomAccess));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn supports(&self, capability: Capability) -> bool {
+        match capability {
+            Capability::RandomAccess | Capability::FastSkip => !self.is_stream,
+            Capability::Memo => self.memo_reader.is_some(),
+        }
+    }
+
+    /// Returns an error telling that the operation needs the capability,
+    /// if the reader does not have it
+    pub(crate) fn require(&self, capability: Capability, record_num: usize) -> Result<(), Error> {
+        if self.supports(capability) {
+            Ok(())
+        } else {
+            Err(Error {
+                record_num,
+                field: None,
+                kind: ErrorKind::Unsupported(capability),
+            })
+        }
+    }
+}
+
+impl<R: Read> Reader<SequentialSource<R>> {
+    /// Creates a reader from a stream that cannot seek, such as a
+    /// decompressor, a pipe or the body of a network response.
+    ///
+    /// The records can only be read in order and memo fields are not supported,
+    /// see [Capability](enum.Capability.html).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), dbase::Error> {
+    /// let file = std::fs::File::open("tests/data/line.dbf").unwrap();
+    /// let mut reader = dbase::Reader::from_stream(file)?;
+    /// let records = reader.read()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// Reading a table piped to the standard input:
+    ///
+    /// ```no_run
+    /// # fn main() -> Result<(), dbase::Error> {
+    /// let mut reader = dbase::Reader::from_stream(std::io::stdin().lock())?;
+    /// for record in reader.iter_records() {
+    ///     println!("{:?}", record?);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_stream(source: R) -> Result<Self, Error> {
+        Self::from_stream_with_options(source, ReadingOptions::default())
+    }
+
+    /// Same as [from_stream](#method.from_stream) but the content is read using the given options
+    pub fn from_stream_with_options(source: R, options: ReadingOptions) -> Result<Self, Error> {
+        let mut reader = Reader::with_options(SequentialSource::new(source), options)?;
+        reader.is_stream = true;
+        Ok(reader)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::io::Cursor;
+
+    #[test]
+    fn sequential_source_seeks() {
+        let content = (0..=255u8).cycle().take(2000).collect::<Vec<u8>>();
+        let mut source = SequentialSource::new(&content[..]);
+
+        let mut buffer = [0u8; 4];
+        source.seek(SeekFrom::Start(10)).unwrap();
+        source.read_exact(&mut buffer).unwrap();
+        assert_eq!(buffer, [10, 11, 12, 13]);
+
+        source.seek(SeekFrom::Current(-3)).unwrap();
+        source.read_exact(&mut buffer).unwrap();
+        assert_eq!(buffer, [11, 12, 13, 14]);
+
+        source.seek(SeekFrom::Current(1000)).unwrap();
+        assert_eq!(source.position(), 1015);
+        source.read_exact(&mut buffer).unwrap();
+        assert_eq!(buffer, [247, 248, 249, 250]);
+
+        assert!(source.seek(SeekFrom::Start(0)).is_err());
+        assert!(source.seek(SeekFrom::End(0)).is_err());
+        assert_eq!(source.stream_position().unwrap(), 1019);
+    }
+
+    #[test]
+    fn read_from_stream() {
+        let content = std::fs::read("tests/data/line.dbf").unwrap();
+        let expected = Reader::new(Cursor::new(content.clone()))
+            .unwrap()
+            .read()
+            .unwrap();
+
+        let mut reader = Reader::from_stream(&content[..]).unwrap();
+        assert_eq!(reader.read().unwrap(), expected);
+    }
+
+    #[test]
+    fn streams_only_go_forward() {
+        let content = std::fs::read("tests/data/stations.dbf").unwrap();
+        let expected = Reader::new(Cursor::new(content.clone()))
+            .unwrap()
+            .read()
+            .unwrap();
+
+        let mut reader = Reader::from_stream(&content[..]).unwrap();
+        assert!(!reader.supports(Capability::RandomAccess));
+        assert!(!reader.supports(Capability::FastSkip));
+        assert!(!reader.supports(Capability::Memo));
+
+        // Going forward reads the records in between
+        assert_eq!(reader.skip_records(1).unwrap(), 1);
+        assert_eq!(reader.record_at(2).unwrap(), expected[2]);
+        assert_eq!(reader.record_bytes_at(3).unwrap().len(), reader.record_length());
+
+        let error = reader.record_at(1).unwrap_err();
+        assert!(matches!(
+            error.kind(),
+            ErrorKind::Unsupported(Capability::RandomAccess)
+        ));
+        assert_eq!(error.record_num(), 1);
+        assert!(matches!(
+            reader.index_by("name").unwrap_err().kind(),
+            ErrorKind::Unsupported(Capability::RandomAccess)
+        ));
+        assert!(reader.record_bytes_at(0).is_err());
+
+        // The failed calls did not move the reader
+        assert_eq!(reader.read().unwrap(), &expected[4..]);
+    }
+}
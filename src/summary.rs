@@ -0,0 +1,374 @@
+//! Module with the definition of fn's and struct's to summarize
+//! the values of each field of dBase files
+//!
+//! The records are read once, the number of distinct values is estimated
+//! with a [HyperLogLog](https://en.wikipedia.org/wiki/HyperLogLog) sketch
+//! so that tables of any size are summarized with a fixed amount of memory per field.
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::io::{Read, Seek};
+use std::path::Path;
+
+use crate::error::Error;
+use crate::reading::{Reader, RecordValues};
+use crate::record::field::{FieldType, FieldValue};
+
+/// Number of bits of the hashes used to select the register of the sketch
+const SKETCH_PRECISION: u32 = 12;
+const SKETCH_NUM_REGISTERS: usize = 1 << SKETCH_PRECISION;
+
+/// HyperLogLog sketch estimating the number of distinct values added,
+/// the error is around 1.6%
+struct DistinctCounter {
+    registers: Vec<u8>,
+}
+
+impl DistinctCounter {
+    fn new() -> Self {
+        Self {
+            registers: vec![0u8; SKETCH_NUM_REGISTERS],
+        }
+    }
+
+    fn add(&mut self, hash: u64) {
+        let index = (hash >> (64 - SKETCH_PRECISION)) as usize;
+        // The bit set makes the rank at most 64 - SKETCH_PRECISION + 1
+        let rank = ((hash << SKETCH_PRECISION) | (1 << (SKETCH_PRECISION - 1))).leading_zeros() + 1;
+        self.registers[index] = self.registers[index].max(rank as u8);
+    }
+
+    fn estimate(&self) -> usize {
+        let num_registers = SKETCH_NUM_REGISTERS as f64;
+        let alpha = 0.7213 / (1.0 + 1.079 / num_registers);
+        let sum = self
+            .registers
+            .iter()
+            .map(|register| 2f64.powi(-i32::from(*register)))
+            .sum::<f64>();
+        let estimate = alpha * num_registers * num_registers / sum;
+        let num_zeros = self.registers.iter().filter(|r| **r == 0).count();
+        // For small cardinalities, linear counting is way more precise
+        if estimate <= 2.5 * num_registers && num_zeros > 0 {
+            (num_registers * (num_registers / num_zeros as f64).ln()).round() as usize
+        } else {
+            estimate.round() as usize
+        }
+    }
+}
+
+/// Key used to find the minimum and maximum values,
+/// the keys of the values of a field are all of the same variant
+#[derive(Copy, Clone, PartialEq, PartialOrd)]
+enum SortKey {
+    Number(f64),
+    /// year, month, day, hours, minutes, seconds
+    Moment([u32; 6]),
+}
+
+/// Returns the key of the values that have a minimum and maximum
+fn sort_key(value: &FieldValue) -> Option<SortKey> {
+    let key = match value {
+        FieldValue::Numeric(Some(n)) | FieldValue::Currency(n) | FieldValue::Double(n) => {
+            SortKey::Number(*n)
+        }
+        FieldValue::Float(Some(f)) => SortKey::Number(f64::from(*f)),
+        FieldValue::DBase7Double(Some(d)) => SortKey::Number(*d),
+        FieldValue::Integer(i) | FieldValue::Long(Some(i)) | FieldValue::Autoincrement(i) => {
+            SortKey::Number(f64::from(*i))
+        }
+        FieldValue::NumericInteger(Some(i)) => SortKey::Number(*i as f64),
+        FieldValue::Date(Some(d)) => SortKey::Moment([d.year(), d.month(), d.day(), 0, 0, 0]),
+        FieldValue::DateTime(dt) | FieldValue::Timestamp(Some(dt)) => {
+            let (date, time) = (dt.date(), dt.time());
+            SortKey::Moment([
+                date.year(),
+                date.month(),
+                date.day(),
+                time.hours(),
+                time.minutes(),
+                time.seconds(),
+            ])
+        }
+        _ => return None,
+    };
+    match key {
+        SortKey::Number(n) if n.is_nan() => None,
+        _ => Some(key),
+    }
+}
+
+/// Copies the values that have a sort key
+fn copy_value(value: &FieldValue) -> Option<FieldValue> {
+    match value {
+        FieldValue::Numeric(n) => Some(FieldValue::Numeric(*n)),
+        FieldValue::Float(f) => Some(FieldValue::Float(*f)),
+        FieldValue::Integer(i) => Some(FieldValue::Integer(*i)),
+        FieldValue::NumericInteger(i) => Some(FieldValue::NumericInteger(*i)),
+        FieldValue::Currency(c) => Some(FieldValue::Currency(*c)),
+        FieldValue::Double(d) => Some(FieldValue::Double(*d)),
+        FieldValue::Date(d) => Some(FieldValue::Date(*d)),
+        FieldValue::DateTime(dt) => Some(FieldValue::DateTime(*dt)),
+        FieldValue::Long(i) => Some(FieldValue::Long(*i)),
+        FieldValue::DBase7Double(d) => Some(FieldValue::DBase7Double(*d)),
+        FieldValue::Timestamp(dt) => Some(FieldValue::Timestamp(*dt)),
+        FieldValue::Autoincrement(i) => Some(FieldValue::Autoincrement(*i)),
+        _ => None,
+    }
+}
+
+/// Hashes the value, `None` for empty values
+fn hash_value(value: &FieldValue) -> Option<u64> {
+    let mut hasher = DefaultHasher::new();
+    match value {
+        FieldValue::Character(Some(s)) => s.hash(&mut hasher),
+        FieldValue::Memo(s) if !s.is_empty() => s.hash(&mut hasher),
+        FieldValue::Numeric(Some(n)) => n.to_bits().hash(&mut hasher),
+        FieldValue::Float(Some(f)) => f.to_bits().hash(&mut hasher),
+        FieldValue::Logical(Some(b)) => b.hash(&mut hasher),
+        FieldValue::Date(Some(d)) => (d.year(), d.month(), d.day()).hash(&mut hasher),
+        FieldValue::Integer(i) | FieldValue::Long(Some(i)) | FieldValue::Autoincrement(i) => {
+            i.hash(&mut hasher)
+        }
+        FieldValue::DBase7Double(Some(d)) => d.to_bits().hash(&mut hasher),
+        FieldValue::NumericInteger(Some(i)) => i.hash(&mut hasher),
+        FieldValue::Currency(c) | FieldValue::Double(c) => c.to_bits().hash(&mut hasher),
+        FieldValue::DateTime(dt) | FieldValue::Timestamp(Some(dt)) => {
+            let (date, time) = (dt.date(), dt.time());
+            (date.year(), date.month(), date.day()).hash(&mut hasher);
+            (time.hours(), time.minutes(), time.seconds()).hash(&mut hasher);
+        }
+        _ => return None,
+    }
+    Some(hasher.finish())
+}
+
+/// Summary of the values of a field
+#[derive(Debug, PartialEq)]
+pub struct FieldSummary {
+    /// The name of the field
+    pub name: String,
+    /// The type of the field
+    pub field_type: FieldType,
+    /// The number of empty values
+    /// (blank strings, numbers, dates and logicals, empty memos)
+    pub num_nulls: usize,
+    /// The estimated number of distinct non-empty values
+    pub approx_num_distinct: usize,
+    /// The smallest value, for numbers, dates and date times
+    pub min: Option<FieldValue>,
+    /// The largest value, for numbers, dates and date times
+    pub max: Option<FieldValue>,
+    /// The number of chars of the longest value, for Character and Memo fields
+    pub max_length: Option<usize>,
+}
+
+/// Summary of the values of the fields of a table, made by
+/// [Reader::summarize](struct.Reader.html#method.summarize)
+#[derive(Debug, PartialEq)]
+pub struct TableSummary {
+    /// The number of records summarized, the records marked as deleted are not summarized
+    pub num_records: usize,
+    /// The number of records marked as deleted
+    pub num_deleted_records: usize,
+    /// The summaries of the fields, in the order of the fields
+    pub fields: Vec<FieldSummary>,
+}
+
+impl TableSummary {
+    /// Returns the summary of the field with the given name
+    pub fn field(&self, name: &str) -> Option<&FieldSummary> {
+        self.fields.iter().find(|summary| summary.name == name)
+    }
+}
+
+struct FieldStatistics {
+    summary: FieldSummary,
+    distinct: DistinctCounter,
+    min_key: Option<SortKey>,
+    max_key: Option<SortKey>,
+}
+
+impl FieldStatistics {
+    fn add(&mut self, value: &FieldValue) {
+        match hash_value(value) {
+            Some(hash) => self.distinct.add(hash),
+            None => self.summary.num_nulls += 1,
+        }
+        if let (FieldValue::Character(Some(s)) | FieldValue::Memo(s), Some(max_length)) =
+            (value, self.summary.max_length.as_mut())
+        {
+            *max_length = (*max_length).max(s.chars().count());
+        }
+        if let Some(key) = sort_key(value) {
+            if self.min_key.is_none_or(|min| key < min) {
+                self.min_key = Some(key);
+                self.summary.min = copy_value(value);
+            }
+            if self.max_key.is_none_or(|max| key > max) {
+                self.max_key = Some(key);
+                self.summary.max = copy_value(value);
+            }
+        }
+    }
+}
+
+impl<T: Read + Seek> Reader<T> {
+    /// Reads the remaining records and summarizes the values of each field
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dbase::FieldValue;
+    /// # fn main() -> Result<(), dbase::Error> {
+    /// let mut reader = dbase::Reader::from_path("tests/data/stations.dbf")?;
+    /// let summary = reader.summarize()?;
+    /// assert_eq!(summary.num_records, 6);
+    /// let name = summary.field("name").unwrap();
+    /// assert_eq!(name.approx_num_distinct, 6);
+    /// assert_eq!(name.min, None);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn summarize(&mut self) -> Result<TableSummary, Error> {
+        let mut statistics = self
+            .fields_info
+            .iter()
+            .filter(|field_info| !field_info.is_deletion_flag())
+            .map(|field_info| FieldStatistics {
+                summary: FieldSummary {
+                    name: field_info.name().to_owned(),
+                    field_type: field_info.field_type,
+                    num_nulls: 0,
+                    approx_num_distinct: 0,
+                    min: None,
+                    max: None,
+                    max_length: match field_info.field_type {
+                        FieldType::Character | FieldType::Memo => Some(0),
+                        _ => None,
+                    },
+                },
+                distinct: DistinctCounter::new(),
+                min_key: None,
+                max_key: None,
+            })
+            .collect::<Vec<FieldStatistics>>();
+
+        let mut num_records = 0;
+        let mut num_deleted_records = 0;
+        while let Some(record) = self.read_next_record_as::<RecordValues>() {
+            let record = record?;
+            if record.is_deleted {
+                num_deleted_records += 1;
+                continue;
+            }
+            for (field_statistics, value) in statistics.iter_mut().zip(&record.values) {
+                field_statistics.add(value);
+            }
+            num_records += 1;
+        }
+
+        let fields = statistics
+            .into_iter()
+            .map(|field_statistics| FieldSummary {
+                approx_num_distinct: field_statistics.distinct.estimate(),
+                ..field_statistics.summary
+            })
+            .collect();
+        Ok(TableSummary {
+            num_records,
+            num_deleted_records,
+            fields,
+        })
+    }
+}
+
+/// One liner to summarize the values of each field of the file at the given path
+pub fn summarize<P: AsRef<Path>>(path: P) -> Result<TableSummary, Error> {
+    Reader::from_path(path)?.summarize()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::convert::TryFrom;
+    use std::io::Cursor;
+
+    use crate::{Date, FieldName, Record, TableWriterBuilder};
+
+    #[test]
+    fn estimate_distinct_values() {
+        let mut counter = DistinctCounter::new();
+        assert_eq!(counter.estimate(), 0);
+        for i in 0..10_000u32 {
+            counter.add(hash_value(&FieldValue::Integer((i % 5_000) as i32)).unwrap());
+        }
+        let estimate = counter.estimate() as f64;
+        assert!((estimate - 5_000.0).abs() < 250.0, "{}", estimate);
+    }
+
+    #[test]
+    fn summary_of_fields() {
+        let values = [
+            (Some("Ferris"), Some(1.5), Some(Date::new(15, 5, 2015))),
+            (Some("Corro"), Some(-2.0), None),
+            (None, None, Some(Date::new(1, 1, 2000))),
+            (Some("Ferris"), Some(3.25), Some(Date::new(24, 12, 2021))),
+        ];
+        let records = values
+            .iter()
+            .map(|(name, price, date)| {
+                let mut record = Record::default();
+                record.insert(
+                    "NAME".to_owned(),
+                    FieldValue::Character(name.map(str::to_owned)),
+                );
+                record.insert("PRICE".to_owned(), FieldValue::Numeric(*price));
+                record.insert("SINCE".to_owned(), FieldValue::Date(*date));
+                record
+            })
+            .collect::<Vec<Record>>();
+        let content = TableWriterBuilder::new()
+            .add_character_field(FieldName::try_from("NAME").unwrap(), 10)
+            .add_numeric_field(FieldName::try_from("PRICE").unwrap(), 8, 2)
+            .add_date_field(FieldName::try_from("SINCE").unwrap())
+            .build_with_dest(Cursor::new(Vec::<u8>::new()))
+            .write(&records)
+            .unwrap()
+            .into_inner();
+
+        let summary = Reader::new(Cursor::new(content))
+            .unwrap()
+            .summarize()
+            .unwrap();
+        assert_eq!(summary.num_records, 4);
+        assert_eq!(summary.num_deleted_records, 0);
+        assert_eq!(
+            summary.field("NAME"),
+            Some(&FieldSummary {
+                name: "NAME".to_owned(),
+                field_type: FieldType::Character,
+                num_nulls: 1,
+                approx_num_distinct: 2,
+                min: None,
+                max: None,
+                max_length: Some(6),
+            })
+        );
+        let price = summary.field("PRICE").unwrap();
+        assert_eq!(price.num_nulls, 1);
+        assert_eq!(price.approx_num_distinct, 3);
+        assert_eq!(price.min, Some(FieldValue::Numeric(Some(-2.0))));
+        assert_eq!(price.max, Some(FieldValue::Numeric(Some(3.25))));
+        assert_eq!(price.max_length, None);
+        let since = summary.field("SINCE").unwrap();
+        assert_eq!(
+            since.min,
+            Some(FieldValue::Date(Some(Date::new(1, 1, 2000))))
+        );
+        assert_eq!(
+            since.max,
+            Some(FieldValue::Date(Some(Date::new(24, 12, 2021))))
+        );
+    }
+}
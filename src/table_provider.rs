@@ -0,0 +1,236 @@
+//! Module with the definition of the [DataFusion](https://datafusion.apache.org)
+//! table provider of dBase files
+//!
+//! The columns have the types of the [Arrow conversion](fn.arrow_data_type.html),
+//! only the columns used by a query are decoded.
+use std::any::Any;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::Arc;
+
+use arrow_array::RecordBatch;
+use arrow_schema::SchemaRef;
+use datafusion::catalog::{Session, TableProvider};
+use datafusion::error::{DataFusionError, Result};
+use datafusion::execution::TaskContext;
+use datafusion::logical_expr::{Expr, TableType};
+use datafusion::physical_expr::LexOrdering;
+use datafusion::physical_plan::stream::RecordBatchStreamAdapter;
+use datafusion::physical_plan::streaming::{PartitionStream, StreamingTableExec};
+use datafusion::physical_plan::{ExecutionPlan, SendableRecordBatchStream};
+
+use crate::error::Error;
+use crate::reading::{Reader, ReadingOptions};
+
+/// DataFusion table provider reading a dBase file
+///
+/// The file is read again each time the table is scanned.
+///
+/// # Example
+///
+/// ```
+/// # extern crate datafusion;
+/// # extern crate dbase;
+/// # extern crate tokio;
+/// # use std::sync::Arc;
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let table = dbase::DbaseTableProvider::from_path("tests/data/stations.dbf")?;
+/// let context = datafusion::prelude::SessionContext::new();
+/// context.register_table("stations", Arc::new(table))?;
+///
+/// let runtime = tokio::runtime::Builder::new_current_thread().build()?;
+/// let query = runtime.block_on(context.sql("SELECT name FROM stations LIMIT 2"))?;
+/// let batches = runtime.block_on(query.collect())?;
+/// assert_eq!(batches[0].num_rows(), 2);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct DbaseTableProvider {
+    path: PathBuf,
+    options: ReadingOptions,
+    schema: SchemaRef,
+}
+
+impl DbaseTableProvider {
+    /// Creates a table provider for the file at the given path,
+    /// the header is read to know the schema of the table.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        Self::from_path_with_options(path, ReadingOptions::default())
+    }
+
+    /// Creates a table provider for the file at the given path, read with the given options
+    pub fn from_path_with_options<P: AsRef<Path>>(
+        path: P,
+        options: ReadingOptions,
+    ) -> Result<Self, Error> {
+        let reader = Reader::from_path_with_options(path.as_ref(), options.clone())?;
+        Ok(Self {
+            path: path.as_ref().to_path_buf(),
+            options,
+            schema: Arc::new(reader.arrow_schema()),
+        })
+    }
+}
+
+impl TableProvider for DbaseTableProvider {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    fn table_type(&self) -> TableType {
+        TableType::Base
+    }
+
+    // This is what `#[async_trait]` expands `async fn scan` to,
+    // `async` is not a keyword in edition 2015.
+    fn scan<'life0, 'life1, 'life2, 'life3, 'async_trait>(
+        &'life0 self,
+        state: &'life1 dyn Session,
+        projection: Option<&'life2 Vec<usize>>,
+        _filters: &'life3 [Expr],
+        limit: Option<usize>,
+    ) -> Pin<Box<dyn Future<Output = Result<Arc<dyn ExecutionPlan>>> + Send + 'async_trait>>
+    where
+        'life0: 'async_trait,
+        'life1: 'async_trait,
+        'life2: 'async_trait,
+        'life3: 'async_trait,
+        Self: 'async_trait,
+    {
+        let projection = projection
+            .cloned()
+            .unwrap_or_else(|| (0..self.schema.fields().len()).collect());
+        let plan = self
+            .schema
+            .project(&projection)
+            .map_err(DataFusionError::from)
+            .and_then(|schema| {
+                let schema = Arc::new(schema);
+                let partition = DbasePartition {
+                    path: self.path.clone(),
+                    options: self.options.clone(),
+                    schema: schema.clone(),
+                    projection,
+                    batch_size: state.config().batch_size(),
+                };
+                StreamingTableExec::try_new(
+                    schema,
+                    vec![Arc::new(partition) as Arc<dyn PartitionStream>],
+                    None,
+                    None::<LexOrdering>,
+                    false,
+                    limit,
+                )
+            })
+            .map(|plan| Arc::new(plan) as Arc<dyn ExecutionPlan>);
+        Box::pin(std::future::ready(plan))
+    }
+}
+
+/// The stream of the record batches of a scan, the values
+/// of the projected columns are the only ones decoded.
+#[derive(Debug)]
+struct DbasePartition {
+    path: PathBuf,
+    options: ReadingOptions,
+    schema: SchemaRef,
+    projection: Vec<usize>,
+    batch_size: usize,
+}
+
+impl PartitionStream for DbasePartition {
+    fn schema(&self) -> &SchemaRef {
+        &self.schema
+    }
+
+    fn execute(&self, _ctx: Arc<TaskContext>) -> SendableRecordBatchStream {
+        type Batches = Box<dyn Iterator<Item = Result<RecordBatch>> + Send>;
+        let batches = Reader::from_path_with_options(&self.path, self.options.clone())
+            .map_err(|error| DataFusionError::External(Box::new(error)))
+            .and_then(|reader| {
+                reader
+                    .into_record_batches(self.batch_size)
+                    .with_projection(&self.projection)
+                    .map_err(DataFusionError::from)
+            });
+        let batches: Batches = match batches {
+            Ok(batches) => Box::new(batches.map(|batch| batch.map_err(DataFusionError::from))),
+            Err(error) => Box::new(std::iter::once(Err(error))),
+        };
+        Box::pin(RecordBatchStreamAdapter::new(
+            self.schema.clone(),
+            futures::stream::iter(batches),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    extern crate tokio;
+
+    use super::*;
+
+    use arrow_array::{Array, Int64Array, StringArray};
+    use datafusion::prelude::SessionContext;
+
+    fn query(sql: &str) -> Vec<RecordBatch> {
+        let table = DbaseTableProvider::from_path("tests/data/stations.dbf").unwrap();
+        let context = SessionContext::new();
+        context.register_table("stations", Arc::new(table)).unwrap();
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        let query = runtime.block_on(context.sql(sql)).unwrap();
+        runtime.block_on(query.collect()).unwrap()
+    }
+
+    #[test]
+    fn query_table() {
+        let batches = query("SELECT count(*) FROM stations");
+        let counts = batches[0]
+            .column(0)
+            .as_any()
+            .downcast_ref::<Int64Array>()
+            .unwrap();
+        assert_eq!(counts.value(0), 6);
+
+        let batches = query(
+            "SELECT \"marker-col\", name FROM stations \
+             WHERE name LIKE 'Van %' ORDER BY name",
+        );
+        assert_eq!(batches[0].schema().fields().len(), 2);
+        let names = batches[0]
+            .column(1)
+            .as_any()
+            .downcast_ref::<StringArray>()
+            .unwrap();
+        assert_eq!(names.value(0), "Van Dorn Street");
+    }
+
+    #[test]
+    fn scan_only_reads_the_projection() {
+        let table = DbaseTableProvider::from_path("tests/data/stations.dbf").unwrap();
+        let context = SessionContext::new();
+        let projection = vec![3, 0];
+        let plan = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap()
+            .block_on(table.scan(&context.state(), Some(&projection), &[], Some(1)))
+            .unwrap();
+        let schema = plan.schema();
+        assert_eq!(
+            schema
+                .fields()
+                .iter()
+                .map(|field| field.name().as_str())
+                .collect::<Vec<&str>>(),
+            vec!["line", "name"]
+        );
+    }
+}
@@ -0,0 +1,631 @@
+//! Module with the definition of fn's and struct's to check dBase files
+//! before using them
+//!
+//! The checks go from the header, to the fields descriptors, to each value of the records,
+//! and the problems found are reported instead of stopping at the first one.
+use std::fmt;
+use std::fs::File;
+use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+
+use byteorder::ReadBytesExt;
+
+use crate::error::{Error, ErrorKind, FieldIOError};
+use crate::header::Header;
+use crate::raw::IoSource;
+use crate::encoding::UnicodeLossy;
+use crate::reading::{read_table_info_tolerant, FieldIterator, Flavor, Reader};
+use crate::record::field::{read_memo_index, Date, FieldType, FieldValue};
+use crate::record::{field_name_issues, FieldInfo, FieldNameIssue};
+use crate::writing::{table_info_size, FILE_TERMINATOR};
+
+/// A problem found by [validate](fn.validate.html)
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValidationIssue {
+    /// The number of records in the header does not match the size of the file
+    NumRecordsMismatch {
+        /// Number of records in the header
+        in_header: u32,
+        /// Number of complete records the file contains
+        in_file: u64,
+    },
+    /// The fields descriptors do not end with their terminator before the offset
+    /// to the first record, the records are not checked as where they start is unknown
+    MissingFieldsTerminator,
+    /// The offset to the first record does not match the size of the fields descriptors
+    HeaderSizeMismatch { in_header: u16, expected: usize },
+    /// The size of the records is not the sum of the lengths of the fields
+    RecordSizeMismatch { in_header: u16, expected: usize },
+    /// The name of a field is duplicated or does not follow the rules of the format
+    InvalidFieldName(FieldNameIssue),
+    /// The length of the field is not one its type allows
+    InvalidFieldLength {
+        field: String,
+        field_type: FieldType,
+        length: u16,
+    },
+    /// The field has more decimal places than its length allows
+    InvalidDecimalPlaces {
+        field: String,
+        length: u16,
+        num_decimal_places: u8,
+    },
+    /// The table has memo fields but its memo file could not be opened
+    MissingMemoFile,
+    /// A value cannot be read, eg: a numeric value that is not a number
+    InvalidValue {
+        record: usize,
+        field: String,
+        error: String,
+    },
+    /// A date is not a day of the calendar
+    InvalidDate {
+        record: usize,
+        field: String,
+        date: Date,
+    },
+    /// A memo field points to data that cannot be read from the memo file
+    BrokenMemoPointer {
+        record: usize,
+        field: String,
+        error: String,
+    },
+    /// The byte after the last record is not the end of file marker
+    MissingEndOfFileMarker,
+}
+
+impl ValidationIssue {
+    /// Returns the index of the record the issue is about,
+    /// `None` for the issues of the whole table
+    pub fn record(&self) -> Option<usize> {
+        match self {
+            ValidationIssue::InvalidValue { record, .. }
+            | ValidationIssue::InvalidDate { record, .. }
+            | ValidationIssue::BrokenMemoPointer { record, .. } => Some(*record),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidationIssue::NumRecordsMismatch { in_header, in_file } => write!(
+                f,
+                "the header has {} records, the file contains {}",
+                in_header, in_file
+            ),
+            ValidationIssue::HeaderSizeMismatch {
+                in_header,
+                expected,
+            } => write!(
+                f,
+                "the records start at {} instead of {}",
+                in_header, expected
+            ),
+            ValidationIssue::RecordSizeMismatch {
+                in_header,
+                expected,
+            } => write!(
+                f,
+                "the records are {} bytes long instead of {}",
+                in_header, expected
+            ),
+            ValidationIssue::InvalidFieldName(issue) => write!(f, "{}", issue),
+            ValidationIssue::InvalidFieldLength {
+                field,
+                field_type,
+                length,
+            } => write!(
+                f,
+                "field '{}' is a {:?} of length {}",
+                field, field_type, length
+            ),
+            ValidationIssue::InvalidDecimalPlaces {
+                field,
+                length,
+                num_decimal_places,
+            } => write!(
+                f,
+                "field '{}' of length {} has {} decimal places",
+                field, length, num_decimal_places
+            ),
+            ValidationIssue::MissingMemoFile => write!(f, "the memo file is missing"),
+            ValidationIssue::InvalidValue {
+                record,
+                field,
+                error,
+            } => write!(
+                f,
+                "record {}: field '{}' cannot be read: {}",
+                record, field, error
+            ),
+            ValidationIssue::InvalidDate {
+                record,
+                field,
+                date,
+            } => write!(
+                f,
+                "record {}: field '{}' has the invalid date {}",
+                record, field, date
+            ),
+            ValidationIssue::BrokenMemoPointer {
+                record,
+                field,
+                error,
+            } => write!(
+                f,
+                "record {}: the memo of field '{}' cannot be read: {}",
+                record, field, error
+            ),
+            ValidationIssue::MissingEndOfFileMarker => {
+                write!(f, "the end of file marker is missing")
+            }
+            ValidationIssue::MissingFieldsTerminator => {
+                write!(f, "the fields descriptors do not end with their terminator")
+            }
+        }
+    }
+}
+
+/// Report made by [validate](fn.validate.html)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ValidationReport {
+    /// The number of records whose values were checked
+    pub num_records_checked: usize,
+    /// The problems found, the ones of the whole table first
+    pub issues: Vec<ValidationIssue>,
+}
+
+impl ValidationReport {
+    /// Returns whether no problem was found
+    pub fn is_valid(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    /// Returns the problems found in the values of the records
+    pub fn record_issues(&self) -> impl Iterator<Item = &ValidationIssue> {
+        self.issues.iter().filter(|issue| issue.record().is_some())
+    }
+}
+
+/// Returns whether the length is one the type of field allows
+fn is_valid_length(field_type: FieldType, length: u16) -> bool {
+    match field_type {
+        FieldType::Logical => length == 1,
+        FieldType::Date => length == 8,
+        FieldType::Integer | FieldType::Long | FieldType::Autoincrement => length == 4,
+        FieldType::Double
+        | FieldType::Currency
+        | FieldType::DateTime
+        | FieldType::DBase7Double
+        | FieldType::Timestamp => length == 8,
+        FieldType::Memo => length == 10 || length == 4,
+        FieldType::Numeric | FieldType::Float | FieldType::Character => length > 0,
+    }
+}
+
+fn check_fields(fields_info: &[FieldInfo], issues: &mut Vec<ValidationIssue>) {
+    issues.extend(
+        field_name_issues(fields_info)
+            .into_iter()
+            .map(ValidationIssue::InvalidFieldName),
+    );
+    for field_info in fields_info.iter().filter(|f| !f.is_deletion_flag()) {
+        if !is_valid_length(field_info.field_type, field_info.field_length) {
+            issues.push(ValidationIssue::InvalidFieldLength {
+                field: field_info.name().to_owned(),
+                field_type: field_info.field_type,
+                length: field_info.field_length,
+            });
+        }
+        if field_info.num_decimal_places > 0
+            && u16::from(field_info.num_decimal_places) >= field_info.field_length
+        {
+            issues.push(ValidationIssue::InvalidDecimalPlaces {
+                field: field_info.name().to_owned(),
+                length: field_info.field_length,
+                num_decimal_places: field_info.num_decimal_places,
+            });
+        }
+    }
+}
+
+/// Returns the size of the header, fields descriptors and backlink of the table
+pub(crate) fn expected_header_size(header: &Header, fields_info: &[FieldInfo]) -> usize {
+    // The fields information contains the deletion flag
+    table_info_size(header, fields_info.len() - 1)
+}
+
+/// Returns the size of the records, the sum of the lengths of the fields
+pub(crate) fn expected_record_size(fields_info: &[FieldInfo]) -> usize {
+    fields_info
+        .iter()
+        .map(|field_info| usize::from(field_info.field_length))
+        .sum()
+}
+
+fn check_header(
+    header: &Header,
+    fields_info: &[FieldInfo],
+    file_size: u64,
+    issues: &mut Vec<ValidationIssue>,
+) {
+    let expected_header_size = expected_header_size(header, fields_info);
+    if usize::from(header.offset_to_first_record) != expected_header_size {
+        issues.push(ValidationIssue::HeaderSizeMismatch {
+            in_header: header.offset_to_first_record,
+            expected: expected_header_size,
+        });
+    }
+
+    let expected_record_size = expected_record_size(fields_info);
+    if usize::from(header.size_of_record) != expected_record_size {
+        issues.push(ValidationIssue::RecordSizeMismatch {
+            in_header: header.size_of_record,
+            expected: expected_record_size,
+        });
+    }
+
+    if header.size_of_record > 0 {
+        let records_size = file_size.saturating_sub(u64::from(header.offset_to_first_record));
+        let num_records_in_file = records_size / u64::from(header.size_of_record);
+        // The end of file marker may be missing, making the file one byte shorter
+        let num_records_with_marker =
+            records_size.saturating_sub(1) / u64::from(header.size_of_record);
+        let num_records = u64::from(header.num_records);
+        if num_records != num_records_in_file && num_records != num_records_with_marker {
+            issues.push(ValidationIssue::NumRecordsMismatch {
+                in_header: header.num_records,
+                in_file: num_records_in_file,
+            });
+        }
+    }
+}
+
+/// Checks the values of the record, the source must be at its start
+fn check_record<T: Read + Seek>(
+    field_iterator: &mut FieldIterator<T>,
+    record: usize,
+    has_memo_file: bool,
+    issues: &mut Vec<ValidationIssue>,
+) -> Result<(), FieldIOError> {
+    let io_error = |error: std::io::Error| FieldIOError::new(ErrorKind::IoError(error), None);
+    let start = field_iterator.source.stream_position().map_err(io_error)?;
+    let mut field_end = start;
+    for field_info in field_iterator.fields_info.by_ref() {
+        field_end += u64::from(field_info.field_length);
+        if field_info.is_deletion_flag() {
+            field_iterator
+                .source
+                .seek(SeekFrom::Start(field_end))
+                .map_err(io_error)?;
+            continue;
+        }
+        let field = field_info.name().to_owned();
+        if let (FieldType::Memo, Some(memo_reader)) =
+            (field_info.field_type, field_iterator.memo_reader.as_mut())
+        {
+            let field_start = field_end - u64::from(field_info.field_length);
+            let index = read_memo_index(&mut IoSource(&mut *field_iterator.source), field_info);
+            field_iterator
+                .source
+                .seek(SeekFrom::Start(field_start))
+                .map_err(io_error)?;
+            if let Ok(Some(index)) = index {
+                if !memo_reader.contains_block(index).map_err(io_error)? {
+                    issues.push(ValidationIssue::BrokenMemoPointer {
+                        record,
+                        field,
+                        error: format!("block {} is not in the memo file", index),
+                    });
+                    field_iterator
+                        .source
+                        .seek(SeekFrom::Start(field_end))
+                        .map_err(io_error)?;
+                    continue;
+                }
+            }
+        }
+        let value = FieldValue::read_from(
+            field_iterator.source,
+            field_iterator.memo_reader,
+            field_info,
+            field_iterator.options,
+        );
+        match value {
+            Ok(FieldValue::Date(Some(date))) if !date.is_day_of_calendar() => {
+                issues.push(ValidationIssue::InvalidDate {
+                    record,
+                    field,
+                    date,
+                });
+            }
+            Ok(_) => {}
+            Err(_) if field_info.field_type == FieldType::Memo && !has_memo_file => {}
+            Err(error) if field_info.field_type == FieldType::Memo => {
+                issues.push(ValidationIssue::BrokenMemoPointer {
+                    record,
+                    field,
+                    error: error.to_string(),
+                });
+            }
+            // The record is truncated, which is reported with the number of records
+            Err(ErrorKind::IoError(error)) => return Err(io_error(error)),
+            Err(error) => {
+                issues.push(ValidationIssue::InvalidValue {
+                    record,
+                    field,
+                    error: error.to_string(),
+                });
+            }
+        }
+        // Whatever the value read, the next one starts after the field
+        field_iterator
+            .source
+            .seek(SeekFrom::Start(field_end))
+            .map_err(io_error)?;
+    }
+    Ok(())
+}
+
+/// Checks the dBase file at the given path and reports the problems found:
+///
+/// - the fields descriptors must end with their terminator
+/// - the number of records, size of the header and size of the records
+///   must match the content of the file and the fields descriptors
+/// - the fields must have valid and unique names, and lengths their types allow
+/// - each value of the records must be readable, dates must be days of the calendar
+///   and memo fields must point to data of the memo file
+/// - the file must end with the end of file marker
+///
+/// An error is only returned when the file cannot be read at all.
+///
+/// # Example
+///
+/// ```
+/// # fn main() -> Result<(), dbase::Error> {
+/// let report = dbase::validate("tests/data/stations.dbf")?;
+/// for issue in &report.issues {
+///     println!("{}", issue);
+/// }
+/// assert!(matches!(
+///     report.issues[0],
+///     dbase::ValidationIssue::RecordSizeMismatch { .. }
+/// ));
+/// # Ok(())
+/// # }
+/// ```
+pub fn validate<P: AsRef<Path>>(path: P) -> Result<ValidationReport, Error> {
+    let path = path.as_ref();
+    let file_size = std::fs::metadata(path)
+        .map_err(|error| Error::io_error(error, 0))?
+        .len();
+
+    let mut issues = vec![];
+    let file = File::open(path).map_err(|error| Error::io_error(error, 0))?;
+    let (header, fields_info, _, has_terminator) =
+        read_table_info_tolerant(&mut BufReader::new(file), Flavor::Standard, &UnicodeLossy)?;
+    if !has_terminator {
+        issues.push(ValidationIssue::MissingFieldsTerminator);
+        check_header(&header, &fields_info, file_size, &mut issues);
+        check_fields(&fields_info, &mut issues);
+        return Ok(ValidationReport {
+            num_records_checked: 0,
+            issues,
+        });
+    }
+
+    let mut has_memo_file = true;
+    let mut reader = match Reader::from_path(path) {
+        Ok(reader) => reader,
+        Err(Error {
+            kind: ErrorKind::ErrorOpeningMemoFile(_),
+            ..
+        }) => {
+            issues.push(ValidationIssue::MissingMemoFile);
+            has_memo_file = false;
+            let file = File::open(path).map_err(|error| Error::io_error(error, 0))?;
+            Reader::new(BufReader::new(file))?
+        }
+        Err(error) => return Err(error),
+    };
+    check_header(reader.header(), reader.fields(), file_size, &mut issues);
+    check_fields(reader.fields(), &mut issues);
+    let header = reader.header();
+    let num_records = header.num_records as usize;
+
+    let mut num_records_checked = 0;
+    let mut record_issues = vec![];
+    for record in 0..num_records {
+        let checked = reader.read_current_record_with(|field_iterator| {
+            check_record(field_iterator, record, has_memo_file, &mut record_issues)
+        });
+        if checked.is_err() {
+            break;
+        }
+        num_records_checked += 1;
+    }
+
+    // The records are read one field after the other, so the source is
+    // after the last record even if the size of records in the header is wrong
+    let marker = if num_records_checked == num_records {
+        reader.source.read_u8().ok()
+    } else {
+        None
+    };
+    if marker != Some(FILE_TERMINATOR) {
+        issues.push(ValidationIssue::MissingEndOfFileMarker);
+    }
+    issues.extend(record_issues);
+
+    Ok(ValidationReport {
+        num_records_checked,
+        issues,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::convert::TryFrom;
+    use std::io::Cursor;
+
+    use crate::{FieldName, Record, TableWriterBuilder};
+
+    fn table() -> Vec<u8> {
+        let records = [
+            ("Ferris", 1.5, Date::new(15, 5, 2015)),
+            ("Corro", 2.0, Date::new(1, 1, 2000)),
+        ]
+        .iter()
+        .map(|(name, price, date)| {
+            let mut record = Record::default();
+            record.insert(
+                "NAME".to_owned(),
+                FieldValue::Character(Some(name.to_string())),
+            );
+            record.insert("PRICE".to_owned(), FieldValue::Numeric(Some(*price)));
+            record.insert("SINCE".to_owned(), FieldValue::Date(Some(*date)));
+            record
+        })
+        .collect::<Vec<Record>>();
+        TableWriterBuilder::new()
+            .add_character_field(FieldName::try_from("NAME").unwrap(), 10)
+            .add_numeric_field(FieldName::try_from("PRICE").unwrap(), 8, 2)
+            .add_date_field(FieldName::try_from("SINCE").unwrap())
+            .build_with_dest(Cursor::new(Vec::<u8>::new()))
+            .write(&records)
+            .unwrap()
+            .into_inner()
+    }
+
+    fn validate_content(name: &str, content: &[u8]) -> ValidationReport {
+        let path = std::env::temp_dir().join(format!("dbase_validate_{}.dbf", name));
+        std::fs::write(&path, content).unwrap();
+        let report = validate(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        report
+    }
+
+    #[test]
+    fn missing_fields_terminator() {
+        let mut content = std::fs::read("tests/data/stations.dbf").unwrap();
+        // The offset ends one descriptor before the terminator
+        let offset = u16::from_le_bytes([content[8], content[9]]) - 32;
+        content[8..10].copy_from_slice(&offset.to_le_bytes());
+
+        let report = validate_content("missing_terminator", &content);
+        assert_eq!(report.num_records_checked, 0);
+        assert_eq!(report.issues[0], ValidationIssue::MissingFieldsTerminator);
+    }
+
+    #[test]
+    fn valid_table() {
+        let report = validate_content("valid", &table());
+        assert_eq!(report.num_records_checked, 2);
+        assert!(report.is_valid(), "{:?}", report.issues);
+    }
+
+    #[test]
+    fn invalid_values_and_header() {
+        let mut content = table();
+        // Three records in the header
+        content[4] = 3;
+        let first_record = 32 + 3 * 32 + 1;
+        let size_of_record = 1 + 10 + 8 + 8;
+        content[first_record + 11..first_record + 19].copy_from_slice(b"    1.x0");
+        content[first_record + size_of_record + 19..first_record + size_of_record + 27]
+            .copy_from_slice(b"20000230");
+        content.pop();
+
+        let report = validate_content("invalid", &content);
+        assert_eq!(report.num_records_checked, 2);
+        assert_eq!(
+            report.issues[0],
+            ValidationIssue::NumRecordsMismatch {
+                in_header: 3,
+                in_file: 2
+            }
+        );
+        assert_eq!(report.issues[1], ValidationIssue::MissingEndOfFileMarker);
+        assert!(matches!(
+            &report.issues[2],
+            ValidationIssue::InvalidValue { record: 0, field, .. } if field == "PRICE"
+        ));
+        assert_eq!(
+            report.issues[3],
+            ValidationIssue::InvalidDate {
+                record: 1,
+                field: "SINCE".to_owned(),
+                date: Date::new(30, 2, 2000)
+            }
+        );
+        assert_eq!(report.record_issues().count(), 2);
+        assert_eq!(report.issues.len(), 4);
+    }
+
+    #[test]
+    fn invalid_descriptors() {
+        let mut fields = vec![FieldInfo::new_deletion_flag()];
+        fields.push(FieldInfo::new(
+            FieldName::try_from("NAME").unwrap(),
+            FieldType::Character,
+            10,
+        ));
+        fields.push(FieldInfo::new(
+            FieldName::try_from("name").unwrap(),
+            FieldType::Date,
+            6,
+        ));
+        let mut issues = vec![];
+        check_fields(&fields, &mut issues);
+        assert_eq!(
+            issues,
+            vec![
+                ValidationIssue::InvalidFieldName(FieldNameIssue::Duplicate {
+                    index: 1,
+                    name: "name".to_owned()
+                }),
+                ValidationIssue::InvalidFieldLength {
+                    field: "name".to_owned(),
+                    field_type: FieldType::Date,
+                    length: 6
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn broken_and_missing_memo() {
+        // dBase III table with a memo field, the second record points after the memo file
+        let mut table = vec![0x83, 120, 1, 1, 2, 0, 0, 0, 65, 0, 11, 0];
+        table.resize(Header::SIZE, 0);
+        table.extend_from_slice(b"NOTES\0\0\0\0\0\0M");
+        table.extend_from_slice(&[0, 0, 0, 0, 10, 0]);
+        table.resize(Header::SIZE + FieldInfo::SIZE, 0);
+        table.push(0x0D);
+        table.extend_from_slice(b"          1          9\x1A");
+        let mut memo = vec![2, 0, 0, 0];
+        memo.resize(512, 0);
+        memo.extend_from_slice(b"Ferris the crab\x1A\x1A");
+        memo.resize(1024, 0);
+
+        let path = std::env::temp_dir().join("dbase_validate_memo.dbf");
+        let memo_path = path.with_extension("dbt");
+        std::fs::write(&path, &table).unwrap();
+        std::fs::write(&memo_path, &memo).unwrap();
+        let report = validate(&path).unwrap();
+        assert!(matches!(
+            &report.issues[..],
+            [ValidationIssue::BrokenMemoPointer { record: 1, field, .. }] if field == "NOTES"
+        ));
+
+        std::fs::remove_file(&memo_path).unwrap();
+        let report = validate(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(report.issues, vec![ValidationIssue::MissingMemoFile]);
+        assert_eq!(report.num_records_checked, 2);
+    }
+}
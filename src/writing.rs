@@ -1,17 +1,32 @@
 //! Module with all structs & functions charged of writing .dbf file content
+use std::borrow::Borrow;
+use std::convert::TryFrom;
 use std::fs::File;
 use std::io::{BufWriter, Cursor, Write};
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 use byteorder::WriteBytesExt;
 
-use crate::header::Header;
-use crate::reading::TERMINATOR_VALUE;
-use crate::record::{field::FieldType, FieldInfo, FieldName};
+use crate::encoding::{Encoding, UnicodeLossy};
+use crate::encryption::{encrypt_record, RecordCipher};
+use crate::error::FormatLimit;
+use crate::header::{Header, Version};
+use crate::index::{IndexBuilder, IndexUpdater};
+use crate::reading::{
+    FieldIterator, ReadableRecord, ReadingOptions, BACKLINK_SIZE, TERMINATOR_VALUE,
+};
+use crate::record::field::{Date, FieldValue, MemoReader};
+use crate::record::{field::FieldType, field_names_match, FieldInfo, FieldName};
+use crate::schema::TableSchema;
+use crate::split::SplitTable;
 use crate::{Error, ErrorKind, FieldIOError, Record};
 
 /// A dbase file ends with this byte
-const FILE_TERMINATOR: u8 = 0x1A;
+pub(crate) const FILE_TERMINATOR: u8 = 0x1A;
+
+/// Maximum size of a table, the offsets of the format are signed 32 bit integers
+pub(crate) const MAX_TABLE_SIZE: u64 = i32::MAX as u64;
 
 /// Builder to be used to create a [TableWriter](struct.TableWriter.html).
 ///
@@ -34,9 +49,18 @@ const FILE_TERMINATOR: u8 = 0x1A;
 ///     .add_character_field(FieldName::try_from("Last Name").unwrap(), 50)
 ///     .build_with_dest(Cursor::new(Vec::<u8>::new()));
 /// ```
+#[derive(Clone)]
 pub struct TableWriterBuilder {
-    v: Vec<FieldInfo>,
-    hdr: Header,
+    pub(crate) v: Vec<FieldInfo>,
+    pub(crate) hdr: Header,
+    pub(crate) backlink: Option<String>,
+    pub(crate) defaults: FieldDefaults,
+    pub(crate) max_file_size: u64,
+    pub(crate) overflow_policy: OverflowPolicy,
+    /// Encoding of the names of the fields
+    pub(crate) encoding: Arc<dyn Encoding>,
+    /// Cipher the records are encrypted with
+    pub(crate) cipher: Option<Arc<dyn RecordCipher>>,
 }
 
 impl TableWriterBuilder {
@@ -45,8 +69,42 @@ impl TableWriterBuilder {
         Self::default()
     }
 
+    /// Creates a builder with the fields of the struct, inferred from the types of
+    /// its fields and their `#[dbase(...)]` attributes
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # #[macro_use] extern crate dbase;
+    /// # fn main() -> Result<(), dbase::Error> {
+    /// dbase_record!(
+    ///     struct Product {
+    ///         #[dbase(len = 30)]
+    ///         name: String,
+    ///         #[dbase(len = 10, decimals = 2)]
+    ///         price: f64,
+    ///     }
+    /// );
+    ///
+    /// let writer = dbase::TableWriterBuilder::from_schema::<Product>()?
+    ///     .build_with_dest(std::io::Cursor::new(Vec::<u8>::new()));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_schema<R: TableSchema>() -> Result<Self, Error> {
+        R::add_fields(Self::new())
+    }
+
     /// Gets the field definition from the reader to construct the TableWriter
     ///
+    /// The names of the fields are encoded with the encoding of the reader.
+    /// Encrypted tables are written encrypted with the cipher of the reader.
+    ///
+    /// Indexes of the table the reader reads are not carried over,
+    /// they have to be attached to the writer with
+    /// [add_index](struct.TableWriter.html#method.add_index) to be kept in sync
+    /// with the new content.
+    ///
     /// # Example
     /// ```
     /// use dbase::{FieldValue, TableWriterBuilder};
@@ -67,17 +125,58 @@ impl TableWriterBuilder {
     pub fn from_reader<T: std::io::Read + std::io::Seek>(
         reader: crate::reading::Reader<T>,
     ) -> Self {
-        let mut fields_info = reader.fields_info;
+        let cipher = reader.cipher().cloned();
+        Self::from_table_info(
+            reader.fields_info,
+            reader.header,
+            reader.backlink,
+            reader.options.encoding,
+            cipher,
+        )
+    }
+
+    /// Same as [from_reader](#method.from_reader), without consuming the reader
+    pub(crate) fn from_reader_ref<T: std::io::Read + std::io::Seek>(
+        reader: &crate::reading::Reader<T>,
+    ) -> Self {
+        Self::from_table_info(
+            reader.fields_info.clone(),
+            reader.header.clone(),
+            reader.backlink.clone(),
+            reader.options.encoding.clone(),
+            reader.cipher().cloned(),
+        )
+    }
+
+    fn from_table_info(
+        mut fields_info: Vec<FieldInfo>,
+        mut hdr: Header,
+        backlink: Option<String>,
+        encoding: Arc<dyn Encoding>,
+        cipher: Option<Arc<dyn RecordCipher>>,
+    ) -> Self {
         if let Some(i) = fields_info.first() {
             if i.is_deletion_flag() {
                 fields_info.remove(0);
             }
         }
-        let mut hdr = reader.header;
         hdr.update_date();
+        if let Version::DBase2 = hdr.file_type {
+            // The tables are written with the header of dBase III,
+            // which has the same field types
+            hdr.file_type = Version::DBase3 {
+                supports_memo: false,
+            };
+        }
         Self {
             v: fields_info,
             hdr,
+            backlink,
+            defaults: FieldDefaults::default(),
+            max_file_size: MAX_TABLE_SIZE,
+            overflow_policy: OverflowPolicy::default(),
+            encoding,
+            cipher,
         }
     }
 
@@ -136,9 +235,7 @@ impl TableWriterBuilder {
                 .size()
                 .expect("Internal error Integer field date should be known"),
         ));
-        self.hdr.file_type = crate::header::Version::FoxPro2 {
-            supports_memo: false,
-        };
+        self.set_version_supporting(FieldType::Integer);
         self
     }
 
@@ -151,9 +248,7 @@ impl TableWriterBuilder {
                 .size()
                 .expect("Internal error datetime field date should be known"),
         ));
-        self.hdr.file_type = crate::header::Version::FoxPro2 {
-            supports_memo: false,
-        };
+        self.set_version_supporting(FieldType::DateTime);
         self
     }
 
@@ -166,9 +261,7 @@ impl TableWriterBuilder {
                 .size()
                 .expect("Internal error Double field date should be known"),
         ));
-        self.hdr.file_type = crate::header::Version::FoxPro2 {
-            supports_memo: false,
-        };
+        self.set_version_supporting(FieldType::Double);
         self
     }
 
@@ -181,14 +274,262 @@ impl TableWriterBuilder {
                 .size()
                 .expect("Internal error Currency field date should be known"),
         ));
-        self.hdr.file_type = crate::header::Version::FoxPro2 {
-            supports_memo: false,
-        };
+        self.set_version_supporting(FieldType::Currency);
+        self
+    }
+
+    /// Adds a [Long](enum.FieldValue.html#variant.Long)
+    ///
+    /// The binary fields of dBase 7 switch the table to dBase 7,
+    /// unless its version already supports them.
+    pub fn add_long_field(mut self, name: FieldName) -> Self {
+        self.add_dbase7_field(name, FieldType::Long);
+        self
+    }
+
+    /// Adds a [DBase7Double](enum.FieldValue.html#variant.DBase7Double)
+    pub fn add_dbase7_double_field(mut self, name: FieldName) -> Self {
+        self.add_dbase7_field(name, FieldType::DBase7Double);
+        self
+    }
+
+    /// Adds a [Timestamp](enum.FieldValue.html#variant.Timestamp)
+    pub fn add_timestamp_field(mut self, name: FieldName) -> Self {
+        self.add_dbase7_field(name, FieldType::Timestamp);
         self
     }
+
+    /// Adds an [Autoincrement](enum.FieldValue.html#variant.Autoincrement)
+    pub fn add_autoincrement_field(mut self, name: FieldName) -> Self {
+        self.add_dbase7_field(name, FieldType::Autoincrement);
+        self
+    }
+
+    fn add_dbase7_field(&mut self, name: FieldName, field_type: FieldType) {
+        self.v.push(FieldInfo::new(
+            name,
+            field_type,
+            field_type
+                .size()
+                .expect("Internal error dBase 7 field size should be known"),
+        ));
+        if !self.hdr.file_type.supports_field_type(field_type) {
+            self.hdr.file_type = crate::header::Version::DBase7 {
+                supports_memo: false,
+            };
+        }
+    }
+
+    /// Switches the table to FoxPro 2 when its version does not support the field type,
+    /// FoxPro 2 tables with Visual FoxPro field types are commonly read
+    /// and do not need a backlink
+    fn set_version_supporting(&mut self, field_type: FieldType) {
+        if !self.hdr.file_type.supports_field_type(field_type) {
+            self.hdr.file_type = crate::header::Version::FoxPro2 {
+                supports_memo: false,
+            };
+        }
+    }
+
+    /// Sets what is written in the fields records do not have a value for,
+    /// when no default value was given for the field or its type
+    ///
+    /// Missing fields are an error by default.
+    pub fn missing_field_policy(mut self, policy: MissingFieldPolicy) -> Self {
+        self.defaults.policy = policy;
+        self
+    }
+
+    /// Sets the value written in the field with the given name
+    /// when records do not have a value for it
+    ///
+    /// Names are compared ignoring the ASCII case, like dBase does.
+    pub fn default_value(mut self, name: &str, value: FieldValue) -> Self {
+        self.defaults
+            .by_name
+            .retain(|(field_name, _)| !field_names_match(field_name, name));
+        self.defaults.by_name.push((name.to_owned(), value));
+        self
+    }
+
+    /// Sets the value written in the fields of the given type
+    /// when records do not have a value for them,
+    /// values given with [default_value](#method.default_value) take precedence
+    pub fn default_value_for_type(mut self, field_type: FieldType, value: FieldValue) -> Self {
+        self.defaults.by_type.retain(|(t, _)| *t != field_type);
+        self.defaults.by_type.push((field_type, value));
+        self
+    }
+
+    /// Sets what is done with the values that do not fit in their field,
+    /// they are written unchecked by default
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dbase::{ErrorKind, FieldName, OverflowPolicy, Record, TableWriterBuilder};
+    /// use std::convert::TryFrom;
+    /// use std::io::Cursor;
+    ///
+    /// let mut record = Record::default();
+    /// record.insert("PRICE".to_owned(), dbase::FieldValue::Numeric(Some(1234.5)));
+    /// let builder = TableWriterBuilder::new()
+    ///     .add_numeric_field(FieldName::try_from("PRICE").unwrap(), 6, 2);
+    ///
+    /// let error = builder
+    ///     .overflow_policy(OverflowPolicy::Fail)
+    ///     .build_with_dest(Cursor::new(Vec::<u8>::new()))
+    ///     .write(&[record])
+    ///     .unwrap_err();
+    /// assert!(matches!(error.kind(), ErrorKind::ValueTooLong(_)));
+    /// assert_eq!(error.field().as_ref().unwrap().name(), "PRICE");
+    /// ```
+    pub fn overflow_policy(mut self, policy: OverflowPolicy) -> Self {
+        self.overflow_policy = policy;
+        self
+    }
+
+    /// Sets the encoding of the names of the fields and of the Character values,
+    /// UTF-8 by default
+    ///
+    /// Names and values with letters that are not ASCII (`AÑO`, `STRAßE`) are written
+    /// in the code page of the applications that read the table.
+    /// Writing fails if the encoding does not [encode](trait.Encoding.html#method.encode)
+    /// a name or a value, or if a name is too long once encoded. The length of values
+    /// is also the one of their encoded bytes, as checked by
+    /// the [OverflowPolicy](enum.OverflowPolicy.html).
+    pub fn encoding<E: Encoding + 'static>(mut self, encoding: E) -> Self {
+        self.encoding = Arc::new(encoding);
+        self
+    }
+
+    /// Encrypts the records with the cipher and sets the encryption flag of the header,
+    /// for the applications that only open encrypted tables
+    ///
+    /// The cipher holds the key, the crate does not have the undocumented scheme
+    /// of dBase IV `PROTECT`: see [RecordCipher](trait.RecordCipher.html).
+    /// The tables are read back with
+    /// [ReadingOptions::decryption](struct.ReadingOptions.html#method.decryption).
+    pub fn encryption<C: RecordCipher + 'static>(mut self, cipher: C) -> Self {
+        self.hdr.encryption_flag = 1;
+        self.cipher = Some(Arc::new(cipher));
+        self
+    }
+
+    /// Sets the date of the last update written in the header, today by default
+    ///
+    /// The date is the only part of the written bytes that changes from one write
+    /// to the other, the reserved bytes are zeroed and the values are padded with spaces:
+    /// writing the same records with the same date gives the same bytes.
+    /// The year must be between 1900 and 2155.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), dbase::Error> {
+    /// let write = || -> Result<Vec<u8>, dbase::Error> {
+    ///     let mut reader = dbase::Reader::from_path("tests/data/line.dbf")?;
+    ///     let records = reader.read()?;
+    ///     let dst = dbase::TableWriterBuilder::from_reader(reader)
+    ///         .last_update(dbase::Date::new(1, 1, 2000))
+    ///         .build_with_dest(std::io::Cursor::new(Vec::<u8>::new()))
+    ///         .write(&records)?;
+    ///     Ok(dst.into_inner())
+    /// };
+    /// assert_eq!(write()?, write()?);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn last_update(mut self, date: Date) -> Self {
+        self.hdr.last_update = date;
+        self
+    }
+
+    /// Sets the maximum size of the written tables, the 2 GB the format allows by default
+    ///
+    /// Writing more records than fit is an error, unless they are written with
+    /// [write_to_files](#method.write_to_files), which continues in another file.
+    pub fn max_file_size(mut self, size: u64) -> Self {
+        self.max_file_size = size.min(MAX_TABLE_SIZE);
+        self
+    }
+
     /// Builds the writer and set the dst as where the file data will be written
     pub fn build_with_dest<W: Write>(self, dst: W) -> TableWriter<W> {
-        TableWriter::new(dst, self.v, self.hdr)
+        TableWriter::new(dst, self)
+    }
+
+    /// Writes the records to the file at the given path, and to numbered continuation
+    /// files next to it (`table_2.dbf`, `table_3.dbf`, ...) for the records that do not
+    /// fit in the [maximum size](#method.max_file_size) of a file or in its record count
+    ///
+    /// Returns the tables written, in order. At least one table is written,
+    /// even when there are no records.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), dbase::Error> {
+    /// let mut reader = dbase::Reader::from_path("tests/data/stations.dbf")?;
+    /// let records = reader.read()?;
+    /// let path = std::env::temp_dir().join("dbase_write_to_files_doc.dbf");
+    /// let tables = dbase::TableWriterBuilder::from_reader(reader)
+    ///     .max_file_size(2048)
+    ///     .write_to_files(&path, &records)?;
+    /// assert!(tables.len() > 1);
+    /// # for table in &tables {
+    /// #     std::fs::remove_file(&table.path).unwrap();
+    /// # }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn write_to_files<P: AsRef<Path>, R: WritableRecord>(
+        self,
+        path: P,
+        records: &[R],
+    ) -> Result<Vec<SplitTable>, Error> {
+        let path = path.as_ref();
+        check_format_limits(&self.hdr, &self.v, 0, self.max_file_size)?;
+        let record_size = record_size(&self.v);
+        let available = self
+            .max_file_size
+            .saturating_sub(table_info_size(&self.hdr, self.v.len()) as u64 + 1);
+        let records_per_file = (available / record_size).clamp(1, u64::from(u32::MAX)) as usize;
+
+        let mut tables = vec![];
+        let mut chunks = records.chunks(records_per_file);
+        let mut next_chunk = chunks.next();
+        loop {
+            let chunk = next_chunk.unwrap_or(&[]);
+            let table_path = continuation_path(path, tables.len());
+            let num_written = tables
+                .iter()
+                .map(|table: &SplitTable| table.num_records)
+                .sum::<usize>();
+            let dst = File::create(&table_path).map_err(|error| Error::io_error(error, 0))?;
+            let result = self
+                .clone()
+                .build_with_dest(BufWriter::new(dst))
+                .write(chunk)
+                .and_then(|dst| {
+                    dst.into_inner()
+                        .map_err(|error| Error::io_error(error.into_error(), 0))
+                });
+            if let Err(mut error) = result {
+                error.record_num += num_written;
+                return Err(error);
+            }
+            tables.push(SplitTable {
+                path: table_path,
+                num_records: chunk.len(),
+                value: None,
+            });
+            next_chunk = chunks.next();
+            if next_chunk.is_none() {
+                break;
+            }
+        }
+        Ok(tables)
     }
 
     /// Helper function to set create a file at the given path
@@ -209,7 +550,172 @@ impl Default for TableWriterBuilder {
         Self {
             v: vec![],
             hdr: Header::new(0, 0, 0),
+            backlink: None,
+            defaults: FieldDefaults::default(),
+            max_file_size: MAX_TABLE_SIZE,
+            overflow_policy: OverflowPolicy::default(),
+            encoding: Arc::new(UnicodeLossy),
+            cipher: None,
+        }
+    }
+}
+
+/// What to write in the fields records do not have a value for
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum MissingFieldPolicy {
+    /// Missing fields are an error
+    /// ([NotEnoughFields](enum.ErrorKind.html#variant.NotEnoughFields) for structs)
+    #[default]
+    Fail,
+    /// Missing fields are written blank and are read back as `None`,
+    /// the types that cannot be `None` (Integer, Currency, Double, DateTime) are zeroed
+    Blank,
+}
+
+/// What to do with the values that do not fit in their field when writing
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum OverflowPolicy {
+    /// Values are not checked, the bytes past the length of their field are dropped
+    #[default]
+    Unchecked,
+    /// Values that do not fit in their field are an error
+    /// ([ValueTooLong](enum.ErrorKind.html#variant.ValueTooLong)),
+    /// like numbers with more decimals than their field and
+    /// dates that are not days of the calendar ([InvalidDate](enum.ErrorKind.html#variant.InvalidDate))
+    Fail,
+    /// Text is truncated to the length of its field and the extra decimals of numbers
+    /// are dropped, numbers whose integer part does not fit and invalid dates are an error
+    Truncate,
+    /// Like `Truncate`, but numbers are rounded to the decimals of their field
+    Round,
+}
+
+/// Encodes the text written in `value` with the encoding of the table,
+/// the text that does not fit in its field is cut before being encoded
+/// when the policy truncates it
+fn encode_text(
+    value: &mut Cursor<Vec<u8>>,
+    field_info: &FieldInfo,
+    encoding: &dyn Encoding,
+    policy: OverflowPolicy,
+) -> Result<(), ErrorKind> {
+    let written = value.position() as usize;
+    let encoded = {
+        let text = match std::str::from_utf8(&value.get_ref()[..written]) {
+            Ok(text) => text,
+            // Bytes that are not text are written as they are
+            Err(_) => return Ok(()),
+        };
+        let mut encoded = encoding
+            .encode(text)
+            .ok_or_else(|| ErrorKind::Message(format!("'{}' cannot be encoded", text)))?;
+        if *encoded == *text.as_bytes() {
+            return Ok(());
         }
+        let length = usize::from(field_info.field_length);
+        if encoded.len() > length
+            && (policy == OverflowPolicy::Truncate || policy == OverflowPolicy::Round)
+        {
+            let mut end = text.len();
+            while encoded.len() > length {
+                end = text[..end].char_indices().last().map_or(0, |(i, _)| i);
+                encoded = encoding.encode(&text[..end]).unwrap_or_default();
+            }
+        }
+        encoded.into_owned()
+    };
+    value.set_position(0);
+    value.write_all(&encoded)?;
+    Ok(())
+}
+
+/// Checks that the value written in `value` fits in its field,
+/// fixing it in place when the policy allows it
+fn fit_value(
+    value: &mut Cursor<Vec<u8>>,
+    field_info: &FieldInfo,
+    policy: OverflowPolicy,
+) -> Result<(), ErrorKind> {
+    if policy == OverflowPolicy::Unchecked {
+        return Ok(());
+    }
+    let length = usize::from(field_info.field_length);
+    let written = value.position() as usize;
+    let text = String::from_utf8_lossy(&value.get_ref()[..written]).into_owned();
+    match field_info.field_type {
+        FieldType::Character if written > length => {
+            if policy == OverflowPolicy::Fail {
+                return Err(ErrorKind::ValueTooLong(text));
+            }
+            // Characters are not cut in two
+            let bytes = value.get_ref();
+            let mut end = length;
+            while end > 0 && (bytes[end] & 0xC0) == 0x80 {
+                end -= 1;
+            }
+            value.set_position(end as u64);
+        }
+        FieldType::Numeric | FieldType::Float if written > 0 => {
+            let num_decimals = usize::from(field_info.num_decimal_places);
+            let (integer_part, decimal_part) = text.split_once('.').unwrap_or((&text, ""));
+            let fitted = if decimal_part.len() <= num_decimals {
+                text.clone()
+            } else if policy == OverflowPolicy::Fail {
+                return Err(ErrorKind::ValueTooLong(text));
+            } else if policy == OverflowPolicy::Round {
+                format!("{:.*}", num_decimals, text.parse::<f64>()?)
+            } else if num_decimals == 0 {
+                integer_part.to_owned()
+            } else {
+                format!("{}.{}", integer_part, &decimal_part[..num_decimals])
+            };
+            let integer_width = fitted.split('.').next().map_or(0, str::len);
+            let max_integer_width = if num_decimals > 0 {
+                length.saturating_sub(num_decimals + 1)
+            } else {
+                length
+            };
+            if integer_width > max_integer_width {
+                return Err(ErrorKind::ValueTooLong(text));
+            }
+            value.set_position(0);
+            value.write_all(fitted.as_bytes())?;
+        }
+        FieldType::Date if !text.trim().is_empty() => {
+            let is_valid = written == length
+                && text
+                    .parse::<Date>()
+                    .is_ok_and(|date| date.is_day_of_calendar());
+            if !is_valid {
+                return Err(ErrorKind::InvalidDate(text));
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// The values written in the fields records do not have a value for
+#[derive(Debug, Clone, Default)]
+pub(crate) struct FieldDefaults {
+    pub(crate) policy: MissingFieldPolicy,
+    pub(crate) by_name: Vec<(String, FieldValue)>,
+    pub(crate) by_type: Vec<(FieldType, FieldValue)>,
+}
+
+impl FieldDefaults {
+    /// Returns the value given for the field or its type
+    fn value_for(&self, field_info: &FieldInfo) -> Option<&FieldValue> {
+        self.by_name
+            .iter()
+            .find(|(name, _)| field_names_match(name, field_info.name()))
+            .map(|(_, value)| value)
+            .or_else(|| {
+                self.by_type
+                    .iter()
+                    .find(|(field_type, _)| *field_type == field_info.field_type)
+                    .map(|(_, value)| value)
+            })
     }
 }
 
@@ -230,12 +736,18 @@ mod private {
     impl_sealed_for!(f64);
     impl_sealed_for!(f32);
     impl_sealed_for!(i32);
+    impl_sealed_for!(i64);
     impl_sealed_for!(Option<f64>);
     impl_sealed_for!(Option<f32>);
+    impl_sealed_for!(Option<i64>);
     impl_sealed_for!(crate::record::field::Date);
     impl_sealed_for!(Option<crate::record::field::Date>);
     impl_sealed_for!(crate::record::field::FieldValue);
     impl_sealed_for!(crate::record::field::DateTime);
+    impl_sealed_for!(Option<i32>);
+    impl_sealed_for!(Option<crate::record::field::DateTime>);
+    impl_sealed_for!(crate::record::CompactString);
+    impl_sealed_for!(Option<crate::record::CompactString>);
 }
 
 /// Trait implemented by types we can write as dBase types
@@ -261,16 +773,20 @@ impl WritableRecord for Record {
         field_writer: &mut FieldWriter<'a, W>,
     ) -> Result<(), FieldIOError> {
         while let Some(name) = field_writer.next_field_name() {
-            let value = self.get(name).ok_or_else(|| {
-                FieldIOError::new(
-                    ErrorKind::Message(format!(
-                        "Could not find field named '{}' in the record map",
-                        name
-                    )),
-                    None,
-                )
-            })?;
-            field_writer.write_next_field_value(value)?;
+            match self.get(name) {
+                Some(value) => field_writer.write_next_field_value(value)?,
+                None => {
+                    if !field_writer.write_next_field_default()? {
+                        return Err(FieldIOError::new(
+                            ErrorKind::Message(format!(
+                                "Could not find field named '{}' in the record map",
+                                name
+                            )),
+                            None,
+                        ));
+                    }
+                }
+            }
         }
         Ok(())
     }
@@ -285,6 +801,12 @@ pub struct FieldWriter<'a, W: Write> {
     pub(crate) dst: &'a mut W,
     pub(crate) fields_info: std::iter::Peekable<std::slice::Iter<'a, FieldInfo>>,
     pub(crate) buffer: Cursor<Vec<u8>>,
+    /// The values of the fields records do not have,
+    /// `None` when missing fields are always an error
+    pub(crate) defaults: Option<&'a FieldDefaults>,
+    pub(crate) overflow_policy: OverflowPolicy,
+    /// The encoding of the Character values
+    pub(crate) encoding: &'a dyn Encoding,
 }
 
 impl<'a, W: Write> FieldWriter<'a, W> {
@@ -301,7 +823,8 @@ impl<'a, W: Write> FieldWriter<'a, W> {
     /// match the expected type an error is returned.
     ///
     /// Values for which the number of bytes written would exceed the specified field_length
-    /// (if it had to be specified) will be truncated
+    /// (if it had to be specified) will be truncated, unless another
+    /// [OverflowPolicy](enum.OverflowPolicy.html) was given to the builder
     ///
     /// Trying to write more values than was declared when creating the writer will cause
     /// an `EndOfRecord` error.
@@ -315,26 +838,53 @@ impl<'a, W: Write> FieldWriter<'a, W> {
             field_value
                 .write_as(field_info.field_type, &mut self.buffer)
                 .map_err(|kind| FieldIOError::new(kind, Some(field_info.clone())))?;
+            if field_info.field_type == FieldType::Character {
+                encode_text(
+                    &mut self.buffer,
+                    field_info,
+                    self.encoding,
+                    self.overflow_policy,
+                )
+                .map_err(|kind| FieldIOError::new(kind, Some(field_info.clone())))?;
+            }
+            fit_value(&mut self.buffer, field_info, self.overflow_policy)
+                .map_err(|kind| FieldIOError::new(kind, Some(field_info.clone())))?;
 
             let mut bytes_written = self.buffer.position();
             let mut bytes_to_pad = i64::from(field_info.field_length) - bytes_written as i64;
             if bytes_to_pad > 0 {
-                if field_info.field_type == FieldType::Float
-                    || field_info.field_type == FieldType::Numeric
+                // Empty values are written as blanks, so they are read back as None
+                if (field_info.field_type == FieldType::Float
+                    || field_info.field_type == FieldType::Numeric)
+                    && bytes_written > 0
                 {
                     // Depending on the locale, the dot might not be the delimiter for floating point
                     // but we are not yet ready to handle correctly codepages, etc
-                    let mut maybe_dot_pos = self.buffer.get_ref().iter().position(|b| *b == b'.');
-                    if maybe_dot_pos.is_none() {
-                        write!(self.buffer, ".").map_err(|error| {
-                            FieldIOError::new(ErrorKind::IoError(error), Some(field_info.clone()))
-                        })?;
-                        bytes_written = self.buffer.position();
-                        maybe_dot_pos = Some(bytes_written as usize)
-                    }
-                    let dot_pos = maybe_dot_pos.unwrap();
-                    let missing_decimals =
-                        field_info.num_decimal_places - (bytes_written - dot_pos as u64) as u8;
+                    let maybe_dot_pos = self.buffer.get_ref()[..bytes_written as usize]
+                        .iter()
+                        .position(|b| *b == b'.');
+                    let dot_pos = match maybe_dot_pos {
+                        Some(dot_pos) => dot_pos as u64,
+                        None => {
+                            write!(self.buffer, ".").map_err(|error| {
+                                FieldIOError::new(
+                                    ErrorKind::IoError(error),
+                                    Some(field_info.clone()),
+                                )
+                            })?;
+                            bytes_written
+                        }
+                    };
+                    bytes_written = self.buffer.position();
+                    let num_decimals_written = (bytes_written - dot_pos - 1) as u8;
+                    // The decimals are not added past the length of the field
+                    let missing_decimals = field_info
+                        .num_decimal_places
+                        .saturating_sub(num_decimals_written)
+                        .min(
+                            u8::try_from(field_info.field_length.saturating_sub(bytes_written as u16))
+                                .unwrap_or(u8::MAX),
+                        );
                     for _ in 0..missing_decimals {
                         write!(self.buffer, "0").map_err(|error| {
                             FieldIOError::new(ErrorKind::IoError(error), Some(field_info.clone()))
@@ -383,7 +933,7 @@ impl<'a, W: Write> FieldWriter<'a, W> {
                 self.dst.write_all(value).map_err(|error| {
                     FieldIOError::new(ErrorKind::IoError(error), Some(field_info.clone()))
                 })?;
-                for _ in 0..field_info.field_length - value.len() as u8 {
+                for _ in 0..usize::from(field_info.field_length) - value.len() {
                     write!(self.dst, " ").map_err(|error| {
                         FieldIOError::new(ErrorKind::IoError(error), Some(field_info.clone()))
                     })?;
@@ -401,6 +951,41 @@ impl<'a, W: Write> FieldWriter<'a, W> {
         }
     }
 
+    /// Writes the default value of the next field, as the record does not have one
+    ///
+    /// Returns false, writing nothing, when the field has no default value.
+    pub(crate) fn write_next_field_default(&mut self) -> Result<bool, FieldIOError> {
+        let (field_info, defaults) = match (self.fields_info.peek(), self.defaults) {
+            (Some(field_info), Some(defaults)) => (*field_info, defaults),
+            _ => return Ok(false),
+        };
+        if let Some(value) = defaults.value_for(field_info) {
+            self.write_next_field_value(value)?;
+            return Ok(true);
+        }
+        if defaults.policy == MissingFieldPolicy::Fail {
+            return Ok(false);
+        }
+        self.fields_info.next();
+        let blank = match field_info.field_type {
+            FieldType::Integer
+            | FieldType::Currency
+            | FieldType::Double
+            | FieldType::DateTime
+            | FieldType::Long
+            | FieldType::DBase7Double
+            | FieldType::Timestamp
+            | FieldType::Autoincrement => 0,
+            _ => b' ',
+        };
+        for _ in 0..field_info.field_length {
+            self.dst.write_u8(blank).map_err(|error| {
+                FieldIOError::new(ErrorKind::IoError(error), Some(field_info.clone()))
+            })?;
+        }
+        Ok(true)
+    }
+
     fn write_deletion_flag(&mut self) -> std::io::Result<()> {
         self.dst.write_u8(b' ')
     }
@@ -410,6 +995,177 @@ impl<'a, W: Write> FieldWriter<'a, W> {
     }
 }
 
+impl<'a> FieldWriter<'a, Vec<u8>> {
+    /// Replaces the content of the buffer by the record with the given index
+    pub(crate) fn write_record<R: WritableRecord>(
+        &mut self,
+        record: &R,
+        record_num: usize,
+    ) -> Result<(), Error> {
+        // The writer always starts again from the first field
+        let fields_info = self.fields_info.clone();
+        self.dst.clear();
+        self.write_deletion_flag()
+            .map_err(|error| Error::io_error(error, record_num))?;
+
+        let mut result = record
+            .write_using(self)
+            .map_err(|error| Error::new(error, record_num));
+        while result.is_ok() && !self.all_fields_were_written() {
+            match self.write_next_field_default() {
+                Ok(true) => {}
+                Ok(false) => break,
+                Err(error) => result = Err(Error::new(error, record_num)),
+            }
+        }
+        let all_fields_were_written = self.all_fields_were_written();
+        self.fields_info = fields_info;
+        result?;
+        if !all_fields_were_written {
+            return Err(Error {
+                record_num,
+                field: None,
+                kind: ErrorKind::NotEnoughFields,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Fails if the table has memo fields, as memo files cannot be written
+pub(crate) fn check_no_memo_fields(fields_info: &[FieldInfo]) -> Result<(), Error> {
+    if fields_info
+        .iter()
+        .any(|field_info| field_info.field_type == FieldType::Memo)
+    {
+        Err(Error {
+            record_num: 0,
+            field: None,
+            kind: ErrorKind::Message(
+                "tables with memo fields cannot be written, as memo files cannot be written"
+                    .to_owned(),
+            ),
+        })
+    } else {
+        Ok(())
+    }
+}
+
+/// Writes the header, the fields information and, for Visual FoxPro tables,
+/// the backlink; in other words everything that comes before the records.
+pub(crate) fn write_table_info<W: Write>(
+    dst: &mut W,
+    header: &Header,
+    fields_info: &[FieldInfo],
+    backlink: Option<&str>,
+    encoding: &dyn Encoding,
+) -> std::io::Result<()> {
+    header.write_to(dst)?;
+    if header.file_type.has_dbase7_layout() {
+        header.write_dbase7_extension(dst)?;
+        for field_info in fields_info {
+            field_info.write_dbase7_to(dst, encoding)?;
+        }
+    } else {
+        for field_info in fields_info {
+            field_info.write_to(dst, encoding)?;
+        }
+    }
+    dst.write_u8(TERMINATOR_VALUE)?;
+    if header.file_type.has_backlink() {
+        let mut backlink = backlink.map_or(vec![], |backlink| backlink.as_bytes().to_vec());
+        backlink.resize(usize::from(BACKLINK_SIZE), 0);
+        dst.write_all(&backlink)?;
+    }
+    Ok(())
+}
+
+/// Returns the length of the records, deletion flag included
+pub(crate) fn record_size(fields_info: &[FieldInfo]) -> u64 {
+    fields_info
+        .iter()
+        .fold(1u64, |size, info| size + u64::from(info.field_length))
+}
+
+/// Fails if a table with the fields and the number of records would exceed
+/// a limit of the format or the maximum size of the file
+pub(crate) fn check_format_limits(
+    header: &Header,
+    fields_info: &[FieldInfo],
+    num_records: usize,
+    max_file_size: u64,
+) -> Result<(), Error> {
+    let record_size = record_size(fields_info);
+    // The terminator ends the file
+    let file_size = table_info_size(header, fields_info.len()) as u64
+        + (num_records as u64).saturating_mul(record_size)
+        + 1;
+    let limit = if record_size > u64::from(u16::MAX) {
+        FormatLimit::RecordLength
+    } else if num_records as u64 > u64::from(u32::MAX) {
+        FormatLimit::NumRecords
+    } else if file_size > max_file_size {
+        FormatLimit::FileSize
+    } else {
+        return Ok(());
+    };
+    Err(Error {
+        record_num: num_records.saturating_sub(1),
+        field: None,
+        kind: ErrorKind::FormatLimitExceeded(limit),
+    })
+}
+
+/// Returns the path of the table with the given index (0 based) of tables
+/// continuing each other: `table.dbf`, `table_2.dbf`, `table_3.dbf`...
+pub(crate) fn continuation_path(path: &Path, index: usize) -> PathBuf {
+    if index == 0 {
+        return path.to_owned();
+    }
+    let stem = path
+        .file_stem()
+        .map_or_else(|| "table".into(), |stem| stem.to_string_lossy());
+    let file_name = match path.extension() {
+        Some(extension) => format!("{}_{}.{}", stem, index + 1, extension.to_string_lossy()),
+        None => format!("{}_{}", stem, index + 1),
+    };
+    path.with_file_name(file_name)
+}
+
+/// Returns the size of everything that comes before the records
+/// (see [write_table_info]), for the given number of fields (the deletion flag not included)
+pub(crate) fn table_info_size(header: &Header, num_fields: usize) -> usize {
+    if let Version::DBase2 = header.file_type {
+        // The header of dBase II tables has room for all the fields they can have
+        return usize::from(Header::DBASE2_SIZE);
+    }
+    let (header_size, descriptor_size) = if header.file_type.has_dbase7_layout() {
+        (Header::DBASE7_SIZE, FieldInfo::DBASE7_SIZE)
+    } else {
+        (Header::SIZE, FieldInfo::SIZE)
+    };
+    let size = header_size + num_fields * descriptor_size + std::mem::size_of::<u8>();
+    if header.file_type.has_backlink() {
+        size + usize::from(BACKLINK_SIZE)
+    } else {
+        size
+    }
+}
+
+/// Updates the sizes and the number of records of the header to match the fields
+/// (the deletion flag not included) and the records written.
+pub(crate) fn update_header(header: &mut Header, fields_info: &[FieldInfo], num_records: usize) {
+    let offset_to_first_record = table_info_size(header, fields_info.len());
+    // The deletion flag is part of the record
+    let size_of_record = fields_info
+        .iter()
+        .fold(1u16, |s, info| s + info.field_length);
+
+    header.num_records = num_records as u32;
+    header.offset_to_first_record = offset_to_first_record as u16;
+    header.size_of_record = size_of_record;
+}
+
 /// Structs that writes dBase records to a destination
 ///
 /// The only way to create a TableWriter is to use its
@@ -420,17 +1176,78 @@ pub struct TableWriter<W: Write> {
     /// contains the header of the input file
     /// if this writer was created form a reader
     header: Header,
+    /// path to the database of Visual FoxPro tables
+    backlink: Option<String>,
+    /// indexes to write along with the records
+    indexes: Vec<(IndexBuilder, Box<dyn Write>)>,
+    /// values of the fields records do not have
+    defaults: FieldDefaults,
+    max_file_size: u64,
+    overflow_policy: OverflowPolicy,
+    encoding: Arc<dyn Encoding>,
+    cipher: Option<Arc<dyn RecordCipher>>,
 }
 
 impl<W: Write> TableWriter<W> {
-    fn new(dst: W, fields_info: Vec<FieldInfo>, origin_header: Header) -> Self {
+    fn new(dst: W, builder: TableWriterBuilder) -> Self {
         Self {
             dst,
-            fields_info,
-            header: origin_header,
+            fields_info: builder.v,
+            header: builder.hdr,
+            backlink: builder.backlink,
+            indexes: vec![],
+            defaults: builder.defaults,
+            max_file_size: builder.max_file_size,
+            overflow_policy: builder.overflow_policy,
+            encoding: builder.encoding,
+            cipher: builder.cipher,
         }
     }
 
+    /// Attaches an index to the table, the keys of the records are computed
+    /// as they are written and the index is written to `dst` after the records.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dbase::{FieldName, IndexBuilder, TableWriterBuilder};
+    /// use std::convert::TryFrom;
+    /// use std::io::Cursor;
+    ///
+    /// # fn main() -> Result<(), dbase::Error> {
+    /// # let index_path = std::env::temp_dir().join("dbase_add_index_doc.ndx");
+    /// let mut reader = dbase::Reader::from_path("tests/data/line.dbf")?;
+    /// let records = reader.read()?;
+    ///
+    /// let writer = TableWriterBuilder::from_reader(reader)
+    ///     .build_with_dest(Cursor::new(Vec::<u8>::new()))
+    ///     .add_index_with_file_dest(IndexBuilder::new("NAME"), &index_path)
+    ///     .unwrap();
+    /// writer.write(&records)?;
+    ///
+    /// let index = dbase::Index::from_path(&index_path)?;
+    /// assert_eq!(index.header().key_expression(), "NAME");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn add_index<I: Write + 'static>(mut self, index: IndexBuilder, dst: I) -> Self {
+        self.indexes.push((index, Box::new(dst)));
+        self
+    }
+
+    /// Helper function to attach an index that will be written to
+    /// a newly created file at the given path.
+    ///
+    /// The file is wrapped in a `BufWriter` to increase performance.
+    pub fn add_index_with_file_dest<P: AsRef<Path>>(
+        self,
+        index: IndexBuilder,
+        path: P,
+    ) -> std::io::Result<Self> {
+        let dst = BufWriter::new(File::create(path)?);
+        Ok(self.add_index(index, dst))
+    }
+
     /// Writes the records to the inner destination
     /// and returns it once finished
     ///
@@ -464,62 +1281,236 @@ impl<W: Write> TableWriter<W> {
     /// let cursor = writer.write(&records).unwrap();
     /// assert_eq!(cursor.position(), 117)
     /// ```
-    pub fn write<R: WritableRecord>(mut self, records: &[R]) -> Result<W, Error> {
-        self.update_header(records.len());
-        self.header
-            .write_to(&mut self.dst)
-            .map_err(|error| Error::io_error(error, 0))?;
-        for record_info in &self.fields_info {
-            record_info
-                .write_to(&mut self.dst)
-                .map_err(|error| Error::io_error(error, 0))?;
+    pub fn write<R: WritableRecord>(self, records: &[R]) -> Result<W, Error> {
+        self.write_iter::<R, _, _>(records.len(), records.iter().map(Ok))
+    }
+
+    /// Writes the `num_records` records the iterator yields,
+    /// without having to hold them all in memory
+    pub(crate) fn write_iter<R, B, I>(self, num_records: usize, records: I) -> Result<W, Error>
+    where
+        R: WritableRecord,
+        B: Borrow<R>,
+        I: IntoIterator<Item = Result<B, Error>>,
+    {
+        let mut writer = self.start(num_records)?;
+        for record in records {
+            writer.write_record(record?.borrow())?;
         }
-        self.dst
-            .write_u8(TERMINATOR_VALUE)
-            .map_err(|error| Error::io_error(error, 0))?;
+        writer.finish()
+    }
+
+    /// Writes everything that comes before the records, announcing `num_records` records,
+    /// the records are then written one at a time with the returned writer
+    pub(crate) fn start(mut self, num_records: usize) -> Result<RecordsWriter<W>, Error> {
+        check_format_limits(
+            &self.header,
+            &self.fields_info,
+            num_records,
+            self.max_file_size,
+        )?;
+        update_header(&mut self.header, &self.fields_info, num_records);
+        write_table_info(
+            &mut self.dst,
+            &self.header,
+            &self.fields_info,
+            self.backlink.as_deref(),
+            &*self.encoding,
+        )
+        .map_err(|error| Error::io_error(error, 0))?;
+
+        let mut indexes = Vec::with_capacity(self.indexes.len());
+        for (index, dst) in self.indexes.drain(..) {
+            let encoding = Arc::clone(&self.encoding);
+            let updater = index.updater(&self.fields_info, encoding).map_err(|kind| Error {
+                record_num: 0,
+                field: None,
+                kind,
+            })?;
+            indexes.push((updater, dst));
+        }
+
+        Ok(RecordsWriter {
+            record_buffer: Vec::<u8>::with_capacity(self.header.size_of_record as usize),
+            dst: self.dst,
+            fields_info: self.fields_info,
+            indexes,
+            defaults: self.defaults,
+            overflow_policy: self.overflow_policy,
+            encoding: self.encoding,
+            cipher: self.cipher,
+            encrypted_buffer: vec![],
+            num_records,
+            num_written: 0,
+        })
+    }
+}
+
+/// Writes the records of a table one at a time, once everything that comes before them
+/// is written by [TableWriter::start]
+pub(crate) struct RecordsWriter<W: Write> {
+    dst: W,
+    fields_info: Vec<FieldInfo>,
+    indexes: Vec<(IndexUpdater, Box<dyn Write>)>,
+    defaults: FieldDefaults,
+    overflow_policy: OverflowPolicy,
+    encoding: Arc<dyn Encoding>,
+    cipher: Option<Arc<dyn RecordCipher>>,
+    /// Records are first written in this buffer, so that their content
+    /// can be read back when indexes have to be updated
+    record_buffer: Vec<u8>,
+    /// Buffer the records are encrypted in, when the table is encrypted
+    encrypted_buffer: Vec<u8>,
+    /// The number of records written in the header
+    num_records: usize,
+    num_written: usize,
+}
+
+/// Encodes the records in the bytes written to the table,
+/// so that they can be encoded in other threads than the one writing them
+#[derive(Debug, Clone)]
+pub(crate) struct RecordEncoder {
+    fields_info: Vec<FieldInfo>,
+    defaults: FieldDefaults,
+    overflow_policy: OverflowPolicy,
+    encoding: Arc<dyn Encoding>,
+}
 
+impl RecordEncoder {
+    /// Returns the length of the records, deletion flag included
+    pub(crate) fn record_size(&self) -> usize {
+        record_size(&self.fields_info) as usize
+    }
+
+    /// Replaces the content of `dst` by the bytes of the record with the given index
+    pub(crate) fn encode<R: WritableRecord>(
+        &self,
+        record: &R,
+        record_num: usize,
+        dst: &mut Vec<u8>,
+    ) -> Result<(), Error> {
         let mut field_writer = FieldWriter {
-            dst: &mut self.dst,
+            dst,
             fields_info: self.fields_info.iter().peekable(),
             buffer: Cursor::new(vec![0u8; 255]),
+            defaults: Some(&self.defaults),
+            overflow_policy: self.overflow_policy,
+            encoding: &*self.encoding,
         };
+        field_writer.write_record(record, record_num)
+    }
+}
 
-        for (i, record) in records.iter().enumerate() {
-            field_writer
-                .write_deletion_flag()
-                .map_err(|error| Error::io_error(error, i))?;
+impl<W: Write> RecordsWriter<W> {
+    /// Returns the encoder of the records of the table
+    pub(crate) fn encoder(&self) -> RecordEncoder {
+        RecordEncoder {
+            fields_info: self.fields_info.clone(),
+            defaults: self.defaults.clone(),
+            overflow_policy: self.overflow_policy,
+            encoding: self.encoding.clone(),
+        }
+    }
 
-            record
-                .write_using(&mut field_writer)
-                .map_err(|error| Error::new(error, i))?;
+    /// Returns the index of the next record, fails if all the records announced were written
+    fn next_record_num(&self) -> Result<usize, Error> {
+        let i = self.num_written;
+        if i == self.num_records {
+            return Err(Error {
+                record_num: i,
+                field: None,
+                kind: ErrorKind::Message("more records than announced".to_owned()),
+            });
+        }
+        Ok(i)
+    }
 
-            if !field_writer.all_fields_were_written() {
-                return Err(Error {
-                    record_num: i,
-                    field: None,
-                    kind: ErrorKind::NotEnoughFields,
-                });
+    /// Writes a record after the ones already written
+    pub(crate) fn write_record<R: WritableRecord>(&mut self, record: &R) -> Result<(), Error> {
+        let i = self.next_record_num()?;
+        let mut field_writer = FieldWriter {
+            dst: &mut self.record_buffer,
+            fields_info: self.fields_info.iter().peekable(),
+            buffer: Cursor::new(vec![0u8; 255]),
+            defaults: Some(&self.defaults),
+            overflow_policy: self.overflow_policy,
+            encoding: &*self.encoding,
+        };
+        field_writer.write_record(record, i)?;
+        let record_buffer = std::mem::take(&mut self.record_buffer);
+        let result = self.write_encoded(&record_buffer);
+        self.record_buffer = record_buffer;
+        result
+    }
+
+    /// Writes a record encoded by a [RecordEncoder] after the ones already written
+    pub(crate) fn write_encoded(&mut self, record: &[u8]) -> Result<(), Error> {
+        let i = self.next_record_num()?;
+        let written = match self.cipher.as_deref() {
+            Some(cipher) => {
+                self.encrypted_buffer.clear();
+                self.encrypted_buffer.extend_from_slice(record);
+                encrypt_record(Some(cipher), i, &mut self.encrypted_buffer);
+                &self.encrypted_buffer
+            }
+            None => record,
+        };
+        self.dst
+            .write_all(written)
+            .map_err(|error| Error::io_error(error, i))?;
+        self.num_written += 1;
+
+        if !self.indexes.is_empty() {
+            // The values are read back in the encoding they were written with
+            let options = ReadingOptions {
+                encoding: Arc::clone(&self.encoding),
+                ..ReadingOptions::default()
+            };
+            // Skip the deletion flag
+            let mut source = Cursor::new(&record[1..]);
+            let written_record = Record::read_using(&mut FieldIterator {
+                source: &mut source,
+                fields_info: self.fields_info.iter().peekable(),
+                memo_reader: &mut None::<MemoReader<Cursor<Vec<u8>>>>,
+                options: &options,
+            })
+            .map_err(|error| Error::new(error, i))?;
+            for (updater, _) in &mut self.indexes {
+                updater
+                    .add(&written_record, i as u32)
+                    .map_err(|kind| Error {
+                        record_num: i,
+                        field: None,
+                        kind,
+                    })?;
             }
-            field_writer.fields_info = self.fields_info.iter().peekable();
+        }
+        Ok(())
+    }
+
+    /// Ends the table and writes the indexes,
+    /// fails if fewer records than announced were written
+    pub(crate) fn finish(mut self) -> Result<W, Error> {
+        let num_records = self.num_records;
+        if self.num_written != num_records {
+            return Err(Error {
+                record_num: self.num_written,
+                field: None,
+                kind: ErrorKind::Message("fewer records than announced".to_owned()),
+            });
         }
 
         self.dst
             .write_u8(FILE_TERMINATOR)
-            .map_err(|error| Error::io_error(error, records.len()))?;
-
-        Ok(self.dst)
-    }
+            .map_err(|error| Error::io_error(error, num_records))?;
 
-    fn update_header(&mut self, num_records: usize) {
-        let offset_to_first_record =
-            Header::SIZE + (self.fields_info.len() * FieldInfo::SIZE) + std::mem::size_of::<u8>();
-        let size_of_record = self
-            .fields_info
-            .iter()
-            .fold(0u16, |s, ref info| s + info.field_length as u16);
+        for (updater, mut dst) in self.indexes {
+            updater
+                .write_to(&mut dst)
+                .and_then(|_| dst.flush())
+                .map_err(|error| Error::io_error(error, num_records))?;
+        }
 
-        self.header.num_records = num_records as u32;
-        self.header.offset_to_first_record = offset_to_first_record as u16;
-        self.header.size_of_record = size_of_record;
+        Ok(self.dst)
     }
 }
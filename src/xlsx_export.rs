@@ -0,0 +1,316 @@
+//! Module with the definition of fn's and struct's to export dBase files
+//! as Excel (.xlsx) workbooks
+//!
+//! Each table is written in its own worksheet, the first row contains the names
+//! of the fields and the cells have the type of the values:
+//!
+//! - Character and Memo values are strings
+//! - Numeric, Float, Double, Currency and Integer values are numbers,
+//!   displayed with the decimal places of the field
+//! - Logical values are booleans
+//! - Date and DateTime values are dates, dates that Excel cannot represent
+//!   are written as text
+//! - empty values are blank cells
+use std::convert::TryFrom;
+use std::io::{Read, Seek};
+use std::path::Path;
+
+use rust_xlsxwriter::{ExcelDateTime, Format, Workbook, Worksheet, XlsxError};
+
+use crate::error::{Error, ErrorKind};
+use crate::reading::{Reader, RecordValues};
+use crate::record::field::{Date, FieldType, FieldValue, Time};
+use crate::record::FieldInfo;
+use crate::sql::text_value;
+
+fn xlsx_error(error: XlsxError, record_num: usize) -> Error {
+    Error {
+        record_num,
+        field: None,
+        kind: ErrorKind::Message(error.to_string()),
+    }
+}
+
+fn excel_date_time(date: Date, time: Option<Time>) -> Option<ExcelDateTime> {
+    let date_time = ExcelDateTime::from_ymd(
+        u16::try_from(date.year()).ok()?,
+        u8::try_from(date.month()).ok()?,
+        u8::try_from(date.day()).ok()?,
+    )
+    .ok()?;
+    match time {
+        Some(time) => date_time
+            .and_hms(
+                u16::try_from(time.hours()).ok()?,
+                u8::try_from(time.minutes()).ok()?,
+                time.seconds(),
+            )
+            .ok(),
+        None => Some(date_time),
+    }
+}
+
+/// The format of the cells of a column
+fn column_format(field_info: &FieldInfo) -> Format {
+    match field_info.field_type {
+        FieldType::Numeric | FieldType::Float if field_info.num_decimal_places > 0 => Format::new()
+            .set_num_format(format!(
+                "0.{}",
+                "0".repeat(usize::from(field_info.num_decimal_places))
+            )),
+        FieldType::Numeric
+        | FieldType::Float
+        | FieldType::Integer
+        | FieldType::Long
+        | FieldType::Autoincrement => Format::new().set_num_format("0"),
+        FieldType::Currency => Format::new().set_num_format("0.0000"),
+        FieldType::Date => Format::new().set_num_format("yyyy-mm-dd"),
+        FieldType::DateTime | FieldType::Timestamp => {
+            Format::new().set_num_format("yyyy-mm-dd hh:mm:ss")
+        }
+        _ => Format::new(),
+    }
+}
+
+fn write_value(
+    worksheet: &mut Worksheet,
+    row: u32,
+    column: u16,
+    value: FieldValue,
+    format: &Format,
+) -> Result<(), XlsxError> {
+    let date_time = match value {
+        FieldValue::Date(Some(date)) => Some(excel_date_time(date, None)),
+        FieldValue::DateTime(date_time) | FieldValue::Timestamp(Some(date_time)) => {
+            Some(excel_date_time(date_time.date(), Some(date_time.time())))
+        }
+        _ => None,
+    };
+    match (value, date_time) {
+        (_, Some(Some(date_time))) => {
+            worksheet.write_datetime_with_format(row, column, &date_time, format)?;
+        }
+        (value, Some(None)) => {
+            if let Some(text) = text_value(&value) {
+                worksheet.write_string(row, column, text)?;
+            }
+        }
+        (FieldValue::Character(Some(text)), _) | (FieldValue::Memo(text), _) => {
+            worksheet.write_string(row, column, text)?;
+        }
+        (FieldValue::Numeric(Some(value)), _)
+        | (FieldValue::Double(value), _)
+        | (FieldValue::Currency(value), _)
+        | (FieldValue::DBase7Double(Some(value)), _) => {
+            worksheet.write_number_with_format(row, column, value, format)?;
+        }
+        (FieldValue::Float(Some(value)), _) => {
+            worksheet.write_number_with_format(row, column, value, format)?;
+        }
+        (FieldValue::Integer(value), _)
+        | (FieldValue::Long(Some(value)), _)
+        | (FieldValue::Autoincrement(value), _) => {
+            worksheet.write_number_with_format(row, column, value, format)?;
+        }
+        (FieldValue::NumericInteger(Some(value)), _) => {
+            worksheet.write_number_with_format(row, column, value as f64, format)?;
+        }
+        (FieldValue::Logical(Some(value)), _) => {
+            worksheet.write_boolean(row, column, value)?;
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Excel workbook to which tables are exported, one worksheet per table
+///
+/// # Example
+///
+/// ```no_run
+/// # fn main() -> Result<(), dbase::Error> {
+/// let mut workbook = dbase::XlsxWorkbook::new();
+/// workbook.add_table("lines", &mut dbase::Reader::from_path("tests/data/line.dbf")?)?;
+/// workbook.add_table("stations", &mut dbase::Reader::from_path("tests/data/stations.dbf")?)?;
+/// workbook.save("tables.xlsx")?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct XlsxWorkbook {
+    workbook: Workbook,
+    sheet_names: Vec<String>,
+}
+
+impl XlsxWorkbook {
+    /// Creates an empty workbook
+    pub fn new() -> Self {
+        Self {
+            workbook: Workbook::new(),
+            sheet_names: vec![],
+        }
+    }
+
+    /// Writes the remaining records of the reader in a new worksheet, the records
+    /// marked as deleted are not written.
+    ///
+    /// The worksheet name must be valid for Excel: at most 31 characters,
+    /// none of `[]:*?/\` and unique in the workbook.
+    /// Returns the number of records written.
+    pub fn add_table<T: Read + Seek>(
+        &mut self,
+        sheet_name: &str,
+        reader: &mut Reader<T>,
+    ) -> Result<usize, Error> {
+        let fields_info = reader
+            .fields_info
+            .iter()
+            .filter(|field_info| !field_info.is_deletion_flag())
+            .cloned()
+            .collect::<Vec<FieldInfo>>();
+        let formats = fields_info
+            .iter()
+            .map(column_format)
+            .collect::<Vec<Format>>();
+
+        // Excel compares the names of the worksheets without case
+        let lowercase_name = sheet_name.to_lowercase();
+        if self.sheet_names.contains(&lowercase_name) {
+            return Err(xlsx_error(
+                XlsxError::SheetnameReused(sheet_name.to_owned()),
+                0,
+            ));
+        }
+        let mut worksheet = Worksheet::new();
+        worksheet
+            .set_name(sheet_name)
+            .map_err(|error| xlsx_error(error, 0))?;
+        let header_format = Format::new().set_bold();
+        for (column, field_info) in (0u16..).zip(&fields_info) {
+            worksheet
+                .write_string_with_format(0, column, field_info.name(), &header_format)
+                .map_err(|error| xlsx_error(error, 0))?;
+        }
+        worksheet
+            .set_freeze_panes(1, 0)
+            .map_err(|error| xlsx_error(error, 0))?;
+
+        let mut num_records = 0;
+        while let Some(record) = reader.read_next_record_as::<RecordValues>() {
+            let record = record?;
+            if record.is_deleted {
+                continue;
+            }
+            let row = u32::try_from(num_records + 1).unwrap_or(u32::MAX);
+            for ((column, value), format) in (0u16..).zip(record.values).zip(&formats) {
+                write_value(&mut worksheet, row, column, value, format)
+                    .map_err(|error| xlsx_error(error, num_records))?;
+            }
+            num_records += 1;
+        }
+        self.workbook.push_worksheet(worksheet);
+        self.sheet_names.push(lowercase_name);
+        Ok(num_records)
+    }
+
+    /// Saves the workbook to the file at the given path
+    pub fn save<P: AsRef<Path>>(mut self, path: P) -> Result<(), Error> {
+        self.workbook
+            .save(path.as_ref())
+            .map_err(|error| xlsx_error(error, 0))
+    }
+
+    /// Returns the content of the .xlsx file of the workbook
+    pub fn save_to_buffer(mut self) -> Result<Vec<u8>, Error> {
+        self.workbook
+            .save_to_buffer()
+            .map_err(|error| xlsx_error(error, 0))
+    }
+}
+
+impl Default for XlsxWorkbook {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Read + Seek> Reader<T> {
+    /// Writes the remaining records in a workbook with a single worksheet
+    /// saved to the given path, see [XlsxWorkbook](struct.XlsxWorkbook.html)
+    ///
+    /// Returns the number of records written.
+    pub fn to_xlsx<P: AsRef<Path>>(&mut self, path: P, sheet_name: &str) -> Result<usize, Error> {
+        let mut workbook = XlsxWorkbook::new();
+        let num_records = workbook.add_table(sheet_name, self)?;
+        workbook.save(path)?;
+        Ok(num_records)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::convert::TryFrom;
+    use std::io::Cursor;
+
+    use crate::{FieldName, Record, TableWriterBuilder};
+
+    fn table() -> Cursor<Vec<u8>> {
+        let records = [
+            (
+                Some("Ferris"),
+                Some(1.5),
+                Some(true),
+                Some(Date::new(15, 5, 2015)),
+            ),
+            (None, None, None, Some(Date::new(0, 0, 0))),
+        ]
+        .iter()
+        .map(|(name, price, paid, date)| {
+            let mut record = Record::default();
+            record.insert(
+                "NAME".to_owned(),
+                FieldValue::Character(name.map(str::to_owned)),
+            );
+            record.insert("PRICE".to_owned(), FieldValue::Numeric(*price));
+            record.insert("PAID".to_owned(), FieldValue::Logical(*paid));
+            record.insert("ON".to_owned(), FieldValue::Date(*date));
+            record
+        })
+        .collect::<Vec<Record>>();
+        let mut cursor = TableWriterBuilder::new()
+            .add_character_field(FieldName::try_from("NAME").unwrap(), 20)
+            .add_numeric_field(FieldName::try_from("PRICE").unwrap(), 8, 2)
+            .add_logical_field(FieldName::try_from("PAID").unwrap())
+            .add_date_field(FieldName::try_from("ON").unwrap())
+            .build_with_dest(Cursor::new(Vec::<u8>::new()))
+            .write(&records)
+            .unwrap();
+        cursor.set_position(0);
+        cursor
+    }
+
+    #[test]
+    fn cell_formats_and_dates() {
+        let mut field =
+            FieldInfo::new(FieldName::try_from("PRICE").unwrap(), FieldType::Numeric, 8);
+        field.num_decimal_places = 3;
+        assert_eq!(column_format(&field), Format::new().set_num_format("0.000"));
+        assert!(excel_date_time(Date::new(15, 5, 2015), None).is_some());
+        assert!(excel_date_time(Date::new(0, 0, 0), None).is_none());
+    }
+
+    #[test]
+    fn export_to_xlsx() {
+        let mut workbook = XlsxWorkbook::new();
+        let mut reader = Reader::new(table()).unwrap();
+        assert_eq!(workbook.add_table("items", &mut reader).unwrap(), 2);
+        let mut reader = Reader::new(table()).unwrap();
+        assert!(workbook.add_table("ITEMS", &mut reader).is_err());
+        let mut reader = Reader::new(table()).unwrap();
+        assert!(workbook.add_table("items [1/2]", &mut reader).is_err());
+
+        let content = workbook.save_to_buffer().unwrap();
+        assert!(content.starts_with(b"PK\x03\x04"));
+    }
+}
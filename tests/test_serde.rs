@@ -136,7 +136,7 @@ mod serde_tests {
 
         let writer = TableWriterBuilder::new()
             .add_logical_field(FieldName::try_from("yes").unwrap())
-            .add_character_field(FieldName::try_from("not present").unwrap(), 50)
+            .add_character_field(FieldName::try_from("absent").unwrap(), 50)
             .build_with_dest(Cursor::new(Vec::<u8>::new()));
 
         let error = writer.write(&records).expect_err("We expected an Error");
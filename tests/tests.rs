@@ -1,11 +1,18 @@
 #[macro_use]
 extern crate dbase;
+extern crate flate2;
 
 use std::io::{Cursor, Read, Seek, Write};
 
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+
 use dbase::{
-    Date, DateTime, FieldIOError, FieldIterator, FieldName, FieldValue, FieldWriter,
-    ReadableRecord, Reader, Record, TableWriterBuilder, Time, WritableRecord,
+    CompactString, Date, DateTime, ErrorKind, FieldIOError, FieldIterator, FieldName,
+    FieldNameCase, FieldNameIssue, FieldNamePolicy, FieldValue, FieldWriter, FormatLimit, Index,
+    IndexBuilder, MissingFieldPolicy, OverflowPolicy, ReadableRecord, Reader, ReadingOptions,
+    Record, RecordCipher, TableWriterBuilder, Time, WritableRecord,
 };
 use std::convert::{TryFrom, TryInto};
 use std::fmt::Debug;
@@ -164,10 +171,7 @@ fn from_scratch_fox_pro_record() {
     record.insert(String::from("currency"), FieldValue::Currency(4567.134));
     record.insert(
         String::from("datetime"),
-        FieldValue::DateTime(DateTime::new(
-            Date::new(01, 06, 2006),
-            Time::new(12, 50, 20),
-        )),
+        FieldValue::DateTime(DateTime::new(Date::new(1, 6, 2006), Time::new(12, 50, 20))),
     );
 
     let records = vec![record];
@@ -193,7 +197,7 @@ fn from_scratch_fox_pro_struct_record() {
         .add_integer_field(FieldName::try_from("integer").unwrap());
 
     let records = vec![FoxProRecord {
-        datetime: DateTime::new(Date::new(12, 02, 1999), Time::new(21, 20, 35)),
+        datetime: DateTime::new(Date::new(12, 2, 1999), Time::new(21, 20, 35)),
         double: 8649.48851,
         currency: 3489.9612314,
         integer: 42069,
@@ -202,6 +206,60 @@ fn from_scratch_fox_pro_struct_record() {
     write_read_compare(&records, writer_builder);
 }
 
+dbase_record! {
+    #[derive(Clone, Debug, PartialEq)]
+    struct Invoice {
+        #[dbase(name = "CUSTOMER", len = 30)]
+        customer_name: String,
+        #[dbase(len = 10, decimals = 2)]
+        amount: f64,
+        #[dbase(field_type = dbase::FieldType::Currency)]
+        tax: f64,
+        paid: Option<bool>,
+        issued: Date,
+        lines: i32,
+    }
+}
+
+#[test]
+fn schema_from_struct() {
+    let writer_builder = TableWriterBuilder::from_schema::<Invoice>().unwrap();
+    let records = vec![Invoice {
+        customer_name: "Ferris".to_string(),
+        amount: 1234.5,
+        tax: 246.9,
+        paid: None,
+        issued: Date::new(14, 10, 2026),
+        lines: 3,
+    }];
+    let mut dst = writer_builder
+        .clone()
+        .build_with_dest(Cursor::new(Vec::<u8>::new()))
+        .write(&records)
+        .unwrap();
+    dst.set_position(0);
+    let reader = Reader::new(dst).unwrap();
+    let fields = reader
+        .fields()
+        .iter()
+        .skip(1)
+        .map(|info| (info.name().to_owned(), info.field_type(), info.length()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        fields,
+        vec![
+            ("CUSTOMER".to_owned(), dbase::FieldType::Character, 30),
+            ("amount".to_owned(), dbase::FieldType::Numeric, 10),
+            ("tax".to_owned(), dbase::FieldType::Currency, 8),
+            ("paid".to_owned(), dbase::FieldType::Logical, 1),
+            ("issued".to_owned(), dbase::FieldType::Date, 8),
+            ("lines".to_owned(), dbase::FieldType::Integer, 4),
+        ]
+    );
+
+    write_read_compare(&records, writer_builder);
+}
+
 dbase_record! {
     #[derive(Clone, Debug, PartialEq)]
     struct User {
@@ -248,3 +306,589 @@ fn the_classical_user_record_example() {
 
     assert_eq!(read_records, users);
 }
+
+#[test]
+fn indexes_are_written_along_with_records() {
+    let index_path = std::env::temp_dir().join("dbase_indexes_written_along.ndx");
+    let users = vec![
+        User {
+            first_name: "Ferrys".to_string(),
+            last_name: "Rust".to_string(),
+        },
+        User {
+            first_name: "Alex".to_string(),
+            last_name: "Rider".to_string(),
+        },
+        User {
+            first_name: "Jamie".to_string(),
+            last_name: "Oliver".to_string(),
+        },
+    ];
+
+    let writer = TableWriterBuilder::new()
+        .add_character_field("First Name".try_into().unwrap(), 50)
+        .add_character_field("Last Name".try_into().unwrap(), 50)
+        .build_with_dest(Cursor::new(Vec::<u8>::new()))
+        .add_index_with_file_dest(IndexBuilder::new("Last Name"), &index_path)
+        .unwrap();
+    let mut cursor = writer.write(&users).unwrap();
+    cursor.set_position(0);
+
+    let mut reader = Reader::new(cursor).unwrap();
+    let mut index = Index::from_path(&index_path).unwrap();
+    let by_last_name = index
+        .range_as::<User, _, &str, _>(&mut reader, ..)
+        .unwrap()
+        .map(|user| user.unwrap().last_name)
+        .collect::<Vec<String>>();
+    assert_eq!(by_last_name, vec!["Oliver", "Rider", "Rust"]);
+}
+
+#[test]
+fn read_gzipped_stream() {
+    let mut encoder = GzEncoder::new(Vec::<u8>::new(), Compression::default());
+    encoder
+        .write_all(&std::fs::read(LINE_DBF).unwrap())
+        .unwrap();
+    let compressed = encoder.finish().unwrap();
+
+    let mut reader = Reader::from_stream(GzDecoder::new(&compressed[..])).unwrap();
+    let records = reader.read().unwrap();
+    assert_eq!(records, dbase::read(LINE_DBF).unwrap());
+}
+
+#[test]
+fn write_read_numeric_values() {
+    let records = [Some(1.25), None, Some(-3.5), Some(7.0)]
+        .iter()
+        .map(|value| {
+            let mut record = Record::default();
+            record.insert("PRICE".to_owned(), FieldValue::Numeric(*value));
+            record
+        })
+        .collect::<Vec<Record>>();
+
+    let writer_builder =
+        TableWriterBuilder::new().add_numeric_field(FieldName::try_from("PRICE").unwrap(), 10, 2);
+    write_read_compare(&records, writer_builder);
+}
+
+#[test]
+fn duplicated_field_names() {
+    let mut record = Record::default();
+    record.insert(
+        "NAME".to_owned(),
+        FieldValue::Character(Some("Ferris".to_owned())),
+    );
+    let content = TableWriterBuilder::new()
+        .add_character_field(FieldName::try_from("NAME").unwrap(), 10)
+        .add_character_field(FieldName::try_from("NAME").unwrap(), 12)
+        .build_with_dest(Cursor::new(Vec::<u8>::new()))
+        .write(&[record])
+        .unwrap()
+        .into_inner();
+
+    let mut reader = Reader::new(Cursor::new(content.clone())).unwrap();
+    assert_eq!(
+        reader.field_name_issues(),
+        &[FieldNameIssue::Duplicate {
+            index: 1,
+            name: "NAME".to_owned()
+        }]
+    );
+    assert_eq!(reader.read().unwrap().pop().unwrap().into_iter().count(), 1);
+
+    let options = ReadingOptions::new().field_name_policy(FieldNamePolicy::RenameDuplicates);
+    let mut reader = Reader::with_options(Cursor::new(content.clone()), options).unwrap();
+    let record = reader.read().unwrap().pop().unwrap();
+    assert_eq!(record.get("NAME"), record.get("NAME_2"));
+    assert!(record.get("NAME").is_some());
+
+    let options = ReadingOptions::new().field_name_policy(FieldNamePolicy::Fail);
+    let error = Reader::with_options(Cursor::new(content), options)
+        .err()
+        .unwrap();
+    assert!(matches!(error.kind(), ErrorKind::InvalidFieldName(_)));
+}
+
+#[test]
+fn field_names_in_one_case() {
+    let options = ReadingOptions::new().field_name_case(FieldNameCase::Upper);
+    let mut reader = Reader::from_path_with_options("tests/data/stations.dbf", options).unwrap();
+    assert_eq!(reader.fields()[2].name(), "MARKER-COL");
+    let record = reader.read().unwrap().remove(0);
+    assert!(record.get("name").is_none());
+    assert_eq!(
+        record.get("NAME"),
+        Some(&FieldValue::Character(Some("Van Dorn Street".to_owned())))
+    );
+
+    let options = ReadingOptions::new().field_name_case(FieldNameCase::Lower);
+    let mut reader = Reader::from_path_with_options("tests/data/stations.dbf", options).unwrap();
+    let mut record = reader.read().unwrap().remove(0);
+    assert_eq!(record.get_ignore_case("Marker-Sym"), record.get("marker-sym"));
+    assert!(record.get_mut_ignore_case("LINE").is_some());
+    assert!(record.get_ignore_case("missing").is_none());
+}
+
+#[test]
+fn encrypted_table_is_rejected() {
+    let mut content = std::fs::read(LINE_DBF).unwrap();
+    // The encryption flag of dBase IV
+    content[15] = 1;
+    let error = Reader::new(Cursor::new(content)).err().unwrap();
+    assert!(matches!(error.kind(), ErrorKind::EncryptedTable));
+}
+
+/// Adds the key to the bytes, shifted by the index of the record
+#[derive(Debug, Clone)]
+struct ShiftCipher(Vec<u8>);
+
+impl ShiftCipher {
+    fn shift(&self, record_index: u32, position: usize) -> u8 {
+        self.0[position % self.0.len()].wrapping_add(record_index as u8)
+    }
+}
+
+impl RecordCipher for ShiftCipher {
+    fn encrypt(&self, record_index: u32, fields: &mut [u8]) {
+        for (i, byte) in fields.iter_mut().enumerate() {
+            *byte = byte.wrapping_add(self.shift(record_index, i));
+        }
+    }
+
+    fn decrypt(&self, record_index: u32, fields: &mut [u8]) {
+        for (i, byte) in fields.iter_mut().enumerate() {
+            *byte = byte.wrapping_sub(self.shift(record_index, i));
+        }
+    }
+}
+
+#[test]
+fn encrypted_table_round_trip() {
+    let cipher = ShiftCipher(b"key".to_vec());
+    let records = vec![
+        Record::builder().set("NAME", "Alice").set("AGE", 31.0).build(),
+        Record::builder().set("NAME", "Bob").set("AGE", 42.0).build(),
+    ];
+    let content = TableWriterBuilder::new()
+        .add_character_field(FieldName::try_from("NAME").unwrap(), 10)
+        .add_numeric_field(FieldName::try_from("AGE").unwrap(), 3, 0)
+        .encryption(cipher.clone())
+        .build_with_dest(Cursor::new(Vec::<u8>::new()))
+        .write(&records)
+        .unwrap()
+        .into_inner();
+    assert_eq!(content[15], 1);
+    assert!(!content.windows(5).any(|bytes| bytes == b"Alice"));
+
+    let error = Reader::new(Cursor::new(content.clone())).err().unwrap();
+    assert!(matches!(error.kind(), ErrorKind::EncryptedTable));
+
+    let options = ReadingOptions::default().decryption(cipher.clone());
+    let mut reader = Reader::with_options(Cursor::new(content.clone()), options.clone()).unwrap();
+    assert!(reader.header().is_encrypted());
+    let read_records = reader.read().unwrap();
+    assert_eq!(read_records, records);
+
+    let mut reader = Reader::with_options(Cursor::new(content.clone()), options.clone()).unwrap();
+    let names = reader.read_column::<String>("NAME").unwrap();
+    assert_eq!(names, vec![Some("Alice".to_owned()), Some("Bob".to_owned())]);
+
+    // The tables written from a reader of an encrypted table are encrypted the same way
+    let reader = Reader::with_options(Cursor::new(content.clone()), options.clone()).unwrap();
+    let rewritten = TableWriterBuilder::from_reader(reader)
+        .build_with_dest(Cursor::new(Vec::<u8>::new()))
+        .write(&read_records)
+        .unwrap()
+        .into_inner();
+    let header_size = rewritten.len() - 2 * 14 - 1;
+    assert_eq!(rewritten[header_size..], content[header_size..]);
+}
+
+dbase_record! {
+    #[derive(Clone, Debug, PartialEq)]
+    struct CompactUser {
+        first_name: CompactString,
+        last_name: Option<CompactString>,
+    }
+}
+
+#[test]
+fn compact_string_record() {
+    let users = vec![
+        CompactUser {
+            first_name: "Ferrys".into(),
+            last_name: Some("Rust".into()),
+        },
+        CompactUser {
+            first_name: "A first name too long to be inline".into(),
+            last_name: None,
+        },
+    ];
+    let cursor = TableWriterBuilder::new()
+        .add_character_field("First Name".try_into().unwrap(), 50)
+        .add_character_field("Last Name".try_into().unwrap(), 50)
+        .build_with_dest(Cursor::new(Vec::<u8>::new()))
+        .write(&users)
+        .unwrap();
+
+    let mut reader = Reader::new(Cursor::new(cursor.into_inner())).unwrap();
+    let read_records = reader.read_as::<CompactUser>().unwrap();
+    assert_eq!(read_records, users);
+    assert!(!read_records[1].first_name.is_inline());
+}
+
+#[test]
+fn read_with_small_buffer() {
+    let expected = dbase::read("tests/data/stations.dbf").unwrap();
+    let options = ReadingOptions::new().buffer_size(16);
+    let mut reader = Reader::from_path_with_options("tests/data/stations.dbf", options).unwrap();
+    assert_eq!(reader.read().unwrap(), expected);
+}
+
+struct FirstNameOnly {
+    first_name: String,
+}
+
+impl WritableRecord for FirstNameOnly {
+    fn write_using<'a, W: Write>(
+        &self,
+        field_writer: &mut FieldWriter<'a, W>,
+    ) -> Result<(), FieldIOError> {
+        field_writer.write_next_field_value(&self.first_name)
+    }
+}
+
+#[test]
+fn missing_fields_on_write() {
+    let writer_builder = TableWriterBuilder::new()
+        .add_character_field(FieldName::try_from("first_name").unwrap(), 20)
+        .add_character_field(FieldName::try_from("last_name").unwrap(), 20)
+        .add_numeric_field(FieldName::try_from("age").unwrap(), 3, 0)
+        .add_integer_field(FieldName::try_from("visits").unwrap());
+    let users = vec![FirstNameOnly {
+        first_name: "Ferris".to_owned(),
+    }];
+
+    let error = writer_builder
+        .clone()
+        .build_with_dest(Cursor::new(Vec::<u8>::new()))
+        .write(&users)
+        .unwrap_err();
+    assert!(matches!(error.kind(), ErrorKind::NotEnoughFields));
+
+    let mut dst = writer_builder
+        .clone()
+        .missing_field_policy(MissingFieldPolicy::Blank)
+        .default_value("last_name", FieldValue::Character(Some("Crab".to_owned())))
+        .build_with_dest(Cursor::new(Vec::<u8>::new()))
+        .write(&users)
+        .unwrap();
+    dst.set_position(0);
+    let records = Reader::new(dst).unwrap().read().unwrap();
+    assert_eq!(
+        records[0].get("last_name"),
+        Some(&FieldValue::Character(Some("Crab".to_owned())))
+    );
+    assert_eq!(records[0].get("age"), Some(&FieldValue::Numeric(None)));
+    assert_eq!(records[0].get("visits"), Some(&FieldValue::Integer(0)));
+
+    // Like dBase, the defaults are found ignoring the case of the names
+    let mut dst = writer_builder
+        .clone()
+        .missing_field_policy(MissingFieldPolicy::Blank)
+        .default_value("LAST_NAME", FieldValue::Character(Some("Crab".to_owned())))
+        .build_with_dest(Cursor::new(Vec::<u8>::new()))
+        .write(&users)
+        .unwrap();
+    dst.set_position(0);
+    let records = Reader::new(dst).unwrap().read().unwrap();
+    assert_eq!(
+        records[0].get("last_name"),
+        Some(&FieldValue::Character(Some("Crab".to_owned())))
+    );
+
+    let mut record = Record::default();
+    record.insert(
+        "first_name".to_owned(),
+        FieldValue::from("Ferris".to_owned()),
+    );
+    let error = writer_builder
+        .clone()
+        .default_value_for_type(dbase::FieldType::Numeric, FieldValue::Numeric(Some(7.0)))
+        .build_with_dest(Cursor::new(Vec::<u8>::new()))
+        .write(&[record.clone()])
+        .unwrap_err();
+    assert!(matches!(error.kind(), ErrorKind::Message(_)));
+
+    let mut dst = writer_builder
+        .missing_field_policy(MissingFieldPolicy::Blank)
+        .default_value_for_type(dbase::FieldType::Numeric, FieldValue::Numeric(Some(7.0)))
+        .build_with_dest(Cursor::new(Vec::<u8>::new()))
+        .write(&[record])
+        .unwrap();
+    dst.set_position(0);
+    let records = Reader::new(dst).unwrap().read().unwrap();
+    assert_eq!(
+        records[0].get("last_name"),
+        Some(&FieldValue::Character(None))
+    );
+    assert_eq!(records[0].get("age"), Some(&FieldValue::Numeric(Some(7.0))));
+}
+
+#[test]
+fn format_limits_on_write() {
+    let writer_builder = (0..260).fold(TableWriterBuilder::new(), |builder, i| {
+        builder.add_character_field(
+            FieldName::try_from(format!("F{}", i).as_str()).unwrap(),
+            254,
+        )
+    });
+    let error = writer_builder
+        .build_with_dest(Cursor::new(Vec::<u8>::new()))
+        .write::<Record>(&[])
+        .unwrap_err();
+    assert!(matches!(
+        error.kind(),
+        ErrorKind::FormatLimitExceeded(FormatLimit::RecordLength)
+    ));
+
+    let mut reader = Reader::from_path("tests/data/stations.dbf").unwrap();
+    let records = reader.read().unwrap();
+    let writer_builder = TableWriterBuilder::from_reader(reader).max_file_size(2048);
+    let error = writer_builder
+        .clone()
+        .build_with_dest(Cursor::new(Vec::<u8>::new()))
+        .write(&records)
+        .unwrap_err();
+    assert!(matches!(
+        error.kind(),
+        ErrorKind::FormatLimitExceeded(FormatLimit::FileSize)
+    ));
+
+    let path = std::env::temp_dir().join("dbase_format_limits.dbf");
+    let tables = writer_builder.write_to_files(&path, &records).unwrap();
+    let mut read_back = vec![];
+    for table in &tables {
+        assert!(std::fs::metadata(&table.path).unwrap().len() <= 2048);
+        let table_records = dbase::read(&table.path).unwrap();
+        std::fs::remove_file(&table.path).unwrap();
+        assert_eq!(table_records.len(), table.num_records);
+        read_back.extend(table_records);
+    }
+    assert!(tables.len() > 1);
+    assert_eq!(tables[0].path, path);
+    assert_eq!(
+        tables[1].path,
+        std::env::temp_dir().join("dbase_format_limits_2.dbf")
+    );
+    assert_eq!(read_back, records);
+}
+
+#[test]
+fn reproducible_writes() {
+    let write = || {
+        let mut reader = Reader::from_path("tests/data/stations.dbf").unwrap();
+        let records = reader.read().unwrap();
+        TableWriterBuilder::from_reader(reader)
+            .last_update(Date::new(24, 12, 2001))
+            .build_with_dest(Cursor::new(Vec::<u8>::new()))
+            .write(&records)
+            .unwrap()
+            .into_inner()
+    };
+    let first = write();
+    assert_eq!(first, write());
+    assert_eq!(&first[1..4], &[101, 12, 24]);
+}
+
+#[test]
+fn overflow_policies() {
+    let builder = TableWriterBuilder::new()
+        .add_character_field(FieldName::try_from("NAME").unwrap(), 6)
+        .add_numeric_field(FieldName::try_from("PRICE").unwrap(), 6, 2)
+        .add_date_field(FieldName::try_from("SOLD").unwrap());
+    let record = |name: &str, price: f64, sold: Date| {
+        let mut record = Record::default();
+        record.insert(
+            "NAME".to_owned(),
+            FieldValue::Character(Some(name.to_owned())),
+        );
+        record.insert("PRICE".to_owned(), FieldValue::Numeric(Some(price)));
+        record.insert("SOLD".to_owned(), FieldValue::Date(Some(sold)));
+        record
+    };
+    let write = |policy: OverflowPolicy, record: Record| {
+        builder
+            .clone()
+            .overflow_policy(policy)
+            .build_with_dest(Cursor::new(Vec::<u8>::new()))
+            .write(&[record])
+            .map(|dst| {
+                let mut reader = Reader::new(Cursor::new(dst.into_inner())).unwrap();
+                reader.read().unwrap().remove(0)
+            })
+    };
+    let day = Date::new(24, 12, 2001);
+
+    let long_name = record("Crabeé", 1.23456, day);
+    let error = write(OverflowPolicy::Fail, long_name.clone()).unwrap_err();
+    assert!(matches!(error.kind(), ErrorKind::ValueTooLong(_)));
+    assert_eq!(error.field().as_ref().unwrap().name(), "NAME");
+    assert_eq!(error.record_num(), 0);
+
+    let written = write(OverflowPolicy::Truncate, long_name.clone()).unwrap();
+    assert_eq!(
+        written.get("NAME"),
+        Some(&FieldValue::Character(Some("Crabe".to_owned())))
+    );
+    assert_eq!(written.get("PRICE"), Some(&FieldValue::Numeric(Some(1.23))));
+    let written = write(OverflowPolicy::Round, record("Crab", 1.236, day)).unwrap();
+    assert_eq!(written.get("PRICE"), Some(&FieldValue::Numeric(Some(1.24))));
+
+    for policy in &[
+        OverflowPolicy::Fail,
+        OverflowPolicy::Truncate,
+        OverflowPolicy::Round,
+    ] {
+        let error = write(*policy, record("Crab", 1234.5, day)).unwrap_err();
+        assert!(matches!(error.kind(), ErrorKind::ValueTooLong(_)));
+        let error = write(*policy, record("Crab", 1.5, Date::new(30, 2, 2001))).unwrap_err();
+        assert!(matches!(error.kind(), ErrorKind::InvalidDate(_)));
+    }
+    assert!(write(OverflowPolicy::Unchecked, record("Crab", 1234.5, day)).is_ok());
+}
+
+#[derive(Debug)]
+struct Latin1;
+
+impl dbase::Encoding for Latin1 {
+    fn decode<'a>(&self, bytes: &'a [u8]) -> Result<std::borrow::Cow<'a, str>, dbase::DecodeError> {
+        Ok(bytes
+            .iter()
+            .map(|b| char::from(*b))
+            .collect::<String>()
+            .into())
+    }
+
+    fn encode<'a>(&self, text: &'a str) -> Option<std::borrow::Cow<'a, [u8]>> {
+        text.chars()
+            .map(|c| u8::try_from(u32::from(c)).ok())
+            .collect::<Option<Vec<u8>>>()
+            .map(Into::into)
+    }
+}
+
+#[test]
+fn non_ascii_field_names() {
+    let mut record = Record::default();
+    record.insert("AÑO".to_owned(), FieldValue::Numeric(Some(1999.0)));
+    record.insert(
+        "STRAßE".to_owned(),
+        FieldValue::Character(Some("Hauptstraße".to_owned())),
+    );
+    let table = TableWriterBuilder::new()
+        .add_numeric_field(FieldName::new("AÑO").unwrap(), 4, 0)
+        .add_character_field(FieldName::new("STRAßE").unwrap(), 20)
+        .encoding(Latin1)
+        .build_with_dest(Cursor::new(Vec::<u8>::new()))
+        .write(&[record.clone()])
+        .unwrap()
+        .into_inner();
+    assert_eq!(&table[32..36], &[b'A', 0xD1, b'O', 0]);
+    assert_eq!(&table[64..71], &[b'S', b'T', b'R', b'A', 0xDF, b'E', 0]);
+    // The values are encoded too
+    assert_eq!(&table[102..122], b"Hauptstra\xDFe         ");
+
+    let mut reader = dbase::ReaderBuilder::new()
+        .encoding(Latin1)
+        .build_with_source(Cursor::new(table.clone()))
+        .unwrap();
+    assert!(reader.field_name_issues().is_empty());
+    let names = reader.fields()[1..]
+        .iter()
+        .map(|field| field.name().to_owned())
+        .collect::<Vec<_>>();
+    assert_eq!(names, vec!["AÑO", "STRAßE"]);
+    let records = reader.read().unwrap();
+    assert_eq!(records[0], record);
+
+    // The names are written again with the encoding of the reader
+    let copy = TableWriterBuilder::from_reader(reader)
+        .last_update(Date::new(1, 1, 2000))
+        .build_with_dest(Cursor::new(Vec::<u8>::new()))
+        .write(&records)
+        .unwrap()
+        .into_inner();
+    assert_eq!(&copy[32..], &table[32..]);
+
+    // The length of the values is the one of their encoded bytes
+    let mut street = Record::default();
+    street.insert(
+        "STRAßE".to_owned(),
+        FieldValue::Character(Some("Hauptstraße".to_owned())),
+    );
+    let write_street = |length: u8, policy: OverflowPolicy, is_latin1: bool| {
+        let mut builder = TableWriterBuilder::new()
+            .add_character_field(FieldName::new("STRAßE").unwrap(), length)
+            .overflow_policy(policy);
+        if is_latin1 {
+            builder = builder.encoding(Latin1);
+        }
+        builder
+            .build_with_dest(Cursor::new(Vec::<u8>::new()))
+            .write(&[street.clone()])
+            .map(|table| table.into_inner()[66..66 + usize::from(length)].to_vec())
+    };
+    assert!(write_street(11, OverflowPolicy::Fail, true).is_ok());
+    let error = write_street(11, OverflowPolicy::Fail, false).unwrap_err();
+    assert!(matches!(error.kind(), ErrorKind::ValueTooLong(_)));
+    assert_eq!(
+        write_street(10, OverflowPolicy::Truncate, true).unwrap(),
+        b"Hauptstra\xDF"
+    );
+    assert_eq!(
+        write_street(10, OverflowPolicy::Truncate, false).unwrap(),
+        b"Hauptstra "
+    );
+
+    let error = TableWriterBuilder::new()
+        .add_character_field(FieldName::try_from("CAFÉ_€").unwrap(), 4)
+        .encoding(Latin1)
+        .build_with_dest(Cursor::new(Vec::<u8>::new()))
+        .write::<Record>(&[])
+        .unwrap_err();
+    assert!(matches!(error.kind(), ErrorKind::IoError(_)));
+}
+
+#[test]
+fn records_built_with_the_macro() {
+    let records = vec![
+        record! {
+            "NAME" => "Smith",
+            "AMOUNT" => 12.5,
+            "COUNT" => 3,
+            "PAID" => true,
+        },
+        Record::builder()
+            .set("NAME", None::<&str>)
+            .set("AMOUNT", None::<f64>)
+            .set("COUNT", None::<i32>)
+            .set(String::from("PAID"), false)
+            .build(),
+    ];
+    let mut dst = TableWriterBuilder::new()
+        .add_character_field(FieldName::try_from("NAME").unwrap(), 10)
+        .add_numeric_field(FieldName::try_from("AMOUNT").unwrap(), 8, 2)
+        .add_numeric_field(FieldName::try_from("COUNT").unwrap(), 5, 0)
+        .add_logical_field(FieldName::try_from("PAID").unwrap())
+        .build_with_dest(Cursor::new(Vec::<u8>::new()))
+        .write(&records)
+        .unwrap();
+    dst.set_position(0);
+
+    let options = ReadingOptions::new().integer_numerics(true);
+    let read_records = Reader::with_options(dst, options).unwrap().read().unwrap();
+    assert_eq!(read_records, records);
+}